@@ -20,6 +20,29 @@ export type { ABEMasterKey, ABEPublicParams, ABEPrivateKey };
 let wasmModule: InitOutput | null = null;
 let isInitialized = false;
 
+/**
+ * 復号失敗の分類（復号関数が投げるErrorのcodeプロパティ）
+ */
+export type ABEDecryptErrorCode =
+  | "COUNT_MISMATCH"
+  | "POLICY_NOT_SATISFIED"
+  | "CORRUPT"
+  | "INCOMPATIBLE_FORMAT"
+  | "AUTHORITY_MISMATCH";
+
+/**
+ * 復号のエラーをメッセージに説明を付けたErrorに変換
+ * wasmのエラーのcodeプロパティ（ABEDecryptErrorCode）は引き継ぎます。
+ */
+function decryptFailure(description: string, error: unknown): Error & { code?: ABEDecryptErrorCode } {
+  const wrapped: Error & { code?: ABEDecryptErrorCode } = new Error(`${description}: ${error}`);
+  const code = (error as { code?: unknown } | null)?.code;
+  if (typeof code === "string") {
+    wrapped.code = code as ABEDecryptErrorCode;
+  }
+  return wrapped;
+}
+
 /**
  * WebAssemblyモジュールを初期化
  */
@@ -29,27 +52,538 @@ export async function initABE(): Promise<void> {
   }
 
   try {
-    // WebAssemblyモジュールをロード
-    const wasmInit = (await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js")).default;
-
-    // Wasmファイルのパスを明示的に指定（オブジェクト形式で渡す）
-    wasmModule = await wasmInit({
-      module_or_path: new URL("../../wasm-src/abe-wasm/pkg/abe_wasm_bg.wasm", import.meta.url).href,
-    });
-    wasmModule.init();
-    isInitialized = true;
+    // WebAssemblyモジュールをロード
+    const wasmInit = (await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js")).default;
+
+    // Wasmファイルのパスを明示的に指定（オブジェクト形式で渡す）
+    wasmModule = await wasmInit({
+      module_or_path: new URL("../../wasm-src/abe-wasm/pkg/abe_wasm_bg.wasm", import.meta.url).href,
+    });
+    wasmModule.init();
+    isInitialized = true;
+  } catch (error) {
+    throw new Error(`Failed to initialize ABE WebAssembly module: ${error}`);
+  }
+}
+
+/**
+ * ABEのマスター鍵ペアを生成
+ */
+export async function generateABEKeyPair(): Promise<{
+  masterKey: ABEMasterKey;
+  publicParams: ABEPublicParams;
+}> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+  const result = abe.setup();
+
+  if (!result || typeof result !== "object") {
+    throw new Error("Failed to generate ABE key pair");
+  }
+
+  const masterKey = (result as any).master_key as ABEMasterKey;
+  const publicParams = (result as any).public_params as ABEPublicParams;
+
+  return { masterKey, publicParams };
+}
+
+/**
+ * 保存しておいたマスター秘密鍵を復元
+ * 0や曲線の位数以上の値は拒否されます。
+ * @param secret マスター秘密鍵（32バイト）
+ */
+export async function importABEMasterKey(secret: Uint8Array): Promise<ABEMasterKey> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABEMasterKey } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+
+  try {
+    return ABEMasterKey.from_bytes(secret);
+  } catch (error) {
+    throw new Error(`ABE master key import failed: ${error}`);
+  }
+}
+
+/**
+ * 保存しておいた公開パラメータを復元
+ * 長さが不正な値や、曲線上の有効な点でない値は拒否されます。
+ * @param params 公開パラメータ（65バイト）
+ */
+export async function importABEPublicParams(params: Uint8Array): Promise<ABEPublicParams> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABEPublicParams } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+
+  try {
+    return ABEPublicParams.from_bytes(params);
+  } catch (error) {
+    throw new Error(`ABE public params import failed: ${error}`);
+  }
+}
+
+/**
+ * マスター秘密鍵から公開パラメータ（P_pub = αP）を計算
+ * マスター秘密鍵は32バイトのビッグエンディアンのスカラー、公開パラメータは非圧縮形式の65バイト（0x04 || x || y）です。
+ * 他言語の実装とエンコーディングを突き合わせる場合に使います。
+ * @param masterKey マスター鍵
+ */
+export async function deriveABEPublicParams(masterKey: ABEMasterKey): Promise<ABEPublicParams> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.derive_public_params(masterKey);
+  } catch (error) {
+    throw new Error(`ABE public params derivation failed: ${error}`);
+  }
+}
+
+/**
+ * 権限者のDilithium（ML-DSA-65）の秘密鍵で公開パラメータに署名
+ * 中間者による公開パラメータの差し替えを防ぐため、配布時には署名付きの形式を使い、受信側はverifyABEPublicParamsで検証します。
+ * @param publicParams 公開パラメータ
+ * @param authorityPrivateKey 権限者のDilithiumの秘密鍵
+ * @returns 署名付き公開パラメータ（version (1バイト) || 公開パラメータ (65バイト) || 署名）
+ */
+export async function signABEPublicParams(
+  publicParams: ABEPublicParams,
+  authorityPrivateKey: Uint8Array
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.sign_public_params(publicParams, authorityPrivateKey);
+  } catch (error) {
+    throw new Error(`ABE public params signing failed: ${error}`);
+  }
+}
+
+/**
+ * 署名付き公開パラメータを権限者のDilithiumの公開鍵で検証し、公開パラメータを取り出す
+ * 改ざんされている場合や、権限者の鍵が異なる場合は拒否されます。
+ * @param signedParams signABEPublicParamsで作成した署名付き公開パラメータ
+ * @param authorityPublicKey 権限者のDilithiumの公開鍵
+ */
+export async function verifyABEPublicParams(
+  signedParams: Uint8Array,
+  authorityPublicKey: Uint8Array
+): Promise<ABEPublicParams> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.verify_public_params(signedParams, authorityPublicKey);
+  } catch (error) {
+    throw new Error(`ABE public params verification failed: ${error}`);
+  }
+}
+
+/**
+ * 保存しておいた秘密鍵を、鍵のバイト列と属性から復元
+ * ヘッダと鍵コンポーネントの数のみを確認します（各コンポーネントの検査にはdescribeABEKeyを使います）。
+ * 注意: 属性の有効期限は復元されないため、有効期限付きの属性を持つ鍵には使用しないでください
+ * （すべてのフィールドを保存するにはABEPrivateKey.to_base64とimportABEPrivateKeyFromStringを使います）。
+ * 鍵の発行元も復元されないため、encryptABEBoundの暗号文を復号する場合はpublicParamsを指定します。
+ * ブール式・しきい値のポリシーの復号に使うLSSS鍵も復元されないため、それらの暗号文は復号できません。
+ * @param key 秘密鍵のバイト列（ABEPrivateKey.key）
+ * @param attributes 秘密鍵の属性（ABEPrivateKey.attributes）
+ * @param publicParams 鍵を発行した権限者の公開パラメータ（省略時は発行元が不明な鍵になる）
+ */
+export async function importABEPrivateKey(
+  key: Uint8Array,
+  attributes: string[],
+  publicParams?: ABEPublicParams
+): Promise<ABEPrivateKey> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABEPrivateKey } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+
+  try {
+    const privateKey = ABEPrivateKey.from_bytes(key, attributes);
+    if (publicParams) {
+      privateKey.set_authority(publicParams);
+    }
+    return privateKey;
+  } catch (error) {
+    throw new Error(`ABE private key import failed: ${error}`);
+  }
+}
+
+/**
+ * to_base64で保存したマスター秘密鍵の文字列から復元
+ * importABEMasterKeyと同じく、0や曲線の位数以上の値は拒否されます。
+ * @param encoded ABEMasterKey.to_base64() の文字列
+ */
+export async function importABEMasterKeyFromString(encoded: string): Promise<ABEMasterKey> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABEMasterKey } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+
+  try {
+    return ABEMasterKey.from_base64(encoded);
+  } catch (error) {
+    throw new Error(`ABE master key import failed: ${error}`);
+  }
+}
+
+/**
+ * to_base64で保存した公開パラメータの文字列から復元
+ * importABEPublicParamsと同じく、長さが不正な値や有効な点でない値は拒否されます。
+ * @param encoded ABEPublicParams.to_base64() の文字列
+ */
+export async function importABEPublicParamsFromString(encoded: string): Promise<ABEPublicParams> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABEPublicParams } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+
+  try {
+    return ABEPublicParams.from_base64(encoded);
+  } catch (error) {
+    throw new Error(`ABE public params import failed: ${error}`);
+  }
+}
+
+/**
+ * to_base64で保存した秘密鍵の文字列から復元
+ * importABEPrivateKeyと異なり、属性・有効期限・発行元もすべて復元されます。
+ * localStorageに保存した鍵を読み込み直す用途に使います。
+ * @param encoded ABEPrivateKey.to_base64() の文字列
+ */
+export async function importABEPrivateKeyFromString(encoded: string): Promise<ABEPrivateKey> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABEPrivateKey } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+
+  try {
+    return ABEPrivateKey.from_base64(encoded);
+  } catch (error) {
+    throw new Error(`ABE private key import failed: ${error}`);
+  }
+}
+
+/**
+ * 属性セットから秘密鍵を生成
+ */
+export async function extractABEKey(
+  masterKey: ABEMasterKey,
+  attributes: string[]
+): Promise<ABEPrivateKey> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+  const privateKey = abe.key_gen(masterKey, attributes);
+
+  if (!privateKey) {
+    throw new Error("Failed to extract ABE private key");
+  }
+
+  return privateKey;
+}
+
+/**
+ * 属性ごとに有効期限を付けて秘密鍵を生成
+ * 有効期限は鍵に暗号的に束縛され、decryptABEAtで現在時刻と照合されます。
+ * 有効期限が切れた属性は、鍵に含まれていないものとして扱われます。
+ * @param masterKey マスター鍵
+ * @param attributes 属性セット
+ * @param expiresAt 属性と同じ順序の有効期限（UNIX時刻、秒）。0nは有効期限なし
+ */
+export async function extractABEKeyWithExpiry(
+  masterKey: ABEMasterKey,
+  attributes: string[],
+  expiresAt: bigint[]
+): Promise<ABEPrivateKey> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.key_gen_with_expiry(masterKey, attributes, BigUint64Array.from(expiresAt));
+  } catch (error) {
+    throw new Error(`ABE key extraction failed: ${error}`);
+  }
+}
+
+/**
+ * メッセージを暗号化
+ * ポリシーにはカンマ区切りの属性リストのほか、AND・OR・括弧・"k of (...)"を使ったブール式を指定できます。
+ * カンマ区切りの属性リストでは各属性の暗号文コンポーネントに属性名を保存するため、
+ * リストの属性をすべて持つ秘密鍵であれば、それ以外の属性を持っていても復号できます。
+ * ブール式のポリシーはLSSS行列（行ごとに属性のラベルが付いた線形秘密分散）に変換してWaters方式で暗号化し、
+ * ポリシーを満たす属性を持つ秘密鍵で復号できます
+ * （"A OR B" で暗号化した場合、Bだけを持つ鍵でも復号できます）。NOTと時間制限属性はブール式では使用できません。
+ * 注意: カンマ区切りの属性リストの暗号文は結託耐性がなく、属性Aの鍵と属性Bの鍵を持つ2人が組み合わせると
+ * "A,B" の暗号文を復号できます。結託を防ぐ必要がある場合は "A AND B" のようにブール式で指定してください。
+ * @param publicParams 公開パラメータ
+ * @param policy アクセスポリシー（例: "A,B,C"、"(doctor AND cardiology) OR admin"、"2 of (a, b, c, d)"）
+ * @param message 暗号化するメッセージ
+ */
+export async function encryptABE(
+  publicParams: ABEPublicParams,
+  policy: string,
+  message: Uint8Array
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.encrypt(publicParams, policy, message);
+  } catch (error) {
+    throw new Error(`ABE encrypt failed: ${error}`);
+  }
+}
+
+/**
+ * 暗号文を発行元（publicParamsの権限者）に束縛して暗号化
+ * 公開パラメータのハッシュ（発行元ID）を暗号文に埋め込み、復号時に秘密鍵の発行元と照合します。
+ * 別の権限者が同じ属性名で発行した鍵は、属性が一致していても拒否されます
+ * （束縛しない暗号文では、別の権限者の鍵での復号は認証タグの照合で拒否されます）。
+ * 復号はdecryptABE・decryptABEAtでそのまま行えます。
+ * @param publicParams 公開パラメータ
+ * @param policy アクセスポリシー（カンマ区切りの属性リスト、例: "A,B,C"）
+ * @param message 暗号化するメッセージ
+ */
+export async function encryptABEBound(
+  publicParams: ABEPublicParams,
+  policy: string,
+  message: Uint8Array
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.encrypt_bound(publicParams, policy, message);
+  } catch (error) {
+    throw new Error(`ABE bound encrypt failed: ${error}`);
+  }
+}
+
+/**
+ * メッセージを暗号化し、復号に必要な属性のヒントを併せて返す
+ * ヒントはポリシーに含まれる属性名の一覧で、ポリシー以上の情報は含みません。
+ * 受信者が復号を試す前に、不足している属性を表示する用途を想定しています。
+ * @param publicParams 公開パラメータ
+ * @param policy アクセスポリシー（カンマ区切りの属性リスト、例: "A,B,C"）
+ * @param message 暗号化するメッセージ
+ */
+export async function encryptABEWithHint(
+  publicParams: ABEPublicParams,
+  policy: string,
+  message: Uint8Array
+): Promise<{ ciphertext: Uint8Array; requiredAttributes: string[] }> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    const result = abe.encrypt_with_hint(publicParams, policy, message);
+    return {
+      ciphertext: result.ciphertext,
+      requiredAttributes: result.required_attributes,
+    };
+  } catch (error) {
+    throw new Error(`ABE encrypt failed: ${error}`);
+  }
+}
+
+/**
+ * 属性のリストのうち少なくともk個を持つ秘密鍵で復号できるように暗号化（しきい値ポリシー）
+ * "k of (...)" のブール式と同じLSSS行列の形式で暗号化し、decryptABE・decryptABEAtでそのまま復号できます。
+ * 時間制限属性（valid_until:）はしきい値ポリシーに指定できません。
+ * @param publicParams 公開パラメータ
+ * @param attributes 属性のリスト（n個、重複なし）
+ * @param k 復号に必要な属性の数（1以上n以下）
+ * @param message 暗号化するメッセージ
+ */
+export async function encryptABEThreshold(
+  publicParams: ABEPublicParams,
+  attributes: string[],
+  k: number,
+  message: Uint8Array
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.encrypt_threshold(publicParams, attributes, k, message);
+  } catch (error) {
+    throw new Error(`ABE threshold encrypt failed: ${error}`);
+  }
+}
+
+/**
+ * 暗号文を復号化
+ * 秘密鍵の属性を暗号文の属性と照合し、ポリシーを満たさない場合は「ポリシーを満たしていません」のエラーになります。
+ * 秘密鍵は暗号文が必要とする以上の属性を持っていてもよく、必要な属性の鍵コンポーネントだけを使います。
+ * 暗号文のAES-256-GCMの認証タグを照合し、一致しない（改ざん・鍵の誤り）場合はエラーになります。
+ * 認証タグのないAES-256-GCMの導入前の形式の暗号文はINCOMPATIBLE_FORMATになります（upgradeABECiphertextで移行する）。
+ * エラーのcodeプロパティ（ABEDecryptErrorCode）で失敗の原因を判別できます。
+ * @param privateKey 秘密鍵
+ * @param ciphertext 暗号文
+ * @param strict trueの場合、再直列化した結果が入力と一致しない非正規な点のエンコーディングを拒否する（省略時はfalse）
+ */
+export async function decryptABE(
+  privateKey: ABEPrivateKey,
+  ciphertext: Uint8Array,
+  strict?: boolean
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    if (strict !== undefined) {
+      abe.strict = strict;
+    }
+    return abe.decrypt(privateKey, ciphertext);
+  } catch (error) {
+    throw decryptFailure("ABE decrypt failed", error);
+  }
+}
+
+/**
+ * 現在時刻を指定して暗号文を復号化
+ * 時間制限（valid_until）付きの暗号文は、有効期限以前のみ復号できます。
+ * @param privateKey 秘密鍵
+ * @param ciphertext 暗号文
+ * @param currentTime 現在時刻（UNIX時刻、秒）
+ */
+export async function decryptABEAt(
+  privateKey: ABEPrivateKey,
+  ciphertext: Uint8Array,
+  currentTime: bigint
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.decrypt_at(privateKey, ciphertext, currentTime);
+  } catch (error) {
+    throw decryptFailure("ABE decrypt failed", error);
+  }
+}
+
+/**
+ * ポスト量子ハイブリッド方式でメッセージを暗号化
+ * メッセージの対称鍵をABEの鍵シェアとML-KEM（Kyber）の共有秘密の両方から導出するため、
+ * BN254のペアリングが破られても、Kyberの秘密鍵がなければ復号できません。
+ * @param publicParams 公開パラメータ
+ * @param policy アクセスポリシー（カンマ区切りの属性リスト、例: "A,B,C"）
+ * @param kyberPublicKey 受信者のKyberの公開鍵
+ * @param message 暗号化するメッセージ
+ */
+export async function encryptABEHybrid(
+  publicParams: ABEPublicParams,
+  policy: string,
+  kyberPublicKey: Uint8Array,
+  message: Uint8Array
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.encrypt_hybrid(publicParams, policy, kyberPublicKey, message);
   } catch (error) {
-    throw new Error(`Failed to initialize ABE WebAssembly module: ${error}`);
+    throw new Error(`ABE hybrid encrypt failed: ${error}`);
   }
 }
 
 /**
- * ABEのマスター鍵ペアを生成
+ * ハイブリッド方式の暗号文を復号化
+ * ポリシーを満たす秘密鍵とKyberの秘密鍵の両方が必要です。
+ * @param privateKey 秘密鍵
+ * @param kyberPrivateKey Kyberの秘密鍵
+ * @param kyberPublicKey Kyberの公開鍵（秘密鍵の復元に必要）
+ * @param ciphertext encryptABEHybridで作成した暗号文
+ * @param currentTime 現在時刻（UNIX時刻、秒）。時間制限付きのポリシーの場合に指定
  */
-export async function generateABEKeyPair(): Promise<{
-  masterKey: ABEMasterKey;
-  publicParams: ABEPublicParams;
-}> {
+export async function decryptABEHybrid(
+  privateKey: ABEPrivateKey,
+  kyberPrivateKey: Uint8Array,
+  kyberPublicKey: Uint8Array,
+  ciphertext: Uint8Array,
+  currentTime?: bigint
+): Promise<Uint8Array> {
   await initABE();
   if (!wasmModule) {
     throw new Error("ABE module not initialized");
@@ -57,25 +591,26 @@ export async function generateABEKeyPair(): Promise<{
 
   const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
   const abe = new ABE();
-  const result = abe.setup();
 
-  if (!result || typeof result !== "object") {
-    throw new Error("Failed to generate ABE key pair");
+  try {
+    return abe.decrypt_hybrid(privateKey, kyberPrivateKey, kyberPublicKey, ciphertext, currentTime);
+  } catch (error) {
+    throw decryptFailure("ABE hybrid decrypt failed", error);
   }
-
-  const masterKey = (result as any).master_key as ABEMasterKey;
-  const publicParams = (result as any).public_params as ABEPublicParams;
-
-  return { masterKey, publicParams };
 }
 
 /**
- * 属性セットから秘密鍵を生成
+ * 暗号文の復号を試み、失敗した場合は例外の代わりにundefinedを返す
+ * 受信した暗号文を手元のすべての鍵で順に試すような用途向けで、ポリシーを満たさない鍵や壊れた暗号文はundefinedになります。
+ * 別のマスター鍵で発行された鍵は、暗号文のAES-256-GCMの認証タグの照合で検出してundefinedになります。
+ * 認証タグのない以前の形式の暗号文は鍵の正否を判定できないため、常にundefinedになります（upgradeABECiphertextで移行する）。
+ * @param privateKey 秘密鍵
+ * @param ciphertext 暗号文
  */
-export async function extractABEKey(
-  masterKey: ABEMasterKey,
-  attributes: string[]
-): Promise<ABEPrivateKey> {
+export async function tryDecryptABE(
+  privateKey: ABEPrivateKey,
+  ciphertext: Uint8Array
+): Promise<Uint8Array | undefined> {
   await initABE();
   if (!wasmModule) {
     throw new Error("ABE module not initialized");
@@ -83,26 +618,49 @@ export async function extractABEKey(
 
   const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
   const abe = new ABE();
-  const privateKey = abe.key_gen(masterKey, attributes);
 
-  if (!privateKey) {
-    throw new Error("Failed to extract ABE private key");
+  return abe.try_decrypt(privateKey, ciphertext);
+}
+
+/**
+ * ハイブリッド方式の暗号文の復号を試み、失敗した場合は例外の代わりにundefinedを返す
+ * 暗号文のタグで成否を判定するため、鍵が異なる場合・ポリシーを満たさない場合・暗号文が壊れている場合はいずれもundefinedになります。
+ * @param privateKey 秘密鍵
+ * @param kyberPrivateKey Kyberの秘密鍵
+ * @param kyberPublicKey Kyberの公開鍵（秘密鍵の復元に必要）
+ * @param ciphertext encryptABEHybridで作成した暗号文
+ * @param currentTime 現在時刻（UNIX時刻、秒）。時間制限付きのポリシーの場合に指定
+ */
+export async function tryDecryptABEHybrid(
+  privateKey: ABEPrivateKey,
+  kyberPrivateKey: Uint8Array,
+  kyberPublicKey: Uint8Array,
+  ciphertext: Uint8Array,
+  currentTime?: bigint
+): Promise<Uint8Array | undefined> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
   }
 
-  return privateKey;
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  return abe.try_decrypt_hybrid(privateKey, kyberPrivateKey, kyberPublicKey, ciphertext, currentTime);
 }
 
 /**
- * メッセージを暗号化
+ * メッセージを暗号化し、base64url（パディングなし）の文字列で返す
+ * URLやQRコードに埋め込む用途向けです。decryptABEFromStringで復号できます。
  * @param publicParams 公開パラメータ
  * @param policy アクセスポリシー（カンマ区切りの属性リスト、例: "A,B,C"）
  * @param message 暗号化するメッセージ
  */
-export async function encryptABE(
+export async function encryptABEToString(
   publicParams: ABEPublicParams,
   policy: string,
   message: Uint8Array
-): Promise<Uint8Array> {
+): Promise<string> {
   await initABE();
   if (!wasmModule) {
     throw new Error("ABE module not initialized");
@@ -112,18 +670,23 @@ export async function encryptABE(
   const abe = new ABE();
 
   try {
-    return abe.encrypt(publicParams, policy, message);
+    return abe.encrypt_to_string(publicParams, policy, message);
   } catch (error) {
     throw new Error(`ABE encrypt failed: ${error}`);
   }
 }
 
 /**
- * 暗号文を復号化
+ * base64url形式の暗号文を復号化
+ * 空白やパディング（=）が含まれていても受け付けます。
+ * @param privateKey 秘密鍵
+ * @param encoded base64url形式の暗号文
+ * @param currentTime 現在時刻（UNIX時刻、秒）。時間制限付きの暗号文の場合に指定
  */
-export async function decryptABE(
+export async function decryptABEFromString(
   privateKey: ABEPrivateKey,
-  ciphertext: Uint8Array
+  encoded: string,
+  currentTime?: bigint
 ): Promise<Uint8Array> {
   await initABE();
   if (!wasmModule) {
@@ -134,14 +697,240 @@ export async function decryptABE(
   const abe = new ABE();
 
   try {
-    return abe.decrypt(privateKey, ciphertext);
+    return abe.decrypt_from_string(privateKey, encoded, currentTime);
+  } catch (error) {
+    throw decryptFailure("ABE decrypt failed", error);
+  }
+}
+
+/**
+ * 公開パラメータから安定した鍵識別子（KID）を導出
+ * SHA-256(公開パラメータ) の先頭16バイトをbase64url（パディングなし）で表した22文字の文字列で、
+ * 他のモジュールのKIDと同じ方式です。CP-ABE・KP-ABEのどちらの公開パラメータにも使えます。
+ * @param publicParams 公開パラメータ
+ * @returns 鍵識別子（22文字）
+ */
+export async function getABEKeyId(publicParams: ABEPublicParams): Promise<string> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { key_id } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+
+  try {
+    return key_id(publicParams.params);
+  } catch (error) {
+    throw new Error(`ABE key id derivation failed: ${error}`);
+  }
+}
+
+/**
+ * これまでに計算したペアリングの回数を取得
+ * 性能測定用です。誤った鍵での復号がペアリングの前に打ち切られることの確認などに使います。
+ */
+export async function getABEPairingCount(): Promise<bigint> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { pairing_count } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  return pairing_count();
+}
+
+/**
+ * これまでに属性ハッシュのキャッシュから取得できた回数を取得
+ * 性能測定用です。同じ属性で鍵生成・暗号化を繰り返した場合にハッシュ計算が省かれていることの確認などに使います。
+ */
+export async function getABEAttributeCacheHits(): Promise<bigint> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { attribute_cache_hits } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  return attribute_cache_hits();
+}
+
+/**
+ * 秘密鍵の属性と鍵コンポーネントの情報
+ */
+export interface ABEKeyComponentInfo {
+  /** 属性 */
+  attribute: string;
+  /** 鍵コンポーネントのフィンガープリント（SHA-256の先頭8バイトのhex、圧縮形式でも同じ値） */
+  fingerprint: string;
+  /** 鍵コンポーネントが曲線上の有効な点かどうか */
+  valid: boolean;
+  /** 属性の有効期限（UNIX時刻、有効期限付きの属性のみ） */
+  expires_at?: bigint;
+}
+
+/**
+ * 秘密鍵に含まれる属性と、鍵コンポーネントのフィンガープリントの一覧を取得
+ * 鍵が付与する権限の監査や、個々の鍵コンポーネントの破損の検出に使います。
+ * @param privateKey 秘密鍵
+ * @throws Error 鍵コンポーネントの数が属性の数と一致しない場合
+ */
+export async function describeABEKey(privateKey: ABEPrivateKey): Promise<ABEKeyComponentInfo[]> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  try {
+    return privateKey.describe();
+  } catch (error) {
+    throw new Error(`ABE key describe failed: ${error}`);
+  }
+}
+
+/**
+ * 秘密鍵が属性の鍵コンポーネントを保有していることの証明を生成
+ * 鍵そのものを明かさずに、検証者に属性の保有を示せます（検証はverifyABEAttributeProof）。
+ * @param privateKey 属性を含む秘密鍵
+ * @param attribute 保有を示す属性
+ * @param challenge 検証者が証明の要求ごとに指定する値（ランダムなノンスなど）。
+ *   証明はchallengeに束縛されるため、検証者は毎回新しい値を渡すことで、過去の証明の再利用（リプレイ）を防げます
+ * @returns 証明（A (384バイト) || Z (130バイト)）
+ * @throws Error 秘密鍵に属性が含まれない場合
+ */
+export async function proveABEAttribute(
+  privateKey: ABEPrivateKey,
+  attribute: string,
+  challenge: Uint8Array
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.prove_attribute(privateKey, attribute, challenge);
+  } catch (error) {
+    throw new Error(`ABE attribute proof failed: ${error}`);
+  }
+}
+
+/**
+ * 属性の鍵保有証明を検証
+ * @param publicParams 鍵を発行した権限者の公開パラメータ
+ * @param attribute 保有を確認する属性
+ * @param challenge 証明を要求したときに渡したchallenge（証明の生成時と同じ値）
+ * @param proof proveABEAttributeで生成した証明
+ * @returns 証明が正しい場合はtrue（形式が不正な証明もfalse）
+ */
+export async function verifyABEAttributeProof(
+  publicParams: ABEPublicParams,
+  attribute: string,
+  challenge: Uint8Array,
+  proof: Uint8Array
+): Promise<boolean> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.verify_attribute_proof(publicParams, attribute, challenge, proof);
+  } catch (error) {
+    throw new Error(`ABE attribute proof verification failed: ${error}`);
+  }
+}
+
+/**
+ * 属性セットがポリシーを満たすかを評価
+ * 復号を行わずにアクセス可否を確認できます（UIでの事前確認など）。
+ * ポリシーはAND・OR・NOT・閾値（例: "2 of (A, B, C)"）を使用できます。
+ * @param policy ポリシー文字列
+ * @param attributes 属性セット
+ * @param maxDepth 括弧・閾値・NOTの入れ子の深さの上限（省略時は64）
+ * @throws Error ポリシーの構文が不正な場合、または入れ子が上限を超える場合
+ */
+export async function checkABEPolicy(
+  policy: string,
+  attributes: string[],
+  maxDepth?: number
+): Promise<boolean> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+
+  try {
+    return maxDepth === undefined
+      ? ABE.attributes_satisfy(policy, attributes)
+      : ABE.attributes_satisfy_with_max_depth(policy, attributes, maxDepth);
+  } catch (error) {
+    throw new Error(`ABE policy check failed: ${error}`);
+  }
+}
+
+/**
+ * 暗号論的に安全な乱数のバイト列を生成
+ * 鍵生成と同じエントロピー源を使用します。nonceやsaltなど、アプリケーション側で必要な乱数に
+ * Math.randomの代わりに使えます。エントロピー源が利用できない場合はエラーになります。
+ *
+ * @param length 生成するバイト数
+ * @returns 乱数のバイト列
+ */
+export async function getABERandomBytes(length: number): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { random_bytes } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+
+  try {
+    return random_bytes(length);
   } catch (error) {
-    throw new Error(`ABE decrypt failed: ${error}`);
+    throw new Error(`ABE random bytes generation failed: ${error}`);
+  }
+}
+
+/**
+ * セキュリティ関連イベント
+ * detailにはエラー理由などの説明のみが入り、鍵や平文などの秘密情報は含まれません。
+ */
+export interface ABESecurityEvent {
+  /** 復号の失敗・曲線上の有効な点でない入力の拒否・乱数生成の失敗 */
+  event: "decrypt_failed" | "point_rejected" | "rng_failed";
+  /** 失敗の理由 */
+  detail: string;
+}
+
+/**
+ * セキュリティ関連イベントのコールバックを設定
+ * 1回の失敗につき1つのイベントが通知されます（点の検証失敗による復号失敗はpoint_rejectedのみ）。
+ * 試行を前提とした関数（tryDecryptABEなど）の失敗は通知されません。
+ * コールバックはWeb Workerごとに個別に登録する必要があります。
+ * @param callback イベントを受け取る関数（undefinedで解除）
+ */
+export async function setABEEventCallback(
+  callback: ((event: ABESecurityEvent) => void) | undefined
+): Promise<void> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
   }
+
+  const { set_event_callback } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  set_event_callback(callback);
 }
 
 /**
- * 基本的な動作確認用のテスト関数
+ * モジュールの動作確認
+ * CP-ABE・KP-ABEの鍵生成→暗号化→復号の往復が正しく動作するかを確認します。
  */
 export async function testABE(): Promise<boolean> {
   try {
@@ -150,15 +939,43 @@ export async function testABE(): Promise<boolean> {
       return false;
     }
 
-    const { add } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
-    const result = add(2, 3);
-    return result === 5;
+    const { health_check } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+    return health_check();
   } catch (error) {
     console.error("ABE test failed:", error);
     return false;
   }
 }
 
+/**
+ * 旧形式（ヘッダなし）・AES-256-GCMの導入前の形式の暗号文を現在の形式に移行
+ * 保存済みデータをバックグラウンドで移行する用途を想定しています。
+ * 既にAES-256-GCMの暗号文はそのまま返します。
+ * 注意: 以前の形式は認証されていないため、誤った鍵を渡すと無意味なデータが暗号化し直されます。
+ * @param oldCiphertext 以前の形式の暗号文
+ * @param privateKey 復号に使う秘密鍵（属性が再暗号化のポリシーになる。旧方式の属性ハッシュの暗号文にはバージョン1の秘密鍵）
+ * @param publicParams 再暗号化に使う公開パラメータ
+ */
+export async function upgradeABECiphertext(
+  oldCiphertext: Uint8Array,
+  privateKey: ABEPrivateKey,
+  publicParams: ABEPublicParams
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { ABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const abe = new ABE();
+
+  try {
+    return abe.upgrade_ciphertext(oldCiphertext, privateKey, publicParams);
+  } catch (error) {
+    throw new Error(`ABE ciphertext upgrade failed: ${error}`);
+  }
+}
+
 // ============================================================================
 // KP-ABE (Key-Policy Attribute-Based Encryption) 関数
 // ============================================================================
@@ -274,6 +1091,86 @@ export async function decryptKPABE(
   try {
     return kpabe.decrypt(privateKey, ciphertext);
   } catch (error) {
-    throw new Error(`KP-ABE decrypt failed: ${error}`);
+    throw decryptFailure("KP-ABE decrypt failed", error);
+  }
+}
+
+/**
+ * 属性セットからメッセージを暗号化し、base64url（パディングなし）の文字列で返す
+ * URLやQRコードに埋め込む用途向けです。decryptKPABEFromStringで復号できます。
+ * @param publicParams 公開パラメータ
+ * @param attributes 属性セット（例: ["A", "B", "C"]）
+ * @param message 暗号化するメッセージ
+ */
+export async function encryptKPABEToString(
+  publicParams: ABEPublicParams,
+  attributes: string[],
+  message: Uint8Array
+): Promise<string> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { KPABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const kpabe = new KPABE();
+
+  try {
+    return kpabe.encrypt_to_string(publicParams, attributes, message);
+  } catch (error) {
+    throw new Error(`KP-ABE encrypt failed: ${error}`);
+  }
+}
+
+/**
+ * base64url形式のKP-ABE暗号文を復号化
+ * 空白やパディング（=）が含まれていても受け付けます。
+ * @param privateKey 秘密鍵
+ * @param encoded base64url形式の暗号文
+ */
+export async function decryptKPABEFromString(
+  privateKey: ABEPrivateKey,
+  encoded: string
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { KPABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const kpabe = new KPABE();
+
+  try {
+    return kpabe.decrypt_from_string(privateKey, encoded);
+  } catch (error) {
+    throw decryptFailure("KP-ABE decrypt failed", error);
+  }
+}
+
+/**
+ * 旧形式（ヘッダなし）・AES-256-GCMの導入前の形式のKP-ABE暗号文を現在の形式に移行
+ * 既にAES-256-GCMの暗号文はそのまま返します。
+ * 注意: 以前の形式は認証されていないため、誤った鍵を渡すと無意味なデータが暗号化し直されます。
+ * @param oldCiphertext 以前の形式の暗号文
+ * @param privateKey 復号に使う秘密鍵（ポリシーの属性で再暗号化する。旧方式の属性ハッシュの暗号文にはバージョン1の秘密鍵）
+ * @param publicParams 再暗号化に使う公開パラメータ
+ */
+export async function upgradeKPABECiphertext(
+  oldCiphertext: Uint8Array,
+  privateKey: ABEPrivateKey,
+  publicParams: ABEPublicParams
+): Promise<Uint8Array> {
+  await initABE();
+  if (!wasmModule) {
+    throw new Error("ABE module not initialized");
+  }
+
+  const { KPABE } = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+  const kpabe = new KPABE();
+
+  try {
+    return kpabe.upgrade_ciphertext(oldCiphertext, privateKey, publicParams);
+  } catch (error) {
+    throw new Error(`KP-ABE ciphertext upgrade failed: ${error}`);
   }
 }
@@ -16,6 +16,17 @@ import type {
 // 型をエクスポート
 export type { IBEMasterKey, IBEPublicParams, IBEPrivateKey };
 
+/**
+ * アイデンティティを曲線上の点に写像する方式（hash-to-curve）
+ *
+ * - "rfc9380": RFC 9380の構成（BN254G2_XMD:SHA-256_SVDW_RO_）。既定
+ * - "try_and_increment": ハッシュをx座標の候補とし、曲線上の点になるまで1ずつ増やす方式
+ * - "legacy": 旧方式。1つの秘密鍵から他のアイデンティティの秘密鍵を計算できてしまうため、既存の鍵・暗号文の移行用
+ *
+ * 方式は秘密鍵と暗号文に記録され、同じ方式の鍵と暗号文の組み合わせでのみ復号できます。
+ */
+export type IBEHashToCurve = "rfc9380" | "try_and_increment" | "legacy";
+
 let wasmModule: InitOutput | null = null;
 let isInitialized = false;
 
@@ -84,17 +95,124 @@ export async function generateIBEKeyPair(): Promise<{
   }
 }
 
+/**
+ * シードから決定的にIBEのマスター鍵ペアを生成
+ *
+ * 同じシードからは常に同じマスター鍵と公開パラメータが得られるため、
+ * 再現可能なテストや他の実装とのテストベクタ、鍵セレモニーに使えます。
+ * シードはマスター秘密鍵と同等に扱ってください。
+ *
+ * @param seed シード（32バイト、すべて0は不可）
+ * @returns マスター鍵と公開パラメータ
+ * @throws Error シードの長さが32バイトでない場合、またはすべて0の場合
+ */
+export async function generateIBEKeyPairFromSeed(seed: Uint8Array): Promise<{
+  masterKey: IBEMasterKey;
+  publicParams: IBEPublicParams;
+}> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  const result = ibe.setup_from_seed(seed);
+  return {
+    masterKey: (result as any).master_key,
+    publicParams: (result as any).public_params,
+  };
+}
+
+/**
+ * 保存しておいたマスター秘密鍵を復元
+ *
+ * @param secret マスター秘密鍵（32バイト）
+ * @returns マスター鍵
+ * @throws Error 0や曲線の位数以上の値など、範囲外の鍵の場合
+ */
+export async function importIBEMasterKey(secret: Uint8Array): Promise<IBEMasterKey> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBEMasterKey } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+
+  try {
+    return IBEMasterKey.from_bytes(secret);
+  } catch (error) {
+    throw new Error(`IBE master key import failed: ${error}`);
+  }
+}
+
+/**
+ * 保存しておいた公開パラメータを復元
+ *
+ * @param params 公開パラメータ（65バイト）
+ * @returns 公開パラメータ
+ * @throws Error 長さが不正な値や、曲線上の有効な点でない値の場合
+ */
+export async function importIBEPublicParams(params: Uint8Array): Promise<IBEPublicParams> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBEPublicParams } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+
+  try {
+    return IBEPublicParams.from_bytes(params);
+  } catch (error) {
+    throw new Error(`IBE public params import failed: ${error}`);
+  }
+}
+
+/**
+ * マスター秘密鍵から公開パラメータ（P_pub = sP）を計算
+ *
+ * マスター秘密鍵は32バイトのビッグエンディアンのスカラー、
+ * 公開パラメータは非圧縮形式の65バイト（0x04 || x || y）です。
+ * 他言語の実装とエンコーディングを突き合わせる場合に使います。
+ *
+ * @param masterKey マスター鍵
+ * @returns 公開パラメータ
+ * @throws Error マスター鍵が不正な場合
+ */
+export async function deriveIBEPublicParams(masterKey: IBEMasterKey): Promise<IBEPublicParams> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    return ibe.derive_public_params(masterKey);
+  } catch (error) {
+    throw new Error(`IBE public params derivation failed: ${error}`);
+  }
+}
+
 /**
  * アイデンティティから秘密鍵を抽出
  *
  * @param masterKey マスター鍵
  * @param identity アイデンティティ（文字列）
+ * @param hashToCurve hash-to-curveの方式（省略時は"rfc9380"）
  * @returns 秘密鍵
  * @throws Error 初期化されていない場合、または実装が未完成の場合
  */
 export async function extractIBEKey(
   masterKey: IBEMasterKey,
-  identity: string
+  identity: string,
+  hashToCurve?: IBEHashToCurve
 ): Promise<IBEPrivateKey> {
   await initIBE();
 
@@ -106,6 +224,9 @@ export async function extractIBEKey(
   const ibe = new IBE();
 
   try {
+    if (hashToCurve) {
+      ibe.set_hash_to_curve(hashToCurve);
+    }
     return ibe.extract(masterKey, identity);
   } catch (error) {
     throw new Error(`IBE extract failed: ${error}`);
@@ -114,17 +235,20 @@ export async function extractIBEKey(
 
 /**
  * メッセージを暗号化
+ * ペアリングから導出した対称鍵で、メッセージをAES-256-GCMで暗号化します（旧方式"legacy"でも同じ）。
  *
  * @param publicParams 公開パラメータ
  * @param identity アイデンティティ（文字列）
  * @param message 暗号化するメッセージ
+ * @param hashToCurve hash-to-curveの方式（省略時は"rfc9380"、受信者の鍵と同じ方式を指定する）
  * @returns 暗号文
  * @throws Error 初期化されていない場合、または実装が未完成の場合
  */
 export async function encryptIBE(
   publicParams: IBEPublicParams,
   identity: string,
-  message: Uint8Array
+  message: Uint8Array,
+  hashToCurve?: IBEHashToCurve
 ): Promise<Uint8Array> {
   await initIBE();
 
@@ -136,23 +260,163 @@ export async function encryptIBE(
   const ibe = new IBE();
 
   try {
+    if (hashToCurve) {
+      ibe.set_hash_to_curve(hashToCurve);
+    }
     return ibe.encrypt(publicParams, identity, message);
   } catch (error) {
     throw new Error(`IBE encrypt failed: ${error}`);
   }
 }
 
+/**
+ * アイデンティティを事前ハッシュ（SHA-256）した32バイトを取得
+ *
+ * extractIBEKeyPrehashed・encryptIBEPrehashedに渡すと、アイデンティティそのものを渡した場合と
+ * 同じ秘密鍵・暗号文になります。長いアイデンティティを繰り返し使う場合に、一度だけハッシュして保存しておけます。
+ *
+ * @param identity アイデンティティ（文字列）
+ * @returns 事前ハッシュ（32バイト）
+ */
+export async function prehashIBEIdentity(identity: string): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { prehash_identity } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  return prehash_identity(identity);
+}
+
+/**
+ * 事前ハッシュしたアイデンティティから秘密鍵を抽出
+ *
+ * extractIBEKeyでアイデンティティそのものから抽出した秘密鍵と同じ鍵になります（identityは空文字列）。
+ *
+ * @param masterKey マスター鍵
+ * @param identityDigest prehashIBEIdentityで計算した事前ハッシュ（32バイト）
+ * @param hashToCurve hash-to-curveの方式（省略時は"rfc9380"、"legacy"は事前ハッシュに対応しない）
+ * @returns 秘密鍵
+ * @throws Error 事前ハッシュの長さが不正な場合、または"legacy"を指定した場合
+ */
+export async function extractIBEKeyPrehashed(
+  masterKey: IBEMasterKey,
+  identityDigest: Uint8Array,
+  hashToCurve?: IBEHashToCurve
+): Promise<IBEPrivateKey> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    if (hashToCurve) {
+      ibe.set_hash_to_curve(hashToCurve);
+    }
+    return ibe.extract_prehashed(masterKey, identityDigest);
+  } catch (error) {
+    throw new Error(`IBE extract failed: ${error}`);
+  }
+}
+
+/**
+ * 事前ハッシュしたアイデンティティ宛てにメッセージを暗号化
+ *
+ * encryptIBEでアイデンティティそのものを指定した場合と同じ方式で暗号化し、同じ秘密鍵で復号できます。
+ *
+ * @param publicParams 公開パラメータ
+ * @param identityDigest prehashIBEIdentityで計算した事前ハッシュ（32バイト）
+ * @param message 暗号化するメッセージ
+ * @param hashToCurve hash-to-curveの方式（省略時は"rfc9380"、"legacy"は事前ハッシュに対応しない）
+ * @returns 暗号文
+ * @throws Error 事前ハッシュの長さが不正な場合、または"legacy"を指定した場合
+ */
+export async function encryptIBEPrehashed(
+  publicParams: IBEPublicParams,
+  identityDigest: Uint8Array,
+  message: Uint8Array,
+  hashToCurve?: IBEHashToCurve
+): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    if (hashToCurve) {
+      ibe.set_hash_to_curve(hashToCurve);
+    }
+    return ibe.encrypt_prehashed(publicParams, identityDigest, message);
+  } catch (error) {
+    throw new Error(`IBE encrypt failed: ${error}`);
+  }
+}
+
+/**
+ * 合成IV方式でメッセージを決定的に暗号化
+ *
+ * 乱数の代わりに HMAC(messageKey, identity || message) から暗号化用の値を導出するため、
+ * 乱数生成器が信頼できない環境でも安全に暗号化できます。
+ * ただし同じアイデンティティ宛ての同じメッセージは同じ暗号文になるため、
+ * 「同一のメッセージが送られた」という事実は漏れます。
+ *
+ * @param publicParams 公開パラメータ
+ * @param identity アイデンティティ（文字列）
+ * @param message 暗号化するメッセージ
+ * @param messageKey 送信者が秘密に保持するメッセージ鍵（32バイト以上）
+ * @param hashToCurve hash-to-curveの方式（省略時は"rfc9380"、受信者の鍵と同じ方式を指定する）
+ * @returns 暗号文（decryptIBEで復号可能）
+ * @throws Error 初期化されていない場合、またはメッセージ鍵が短すぎる場合
+ */
+export async function encryptIBEDeterministic(
+  publicParams: IBEPublicParams,
+  identity: string,
+  message: Uint8Array,
+  messageKey: Uint8Array,
+  hashToCurve?: IBEHashToCurve
+): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    if (hashToCurve) {
+      ibe.set_hash_to_curve(hashToCurve);
+    }
+    return ibe.encrypt_deterministic(publicParams, identity, message, messageKey);
+  } catch (error) {
+    throw new Error(`IBE deterministic encrypt failed: ${error}`);
+  }
+}
+
 /**
  * 暗号文を復号化
  *
  * @param privateKey 秘密鍵
  * @param ciphertext 暗号文
+ * @param strict trueの場合、再直列化した結果が入力と一致しない非正規な点のエンコーディングを拒否する（省略時はfalse）
  * @returns 復号化されたメッセージ
- * @throws Error 初期化されていない場合、または実装が未完成の場合
+ * @throws Error 初期化されていない場合、AES-256-GCMの認証タグが一致しない（改ざん・アイデンティティの異なる鍵）場合、
+ *   またはGCM導入前の旧形式の暗号文の場合（decryptIBELegacyで移行する）
  */
 export async function decryptIBE(
   privateKey: IBEPrivateKey,
-  ciphertext: Uint8Array
+  ciphertext: Uint8Array,
+  strict?: boolean
 ): Promise<Uint8Array> {
   await initIBE();
 
@@ -164,6 +428,9 @@ export async function decryptIBE(
   const ibe = new IBE();
 
   try {
+    if (strict !== undefined) {
+      ibe.strict = strict;
+    }
     return ibe.decrypt(privateKey, ciphertext);
   } catch (error) {
     throw new Error(`IBE decrypt failed: ${error}`);
@@ -171,14 +438,480 @@ export async function decryptIBE(
 }
 
 /**
- * 基本的なテスト関数（動作確認用）
+ * AES-256-GCMの導入前の旧形式（対称鍵を繰り返したXOR）の暗号文を復号化
+ *
+ * 保存済みの旧形式の暗号文を復号し、encryptIBEで暗号化し直すための移行専用の関数です。
+ * 旧方式（legacy）の秘密鍵で、方式のIDを持たない（0x04から始まる）暗号文だけを受け付けます。
+ * 注意: 旧形式には完全性の保護がないため、改ざんされた暗号文やアイデンティティの異なる鍵でも
+ * 例外にならず無関係なバイト列が返ります。受信した暗号文の復号には使わないでください。
+ *
+ * @param privateKey 秘密鍵
+ * @param ciphertext 旧形式の暗号文
+ * @returns 復号化されたメッセージ
+ * @throws Error 初期化されていない場合、またはAES-256-GCMで暗号化された暗号文の場合（decryptIBEで復号する）
+ */
+export async function decryptIBELegacy(
+  privateKey: IBEPrivateKey,
+  ciphertext: Uint8Array
+): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    return ibe.decrypt_legacy(privateKey, ciphertext);
+  } catch (error) {
+    throw new Error(`IBE legacy decrypt failed: ${error}`);
+  }
+}
+
+/**
+ * 暗号文を呼び出し側のバッファに復号化
+ *
+ * 復号のたびに新しいバッファを確保しないため、同じバッファで繰り返し復号する場合に使います。
+ * 平文の長さは暗号文の長さから65バイト（Uコンポーネント）を引いた値です。
+ *
+ * @param privateKey 秘密鍵
+ * @param ciphertext 暗号文
+ * @param out 復号化したメッセージを書き込むバッファ（先頭から書き込み、残りは変更しない）
+ * @returns 書き込んだバイト数
+ * @throws Error バッファが平文より短い場合、暗号文が不正な場合、またはGCM導入前の旧形式の暗号文の場合
+ */
+export async function decryptIBEInto(
+  privateKey: IBEPrivateKey,
+  ciphertext: Uint8Array,
+  out: Uint8Array
+): Promise<number> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    return ibe.decrypt_into(privateKey, ciphertext, out);
+  } catch (error) {
+    throw new Error(`IBE decrypt failed: ${error}`);
+  }
+}
+
+/**
+ * メッセージを暗号化し、base64url（パディングなし）の文字列で返す
+ * URLやQRコードに埋め込む用途向けです。decryptIBEFromStringで復号できます。
+ *
+ * @param publicParams 公開パラメータ
+ * @param identity アイデンティティ（文字列）
+ * @param message 暗号化するメッセージ
+ * @returns base64url形式の暗号文
+ * @throws Error 初期化されていない場合、または暗号化に失敗した場合
+ */
+export async function encryptIBEToString(
+  publicParams: IBEPublicParams,
+  identity: string,
+  message: Uint8Array
+): Promise<string> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    return ibe.encrypt_to_string(publicParams, identity, message);
+  } catch (error) {
+    throw new Error(`IBE encrypt failed: ${error}`);
+  }
+}
+
+/**
+ * base64url形式の暗号文を復号化
+ * 空白やパディング（=）が含まれていても受け付けます。
+ *
+ * @param privateKey 秘密鍵
+ * @param encoded base64url形式の暗号文
+ * @returns 復号化されたメッセージ
+ * @throws Error 初期化されていない場合、または文字列・暗号文が不正な場合
+ */
+export async function decryptIBEFromString(
+  privateKey: IBEPrivateKey,
+  encoded: string
+): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    return ibe.decrypt_from_string(privateKey, encoded);
+  } catch (error) {
+    throw new Error(`IBE decrypt failed: ${error}`);
+  }
+}
+
+/**
+ * 群要素（G1またはG2の点）を暗号化
+ * 閾値暗号やMPCなどのプロトコルで、バイト列ではなく群要素を受け渡すための低レベルAPIです。
+ *
+ * @param publicParams 公開パラメータ
+ * @param identity アイデンティティ（文字列）
+ * @param point 非圧縮形式の点（G1は65バイト、G2は130バイト）
+ * @returns 暗号文（decryptIBEPointで復号可能）
+ * @throws Error 初期化されていない場合、または曲線上の有効な点でない場合
+ */
+export async function encryptIBEPoint(
+  publicParams: IBEPublicParams,
+  identity: string,
+  point: Uint8Array
+): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    return ibe.encrypt_point(publicParams, identity, point);
+  } catch (error) {
+    throw new Error(`IBE point encrypt failed: ${error}`);
+  }
+}
+
+/**
+ * encryptIBEPointで暗号化した群要素を復号化
+ *
+ * @param privateKey 秘密鍵
+ * @param ciphertext 暗号文
+ * @returns 非圧縮形式の点
+ * @throws Error 初期化されていない場合、復号に失敗した場合、または復号結果が曲線上の有効な点でない場合
+ */
+export async function decryptIBEPoint(
+  privateKey: IBEPrivateKey,
+  ciphertext: Uint8Array
+): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    return ibe.decrypt_point(privateKey, ciphertext);
+  } catch (error) {
+    throw new Error(`IBE point decrypt failed: ${error}`);
+  }
+}
+
+/**
+ * ポスト量子ハイブリッド方式でメッセージを暗号化
+ *
+ * BN254上のペアリングは量子計算機に対して安全ではないため、移行期間向けに
+ * メッセージの対称鍵をIBEの鍵シェアとML-KEM（Kyber）の共有秘密の両方から導出します。
+ * ペアリングが破られても、Kyberの秘密鍵がなければメッセージは復号できません。
+ *
+ * @param publicParams 公開パラメータ
+ * @param identity アイデンティティ（文字列）
+ * @param kyberPublicKey 受信者のKyberの公開鍵
+ * @param message 暗号化するメッセージ
+ * @returns 暗号文（decryptIBEHybridで復号可能）
+ * @throws Error 初期化されていない場合、またはKyberの公開鍵のサイズが不正な場合
+ */
+export async function encryptIBEHybrid(
+  publicParams: IBEPublicParams,
+  identity: string,
+  kyberPublicKey: Uint8Array,
+  message: Uint8Array
+): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    return ibe.encrypt_hybrid(publicParams, identity, kyberPublicKey, message);
+  } catch (error) {
+    throw new Error(`IBE hybrid encrypt failed: ${error}`);
+  }
+}
+
+/**
+ * ハイブリッド方式の暗号文を復号化
+ * アイデンティティの秘密鍵とKyberの秘密鍵の両方が必要です。
+ *
+ * @param privateKey 秘密鍵
+ * @param kyberPrivateKey Kyberの秘密鍵
+ * @param kyberPublicKey Kyberの公開鍵（秘密鍵の復元に必要）
+ * @param ciphertext encryptIBEHybridで作成した暗号文
+ * @returns 復号化されたメッセージ
+ * @throws Error どちらかの鍵が誤っている場合、または暗号文が改ざんされている場合
+ */
+export async function decryptIBEHybrid(
+  privateKey: IBEPrivateKey,
+  kyberPrivateKey: Uint8Array,
+  kyberPublicKey: Uint8Array,
+  ciphertext: Uint8Array
+): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  try {
+    return ibe.decrypt_hybrid(privateKey, kyberPrivateKey, kyberPublicKey, ciphertext);
+  } catch (error) {
+    throw new Error(`IBE hybrid decrypt failed: ${error}`);
+  }
+}
+
+/**
+ * 暗号文の復号を試み、失敗した場合は例外の代わりにundefinedを返す
+ *
+ * 受信した暗号文を手元のすべての鍵で順に試すような用途向けです。
+ * AES-256-GCMの認証タグで成否を判定するため、アイデンティティの異なる鍵ではundefinedになります。
+ * GCM導入前の旧形式の暗号文は完全性タグがなく鍵の正否を判定できないため、常にundefinedになります。
+ *
+ * @param privateKey 秘密鍵
+ * @param ciphertext 暗号文
+ * @returns 復号化されたメッセージ。暗号文が壊れている場合などはundefined
+ */
+export async function tryDecryptIBE(
+  privateKey: IBEPrivateKey,
+  ciphertext: Uint8Array
+): Promise<Uint8Array | undefined> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  return ibe.try_decrypt(privateKey, ciphertext);
+}
+
+/**
+ * ハイブリッド方式の暗号文の復号を試み、失敗した場合は例外の代わりにundefinedを返す
+ *
+ * 暗号文のタグで成否を判定するため、アイデンティティやKyberの鍵が異なる場合も
+ * 暗号文が壊れている場合もundefinedになります。
+ *
+ * @param privateKey 秘密鍵
+ * @param kyberPrivateKey Kyberの秘密鍵
+ * @param kyberPublicKey Kyberの公開鍵（秘密鍵の復元に必要）
+ * @param ciphertext encryptIBEHybridで作成した暗号文
+ * @returns 復号化されたメッセージ。復号できない場合はundefined
+ */
+export async function tryDecryptIBEHybrid(
+  privateKey: IBEPrivateKey,
+  kyberPrivateKey: Uint8Array,
+  kyberPublicKey: Uint8Array,
+  ciphertext: Uint8Array
+): Promise<Uint8Array | undefined> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { IBE } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  const ibe = new IBE();
+
+  return ibe.try_decrypt_hybrid(privateKey, kyberPrivateKey, kyberPublicKey, ciphertext);
+}
+
+/**
+ * 公開パラメータから安定した鍵識別子（KID）を導出
+ * SHA-256(公開パラメータ) の先頭16バイトをbase64url（パディングなし）で表した22文字の文字列で、
+ * Kyber・Dilithium・FALCONの公開鍵のKIDと同じ方式です。
+ * @param publicParams 公開パラメータ
+ * @returns 鍵識別子（22文字）
+ */
+export async function getIBEKeyId(publicParams: IBEPublicParams): Promise<string> {
+  await initIBE();
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { key_id } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+
+  try {
+    return key_id(publicParams.params);
+  } catch (error) {
+    throw new Error(`IBE key id derivation failed: ${error}`);
+  }
+}
+
+/**
+ * ECIES方式の鍵ペアを生成（アイデンティティを使わない通常の公開鍵暗号化用）
+ * IBEと同じBN254のG1の群を使いますが、ペアリングは使いません。
+ *
+ * @returns 秘密鍵（32バイトのスカラーx）と公開鍵（Q = xPの非圧縮形式、65バイト）
+ */
+export async function generateECIESKeyPair(): Promise<{
+  privateKey: Uint8Array;
+  publicKey: Uint8Array;
+}> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { ecies_generate_keypair } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+
+  try {
+    const result = ecies_generate_keypair();
+    return {
+      privateKey: result.private_key,
+      publicKey: result.public_key,
+    };
+  } catch (error) {
+    throw new Error(`ECIES key generation failed: ${error}`);
+  }
+}
+
+/**
+ * ECIES方式でメッセージを暗号化
+ * 暗号化ごとに一時鍵を生成し、共有点から導出した鍵でAES-256-GCMにより暗号化と認証を行います。
+ *
+ * @param publicKey 受信者の公開鍵（65バイト）
+ * @param message 暗号化するメッセージ
+ * @returns 暗号文
+ */
+export async function encryptECIES(publicKey: Uint8Array, message: Uint8Array): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { ecies_encrypt } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+
+  try {
+    return ecies_encrypt(publicKey, message);
+  } catch (error) {
+    throw new Error(`ECIES encrypt failed: ${error}`);
+  }
+}
+
+/**
+ * encryptECIESで暗号化した暗号文を復号化
+ * 秘密鍵が誤っている場合や暗号文が壊れている場合は、認証に失敗してエラーになります。
+ *
+ * @param privateKey 秘密鍵（32バイト）
+ * @param ciphertext 暗号文
+ * @returns 復号したメッセージ
+ */
+export async function decryptECIES(privateKey: Uint8Array, ciphertext: Uint8Array): Promise<Uint8Array> {
+  await initIBE();
+
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { ecies_decrypt } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+
+  try {
+    return ecies_decrypt(privateKey, ciphertext);
+  } catch (error) {
+    throw new Error(`ECIES decrypt failed: ${error}`);
+  }
+}
+
+/**
+ * 暗号論的に安全な乱数のバイト列を生成
+ * 鍵生成と同じエントロピー源を使用します。nonceやsaltなど、アプリケーション側で必要な乱数に
+ * Math.randomの代わりに使えます。エントロピー源が利用できない場合はエラーになります。
+ *
+ * @param length 生成するバイト数
+ * @returns 乱数のバイト列
+ */
+export async function getIBERandomBytes(length: number): Promise<Uint8Array> {
+  await initIBE();
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { random_bytes } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+
+  try {
+    return random_bytes(length);
+  } catch (error) {
+    throw new Error(`IBE random bytes generation failed: ${error}`);
+  }
+}
+
+/**
+ * セキュリティ関連イベント
+ * detailにはエラー理由などの説明のみが入り、鍵や平文などの秘密情報は含まれません。
+ */
+export interface IBESecurityEvent {
+  /** 復号の失敗・曲線上の有効な点でない入力の拒否・乱数生成の失敗 */
+  event: "decrypt_failed" | "point_rejected" | "rng_failed";
+  /** 失敗の理由 */
+  detail: string;
+}
+
+/**
+ * セキュリティ関連イベントのコールバックを設定
+ * 1回の失敗につき1つのイベントが通知されます（点の検証失敗による復号失敗はpoint_rejectedのみ）。
+ * 試行を前提とした関数（tryDecryptIBEなど）の失敗は通知されません。
+ * コールバックはWeb Workerごとに個別に登録する必要があります。
+ * @param callback イベントを受け取る関数（undefinedで解除）
+ */
+export async function setIBEEventCallback(
+  callback: ((event: IBESecurityEvent) => void) | undefined
+): Promise<void> {
+  await initIBE();
+  if (!wasmModule) {
+    throw new Error("IBE module not initialized");
+  }
+
+  const { set_event_callback } = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+  set_event_callback(callback);
+}
+
+/**
+ * モジュールの動作確認
+ * 鍵生成→鍵抽出→暗号化→復号の往復が正しく動作するかを確認します。
+ *
+ * @returns 正常に動作していればtrue
  */
-export async function testIBE(): Promise<number> {
+export async function testIBE(): Promise<boolean> {
   await initIBE();
 
   if (!wasmModule) {
     throw new Error("IBE module not initialized");
   }
 
-  return wasmModule.add(2, 3);
+  return wasmModule.health_check();
 }
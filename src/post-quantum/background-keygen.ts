@@ -0,0 +1,157 @@
+/**
+ * 耐量子暗号の鍵生成をメインスレッド外で行うためのラッパー
+ *
+ * 低速な端末では、耐量子暗号の鍵生成がUIを一時的に止めることがあります。
+ * Wasmの鍵生成は途中で中断・再開できないため、鍵生成全体をWeb Worker（keygen-worker.ts）で実行し、
+ * 完了をコールバックとPromiseで通知します。
+ * Web Workerを使えない環境では、イベントループに一度処理を返してからメインスレッドで生成します。
+ */
+
+import { generateDilithiumKeyPair } from "./dilithium.js";
+import { generateFalconKeyPair } from "./falcon.js";
+import { generateKyberKeyPair } from "./kyber.js";
+
+/**
+ * バックグラウンドで生成できるパラメータセット
+ */
+export type BackgroundKeygenAlgorithm = "ML-KEM-768" | "ML-DSA-65" | "FALCON-512";
+
+/**
+ * 生成した鍵ペア
+ */
+export interface BackgroundKeyPair {
+  publicKey: Uint8Array;
+  privateKey: Uint8Array;
+}
+
+/**
+ * 鍵生成の完了を通知するコールバック
+ * 成功時はerrorがnullでkeypairに鍵ペアが、失敗時はerrorにエラーが渡されます。
+ */
+export type BackgroundKeygenCallback = (error: Error | null, keypair?: BackgroundKeyPair) => void;
+
+/**
+ * Workerとの間でやり取りするメッセージ
+ */
+export interface KeygenWorkerRequest {
+  algorithm: BackgroundKeygenAlgorithm;
+}
+
+export type KeygenWorkerResponse =
+  | { ok: true; publicKey: Uint8Array; privateKey: Uint8Array }
+  | { ok: false; error: string };
+
+/**
+ * バックグラウンドでの鍵生成のオプション
+ */
+export interface BackgroundKeygenOptions {
+  /**
+   * keygen-worker.tsを実行するWorkerを作成する関数
+   * 省略時は new Worker(new URL("./keygen-worker.js", import.meta.url), { type: "module" }) を使用します。
+   * バンドラーでWorkerのパスを解決させる場合は、この関数でWorkerを作成してください。
+   */
+  createWorker?: () => any;
+  /**
+   * falseの場合はWorkerを使わず、メインスレッドで生成します（既定はtrue）
+   */
+  useWorker?: boolean;
+}
+
+/**
+ * パラメータセットに対応する鍵生成を実行（Worker内とメインスレッドでの生成で共通）
+ *
+ * @param algorithm パラメータセット
+ * @returns 公開鍵と秘密鍵のペア
+ */
+export async function generateKeyPairFor(algorithm: BackgroundKeygenAlgorithm): Promise<BackgroundKeyPair> {
+  switch (algorithm) {
+    case "ML-KEM-768":
+      return generateKyberKeyPair();
+    case "ML-DSA-65":
+      return generateDilithiumKeyPair();
+    case "FALCON-512":
+      return generateFalconKeyPair();
+    default:
+      throw new Error(`Unsupported algorithm for background keygen: ${algorithm}`);
+  }
+}
+
+/**
+ * Web Workerを利用できる環境かどうか
+ */
+export function isWorkerAvailable(): boolean {
+  return typeof (globalThis as any).Worker === "function";
+}
+
+/**
+ * メインスレッドを止めずに耐量子暗号の鍵ペアを生成
+ *
+ * Web Workerを利用できる環境では、keygen-worker.tsを実行するWorkerで鍵を生成し、
+ * 生成後にWorkerを終了します。Workerを利用できない環境（またはuseWorkerがfalseの場合）は、
+ * イベントループに一度処理を返してからメインスレッドで生成します。
+ *
+ * @param algorithm パラメータセット
+ * @param onComplete 完了時に呼ばれるコールバック（省略可）
+ * @param options Workerの作成方法などのオプション
+ * @returns 公開鍵と秘密鍵のペア（コールバックと同じ結果）
+ */
+export async function generateKeyPairInBackground(
+  algorithm: BackgroundKeygenAlgorithm,
+  onComplete?: BackgroundKeygenCallback,
+  options: BackgroundKeygenOptions = {}
+): Promise<BackgroundKeyPair> {
+  const useWorker = options.useWorker ?? true;
+
+  try {
+    const keypair =
+      useWorker && (options.createWorker || isWorkerAvailable())
+        ? await generateInWorker(algorithm, options.createWorker)
+        : await generateOnMainThread(algorithm);
+    onComplete?.(null, keypair);
+    return keypair;
+  } catch (error) {
+    const wrapped = error instanceof Error ? error : new Error(String(error));
+    onComplete?.(wrapped);
+    throw wrapped;
+  }
+}
+
+/**
+ * Workerで鍵ペアを生成し、完了後にWorkerを終了する
+ */
+function generateInWorker(
+  algorithm: BackgroundKeygenAlgorithm,
+  createWorker?: () => any
+): Promise<BackgroundKeyPair> {
+  const worker = createWorker
+    ? createWorker()
+    : new (globalThis as any).Worker(new URL("./keygen-worker.js", import.meta.url), { type: "module" });
+
+  return new Promise<BackgroundKeyPair>((resolve, reject) => {
+    worker.onmessage = (event: { data: KeygenWorkerResponse }) => {
+      worker.terminate();
+      const response = event.data;
+      if (response.ok) {
+        resolve({ publicKey: response.publicKey, privateKey: response.privateKey });
+      } else {
+        reject(new Error(`Background keygen failed: ${response.error}`));
+      }
+    };
+    worker.onerror = (event: { message?: string }) => {
+      worker.terminate();
+      reject(new Error(`Background keygen worker error: ${event.message ?? "unknown error"}`));
+    };
+
+    const request: KeygenWorkerRequest = { algorithm };
+    worker.postMessage(request);
+  });
+}
+
+/**
+ * イベントループに一度処理を返してから、メインスレッドで鍵ペアを生成する
+ * 呼び出し直後の描画や入力処理を先に済ませるためのもので、生成中はメインスレッドが止まる
+ */
+async function generateOnMainThread(algorithm: BackgroundKeygenAlgorithm): Promise<BackgroundKeyPair> {
+  await new Promise((resolve) => setTimeout(resolve, 0));
+  return generateKeyPairFor(algorithm);
+}
@@ -63,12 +63,20 @@ export async function initDilithium(): Promise<void> {
   }
 }
 
+/**
+ * ML-DSAのセキュリティレベル（ML-DSA-44・ML-DSA-65・ML-DSA-87）
+ */
+export type DilithiumSecurityLevel = 44 | 65 | 87;
+
 /**
  * Dilithium鍵ペアを生成
+ * 署名・検証では鍵のサイズからパラメータセットを判別するため、レベルを再度指定する必要はありません。
  *
+ * @param level セキュリティレベル（省略時は65）
  * @returns 公開鍵と秘密鍵のペア
+ * @throws Error 44・65・87以外のレベルの場合
  */
-export async function generateDilithiumKeyPair(): Promise<{
+export async function generateDilithiumKeyPair(level?: DilithiumSecurityLevel): Promise<{
   publicKey: Uint8Array;
   privateKey: Uint8Array;
 }> {
@@ -80,7 +88,7 @@ export async function generateDilithiumKeyPair(): Promise<{
   }
 
   // generate_keypairは直接DilithiumKeyPairを返す（エラー時は例外が投げられる）
-  const keypair = wasmExports.generate_keypair();
+  const keypair = wasmExports.generate_keypair(level);
 
   if (!keypair) {
     throw new Error("Failed to generate Dilithium key pair: null result");
@@ -89,6 +97,55 @@ export async function generateDilithiumKeyPair(): Promise<{
   return extractKeyPairData(keypair);
 }
 
+/**
+ * シードから決定的にDilithium鍵ペアを生成
+ * 同じシードからは常に同じ鍵ペアが得られます（FIPS 204のシードξから生成した鍵ペアと一致します）。
+ * シードは秘密鍵と同等に扱ってください。
+ *
+ * @param seed 鍵生成シード（32バイト、すべて0は不可）
+ * @returns 公開鍵と秘密鍵のペア
+ */
+export async function generateDilithiumKeyPairFromSeed(seed: Uint8Array): Promise<{
+  publicKey: Uint8Array;
+  privateKey: Uint8Array;
+}> {
+  await initDilithium();
+
+  // wasmExportsから直接generate_keypair_from_seed関数を取得
+  if (!wasmExports || !wasmExports.generate_keypair_from_seed) {
+    throw new Error("generate_keypair_from_seed function not found in wasm exports");
+  }
+
+  // generate_keypair_from_seedは直接DilithiumKeyPairを返す（エラー時は例外が投げられる）
+  return extractKeyPairData(wasmExports.generate_keypair_from_seed(seed));
+}
+
+/**
+ * 複数のシードからまとめて決定的にDilithium鍵ペアを生成
+ * 大量の鍵を再現可能な形で払い出す用途向けで、wasmの呼び出しが1回で済みます。
+ * 鍵の生成前にすべてのシードを確認し、1つでも不正なものがあれば何も生成せずにエラーになります。
+ *
+ * @param seeds 鍵生成シード（それぞれ32バイト、すべて0は不可）の配列
+ * @returns シードと同じ順の鍵ペアの配列
+ */
+export async function generateDilithiumKeyPairsFromSeeds(seeds: Uint8Array[]): Promise<
+  {
+    publicKey: Uint8Array;
+    privateKey: Uint8Array;
+  }[]
+> {
+  await initDilithium();
+
+  // wasmExportsから直接generate_keypairs_from_seeds関数を取得
+  if (!wasmExports || !wasmExports.generate_keypairs_from_seeds) {
+    throw new Error("generate_keypairs_from_seeds function not found in wasm exports");
+  }
+
+  // generate_keypairs_from_seedsはDilithiumKeyPairの配列を返す（エラー時は例外が投げられる）
+  const keypairs: any[] = wasmExports.generate_keypairs_from_seeds(seeds);
+  return keypairs.map((keypair) => extractKeyPairData(keypair));
+}
+
 /**
  * DilithiumKeyPairオブジェクトから鍵データを抽出
  */
@@ -97,9 +154,9 @@ function extractKeyPairData(keypair: any): {
   privateKey: Uint8Array;
 } {
   try {
-    // プロパティを取得（getterとして定義されている）
-    const publicKey = keypair.public_key;
-    const privateKey = keypair.private_key;
+    // 直後に解放するため、クローンせずに所有権ごと取り出す
+    const publicKey = keypair.take_public_key();
+    const privateKey = keypair.take_private_key();
 
     if (!publicKey || publicKey.length === 0) {
       throw new Error(
@@ -128,10 +185,13 @@ function extractKeyPairData(keypair: any): {
 
 /**
  * メッセージに署名
+ * FIPS 204のpureモード（空のコンテキスト文字列）で署名するため、他のML-DSAの実装でも検証できます。
+ * パラメータセットは秘密鍵のサイズから判別します。
  *
  * @param message 署名するメッセージ
- * @param privateKey 秘密鍵
+ * @param privateKey 秘密鍵（ML-DSA-44・65・87のいずれか）
  * @returns 署名
+ * @throws Error 秘密鍵のサイズがどのレベルとも一致しない場合
  */
 export async function signDilithium(
   message: Uint8Array,
@@ -154,12 +214,39 @@ export async function signDilithium(
   return new Uint8Array(signature);
 }
 
+/**
+ * メッセージに決定的に署名
+ * FIPS 204の決定的な変種（乱数rndをすべて0にする）で、同じ鍵・メッセージからは常に同じ署名が得られます。
+ * 再現可能なテストベクタの作成や、乱数生成器を信頼できない環境向けです。署名はverifyDilithiumで検証できます。
+ * 通常はヘッジ付きの署名（signDilithium）を使用してください。
+ *
+ * @param message 署名するメッセージ
+ * @param privateKey 秘密鍵（ML-DSA-44・65・87のいずれか）
+ * @returns 署名
+ * @throws Error 秘密鍵のサイズがどのレベルとも一致しない場合
+ */
+export async function signDilithiumDeterministic(
+  message: Uint8Array,
+  privateKey: Uint8Array
+): Promise<Uint8Array> {
+  await initDilithium();
+
+  // wasmExportsから直接sign_deterministic関数を取得
+  if (!wasmExports || !wasmExports.sign_deterministic) {
+    throw new Error("sign_deterministic function not found in wasm exports");
+  }
+
+  // sign_deterministicは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return wasmExports.sign_deterministic(message, privateKey);
+}
+
 /**
  * 署名を検証
+ * パラメータセットは公開鍵のサイズから判別します。
  *
  * @param message 元のメッセージ
  * @param signature 署名
- * @param publicKey 公開鍵
+ * @param publicKey 公開鍵（ML-DSA-44・65・87のいずれか）
  * @returns 検証結果（true: 有効、false: 無効）
  */
 export async function verifyDilithium(
@@ -177,3 +264,654 @@ export async function verifyDilithium(
   // verifyは直接boolを返す
   return wasmExports.verify(message, signature, publicKey);
 }
+
+/**
+ * 署名の詳細な検証結果
+ * - valid: 署名が有効
+ * - invalid: 入力の形式は正しいが、署名が一致しない
+ * - malformed_input: 公開鍵・署名のサイズが不正（errorに理由が入る）
+ */
+export interface DilithiumVerifyResult {
+  status: "valid" | "invalid" | "malformed_input";
+  error: string | null;
+}
+
+/**
+ * 署名を検証し、入力の不正と署名の不一致を区別して返す
+ * verifyDilithiumはどちらの場合もfalseを返すため、利用者に原因を示したい場合はこちらを使用してください。
+ *
+ * @param message 元のメッセージ
+ * @param signature 署名
+ * @param publicKey 公開鍵（ML-DSA-44・65・87のいずれか）
+ * @returns 検証結果
+ */
+export async function verifyDilithiumDetailed(
+  message: Uint8Array,
+  signature: Uint8Array,
+  publicKey: Uint8Array
+): Promise<DilithiumVerifyResult> {
+  await initDilithium();
+
+  // wasmExportsから直接verify_detailed関数を取得
+  if (!wasmExports || !wasmExports.verify_detailed) {
+    throw new Error("verify_detailed function not found in wasm exports");
+  }
+
+  return wasmExports.verify_detailed(message, signature, publicKey);
+}
+
+/**
+ * コンテキスト文字列を指定してメッセージに署名
+ * 署名をアプリケーションの用途に束縛し、異なるコンテキスト文字列では検証に失敗します（FIPS 204のコンテキスト文字列）。
+ * 空のコンテキスト文字列を指定した場合はsignDilithiumと同じ署名になり、verifyDilithiumでも検証できます。
+ *
+ * @param message 署名するメッセージ
+ * @param context コンテキスト文字列（最大255バイト）
+ * @param privateKey 秘密鍵（ML-DSA-44・65・87のいずれか）
+ * @returns 署名
+ * @throws Error コンテキスト文字列が255バイトを超える場合、秘密鍵のサイズが不正な場合
+ */
+export async function signDilithiumWithContext(
+  message: Uint8Array,
+  context: Uint8Array,
+  privateKey: Uint8Array
+): Promise<Uint8Array> {
+  await initDilithium();
+
+  // wasmExportsから直接sign_with_context関数を取得
+  if (!wasmExports || !wasmExports.sign_with_context) {
+    throw new Error("sign_with_context function not found in wasm exports");
+  }
+
+  // sign_with_contextは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return wasmExports.sign_with_context(message, context, privateKey);
+}
+
+/**
+ * コンテキスト文字列を指定して署名を検証
+ *
+ * @param message 元のメッセージ
+ * @param context 署名時に指定したコンテキスト文字列
+ * @param signature 署名
+ * @param publicKey 公開鍵
+ * @returns 検証結果（true: 有効、false: 無効）
+ */
+export async function verifyDilithiumWithContext(
+  message: Uint8Array,
+  context: Uint8Array,
+  signature: Uint8Array,
+  publicKey: Uint8Array
+): Promise<boolean> {
+  await initDilithium();
+
+  // wasmExportsから直接verify_with_context関数を取得
+  if (!wasmExports || !wasmExports.verify_with_context) {
+    throw new Error("verify_with_context function not found in wasm exports");
+  }
+
+  // verify_with_contextは直接boolを返す
+  return wasmExports.verify_with_context(message, context, signature, publicKey);
+}
+
+/**
+ * 封筒形式の署名で扱えるML-DSAのパラメータセット
+ */
+export type DilithiumLevel = "ML-DSA-44" | "ML-DSA-65" | "ML-DSA-87";
+
+// パラメータセット名とセキュリティレベルの対応
+const DILITHIUM_SECURITY_LEVELS: Record<string, DilithiumSecurityLevel | undefined> = {
+  "ML-DSA-44": 44,
+  "ML-DSA-65": 65,
+  "ML-DSA-87": 87,
+};
+
+/**
+ * パラメータセット名を指定してML-DSA鍵ペアを生成
+ * generateDilithiumKeyPairのレベルをパラメータセット名で指定するもので、生成される鍵ペアは同じです。
+ *
+ * @param level パラメータセット
+ * @returns 公開鍵と秘密鍵のペア
+ * @throws Error 未対応のパラメータセットの場合
+ */
+export async function generateDilithiumKeyPairForLevel(level: DilithiumLevel): Promise<{
+  publicKey: Uint8Array;
+  privateKey: Uint8Array;
+}> {
+  const securityLevel = DILITHIUM_SECURITY_LEVELS[level];
+  if (securityLevel === undefined) {
+    throw new Error(`Unknown ML-DSA parameter set: ${level} (expected ML-DSA-44, ML-DSA-65 or ML-DSA-87)`);
+  }
+
+  return generateDilithiumKeyPair(securityLevel);
+}
+
+/**
+ * 封筒形式の署名のオプション
+ */
+export interface DilithiumEnvelopeOptions {
+  /** コンテキスト文字列（最大255バイト）。FIPS 204のコンテキスト付き署名になり、検証時にも同じ値が必要 */
+  context?: Uint8Array;
+  /** 署名した鍵の鍵ハッシュ（FIPS 204のtr）を含める。検証時に公開鍵と一致しない場合はエラーになる */
+  includeKeyHash?: boolean;
+}
+
+/**
+ * 封筒形式の署名のメタデータ
+ */
+export interface DilithiumEnvelopeMetadata {
+  algo: "ML-DSA";
+  level: DilithiumLevel;
+  /** コンテキスト文字列（ない場合はnull） */
+  context: Uint8Array | null;
+  has_keyhash: boolean;
+}
+
+/**
+ * メッセージに署名し、レベルタグ付きの封筒形式で返す
+ * レベル（ML-DSA-44・65・87）は秘密鍵のサイズから判定します。
+ * コンテキスト文字列か鍵ハッシュを指定した場合は、それらを含む拡張封筒形式になります。
+ *
+ * @param message 署名するメッセージ
+ * @param privateKey 秘密鍵
+ * @param options コンテキスト文字列・鍵ハッシュの指定
+ * @returns 封筒形式の署名（レベルタグ (1バイト) || 署名、または拡張封筒形式）
+ */
+export async function signDilithiumEnveloped(
+  message: Uint8Array,
+  privateKey: Uint8Array,
+  options: DilithiumEnvelopeOptions = {}
+): Promise<Uint8Array> {
+  await initDilithium();
+
+  // wasmExportsから直接sign_enveloped関数を取得
+  if (!wasmExports || !wasmExports.sign_enveloped) {
+    throw new Error("sign_enveloped function not found in wasm exports");
+  }
+
+  // sign_envelopedは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return new Uint8Array(
+    wasmExports.sign_enveloped(message, privateKey, options.context, options.includeKeyHash)
+  );
+}
+
+/**
+ * 封筒形式の署名を検証
+ * 署名のレベルと公開鍵のレベルが異なる場合は、falseではなくエラーになります。
+ *
+ * @param message 元のメッセージ
+ * @param envelope signDilithiumEnvelopedで生成した署名
+ * @param publicKey 公開鍵
+ * @returns 検証結果（true: 有効、false: 無効）
+ * @throws Error レベルが一致しない場合、またはタグ・鍵・署名の形式が不正な場合
+ */
+export async function verifyDilithiumEnveloped(
+  message: Uint8Array,
+  envelope: Uint8Array,
+  publicKey: Uint8Array
+): Promise<boolean> {
+  await initDilithium();
+
+  // wasmExportsから直接verify_enveloped関数を取得
+  if (!wasmExports || !wasmExports.verify_enveloped) {
+    throw new Error("verify_enveloped function not found in wasm exports");
+  }
+
+  // verify_envelopedは直接boolを返す（エラー時は例外が投げられる）
+  return wasmExports.verify_enveloped(message, envelope, publicKey);
+}
+
+/**
+ * 封筒形式の署名のメタデータを構造だけから読み取る
+ * 署名は検証しないため、返した値（特にコンテキスト文字列）は検証に成功するまで信頼しないでください。
+ *
+ * @param envelope 封筒形式の署名
+ * @returns 署名のメタデータ
+ * @throws Error 封筒の形式が不正な場合
+ */
+export async function readDilithiumSignatureEnvelope(envelope: Uint8Array): Promise<DilithiumEnvelopeMetadata> {
+  await initDilithium();
+
+  // wasmExportsから直接read_signature_envelope関数を取得
+  if (!wasmExports || !wasmExports.read_signature_envelope) {
+    throw new Error("read_signature_envelope function not found in wasm exports");
+  }
+
+  return wasmExports.read_signature_envelope(envelope);
+}
+
+/**
+ * 封筒形式の署名を検証し、成功した場合のみメタデータを返す
+ *
+ * @param message 元のメッセージ
+ * @param envelope 封筒形式の署名
+ * @param publicKey 公開鍵
+ * @returns 署名のメタデータ
+ * @throws Error 署名が無効な場合、またはverifyDilithiumEnvelopedがエラーになる場合
+ */
+export async function verifyDilithiumEnvelopedAndExtract(
+  message: Uint8Array,
+  envelope: Uint8Array,
+  publicKey: Uint8Array
+): Promise<DilithiumEnvelopeMetadata> {
+  await initDilithium();
+
+  // wasmExportsから直接verify_and_extract関数を取得
+  if (!wasmExports || !wasmExports.verify_and_extract) {
+    throw new Error("verify_and_extract function not found in wasm exports");
+  }
+
+  return wasmExports.verify_and_extract(message, envelope, publicKey);
+}
+
+/**
+ * base64でエンコードされた署名・公開鍵で署名を検証
+ * 鍵や署名を文字列で保持している場合に、呼び出し側での変換を不要にします。
+ *
+ * @param message 元のメッセージ（UTF-8文字列）
+ * @param signatureBase64 署名（base64）
+ * @param publicKeyBase64 公開鍵（base64）
+ * @returns 検証結果（true: 有効、false: 無効）
+ * @throws Error base64や鍵・署名のサイズが不正な場合
+ */
+export async function verifyDilithiumEncoded(
+  message: string,
+  signatureBase64: string,
+  publicKeyBase64: string
+): Promise<boolean> {
+  await initDilithium();
+
+  // wasmExportsから直接verify_encoded関数を取得
+  if (!wasmExports || !wasmExports.verify_encoded) {
+    throw new Error("verify_encoded function not found in wasm exports");
+  }
+
+  return wasmExports.verify_encoded(message, signatureBase64, publicKeyBase64);
+}
+
+/**
+ * 複数の候補の公開鍵で署名を検証
+ * 鍵のローテーション期間中など、どの鍵で署名されたか分からない場合に使用します。
+ *
+ * @param message 元のメッセージ
+ * @param signature 署名
+ * @param publicKeys 候補の公開鍵の配列
+ * @returns 署名を検証できた最初の公開鍵のインデックス（どれも一致しない場合はnull）
+ */
+export async function verifyDilithiumAny(
+  message: Uint8Array,
+  signature: Uint8Array,
+  publicKeys: Uint8Array[]
+): Promise<number | null> {
+  await initDilithium();
+
+  // wasmExportsから直接verify_any関数を取得
+  if (!wasmExports || !wasmExports.verify_any) {
+    throw new Error("verify_any function not found in wasm exports");
+  }
+
+  // verify_anyはOption<usize>を返す（Noneはundefinedになる）
+  const index = wasmExports.verify_any(message, signature, publicKeys);
+  return index === undefined ? null : index;
+}
+
+/**
+ * 複数の署名をまとめて検証
+ * 多数の署名を検証する場合に、Wasmの呼び出しを1回にまとめます。
+ * messages・signatures・publicKeysの同じ位置の要素を1組として検証します。
+ *
+ * @param messages メッセージの配列
+ * @param signatures 署名の配列
+ * @param publicKeys 公開鍵の配列
+ * @returns 組ごとの検証結果（true: 有効、false: 無効）
+ * @throws Error 配列の長さが一致しない場合
+ */
+export async function verifyDilithiumBatch(
+  messages: Uint8Array[],
+  signatures: Uint8Array[],
+  publicKeys: Uint8Array[]
+): Promise<boolean[]> {
+  await initDilithium();
+
+  // wasmExportsから直接verify_batch関数を取得
+  if (!wasmExports || !wasmExports.verify_batch) {
+    throw new Error("verify_batch function not found in wasm exports");
+  }
+
+  // verify_batchは組ごとの結果（1: 有効、0: 無効）を返す（エラー時は例外が投げられる）
+  const results: Uint8Array = wasmExports.verify_batch(messages, signatures, publicKeys);
+  return Array.from(results, (result) => result === 1);
+}
+
+/**
+ * ハイブリッド署名（Ed25519 + ML-DSA）の鍵ペアを生成
+ * どちらか一方の方式が破られても安全性を保つため、古典署名と耐量子署名を併用します。
+ * 署名・検証ではML-DSAの部分の鍵のサイズからパラメータセットを判別します。
+ *
+ * @param level ML-DSAのセキュリティレベル（省略時は65）
+ * @returns 公開鍵（Ed25519公開鍵 || ML-DSA公開鍵）と秘密鍵（Ed25519秘密鍵 || ML-DSA秘密鍵）のペア
+ * @throws Error 44・65・87以外のレベルの場合
+ */
+export async function generateDilithiumHybridKeyPair(level?: DilithiumSecurityLevel): Promise<{
+  publicKey: Uint8Array;
+  privateKey: Uint8Array;
+}> {
+  await initDilithium();
+
+  // wasmExportsから直接hybrid_generate_keypair関数を取得
+  if (!wasmExports || !wasmExports.hybrid_generate_keypair) {
+    throw new Error("hybrid_generate_keypair function not found in wasm exports");
+  }
+
+  // hybrid_generate_keypairは直接DilithiumKeyPairを返す（エラー時は例外が投げられる）
+  return extractKeyPairData(wasmExports.hybrid_generate_keypair(level));
+}
+
+/**
+ * ハイブリッド署名を生成
+ *
+ * @param message 署名するメッセージ
+ * @param privateKey generateDilithiumHybridKeyPairで生成した秘密鍵
+ * @returns 署名（Ed25519署名 (64バイト) || ML-DSA署名）
+ * @throws Error 秘密鍵のサイズが一致しない場合
+ */
+export async function signDilithiumHybrid(message: Uint8Array, privateKey: Uint8Array): Promise<Uint8Array> {
+  await initDilithium();
+
+  // wasmExportsから直接hybrid_sign関数を取得
+  if (!wasmExports || !wasmExports.hybrid_sign) {
+    throw new Error("hybrid_sign function not found in wasm exports");
+  }
+
+  // hybrid_signは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return new Uint8Array(wasmExports.hybrid_sign(message, privateKey));
+}
+
+/**
+ * ハイブリッド署名を検証
+ * Ed25519署名とML-DSA署名の両方が有効な場合のみtrueになります。
+ *
+ * @param message 元のメッセージ
+ * @param signature signDilithiumHybridで生成した署名
+ * @param publicKey generateDilithiumHybridKeyPairで生成した公開鍵
+ * @returns 検証結果（true: 有効、false: 無効）
+ */
+export async function verifyDilithiumHybrid(
+  message: Uint8Array,
+  signature: Uint8Array,
+  publicKey: Uint8Array
+): Promise<boolean> {
+  await initDilithium();
+
+  // wasmExportsから直接hybrid_verify関数を取得
+  if (!wasmExports || !wasmExports.hybrid_verify) {
+    throw new Error("hybrid_verify function not found in wasm exports");
+  }
+
+  // hybrid_verifyは直接boolを返す
+  return wasmExports.hybrid_verify(message, signature, publicKey);
+}
+
+/**
+ * WebAuthn風のチャレンジに署名
+ * チャレンジとオリジンを束縛した署名を生成します（パスワードレス認証のデモ用）。
+ *
+ * @param challenge サーバーが発行したチャレンジ（16バイト以上）
+ * @param origin 認証を要求したオリジン（例: "https://example.com"）
+ * @param privateKey 秘密鍵
+ * @returns 署名
+ */
+export async function signDilithiumChallenge(
+  challenge: Uint8Array,
+  origin: string,
+  privateKey: Uint8Array
+): Promise<Uint8Array> {
+  await initDilithium();
+
+  // wasmExportsから直接sign_challenge関数を取得
+  if (!wasmExports || !wasmExports.sign_challenge) {
+    throw new Error("sign_challenge function not found in wasm exports");
+  }
+
+  // sign_challengeは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return new Uint8Array(wasmExports.sign_challenge(challenge, origin, privateKey));
+}
+
+/**
+ * WebAuthn風のチャレンジ署名を検証
+ *
+ * @param challenge サーバーが発行したチャレンジ
+ * @param origin 期待するオリジン
+ * @param signature 署名
+ * @param publicKey 公開鍵
+ * @returns 検証結果（true: 有効、false: 無効）
+ */
+export async function verifyDilithiumChallenge(
+  challenge: Uint8Array,
+  origin: string,
+  signature: Uint8Array,
+  publicKey: Uint8Array
+): Promise<boolean> {
+  await initDilithium();
+
+  // wasmExportsから直接verify_challenge関数を取得
+  if (!wasmExports || !wasmExports.verify_challenge) {
+    throw new Error("verify_challenge function not found in wasm exports");
+  }
+
+  // verify_challengeは直接boolを返す
+  return wasmExports.verify_challenge(challenge, origin, signature, publicKey);
+}
+
+/**
+ * HashML-DSAで使用するハッシュ関数のOID
+ */
+export const DILITHIUM_PREHASH_OID = {
+  SHA256: "2.16.840.1.101.3.4.2.1",
+  SHA384: "2.16.840.1.101.3.4.2.2",
+  SHA512: "2.16.840.1.101.3.4.2.3",
+  SHA3_256: "2.16.840.1.101.3.4.2.8",
+  SHA3_512: "2.16.840.1.101.3.4.2.10",
+  SHAKE128: "2.16.840.1.101.3.4.2.11",
+  SHAKE256: "2.16.840.1.101.3.4.2.12",
+} as const;
+
+/**
+ * 事前に計算したハッシュ値に署名（HashML-DSA）
+ * FIPS 204の事前ハッシュモードで、ハッシュ関数のOIDを署名対象に含めます。
+ *
+ * @param digest メッセージのハッシュ値
+ * @param oid ハッシュ関数のOID（DILITHIUM_PREHASH_OIDなど）
+ * @param privateKey 秘密鍵
+ * @returns 署名
+ * @throws Error 未対応のOIDの場合、またはハッシュ値の長さがOIDと一致しない場合
+ */
+export async function signDilithiumPrehash(
+  digest: Uint8Array,
+  oid: string,
+  privateKey: Uint8Array
+): Promise<Uint8Array> {
+  await initDilithium();
+
+  // wasmExportsから直接sign_prehash関数を取得
+  if (!wasmExports || !wasmExports.sign_prehash) {
+    throw new Error("sign_prehash function not found in wasm exports");
+  }
+
+  // sign_prehashは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return new Uint8Array(wasmExports.sign_prehash(digest, oid, privateKey));
+}
+
+/**
+ * 事前ハッシュモード（HashML-DSA）の署名を検証
+ *
+ * @param digest メッセージのハッシュ値
+ * @param oid 署名時に指定したハッシュ関数のOID
+ * @param signature 署名
+ * @param publicKey 公開鍵
+ * @returns 検証結果（true: 有効、false: 無効）
+ */
+export async function verifyDilithiumPrehash(
+  digest: Uint8Array,
+  oid: string,
+  signature: Uint8Array,
+  publicKey: Uint8Array
+): Promise<boolean> {
+  await initDilithium();
+
+  // wasmExportsから直接verify_prehash関数を取得
+  if (!wasmExports || !wasmExports.verify_prehash) {
+    throw new Error("verify_prehash function not found in wasm exports");
+  }
+
+  // verify_prehashは直接boolを返す
+  return wasmExports.verify_prehash(digest, oid, signature, publicKey);
+}
+
+/**
+ * SHA-512のハッシュ値に署名（HashML-DSA with SHA-512）
+ * 大きなファイルはJS側でストリーミングしながらSHA-512でハッシュし、ハッシュ値だけを渡してください。
+ * 署名対象にはSHA-512のOID（DILITHIUM_PREHASH_OID.SHA512 = 2.16.840.1.101.3.4.2.3）が埋め込まれ、
+ * signDilithiumPrehashにSHA-512のOIDを指定した場合と同じ署名形式になります。
+ *
+ * @param digest メッセージのSHA-512ハッシュ値（64バイト）
+ * @param privateKey 秘密鍵
+ * @returns 署名
+ * @throws Error ハッシュ値が64バイトでない場合
+ */
+export async function signDilithiumPrehashed(digest: Uint8Array, privateKey: Uint8Array): Promise<Uint8Array> {
+  await initDilithium();
+
+  // wasmExportsから直接sign_prehashed関数を取得
+  if (!wasmExports || !wasmExports.sign_prehashed) {
+    throw new Error("sign_prehashed function not found in wasm exports");
+  }
+
+  // sign_prehashedは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return new Uint8Array(wasmExports.sign_prehashed(digest, privateKey));
+}
+
+/**
+ * signDilithiumPrehashedの署名を検証（HashML-DSA with SHA-512）
+ *
+ * @param digest メッセージのSHA-512ハッシュ値（64バイト）
+ * @param signature 署名
+ * @param publicKey 公開鍵
+ * @returns 検証結果（true: 有効、false: 無効）
+ */
+export async function verifyDilithiumPrehashed(
+  digest: Uint8Array,
+  signature: Uint8Array,
+  publicKey: Uint8Array
+): Promise<boolean> {
+  await initDilithium();
+
+  // wasmExportsから直接verify_prehashed関数を取得
+  if (!wasmExports || !wasmExports.verify_prehashed) {
+    throw new Error("verify_prehashed function not found in wasm exports");
+  }
+
+  // verify_prehashedは直接boolを返す
+  return wasmExports.verify_prehashed(digest, signature, publicKey);
+}
+
+/**
+ * 公開鍵のJWK表現（AKP形式）
+ */
+export interface DilithiumPublicJwk {
+  kty: "AKP";
+  /** パラメータセット（公開鍵のサイズから判別） */
+  alg: DilithiumLevel;
+  pub: string;
+}
+
+/**
+ * 公開鍵をJWK形式に変換
+ *
+ * @param publicKey 公開鍵
+ * @returns JWK（pubはbase64urlエンコードされた公開鍵）
+ */
+export async function exportDilithiumPublicKeyJwk(publicKey: Uint8Array): Promise<DilithiumPublicJwk> {
+  await initDilithium();
+
+  // wasmExportsから直接public_key_to_jwk関数を取得
+  if (!wasmExports || !wasmExports.public_key_to_jwk) {
+    throw new Error("public_key_to_jwk function not found in wasm exports");
+  }
+
+  // public_key_to_jwkはResult<JsValue, JsValue>を返す
+  return wasmExports.public_key_to_jwk(publicKey);
+}
+
+/**
+ * JWK形式から公開鍵を取り出す
+ *
+ * @param jwk 公開鍵のJWK
+ * @returns 公開鍵
+ */
+export async function importDilithiumPublicKeyJwk(jwk: DilithiumPublicJwk): Promise<Uint8Array> {
+  await initDilithium();
+
+  // wasmExportsから直接public_key_from_jwk関数を取得
+  if (!wasmExports || !wasmExports.public_key_from_jwk) {
+    throw new Error("public_key_from_jwk function not found in wasm exports");
+  }
+
+  // public_key_from_jwkはResult<Vec<u8>, JsValue>を返す
+  return new Uint8Array(wasmExports.public_key_from_jwk(jwk));
+}
+
+/**
+ * 公開鍵から安定した鍵識別子（KID）を導出
+ * SHA-256(公開鍵) の先頭16バイトをbase64url（パディングなし）で表した22文字の文字列です。
+ * ML-DSA-44・65・87のいずれの公開鍵にも使えます。
+ *
+ * @param publicKey 公開鍵
+ * @returns 鍵識別子（22文字）
+ */
+export async function getDilithiumKeyId(publicKey: Uint8Array): Promise<string> {
+  await initDilithium();
+
+  // wasmExportsから直接key_id関数を取得
+  if (!wasmExports || !wasmExports.key_id) {
+    throw new Error("key_id function not found in wasm exports");
+  }
+
+  // key_idはResult<String, JsValue>を返す
+  return wasmExports.key_id(publicKey);
+}
+
+/**
+ * モジュールの動作確認
+ * 鍵生成→署名→検証の往復が正しく動作するかを確認します。
+ *
+ * @returns 正常に動作していればtrue
+ */
+export async function testDilithium(): Promise<boolean> {
+  await initDilithium();
+
+  // wasmExportsから直接health_check関数を取得
+  if (!wasmExports || !wasmExports.health_check) {
+    throw new Error("health_check function not found in wasm exports");
+  }
+
+  return wasmExports.health_check();
+}
+
+/**
+ * 乱数生成器の動作確認
+ * 鍵ペアを2回生成し、両者が異なること・すべて0でないことを確認します。
+ * 乱数がすべて0になる、固定のシードから生成されているといった不具合を検出するためのもので、
+ * アプリの起動時にfalseであれば鍵の生成・使用を中止することを想定しています。
+ *
+ * @returns 乱数生成器が正常と判断できればtrue
+ */
+export async function checkDilithiumEntropy(): Promise<boolean> {
+  await initDilithium();
+
+  // wasmExportsから直接entropy_sanity_check関数を取得
+  if (!wasmExports || !wasmExports.entropy_sanity_check) {
+    throw new Error("entropy_sanity_check function not found in wasm exports");
+  }
+
+  return wasmExports.entropy_sanity_check();
+}
@@ -64,12 +64,20 @@ export async function initFalcon(): Promise<void> {
   }
 }
 
+/**
+ * FALCONのパラメータセット（FALCON-512・FALCON-1024）
+ */
+export type FalconDegree = 512 | 1024;
+
 /**
  * FALCON鍵ペアを生成
+ * 署名・検証では鍵からパラメータセットを判別するため、次数を再度指定する必要はありません。
  *
+ * @param degree パラメータセット（省略時は512）
  * @returns 公開鍵と秘密鍵のペア
+ * @throws Error 512・1024以外の次数の場合
  */
-export async function generateFalconKeyPair(): Promise<{
+export async function generateFalconKeyPair(degree?: FalconDegree): Promise<{
   publicKey: Uint8Array;
   privateKey: Uint8Array;
 }> {
@@ -81,7 +89,7 @@ export async function generateFalconKeyPair(): Promise<{
   }
 
   // generate_keypairはResult<FalconKeyPair, JsValue>を返す
-  const keypair = wasmExports.generate_keypair();
+  const keypair = wasmExports.generate_keypair(degree);
 
   if (!keypair) {
     throw new Error("Failed to generate FALCON key pair: null result");
@@ -98,9 +106,9 @@ function extractKeyPairData(keypair: any): {
   privateKey: Uint8Array;
 } {
   try {
-    // プロパティを取得（getterとして定義されている）
-    const publicKey = keypair.public_key;
-    const privateKey = keypair.private_key;
+    // 直後に解放するため、クローンせずに所有権ごと取り出す
+    const publicKey = keypair.take_public_key();
+    const privateKey = keypair.take_private_key();
 
     if (!publicKey || publicKey.length === 0) {
       throw new Error(
@@ -175,3 +183,204 @@ export async function verifyFalcon(
   // verify_signatureはResult<bool, JsValue>を返す
   return wasmExports.verify_signature(message, signature, publicKey);
 }
+
+/**
+ * base64でエンコードされた署名・公開鍵で署名を検証
+ * 鍵や署名を文字列で保持している場合に、呼び出し側での変換を不要にします。
+ *
+ * @param message 元のメッセージ（UTF-8文字列）
+ * @param signatureBase64 署名（base64）
+ * @param publicKeyBase64 公開鍵（base64）
+ * @returns 検証結果（true: 有効、false: 無効）
+ * @throws Error base64や鍵・署名の形式が不正な場合
+ */
+export async function verifyFalconEncoded(
+  message: string,
+  signatureBase64: string,
+  publicKeyBase64: string
+): Promise<boolean> {
+  await initFalcon();
+
+  // wasmExportsから直接verify_encoded関数を取得
+  if (!wasmExports || !wasmExports.verify_encoded) {
+    throw new Error("verify_encoded function not found in wasm exports");
+  }
+
+  return wasmExports.verify_encoded(message, signatureBase64, publicKeyBase64);
+}
+
+/**
+ * 複数の候補の公開鍵で署名を検証
+ * 鍵のローテーション期間中など、どの鍵で署名されたか分からない場合に使用します。
+ *
+ * @param message 元のメッセージ
+ * @param signature 署名
+ * @param publicKeys 候補の公開鍵の配列
+ * @returns 署名を検証できた最初の公開鍵のインデックス（どれも一致しない場合はnull）
+ */
+export async function verifyFalconAny(
+  message: Uint8Array,
+  signature: Uint8Array,
+  publicKeys: Uint8Array[]
+): Promise<number | null> {
+  await initFalcon();
+
+  // wasmExportsから直接verify_any関数を取得
+  if (!wasmExports || !wasmExports.verify_any) {
+    throw new Error("verify_any function not found in wasm exports");
+  }
+
+  // verify_anyはOption<usize>を返す（Noneはundefinedになる）
+  const index = wasmExports.verify_any(message, signature, publicKeys);
+  return index === undefined ? null : index;
+}
+
+/**
+ * シードから決定的にFALCON鍵ペアを生成
+ * 同じシードと次数からは常に同じ鍵ペアが得られます。
+ *
+ * @param seed 鍵生成シード（32バイト）
+ * @param degree パラメータセット（省略時は512）
+ * @returns 公開鍵と秘密鍵のペア
+ */
+export async function generateFalconKeyPairFromSeed(
+  seed: Uint8Array,
+  degree?: FalconDegree
+): Promise<{
+  publicKey: Uint8Array;
+  privateKey: Uint8Array;
+}> {
+  await initFalcon();
+
+  // wasmExportsから直接keypair_from_seed関数を取得
+  if (!wasmExports || !wasmExports.keypair_from_seed) {
+    throw new Error("keypair_from_seed function not found in wasm exports");
+  }
+
+  // keypair_from_seedはResult<FalconKeyPair, JsValue>を返す
+  return extractKeyPairData(wasmExports.keypair_from_seed(seed, degree));
+}
+
+/**
+ * 親シードとインデックスから子シードを導出（HKDF-SHA256）
+ * generateFalconKeyPairFromSeedと組み合わせて、階層的な鍵ツリーを構築できます。
+ *
+ * @param parentSeed 親シード（32バイト）
+ * @param index 子のインデックス（0〜2^32-1）
+ * @returns 子シード（32バイト）
+ */
+export async function deriveFalconChildSeed(
+  parentSeed: Uint8Array,
+  index: number
+): Promise<Uint8Array> {
+  await initFalcon();
+
+  // wasmExportsから直接derive_child_seed関数を取得
+  if (!wasmExports || !wasmExports.derive_child_seed) {
+    throw new Error("derive_child_seed function not found in wasm exports");
+  }
+
+  // derive_child_seedはResult<Vec<u8>, JsValue>を返す
+  return new Uint8Array(wasmExports.derive_child_seed(parentSeed, index));
+}
+
+/**
+ * ルートシードからパス（インデックスの列）をたどってシードを導出
+ * 例: パス [0, 3] は deriveFalconChildSeed(deriveFalconChildSeed(root, 0), 3) と同じです。
+ *
+ * @param rootSeed ルートシード（32バイト）
+ * @param path インデックスの列
+ * @returns パスの末端のシード（32バイト）
+ */
+export async function deriveFalconPathSeed(
+  rootSeed: Uint8Array,
+  path: number[]
+): Promise<Uint8Array> {
+  let seed = rootSeed;
+  for (const index of path) {
+    seed = await deriveFalconChildSeed(seed, index);
+  }
+  return seed;
+}
+
+/**
+ * 公開鍵のJWK表現（AKP形式）
+ */
+export interface FalconPublicJwk {
+  kty: "AKP";
+  alg: "FN-DSA-512" | "FN-DSA-1024";
+  pub: string;
+}
+
+/**
+ * 公開鍵をJWK形式に変換
+ *
+ * @param publicKey 公開鍵
+ * @returns JWK（pubはbase64urlエンコードされた公開鍵）
+ */
+export async function exportFalconPublicKeyJwk(publicKey: Uint8Array): Promise<FalconPublicJwk> {
+  await initFalcon();
+
+  // wasmExportsから直接public_key_to_jwk関数を取得
+  if (!wasmExports || !wasmExports.public_key_to_jwk) {
+    throw new Error("public_key_to_jwk function not found in wasm exports");
+  }
+
+  // public_key_to_jwkはResult<JsValue, JsValue>を返す
+  return wasmExports.public_key_to_jwk(publicKey);
+}
+
+/**
+ * JWK形式から公開鍵を取り出す
+ *
+ * @param jwk 公開鍵のJWK
+ * @returns 公開鍵
+ */
+export async function importFalconPublicKeyJwk(jwk: FalconPublicJwk): Promise<Uint8Array> {
+  await initFalcon();
+
+  // wasmExportsから直接public_key_from_jwk関数を取得
+  if (!wasmExports || !wasmExports.public_key_from_jwk) {
+    throw new Error("public_key_from_jwk function not found in wasm exports");
+  }
+
+  // public_key_from_jwkはResult<Vec<u8>, JsValue>を返す
+  return new Uint8Array(wasmExports.public_key_from_jwk(jwk));
+}
+
+/**
+ * 公開鍵から安定した鍵識別子（KID）を導出
+ * SHA-256(公開鍵) の先頭16バイトをbase64url（パディングなし）で表した22文字の文字列です。
+ * 他のモジュールのKIDと同じ方式のため、鍵の種類によらず同じ形式で扱えます。
+ *
+ * @param publicKey 公開鍵
+ * @returns 鍵識別子（22文字）
+ */
+export async function getFalconKeyId(publicKey: Uint8Array): Promise<string> {
+  await initFalcon();
+
+  // wasmExportsから直接key_id関数を取得
+  if (!wasmExports || !wasmExports.key_id) {
+    throw new Error("key_id function not found in wasm exports");
+  }
+
+  // key_idはResult<String, JsValue>を返す
+  return wasmExports.key_id(publicKey);
+}
+
+/**
+ * モジュールの動作確認
+ * 鍵生成→署名→検証の往復が正しく動作するかを確認します。
+ *
+ * @returns 正常に動作していればtrue
+ */
+export async function testFalcon(): Promise<boolean> {
+  await initFalcon();
+
+  // wasmExportsから直接health_check関数を取得
+  if (!wasmExports || !wasmExports.health_check) {
+    throw new Error("health_check function not found in wasm exports");
+  }
+
+  return wasmExports.health_check();
+}
@@ -0,0 +1,31 @@
+/**
+ * 耐量子暗号の鍵生成を行うWeb Workerのエントリポイント
+ *
+ * generateKeyPairInBackground（background-keygen.ts）から起動され、
+ * { algorithm } を受け取ると鍵ペアを生成して { ok, publicKey, privateKey } を返します。
+ * 鍵のバッファは転送（transfer）するため、コピーは発生しません。
+ *
+ * Workerの作成例（モジュールWorkerとして起動する）:
+ *   new Worker(new URL("./keygen-worker.js", import.meta.url), { type: "module" })
+ */
+
+import {
+  type KeygenWorkerRequest,
+  type KeygenWorkerResponse,
+  generateKeyPairFor,
+} from "./background-keygen.js";
+
+// Workerのグローバルスコープ（DOMの型定義を使わないため、型アサーションで扱う）
+const scope = globalThis as any;
+
+scope.onmessage = async (event: { data: KeygenWorkerRequest }) => {
+  let response: KeygenWorkerResponse;
+  try {
+    const { publicKey, privateKey } = await generateKeyPairFor(event.data.algorithm);
+    response = { ok: true, publicKey, privateKey };
+    scope.postMessage(response, [publicKey.buffer, privateKey.buffer]);
+  } catch (error) {
+    response = { ok: false, error: String(error) };
+    scope.postMessage(response);
+  }
+};
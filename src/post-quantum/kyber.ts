@@ -59,12 +59,20 @@ export async function initKyber(): Promise<void> {
   }
 }
 
+/**
+ * ML-KEMのパラメータセット（ML-KEM-512・ML-KEM-768・ML-KEM-1024）
+ */
+export type KyberLevel = 512 | 768 | 1024;
+
 /**
  * Kyber鍵ペアを生成
+ * カプセル化・デカプセル化では鍵のサイズからパラメータセットを判別するため、レベルを再度指定する必要はありません。
  *
+ * @param level パラメータセット（省略時は768）
  * @returns 公開鍵と秘密鍵のペア
+ * @throws Error 512・768・1024以外のレベルの場合
  */
-export async function generateKyberKeyPair(): Promise<{
+export async function generateKyberKeyPair(level?: KyberLevel): Promise<{
   publicKey: Uint8Array;
   privateKey: Uint8Array;
 }> {
@@ -76,7 +84,7 @@ export async function generateKyberKeyPair(): Promise<{
   }
 
   // generate_keypairは直接KyberKeyPairを返す（エラー時は例外が投げられる）
-  const keypair = wasmExports.generate_keypair();
+  const keypair = wasmExports.generate_keypair(level);
 
   if (!keypair) {
     throw new Error("Failed to generate Kyber key pair: null result");
@@ -85,6 +93,55 @@ export async function generateKyberKeyPair(): Promise<{
   return extractKeyPairData(keypair);
 }
 
+/**
+ * シードから決定的にKyber鍵ペアを生成
+ * 同じシードからは常に同じ鍵ペアが得られます（FIPS 203のシードd || zから生成した鍵ペアと一致します）。
+ * シードは秘密鍵と同等に扱ってください。
+ *
+ * @param seed 鍵生成シード（64バイト、すべて0は不可）
+ * @returns 公開鍵と秘密鍵のペア
+ */
+export async function generateKyberKeyPairFromSeed(seed: Uint8Array): Promise<{
+  publicKey: Uint8Array;
+  privateKey: Uint8Array;
+}> {
+  await initKyber();
+
+  // wasmExportsから直接generate_keypair_from_seed関数を取得
+  if (!wasmExports || !wasmExports.generate_keypair_from_seed) {
+    throw new Error("generate_keypair_from_seed function not found in wasm exports");
+  }
+
+  // generate_keypair_from_seedは直接KyberKeyPairを返す（エラー時は例外が投げられる）
+  return extractKeyPairData(wasmExports.generate_keypair_from_seed(seed));
+}
+
+/**
+ * 複数のシードからまとめて決定的にKyber鍵ペアを生成
+ * 大量の鍵を再現可能な形で払い出す用途向けで、wasmの呼び出しが1回で済みます。
+ * 鍵の生成前にすべてのシードを確認し、1つでも不正なものがあれば何も生成せずにエラーになります。
+ *
+ * @param seeds 鍵生成シード（それぞれ64バイト、すべて0は不可）の配列
+ * @returns シードと同じ順の鍵ペアの配列
+ */
+export async function generateKyberKeyPairsFromSeeds(seeds: Uint8Array[]): Promise<
+  {
+    publicKey: Uint8Array;
+    privateKey: Uint8Array;
+  }[]
+> {
+  await initKyber();
+
+  // wasmExportsから直接generate_keypairs_from_seeds関数を取得
+  if (!wasmExports || !wasmExports.generate_keypairs_from_seeds) {
+    throw new Error("generate_keypairs_from_seeds function not found in wasm exports");
+  }
+
+  // generate_keypairs_from_seedsはKyberKeyPairの配列を返す（エラー時は例外が投げられる）
+  const keypairs: any[] = wasmExports.generate_keypairs_from_seeds(seeds);
+  return keypairs.map((keypair) => extractKeyPairData(keypair));
+}
+
 /**
  * KyberKeyPairオブジェクトから鍵データを抽出
  */
@@ -93,9 +150,9 @@ function extractKeyPairData(keypair: any): {
   privateKey: Uint8Array;
 } {
   try {
-    // プロパティを取得（getterとして定義されている）
-    const publicKey = keypair.public_key;
-    const privateKey = keypair.private_key;
+    // 直後に解放するため、クローンせずに所有権ごと取り出す
+    const publicKey = keypair.take_public_key();
+    const privateKey = keypair.take_private_key();
 
     if (!publicKey || publicKey.length === 0) {
       throw new Error(
@@ -148,8 +205,45 @@ export async function encapsulateKyber(publicKey: Uint8Array): Promise<{
 
   try {
     return {
-      ciphertext: new Uint8Array(result.ciphertext),
-      sharedSecret: new Uint8Array(result.shared_secret),
+      ciphertext: new Uint8Array(result.take_ciphertext()),
+      sharedSecret: new Uint8Array(result.take_shared_secret()),
+    };
+  } finally {
+    // メモリリークを防ぐためにリソースを解放
+    if (typeof result.free === "function") {
+      result.free();
+    }
+  }
+}
+
+/**
+ * 乱数を指定して決定的に鍵カプセル化を行う
+ * 既知解テスト（KAT）でFIPS 203のML-KEM.Encaps_internal(ek, m)の出力と照合するためのものです。
+ * 同じ公開鍵と乱数からは常に同じ結果になるため、通常の用途ではencapsulateKyberを使用してください。
+ *
+ * @param publicKey 公開鍵
+ * @param coins カプセル化の乱数m（32バイト）
+ * @returns 暗号文と共有秘密
+ */
+export async function encapsulateKyberDeterministic(
+  publicKey: Uint8Array,
+  coins: Uint8Array
+): Promise<{
+  ciphertext: Uint8Array;
+  sharedSecret: Uint8Array;
+}> {
+  await initKyber();
+
+  if (!wasmExports || !wasmExports.encapsulate_deterministic) {
+    throw new Error("encapsulate_deterministic function not found in wasm exports");
+  }
+
+  // encapsulate_deterministicはKyberEncapsulationを返す（エラー時は例外が投げられる）
+  const result = wasmExports.encapsulate_deterministic(publicKey, coins);
+  try {
+    return {
+      ciphertext: new Uint8Array(result.take_ciphertext()),
+      sharedSecret: new Uint8Array(result.take_shared_secret()),
     };
   } finally {
     // メモリリークを防ぐためにリソースを解放
@@ -189,3 +283,571 @@ export async function decapsulateKyber(
   // Vec<u8>は自動的にUint8Arrayに変換される
   return new Uint8Array(sharedSecret);
 }
+
+/**
+ * 鍵カプセル化を行い、暗号文を転送形式で返す
+ * 転送形式は version (1バイト) || length (4バイト、ビッグエンディアン) || 暗号文 です。
+ * 共有秘密は秘密情報のため転送形式には含まれません。
+ *
+ * @param publicKey 公開鍵
+ * @returns 転送形式の暗号文と共有秘密
+ */
+export async function encapsulateKyberForTransport(publicKey: Uint8Array): Promise<{
+  transport: Uint8Array;
+  sharedSecret: Uint8Array;
+}> {
+  await initKyber();
+
+  if (!wasmExports || !wasmExports.encapsulate) {
+    throw new Error("encapsulate function not found in wasm exports");
+  }
+
+  // encapsulateは直接KyberEncapsulationを返す（エラー時は例外が投げられる）
+  const result = wasmExports.encapsulate(publicKey);
+  try {
+    return {
+      transport: new Uint8Array(result.to_transport()),
+      sharedSecret: new Uint8Array(result.take_shared_secret()),
+    };
+  } finally {
+    // メモリリークを防ぐためにリソースを解放
+    if (typeof result.free === "function") {
+      result.free();
+    }
+  }
+}
+
+/**
+ * 転送形式から暗号文を取り出す
+ * 取り出した暗号文はdecapsulateKyberにそのまま渡せます。
+ *
+ * @param transport encapsulateKyberForTransportで生成したバイト列
+ * @returns 暗号文
+ * @throws Error 対応していないバージョン、長さの不一致、またはML-KEMの暗号文のサイズでない場合
+ */
+export async function readKyberTransport(transport: Uint8Array): Promise<Uint8Array> {
+  await initKyber();
+
+  if (!wasmExports || !wasmExports.from_transport) {
+    throw new Error("from_transport function not found in wasm exports");
+  }
+
+  // from_transportは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return new Uint8Array(wasmExports.from_transport(transport));
+}
+
+/**
+ * 鍵カプセル化を行い、鍵確認タグを付けて返す（イニシエータ側）
+ * イニシエータは暗号文と鍵確認タグをレスポンダに送り、レスポンダはrespondKyberSessionで検証します。
+ *
+ * @param publicKey レスポンダの公開鍵
+ * @returns 暗号文、共有秘密、鍵確認タグ（32バイト）
+ */
+export async function initiateKyberSession(publicKey: Uint8Array): Promise<{
+  ciphertext: Uint8Array;
+  sharedSecret: Uint8Array;
+  confirmationTag: Uint8Array;
+}> {
+  await initKyber();
+
+  if (!wasmExports || !wasmExports.encapsulate) {
+    throw new Error("encapsulate function not found in wasm exports");
+  }
+
+  // encapsulateは直接KyberEncapsulationを返す（エラー時は例外が投げられる）
+  const result = wasmExports.encapsulate(publicKey);
+  try {
+    // 鍵確認タグは共有秘密と暗号文から計算するため、取り出す前に求める
+    const confirmationTag = new Uint8Array(result.confirmation_tag());
+    return {
+      ciphertext: new Uint8Array(result.take_ciphertext()),
+      sharedSecret: new Uint8Array(result.take_shared_secret()),
+      confirmationTag,
+    };
+  } finally {
+    // メモリリークを防ぐためにリソースを解放
+    if (typeof result.free === "function") {
+      result.free();
+    }
+  }
+}
+
+/**
+ * 鍵確認付きのデカプセル化（レスポンダ側）
+ * ML-KEMは不正な暗号文に対してもエラーを返さず擬似乱数の共有秘密を返す（暗黙的拒否）ため、
+ * イニシエータの鍵確認タグを検証してから共有秘密を返します。
+ *
+ * @param ciphertext イニシエータから受け取った暗号文
+ * @param privateKey 秘密鍵
+ * @param publicKey 公開鍵
+ * @param confirmationTag イニシエータから受け取った鍵確認タグ
+ * @returns 鍵確認に成功した場合の共有秘密
+ * @throws Error 暗号文が改ざんされている、鍵が一致しない、または鍵確認タグが一致しない場合
+ */
+export async function respondKyberSession(
+  ciphertext: Uint8Array,
+  privateKey: Uint8Array,
+  publicKey: Uint8Array,
+  confirmationTag: Uint8Array
+): Promise<Uint8Array> {
+  await initKyber();
+
+  if (!wasmExports || !wasmExports.responder_session) {
+    throw new Error("responder_session function not found in wasm exports");
+  }
+
+  // responder_sessionは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return new Uint8Array(wasmExports.responder_session(ciphertext, privateKey, publicKey, confirmationTag));
+}
+
+/**
+ * ハイブリッドKEM（X25519 + ML-KEM）の鍵ペア
+ */
+export interface KyberHybridKeyPairData {
+  classicalPublicKey: Uint8Array;
+  classicalPrivateKey: Uint8Array;
+  pqPublicKey: Uint8Array;
+  pqPrivateKey: Uint8Array;
+}
+
+/**
+ * ハイブリッドKEM（X25519 + ML-KEM）の鍵ペアを生成
+ * どちらか一方の方式が破られても共有秘密の安全性を保つため、古典的な鍵共有と耐量子KEMを併用します。
+ *
+ * @param level ML-KEMのパラメータセット（省略時は768）
+ * @returns X25519の鍵ペアとML-KEMの鍵ペア
+ */
+export async function generateKyberHybridKeyPair(level?: KyberLevel): Promise<KyberHybridKeyPairData> {
+  await initKyber();
+
+  if (!wasmExports || !wasmExports.generate_hybrid_keypair) {
+    throw new Error("generate_hybrid_keypair function not found in wasm exports");
+  }
+
+  const keypair = wasmExports.generate_hybrid_keypair(level);
+  try {
+    return {
+      classicalPublicKey: new Uint8Array(keypair.classical_public_key),
+      classicalPrivateKey: new Uint8Array(keypair.classical_private_key),
+      pqPublicKey: new Uint8Array(keypair.pq_public_key),
+      pqPrivateKey: new Uint8Array(keypair.pq_private_key),
+    };
+  } finally {
+    // メモリリークを防ぐためにリソースを解放
+    if (typeof keypair.free === "function") {
+      keypair.free();
+    }
+  }
+}
+
+/**
+ * ハイブリッドKEMのカプセル化
+ * X25519とML-KEMの共有秘密をHKDF-SHA256で1つの32バイトの共有秘密にまとめます。
+ *
+ * @param classicalPublicKey 受信者のX25519公開鍵（32バイト）
+ * @param pqPublicKey 受信者のML-KEM公開鍵
+ * @returns 暗号文（X25519の一時公開鍵 || ML-KEMの暗号文）と共有秘密
+ */
+export async function hybridEncapsulateKyber(
+  classicalPublicKey: Uint8Array,
+  pqPublicKey: Uint8Array
+): Promise<{
+  ciphertext: Uint8Array;
+  sharedSecret: Uint8Array;
+}> {
+  await initKyber();
+
+  if (!wasmExports || !wasmExports.hybrid_encapsulate) {
+    throw new Error("hybrid_encapsulate function not found in wasm exports");
+  }
+
+  // hybrid_encapsulateはKyberEncapsulationを返す（エラー時は例外が投げられる）
+  const result = wasmExports.hybrid_encapsulate(classicalPublicKey, pqPublicKey);
+  try {
+    return {
+      ciphertext: new Uint8Array(result.take_ciphertext()),
+      sharedSecret: new Uint8Array(result.take_shared_secret()),
+    };
+  } finally {
+    // メモリリークを防ぐためにリソースを解放
+    if (typeof result.free === "function") {
+      result.free();
+    }
+  }
+}
+
+/**
+ * ハイブリッドKEMのデカプセル化
+ *
+ * @param ciphertext hybridEncapsulateKyberで生成した暗号文
+ * @param classicalPrivateKey X25519秘密鍵（32バイト）
+ * @param pqPrivateKey ML-KEM秘密鍵
+ * @param pqPublicKey ML-KEM公開鍵（秘密鍵の復元に必要）
+ * @returns 32バイトの共有秘密
+ */
+export async function hybridDecapsulateKyber(
+  ciphertext: Uint8Array,
+  classicalPrivateKey: Uint8Array,
+  pqPrivateKey: Uint8Array,
+  pqPublicKey: Uint8Array
+): Promise<Uint8Array> {
+  await initKyber();
+
+  if (!wasmExports || !wasmExports.hybrid_decapsulate) {
+    throw new Error("hybrid_decapsulate function not found in wasm exports");
+  }
+
+  return new Uint8Array(wasmExports.hybrid_decapsulate(ciphertext, classicalPrivateKey, pqPrivateKey, pqPublicKey));
+}
+
+/**
+ * 公開鍵から安定した鍵識別子（KID）を導出
+ * SHA-256(公開鍵) の先頭16バイトをbase64url（パディングなし）で表した22文字の文字列です。
+ * 同じ公開鍵からは常に同じ値になるため、鍵の索引やヘッダでの参照に使えます。
+ *
+ * @param publicKey 公開鍵
+ * @returns 鍵識別子（22文字）
+ */
+export async function getKyberKeyId(publicKey: Uint8Array): Promise<string> {
+  await initKyber();
+
+  // wasmExportsから直接key_id関数を取得
+  if (!wasmExports || !wasmExports.key_id) {
+    throw new Error("key_id function not found in wasm exports");
+  }
+
+  // key_idはResult<String, JsValue>を返す
+  return wasmExports.key_id(publicKey);
+}
+
+/**
+ * 復号に対応している暗号文の形式のバージョンを取得
+ * ストリーム暗号化と複数受信者向けコンテナの先頭1バイトのバージョンで、新しい順に並びます。
+ * 暗号化では常に先頭（最新）のバージョンを使い、それ以外は旧形式の復号のためだけに対応しています。
+ * 一覧にないバージョンの暗号文は、バージョン番号を含む「Unsupported ... version」のエラーになります。
+ *
+ * @returns 対応しているバージョンの一覧（例: [2, 1]）
+ */
+export async function getSupportedKyberCiphertextVersions(): Promise<number[]> {
+  await initKyber();
+
+  // wasmExportsから直接supported_ciphertext_versions関数を取得
+  if (!wasmExports || !wasmExports.supported_ciphertext_versions) {
+    throw new Error("supported_ciphertext_versions function not found in wasm exports");
+  }
+
+  return Array.from(wasmExports.supported_ciphertext_versions() as Uint8Array);
+}
+
+/**
+ * 複数受信者向けコンテナの本文に使える認証付き暗号
+ */
+export type KyberAeadCipher = "chacha20-poly1305" | "aes-256-gcm";
+
+/**
+ * この環境で速い方の認証付き暗号を計測して推奨
+ *
+ * AES-256-GCMとChaCha20-Poly1305で同じ量のデータを暗号化し、速い方を返します。
+ * ハードウェア支援の有無で結果が変わるため、アプリの起動時に1回呼び出す想定です。
+ * 結果は記録され、sealKyberMultiで"auto"を指定した場合に使われます。
+ *
+ * @param workFactor 各暗号で暗号化する4KiBのブロックの数（1〜65536、省略時は256）
+ * @returns 推奨する暗号
+ */
+export async function recommendKyberAead(workFactor?: number): Promise<KyberAeadCipher> {
+  await initKyber();
+
+  // wasmExportsから直接recommend_aead関数を取得
+  if (!wasmExports || !wasmExports.recommend_aead) {
+    throw new Error("recommend_aead function not found in wasm exports");
+  }
+
+  // recommend_aeadはResult<String, JsValue>を返す
+  return wasmExports.recommend_aead(workFactor) as KyberAeadCipher;
+}
+
+/**
+ * モジュールの動作確認
+ * 鍵生成→カプセル化→デカプセル化の往復が正しく動作するかを確認します。
+ *
+ * @returns 正常に動作していればtrue
+ */
+export async function testKyber(): Promise<boolean> {
+  await initKyber();
+
+  // wasmExportsから直接health_check関数を取得
+  if (!wasmExports || !wasmExports.health_check) {
+    throw new Error("health_check function not found in wasm exports");
+  }
+
+  return wasmExports.health_check();
+}
+
+/**
+ * 乱数生成器の動作確認
+ * 鍵ペアを2回生成し、両者が異なること・すべて0でないことを確認します。
+ * 乱数がすべて0になる、固定のシードから生成されているといった不具合を検出するためのもので、
+ * アプリの起動時にfalseであれば鍵の生成・使用を中止することを想定しています。
+ *
+ * @returns 乱数生成器が正常と判断できればtrue
+ */
+export async function checkKyberEntropy(): Promise<boolean> {
+  await initKyber();
+
+  // wasmExportsから直接entropy_sanity_check関数を取得
+  if (!wasmExports || !wasmExports.entropy_sanity_check) {
+    throw new Error("entropy_sanity_check function not found in wasm exports");
+  }
+
+  return wasmExports.entropy_sanity_check();
+}
+
+/**
+ * Kyberで封をしたストリーム暗号化用のTransformStreamを作成
+ *
+ * 入力チャンクごとに認証付きで暗号化したフレームを出力します。
+ * 出力を順に連結したものが暗号化ストリームになり、createKyberDecryptStreamで復号できます。
+ * ファイル全体をメモリに保持せずに暗号化できます（1チャンクは最大16MiB）。
+ *
+ * 通信量が限られる環境向けに、チャンクごとの認証タグを12バイトまで短くできます。
+ * タグの長さはストリームヘッダに記録されるため、復号側での指定は不要です。
+ * 注意: タグを短くすると改ざん（偽造）が検出されない確率が上がります。特に理由がなければ既定の16バイトを使ってください。
+ *
+ * @param publicKey 受信者の公開鍵
+ * @param tagLength 認証タグの長さ（12〜16バイト、省略時は16）
+ * @returns 平文を受け取り暗号化ストリームを出力するTransformStream
+ */
+export async function createKyberEncryptStream(
+  publicKey: Uint8Array,
+  tagLength?: number
+): Promise<TransformStream<Uint8Array, Uint8Array>> {
+  await initKyber();
+
+  // wasmExportsから直接KyberStreamEncryptorを取得
+  if (!wasmExports || !wasmExports.KyberStreamEncryptor) {
+    throw new Error("KyberStreamEncryptor not found in wasm exports");
+  }
+
+  const encryptor = wasmExports.KyberStreamEncryptor.stream_init(publicKey, tagLength);
+
+  return new TransformStream<Uint8Array, Uint8Array>({
+    transform(chunk, controller) {
+      controller.enqueue(new Uint8Array(encryptor.stream_push(chunk)));
+    },
+    flush(controller) {
+      try {
+        controller.enqueue(new Uint8Array(encryptor.stream_finish()));
+      } finally {
+        // メモリリークを防ぐためにリソースを解放
+        encryptor.free();
+      }
+    },
+  });
+}
+
+/**
+ * Kyberで封をしたストリームを復号するTransformStreamを作成
+ *
+ * 入力は任意の境界で分割されていてもかまいません。
+ * 改ざんや切り詰め（終端フレームの欠落）を検出した場合はストリームをエラーにします。
+ *
+ * @param privateKey 秘密鍵
+ * @param publicKey 公開鍵（秘密鍵の復元に必要）
+ * @returns 暗号化ストリームを受け取り平文を出力するTransformStream
+ */
+export async function createKyberDecryptStream(
+  privateKey: Uint8Array,
+  publicKey: Uint8Array
+): Promise<TransformStream<Uint8Array, Uint8Array>> {
+  await initKyber();
+
+  // wasmExportsから直接KyberStreamDecryptorを取得
+  if (!wasmExports || !wasmExports.KyberStreamDecryptor) {
+    throw new Error("KyberStreamDecryptor not found in wasm exports");
+  }
+
+  const decryptor = wasmExports.KyberStreamDecryptor.stream_init(privateKey, publicKey);
+
+  return new TransformStream<Uint8Array, Uint8Array>({
+    transform(chunk, controller) {
+      const plaintext = new Uint8Array(decryptor.stream_push(chunk));
+      if (plaintext.length > 0) {
+        controller.enqueue(plaintext);
+      }
+    },
+    flush() {
+      try {
+        decryptor.stream_finish();
+      } finally {
+        // メモリリークを防ぐためにリソースを解放
+        decryptor.free();
+      }
+    },
+  });
+}
+
+/**
+ * 複数の受信者向けにメッセージを暗号化
+ *
+ * 本文は1回だけ暗号化し、本文を暗号化したコンテンツ鍵を受信者ごとにKyberで封入します。
+ * 本文のnonceはすべての受信者スロットのハッシュから導出するため、
+ * どれか1つのスロットを書き換えると全受信者の復号が失敗します。
+ *
+ * 本文の認証タグは12バイトまで短くでき、その長さはコンテナのヘッダに記録されます。
+ * 注意: タグを短くすると改ざん（偽造）が検出されない確率が上がります。特に理由がなければ既定の16バイトを使ってください。
+ *
+ * 本文の暗号はChaCha20-Poly1305とAES-256-GCMから選べ、コンテナのヘッダに記録されます。
+ * "auto"を指定すると、recommendKyberAeadでこの環境で速いと判定された暗号を使います。
+ *
+ * @param publicKeys 受信者の公開鍵の配列（1〜255個）
+ * @param plaintext 暗号化するメッセージ
+ * @param tagLength 本文の認証タグの長さ（12〜16バイト、省略時は16）
+ * @param cipher 本文の暗号（省略時は"chacha20-poly1305"）
+ * @returns コンテナ（openKyberMultiで復号可能）
+ */
+export async function sealKyberMulti(
+  publicKeys: Uint8Array[],
+  plaintext: Uint8Array,
+  tagLength?: number,
+  cipher?: KyberAeadCipher | "auto"
+): Promise<Uint8Array> {
+  await initKyber();
+
+  // wasmExportsから直接kyber_seal_multi関数を取得
+  if (!wasmExports || !wasmExports.kyber_seal_multi) {
+    throw new Error("kyber_seal_multi function not found in wasm exports");
+  }
+
+  // kyber_seal_multiは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return new Uint8Array(wasmExports.kyber_seal_multi(publicKeys, plaintext, tagLength, cipher));
+}
+
+/**
+ * 複数受信者向けのコンテナを復号
+ *
+ * @param container sealKyberMultiで作成したコンテナ
+ * @param privateKey 秘密鍵
+ * @param publicKey 公開鍵（秘密鍵の復元に必要）
+ * @returns 復号したメッセージ
+ * @throws Error 自分宛てのスロットがない場合、またはコンテナが改ざんされている場合
+ */
+export async function openKyberMulti(
+  container: Uint8Array,
+  privateKey: Uint8Array,
+  publicKey: Uint8Array
+): Promise<Uint8Array> {
+  await initKyber();
+
+  // wasmExportsから直接kyber_open_multi関数を取得
+  if (!wasmExports || !wasmExports.kyber_open_multi) {
+    throw new Error("kyber_open_multi function not found in wasm exports");
+  }
+
+  // kyber_open_multiは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return new Uint8Array(wasmExports.kyber_open_multi(container, privateKey, publicKey));
+}
+
+/**
+ * ランダムアクセス可能な形式でファイルを暗号化
+ *
+ * 平文を固定サイズのチャンクに分け、チャンクごとに別の鍵で認証付き暗号化します。
+ * リモートに置いた暗号化ファイルから、ヘッダと必要なチャンクだけをダウンロードして
+ * 一部の範囲を復号できます（getKyberSeekableChunkRange・decryptKyberRangeを参照）。
+ * 改ざんの検出はチャンク単位で、復号した範囲に含まれるチャンクだけを検証します。
+ *
+ * @param publicKey 受信者の公開鍵
+ * @param plaintext 暗号化するファイルの内容
+ * @param chunkSize チャンクの平文のサイズ（1バイト〜16MiB、省略時は64KiB）
+ * @param tagLength チャンクごとの認証タグの長さ（12〜16バイト、省略時は16）
+ * @returns 暗号化ファイル（ヘッダ || チャンク*）
+ */
+export async function sealKyberSeekable(
+  publicKey: Uint8Array,
+  plaintext: Uint8Array,
+  chunkSize?: number,
+  tagLength?: number
+): Promise<Uint8Array> {
+  await initKyber();
+
+  // wasmExportsから直接kyber_seal_seekable関数を取得
+  if (!wasmExports || !wasmExports.kyber_seal_seekable) {
+    throw new Error("kyber_seal_seekable function not found in wasm exports");
+  }
+
+  // kyber_seal_seekableは直接Vec<u8>を返す（エラー時は例外が投げられる）
+  return new Uint8Array(wasmExports.kyber_seal_seekable(publicKey, plaintext, chunkSize, tagLength));
+}
+
+/**
+ * ランダムアクセス可能な暗号化ファイルのヘッダのサイズを取得
+ *
+ * @returns ヘッダのサイズ（バイト）。暗号化ファイルの先頭からこの長さをダウンロードする
+ */
+export async function getKyberSeekableHeaderSize(): Promise<number> {
+  await initKyber();
+
+  // wasmExportsから直接kyber_seekable_header_size関数を取得
+  if (!wasmExports || !wasmExports.kyber_seekable_header_size) {
+    throw new Error("kyber_seekable_header_size function not found in wasm exports");
+  }
+
+  return wasmExports.kyber_seekable_header_size();
+}
+
+/**
+ * 平文の範囲の復号に必要な、暗号化ファイル内のバイト範囲を取得
+ *
+ * @param sealedHeader 暗号化ファイルのヘッダ（先頭getKyberSeekableHeaderSizeバイト）
+ * @param start 平文の範囲の開始位置
+ * @param length 平文の範囲の長さ
+ * @returns ダウンロードする範囲（暗号化ファイル内の開始位置とバイト数）
+ * @throws Error ヘッダが不正な場合、または範囲が平文の長さを超える場合
+ */
+export async function getKyberSeekableChunkRange(
+  sealedHeader: Uint8Array,
+  start: number,
+  length: number
+): Promise<{ offset: number; length: number }> {
+  await initKyber();
+
+  // wasmExportsから直接kyber_seekable_chunk_range関数を取得
+  if (!wasmExports || !wasmExports.kyber_seekable_chunk_range) {
+    throw new Error("kyber_seekable_chunk_range function not found in wasm exports");
+  }
+
+  // u64の引数・戻り値はBigIntとして受け渡す
+  const [offset, byteLength] = wasmExports.kyber_seekable_chunk_range(sealedHeader, BigInt(start), BigInt(length));
+  return { offset: Number(offset), length: Number(byteLength) };
+}
+
+/**
+ * ランダムアクセス可能な暗号化ファイルの一部の範囲だけを復号
+ *
+ * @param sealedHeader 暗号化ファイルのヘッダ（先頭getKyberSeekableHeaderSizeバイト）
+ * @param chunkBytes getKyberSeekableChunkRangeが返した範囲の暗号化ファイルのバイト列
+ * @param start 平文の範囲の開始位置
+ * @param length 平文の範囲の長さ
+ * @param privateKey 秘密鍵
+ * @param publicKey 公開鍵（秘密鍵の復元に必要）
+ * @returns 復号した平文の範囲
+ * @throws Error 範囲に含まれるチャンクまたはヘッダが改ざんされている場合、または鍵が一致しない場合
+ */
+export async function decryptKyberRange(
+  sealedHeader: Uint8Array,
+  chunkBytes: Uint8Array,
+  start: number,
+  length: number,
+  privateKey: Uint8Array,
+  publicKey: Uint8Array
+): Promise<Uint8Array> {
+  await initKyber();
+
+  // wasmExportsから直接kyber_decrypt_range関数を取得
+  if (!wasmExports || !wasmExports.kyber_decrypt_range) {
+    throw new Error("kyber_decrypt_range function not found in wasm exports");
+  }
+
+  return new Uint8Array(
+    wasmExports.kyber_decrypt_range(sealedHeader, chunkBytes, BigInt(start), BigInt(length), privateKey, publicKey)
+  );
+}
@@ -0,0 +1,163 @@
+/**
+ * 耐量子署名の共通インターフェース
+ *
+ * Dilithium（ML-DSA）とFALCONは、Wasmモジュールごとに関数名や検証時のエラーの扱いが異なります。
+ * このモジュールはアルゴリズム名を引数に取る共通の関数を提供し、内部で各モジュールに振り分けます。
+ * 鍵と署名のバイト形式はアルゴリズムごとに異なり、アルゴリズム間で互換性はありません。
+ *
+ * アルゴリズムと各モジュールの対応:
+ *
+ *   アルゴリズム    モジュール         鍵生成                           署名/検証
+ *   ML-DSA-44      dilithium-wasm     generate_keypair(44)             sign / verify
+ *   ML-DSA-65      dilithium-wasm     generate_keypair(65)             sign / verify
+ *   ML-DSA-87      dilithium-wasm     generate_keypair(87)             sign / verify
+ *   FALCON-512     falcon-rust-wasm   generate_keypair(512)            sign_message / verify_signature
+ *   FALCON-1024    falcon-rust-wasm   generate_keypair(1024)           sign_message / verify_signature
+ *
+ * 各モジュールは鍵からパラメータセットを判別しますが、このモジュールでは指定したアルゴリズムの鍵であることも確認します。
+ */
+
+import { generateDilithiumKeyPair, signDilithium, verifyDilithium } from "./dilithium.js";
+import { generateFalconKeyPair, signFalcon, verifyFalcon } from "./falcon.js";
+
+/**
+ * 共通インターフェースで扱える署名アルゴリズム
+ */
+export type SignatureAlgorithm = "ML-DSA-44" | "ML-DSA-65" | "ML-DSA-87" | "FALCON-512" | "FALCON-1024";
+
+/**
+ * 署名アルゴリズムの情報
+ */
+export interface SignatureAlgorithmInfo {
+  /** 署名方式 */
+  family: "ML-DSA" | "FALCON";
+  /** 公開鍵のサイズ（バイト） */
+  publicKeySize: number;
+  /** 秘密鍵のサイズ（バイト） */
+  privateKeySize: number;
+}
+
+/**
+ * 各署名アルゴリズムの情報
+ * FALCONの署名は可変長のため、署名のサイズは含めません。
+ */
+export const SIGNATURE_ALGORITHMS: Record<SignatureAlgorithm, SignatureAlgorithmInfo> = {
+  "ML-DSA-44": { family: "ML-DSA", publicKeySize: 1312, privateKeySize: 2560 },
+  "ML-DSA-65": { family: "ML-DSA", publicKeySize: 1952, privateKeySize: 4032 },
+  "ML-DSA-87": { family: "ML-DSA", publicKeySize: 2592, privateKeySize: 4896 },
+  "FALCON-512": { family: "FALCON", publicKeySize: 897, privateKeySize: 1281 },
+  "FALCON-1024": { family: "FALCON", publicKeySize: 1793, privateKeySize: 2305 },
+};
+
+/**
+ * FALCONのverify_signatureが形式の不正な公開鍵・署名に対して返すエラー（文字列）の接頭辞
+ */
+const FALCON_MALFORMED_INPUT_ERRORS = ["Invalid public key", "Invalid signature"];
+
+/**
+ * FALCONの検証のエラーが、形式の不正な公開鍵・署名によるものかどうか
+ * WebAssemblyモジュールの初期化・読み込みの失敗（Error）はこれに当たらない
+ */
+function isFalconMalformedInputError(error: unknown): boolean {
+  return typeof error === "string" && FALCON_MALFORMED_INPUT_ERRORS.some((prefix) => error.startsWith(prefix));
+}
+
+/**
+ * アルゴリズムの情報を取得（未対応のアルゴリズムの場合はエラー）
+ */
+function algorithmInfo(algorithm: SignatureAlgorithm): SignatureAlgorithmInfo {
+  const info = SIGNATURE_ALGORITHMS[algorithm];
+  if (!info) {
+    throw new Error(`Unsupported signature algorithm: ${algorithm}`);
+  }
+  return info;
+}
+
+/**
+ * 指定したアルゴリズムの鍵ペアを生成
+ *
+ * @param algorithm 署名アルゴリズム
+ * @returns 公開鍵と秘密鍵のペア
+ * @throws Error 未対応のアルゴリズムの場合
+ */
+export async function generateSignatureKeyPair(algorithm: SignatureAlgorithm): Promise<{
+  publicKey: Uint8Array;
+  privateKey: Uint8Array;
+}> {
+  switch (algorithm) {
+    case "ML-DSA-44":
+      return generateDilithiumKeyPair(44);
+    case "ML-DSA-65":
+      return generateDilithiumKeyPair(65);
+    case "ML-DSA-87":
+      return generateDilithiumKeyPair(87);
+    case "FALCON-512":
+      return generateFalconKeyPair(512);
+    case "FALCON-1024":
+      return generateFalconKeyPair(1024);
+    default:
+      throw new Error(`Unsupported signature algorithm: ${algorithm}`);
+  }
+}
+
+/**
+ * 指定したアルゴリズムでメッセージに署名
+ *
+ * @param algorithm 署名アルゴリズム
+ * @param message 署名するメッセージ
+ * @param privateKey 秘密鍵（algorithmの鍵）
+ * @returns 署名
+ * @throws Error 未対応のアルゴリズムの場合、秘密鍵のサイズがアルゴリズムと一致しない場合
+ */
+export async function signWithAlgorithm(
+  algorithm: SignatureAlgorithm,
+  message: Uint8Array,
+  privateKey: Uint8Array
+): Promise<Uint8Array> {
+  const info = algorithmInfo(algorithm);
+  if (privateKey.length !== info.privateKeySize) {
+    throw new Error(
+      `Invalid ${algorithm} private key size: expected ${info.privateKeySize}, got ${privateKey.length}`
+    );
+  }
+
+  return info.family === "ML-DSA" ? signDilithium(message, privateKey) : signFalcon(message, privateKey);
+}
+
+/**
+ * 指定したアルゴリズムで署名を検証
+ * 公開鍵・署名の形式が不正な場合は、アルゴリズムによらずfalseを返します。
+ *
+ * @param algorithm 署名アルゴリズム
+ * @param message 元のメッセージ
+ * @param signature 署名
+ * @param publicKey 公開鍵（algorithmの鍵）
+ * @returns 検証結果（true: 有効、false: 無効）
+ * @throws Error 未対応のアルゴリズムの場合、WebAssemblyモジュールを読み込めない場合
+ */
+export async function verifyWithAlgorithm(
+  algorithm: SignatureAlgorithm,
+  message: Uint8Array,
+  signature: Uint8Array,
+  publicKey: Uint8Array
+): Promise<boolean> {
+  const info = algorithmInfo(algorithm);
+  if (publicKey.length !== info.publicKeySize) {
+    return false;
+  }
+
+  if (info.family === "ML-DSA") {
+    return verifyDilithium(message, signature, publicKey);
+  }
+
+  // FALCONのverify_signatureは形式が不正な公開鍵・署名をエラーにするため、ML-DSAに合わせてfalseにする
+  // それ以外のエラー（モジュールの読み込みの失敗など）は無効な署名と区別できるようそのまま投げる
+  try {
+    return await verifyFalcon(message, signature, publicKey);
+  } catch (error) {
+    if (isFalconMalformedInputError(error)) {
+      return false;
+    }
+    throw error;
+  }
+}
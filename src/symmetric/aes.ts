@@ -136,6 +136,25 @@ export function decryptAES(
   }
 }
 
+/**
+ * 暗号化結果を復号し、同じ鍵・平文のまま新しいIVで暗号化し直します。
+ *
+ * IVを変えると暗号文が変わること（意図せず決定的な暗号化になっていないこと）と、
+ * どちらの暗号化結果も復号できることをテストで確認するためのヘルパーです。
+ *
+ * @param sealed - encryptAESの暗号化結果
+ * @param key - 暗号化に使用した鍵（32バイト）
+ * @returns 新しいランダムなIVで暗号化し直した結果
+ * @throws 鍵の長さが不正な場合、または認証に失敗した場合にエラーをスローします。
+ */
+export function cloneAESWithNewNonce(
+  sealed: AesEncryptionResult,
+  key: Uint8Array
+): AesEncryptionResult {
+  const plaintext = decryptAES(sealed.ciphertext, key, sealed.iv, sealed.authTag);
+  return encryptAES(plaintext, key);
+}
+
 /**
  * ランダムなAES鍵を生成します。
  *
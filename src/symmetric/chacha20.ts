@@ -142,6 +142,25 @@ export function decryptChaCha20(
   }
 }
 
+/**
+ * 暗号化結果を復号し、同じ鍵・平文のまま新しいノンスで暗号化し直します。
+ *
+ * ノンスを変えると暗号文が変わること（意図せず決定的な暗号化になっていないこと）と、
+ * どちらの暗号化結果も復号できることをテストで確認するためのヘルパーです。
+ *
+ * @param sealed - encryptChaCha20の暗号化結果
+ * @param key - 暗号化に使用した鍵（32バイト）
+ * @returns 新しいランダムなノンスで暗号化し直した結果
+ * @throws 鍵の長さが不正な場合、または認証に失敗した場合にエラーをスローします。
+ */
+export function cloneChaCha20WithNewNonce(
+  sealed: ChaCha20EncryptionResult,
+  key: Uint8Array
+): ChaCha20EncryptionResult {
+  const plaintext = decryptChaCha20(sealed.ciphertext, key, sealed.nonce, sealed.authTag);
+  return encryptChaCha20(plaintext, key);
+}
+
 /**
  * ランダムなChaCha20鍵を生成します。
  *
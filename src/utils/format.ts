@@ -16,6 +16,7 @@ export function bytesToHex(bytes: Uint8Array): string {
 
 /**
  * 16進数文字列をバイト配列に変換します。
+ * 空白は無視し、大文字・小文字のどちらも受け付けます。
  *
  * @param hex - 16進数文字列（例: "48656c6c6f"）
  * @returns バイト配列
@@ -28,13 +29,14 @@ export function hexToBytes(hex: string): Uint8Array {
   if (cleanHex.length % 2 !== 0) {
     throw new Error("Invalid hex string: length must be even");
   }
+  // parseIntは"0g"のように途中までしか16進数でない文字列も受け付けるため、文字を先に確認する
+  const invalid = cleanHex.search(/[^0-9a-fA-F]/);
+  if (invalid !== -1) {
+    throw new Error(`Invalid hex string: invalid character at position ${invalid}`);
+  }
   const bytes = new Uint8Array(cleanHex.length / 2);
   for (let i = 0; i < cleanHex.length; i += 2) {
-    const byte = Number.parseInt(cleanHex.substring(i, i + 2), 16);
-    if (Number.isNaN(byte)) {
-      throw new Error(`Invalid hex string: invalid character at position ${i}`);
-    }
-    bytes[i / 2] = byte;
+    bytes[i / 2] = Number.parseInt(cleanHex.substring(i, i + 2), 16);
   }
   return bytes;
 }
@@ -57,19 +59,30 @@ export function bytesToBase64(bytes: Uint8Array): string {
   return btoa(binary);
 }
 
+// 標準のアルファベット・パディング付きのBase64（bytesToBase64の出力形式）
+const BASE64_PATTERN = /^(?:[A-Za-z0-9+/]{4})*(?:[A-Za-z0-9+/]{2}==|[A-Za-z0-9+/]{3}=)?$/;
+
 /**
  * Base64文字列をバイト配列に変換します。
+ * 標準のアルファベット・パディング付きの形式のみを受け付けます（前後の空白は無視）。
  *
  * @param base64 - Base64文字列
  * @returns バイト配列
+ * @throws 無効なBase64文字列の場合にエラーをスローします。
  */
 export function base64ToBytes(base64: string): Uint8Array {
+  // BufferやatobはBase64以外の文字を読み飛ばすことがあるため、形式を先に確認する
+  const cleanBase64 = base64.trim();
+  if (!BASE64_PATTERN.test(cleanBase64)) {
+    throw new Error("Invalid base64 string: expected standard alphabet with padding");
+  }
+
   // Node.js環境ではBufferを使用、ブラウザ環境ではatobを使用
   if (typeof Buffer !== "undefined") {
-    return new Uint8Array(Buffer.from(base64, "base64"));
+    return new Uint8Array(Buffer.from(cleanBase64, "base64"));
   }
   // ブラウザ環境
-  const binary = atob(base64);
+  const binary = atob(cleanBase64);
   const bytes = new Uint8Array(binary.length);
   for (let i = 0; i < binary.length; i++) {
     bytes[i] = binary.charCodeAt(i);
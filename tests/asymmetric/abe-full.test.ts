@@ -0,0 +1,1933 @@
+/**
+ * CP-ABE（Ciphertext-Policy Attribute-Based Encryption）のテスト
+ *
+ * CP-ABEスキームの動作確認
+ * CP-ABEでは、鍵生成時に属性セットを指定し、
+ * 暗号化時にポリシー（属性リスト）を指定します。
+ */
+
+import { beforeAll, describe, expect, it } from "vitest";
+import {
+  checkABEPolicy,
+  decryptABE,
+  decryptABEAt,
+  decryptABEFromString,
+  decryptABEHybrid,
+  decryptKPABE,
+  deriveABEPublicParams,
+  describeABEKey,
+  encryptABE,
+  encryptABEBound,
+  encryptABEHybrid,
+  encryptABEThreshold,
+  encryptABEToString,
+  encryptABEWithHint,
+  encryptKPABE,
+  extractABEKey,
+  extractABEKeyWithExpiry,
+  extractKPABEKey,
+  generateABEKeyPair,
+  getABEAttributeCacheHits,
+  getABEKeyId,
+  getABEPairingCount,
+  getABERandomBytes,
+  importABEMasterKey,
+  importABEMasterKeyFromString,
+  importABEPrivateKey,
+  importABEPrivateKeyFromString,
+  importABEPublicParams,
+  importABEPublicParamsFromString,
+  initABE,
+  proveABEAttribute,
+  setABEEventCallback,
+  signABEPublicParams,
+  tryDecryptABE,
+  tryDecryptABEHybrid,
+  upgradeABECiphertext,
+  verifyABEAttributeProof,
+  verifyABEPublicParams,
+  type ABEPrivateKey,
+  type ABESecurityEvent,
+} from "../../src/asymmetric/abe.js";
+import { generateDilithiumKeyPair, verifyDilithium } from "../../src/post-quantum/dilithium.js";
+import { generateKyberKeyPair } from "../../src/post-quantum/kyber.js";
+import { bytesToHex, hexToBytes } from "../../src/utils/format.js";
+
+// 旧方式（文脈なしの鍵導出）で作成した旧形式の暗号文のテストベクタ
+// マスター秘密鍵は0x11の32バイト、暗号化時の属性は["A", "B"]
+const LEGACY_MASTER_KEY = hexToBytes("1111111111111111111111111111111111111111111111111111111111111111");
+const LEGACY_PUBLIC_PARAMS = hexToBytes(
+  "04087e5868e0460f8562cd4e4043bb41a7fb4615b9aa95de00f5c6ec1afe5bd8" +
+  "e904397124bf97bb30768a43bfe3e30e8b54e1e3630c0721758dd362befff366" +
+  "c0"
+);
+const LEGACY_CP_CIPHERTEXT = hexToBytes(
+  "0204105c843b1bba52aa95bcd53e56da8d139650dde567396fd18b309c30b225" +
+  "9cc503db1b061f9f7f4fe12da2e0f6a83cd5c2e1f1be62536b66891ec1aa2bbc" +
+  "a1b22b301e758d0998de97681999afa794accdb404189761c29c610cd1095616" +
+  "8559050976a1a080a4cda7afd0c426ff4f8ee6a20e12185528b580d0d68fa9fe" +
+  "9dda36381a4917fbb8037d04c2978c28a03f1eaa8a10568c98fa5ef01d4b17d3" +
+  "f4c605bf329121544a108a6d612612854b7abed7db19ec020def9fdb1d733d0c" +
+  "c65661757408e3d4a31c39828868fd150f4a8ed88a000417c17a7b52a153778e" +
+  "fc623e30255c3a5418b44302997d49357305610bb95fdb0b49184a00f84eb1a4" +
+  "8b292fb921cf27ab12af0101f82ce743d091c33e5db7b908b2105ea22e1066e4" +
+  "ada1dc73466abd6d0cada69b7bc10b874dd23f2b0530510849f6efe65e34eb0f" +
+  "4c0924d5ce323fe36319d3ff67c8ee78539d5e6222adde00"
+);
+
+// 旧方式の属性ハッシュ（バージョン1）で上のマスター秘密鍵から生成した属性["A", "B"]の秘密鍵
+// 旧形式の暗号文はこの鍵でのみ復号・移行できる
+const LEGACY_CP_PRIVATE_KEY = hexToBytes(
+  "01010401b8cd0efe944e12ec592c2021ee3cda5d4e6c2618d9c1bab53984c9c5" +
+  "b534e82047d2cc444a28df03c89278a42a0ce4b08fb398365e41ca8c7756da39" +
+  "24692505c66a3d755e4dd2cfe3ad67fb5c073358b49bd57a354ad0d9c6bcde1a" +
+  "ddf9ec19e28d563e8fd59417d510fdcef5c9e8a94d21607b460f6113e8fc7331" +
+  "be9a8b0004070b82710ad64888e64162ea5e830943bee54ed9ba83a1549cca3d" +
+  "38f7b6397d153c2e64fccd6f4b94ad796ef122dee14f5aee54e51009f00c3e0a" +
+  "92178dbcb70596a474cdbffb2bd3119619f43a691aa3d0e6884de8c7cd277499" +
+  "6ed955714f17dc9bf1c6500662f01f4e1112314d14dca4d3d3ab459578d1867d" +
+  "fd9e4e2de200"
+);
+
+describe("CP-ABE Full Implementation", () => {
+  beforeAll(async () => {
+    // WebAssemblyモジュールを初期化
+    await initABE();
+  });
+
+  it("should return a hint matching the policy leaves", async () => {
+    const { masterKey, publicParams } = await generateABEKeyPair();
+    const policy = "A, B,C";
+    const message = new TextEncoder().encode("Hello, CP-ABE!");
+
+    const { ciphertext, requiredAttributes } = await encryptABEWithHint(
+      publicParams,
+      policy,
+      message
+    );
+
+    // ヒントはポリシーの属性と一致する
+    expect(requiredAttributes).toEqual(["A", "B", "C"]);
+
+    // ヒントの属性を持つ鍵で復号できる
+    const privateKey = await extractABEKey(masterKey, requiredAttributes);
+    const decrypted = await decryptABE(privateKey, ciphertext);
+    expect(new TextDecoder().decode(decrypted)).toBe("Hello, CP-ABE!");
+  });
+
+  it("should not repeat duplicated attributes in the hint", async () => {
+    const { publicParams } = await generateABEKeyPair();
+    const message = new TextEncoder().encode("Hello, CP-ABE!");
+
+    const { requiredAttributes } = await encryptABEWithHint(publicParams, "A,B,A", message);
+
+    expect(requiredAttributes).toEqual(["A", "B"]);
+  });
+
+  it("should keep the attribute cache consistent under concurrent operations", async () => {
+    const { masterKey, publicParams } = await generateABEKeyPair();
+
+    // 同じ属性・異なる属性の鍵生成と暗号化を並行して実行する
+    const jobs = Array.from({ length: 8 }, async (_, i) => {
+      const attributes = [`shared`, `user${i % 3}`];
+      const message = new TextEncoder().encode(`message ${i}`);
+
+      const [privateKey, ciphertext] = await Promise.all([
+        extractABEKey(masterKey, attributes),
+        encryptABE(publicParams, attributes.join(","), message),
+      ]);
+      const decrypted = await decryptABE(privateKey, ciphertext);
+
+      return new TextDecoder().decode(decrypted);
+    });
+
+    const results = await Promise.all(jobs);
+    expect(results).toEqual(Array.from({ length: 8 }, (_, i) => `message ${i}`));
+  });
+
+  it("should decrypt identically with a compacted-then-decompressed key", async () => {
+    const { masterKey, publicParams } = await generateABEKeyPair();
+    const attributes = ["A", "B", "C"];
+    const message = new TextEncoder().encode("Hello, compact key!");
+
+    const privateKey = await extractABEKey(masterKey, attributes);
+    const ciphertext = await encryptABE(publicParams, attributes.join(","), message);
+
+    // 圧縮形式はおよそ半分のサイズになる
+    const compacted = privateKey.compact();
+    expect(compacted.is_compact).toBe(true);
+    expect(compacted.size_bytes()).toBeLessThan(privateKey.size_bytes() / 2 + 2);
+
+    // 圧縮形式のままでは復号できない
+    await expect(decryptABE(compacted, ciphertext)).rejects.toThrow();
+
+    // 展開した鍵は元の鍵と同じで、同じ結果に復号できる
+    const restored = compacted.decompress();
+    expect(restored.key).toEqual(privateKey.key);
+    expect(restored.attributes).toEqual(privateKey.attributes);
+    expect(await decryptABE(restored, ciphertext)).toEqual(await decryptABE(privateKey, ciphertext));
+  });
+
+  it("should upgrade a legacy ciphertext to the current format", async () => {
+    const masterKey = await importABEMasterKey(LEGACY_MASTER_KEY);
+    const publicParams = await importABEPublicParams(LEGACY_PUBLIC_PARAMS);
+    const legacyKey = await importABEPrivateKey(LEGACY_CP_PRIVATE_KEY, ["A", "B"]);
+    const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+
+    // 旧形式のままでは現在の復号処理で扱えない
+    await expect(decryptABE(privateKey, LEGACY_CP_CIPHERTEXT)).rejects.toThrow();
+
+    // 旧方式の属性ハッシュの鍵で復号し、現在の属性ハッシュで暗号化し直す
+    const upgraded = await upgradeABECiphertext(LEGACY_CP_CIPHERTEXT, legacyKey, publicParams);
+    expect(upgraded[1]).toBe(privateKey.key[1]);
+    await expect(decryptABE(legacyKey, upgraded)).rejects.toThrow(/属性ハッシュのバージョンが一致しません/);
+    const decrypted = await decryptABE(privateKey, upgraded);
+    expect(new TextDecoder().decode(decrypted)).toBe("Legacy CP-ABE data");
+
+    // 現在の形式の暗号文はそのまま返る
+    expect(await upgradeABECiphertext(upgraded, privateKey, publicParams)).toEqual(upgraded);
+  });
+
+  it("should bind the key derivation to the time limit", async () => {
+    const { masterKey, publicParams } = await generateABEKeyPair();
+    const privateKey = await extractABEKey(masterKey, ["A", "valid_until:50"]);
+    const message = new TextEncoder().encode("Time-limited data");
+
+    const ciphertext = await encryptABE(publicParams, "A,valid_until:100", message);
+    expect(await decryptABEAt(privateKey, ciphertext, 10n)).toEqual(message);
+
+    // valid_until（ヘッダ・属性数の後の8バイト）を延長すると、時間制限属性のコンポーネントと一致しなくなり復号できない
+    const tampered = new Uint8Array(ciphertext);
+    tampered[10] += 1;
+    await expect(decryptABEAt(privateKey, tampered, 10n)).rejects.toThrow(/ポリシーを満たしていません/);
+  });
+
+  it("should reject a time-limited key that lacks one of the other attributes", async () => {
+    const { masterKey, publicParams } = await generateABEKeyPair();
+    const message = new TextEncoder().encode("Time-limited data");
+    const ciphertext = await encryptABE(publicParams, "A,B,valid_until:100", message);
+
+    // 時間制限は満たしていても、Bを持たない鍵では復号できない
+    const missingB = await extractABEKey(masterKey, ["A", "C", "valid_until:50"]);
+    await expect(decryptABEAt(missingB, ciphertext, 10n)).rejects.toThrow(/ポリシーを満たしていません/);
+
+    const holdsAll = await extractABEKey(masterKey, ["A", "B", "valid_until:50"]);
+    expect(await decryptABEAt(holdsAll, ciphertext, 10n)).toEqual(message);
+  });
+
+  it("should not decrypt when the time limit is stripped", async () => {
+    const { masterKey, publicParams } = await generateABEKeyPair();
+    const privateKey = await extractABEKey(masterKey, ["A", "valid_until:100"]);
+    const message = new TextEncoder().encode("Time-limited data");
+
+    // valid_untilを0（時間制限なし）にすると時刻の確認は通るが、鍵の導出文脈が変わり復号できない
+    const ciphertext = await encryptABE(publicParams, "A,valid_until:100", message);
+    const stripped = new Uint8Array(ciphertext);
+    stripped.fill(0, 3, 3 + 8);
+    await expect(decryptABE(privateKey, stripped)).rejects.toThrow(/認証タグが一致しません/);
+
+    // 発行元に束縛した暗号文（header (2バイト) || 発行元ID (32バイト) || num_attrs (1バイト) || valid_until (8バイト) || ...）も同様
+    const bound = await encryptABEBound(publicParams, "A,valid_until:100", message);
+    expect(await decryptABEAt(privateKey, bound, 10n)).toEqual(message);
+    const strippedBound = new Uint8Array(bound);
+    strippedBound.fill(0, 35, 35 + 8);
+    await expect(decryptABE(privateKey, strippedBound)).rejects.toThrow(/認証タグが一致しません/);
+  });
+
+  it("should round-trip through the base64url string form", async () => {
+    const { masterKey, publicParams } = await generateABEKeyPair();
+    const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+    const message = new TextEncoder().encode("Ciphertext in a QR code");
+
+    const encoded = await encryptABEToString(publicParams, "A,B", message);
+    // URLにそのまま埋め込める文字だけで構成され、パディングを含まない
+    expect(encoded).toMatch(/^[A-Za-z0-9_-]+$/);
+    expect(await decryptABEFromString(privateKey, encoded)).toEqual(message);
+
+    // 改行・空白やパディングが付いていても復号できる
+    const wrapped = `  ${encoded.slice(0, 40)}\n${encoded.slice(40)}==\n`;
+    expect(await decryptABEFromString(privateKey, wrapped)).toEqual(message);
+
+    await expect(decryptABEFromString(privateKey, "not+base64/url")).rejects.toThrow();
+  });
+
+  it("should decrypt a time-limited string ciphertext with the current time", async () => {
+    const { masterKey, publicParams } = await generateABEKeyPair();
+    const privateKey = await extractABEKey(masterKey, ["A", "valid_until:50"]);
+    const message = new TextEncoder().encode("Time-limited data");
+
+    const encoded = await encryptABEToString(publicParams, "A,valid_until:100", message);
+
+    await expect(decryptABEFromString(privateKey, encoded)).rejects.toThrow();
+    expect(await decryptABEFromString(privateKey, encoded, 10n)).toEqual(message);
+  });
+
+  describe("pairing-free pre-screening", () => {
+    it("should reject a non-matching key without computing any pairing", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const matchingKey = await extractABEKey(masterKey, ["A", "B"]);
+      const wrongKey = await extractABEKey(masterKey, ["A"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", new TextEncoder().encode("data"));
+
+      // 属性数が一致しない鍵は、ペアリングを行わずに拒否される
+      const before = await getABEPairingCount();
+      await expect(decryptABE(wrongKey, ciphertext)).rejects.toThrow();
+      expect(await getABEPairingCount()).toBe(before);
+
+      // 一致する鍵は属性名で照合し、ペアリングを1回行って復号する
+      await decryptABE(matchingKey, ciphertext);
+      expect(await getABEPairingCount()).toBe(before + 1n);
+    });
+
+    it("should reject an expired attribute before verifying its expiry tag", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKeyWithExpiry(masterKey, ["A", "B"], [100n, 0n]);
+      const ciphertext = await encryptABE(publicParams, "A,B", new TextEncoder().encode("data"));
+
+      const before = await getABEPairingCount();
+      await expect(decryptABEAt(privateKey, ciphertext, 500n)).rejects.toThrow(/有効期限/);
+      expect(await getABEPairingCount()).toBe(before);
+    });
+  });
+
+  describe("attribute hash cache", () => {
+    it("should hash an attribute to the same point with and without the cache", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      // 他のテストでキャッシュされていない属性名を使う
+      const attributes = [`cache-${crypto.randomUUID()}`, `cache-${crypto.randomUUID()}`];
+
+      // 初回はキャッシュにないためハッシュを計算し、2回目はキャッシュから取得する
+      const first = await getABEAttributeCacheHits();
+      const uncachedKey = await extractABEKey(masterKey, attributes);
+      const second = await getABEAttributeCacheHits();
+      const cachedKey = await extractABEKey(masterKey, attributes);
+      const third = await getABEAttributeCacheHits();
+      expect(third - second - (second - first)).toBe(BigInt(attributes.length));
+
+      // 鍵生成は決定的なため、キャッシュの有無で同じ鍵になる
+      expect(cachedKey.key).toEqual(uncachedKey.key);
+
+      // キャッシュした点で暗号化した暗号文を、キャッシュ前の点で生成した鍵で復号できる
+      const message = new TextEncoder().encode("cached attribute");
+      const ciphertext = await encryptABE(publicParams, attributes.join(","), message);
+      expect(await decryptABE(uncachedKey, ciphertext)).toEqual(message);
+    });
+
+    it("should skip hashing for attributes that were already used", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      const attributes = [`cache-${crypto.randomUUID()}`, `cache-${crypto.randomUUID()}`, `cache-${crypto.randomUUID()}`];
+      const message = new TextEncoder().encode("data");
+
+      await encryptABE(publicParams, attributes.join(","), message);
+      const before = await getABEAttributeCacheHits();
+      await encryptABE(publicParams, attributes.join(","), message);
+      const repeated = (await getABEAttributeCacheHits()) - before;
+
+      // 2回目の暗号化では、すべての属性のハッシュがキャッシュから取得される
+      expect(repeated).toBeGreaterThanOrEqual(BigInt(attributes.length));
+    });
+  });
+
+  describe("attribute matching", () => {
+    it("should reject a key whose attribute names differ even if the counts match", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABE(publicParams, "A,B", new TextEncoder().encode("data"));
+
+      // 属性数は同じでも、暗号文の属性を持たない鍵は属性数の不一致とは別のエラーになる
+      for (const attributes of [["A", "C"], ["C", "B"], ["C", "D"]]) {
+        const privateKey = await extractABEKey(masterKey, attributes);
+        const rejected = decryptABE(privateKey, ciphertext);
+        await expect(rejected).rejects.toThrow(/ポリシーを満たしていません/);
+        await expect(rejected).rejects.not.toThrow(/属性が一致しません/);
+      }
+    });
+
+    it("should decrypt a 2-attribute ciphertext with a 5-attribute key", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const message = new TextEncoder().encode("Subset policy");
+      const ciphertext = await encryptABE(publicParams, "engineering,tokyo", message);
+
+      // 暗号文が必要としない属性を持っていても、必要な属性の鍵コンポーネントだけを使って復号できる
+      const privateKey = await extractABEKey(masterKey, ["admin", "tokyo", "sales", "engineering", "osaka"]);
+      const before = await getABEPairingCount();
+      expect(await decryptABE(privateKey, ciphertext)).toEqual(message);
+      expect(await getABEPairingCount()).toBe(before + 1n);
+
+      // 必要な属性が1つでも欠けていれば復号できない
+      const partialKey = await extractABEKey(masterKey, ["admin", "tokyo", "sales", "osaka", "nagoya"]);
+      await expect(decryptABE(partialKey, ciphertext)).rejects.toThrow(/ポリシーを満たしていません.*engineering/);
+    });
+
+    it("should store the attribute name with each ciphertext component", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABE(publicParams, "A,BC", new TextEncoder().encode("data"));
+
+      // header (2バイト) || num_attrs (1バイト) || valid_until (8バイト) || C0 (65バイト) || (len(attr) || attr || C_attr)...
+      expect(ciphertext[0]).toBe(0x06 | 0x20);
+      expect(ciphertext[2]).toBe(2);
+      const first = 2 + 1 + 8 + 65;
+      expect(ciphertext[first]).toBe(1);
+      expect(new TextDecoder().decode(ciphertext.slice(first + 1, first + 2))).toBe("A");
+      const second = first + 1 + 1 + 130;
+      expect(ciphertext[second]).toBe(2);
+      expect(new TextDecoder().decode(ciphertext.slice(second + 1, second + 3))).toBe("BC");
+    });
+
+    it("should still decrypt bound ciphertexts, which carry no attribute names, with a larger key", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const message = new TextEncoder().encode("Bound subset policy");
+      const ciphertext = await encryptABEBound(publicParams, "A,B", message);
+
+      const privateKey = await extractABEKey(masterKey, ["C", "B", "D", "A"]);
+      expect(await decryptABE(privateKey, ciphertext)).toEqual(message);
+    });
+
+    it("should decrypt with a key that holds the same attributes in a different order", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const message = new TextEncoder().encode("Reordered attributes");
+      const ciphertext = await encryptABE(publicParams, "A,B,C", message);
+
+      const privateKey = await extractABEKey(masterKey, ["C", "A", "B"]);
+      expect(await decryptABE(privateKey, ciphertext)).toEqual(message);
+    });
+  });
+
+  describe("attribute hash-to-curve", () => {
+    // マスター秘密鍵が1の場合、鍵コンポーネント αH(attr) は写像した点 H(attr) そのものになる
+    const ONE = hexToBytes("0000000000000000000000000000000000000000000000000000000000000001");
+    // ヘッダ（2バイト）の後に、非圧縮形式のG2の点（130バイト）が属性の順に並ぶ
+    const component = (key: Uint8Array, index: number) => key.slice(2 + 130 * index, 2 + 130 * (index + 1));
+
+    it("should map the same attribute to the same point", async () => {
+      const masterKey = await importABEMasterKey(ONE);
+
+      const first = await extractABEKey(masterKey, ["admin", "tokyo"]);
+      const second = await extractABEKey(masterKey, ["tokyo", "admin"]);
+
+      expect(component(first.key, 0)).toEqual(component(second.key, 1));
+      expect(component(first.key, 1)).toEqual(component(second.key, 0));
+    });
+
+    it("should map different attributes to distinct points", async () => {
+      const masterKey = await importABEMasterKey(ONE);
+      const attributes = ["admin", "Admin", "admin2", "tokyo"];
+
+      const privateKey = await extractABEKey(masterKey, attributes);
+      const points = attributes.map((_, i) => bytesToHex(component(privateKey.key, i)));
+
+      expect(new Set(points).size).toBe(attributes.length);
+    });
+
+    it("should record the RFC 9380 attribute hash version in keys and ciphertexts", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+
+      const privateKey = await extractABEKey(masterKey, ["A"]);
+      const ciphertext = await encryptABE(publicParams, "A", new TextEncoder().encode("Hello"));
+
+      expect(privateKey.key[1]).toBe(5);
+      expect(ciphertext[1]).toBe(5);
+    });
+
+    it("should differ from the legacy hash, which exposed the discrete log of H(attr)", async () => {
+      const masterKey = await importABEMasterKey(LEGACY_MASTER_KEY);
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+
+      expect(LEGACY_CP_PRIVATE_KEY[1]).toBe(1);
+      expect(component(privateKey.key, 0)).not.toEqual(component(LEGACY_CP_PRIVATE_KEY, 0));
+      expect(component(privateKey.key, 1)).not.toEqual(component(LEGACY_CP_PRIVATE_KEY, 1));
+    });
+  });
+
+  describe("decrypt error codes", () => {
+    const message = new TextEncoder().encode("data");
+
+    // 復号に失敗したErrorのcodeプロパティを取り出す
+    const errorCode = async (decrypting: Promise<unknown>): Promise<unknown> => {
+      const error = await decrypting.then(
+        () => undefined,
+        (error: unknown) => error
+      );
+      expect(error).toBeInstanceOf(Error);
+      return (error as { code?: unknown }).code;
+    };
+
+    it("should report COUNT_MISMATCH for a key with fewer attributes than the ciphertext", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      expect(await errorCode(decryptABE(privateKey, ciphertext))).toBe("COUNT_MISMATCH");
+    });
+
+    it("should report POLICY_NOT_SATISFIED for a key whose attributes do not match", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "C"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      expect(await errorCode(decryptABE(privateKey, ciphertext))).toBe("POLICY_NOT_SATISFIED");
+    });
+
+    it("should report CORRUPT for a tampered or truncated ciphertext", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      // header (2バイト) || num_attrs (1バイト) || valid_until (8バイト) || C0 (65バイト) || V || ...
+      const tampered = new Uint8Array(ciphertext);
+      tampered[2 + 1 + 8 + 65 + 12] ^= 0x01;
+      expect(await errorCode(decryptABE(privateKey, tampered))).toBe("CORRUPT");
+      expect(await errorCode(decryptABE(privateKey, ciphertext.slice(0, 20)))).toBe("CORRUPT");
+    });
+
+    it("should report INCOMPATIBLE_FORMAT for a ciphertext of another scheme", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const kpCiphertext = await encryptKPABE(publicParams, ["A", "B"], message);
+
+      expect(await errorCode(decryptABE(privateKey, kpCiphertext))).toBe("INCOMPATIBLE_FORMAT");
+    });
+
+    it("should report AUTHORITY_MISMATCH for a bound ciphertext from another authority", async () => {
+      const authorityA = await generateABEKeyPair();
+      const authorityB = await generateABEKeyPair();
+      const keyFromB = await extractABEKey(authorityB.masterKey, ["A", "B"]);
+      const ciphertext = await encryptABEBound(authorityA.publicParams, "A,B", message);
+
+      expect(await errorCode(decryptABE(keyFromB, ciphertext))).toBe("AUTHORITY_MISMATCH");
+    });
+
+    describe("crafted length fields", () => {
+      // 属性数と実際の長さが矛盾する暗号文を組み立てる（header (2バイト) || num_attrs (1バイト) || rest）
+      const craft = (tag: number, version: number, numAttrs: number, rest: Uint8Array): Uint8Array =>
+        new Uint8Array([tag, version, numAttrs, ...rest]);
+
+      it("should report CORRUPT when the attribute count exceeds the ciphertext length", async () => {
+        const { masterKey, publicParams } = await generateABEKeyPair();
+        const attributes = ["A", "B", "C", "D", "E"];
+        const privateKey = await extractABEKey(masterKey, attributes);
+        const ciphertext = await encryptABE(publicParams, "A,B", message);
+        const validUntilAndC0 = ciphertext.slice(3, 3 + 8 + 65);
+
+        // 属性を固定長で並べた形式（0x01 | GCM）: 5属性分（650バイト）の属性コンポーネントがない
+        const unlabeled = craft(0x21, ciphertext[1], attributes.length, new Uint8Array([...validUntilAndC0, 1, 2, 3]));
+        expect(await errorCode(decryptABE(privateKey, unlabeled))).toBe("CORRUPT");
+
+        // 属性名付きの形式: 属性数は3だが、コンポーネントは1つだけ
+        const labeled = craft(ciphertext[0], ciphertext[1], 3, ciphertext.slice(3, 3 + 8 + 65 + 1 + 1 + 130));
+        expect(await errorCode(decryptABE(privateKey, labeled))).toBe("CORRUPT");
+
+        // 属性名の長さ（255バイト）が残りの長さを超える
+        const longName = new Uint8Array(ciphertext);
+        longName[2 + 1 + 8 + 65] = 0xff;
+        expect(await errorCode(decryptABE(privateKey, longName.slice(0, 200)))).toBe("CORRUPT");
+      });
+
+      it("should report CORRUPT when the components leave no room for V", async () => {
+        const { masterKey, publicParams } = await generateABEKeyPair();
+        const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+        const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+        // 長さはちょうど属性コンポーネント2つ分で、Vの長さが0になる（ciphertext.len() - attrs_size == C0の終端）
+        const noV = craft(0x21, ciphertext[1], 2, new Uint8Array([...ciphertext.slice(3, 3 + 8 + 65), ...new Uint8Array(2 * 130)]));
+        expect(await errorCode(decryptABE(privateKey, noV))).toBe("CORRUPT");
+
+        // 属性数が0の暗号文
+        expect(await errorCode(decryptABE(privateKey, craft(0x21, ciphertext[1], 0, ciphertext.slice(3))))).toBe("CORRUPT");
+      });
+
+      it("should report CORRUPT for crafted KP-ABE lengths", async () => {
+        const { masterKey, publicParams } = await generateABEKeyPair();
+        const privateKey = await extractKPABEKey(masterKey, "A,B");
+        const ciphertext = await encryptKPABE(publicParams, ["A", "B"], message);
+        const c0 = ciphertext.slice(3, 3 + 65);
+
+        // KP-ABE: header (2バイト) || num_attrs (1バイト) || C0 (65バイト) || V || C_attrs
+        const truncated = craft(ciphertext[0], ciphertext[1], 2, new Uint8Array([...c0, 1, 2, 3]));
+        expect(await errorCode(decryptKPABE(privateKey, truncated))).toBe("CORRUPT");
+        const noV = craft(ciphertext[0], ciphertext[1], 2, new Uint8Array([...c0, ...new Uint8Array(2 * 130)]));
+        expect(await errorCode(decryptKPABE(privateKey, noV))).toBe("CORRUPT");
+      });
+    });
+  });
+
+  describe("attribute possession proof", () => {
+    it("should verify a proof from a key holding the attribute", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["engineering", "tokyo"]);
+      const challenge = crypto.getRandomValues(new Uint8Array(32));
+
+      const proof = await proveABEAttribute(privateKey, "tokyo", challenge);
+      expect(proof.length).toBe(384 + 130);
+      expect(await verifyABEAttributeProof(publicParams, "tokyo", challenge, proof)).toBe(true);
+
+      // 証明は属性とchallengeに束縛されている
+      expect(await verifyABEAttributeProof(publicParams, "engineering", challenge, proof)).toBe(false);
+      const otherChallenge = crypto.getRandomValues(new Uint8Array(32));
+      expect(await verifyABEAttributeProof(publicParams, "tokyo", otherChallenge, proof)).toBe(false);
+    });
+
+    it("should reject forgeries from keys that do not hold the attribute", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const challenge = crypto.getRandomValues(new Uint8Array(32));
+
+      // 属性を持たない鍵では証明を生成できない
+      const nonHolder = await extractABEKey(masterKey, ["sales"]);
+      await expect(proveABEAttribute(nonHolder, "tokyo", challenge)).rejects.toThrow(/属性「tokyo」が含まれていません/);
+
+      // 別の属性の鍵コンポーネントを、属性名を偽って差し出しても検証に失敗する
+      const relabeled = await importABEPrivateKey(nonHolder.key, ["tokyo"]);
+      const relabeledProof = await proveABEAttribute(relabeled, "tokyo", challenge);
+      expect(await verifyABEAttributeProof(publicParams, "tokyo", challenge, relabeledProof)).toBe(false);
+
+      // 別の権限者が発行した同じ属性の鍵による証明も受け付けない
+      const other = await generateABEKeyPair();
+      const otherKey = await extractABEKey(other.masterKey, ["tokyo"]);
+      const otherProof = await proveABEAttribute(otherKey, "tokyo", challenge);
+      expect(await verifyABEAttributeProof(publicParams, "tokyo", challenge, otherProof)).toBe(false);
+
+      // 形式が不正な証明はエラーにならずfalseになる
+      expect(await verifyABEAttributeProof(publicParams, "tokyo", challenge, new Uint8Array(10))).toBe(false);
+      const garbage = crypto.getRandomValues(new Uint8Array(384 + 130));
+      expect(await verifyABEAttributeProof(publicParams, "tokyo", challenge, garbage)).toBe(false);
+    });
+  });
+
+  describe("security events", () => {
+    const message = new TextEncoder().encode("top secret plaintext");
+
+    // コールバックを登録してrunを実行し、通知されたイベントを返す
+    const recordEvents = async (run: () => Promise<unknown>): Promise<ABESecurityEvent[]> => {
+      const events: ABESecurityEvent[] = [];
+      await setABEEventCallback((event) => events.push({ event: event.event, detail: event.detail }));
+      try {
+        await run().catch(() => undefined);
+      } finally {
+        await setABEEventCallback(undefined);
+      }
+      return events;
+    };
+
+    it("should emit a single decrypt_failed with a reason and no secret bytes", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "C"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      const events = await recordEvents(() => decryptABE(privateKey, ciphertext));
+      expect(events.map((event) => event.event)).toEqual(["decrypt_failed"]);
+      expect(events[0].detail).toMatch(/^POLICY_NOT_SATISFIED: ポリシーを満たしていません/);
+
+      // 鍵コンポーネント・暗号文・平文のバイト列はhexでも文字列でも含まれない
+      const detail = events[0].detail.toLowerCase();
+      expect(detail).not.toContain(bytesToHex(privateKey.key.slice(2, 34)));
+      expect(detail).not.toContain(bytesToHex(ciphertext.slice(11, 43)));
+      expect(detail).not.toContain(new TextDecoder().decode(message));
+    });
+
+    it("should emit a single decrypt_failed for a failed authentication tag", async () => {
+      const other = await generateABEKeyPair();
+      const { publicParams } = await generateABEKeyPair();
+      const wrongKey = await extractABEKey(other.masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      const events = await recordEvents(() => decryptABE(wrongKey, ciphertext));
+      expect(events.map((event) => event.event)).toEqual(["decrypt_failed"]);
+      expect(events[0].detail).toMatch(/^CORRUPT: /);
+      expect(events[0].detail).not.toContain(new TextDecoder().decode(message));
+    });
+
+    it("should emit only point_rejected when a point fails validation", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      const tampered = new Uint8Array(ciphertext);
+      tampered.fill(0x5a, 2 + 1 + 8 + 1, 2 + 1 + 8 + 65);
+      const events = await recordEvents(() => decryptABE(privateKey, tampered));
+      expect(events).toEqual([{ event: "point_rejected", detail: "G1の点が不正です: 曲線上の点でないか、無限遠点です" }]);
+    });
+
+    it("should not emit events for trial decryption or after the callback is removed", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "C"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      expect(await recordEvents(() => tryDecryptABE(privateKey, ciphertext))).toEqual([]);
+
+      const events: ABESecurityEvent[] = [];
+      await setABEEventCallback((event) => events.push(event));
+      await setABEEventCallback(undefined);
+      await expect(decryptABE(privateKey, ciphertext)).rejects.toThrow();
+      expect(events).toEqual([]);
+    });
+  });
+
+  describe("scheme check", () => {
+    it("should reject a KP-ABE ciphertext as the wrong scheme", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const kpCiphertext = await encryptKPABE(publicParams, ["A", "B"], new TextEncoder().encode("data"));
+
+      // 属性が一致していても、KP-ABEの暗号文はCP-ABEの復号に渡すとスキームの不一致として拒否される
+      await expect(decryptABE(privateKey, kpCiphertext)).rejects.toThrow(/wrong scheme/);
+      await expect(decryptABE(privateKey, kpCiphertext)).rejects.toThrow(/KP-ABE用ですが、CP-ABEで復号/);
+    });
+  });
+
+  describe("attribute count boundaries", () => {
+    // 暗号文の属性数は1バイトで保存されるため、255個が上限
+    const maxAttributes = Array.from({ length: 255 }, (_, i) => `attr${i}`);
+
+    it("should round-trip a ciphertext with exactly 255 attributes", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, maxAttributes);
+      const message = new TextEncoder().encode("Maximum attributes");
+
+      const ciphertext = await encryptABE(publicParams, maxAttributes.join(","), message);
+      // ヘッダ（2バイト）の後の属性数
+      expect(ciphertext[2]).toBe(255);
+      expect(await decryptABE(privateKey, ciphertext)).toEqual(message);
+    }, 60_000);
+
+    it("should refuse to encrypt with 256 attributes", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      const tooMany = [...maxAttributes, "attr255"];
+
+      await expect(
+        encryptABE(publicParams, tooMany.join(","), new TextEncoder().encode("data"))
+      ).rejects.toThrow(/属性が多すぎます/);
+    });
+
+    it("should reject a ciphertext whose attribute count is zero", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, []);
+      const ciphertext = await encryptABE(publicParams, "A", new TextEncoder().encode("data"));
+
+      // 属性のない鍵と属性数を0に書き換えた暗号文の組み合わせでも、Vをそのまま返さない
+      const tampered = new Uint8Array(ciphertext);
+      tampered[2] = 0;
+      await expect(decryptABE(privateKey, tampered)).rejects.toThrow(/属性数が0/);
+    });
+  });
+
+  describe("key inspection", () => {
+    it("should describe each attribute with a fingerprint of its key component", async () => {
+      const { masterKey } = await generateABEKeyPair();
+      const attributes = ["admin", "engineering", "tokyo"];
+      const privateKey = await extractABEKey(masterKey, attributes);
+
+      const described = await describeABEKey(privateKey);
+      expect(described.map((entry) => entry.attribute)).toEqual(attributes);
+      for (const entry of described) {
+        expect(entry.fingerprint).toMatch(/^[0-9a-f]{16}$/);
+        expect(entry.valid).toBe(true);
+      }
+      expect(new Set(described.map((entry) => entry.fingerprint)).size).toBe(attributes.length);
+
+      // 圧縮形式の鍵でもフィンガープリントは変わらない
+      expect(await describeABEKey(privateKey.compact())).toEqual(described);
+    });
+
+    it("should change only the fingerprint of a corrupted component", async () => {
+      const masterKey = await importABEMasterKey(LEGACY_MASTER_KEY);
+      const privateKey = await extractABEKey(masterKey, ["A", "B", "C"]);
+      const described = await describeABEKey(privateKey);
+
+      // ヘッダ（2バイト）の後に、非圧縮形式のG2の点（130バイト）が属性の順に並ぶ
+      const corruptedKey = new Uint8Array(privateKey.key);
+      corruptedKey[2 + 130 + 40] ^= 0x01;
+      const corrupted = await describeABEKey(
+        await importABEPrivateKey(corruptedKey, ["A", "B", "C"])
+      );
+
+      expect(corrupted[0]).toEqual(described[0]);
+      expect(corrupted[1].fingerprint).not.toBe(described[1].fingerprint);
+      expect(corrupted[1].valid).toBe(false);
+      expect(corrupted[2]).toEqual(described[2]);
+    });
+
+    it("should reject an imported key whose components do not match its attributes", async () => {
+      const { masterKey } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+
+      await expect(importABEPrivateKey(privateKey.key, ["A"])).rejects.toThrow(/属性の数と一致しません/);
+      await expect(importABEPrivateKey(new Uint8Array([0xff, 0x01]), [])).rejects.toThrow(/スキームが不明です/);
+    });
+
+    it("should include the expiry of attributes that have one", async () => {
+      const { masterKey } = await generateABEKeyPair();
+      const privateKey = await extractABEKeyWithExpiry(masterKey, ["A", "B"], [0n, 1_900_000_000n]);
+
+      const described = await describeABEKey(privateKey);
+      expect(described[0].expires_at).toBeUndefined();
+      expect(described[1].expires_at).toBe(1_900_000_000n);
+    });
+  });
+
+  describe("policy evaluation", () => {
+    // [ポリシー, 属性セット, 期待する結果]
+    const check = async (cases: [string, string[], boolean][]) => {
+      for (const [policy, attributes, expected] of cases) {
+        expect(await checkABEPolicy(policy, attributes), `${policy} with [${attributes}]`).toBe(expected);
+      }
+    };
+
+    it("should require every child of an AND gate", async () => {
+      await check([
+        ["A AND B", ["A", "B"], true],
+        ["A AND B", ["A"], false],
+        ["A AND B", ["B", "C"], false],
+        // カンマ区切りはANDとして扱う
+        ["A, B", ["A", "B"], true],
+        ["A, B", ["A"], false],
+      ]);
+    });
+
+    it("should require any child of an OR gate", async () => {
+      await check([
+        ["A OR B", ["A"], true],
+        ["A OR B", ["B"], true],
+        ["A OR B", ["A", "B"], true],
+        ["A OR B", ["C"], false],
+      ]);
+    });
+
+    it("should require at least k children of a threshold gate", async () => {
+      await check([
+        ["2 of (A, B, C)", ["A", "C"], true],
+        ["2 of (A, B, C)", ["A", "B", "C"], true],
+        ["2 of (A, B, C)", ["B"], false],
+        ["2 of (A, B, C)", ["B", "D"], false],
+        ["3 of (A, B, C)", ["A", "B"], false],
+      ]);
+    });
+
+    it("should invert the child of a NOT gate", async () => {
+      await check([
+        ["NOT A", ["B"], true],
+        ["NOT A", ["A"], false],
+        ["A AND NOT B", ["A"], true],
+        ["A AND NOT B", ["A", "B"], false],
+      ]);
+    });
+
+    it("should evaluate the empty attribute set", async () => {
+      await check([
+        ["A", [], false],
+        ["A AND B", [], false],
+        ["A OR B", [], false],
+        ["1 of (A, B)", [], false],
+        // 何も持たない属性セットは、否定だけのポリシーを満たす
+        ["NOT A", [], true],
+      ]);
+    });
+  });
+
+  describe("policy nesting limit", () => {
+    const nested = (depth: number) => `${"(".repeat(depth)}A${")".repeat(depth)}`;
+
+    it("should evaluate a policy nested exactly to the default limit", async () => {
+      expect(await checkABEPolicy(nested(64), ["A"])).toBe(true);
+      expect(await checkABEPolicy(`${"NOT ".repeat(64)}A`, ["A"])).toBe(true);
+    });
+
+    it("should reject a policy nested beyond the default limit", async () => {
+      await expect(checkABEPolicy(nested(65), ["A"])).rejects.toThrow(/入れ子が深すぎます（上限: 64）/);
+      await expect(checkABEPolicy(`${"NOT ".repeat(65)}A`, ["A"])).rejects.toThrow(/入れ子が深すぎます/);
+    });
+
+    it("should reject maliciously deep nesting with an error instead of a crash", async () => {
+      await expect(checkABEPolicy(nested(1_000_000), ["A"])).rejects.toThrow(/入れ子が深すぎます/);
+
+      // エラーの後もモジュールは引き続き使える
+      expect(await checkABEPolicy("2 of (A, B, C)", ["A", "C"])).toBe(true);
+    });
+
+    it("should honor a caller-supplied limit", async () => {
+      // 閾値の括弧も1段と数える
+      expect(await checkABEPolicy("1 of ((A), B)", ["A"], 2)).toBe(true);
+      await expect(checkABEPolicy("1 of (((A)), B)", ["A"], 2)).rejects.toThrow(/上限: 2/);
+      expect(await checkABEPolicy(nested(100), ["A"], 128)).toBe(true);
+    });
+  });
+
+  describe("post-quantum hybrid encryption", () => {
+    const message = new TextEncoder().encode("Hybrid CP-ABE + ML-KEM message that spans several AES blocks");
+
+    // ハイブリッド暗号文からペアリング層の暗号文を取り出す
+    // version (1バイト) || ML-KEM-768の暗号文 (1088バイト) || 長さ (4バイト) || ペアリング層の暗号文 || ...
+    function pairingLayerOf(ciphertext: Uint8Array): Uint8Array {
+      const offset = 1 + 1088;
+      const length = new DataView(ciphertext.buffer, ciphertext.byteOffset).getUint32(offset);
+      return ciphertext.slice(offset + 4, offset + 4 + length);
+    }
+
+    it("should decrypt with both the ABE key and the Kyber key", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const kyber = await generateKyberKeyPair();
+
+      const ciphertext = await encryptABEHybrid(publicParams, "A,B", kyber.publicKey, message);
+
+      expect(await decryptABEHybrid(privateKey, kyber.privateKey, kyber.publicKey, ciphertext)).toEqual(message);
+    });
+
+    it("should require the Kyber key", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const kyber = await generateKyberKeyPair();
+      const otherKyber = await generateKyberKeyPair();
+
+      const ciphertext = await encryptABEHybrid(publicParams, "A,B", kyber.publicKey, message);
+
+      await expect(
+        decryptABEHybrid(privateKey, otherKyber.privateKey, otherKyber.publicKey, ciphertext)
+      ).rejects.toThrow(/認証に失敗しました/);
+    });
+
+    it("should require an ABE key that satisfies the policy", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "C"]);
+      const kyber = await generateKyberKeyPair();
+
+      const ciphertext = await encryptABEHybrid(publicParams, "A,B", kyber.publicKey, message);
+
+      await expect(
+        decryptABEHybrid(privateKey, kyber.privateKey, kyber.publicKey, ciphertext)
+      ).rejects.toThrow();
+    });
+
+    it("should keep the message safe when only the pairing layer is compromised", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const kyber = await generateKyberKeyPair();
+      const ciphertext = await encryptABEHybrid(publicParams, "A,B", kyber.publicKey, message);
+
+      // ペアリングが破られた状況を模擬する: 攻撃者はマスター鍵から任意の属性の鍵を作り、
+      // ペアリング層を復号して鍵シェアを得られるが、Kyberの秘密鍵は持っていない
+      const compromisedKey = await extractABEKey(masterKey, ["A", "B"]);
+      const keyShare = await decryptABE(compromisedKey, pairingLayerOf(ciphertext));
+      expect(keyShare.length).toBe(32);
+
+      const attackerKyber = await generateKyberKeyPair();
+      await expect(
+        decryptABEHybrid(compromisedKey, attackerKyber.privateKey, attackerKyber.publicKey, ciphertext)
+      ).rejects.toThrow(/認証に失敗しました/);
+    });
+
+    it("should reject a Kyber public key of the wrong size", async () => {
+      const { publicParams } = await generateABEKeyPair();
+
+      await expect(
+        encryptABEHybrid(publicParams, "A,B", new Uint8Array(100), message)
+      ).rejects.toThrow(/Kyberの公開鍵のサイズが不正です/);
+    });
+  });
+
+  describe("trial decryption", () => {
+    const message = new TextEncoder().encode("Hello, mailbox!");
+
+    it("should return the plaintext on success", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      expect(await tryDecryptABE(privateKey, ciphertext)).toEqual(message);
+    });
+
+    it("should return undefined when the policy is not satisfied", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "C"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      expect(await tryDecryptABE(privateKey, ciphertext)).toBeUndefined();
+    });
+
+    it("should return undefined instead of throwing for corrupt ciphertexts", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      expect(await tryDecryptABE(privateKey, ciphertext.slice(0, 10))).toBeUndefined();
+      expect(await tryDecryptABE(privateKey, new Uint8Array(0))).toBeUndefined();
+    });
+
+    it("should return undefined for unauthenticated formats even with the right key", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      // AES-256-GCMのフラグを外した暗号文と、さらに平文のハッシュのフラグを付けた暗号文
+      const unflagged = new Uint8Array(ciphertext);
+      unflagged[0] &= ~0x20;
+      const hashed = new Uint8Array(unflagged);
+      hashed[0] |= 0x40;
+      expect(await tryDecryptABE(privateKey, unflagged)).toBeUndefined();
+      expect(await tryDecryptABE(privateKey, hashed)).toBeUndefined();
+
+      // ヘッダなしの旧形式は、復号できる旧方式の鍵でもundefinedになる（upgradeABECiphertextで移行する）
+      const legacyKey = await importABEPrivateKey(LEGACY_CP_PRIVATE_KEY, ["A", "B"]);
+      expect(await tryDecryptABE(legacyKey, LEGACY_CP_CIPHERTEXT)).toBeUndefined();
+    });
+
+    it("should decide hybrid ciphertexts by the integrity tag", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const kyber = await generateKyberKeyPair();
+      const otherKyber = await generateKyberKeyPair();
+
+      const ciphertext = await encryptABEHybrid(publicParams, "A,B", kyber.publicKey, message);
+
+      expect(
+        await tryDecryptABEHybrid(privateKey, kyber.privateKey, kyber.publicKey, ciphertext)
+      ).toEqual(message);
+
+      // 別のマスター鍵で発行された、属性の同じ鍵
+      const other = await generateABEKeyPair();
+      const foreignKey = await extractABEKey(other.masterKey, ["A", "B"]);
+      expect(
+        await tryDecryptABEHybrid(foreignKey, kyber.privateKey, kyber.publicKey, ciphertext)
+      ).toBeUndefined();
+      // Kyberの鍵が異なる
+      expect(
+        await tryDecryptABEHybrid(privateKey, otherKyber.privateKey, otherKyber.publicKey, ciphertext)
+      ).toBeUndefined();
+      // 改ざんされた暗号文
+      const tampered = ciphertext.slice();
+      tampered[tampered.length - 1] ^= 0x01;
+      expect(
+        await tryDecryptABEHybrid(privateKey, kyber.privateKey, kyber.publicKey, tampered)
+      ).toBeUndefined();
+    });
+  });
+
+  describe("take_bytes", () => {
+    it("should move out the same bytes as the getters and leave the keys empty", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+
+      const secret = masterKey.secret;
+      const params = publicParams.params;
+      const key = privateKey.key;
+
+      expect(masterKey.take_bytes()).toEqual(secret);
+      expect(publicParams.take_bytes()).toEqual(params);
+      expect(privateKey.take_bytes()).toEqual(key);
+
+      // 取り出した後は空になり、2回目以降も空を返す
+      expect(masterKey.secret.length).toBe(0);
+      expect(publicParams.params.length).toBe(0);
+      expect(privateKey.key.length).toBe(0);
+      expect(privateKey.take_bytes().length).toBe(0);
+      // 属性の一覧はそのまま残る
+      expect(privateKey.attributes).toEqual(["A", "B"]);
+    });
+
+    it("should reject operations with keys whose bytes were taken", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", new Uint8Array([1, 2, 3]));
+
+      privateKey.take_bytes();
+      publicParams.take_bytes();
+      masterKey.take_bytes();
+
+      await expect(decryptABE(privateKey, ciphertext)).rejects.toThrow();
+      await expect(encryptABE(publicParams, "A,B", new Uint8Array([1]))).rejects.toThrow();
+      await expect(extractABEKey(masterKey, ["A"])).rejects.toThrow();
+    });
+  });
+
+  describe("zeroize", () => {
+    it("should wipe the secret bytes and reject later operations", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", new Uint8Array([1, 2, 3]));
+
+      privateKey.zeroize();
+      masterKey.zeroize();
+
+      expect(privateKey.key.length).toBe(0);
+      expect(masterKey.secret.length).toBe(0);
+      await expect(decryptABE(privateKey, ciphertext)).rejects.toThrow();
+      await expect(extractABEKey(masterKey, ["A"])).rejects.toThrow();
+    });
+  });
+
+  describe("clone", () => {
+    it("should produce keys that behave identically to the originals", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKeyWithExpiry(masterKey, ["A", "B"], [0n, 1000n]);
+      const message = new TextEncoder().encode("Hello, clone!");
+
+      const clonedKey = privateKey.clone();
+      expect(clonedKey.key).toEqual(privateKey.key);
+      expect(clonedKey.attributes).toEqual(privateKey.attributes);
+      expect(clonedKey.expires_at("B")).toBe(privateKey.expires_at("B"));
+      expect(clonedKey.authority_id).toEqual(privateKey.authority_id);
+
+      const ciphertext = await encryptABEBound(publicParams.clone(), "A,B", message);
+      expect(await decryptABEAt(clonedKey, ciphertext, 10n)).toEqual(message);
+      expect(await decryptABEAt(privateKey, ciphertext, 10n)).toEqual(message);
+
+      // マスター鍵の複製からも同じ鍵を生成できる
+      expect((await extractABEKey(masterKey.clone(), ["A"])).key).toEqual((await extractABEKey(masterKey, ["A"])).key);
+    });
+
+    it("should keep the clone usable after the original is emptied or freed", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A"]);
+      const ciphertext = await encryptABE(publicParams, "A", new TextEncoder().encode("data"));
+
+      const clonedKey = privateKey.clone();
+      privateKey.take_bytes();
+      expect(await decryptABE(clonedKey, ciphertext)).toEqual(new TextEncoder().encode("data"));
+
+      // 取り出した後の鍵の複製は空のまま
+      expect(privateKey.clone().key.length).toBe(0);
+
+      // 解放した鍵の複製はエラーになる
+      const freedKey = clonedKey.clone();
+      clonedKey.free();
+      expect(() => clonedKey.clone()).toThrow();
+      expect(await decryptABE(freedKey, ciphertext)).toEqual(new TextEncoder().encode("data"));
+    });
+  });
+
+  describe("base64 serialization", () => {
+    it("should round-trip the master key and public params", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+
+      const restoredMaster = await importABEMasterKeyFromString(masterKey.to_base64());
+      const restoredParams = await importABEPublicParamsFromString(publicParams.to_base64());
+      expect(restoredMaster.secret).toEqual(masterKey.secret);
+      expect(restoredParams.params).toEqual(publicParams.params);
+      expect(publicParams.to_base64()).toMatch(/^[A-Za-z0-9_-]+$/);
+    });
+
+    it("should restore a private key with its attributes, expirations, and authority", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKeyWithExpiry(masterKey, ["A", "部署:開発"], [0n, 1000n]);
+      const message = new TextEncoder().encode("Hello, storage!");
+
+      // localStorageに保存して読み込み直す想定
+      const restored = await importABEPrivateKeyFromString(privateKey.to_base64());
+      expect(restored.key).toEqual(privateKey.key);
+      expect(restored.attributes).toEqual(["A", "部署:開発"]);
+      expect(restored.expires_at("部署:開発")).toBe(1000n);
+      expect(restored.authority_id).toEqual(privateKey.authority_id);
+      expect(restored.to_base64()).toBe(privateKey.to_base64());
+
+      const ciphertext = await encryptABEBound(publicParams, "A,部署:開発", message);
+      expect(await decryptABEAt(restored, ciphertext, 10n)).toEqual(message);
+      await expect(decryptABEAt(restored, ciphertext, 2000n)).rejects.toThrow();
+    });
+
+    it("should reject truncated or malformed strings", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const encoded = (await extractABEKey(masterKey, ["A"])).to_base64();
+
+      await expect(importABEPrivateKeyFromString(encoded.slice(0, 20))).rejects.toThrow(/短すぎます/);
+      await expect(importABEPrivateKeyFromString("B" + encoded.slice(1))).rejects.toThrow(/バージョン/);
+      await expect(importABEPrivateKeyFromString("!!")).rejects.toThrow(/base64url/);
+      await expect(importABEMasterKeyFromString(publicParams.to_base64())).rejects.toThrow();
+      await expect(importABEPublicParamsFromString(masterKey.to_base64())).rejects.toThrow();
+    });
+  });
+
+  describe("authenticated payload (AES-256-GCM)", () => {
+    const message = new TextEncoder().encode("Hello, GCM!");
+
+    it("should reject a bit flip anywhere in the ciphertext", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+      expect(await decryptABE(privateKey, ciphertext)).toEqual(message);
+
+      for (let i = 0; i < ciphertext.length; i++) {
+        const tampered = new Uint8Array(ciphertext);
+        tampered[i] ^= 0x01;
+        await expect(decryptABE(privateKey, tampered)).rejects.toThrow();
+      }
+    }, 60_000);
+
+    it("should report an authentication failure when V is modified", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      // V = nonce (12バイト) || 暗号文 || 認証タグ (16バイト)
+      const vLength = 12 + message.length + 16;
+
+      // 各形式の暗号文と、Vの開始位置
+      const bound = await encryptABEBound(publicParams, "A,B", message);
+      const cases: [Uint8Array, number][] = [
+        [bound, 2 + 32 + 1 + 8 + 65],
+        ...[
+          await encryptABE(publicParams, "A,B", message),
+          await encryptABE(publicParams, "A AND (B OR C)", message),
+          await encryptABEThreshold(publicParams, ["A", "B", "C"], 2, message),
+        ].map((ciphertext): [Uint8Array, number] => [ciphertext, ciphertext.length - vLength]),
+      ];
+
+      for (const [ciphertext, vStart] of cases) {
+        // AES-256-GCMのフラグ（0x20）が付き、平文のハッシュのフラグ（0x40）は付かない
+        expect(ciphertext[0] & 0x60).toBe(0x20);
+        // nonce・暗号文・認証タグのそれぞれ
+        for (const index of [vStart, vStart + 12, vStart + vLength - 1]) {
+          const tampered = new Uint8Array(ciphertext);
+          tampered[index] ^= 0x01;
+          await expect(decryptABE(privateKey, tampered)).rejects.toThrow(/認証タグが一致しません/);
+        }
+      }
+    });
+
+    it("should reject a key from a different master key", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      const other = await generateABEKeyPair();
+      const foreignKey = await extractABEKey(other.masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      await expect(decryptABE(foreignKey, ciphertext)).rejects.toThrow(/認証タグが一致しません/);
+      expect(await tryDecryptABE(foreignKey, ciphertext)).toBeUndefined();
+    });
+
+    it("should reject a ciphertext whose AES-256-GCM flag has been cleared", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+
+      for (const ciphertext of [
+        await encryptABE(publicParams, "A,B", message),
+        await encryptABE(publicParams, "A AND (B OR C)", message),
+        await encryptABEBound(publicParams, "A,B", message),
+      ]) {
+        const stripped = new Uint8Array(ciphertext);
+        stripped[0] &= ~0x20;
+
+        await expect(decryptABE(privateKey, stripped)).rejects.toThrow(/認証されていない形式/);
+        expect(await tryDecryptABE(privateKey, stripped)).toBeUndefined();
+
+        // 移行では旧形式の対称鍵（GCMの対称鍵と別のラベルで導出）で復号するため、元の平文は得られない
+        const upgraded = await upgradeABECiphertext(stripped, privateKey, publicParams);
+        expect(await decryptABE(privateKey, upgraded)).not.toEqual(message);
+      }
+    });
+
+    it("should round-trip messages longer than the symmetric key without repeating it", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const zeros = new Uint8Array(64);
+      const ciphertext = await encryptABE(publicParams, "A,B", zeros);
+
+      expect(await decryptABE(privateKey, ciphertext)).toEqual(zeros);
+      // 旧形式では平文が0の場合、Vの前半と後半が同じ対称鍵になっていた
+      const body = ciphertext.slice(ciphertext.length - 16 - 64, ciphertext.length - 16);
+      expect(body.slice(0, 32)).not.toEqual(body.slice(32));
+    });
+
+    it("should not turn a 1 KB zero message into a repeating 32-byte pattern", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const zeros = new Uint8Array(1024);
+      const ciphertext = await encryptABE(publicParams, "A,B", zeros);
+
+      expect(await decryptABE(privateKey, ciphertext)).toEqual(zeros);
+      const body = ciphertext.slice(ciphertext.length - 16 - 1024, ciphertext.length - 16);
+      for (let offset = 32; offset < body.length; offset += 32) {
+        expect(body.slice(offset, offset + 32)).not.toEqual(body.slice(0, 32));
+      }
+    });
+  });
+
+  describe("threshold encryption", () => {
+    const message = new TextEncoder().encode("Hello, threshold!");
+
+    it("should decrypt a 2-of-3 ciphertext with exactly two matching attributes", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABEThreshold(publicParams, ["A", "B", "C"], 2, message);
+
+      for (const attributes of [["A", "B"], ["B", "C"], ["C", "A"]]) {
+        const privateKey = await extractABEKey(masterKey, attributes);
+        const decrypted = await decryptABE(privateKey, ciphertext);
+        expect(new TextDecoder().decode(decrypted)).toBe("Hello, threshold!");
+      }
+    });
+
+    it("should fail a 2-of-3 ciphertext with only one matching attribute", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABEThreshold(publicParams, ["A", "B", "C"], 2, message);
+
+      const single = await extractABEKey(masterKey, ["A"]);
+      await expect(decryptABE(single, ciphertext)).rejects.toThrow(/ポリシーを満たしていません/);
+
+      // ポリシー外の属性は数に入らない
+      const unrelated = await extractABEKey(masterKey, ["A", "D"]);
+      await expect(decryptABE(unrelated, ciphertext)).rejects.toThrow(/ポリシーを満たしていません/);
+    });
+
+    it("should require every attribute when k equals n", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABEThreshold(publicParams, ["A", "B", "C"], 3, message);
+
+      const all = await extractABEKey(masterKey, ["C", "B", "A"]);
+      expect(await decryptABE(all, ciphertext)).toEqual(message);
+
+      const two = await extractABEKey(masterKey, ["A", "B"]);
+      await expect(decryptABE(two, ciphertext)).rejects.toThrow();
+    });
+
+    it("should reject invalid thresholds and attribute lists", async () => {
+      const { publicParams } = await generateABEKeyPair();
+
+      await expect(encryptABEThreshold(publicParams, ["A", "B", "C"], 0, message)).rejects.toThrow();
+      await expect(encryptABEThreshold(publicParams, ["A", "B", "C"], 4, message)).rejects.toThrow();
+      await expect(encryptABEThreshold(publicParams, ["A", "A", "B"], 2, message)).rejects.toThrow();
+      await expect(encryptABEThreshold(publicParams, [], 1, message)).rejects.toThrow();
+    });
+  });
+
+  describe("per-attribute expiry", () => {
+    it("should expose the expiry of each attribute", async () => {
+      const { masterKey } = await generateABEKeyPair();
+      const privateKey = await extractABEKeyWithExpiry(masterKey, ["A", "B"], [100n, 0n]);
+
+      expect(privateKey.expires_at("A")).toBe(100n);
+      expect(privateKey.expires_at("B")).toBeUndefined();
+      expect(privateKey.expires_at("C")).toBeUndefined();
+    });
+
+    it("should satisfy the policy only via the attribute that is still valid", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      // Aは時刻100で失効し、Bは時刻1000まで有効
+      const privateKey = await extractABEKeyWithExpiry(masterKey, ["A", "B"], [100n, 1000n]);
+      const message = new TextEncoder().encode("Per-attribute expiry");
+
+      const bothAttributes = await encryptABE(publicParams, "A,B", message);
+      const validOnly = await encryptABE(publicParams, "B", message);
+
+      // 両方の属性が有効な間は、AとBの両方を要求するポリシーを満たす
+      expect(await decryptABEAt(privateKey, bothAttributes, 50n)).toEqual(message);
+
+      // Aの失効後は、Aを要求するポリシーは満たせない
+      await expect(decryptABEAt(privateKey, bothAttributes, 500n)).rejects.toThrow(/有効期限/);
+
+      // 有効なBだけでポリシーを満たす場合は復号できる
+      expect(await decryptABEAt(privateKey, validOnly, 500n)).toEqual(message);
+
+      // Bも失効すると復号できない
+      await expect(decryptABEAt(privateKey, validOnly, 2000n)).rejects.toThrow(/有効期限/);
+    });
+
+    it("should require the current time to decrypt", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKeyWithExpiry(masterKey, ["A"], [100n]);
+      const ciphertext = await encryptABE(publicParams, "A", new TextEncoder().encode("data"));
+
+      await expect(decryptABE(privateKey, ciphertext)).rejects.toThrow(/decrypt_at/);
+    });
+
+    it("should reject a mismatched number of expiry values", async () => {
+      const { masterKey } = await generateABEKeyPair();
+
+      await expect(extractABEKeyWithExpiry(masterKey, ["A", "B"], [100n])).rejects.toThrow();
+    });
+  });
+
+  describe("interop test vectors", () => {
+    // 他言語の実装が一致を確認するための固定ベクトル
+    // マスター秘密鍵は32バイトのビッグエンディアンのスカラー、
+    // 公開パラメータは非圧縮形式（0x04 || x || y、x・yは32バイトのビッグエンディアン）
+    const VECTORS = [
+      {
+        scalar: "0000000000000000000000000000000000000000000000000000000000000001",
+        // 生成元 P = (-1, 1)
+        publicParams:
+          "04" +
+          "2523648240000001ba344d80000000086121000000000013a700000000000012" +
+          "0000000000000000000000000000000000000000000000000000000000000001",
+      },
+      {
+        scalar: "0000000000000000000000000000000000000000000000000000000000000002",
+        publicParams:
+          "04" +
+          "0948d920900000006e8d1360000000021848400000000004e9c0000000000009" +
+          "17361ed1680000011460b070000000053cb4a0000000000c4860000000000003",
+      },
+      {
+        scalar: "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20",
+        publicParams:
+          "04" +
+          "1e188092e2ed77e30aec282e99b11620ce70233160017897223d81f59068aa5e" +
+          "07a0cc140b65687f722353bf3adce4aea50401d71c7f435702001697448da2c6",
+      },
+    ];
+
+    it("should serialize a known scalar to the documented bytes", async () => {
+      const masterKey = await importABEMasterKey(hexToBytes(VECTORS[0].scalar));
+
+      const expected = new Uint8Array(32);
+      expected[31] = 1;
+      expect(masterKey.secret).toEqual(expected);
+    });
+
+    it("should derive the documented public params from each scalar", async () => {
+      for (const vector of VECTORS) {
+        const masterKey = await importABEMasterKey(hexToBytes(vector.scalar));
+        const publicParams = await deriveABEPublicParams(masterKey);
+
+        expect(masterKey.secret).toEqual(hexToBytes(vector.scalar));
+        expect(publicParams.params).toEqual(hexToBytes(vector.publicParams));
+      }
+    });
+
+    it("should round-trip the documented public params byte for byte", async () => {
+      for (const vector of VECTORS) {
+        const publicParams = await importABEPublicParams(hexToBytes(vector.publicParams));
+        expect(publicParams.params).toEqual(hexToBytes(vector.publicParams));
+      }
+    });
+
+    it("should derive the same public params as setup", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+
+      const derived = await deriveABEPublicParams(masterKey);
+
+      expect(derived.params).toEqual(publicParams.params);
+    });
+  });
+
+  describe("signed public params", () => {
+    it("should accept genuine public params signed by the authority", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const authority = await generateDilithiumKeyPair();
+
+      const signed = await signABEPublicParams(publicParams, authority.privateKey);
+      const verified = await verifyABEPublicParams(signed, authority.publicKey);
+
+      expect(verified.params).toEqual(publicParams.params);
+
+      // 検証した公開パラメータで暗号化でき、鍵で復号できる
+      const message = new TextEncoder().encode("Hello, authority!");
+      const ciphertext = await encryptABE(verified, "A", message);
+      const privateKey = await extractABEKey(masterKey, ["A"]);
+      expect(await decryptABE(privateKey, ciphertext)).toEqual(message);
+    });
+
+    it("should frame the params between the version byte and the signature", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      const authority = await generateDilithiumKeyPair();
+
+      const signed = await signABEPublicParams(publicParams, authority.privateKey);
+
+      expect(signed[0]).toBe(1);
+      expect(signed.slice(1, 66)).toEqual(publicParams.params);
+
+      // 署名はdilithium-wasmのverifyでもラベル付きのメッセージとして検証できる
+      const label = new TextEncoder().encode("abe-wasm public params v1");
+      const signedMessage = new Uint8Array([...label, ...publicParams.params]);
+      expect(await verifyDilithium(signedMessage, signed.slice(66), authority.publicKey)).toBe(true);
+    });
+
+    it("should reject substituted public params", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      const attacker = await generateABEKeyPair();
+      const authority = await generateDilithiumKeyPair();
+
+      const signed = await signABEPublicParams(publicParams, authority.privateKey);
+
+      // 中間者が公開パラメータだけを自分のものに差し替える
+      const substituted = signed.slice();
+      substituted.set(attacker.publicParams.params, 1);
+      await expect(verifyABEPublicParams(substituted, authority.publicKey)).rejects.toThrow(
+        /署名の検証に失敗しました/
+      );
+
+      // 1ビットの改ざん
+      const tampered = signed.slice();
+      tampered[10] ^= 0x01;
+      await expect(verifyABEPublicParams(tampered, authority.publicKey)).rejects.toThrow();
+    });
+
+    it("should reject params signed by another key", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      const authority = await generateDilithiumKeyPair();
+      const impostor = await generateDilithiumKeyPair();
+
+      const signed = await signABEPublicParams(publicParams, impostor.privateKey);
+
+      await expect(verifyABEPublicParams(signed, authority.publicKey)).rejects.toThrow(
+        /署名の検証に失敗しました/
+      );
+    });
+
+    it("should reject malformed blobs and keys", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      const authority = await generateDilithiumKeyPair();
+      const signed = await signABEPublicParams(publicParams, authority.privateKey);
+
+      await expect(verifyABEPublicParams(signed.slice(0, 100), authority.publicKey)).rejects.toThrow();
+      await expect(verifyABEPublicParams(new Uint8Array(0), authority.publicKey)).rejects.toThrow();
+      await expect(verifyABEPublicParams(signed, new Uint8Array(32))).rejects.toThrow();
+      await expect(signABEPublicParams(publicParams, new Uint8Array(32))).rejects.toThrow();
+    });
+  });
+
+  describe("authority binding", () => {
+    const message = new TextEncoder().encode("Hello, authority A!");
+
+    it("should decrypt a bound ciphertext with a key from the same authority", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+
+      const ciphertext = await encryptABEBound(publicParams, "A,B", message);
+      expect(await decryptABE(privateKey, ciphertext)).toEqual(message);
+    });
+
+    it("should reject a key from a different authority even with matching attributes", async () => {
+      const authorityA = await generateABEKeyPair();
+      const authorityB = await generateABEKeyPair();
+      const keyFromB = await extractABEKey(authorityB.masterKey, ["A", "B"]);
+
+      const ciphertext = await encryptABEBound(authorityA.publicParams, "A,B", message);
+      await expect(decryptABE(keyFromB, ciphertext)).rejects.toThrow(/発行元が一致しません/);
+
+      // 束縛しない暗号文では、別の権限者の鍵は認証タグの照合で拒否される
+      const unbound = await encryptABE(authorityA.publicParams, "A,B", message);
+      await expect(decryptABE(keyFromB, unbound)).rejects.toThrow(/認証タグが一致しません/);
+    });
+
+    it("should record the authority of generated keys", async () => {
+      const authorityA = await generateABEKeyPair();
+      const authorityB = await generateABEKeyPair();
+
+      const keyA1 = await extractABEKey(authorityA.masterKey, ["A"]);
+      const keyA2 = await extractABEKey(authorityA.masterKey, ["B", "C"]);
+      const keyB = await extractABEKey(authorityB.masterKey, ["A"]);
+
+      expect(keyA1.authority_id.length).toBe(32);
+      expect(keyA2.authority_id).toEqual(keyA1.authority_id);
+      expect(keyB.authority_id).not.toEqual(keyA1.authority_id);
+    });
+
+    it("should require the authority to be restored for imported keys", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABEBound(publicParams, "A,B", message);
+
+      const withoutAuthority = await importABEPrivateKey(privateKey.key, privateKey.attributes);
+      expect(withoutAuthority.authority_id.length).toBe(0);
+      await expect(decryptABE(withoutAuthority, ciphertext)).rejects.toThrow(/発行元が不明です/);
+
+      const withAuthority = await importABEPrivateKey(privateKey.key, privateKey.attributes, publicParams);
+      expect(await decryptABE(withAuthority, ciphertext)).toEqual(message);
+    });
+
+    it("should not decrypt when the embedded authority is rewritten", async () => {
+      const authorityA = await generateABEKeyPair();
+      const authorityB = await generateABEKeyPair();
+      const keyFromB = await extractABEKey(authorityB.masterKey, ["A", "B"]);
+
+      // 発行元IDをBのものに書き換えても、対称鍵の導出に発行元IDが含まれるため正しく復号できない
+      const ciphertext = await encryptABEBound(authorityA.publicParams, "A,B", message);
+      const forged = new Uint8Array(ciphertext);
+      forged.set(keyFromB.authority_id, 2);
+      await expect(decryptABE(keyFromB, forged)).rejects.toThrow(/認証タグが一致しません/);
+    });
+  });
+
+  describe("boolean access policies", () => {
+    const message = new TextEncoder().encode("Hello, access policy!");
+
+    it("should decrypt an OR policy with a key holding only one branch", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABE(publicParams, "a OR b", message);
+
+      const onlyB = await extractABEKey(masterKey, ["b"]);
+      expect(await decryptABE(onlyB, ciphertext)).toEqual(message);
+
+      const onlyA = await extractABEKey(masterKey, ["a", "c"]);
+      expect(await decryptABE(onlyA, ciphertext)).toEqual(message);
+    });
+
+    it("should evaluate nested AND and OR", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABE(publicParams, "(doctor AND cardiology) OR admin", message);
+
+      for (const attributes of [["doctor", "cardiology"], ["admin"], ["nurse", "cardiology", "doctor"]]) {
+        const privateKey = await extractABEKey(masterKey, attributes);
+        expect(await decryptABE(privateKey, ciphertext)).toEqual(message);
+      }
+
+      const doctorOnly = await extractABEKey(masterKey, ["doctor", "nurse"]);
+      await expect(decryptABE(doctorOnly, ciphertext)).rejects.toThrow(/ポリシーを満たしていません/);
+    });
+
+    it("should support thresholds inside a boolean policy", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABE(publicParams, "2 of (a, b AND c, d) AND e", message);
+
+      const satisfying = await extractABEKey(masterKey, ["d", "c", "b", "e"]);
+      expect(await decryptABE(satisfying, ciphertext)).toEqual(message);
+
+      const missingE = await extractABEKey(masterKey, ["a", "d"]);
+      await expect(decryptABE(missingE, ciphertext)).rejects.toThrow(/ポリシーを満たしていません/);
+    });
+
+    it("should decrypt a k-of-n policy with exactly k matching attributes", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABE(publicParams, "2 of (a, b, c, d)", message);
+
+      for (const attributes of [["a", "b"], ["a", "d"], ["b", "c"], ["d", "c"]]) {
+        const privateKey = await extractABEKey(masterKey, attributes);
+        expect(await decryptABE(privateKey, ciphertext)).toEqual(message);
+      }
+    });
+
+    it("should fail a k-of-n policy with k-1 matching attributes", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const twoOfFour = await encryptABE(publicParams, "2 of (a, b, c, d)", message);
+      const threeOfFour = await encryptABE(publicParams, "3 of (a, b, c, d)", message);
+
+      const single = await extractABEKey(masterKey, ["c", "e"]);
+      await expect(decryptABE(single, twoOfFour)).rejects.toThrow(/ポリシーを満たしていません/);
+
+      const pair = await extractABEKey(masterKey, ["a", "d"]);
+      await expect(decryptABE(pair, threeOfFour)).rejects.toThrow(/ポリシーを満たしていません/);
+
+      const triple = await extractABEKey(masterKey, ["a", "b", "d"]);
+      expect(await decryptABE(triple, threeOfFour)).toEqual(message);
+    });
+
+    it("should reject thresholds outside 1..n", async () => {
+      const { publicParams } = await generateABEKeyPair();
+
+      await expect(encryptABE(publicParams, "5 of (a, b, c, d)", message)).rejects.toThrow(/閾値が不正です/);
+      await expect(encryptABE(publicParams, "0 of (a, b)", message)).rejects.toThrow(/閾値が不正です/);
+    });
+
+    it("should carry the LSSS matrix in the ciphertext", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABE(publicParams, "(a AND b) OR c", message);
+
+      // header (2) || C0 (65) || 行数 || 列数 || 行... || V
+      expect(ciphertext[0]).toBe(0x07 | 0x20);
+      expect(ciphertext[67]).toBe(3);
+      expect(ciphertext[68]).toBe(2);
+      // 行 = len(attr) || attr || 列数 × 32バイト || C_i (130バイト) || D_i (65バイト)、Vはnonce || 暗号文 || 認証タグ
+      expect(ciphertext.length).toBe(2 + 65 + 2 + 3 * (1 + 1 + 2 * 32 + 130 + 65) + 12 + message.length + 16);
+    });
+
+    it("should reconstruct from a satisfying subset of repeated attributes", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABE(publicParams, "(a AND b) OR (a AND c)", message);
+
+      const privateKey = await extractABEKey(masterKey, ["c", "a"]);
+      expect(await decryptABE(privateKey, ciphertext)).toEqual(message);
+    });
+
+    it("should not decrypt when the LSSS matrix is tampered with", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const ciphertext = await encryptABE(publicParams, "(a AND b) OR c", message);
+      const privateKey = await extractABEKey(masterKey, ["a", "b"]);
+
+      // 行aの2列目の要素（1）の最下位バイトを書き換える
+      const tampered = new Uint8Array(ciphertext);
+      tampered[2 + 65 + 2 + 1 + 1 + 2 * 32 - 1] ^= 0x02;
+      await expect(decryptABE(privateKey, tampered)).rejects.toThrow(/認証タグが一致しません/);
+    });
+
+    it("should list the policy leaves as hints", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      const { requiredAttributes } = await encryptABEWithHint(
+        publicParams,
+        "(doctor AND cardiology) OR admin",
+        message
+      );
+      expect(requiredAttributes).toEqual(["doctor", "cardiology", "admin"]);
+    });
+
+    it("should report unbalanced parentheses clearly", async () => {
+      const { publicParams } = await generateABEKeyPair();
+
+      await expect(encryptABE(publicParams, "(a AND b", message)).rejects.toThrow(/「\(」に対応する「\)」がありません/);
+      await expect(encryptABE(publicParams, "a OR b)", message)).rejects.toThrow(/対応する「\(」のない「\)」があります/);
+    });
+
+    it("should reject NOT and time limits in boolean policies", async () => {
+      const { publicParams } = await generateABEKeyPair();
+
+      await expect(encryptABE(publicParams, "a AND NOT b", message)).rejects.toThrow(/NOTを使用できません/);
+      await expect(encryptABE(publicParams, "a OR valid_until:100", message)).rejects.toThrow(/時間制限属性を指定できません/);
+    });
+  });
+
+  describe("collusion resistance", () => {
+    const message = new TextEncoder().encode("Hello, collusion!");
+
+    /**
+     * to_base64の文字列から鍵のバイト列・発行元ID・LSSS鍵を取り出す（有効期限のない鍵のみ）
+     */
+    function splitKeyString(privateKey: ABEPrivateKey) {
+      const bytes = new Uint8Array(Buffer.from(privateKey.to_base64(), "base64url"));
+      const view = new DataView(bytes.buffer);
+      let offset = 1;
+      const keyLength = view.getUint32(offset);
+      const key = bytes.slice(offset + 4, offset + 4 + keyLength);
+      offset += 4 + keyLength;
+      const numAttributes = view.getUint32(offset);
+      offset += 4;
+      for (let i = 0; i < numAttributes; i++) {
+        offset += 4 + view.getUint32(offset);
+      }
+      // 有効期限の数（0）
+      offset += 4;
+      const authority = bytes.slice(offset + 1, offset + 1 + bytes[offset]);
+      offset += 1 + authority.length + 4;
+      return { key, authority, lsssKey: bytes.slice(offset) };
+    }
+
+    /**
+     * 2人の鍵の属性を組み合わせた（結託した）鍵の文字列を組み立てる
+     * 鍵コンポーネントは各鍵のものを並べ、LSSS鍵のK・Lはfirstのものを使う
+     */
+    function colludedKeyString(first: ABEPrivateKey, second: ABEPrivateKey): string {
+      const a = splitKeyString(first);
+      const b = splitKeyString(second);
+      const attributes = [...first.attributes, ...second.attributes].map((attribute) => new TextEncoder().encode(attribute));
+      const key = new Uint8Array([...a.key, ...b.key.slice(2)]);
+      const lsssKey = new Uint8Array([...a.lsssKey, ...b.lsssKey.slice(130 + 65)]);
+
+      const u32 = (value: number) => {
+        const bytes = new Uint8Array(4);
+        new DataView(bytes.buffer).setUint32(0, value);
+        return bytes;
+      };
+      const bytes = new Uint8Array([
+        0x02,
+        ...u32(key.length),
+        ...key,
+        ...u32(attributes.length),
+        ...attributes.flatMap((attribute) => [...u32(attribute.length), ...attribute]),
+        ...u32(0),
+        a.authority.length,
+        ...a.authority,
+        ...u32(lsssKey.length),
+        ...lsssKey,
+      ]);
+      return Buffer.from(bytes).toString("base64url");
+    }
+
+    it("should not let two users combine their attributes to satisfy a boolean policy", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const alice = await extractABEKey(masterKey, ["A"]);
+      const bob = await extractABEKey(masterKey, ["B"]);
+      const colluded = await importABEPrivateKeyFromString(colludedKeyString(alice, bob));
+      expect(colluded.attributes).toEqual(["A", "B"]);
+
+      // 1人で両方の属性を持つ鍵は復号できる
+      const both = await extractABEKey(masterKey, ["A", "B"]);
+      const andPolicy = await encryptABE(publicParams, "A AND B", message);
+      expect(await decryptABE(both, andPolicy)).toEqual(message);
+
+      // 結託した鍵は属性の照合を通るが、LSSS鍵の乱数が異なるため対称鍵が一致しない
+      await expect(decryptABE(colluded, andPolicy)).rejects.toThrow(/認証タグが一致しません/);
+      const threshold = await encryptABEThreshold(publicParams, ["A", "B", "C"], 2, message);
+      await expect(decryptABE(colluded, threshold)).rejects.toThrow(/認証タグが一致しません/);
+    });
+
+    it("should leave comma-separated policies without collusion resistance, as documented", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const alice = await extractABEKey(masterKey, ["A"]);
+      const bob = await extractABEKey(masterKey, ["B"]);
+      const colluded = await importABEPrivateKeyFromString(colludedKeyString(alice, bob));
+
+      // 鍵コンポーネントαH(attr)は鍵ごとの乱数を含まないため、組み合わせた鍵でも復号できてしまう
+      expect(await decryptABE(colluded, await encryptABE(publicParams, "A,B", message))).toEqual(message);
+    });
+
+    it("should issue a different LSSS key on every key generation", async () => {
+      const { masterKey } = await generateABEKeyPair();
+      const first = await extractABEKey(masterKey, ["A"]);
+      const second = await extractABEKey(masterKey, ["A"]);
+
+      expect(first.key).toEqual(second.key);
+      expect(splitKeyString(first).lsssKey).not.toEqual(splitKeyString(second).lsssKey);
+    });
+
+    it("should report INCOMPATIBLE_FORMAT for a key without an LSSS key", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A AND B", message);
+
+      // from_bytesで復元した鍵にはLSSS鍵がない
+      const imported = await importABEPrivateKey(privateKey.key, privateKey.attributes);
+      const error = await decryptABE(imported, ciphertext).catch((error: unknown) => error);
+      expect((error as { code?: unknown }).code).toBe("INCOMPATIBLE_FORMAT");
+      expect(await decryptABE(imported, await encryptABE(publicParams, "A,B", message))).toEqual(message);
+    });
+
+    it("should reject the retired threshold and LSSS tags", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A AND B", message);
+
+      for (const tag of [0x03, 0x05]) {
+        const retired = new Uint8Array(ciphertext);
+        retired[0] = tag | 0x20;
+        const error = await decryptABE(privateKey, retired).catch((error: unknown) => error);
+        expect((error as { code?: unknown }).code).toBe("INCOMPATIBLE_FORMAT");
+        await expect(upgradeABECiphertext(retired, privateKey, publicParams)).rejects.toThrow(/廃止した形式/);
+      }
+    });
+  });
+
+  describe("master key import", () => {
+    // BN254の曲線の位数（ビッグエンディアン）
+    const CURVE_ORDER = BigInt(
+      "0x2523648240000001BA344D8000000007FF9F800000000010A10000000000000D"
+    );
+
+    function toBytes(value: bigint): Uint8Array {
+      const bytes = new Uint8Array(32);
+      for (let i = 31; i >= 0; i--) {
+        bytes[i] = Number(value & 0xffn);
+        value >>= 8n;
+      }
+      return bytes;
+    }
+
+    it("should round-trip a generated master key", async () => {
+      const { masterKey } = await generateABEKeyPair();
+
+      const imported = await importABEMasterKey(masterKey.secret);
+
+      expect(imported.secret).toEqual(masterKey.secret);
+      await expect(extractABEKey(imported, ["A"])).resolves.toBeDefined();
+    });
+
+    it("should accept the largest valid scalar", async () => {
+      await expect(importABEMasterKey(toBytes(CURVE_ORDER - 1n))).resolves.toBeDefined();
+    });
+
+    it("should reject zero", async () => {
+      await expect(importABEMasterKey(new Uint8Array(32))).rejects.toThrow();
+    });
+
+    it("should reject the curve order", async () => {
+      await expect(importABEMasterKey(toBytes(CURVE_ORDER))).rejects.toThrow();
+    });
+
+    it("should reject values above the curve order", async () => {
+      await expect(importABEMasterKey(toBytes(CURVE_ORDER + 1n))).rejects.toThrow();
+      await expect(importABEMasterKey(new Uint8Array(32).fill(0xff))).rejects.toThrow();
+    });
+
+    it("should explain when public params are passed as a master key", async () => {
+      const { publicParams } = await generateABEKeyPair();
+
+      await expect(importABEMasterKey(publicParams.params)).rejects.toThrow(
+        /公開パラメータのように見えます/
+      );
+    });
+
+    it("should explain when a master key is passed as public params", async () => {
+      const { masterKey } = await generateABEKeyPair();
+
+      await expect(importABEPublicParams(masterKey.secret)).rejects.toThrow(
+        /マスター秘密鍵のように見えます/
+      );
+    });
+  });
+
+  describe("key identifier", () => {
+    it("should derive the same KID for the same public params", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const kid = await getABEKeyId(publicParams);
+
+      expect(kid).toMatch(/^[A-Za-z0-9_-]{22}$/);
+      expect(await getABEKeyId(await deriveABEPublicParams(masterKey))).toBe(kid);
+    });
+
+    it("should derive distinct KIDs for distinct authorities", async () => {
+      const first = await generateABEKeyPair();
+      const second = await generateABEKeyPair();
+
+      expect(await getABEKeyId(first.publicParams)).not.toBe(await getABEKeyId(second.publicParams));
+    });
+  });
+
+  describe("random bytes", () => {
+    it("should return the requested number of bytes", async () => {
+      expect((await getABERandomBytes(32)).length).toBe(32);
+      expect((await getABERandomBytes(1)).length).toBe(1);
+      expect((await getABERandomBytes(0)).length).toBe(0);
+    });
+
+    it("should return different bytes on successive calls", async () => {
+      const first = await getABERandomBytes(32);
+      const second = await getABERandomBytes(32);
+
+      expect(first).not.toEqual(second);
+    });
+  });
+
+  describe("curve point validation", () => {
+    const message = new TextEncoder().encode("Hello, ABE!");
+
+    // 0x04の後に任意のバイト列を並べた、曲線上にない非圧縮形式の点
+    const garbagePoint = (size: number, fill: number): Uint8Array => {
+      const bytes = new Uint8Array(size).fill(fill);
+      bytes[0] = 0x04;
+      return bytes;
+    };
+
+    it("should reject a ciphertext whose C0 is not a curve point", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      // header (2バイト) || num_attrs (1バイト) || valid_until (8バイト) || C0 (65バイト) || ...
+      for (const fill of [0x00, 0x5a, 0xff]) {
+        const tampered = new Uint8Array(ciphertext);
+        tampered.set(garbagePoint(65, fill), 2 + 1 + 8);
+        await expect(decryptABE(privateKey, tampered)).rejects.toThrow(/G1の点が不正です/);
+        expect(await tryDecryptABE(privateKey, tampered)).toBeUndefined();
+      }
+    });
+
+    it("should reject a private key whose components are not curve points", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      // ヘッダ（2バイト）の後に、非圧縮形式のG2の点（130バイト）が属性の順に並ぶ
+      for (const fill of [0x00, 0x5a, 0xff]) {
+        const garbageKey = new Uint8Array(privateKey.key);
+        garbageKey.set(garbagePoint(130, fill), 2 + 130);
+        const imported = await importABEPrivateKey(garbageKey, ["A", "B"]);
+        await expect(decryptABE(imported, ciphertext)).rejects.toThrow(/G2の点が不正です/);
+      }
+    });
+
+    it("should reject a non-canonical C0 encoding only in strict mode", async () => {
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const ciphertext = await encryptABE(publicParams, "A,B", message);
+
+      // 非圧縮形式（0x04 || x || y）の先頭バイトを圧縮形式（0x02・0x03 + yの偶奇）に書き換える
+      // 同じ点として読めるが、再直列化すると0x04になるため正規のエンコーディングではない
+      const c0Start = 2 + 1 + 8;
+      const nonCanonical = new Uint8Array(ciphertext);
+      nonCanonical[c0Start] = 0x02 | (ciphertext[c0Start + 64] & 1);
+
+      expect(await decryptABE(privateKey, nonCanonical)).toEqual(message);
+      expect(await decryptABE(privateKey, nonCanonical, false)).toEqual(message);
+      await expect(decryptABE(privateKey, nonCanonical, true)).rejects.toThrow(/非正規なG1の点のエンコーディングです/);
+
+      // 正規のエンコーディングはstrictモードでも復号できる
+      expect(await decryptABE(privateKey, ciphertext, true)).toEqual(message);
+    });
+
+    it("should reject public params that are not a curve point", async () => {
+      for (const fill of [0x00, 0x5a, 0xff]) {
+        await expect(importABEPublicParams(garbagePoint(65, fill))).rejects.toThrow(/有効な点ではありません/);
+      }
+    });
+  });
+});
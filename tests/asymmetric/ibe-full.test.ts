@@ -4,14 +4,57 @@
  * Boneh-Franklin IBEスキームの動作確認
  */
 
+import { gcm } from "@noble/ciphers/aes.js";
 import { beforeAll, describe, expect, it } from "vitest";
 import {
   decryptIBE,
+  decryptIBEFromString,
+  decryptIBEHybrid,
+  decryptECIES,
+  decryptIBEInto,
+  decryptIBELegacy,
+  decryptIBEPoint,
+  deriveIBEPublicParams,
+  encryptECIES,
   encryptIBE,
+  encryptIBEHybrid,
+  encryptIBEToString,
+  encryptIBEDeterministic,
+  encryptIBEPoint,
+  encryptIBEPrehashed,
   extractIBEKey,
+  extractIBEKeyPrehashed,
+  generateECIESKeyPair,
   generateIBEKeyPair,
+  generateIBEKeyPairFromSeed,
+  getIBEKeyId,
+  getIBERandomBytes,
+  importIBEMasterKey,
+  importIBEPublicParams,
   initIBE,
+  prehashIBEIdentity,
+  setIBEEventCallback,
+  tryDecryptIBE,
+  tryDecryptIBEHybrid,
+  type IBESecurityEvent,
 } from "../../src/asymmetric/ibe.js";
+import { hashSHA256 } from "../../src/hash/sha256.js";
+import { generateKyberKeyPair } from "../../src/post-quantum/kyber.js";
+import { bytesToHex, hexToBytes } from "../../src/utils/format.js";
+
+// ハイブリッド暗号文の先頭（version (1バイト) || ML-KEM-768の暗号文 (1088バイト)）のサイズ
+const HYBRID_KEM_PREFIX_SIZE = 1 + 1088;
+
+/**
+ * ハイブリッド暗号文からペアリング層の暗号文を取り出す
+ * （KEM暗号文の後に4バイトのビッグエンディアンの長さ、続いてペアリング層の暗号文）
+ */
+function pairingLayerOf(ciphertext: Uint8Array): Uint8Array {
+  const view = new DataView(ciphertext.buffer, ciphertext.byteOffset, ciphertext.byteLength);
+  const length = view.getUint32(HYBRID_KEM_PREFIX_SIZE);
+  const start = HYBRID_KEM_PREFIX_SIZE + 4;
+  return ciphertext.slice(start, start + length);
+}
 
 describe("IBE Full Implementation", () => {
   beforeAll(async () => {
@@ -36,6 +79,8 @@ describe("IBE Full Implementation", () => {
 
     expect(privateKey).toBeDefined();
     expect(privateKey.key.length).toBeGreaterThan(0);
+    // 復号時の鍵導出に使うアイデンティティを保持している
+    expect(privateKey.identity).toBe(identity);
   });
 
   it("should encrypt and decrypt message", async () => {
@@ -71,12 +116,1094 @@ describe("IBE Full Implementation", () => {
     // identity2の秘密鍵で復号化を試みる（失敗するはず）
     const wrongPrivateKey = await extractIBEKey(masterKey, identity2);
 
-    // 復号化は成功するが、結果は異なる（またはエラーになる）
-    // 注意: Boneh-Franklinスキームでは、異なるアイデンティティの鍵でも
-    // 復号化は技術的に可能だが、結果は無意味なデータになる
-    const decrypted = await decryptIBE(wrongPrivateKey, ciphertext);
+    // 異なるアイデンティティの鍵では対称鍵が一致せず、AES-256-GCMの認証タグの検証に失敗する
+    await expect(decryptIBE(wrongPrivateKey, ciphertext)).rejects.toThrow(/authentication failed/);
+  });
+
+  it("should produce identical ciphertexts in deterministic mode", async () => {
+    const { masterKey, publicParams } = await generateIBEKeyPair();
+    const identity = "user@example.com";
+    const message = new TextEncoder().encode("Hello, SIV!");
+    const messageKey = new Uint8Array(32).fill(7);
+
+    // 同じアイデンティティ・同じメッセージは同じ暗号文になる
+    const ciphertext1 = await encryptIBEDeterministic(publicParams, identity, message, messageKey);
+    const ciphertext2 = await encryptIBEDeterministic(publicParams, identity, message, messageKey);
+    expect(ciphertext1).toEqual(ciphertext2);
+
+    // 通常のdecryptで復号できる
+    const privateKey = await extractIBEKey(masterKey, identity);
+    const decrypted = await decryptIBE(privateKey, ciphertext1);
+    expect(new TextDecoder().decode(decrypted)).toBe("Hello, SIV!");
+
+    // メッセージが異なれば暗号文も異なる
+    const other = await encryptIBEDeterministic(
+      publicParams,
+      identity,
+      new TextEncoder().encode("Hello, SIV?"),
+      messageKey
+    );
+    expect(other).not.toEqual(ciphertext1);
+  });
+
+  it("should reject a short message key in deterministic mode", async () => {
+    const { publicParams } = await generateIBEKeyPair();
+    const message = new TextEncoder().encode("Hello, SIV!");
+
+    await expect(
+      encryptIBEDeterministic(publicParams, "user@example.com", message, new Uint8Array(16))
+    ).rejects.toThrow();
+  });
+
+  it("should round-trip through the base64url string form", async () => {
+    const { masterKey, publicParams } = await generateIBEKeyPair();
+    const privateKey = await extractIBEKey(masterKey, "user@example.com");
+    const message = new TextEncoder().encode("Ciphertext in a URL");
+
+    const encoded = await encryptIBEToString(publicParams, "user@example.com", message);
+    // URLにそのまま埋め込める文字だけで構成され、パディングを含まない
+    expect(encoded).toMatch(/^[A-Za-z0-9_-]+$/);
+    expect(await decryptIBEFromString(privateKey, encoded)).toEqual(message);
+
+    // 改行・空白やパディングが付いていても復号できる
+    const wrapped = `  ${encoded.slice(0, 40)}\n${encoded.slice(40)}==\n`;
+    expect(await decryptIBEFromString(privateKey, wrapped)).toEqual(message);
+  });
+
+  it("should reject a string that is not base64url", async () => {
+    const { masterKey } = await generateIBEKeyPair();
+    const privateKey = await extractIBEKey(masterKey, "user@example.com");
+
+    await expect(decryptIBEFromString(privateKey, "not+base64/url")).rejects.toThrow();
+  });
+
+  describe("take_bytes", () => {
+    it("should move out the same bytes as the getters and leave the keys empty", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, "user@example.com");
+
+      const secret = masterKey.secret;
+      const params = publicParams.params;
+      const key = privateKey.key;
+
+      expect(masterKey.take_bytes()).toEqual(secret);
+      expect(publicParams.take_bytes()).toEqual(params);
+      expect(privateKey.take_bytes()).toEqual(key);
+
+      // 取り出した後は空になり、2回目以降も空を返す
+      expect(masterKey.secret.length).toBe(0);
+      expect(publicParams.params.length).toBe(0);
+      expect(privateKey.key.length).toBe(0);
+      expect(privateKey.take_bytes().length).toBe(0);
+    });
+
+    it("should reject operations with keys whose bytes were taken", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, "user@example.com");
+      const ciphertext = await encryptIBE(publicParams, "user@example.com", new Uint8Array([1, 2, 3]));
+
+      privateKey.take_bytes();
+      publicParams.take_bytes();
+      masterKey.take_bytes();
+
+      await expect(decryptIBE(privateKey, ciphertext)).rejects.toThrow(/Invalid private key length/);
+      await expect(encryptIBE(publicParams, "user@example.com", new Uint8Array([1]))).rejects.toThrow(
+        /Invalid public params length/
+      );
+      await expect(extractIBEKey(masterKey, "user@example.com")).rejects.toThrow(/Invalid master key length/);
+    });
+  });
+
+  describe("zeroize", () => {
+    it("should wipe the secret bytes and reject later operations", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, "user@example.com");
+      const ciphertext = await encryptIBE(publicParams, "user@example.com", new Uint8Array([1, 2, 3]));
+
+      privateKey.zeroize();
+      masterKey.zeroize();
+
+      expect(privateKey.key.length).toBe(0);
+      expect(masterKey.secret.length).toBe(0);
+      await expect(decryptIBE(privateKey, ciphertext)).rejects.toThrow(/Invalid private key length/);
+      await expect(extractIBEKey(masterKey, "user@example.com")).rejects.toThrow(/Invalid master key length/);
+    });
+  });
+
+  describe("clone", () => {
+    it("should produce keys that behave identically to the originals", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, "user@example.com", "try_and_increment");
+      const message = new TextEncoder().encode("Hello, clone!");
+
+      const clonedKey = privateKey.clone();
+      expect(clonedKey.key).toEqual(privateKey.key);
+      expect(clonedKey.identity).toBe(privateKey.identity);
+      expect(clonedKey.hash_to_curve).toBe("try_and_increment");
+
+      const ciphertext = await encryptIBE(publicParams.clone(), "user@example.com", message, "try_and_increment");
+      expect(await decryptIBE(clonedKey, ciphertext)).toEqual(message);
+      expect(await decryptIBE(privateKey, ciphertext)).toEqual(message);
+
+      // マスター鍵の複製からも同じ鍵を抽出できる
+      const fromClone = await extractIBEKey(masterKey.clone(), "user@example.com", "try_and_increment");
+      expect(fromClone.key).toEqual(privateKey.key);
+    });
+
+    it("should keep the clone usable after the original is emptied or freed", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, "user@example.com");
+      const ciphertext = await encryptIBE(publicParams, "user@example.com", new TextEncoder().encode("data"));
+
+      const clonedKey = privateKey.clone();
+      privateKey.take_bytes();
+      expect(await decryptIBE(clonedKey, ciphertext)).toEqual(new TextEncoder().encode("data"));
+
+      // 取り出した後の鍵の複製は空のまま
+      expect(privateKey.clone().key.length).toBe(0);
+
+      // 解放した鍵の複製はエラーになる
+      const freedKey = clonedKey.clone();
+      clonedKey.free();
+      expect(() => clonedKey.clone()).toThrow();
+      expect(await decryptIBE(freedKey, ciphertext)).toEqual(new TextEncoder().encode("data"));
+    });
+  });
+
+  describe("seeded setup", () => {
+    const seed = new Uint8Array(32).fill(0x42);
+
+    it("should derive the same master key pair from the same seed", async () => {
+      const first = await generateIBEKeyPairFromSeed(seed);
+      const second = await generateIBEKeyPairFromSeed(seed);
+
+      expect(first.masterKey.secret).toEqual(second.masterKey.secret);
+      expect(first.publicParams.params).toEqual(second.publicParams.params);
+      expect((await deriveIBEPublicParams(first.masterKey)).params).toEqual(first.publicParams.params);
+    });
+
+    it("should derive different master keys from different seeds", async () => {
+      const other = new Uint8Array(seed);
+      other[31] ^= 0x01;
+
+      const first = await generateIBEKeyPairFromSeed(seed);
+      const second = await generateIBEKeyPairFromSeed(other);
+
+      expect(first.masterKey.secret).not.toEqual(second.masterKey.secret);
+    });
+
+    it("should produce a usable master key pair", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPairFromSeed(seed);
+      const privateKey = await extractIBEKey(masterKey, "user@example.com");
+      const message = new TextEncoder().encode("Hello, seeded IBE!");
+
+      const ciphertext = await encryptIBE(publicParams, "user@example.com", message);
+      expect(await decryptIBE(privateKey, ciphertext)).toEqual(message);
+    });
+
+    it("should reject seeds of the wrong length or all zeros", async () => {
+      await expect(generateIBEKeyPairFromSeed(new Uint8Array(31).fill(1))).rejects.toThrow(
+        /Invalid seed length: expected 32, got 31/
+      );
+      await expect(generateIBEKeyPairFromSeed(new Uint8Array(32))).rejects.toThrow(/all-zero seed/);
+    });
+  });
+
+  describe("master key import", () => {
+    // BN254の曲線の位数（ビッグエンディアン）
+    const CURVE_ORDER = BigInt(
+      "0x2523648240000001BA344D8000000007FF9F800000000010A10000000000000D"
+    );
+
+    function toBytes(value: bigint): Uint8Array {
+      const bytes = new Uint8Array(32);
+      for (let i = 31; i >= 0; i--) {
+        bytes[i] = Number(value & 0xffn);
+        value >>= 8n;
+      }
+      return bytes;
+    }
+
+    it("should round-trip a generated master key", async () => {
+      const { masterKey } = await generateIBEKeyPair();
+
+      const imported = await importIBEMasterKey(masterKey.secret);
+
+      expect(imported.secret).toEqual(masterKey.secret);
+      await expect(extractIBEKey(imported, "user@example.com")).resolves.toBeDefined();
+    });
+
+    it("should accept the largest valid scalar", async () => {
+      await expect(importIBEMasterKey(toBytes(CURVE_ORDER - 1n))).resolves.toBeDefined();
+    });
+
+    it("should reject zero", async () => {
+      await expect(importIBEMasterKey(new Uint8Array(32))).rejects.toThrow();
+    });
+
+    it("should reject the curve order", async () => {
+      await expect(importIBEMasterKey(toBytes(CURVE_ORDER))).rejects.toThrow();
+    });
+
+    it("should reject values above the curve order", async () => {
+      await expect(importIBEMasterKey(toBytes(CURVE_ORDER + 1n))).rejects.toThrow();
+      await expect(importIBEMasterKey(new Uint8Array(32).fill(0xff))).rejects.toThrow();
+    });
+
+    it("should explain when public params are passed as a master key", async () => {
+      const { publicParams } = await generateIBEKeyPair();
+
+      await expect(importIBEMasterKey(publicParams.params)).rejects.toThrow(
+        /looks like public params, not master key/
+      );
+    });
+
+    it("should explain when a master key is passed as public params", async () => {
+      const { masterKey } = await generateIBEKeyPair();
+
+      await expect(importIBEPublicParams(masterKey.secret)).rejects.toThrow(
+        /looks like a master key, not public params/
+      );
+    });
+  });
+
+  describe("interop test vectors", () => {
+    // 他言語の実装が一致を確認するための固定ベクトル
+    // マスター秘密鍵は32バイトのビッグエンディアンのスカラー、
+    // 公開パラメータは非圧縮形式（0x04 || x || y、x・yは32バイトのビッグエンディアン）
+    const VECTORS = [
+      {
+        scalar: "0000000000000000000000000000000000000000000000000000000000000001",
+        // 生成元 P = (-1, 1)
+        publicParams:
+          "04" +
+          "2523648240000001ba344d80000000086121000000000013a700000000000012" +
+          "0000000000000000000000000000000000000000000000000000000000000001",
+      },
+      {
+        scalar: "0000000000000000000000000000000000000000000000000000000000000002",
+        publicParams:
+          "04" +
+          "0948d920900000006e8d1360000000021848400000000004e9c0000000000009" +
+          "17361ed1680000011460b070000000053cb4a0000000000c4860000000000003",
+      },
+      {
+        scalar: "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20",
+        publicParams:
+          "04" +
+          "1e188092e2ed77e30aec282e99b11620ce70233160017897223d81f59068aa5e" +
+          "07a0cc140b65687f722353bf3adce4aea50401d71c7f435702001697448da2c6",
+      },
+    ];
+
+    it("should serialize a known scalar to the documented bytes", async () => {
+      const masterKey = await importIBEMasterKey(hexToBytes(VECTORS[0].scalar));
+
+      const expected = new Uint8Array(32);
+      expected[31] = 1;
+      expect(masterKey.secret).toEqual(expected);
+    });
+
+    it("should derive the documented public params from each scalar", async () => {
+      for (const vector of VECTORS) {
+        const masterKey = await importIBEMasterKey(hexToBytes(vector.scalar));
+        const publicParams = await deriveIBEPublicParams(masterKey);
+
+        expect(masterKey.secret).toEqual(hexToBytes(vector.scalar));
+        expect(publicParams.params).toEqual(hexToBytes(vector.publicParams));
+      }
+    });
+
+    it("should round-trip the documented public params byte for byte", async () => {
+      for (const vector of VECTORS) {
+        const publicParams = await importIBEPublicParams(hexToBytes(vector.publicParams));
+        expect(publicParams.params).toEqual(hexToBytes(vector.publicParams));
+      }
+    });
+
+    it("should derive the same public params as setup", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+
+      const derived = await deriveIBEPublicParams(masterKey);
+
+      expect(derived.params).toEqual(publicParams.params);
+    });
+  });
+
+  describe("hash-to-curve selection", () => {
+    const identity = "user@example.com";
+    const message = new TextEncoder().encode("Hello, interop!");
+    const methods = ["rfc9380", "try_and_increment", "legacy"] as const;
+
+    it("should default to the RFC 9380 suite and record it in the key and ciphertext", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      expect(privateKey.hash_to_curve).toBe("rfc9380");
+      // 方式のID (0x01) にAES-256-GCMのフラグ (0x10) を立てる
+      expect(ciphertext[0]).toBe(0x11);
+      // [ID] || U || nonce (12バイト) || 暗号文 || 認証タグ (16バイト)
+      expect(ciphertext.length).toBe(1 + 65 + 12 + message.length + 16);
+    });
+
+    it("should encrypt with AES-256-GCM for the legacy method too", async () => {
+      const { publicParams } = await generateIBEKeyPair();
+      const ciphertext = await encryptIBE(publicParams, identity, message, "legacy");
+
+      // 旧方式のID (0x03) にAES-256-GCMのフラグ (0x10) を立てる
+      expect(ciphertext[0]).toBe(0x13);
+      expect(ciphertext.length).toBe(1 + 65 + 12 + message.length + 16);
+    });
+
+    it("should reject the legacy method id without the AES-256-GCM flag", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity, "legacy");
+      const ciphertext = await encryptIBE(publicParams, identity, message, "legacy");
+      ciphertext[0] = 0x03;
+
+      await expect(decryptIBE(privateKey, ciphertext)).rejects.toThrow(/missing the AES-256-GCM flag/);
+    });
+
+    it("should decrypt with a key using the same method", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+
+      for (const method of methods) {
+        const privateKey = await extractIBEKey(masterKey, identity, method);
+        const ciphertext = await encryptIBE(publicParams, identity, message, method);
+
+        expect(privateKey.hash_to_curve).toBe(method);
+        expect(await decryptIBE(privateKey, ciphertext)).toEqual(message);
+      }
+    });
+
+    it("should only decrypt with a key using the same method", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+
+      for (const encryptMethod of methods) {
+        const ciphertext = await encryptIBE(publicParams, identity, message, encryptMethod);
+        for (const keyMethod of methods.filter((method) => method !== encryptMethod)) {
+          const privateKey = await extractIBEKey(masterKey, identity, keyMethod);
+          await expect(decryptIBE(privateKey, ciphertext)).rejects.toThrow(/Hash-to-curve method mismatch/);
+        }
+      }
+    });
+
+    it("should map identities to different keys under each method", async () => {
+      const { masterKey } = await generateIBEKeyPair();
+
+      const keys = await Promise.all(methods.map((method) => extractIBEKey(masterKey, identity, method)));
+
+      expect(keys[0].key).not.toEqual(keys[1].key);
+      expect(keys[0].key).not.toEqual(keys[2].key);
+      expect(keys[1].key).not.toEqual(keys[2].key);
+    });
+
+    it("should reject unknown methods", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+
+      await expect(extractIBEKey(masterKey, identity, "sswu" as never)).rejects.toThrow(
+        /Unknown hash-to-curve method/
+      );
+      await expect(encryptIBE(publicParams, identity, message, "sswu" as never)).rejects.toThrow(
+        /Unknown hash-to-curve method/
+      );
+    });
+  });
+
+  describe("identity pre-hashing", () => {
+    // 1 MBのアイデンティティ
+    const longIdentity = "x".repeat(1024 * 1024);
+    const message = new TextEncoder().encode("Hello, long identity!");
+
+    it("should pre-hash identities with SHA-256", async () => {
+      const digest = await prehashIBEIdentity(longIdentity);
+
+      expect(digest.length).toBe(32);
+      expect(digest).toEqual(await hashSHA256(new TextEncoder().encode(longIdentity)));
+    });
+
+    it("should extract the same key from a 1 MB identity and its pre-hash", async () => {
+      const { masterKey } = await generateIBEKeyPair();
+      const digest = await prehashIBEIdentity(longIdentity);
+
+      for (const method of ["rfc9380", "try_and_increment"] as const) {
+        const fromIdentity = await extractIBEKey(masterKey, longIdentity, method);
+        const fromDigest = await extractIBEKeyPrehashed(masterKey, digest, method);
+
+        expect(fromDigest.key).toEqual(fromIdentity.key);
+        expect(fromDigest.hash_to_curve).toBe(method);
+        expect(fromDigest.identity).toBe("");
+      }
+    });
+
+    it("should encrypt to a 1 MB identity and its pre-hash interchangeably", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const digest = await prehashIBEIdentity(longIdentity);
+
+      const fromIdentity = await extractIBEKey(masterKey, longIdentity);
+      const fromDigest = await extractIBEKeyPrehashed(masterKey, digest);
+
+      const ciphertext = await encryptIBE(publicParams, longIdentity, message);
+      const prehashedCiphertext = await encryptIBEPrehashed(publicParams, digest, message);
+
+      expect(prehashedCiphertext[0]).toBe(ciphertext[0]);
+      expect(prehashedCiphertext.length).toBe(ciphertext.length);
+      expect(await decryptIBE(fromDigest, ciphertext)).toEqual(message);
+      expect(await decryptIBE(fromIdentity, prehashedCiphertext)).toEqual(message);
+    });
+
+    it("should not decrypt ciphertexts for a different identity's pre-hash", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKeyPrehashed(masterKey, await prehashIBEIdentity(longIdentity));
+
+      const ciphertext = await encryptIBEPrehashed(publicParams, await prehashIBEIdentity(`${longIdentity}y`), message);
+
+      await expect(decryptIBE(privateKey, ciphertext)).rejects.toThrow(/authentication failed/);
+    });
+
+    it("should reject pre-hashes of the wrong length", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+
+      await expect(extractIBEKeyPrehashed(masterKey, new Uint8Array(31))).rejects.toThrow(
+        /Invalid identity digest length: expected 32, got 31/
+      );
+      await expect(encryptIBEPrehashed(publicParams, new Uint8Array(33), message)).rejects.toThrow(
+        /Invalid identity digest length: expected 32, got 33/
+      );
+    });
+
+    it("should reject pre-hashes with the legacy method", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const digest = await prehashIBEIdentity(longIdentity);
+
+      await expect(extractIBEKeyPrehashed(masterKey, digest, "legacy")).rejects.toThrow(
+        /not supported by the legacy hash-to-curve method/
+      );
+      await expect(encryptIBEPrehashed(publicParams, digest, message, "legacy")).rejects.toThrow(
+        /not supported by the legacy hash-to-curve method/
+      );
+    });
+  });
+
+  describe("decrypt into a caller buffer", () => {
+    const identity = "user@example.com";
+    const message = new TextEncoder().encode("Hello, caller-provided buffer! (longer than one 32-byte key block)");
+
+    it("should write the same plaintext as decrypt and return its length", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      const out = new Uint8Array(message.length);
+      const written = await decryptIBEInto(privateKey, ciphertext, out);
+
+      expect(written).toBe(message.length);
+      expect(out).toEqual(await decryptIBE(privateKey, ciphertext));
+    });
+
+    it("should leave the rest of a larger buffer untouched", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      const out = new Uint8Array(message.length + 16).fill(0xaa);
+      const written = await decryptIBEInto(privateKey, ciphertext, out);
+
+      expect(out.slice(0, written)).toEqual(message);
+      expect(out.slice(written).every((byte) => byte === 0xaa)).toBe(true);
+    });
+
+    it("should reuse one buffer across repeated decrypts", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const out = new Uint8Array(64);
+
+      for (const text of ["first", "second message", "third"]) {
+        const plaintext = new TextEncoder().encode(text);
+        const ciphertext = await encryptIBE(publicParams, identity, plaintext);
+        const written = await decryptIBEInto(privateKey, ciphertext, out);
+        expect(new TextDecoder().decode(out.subarray(0, written))).toBe(text);
+      }
+    });
+
+    it("should reject a buffer that is too small without writing to it", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      const out = new Uint8Array(message.length - 1);
+      await expect(decryptIBEInto(privateKey, ciphertext, out)).rejects.toThrow(
+        /Output buffer too small/
+      );
+      expect(out.every((byte) => byte === 0)).toBe(true);
+    });
+
+    it("should reject a tampered ciphertext and zero the plaintext area", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+      const tampered = ciphertext.slice();
+      tampered[tampered.length - 1] ^= 0x01;
+
+      const out = new Uint8Array(message.length).fill(0xaa);
+      await expect(decryptIBEInto(privateKey, tampered, out)).rejects.toThrow(/authentication failed/);
+      expect(out.every((byte) => byte === 0)).toBe(true);
+    });
+  });
+
+  describe("authenticated payload (AES-256-GCM)", () => {
+    const identity = "user@example.com";
+    // 32バイトを超えるメッセージ（旧形式では対称鍵が繰り返されていた）
+    const message = new TextEncoder().encode("A message that is much longer than a single 32-byte symmetric key.");
+
+    it("should round-trip messages longer than the symmetric key", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      expect(await decryptIBE(privateKey, ciphertext)).toEqual(message);
+    });
+
+    it("should not repeat the keystream across 32-byte blocks", async () => {
+      const { publicParams } = await generateIBEKeyPair();
+      const zeros = new Uint8Array(64);
+      const ciphertext = await encryptIBE(publicParams, identity, zeros);
+
+      // 旧形式では平文が0の場合、Vの前半と後半が同じ対称鍵になっていた
+      const body = ciphertext.slice(1 + 65 + 12, 1 + 65 + 12 + 64);
+      expect(body.slice(0, 32)).not.toEqual(body.slice(32));
+    });
+
+    it("should not turn a 1 KB zero message into a repeating 32-byte pattern", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const zeros = new Uint8Array(1024);
+      const ciphertext = await encryptIBE(publicParams, identity, zeros);
+
+      expect(await decryptIBE(privateKey, ciphertext)).toEqual(zeros);
+      const body = ciphertext.slice(1 + 65 + 12, 1 + 65 + 12 + 1024);
+      for (let offset = 32; offset < body.length; offset += 32) {
+        expect(body.slice(offset, offset + 32)).not.toEqual(body.slice(0, 32));
+      }
+    });
+
+    it("should use a fresh nonce for every encryption", async () => {
+      const { publicParams } = await generateIBEKeyPair();
+      const first = await encryptIBE(publicParams, identity, message);
+      const second = await encryptIBE(publicParams, identity, message);
+
+      expect(first.slice(1 + 65, 1 + 65 + 12)).not.toEqual(second.slice(1 + 65, 1 + 65 + 12));
+    });
+
+    it("should reject a ciphertext tampered anywhere in the V component", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      // nonce・暗号文・認証タグのそれぞれを1ビット反転させる
+      for (const offset of [1 + 65, 1 + 65 + 12, ciphertext.length - 1]) {
+        const tampered = ciphertext.slice();
+        tampered[offset] ^= 0x01;
+        await expect(decryptIBE(privateKey, tampered)).rejects.toThrow(/authentication failed/);
+      }
+    });
+
+    it("should reject a ciphertext tampered in the U component", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+      const other = await encryptIBE(publicParams, identity, message);
+
+      // 1バイト反転させたU（多くの場合は曲線上の点ではなくなる）
+      const flipped = ciphertext.slice();
+      flipped[1 + 65 - 1] ^= 0x01;
+      await expect(decryptIBE(privateKey, flipped)).rejects.toThrow();
+
+      // 別の暗号文の有効なUに差し替えても、認証タグで検出される
+      const swapped = ciphertext.slice();
+      swapped.set(other.slice(1, 1 + 65), 1);
+      await expect(decryptIBE(privateKey, swapped)).rejects.toThrow(/authentication failed/);
+    });
+
+    it("should reject a ciphertext too short to hold the nonce and tag", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      await expect(decryptIBE(privateKey, ciphertext.slice(0, 1 + 65 + 12 + 16))).rejects.toThrow(
+        /no payload/
+      );
+      await expect(decryptIBE(privateKey, ciphertext.slice(0, 1 + 65 + 20))).rejects.toThrow(
+        /Truncated ciphertext/
+      );
+    });
+
+    // 以前の形式（U || M ⊕ 対称鍵）の移行用の復号は、固定された暗号文（tests/regression/golden-vectors.test.ts）で確認する
+    it("should not create ciphertexts without the AES-256-GCM flag", async () => {
+      const { publicParams } = await generateIBEKeyPair();
+      const messageKey = new Uint8Array(32).fill(0x22);
+
+      for (const method of ["rfc9380", "try_and_increment", "legacy"] as const) {
+        const ciphertext = await encryptIBE(publicParams, identity, message, method);
+        const deterministic = await encryptIBEDeterministic(publicParams, identity, message, messageKey, method);
+
+        expect(ciphertext[0] & 0x10).toBe(0x10);
+        expect(deterministic[0] & 0x10).toBe(0x10);
+      }
+    });
+
+    it("should reject a ciphertext whose AES-256-GCM flag has been cleared", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+
+      for (const method of ["rfc9380", "try_and_increment", "legacy"] as const) {
+        const privateKey = await extractIBEKey(masterKey, identity, method);
+        const ciphertext = await encryptIBE(publicParams, identity, message, method);
+        const stripped = ciphertext.slice();
+        stripped[0] &= ~0x10;
+
+        await expect(decryptIBE(privateKey, stripped)).rejects.toThrow(/missing the AES-256-GCM flag/);
+        await expect(decryptIBEInto(privateKey, stripped, new Uint8Array(stripped.length))).rejects.toThrow(
+          /missing the AES-256-GCM flag/
+        );
+        await expect(decryptIBELegacy(privateKey, stripped)).rejects.toThrow(/missing the AES-256-GCM flag/);
+        expect(await tryDecryptIBE(privateKey, stripped)).toBeUndefined();
+      }
+    });
+
+    it("should not expose the AES-256-GCM key through the legacy migration function", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity, "legacy");
+      const ciphertext = await encryptIBE(publicParams, identity, message, "legacy");
+
+      // IDを外すと、旧方式のヘッダなしの旧形式（U || V）として読める
+      const headerless = ciphertext.slice(1);
+      await expect(decryptIBE(privateKey, headerless)).rejects.toThrow(/unauthenticated legacy format/);
+      expect(await tryDecryptIBE(privateKey, headerless)).toBeUndefined();
+
+      // 移行用の復号（V ⊕ 対称鍵）からは旧形式の対称鍵が分かるが、GCMの対称鍵とは別のラベルで導出するため、
+      // その鍵ではGCMの暗号文を復号できない
+      const u = headerless.slice(0, 65);
+      const v = headerless.slice(65);
+      const legacy = await decryptIBELegacy(privateKey, headerless);
+      const xorKey = legacy.slice(0, 32).map((byte, i) => byte ^ v[i]);
+      expect(() => gcm(xorKey, v.slice(0, 12), u).decrypt(v.slice(12))).toThrow();
+    });
+
+    it("should decrypt only unflagged ciphertexts with the legacy migration function", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      await expect(decryptIBELegacy(privateKey, ciphertext)).rejects.toThrow(/not in the legacy format/);
+    });
+  });
+
+  describe("trial decryption", () => {
+    const identity = "user@example.com";
+    const message = new TextEncoder().encode("Hello, mailbox!");
+
+    it("should return the plaintext on success", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      expect(await tryDecryptIBE(privateKey, ciphertext)).toEqual(message);
+    });
+
+    it("should return undefined instead of throwing for corrupt ciphertexts", async () => {
+      const { masterKey } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+
+      // 切り詰められた暗号文・Vコンポーネントのない暗号文
+      expect(await tryDecryptIBE(privateKey, new Uint8Array(10))).toBeUndefined();
+      expect(await tryDecryptIBE(privateKey, new Uint8Array(65))).toBeUndefined();
+    });
+
+    it("should return undefined for a key of a different identity", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const otherKey = await extractIBEKey(masterKey, "other@example.com");
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      expect(await tryDecryptIBE(otherKey, ciphertext)).toBeUndefined();
+    });
+
+    it("should decide hybrid ciphertexts by the integrity tag", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const otherKey = await extractIBEKey(masterKey, "other@example.com");
+      const kyber = await generateKyberKeyPair();
+      const otherKyber = await generateKyberKeyPair();
+
+      const ciphertext = await encryptIBEHybrid(publicParams, identity, kyber.publicKey, message);
+
+      expect(
+        await tryDecryptIBEHybrid(privateKey, kyber.privateKey, kyber.publicKey, ciphertext)
+      ).toEqual(message);
+      // アイデンティティの異なる鍵
+      expect(
+        await tryDecryptIBEHybrid(otherKey, kyber.privateKey, kyber.publicKey, ciphertext)
+      ).toBeUndefined();
+      // Kyberの鍵が異なる
+      expect(
+        await tryDecryptIBEHybrid(privateKey, otherKyber.privateKey, otherKyber.publicKey, ciphertext)
+      ).toBeUndefined();
+      // 改ざんされた暗号文
+      const tampered = ciphertext.slice();
+      tampered[tampered.length - 1] ^= 0x01;
+      expect(
+        await tryDecryptIBEHybrid(privateKey, kyber.privateKey, kyber.publicKey, tampered)
+      ).toBeUndefined();
+    });
+
+    it("should pick out the matching recipient among several keys", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const kyber = await generateKyberKeyPair();
+      const keys = await Promise.all(
+        ["alice@example.com", "bob@example.com", "carol@example.com"].map((id) =>
+          extractIBEKey(masterKey, id)
+        )
+      );
+
+      const ciphertext = await encryptIBEHybrid(publicParams, "bob@example.com", kyber.publicKey, message);
+
+      const results = await Promise.all(
+        keys.map((key) => tryDecryptIBEHybrid(key, kyber.privateKey, kyber.publicKey, ciphertext))
+      );
+      expect(results.map((result) => result !== undefined)).toEqual([false, true, false]);
+      expect(results[1]).toEqual(message);
+    });
+  });
+
+  describe("group element encryption", () => {
+    const identity = "user@example.com";
+
+    it("should round-trip random G1 and G2 points", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+
+      // 公開パラメータ（G1）と秘密鍵（G2）は、ランダムなスカラーを掛けた点
+      const other = await generateIBEKeyPair();
+      const g1 = other.publicParams.params;
+      const g2 = (await extractIBEKey(other.masterKey, identity)).key.slice(0, 130);
+
+      for (const point of [g1, g2]) {
+        const ciphertext = await encryptIBEPoint(publicParams, identity, point);
+        expect(await decryptIBEPoint(privateKey, ciphertext)).toEqual(point);
+      }
+    });
+
+    it("should refuse to encrypt bytes that are not a curve point", async () => {
+      const { publicParams } = await generateIBEKeyPair();
+      const invalid = new Uint8Array(65).fill(0x5a);
+      invalid[0] = 0x04;
+
+      await expect(encryptIBEPoint(publicParams, identity, invalid)).rejects.toThrow(/Invalid G1 point/);
+      await expect(encryptIBEPoint(publicParams, identity, new Uint8Array(64))).rejects.toThrow(
+        /Invalid point length/
+      );
+    });
+
+    it("should reject a decrypted value that is not a curve point", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const invalid = new Uint8Array(65).fill(0x5a);
+      invalid[0] = 0x04;
+
+      // 通常の暗号化で点でないバイト列を暗号化すると、復号はできても点として拒否される
+      const ciphertext = await encryptIBE(publicParams, identity, invalid);
+      expect(await decryptIBE(privateKey, ciphertext)).toEqual(invalid);
+      await expect(decryptIBEPoint(privateKey, ciphertext)).rejects.toThrow(/Invalid G1 point/);
+    });
+  });
+
+  describe("security events", () => {
+    const identity = "user@example.com";
+    const message = new TextEncoder().encode("top secret plaintext");
+
+    // コールバックを登録してrunを実行し、通知されたイベントを返す
+    const recordEvents = async (run: () => Promise<unknown>): Promise<IBESecurityEvent[]> => {
+      const events: IBESecurityEvent[] = [];
+      await setIBEEventCallback((event) => events.push({ event: event.event, detail: event.detail }));
+      try {
+        await run().catch(() => undefined);
+      } finally {
+        await setIBEEventCallback(undefined);
+      }
+      return events;
+    };
+
+    it("should emit a single decrypt_failed with a reason and no secret bytes", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const wrongKey = await extractIBEKey(masterKey, "other@example.com");
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      const events = await recordEvents(() => decryptIBE(wrongKey, ciphertext));
+      expect(events.map((event) => event.event)).toEqual(["decrypt_failed"]);
+      expect(events[0].detail.length).toBeGreaterThan(0);
+
+      // 鍵・暗号文・平文のバイト列はhexでも文字列でも含まれない
+      const detail = events[0].detail.toLowerCase();
+      expect(detail).not.toContain(bytesToHex(wrongKey.key.slice(0, 32)));
+      expect(detail).not.toContain(bytesToHex(ciphertext.slice(1, 33)));
+      expect(detail).not.toContain(new TextDecoder().decode(message));
+    });
+
+    it("should emit only point_rejected when a point fails validation", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      const tampered = ciphertext.slice();
+      tampered.fill(0x5a, 2, 1 + 65);
+      const events = await recordEvents(() => decryptIBE(privateKey, tampered));
+      expect(events.map((event) => event.event)).toEqual(["point_rejected"]);
+      expect(events[0].detail).toMatch(/Invalid G1 point/);
+    });
+
+    it("should not emit events for trial decryption", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const wrongKey = await extractIBEKey(masterKey, "other@example.com");
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      expect(await recordEvents(() => tryDecryptIBE(wrongKey, ciphertext))).toEqual([]);
+    });
+  });
+
+  describe("post-quantum hybrid encryption", () => {
+    const identity = "user@example.com";
+    const message = new TextEncoder().encode("Hybrid IBE + ML-KEM message that spans several AES blocks");
+
+    it("should decrypt with both the IBE key and the Kyber key", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const kyber = await generateKyberKeyPair();
+
+      const ciphertext = await encryptIBEHybrid(publicParams, identity, kyber.publicKey, message);
+
+      expect(await decryptIBEHybrid(privateKey, kyber.privateKey, kyber.publicKey, ciphertext)).toEqual(message);
+    });
+
+    it("should require the Kyber key", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const kyber = await generateKyberKeyPair();
+      const otherKyber = await generateKyberKeyPair();
+
+      const ciphertext = await encryptIBEHybrid(publicParams, identity, kyber.publicKey, message);
+
+      await expect(
+        decryptIBEHybrid(privateKey, otherKyber.privateKey, otherKyber.publicKey, ciphertext)
+      ).rejects.toThrow(/authentication failed/);
+    });
+
+    it("should require the IBE key for the recipient identity", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const otherKey = await extractIBEKey(masterKey, "other@example.com");
+      const kyber = await generateKyberKeyPair();
+
+      const ciphertext = await encryptIBEHybrid(publicParams, identity, kyber.publicKey, message);
+
+      await expect(
+        decryptIBEHybrid(otherKey, kyber.privateKey, kyber.publicKey, ciphertext)
+      ).rejects.toThrow(/authentication failed/);
+    });
+
+    it("should keep the message safe when only the pairing layer is compromised", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const kyber = await generateKyberKeyPair();
+      const ciphertext = await encryptIBEHybrid(publicParams, identity, kyber.publicKey, message);
+
+      // ペアリングが破られた状況を模擬する: 攻撃者はマスター鍵から受信者の秘密鍵を取り出し、
+      // ペアリング層を復号して鍵シェアを得られるが、Kyberの秘密鍵は持っていない
+      const compromisedKey = await extractIBEKey(masterKey, identity);
+      const keyShare = await decryptIBE(compromisedKey, pairingLayerOf(ciphertext));
+      expect(keyShare.length).toBe(32);
+
+      // 鍵シェアはメッセージとは独立したランダムな値で、本文は鍵シェアだけでは復号できない
+      const attackerKyber = await generateKyberKeyPair();
+      await expect(
+        decryptIBEHybrid(compromisedKey, attackerKyber.privateKey, attackerKyber.publicKey, ciphertext)
+      ).rejects.toThrow(/authentication failed/);
+    });
+
+    it("should reject a tampered ciphertext", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const kyber = await generateKyberKeyPair();
+
+      const ciphertext = await encryptIBEHybrid(publicParams, identity, kyber.publicKey, message);
+      const tampered = ciphertext.slice();
+      tampered[tampered.length - 40] ^= 0x01;
+
+      await expect(
+        decryptIBEHybrid(privateKey, kyber.privateKey, kyber.publicKey, tampered)
+      ).rejects.toThrow(/authentication failed/);
+    });
+
+    it("should seal the body with AES-256-GCM and reject the retired version 1 format", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const kyber = await generateKyberKeyPair();
+
+      const ciphertext = await encryptIBEHybrid(publicParams, identity, kyber.publicKey, message);
+
+      // version (1バイト) || KEM暗号文 || 長さ (4バイト) || ペアリング層 || nonce (12バイト) || body || tag (16バイト)
+      expect(ciphertext[0]).toBe(2);
+      const pairingLayer = pairingLayerOf(ciphertext);
+      expect(ciphertext.length).toBe(HYBRID_KEM_PREFIX_SIZE + 4 + pairingLayer.length + 12 + message.length + 16);
+
+      // 鍵ストリームとHMACを使っていたバージョン1の暗号文は受け付けない
+      const retired = ciphertext.slice();
+      retired[0] = 1;
+      await expect(
+        decryptIBEHybrid(privateKey, kyber.privateKey, kyber.publicKey, retired)
+      ).rejects.toThrow(/Unsupported hybrid ciphertext version: expected 2, got 1/);
+    });
+
+    it("should reject a Kyber public key of the wrong size", async () => {
+      const { publicParams } = await generateIBEKeyPair();
+
+      await expect(
+        encryptIBEHybrid(publicParams, identity, new Uint8Array(100), message)
+      ).rejects.toThrow(/Invalid Kyber public key size/);
+    });
+  });
+
+  describe("key identifier", () => {
+    it("should derive the same KID for the same public params", async () => {
+      const { publicParams } = await generateIBEKeyPair();
+      const kid = await getIBEKeyId(publicParams);
+
+      expect(kid).toMatch(/^[A-Za-z0-9_-]{22}$/);
+      expect(await getIBEKeyId(await importIBEPublicParams(publicParams.params))).toBe(kid);
+    });
+
+    it("should derive distinct KIDs for distinct authorities", async () => {
+      const first = await generateIBEKeyPair();
+      const second = await generateIBEKeyPair();
+
+      expect(await getIBEKeyId(first.publicParams)).not.toBe(await getIBEKeyId(second.publicParams));
+    });
+  });
+
+  describe("random bytes", () => {
+    it("should return the requested number of bytes", async () => {
+      expect((await getIBERandomBytes(32)).length).toBe(32);
+      expect((await getIBERandomBytes(1)).length).toBe(1);
+      expect((await getIBERandomBytes(0)).length).toBe(0);
+    });
+
+    it("should return different bytes on successive calls", async () => {
+      const first = await getIBERandomBytes(32);
+      const second = await getIBERandomBytes(32);
+
+      expect(first).not.toEqual(second);
+    });
+  });
+
+  describe("ECIES public-key encryption", () => {
+    it("should round-trip a message with a generated key pair", async () => {
+      const { privateKey, publicKey } = await generateECIESKeyPair();
+      expect(privateKey.length).toBe(32);
+      expect(publicKey.length).toBe(65);
+
+      const message = new TextEncoder().encode("Hello, ECIES!");
+      const ciphertext = await encryptECIES(publicKey, message);
+
+      // version (1バイト) || R (65バイト) || nonce (12バイト) || body || tag (16バイト)
+      expect(ciphertext.length).toBe(1 + 65 + 12 + message.length + 16);
+      expect(await decryptECIES(privateKey, ciphertext)).toEqual(message);
+
+      // 一時鍵は暗号化ごとに異なる
+      expect(await encryptECIES(publicKey, message)).not.toEqual(ciphertext);
+    });
+
+    it("should fail to decrypt with the wrong private key", async () => {
+      const recipient = await generateECIESKeyPair();
+      const other = await generateECIESKeyPair();
+      const ciphertext = await encryptECIES(recipient.publicKey, new TextEncoder().encode("secret"));
+
+      await expect(decryptECIES(other.privateKey, ciphertext)).rejects.toThrow(/authentication failed/);
+    });
+
+    it("should reject a tampered ciphertext", async () => {
+      const { privateKey, publicKey } = await generateECIESKeyPair();
+      const ciphertext = await encryptECIES(publicKey, new TextEncoder().encode("secret"));
+
+      const tampered = new Uint8Array(ciphertext);
+      tampered[1 + 65] ^= 0x01;
+      await expect(decryptECIES(privateKey, tampered)).rejects.toThrow(/authentication failed/);
+    });
+
+    it("should reject a public key that is not a curve point", async () => {
+      const invalid = new Uint8Array(65);
+      invalid[0] = 0x04;
+
+      await expect(encryptECIES(invalid, new TextEncoder().encode("data"))).rejects.toThrow();
+      await expect(encryptECIES(new Uint8Array(64), new TextEncoder().encode("data"))).rejects.toThrow(
+        /public key length/
+      );
+    });
+  });
+
+  describe("curve point validation", () => {
+    const identity = "user@example.com";
+    const message = new TextEncoder().encode("Hello, IBE!");
+
+    // 0x04の後に任意のバイト列を並べた、曲線上にない非圧縮形式の点
+    const garbagePoint = (fill: number): Uint8Array => {
+      const bytes = new Uint8Array(65).fill(fill);
+      bytes[0] = 0x04;
+      return bytes;
+    };
+
+    it("should reject a ciphertext whose U component is not a curve point", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      for (const fill of [0x00, 0x5a, 0xff]) {
+        const tampered = ciphertext.slice();
+        tampered.set(garbagePoint(fill), 1);
+        await expect(decryptIBE(privateKey, tampered)).rejects.toThrow(/Invalid G1 point/);
+        expect(await tryDecryptIBE(privateKey, tampered)).toBeUndefined();
+      }
+    });
+
+    it("should reject a non-canonical U encoding only in strict mode", async () => {
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, identity);
+      const ciphertext = await encryptIBE(publicParams, identity, message);
+
+      // 非圧縮形式（0x04 || x || y）の先頭バイトを圧縮形式（0x02・0x03 + yの偶奇）に書き換える
+      // 同じ点として読めるが、再直列化すると0x04になるため正規のエンコーディングではない
+      const nonCanonical = ciphertext.slice();
+      nonCanonical[1] = 0x02 | (ciphertext[1 + 64] & 1);
+
+      expect(await decryptIBE(privateKey, nonCanonical)).toEqual(message);
+      expect(await decryptIBE(privateKey, nonCanonical, false)).toEqual(message);
+      await expect(decryptIBE(privateKey, nonCanonical, true)).rejects.toThrow(/Non-canonical G1 point encoding/);
+
+      // 正規のエンコーディングはstrictモードでも復号できる
+      expect(await decryptIBE(privateKey, ciphertext, true)).toEqual(message);
+    });
+
+    it("should reject public params that are not a curve point", async () => {
+      for (const fill of [0x00, 0x5a, 0xff]) {
+        await expect(importIBEPublicParams(garbagePoint(fill))).rejects.toThrow(/not a valid curve point/);
+      }
+    });
+
+    it("should reject ECIES keys and ephemeral points that are not curve points", async () => {
+      const { privateKey, publicKey } = await generateECIESKeyPair();
+      const ciphertext = await encryptECIES(publicKey, message);
+
+      await expect(encryptECIES(garbagePoint(0x5a), message)).rejects.toThrow(/Invalid G1 point/);
 
-    // 復号化されたデータは元のメッセージと異なる
-    expect(new TextDecoder().decode(decrypted)).not.toBe("Hello, IBE!");
+      const tampered = new Uint8Array(ciphertext);
+      tampered.set(garbagePoint(0x5a), 1);
+      await expect(decryptECIES(privateKey, tampered)).rejects.toThrow(/Invalid ephemeral public key/);
+    });
   });
 });
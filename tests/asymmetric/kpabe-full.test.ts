@@ -9,11 +9,55 @@
 import { beforeAll, describe, expect, it } from "vitest";
 import {
   decryptKPABE,
+  decryptKPABEFromString,
   encryptKPABE,
+  encryptKPABEToString,
   extractKPABEKey,
   generateKPABEKeyPair,
+  importABEMasterKey,
+  importABEPrivateKey,
+  importABEPublicParams,
   initABE,
+  testABE,
+  upgradeKPABECiphertext,
 } from "../../src/asymmetric/abe.js";
+import { hexToBytes } from "../../src/utils/format.js";
+
+// 旧方式（文脈なしの鍵導出）で作成した旧形式の暗号文のテストベクタ
+// マスター秘密鍵は0x11の32バイト、暗号化時の属性は["A", "B"]
+const LEGACY_MASTER_KEY = hexToBytes("1111111111111111111111111111111111111111111111111111111111111111");
+const LEGACY_PUBLIC_PARAMS = hexToBytes(
+  "04087e5868e0460f8562cd4e4043bb41a7fb4615b9aa95de00f5c6ec1afe5bd8" +
+  "e904397124bf97bb30768a43bfe3e30e8b54e1e3630c0721758dd362befff366" +
+  "c0"
+);
+const LEGACY_KP_CIPHERTEXT = hexToBytes(
+  "0204105c843b1bba52aa95bcd53e56da8d139650dde567396fd18b309c30b225" +
+  "9cc503db1b061f9f7f4fe12da2e0f6a83cd5c2e1f1be62536b66891ec1aa2bbc" +
+  "a1b22b301e758d0998d697681999afa794accdb404189761c29c610cd1095616" +
+  "8559050976a1a080a4cda7afd0c426ff4f8ee6a20e12185528b580d0d68fa9fe" +
+  "9dda36381a4917fbb8037d04c2978c28a03f1eaa8a10568c98fa5ef01d4b17d3" +
+  "f4c605bf329121544a108a6d612612854b7abed7db19ec020def9fdb1d733d0c" +
+  "c65661757408e3d4a31c39828868fd150f4a8ed88a000417c17a7b52a153778e" +
+  "fc623e30255c3a5418b44302997d49357305610bb95fdb0b49184a00f84eb1a4" +
+  "8b292fb921cf27ab12af0101f82ce743d091c33e5db7b908b2105ea22e1066e4" +
+  "ada1dc73466abd6d0cada69b7bc10b874dd23f2b0530510849f6efe65e34eb0f" +
+  "4c0924d5ce323fe36319d3ff67c8ee78539d5e6222adde00"
+);
+
+// 旧方式の属性ハッシュ（バージョン1）で上のマスター秘密鍵から生成したポリシー"A,B"の秘密鍵
+// 旧形式の暗号文はこの鍵でのみ復号・移行できる
+const LEGACY_KP_PRIVATE_KEY = hexToBytes(
+  "02010401b8cd0efe944e12ec592c2021ee3cda5d4e6c2618d9c1bab53984c9c5" +
+  "b534e82047d2cc444a28df03c89278a42a0ce4b08fb398365e41ca8c7756da39" +
+  "24692505c66a3d755e4dd2cfe3ad67fb5c073358b49bd57a354ad0d9c6bcde1a" +
+  "ddf9ec19e28d563e8fd59417d510fdcef5c9e8a94d21607b460f6113e8fc7331" +
+  "be9a8b0004070b82710ad64888e64162ea5e830943bee54ed9ba83a1549cca3d" +
+  "38f7b6397d153c2e64fccd6f4b94ad796ef122dee14f5aee54e51009f00c3e0a" +
+  "92178dbcb70596a474cdbffb2bd3119619f43a691aa3d0e6884de8c7cd277499" +
+  "6ed955714f17dc9bf1c6500662f01f4e1112314d14dca4d3d3ab459578d1867d" +
+  "fd9e4e2de200"
+);
 
 describe("KP-ABE Full Implementation", () => {
   beforeAll(async () => {
@@ -21,6 +65,10 @@ describe("KP-ABE Full Implementation", () => {
     await initABE();
   });
 
+  it("should pass the health check", async () => {
+    expect(await testABE()).toBe(true);
+  });
+
   it("should generate master key pair", async () => {
     const { masterKey, publicParams } = await generateKPABEKeyPair();
 
@@ -105,5 +153,98 @@ describe("KP-ABE Full Implementation", () => {
 
     expect(new TextDecoder().decode(decrypted)).toBe("Multiple attributes test");
   });
-});
 
+  it("should round-trip a ciphertext with exactly 255 attributes", async () => {
+    const { masterKey, publicParams } = await generateKPABEKeyPair();
+    const attributes = Array.from({ length: 255 }, (_, i) => `attr${i}`);
+    const message = new TextEncoder().encode("Maximum attributes");
+
+    const privateKey = await extractKPABEKey(masterKey, attributes.join(","));
+    const ciphertext = await encryptKPABE(publicParams, attributes, message);
+    // ヘッダ（2バイト）の後の属性数
+    expect(ciphertext[2]).toBe(255);
+    expect(await decryptKPABE(privateKey, ciphertext)).toEqual(message);
+
+    // 256個は属性数を1バイトで保存できないため拒否される
+    await expect(encryptKPABE(publicParams, [...attributes, "attr255"], message)).rejects.toThrow();
+  }, 60_000);
+
+  it("should reject a ciphertext whose attribute count is zero", async () => {
+    const { masterKey, publicParams } = await generateKPABEKeyPair();
+    const privateKey = await extractKPABEKey(masterKey, "A");
+    const ciphertext = await encryptKPABE(publicParams, ["A"], new TextEncoder().encode("data"));
+
+    const tampered = new Uint8Array(ciphertext);
+    tampered[2] = 0;
+    await expect(decryptKPABE(privateKey, tampered)).rejects.toThrow(/属性数が0/);
+  });
+
+  it("should round-trip through the base64url string form", async () => {
+    const { masterKey, publicParams } = await generateKPABEKeyPair();
+    const privateKey = await extractKPABEKey(masterKey, "A,B");
+    const message = new TextEncoder().encode("Ciphertext in a URL");
+
+    const encoded = await encryptKPABEToString(publicParams, ["A", "B"], message);
+    // URLにそのまま埋め込める文字だけで構成され、パディングを含まない
+    expect(encoded).toMatch(/^[A-Za-z0-9_-]+$/);
+    expect(await decryptKPABEFromString(privateKey, encoded)).toEqual(message);
+
+    // 改行・空白やパディングが付いていても復号できる
+    const wrapped = `  ${encoded.slice(0, 40)}\n${encoded.slice(40)}==\n`;
+    expect(await decryptKPABEFromString(privateKey, wrapped)).toEqual(message);
+  });
+
+  it("should upgrade a legacy ciphertext to the current format", async () => {
+    const masterKey = await importABEMasterKey(LEGACY_MASTER_KEY);
+    const publicParams = await importABEPublicParams(LEGACY_PUBLIC_PARAMS);
+    const legacyKey = await importABEPrivateKey(LEGACY_KP_PRIVATE_KEY, ["A", "B"]);
+    const privateKey = await extractKPABEKey(masterKey, "A,B");
+
+    // 旧方式の属性ハッシュの鍵で復号し、現在の属性ハッシュで暗号化し直す
+    const upgraded = await upgradeKPABECiphertext(LEGACY_KP_CIPHERTEXT, legacyKey, publicParams);
+    const decrypted = await decryptKPABE(privateKey, upgraded);
+    expect(new TextDecoder().decode(decrypted)).toBe("Legacy KP-ABE data");
+
+    // 現在の形式の暗号文はそのまま返る
+    expect(await upgradeKPABECiphertext(upgraded, privateKey, publicParams)).toEqual(upgraded);
+  });
+
+  it("should verify the AES-256-GCM tag after decryption", async () => {
+    const { masterKey, publicParams } = await generateKPABEKeyPair();
+    const privateKey = await extractKPABEKey(masterKey, "A,B");
+    const message = new TextEncoder().encode("Authenticated KP-ABE data, longer than one 32-byte key");
+    const ciphertext = await encryptKPABE(publicParams, ["A", "B"], message);
+
+    // スキーム識別タグ（0x02）にAES-256-GCMのフラグ（0x20）が付く
+    expect(ciphertext[0]).toBe(0x02 | 0x20);
+    expect(await decryptKPABE(privateKey, ciphertext)).toEqual(message);
+
+    // V（C0の後、属性コンポーネントの前の nonce || 暗号文 || 認証タグ）のどのビットを反転しても復号できない
+    const vStart = 2 + 1 + 65;
+    for (let i = vStart; i < vStart + 12 + message.length + 16; i++) {
+      const tampered = new Uint8Array(ciphertext);
+      tampered[i] ^= 0x80;
+      await expect(decryptKPABE(privateKey, tampered)).rejects.toThrow(/認証タグが一致しません/);
+    }
+
+    // 別のマスター鍵で発行された、属性の同じ鍵
+    const other = await generateKPABEKeyPair();
+    const foreignKey = await extractKPABEKey(other.masterKey, "A,B");
+    await expect(decryptKPABE(foreignKey, ciphertext)).rejects.toThrow(/認証タグが一致しません/);
+  });
+
+  it("should reject a ciphertext whose AES-256-GCM flag has been cleared", async () => {
+    const { masterKey, publicParams } = await generateKPABEKeyPair();
+    const privateKey = await extractKPABEKey(masterKey, "A,B");
+    const message = new TextEncoder().encode("Authenticated KP-ABE data");
+    const ciphertext = await encryptKPABE(publicParams, ["A", "B"], message);
+    const stripped = new Uint8Array(ciphertext);
+    stripped[0] &= ~0x20;
+
+    await expect(decryptKPABE(privateKey, stripped)).rejects.toThrow(/認証されていない形式/);
+
+    // 移行では旧形式の対称鍵（GCMの対称鍵と別のラベルで導出）で復号するため、元の平文は得られない
+    const upgraded = await upgradeKPABECiphertext(stripped, privateKey, publicParams);
+    expect(await decryptKPABE(privateKey, upgraded)).not.toEqual(message);
+  });
+});
@@ -0,0 +1,152 @@
+/**
+ * 乱数生成の失敗に対するABE・IBEの動作確認
+ *
+ * 乱数生成が失敗・劣化した場合に、弱い鍵や暗号文を生成せずにエラーとなることを確認します。
+ * set_test_rng_modeはtest-rng featureを有効にしたビルドでのみ公開されるため、
+ * 通常のビルドではこのテストはスキップされます。
+ *   wasm-pack build --target web -- --features test-rng
+ */
+
+import { afterEach, beforeAll, describe, expect, it } from "vitest";
+import {
+  encryptABE,
+  generateABEKeyPair,
+  getABERandomBytes,
+  initABE,
+  testABE,
+} from "../../src/asymmetric/abe.js";
+import {
+  encryptIBE,
+  generateIBEKeyPair,
+  getIBERandomBytes,
+  initIBE,
+  testIBE,
+} from "../../src/asymmetric/ibe.js";
+
+const abeWasm: any = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+const ibeWasm: any = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+const hasTestRng =
+  typeof abeWasm.set_test_rng_mode === "function" &&
+  typeof ibeWasm.set_test_rng_mode === "function";
+
+const SEED = new Uint8Array(32).fill(0x5a);
+
+describe.skipIf(!hasTestRng)("RNG failure handling", () => {
+  beforeAll(async () => {
+    // WebAssemblyモジュールを初期化
+    await initABE();
+    await initIBE();
+  });
+
+  afterEach(() => {
+    // 他のテストに影響しないよう、通常の乱数に戻す
+    abeWasm.set_test_rng_mode("os", new Uint8Array());
+    ibeWasm.set_test_rng_mode("os", new Uint8Array());
+  });
+
+  describe("ABE", () => {
+    it("should refuse to generate keys when the RNG returns zero bytes", async () => {
+      abeWasm.set_test_rng_mode("zero_bytes", new Uint8Array());
+
+      await expect(generateABEKeyPair()).rejects.toThrow();
+      expect(await testABE()).toBe(false);
+    });
+
+    it("should refuse to encrypt when the RNG fails", async () => {
+      const { publicParams } = await generateABEKeyPair();
+      abeWasm.set_test_rng_mode("fail", new Uint8Array());
+
+      await expect(generateABEKeyPair()).rejects.toThrow();
+      await expect(
+        encryptABE(publicParams, "A", new TextEncoder().encode("secret"))
+      ).rejects.toThrow();
+    });
+
+    it("should refuse to return random bytes when the RNG fails", async () => {
+      abeWasm.set_test_rng_mode("fail", new Uint8Array());
+
+      await expect(getABERandomBytes(32)).rejects.toThrow(/Random number generation failed/);
+    });
+
+    it("should reproduce keys from the same deterministic seed", async () => {
+      abeWasm.set_test_rng_mode("deterministic", SEED);
+      const first = await generateABEKeyPair();
+      abeWasm.set_test_rng_mode("deterministic", SEED);
+      const second = await generateABEKeyPair();
+
+      expect(first.publicParams.params).toEqual(second.publicParams.params);
+
+      // 同じモードのまま続けて生成すると、異なる鍵になる
+      const third = await generateABEKeyPair();
+      expect(third.publicParams.params).not.toEqual(first.publicParams.params);
+    });
+
+    it("should report a failed health check instead of throwing when the RNG fails", async () => {
+      abeWasm.set_test_rng_mode("fail", new Uint8Array());
+
+      // 動作確認の往復が失敗するビルドでは、例外ではなくfalseを返す
+      expect(abeWasm.health_check()).toBe(false);
+      expect(await testABE()).toBe(false);
+    });
+
+    it("should work normally after returning to the OS RNG", async () => {
+      abeWasm.set_test_rng_mode("fail", new Uint8Array());
+      abeWasm.set_test_rng_mode("os", new Uint8Array());
+
+      expect(await testABE()).toBe(true);
+    });
+
+    it("should reject an unknown mode", () => {
+      expect(() => abeWasm.set_test_rng_mode("weak", new Uint8Array())).toThrow();
+    });
+  });
+
+  describe("IBE", () => {
+    it("should refuse to generate keys when the RNG returns zero bytes", async () => {
+      ibeWasm.set_test_rng_mode("zero_bytes", new Uint8Array());
+
+      await expect(generateIBEKeyPair()).rejects.toThrow();
+      expect(await testIBE()).toBe(false);
+    });
+
+    it("should refuse to encrypt when the RNG fails", async () => {
+      const { publicParams } = await generateIBEKeyPair();
+      ibeWasm.set_test_rng_mode("fail", new Uint8Array());
+
+      await expect(generateIBEKeyPair()).rejects.toThrow();
+      await expect(
+        encryptIBE(publicParams, "user@example.com", new TextEncoder().encode("secret"))
+      ).rejects.toThrow();
+    });
+
+    it("should refuse to return random bytes when the RNG returns zero bytes", async () => {
+      ibeWasm.set_test_rng_mode("zero_bytes", new Uint8Array());
+
+      await expect(getIBERandomBytes(32)).rejects.toThrow(/Random number generation failed/);
+    });
+
+    it("should report a failed health check instead of throwing when the RNG fails", async () => {
+      ibeWasm.set_test_rng_mode("fail", new Uint8Array());
+
+      // 動作確認の往復が失敗するビルドでは、例外ではなくfalseを返す
+      expect(ibeWasm.health_check()).toBe(false);
+      expect(await testIBE()).toBe(false);
+    });
+
+    it("should work normally after returning to the OS RNG", async () => {
+      ibeWasm.set_test_rng_mode("fail", new Uint8Array());
+      ibeWasm.set_test_rng_mode("os", new Uint8Array());
+
+      expect(await testIBE()).toBe(true);
+    });
+
+    it("should reproduce keys from the same deterministic seed", async () => {
+      ibeWasm.set_test_rng_mode("deterministic", SEED);
+      const first = await generateIBEKeyPair();
+      ibeWasm.set_test_rng_mode("deterministic", SEED);
+      const second = await generateIBEKeyPair();
+
+      expect(first.publicParams.params).toEqual(second.publicParams.params);
+    });
+  });
+});
@@ -0,0 +1,117 @@
+/**
+ * 耐量子暗号のバックグラウンド鍵生成のテスト
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  type BackgroundKeyPair,
+  type BackgroundKeygenAlgorithm,
+  generateKeyPairInBackground,
+  isWorkerAvailable,
+} from "../../src/post-quantum/background-keygen.js";
+import { signDilithium, verifyDilithium } from "../../src/post-quantum/dilithium.js";
+import { signFalcon, verifyFalcon } from "../../src/post-quantum/falcon.js";
+import { decapsulateKyber, encapsulateKyber } from "../../src/post-quantum/kyber.js";
+
+/**
+ * 鍵ペアが正しく対応しているか（署名の検証、またはカプセル化の往復）を確認
+ */
+async function expectValidKeyPair(algorithm: BackgroundKeygenAlgorithm, keypair: BackgroundKeyPair) {
+  const message = new TextEncoder().encode("background keygen");
+  switch (algorithm) {
+    case "ML-KEM-768": {
+      const { ciphertext, sharedSecret } = await encapsulateKyber(keypair.publicKey);
+      expect(await decapsulateKyber(ciphertext, keypair.privateKey, keypair.publicKey)).toEqual(sharedSecret);
+      break;
+    }
+    case "ML-DSA-65": {
+      const signature = await signDilithium(message, keypair.privateKey);
+      expect(await verifyDilithium(message, signature, keypair.publicKey)).toBe(true);
+      break;
+    }
+    case "FALCON-512": {
+      const signature = await signFalcon(message, keypair.privateKey);
+      expect(await verifyFalcon(message, signature, keypair.publicKey)).toBe(true);
+      break;
+    }
+  }
+}
+
+const algorithms: BackgroundKeygenAlgorithm[] = ["ML-KEM-768", "ML-DSA-65", "FALCON-512"];
+
+describe("耐量子暗号のバックグラウンド鍵生成", () => {
+  describe("Workerを使わない生成", () => {
+    for (const algorithm of algorithms) {
+      it(`${algorithm}の鍵ペアを生成し、コールバックで通知する`, async () => {
+        const results: Array<[Error | null, BackgroundKeyPair | undefined]> = [];
+        const keypair = await generateKeyPairInBackground(
+          algorithm,
+          (error, result) => results.push([error, result]),
+          { useWorker: false }
+        );
+
+        expect(results).toEqual([[null, keypair]]);
+        await expectValidKeyPair(algorithm, keypair);
+      });
+    }
+
+    it("呼び出しはすぐに戻り、鍵生成は後から行われる", async () => {
+      let completed = false;
+      const pending = generateKeyPairInBackground(
+        "ML-DSA-65",
+        () => {
+          completed = true;
+        },
+        { useWorker: false }
+      );
+
+      expect(completed).toBe(false);
+      await pending;
+      expect(completed).toBe(true);
+    });
+
+    it("未対応のパラメータセットはエラーをコールバックで通知する", async () => {
+      let received: Error | null = null;
+      await expect(
+        generateKeyPairInBackground(
+          "ML-DSA-87" as BackgroundKeygenAlgorithm,
+          (error) => {
+            received = error;
+          },
+          { useWorker: false }
+        )
+      ).rejects.toThrow("Unsupported algorithm for background keygen: ML-DSA-87");
+      expect(received).toBeInstanceOf(Error);
+    });
+  });
+
+  // Web Workerを利用できる環境（ブラウザモードなど）でのみ実行する
+  describe.skipIf(!isWorkerAvailable())("Workerでの生成", () => {
+    for (const algorithm of algorithms) {
+      it(`${algorithm}の鍵ペアをメインスレッド外で生成する`, async () => {
+        let workersCreated = 0;
+        const createWorker = () => {
+          workersCreated++;
+          return new (globalThis as any).Worker(
+            new URL("../../src/post-quantum/keygen-worker.ts", import.meta.url),
+            { type: "module" }
+          );
+        };
+
+        let callbackKeyPair: BackgroundKeyPair | undefined;
+        const keypair = await generateKeyPairInBackground(
+          algorithm,
+          (error, result) => {
+            expect(error).toBeNull();
+            callbackKeyPair = result;
+          },
+          { createWorker }
+        );
+
+        expect(workersCreated).toBe(1);
+        expect(callbackKeyPair).toBe(keypair);
+        await expectValidKeyPair(algorithm, keypair);
+      });
+    }
+  });
+});
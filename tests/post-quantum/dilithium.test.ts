@@ -2,19 +2,68 @@
  * CRYSTALS-Dilithium (ML-DSA) のテスト
  */
 
+import { readFileSync } from "node:fs";
 import { describe, it, expect } from "vitest";
 import {
   initDilithium,
+  testDilithium,
+  checkDilithiumEntropy,
   generateDilithiumKeyPair,
+  generateDilithiumKeyPairForLevel,
+  generateDilithiumKeyPairFromSeed,
+  generateDilithiumKeyPairsFromSeeds,
+  generateDilithiumHybridKeyPair,
+  signDilithiumHybrid,
+  verifyDilithiumHybrid,
   signDilithium,
+  signDilithiumEnveloped,
+  signDilithiumWithContext,
+  signDilithiumDeterministic,
   verifyDilithium,
+  verifyDilithiumWithContext,
+  verifyDilithiumDetailed,
+  verifyDilithiumAny,
+  verifyDilithiumBatch,
+  verifyDilithiumEncoded,
+  verifyDilithiumEnveloped,
+  readDilithiumSignatureEnvelope,
+  verifyDilithiumEnvelopedAndExtract,
+  exportDilithiumPublicKeyJwk,
+  importDilithiumPublicKeyJwk,
+  getDilithiumKeyId,
+  signDilithiumChallenge,
+  verifyDilithiumChallenge,
+  signDilithiumPrehash,
+  verifyDilithiumPrehash,
+  signDilithiumPrehashed,
+  verifyDilithiumPrehashed,
+  DILITHIUM_PREHASH_OID,
+  type DilithiumLevel,
+  type DilithiumSecurityLevel,
 } from "../../src/post-quantum/dilithium.js";
+import { bytesToBase64, hexToBytes } from "../../src/utils/format.js";
+import { hashSHA256 } from "../../src/hash/sha256.js";
+import { hashSHA512 } from "../../src/hash/sha512.js";
+
+const dilithiumWasm: any = await import("../../wasm-src/dilithium-wasm/pkg/dilithium_wasm.js");
+// set_test_rng_modeはtest-rng featureを有効にしたビルドでのみ公開される
+//   wasm-pack build --target web -- --features test-rng
+const hasTestRng = typeof dilithiumWasm.set_test_rng_mode === "function";
+
+// OpenSSLで生成したML-DSA-65の決定的署名（生成手順はフィクスチャのsourceを参照）
+const OPENSSL_KAT = JSON.parse(
+  readFileSync(new URL("./fixtures/ml-dsa-65-openssl.json", import.meta.url), "utf8")
+);
 
 describe("CRYSTALS-Dilithium (ML-DSA)", () => {
   it("初期化が成功する", async () => {
     await expect(initDilithium()).resolves.not.toThrow();
   });
 
+  it("ヘルスチェックが成功する", async () => {
+    expect(await testDilithium()).toBe(true);
+  });
+
   it("鍵ペアを生成できる", async () => {
     await initDilithium();
     const keypair = await generateDilithiumKeyPair();
@@ -100,4 +149,854 @@ describe("CRYSTALS-Dilithium (ML-DSA)", () => {
     expect(isValid1).toBe(true);
     expect(isValid2).toBe(true);
   });
+
+  it("公開鍵をJWK経由で往復変換できる", async () => {
+    await initDilithium();
+
+    const { publicKey, privateKey } = await generateDilithiumKeyPair();
+
+    const jwk = await exportDilithiumPublicKeyJwk(publicKey);
+    expect(jwk.kty).toBe("AKP");
+    expect(jwk.alg).toBe("ML-DSA-65");
+    expect(jwk.pub).toMatch(/^[A-Za-z0-9_-]+$/);
+
+    // JSON文字列を経由しても復元できる
+    const restored = await importDilithiumPublicKeyJwk(JSON.parse(JSON.stringify(jwk)));
+    expect(restored).toEqual(publicKey);
+
+    // 復元した公開鍵で署名を検証できる
+    const message = new TextEncoder().encode("JWK round trip");
+    const signature = await signDilithium(message, privateKey);
+    expect(await verifyDilithium(message, signature, restored)).toBe(true);
+  });
+
+  it("アルゴリズムが異なるJWKは拒否される", async () => {
+    await initDilithium();
+
+    const { publicKey } = await generateDilithiumKeyPair();
+    const jwk = await exportDilithiumPublicKeyJwk(publicKey);
+
+    await expect(
+      importDilithiumPublicKeyJwk({ ...jwk, alg: "ES256" } as unknown as typeof jwk)
+    ).rejects.toThrow();
+  });
+
+  it("ML-DSA-44・87の公開鍵はalgにパラメータセットを入れて往復変換できる", async () => {
+    for (const level of ["ML-DSA-44", "ML-DSA-87"] as const) {
+      const { publicKey } = await generateDilithiumKeyPairForLevel(level);
+      const jwk = await exportDilithiumPublicKeyJwk(publicKey);
+
+      expect(jwk.alg).toBe(level);
+      expect(await importDilithiumPublicKeyJwk(jwk)).toEqual(publicKey);
+    }
+
+    // algと公開鍵のサイズが一致しない場合は拒否される
+    const { publicKey } = await generateDilithiumKeyPairForLevel("ML-DSA-44");
+    const jwk = await exportDilithiumPublicKeyJwk(publicKey);
+    await expect(importDilithiumPublicKeyJwk({ ...jwk, alg: "ML-DSA-65" })).rejects.toThrow(
+      "Invalid public key size for ML-DSA-65: expected 1952 bytes, got 1312"
+    );
+  });
+
+  describe("乱数生成器の動作確認", () => {
+    it("通常の乱数ではtrueを返す", async () => {
+      expect(await checkDilithiumEntropy()).toBe(true);
+    });
+
+    it.skipIf(!hasTestRng)("乱数がすべて0の場合はfalseを返す", async () => {
+      await initDilithium();
+      try {
+        dilithiumWasm.set_test_rng_mode("zero_bytes", new Uint8Array());
+        expect(await checkDilithiumEntropy()).toBe(false);
+      } finally {
+        dilithiumWasm.set_test_rng_mode("os", new Uint8Array());
+      }
+      expect(await checkDilithiumEntropy()).toBe(true);
+    });
+
+    it.skipIf(!hasTestRng)("シードから決定的に生成している場合も連続する鍵は異なる", async () => {
+      await initDilithium();
+      try {
+        dilithiumWasm.set_test_rng_mode("deterministic", new Uint8Array(32).fill(0x5a));
+        expect(await checkDilithiumEntropy()).toBe(true);
+      } finally {
+        dilithiumWasm.set_test_rng_mode("os", new Uint8Array());
+      }
+    });
+  });
+
+  describe("セキュリティレベルの選択", () => {
+    const sizes: [DilithiumSecurityLevel, number, number, number][] = [
+      [44, 1312, 2560, 2420],
+      [65, 1952, 4032, 3309],
+      [87, 2592, 4896, 4627],
+    ];
+
+    for (const [level, publicKeySize, privateKeySize, signatureSize] of sizes) {
+      it(`ML-DSA-${level}の鍵で署名と検証ができる`, async () => {
+        const { publicKey, privateKey } = await generateDilithiumKeyPair(level);
+        const message = new TextEncoder().encode(`ML-DSA-${level}`);
+        const signature = await signDilithium(message, privateKey);
+
+        expect(publicKey.length).toBe(publicKeySize);
+        expect(privateKey.length).toBe(privateKeySize);
+        expect(signature.length).toBe(signatureSize);
+        expect(await verifyDilithium(message, signature, publicKey)).toBe(true);
+        expect(await verifyDilithium(new TextEncoder().encode("Other"), signature, publicKey)).toBe(false);
+      });
+    }
+
+    it("レベルを省略するとML-DSA-65の鍵が生成される", async () => {
+      await initDilithium();
+
+      const keypair = dilithiumWasm.generate_keypair();
+      try {
+        expect(keypair.level).toBe(65);
+        expect(keypair.public_key.length).toBe(1952);
+      } finally {
+        keypair.free();
+      }
+    });
+
+    it("異なるレベルの公開鍵では検証に失敗する", async () => {
+      const signer = await generateDilithiumKeyPair(44);
+      const other = await generateDilithiumKeyPair(87);
+      const message = new TextEncoder().encode("Level mismatch");
+      const signature = await signDilithium(message, signer.privateKey);
+
+      expect(await verifyDilithium(message, signature, other.publicKey)).toBe(false);
+    });
+
+    it("未対応のレベルは拒否される", async () => {
+      await expect(generateDilithiumKeyPair(128 as DilithiumSecurityLevel)).rejects.toThrow(
+        "Unsupported ML-DSA level: 128 (expected 44, 65, or 87)"
+      );
+    });
+  });
+
+  describe("決定的な署名", () => {
+    it("同じ鍵・メッセージからは同じ署名が得られる", async () => {
+      const { privateKey } = await generateDilithiumKeyPair();
+      const message = new TextEncoder().encode("Deterministic");
+
+      const first = await signDilithiumDeterministic(message, privateKey);
+      const second = await signDilithiumDeterministic(message, privateKey);
+      expect(second).toEqual(first);
+
+      const other = await signDilithiumDeterministic(new TextEncoder().encode("Other"), privateKey);
+      expect(other).not.toEqual(first);
+    });
+
+    it("決定的な署名は通常のverifyで検証できる", async () => {
+      for (const level of [44, 65, 87] as const) {
+        const { publicKey, privateKey } = await generateDilithiumKeyPair(level);
+        const message = new TextEncoder().encode(`Deterministic ML-DSA-${level}`);
+        const signature = await signDilithiumDeterministic(message, privateKey);
+
+        expect(await verifyDilithium(message, signature, publicKey)).toBe(true);
+        expect(await verifyDilithium(new TextEncoder().encode("Other"), signature, publicKey)).toBe(false);
+      }
+    });
+
+    it("シードから生成した鍵では署名が固定される", async () => {
+      const seed = new Uint8Array(32).fill(0x42);
+      const first = await generateDilithiumKeyPairFromSeed(seed);
+      const second = await generateDilithiumKeyPairFromSeed(seed);
+      const message = new TextEncoder().encode("Reproducible");
+
+      expect(await signDilithiumDeterministic(message, second.privateKey)).toEqual(
+        await signDilithiumDeterministic(message, first.privateKey)
+      );
+    });
+  });
+
+  describe("コンテキスト文字列付きの署名", () => {
+    const encoder = new TextEncoder();
+
+    it("同じコンテキスト文字列でのみ検証できる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const message = encoder.encode("Transfer 100 JPY");
+      const signature = await signDilithiumWithContext(message, encoder.encode("payments/v1"), privateKey);
+
+      expect(await verifyDilithiumWithContext(message, encoder.encode("payments/v1"), signature, publicKey)).toBe(true);
+      expect(await verifyDilithiumWithContext(message, encoder.encode("login/v1"), signature, publicKey)).toBe(false);
+      expect(await verifyDilithiumWithContext(message, new Uint8Array(0), signature, publicKey)).toBe(false);
+      expect(await verifyDilithium(message, signature, publicKey)).toBe(false);
+    });
+
+    it("空のコンテキスト文字列の署名は通常の署名と同じ署名対象になる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const message = encoder.encode("Empty context");
+      const signature = await signDilithium(message, privateKey);
+
+      expect(await verifyDilithiumWithContext(message, new Uint8Array(0), signature, publicKey)).toBe(true);
+
+      const contextSignature = await signDilithiumWithContext(message, new Uint8Array(0), privateKey);
+      expect(await verifyDilithiumWithContext(message, new Uint8Array(0), contextSignature, publicKey)).toBe(true);
+      expect(await verifyDilithium(message, contextSignature, publicKey)).toBe(true);
+    });
+
+    it("封筒形式のコンテキスト文字列付き署名と同じ署名対象になる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const message = encoder.encode("Envelope");
+      const context = encoder.encode("app");
+      const envelope = await signDilithiumEnveloped(message, privateKey, { context });
+      // 拡張封筒形式: tag || flags || len(ctx) || ctx || 署名
+      const signature = envelope.slice(3 + context.length);
+
+      expect(await verifyDilithiumWithContext(message, context, signature, publicKey)).toBe(true);
+    });
+
+    it("255バイトを超えるコンテキスト文字列は拒否される", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const message = encoder.encode("Long context");
+      const signature = await signDilithiumWithContext(message, new Uint8Array(255), privateKey);
+
+      expect(await verifyDilithiumWithContext(message, new Uint8Array(255), signature, publicKey)).toBe(true);
+      await expect(signDilithiumWithContext(message, new Uint8Array(256), privateKey)).rejects.toThrow(
+        "Context is too long: maximum 255 bytes, got 256"
+      );
+      expect(await verifyDilithiumWithContext(message, new Uint8Array(256), signature, publicKey)).toBe(false);
+    });
+  });
+
+  describe("不正なサイズの入力", () => {
+    it("サイズの異なる秘密鍵では署名できない", async () => {
+      const { privateKey } = await generateDilithiumKeyPair();
+      const message = new TextEncoder().encode("Wrong size");
+
+      await expect(signDilithium(message, privateKey.slice(1))).rejects.toThrow(
+        `Invalid private key size: ${privateKey.length - 1} bytes does not match any ML-DSA level`
+      );
+      await expect(signDilithium(message, new Uint8Array(0))).rejects.toThrow(/Invalid private key size/);
+    });
+
+    it("サイズの異なる署名・公開鍵は無効として扱われる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const message = new TextEncoder().encode("Wrong size");
+      const signature = await signDilithium(message, privateKey);
+
+      expect(await verifyDilithium(message, signature.slice(1), publicKey)).toBe(false);
+      expect(await verifyDilithium(message, new Uint8Array([...signature, 0]), publicKey)).toBe(false);
+      expect(await verifyDilithium(message, signature, publicKey.slice(1))).toBe(false);
+    });
+
+    it("詳細な検証結果では入力の不正と署名の不一致が区別される", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const message = new TextEncoder().encode("Detailed");
+      const signature = await signDilithium(message, privateKey);
+
+      expect(await verifyDilithiumDetailed(message, signature, publicKey)).toEqual({ status: "valid", error: null });
+      expect(await verifyDilithiumDetailed(new TextEncoder().encode("Other"), signature, publicKey)).toEqual({
+        status: "invalid",
+        error: null,
+      });
+      expect(await verifyDilithiumDetailed(message, signature.slice(1), publicKey)).toEqual({
+        status: "malformed_input",
+        error: `Invalid ML-DSA-65 signature size: expected ${signature.length}, got ${signature.length - 1}`,
+      });
+      expect(await verifyDilithiumDetailed(message, signature, publicKey.slice(1))).toEqual({
+        status: "malformed_input",
+        error: `Invalid public key size: ${publicKey.length - 1} bytes does not match any ML-DSA level`,
+      });
+    });
+  });
+
+  describe("秘密鍵の消去", () => {
+    it("zeroizeは秘密鍵を消去し、公開鍵は残す", async () => {
+      await initDilithium();
+
+      const keypair = dilithiumWasm.generate_keypair();
+      try {
+        const publicKey = keypair.public_key;
+
+        keypair.zeroize();
+        expect(keypair.private_key.length).toBe(0);
+        expect(keypair.public_key).toEqual(publicKey);
+      } finally {
+        keypair.free();
+      }
+    });
+  });
+
+  describe("ハイブリッド署名（Ed25519 + ML-DSA）", () => {
+    const message = new TextEncoder().encode("Hybrid signature");
+
+    it("連結した署名を検証できる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumHybridKeyPair();
+      expect(publicKey.length).toBe(32 + 1952);
+      expect(privateKey.length).toBe(32 + 4032);
+
+      const signature = await signDilithiumHybrid(message, privateKey);
+      expect(signature.length).toBe(64 + 3309);
+      expect(await verifyDilithiumHybrid(message, signature, publicKey)).toBe(true);
+      expect(await verifyDilithiumHybrid(new TextEncoder().encode("other"), signature, publicKey)).toBe(false);
+    });
+
+    it("ML-DSA-44・87の鍵でも署名・検証でき、レベルは鍵のサイズから判別される", async () => {
+      const level44 = await generateDilithiumHybridKeyPair(44);
+      const level87 = await generateDilithiumHybridKeyPair(87);
+      expect(level44.publicKey.length).toBe(32 + 1312);
+      expect(level87.privateKey.length).toBe(32 + 4896);
+
+      const signature = await signDilithiumHybrid(message, level87.privateKey);
+      expect(signature.length).toBe(64 + 4627);
+      expect(await verifyDilithiumHybrid(message, signature, level87.publicKey)).toBe(true);
+      expect(await verifyDilithiumHybrid(message, signature, level44.publicKey)).toBe(false);
+    });
+
+    it("Ed25519署名を改ざんすると検証に失敗する", async () => {
+      const { publicKey, privateKey } = await generateDilithiumHybridKeyPair();
+      const signature = await signDilithiumHybrid(message, privateKey);
+
+      const tampered = signature.slice();
+      tampered[0] ^= 0x01;
+      expect(await verifyDilithiumHybrid(message, tampered, publicKey)).toBe(false);
+    });
+
+    it("ML-DSA署名を改ざんすると検証に失敗する", async () => {
+      const { publicKey, privateKey } = await generateDilithiumHybridKeyPair();
+      const signature = await signDilithiumHybrid(message, privateKey);
+
+      const tampered = signature.slice();
+      tampered[64 + 100] ^= 0x01;
+      expect(await verifyDilithiumHybrid(message, tampered, publicKey)).toBe(false);
+    });
+
+    it("片方の署名を別の鍵ペアのものに差し替えると検証に失敗する", async () => {
+      const keypair = await generateDilithiumHybridKeyPair();
+      const other = await generateDilithiumHybridKeyPair();
+      const signature = await signDilithiumHybrid(message, keypair.privateKey);
+      const otherSignature = await signDilithiumHybrid(message, other.privateKey);
+
+      const mixed = new Uint8Array([...signature.slice(0, 64), ...otherSignature.slice(64)]);
+      expect(await verifyDilithiumHybrid(message, mixed, keypair.publicKey)).toBe(false);
+    });
+
+    it("サイズの異なる秘密鍵は例外として拒否される", async () => {
+      const { privateKey } = await generateDilithiumHybridKeyPair();
+
+      await expect(signDilithiumHybrid(message, privateKey.slice(1))).rejects.toThrow(
+        `Invalid hybrid private key size: expected one of ${32 + 2560}, ${32 + 4032}, ${32 + 4896} bytes, got ${
+          privateKey.length - 1
+        }`
+      );
+      await expect(signDilithiumHybrid(message, new Uint8Array(0))).rejects.toThrow(
+        /Invalid hybrid private key size/
+      );
+    });
+  });
+
+  describe("WebAuthn風チャレンジ署名", () => {
+    // 固定のチャレンジとオリジン
+    const challenge = new Uint8Array(32).map((_, i) => i);
+    const origin = "https://example.com";
+
+    it("チャレンジ署名を検証できる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+
+      const signature = await signDilithiumChallenge(challenge, origin, privateKey);
+      expect(await verifyDilithiumChallenge(challenge, origin, signature, publicKey)).toBe(true);
+    });
+
+    it("オリジンが異なると検証に失敗する", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+
+      const signature = await signDilithiumChallenge(challenge, origin, privateKey);
+      expect(
+        await verifyDilithiumChallenge(challenge, "https://evil.example.com", signature, publicKey)
+      ).toBe(false);
+    });
+
+    it("チャレンジが異なると検証に失敗する", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+
+      const signature = await signDilithiumChallenge(challenge, origin, privateKey);
+      const otherChallenge = new Uint8Array(challenge);
+      otherChallenge[0] ^= 0xff;
+      expect(await verifyDilithiumChallenge(otherChallenge, origin, signature, publicKey)).toBe(false);
+    });
+
+    it("短すぎるチャレンジは拒否される", async () => {
+      const { privateKey } = await generateDilithiumKeyPair();
+
+      await expect(signDilithiumChallenge(new Uint8Array(8), origin, privateKey)).rejects.toThrow(
+        /Challenge is too short/
+      );
+    });
+  });
+
+  describe("他の実装との相互運用（OpenSSLで生成したML-DSA-65の署名）", () => {
+    const publicKey = hexToBytes(OPENSSL_KAT.publicKey);
+    const message = hexToBytes(OPENSSL_KAT.message);
+
+    it("pureモード（空のコンテキスト文字列）の署名を検証できる", async () => {
+      const signature = hexToBytes(OPENSSL_KAT.pure.signature);
+
+      expect(await verifyDilithium(message, signature, publicKey)).toBe(true);
+      expect(await verifyDilithiumWithContext(message, new Uint8Array(0), signature, publicKey)).toBe(true);
+      expect(await verifyDilithium(new TextEncoder().encode("abd"), signature, publicKey)).toBe(false);
+    });
+
+    it("HashML-DSA（SHA-256）の署名を検証できる", async () => {
+      const { oid, signature } = OPENSSL_KAT.hashMlDsaSha256;
+      const digest = await hashSHA256(message);
+
+      expect(await verifyDilithiumPrehash(digest, oid, hexToBytes(signature), publicKey)).toBe(true);
+      expect(await verifyDilithium(message, hexToBytes(signature), publicKey)).toBe(false);
+    });
+
+    it("HashML-DSA（SHA-512）の署名をsign_prehashedと同じ関数で検証できる", async () => {
+      const { oid, signature } = OPENSSL_KAT.hashMlDsaSha512;
+      const digest = await hashSHA512(message);
+
+      expect(oid).toBe(DILITHIUM_PREHASH_OID.SHA512);
+      expect(await verifyDilithiumPrehashed(digest, hexToBytes(signature), publicKey)).toBe(true);
+      expect(await verifyDilithiumPrehash(digest, oid, hexToBytes(signature), publicKey)).toBe(true);
+      expect(await verifyDilithiumPrehashed(await hashSHA512(new Uint8Array(0)), hexToBytes(signature), publicKey)).toBe(
+        false
+      );
+    });
+  });
+
+  describe("事前ハッシュモード（HashML-DSA）", () => {
+    // SHA-256("abc")
+    const DIGEST = hexToBytes("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    // SHA-256のOID（2.16.840.1.101.3.4.2.1）のDERエンコード
+    const SHA256_OID_DER = hexToBytes("0609608648016503040201");
+
+    it("ハッシュ値に署名して検証できる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const signature = await signDilithiumPrehash(DIGEST, DILITHIUM_PREHASH_OID.SHA256, privateKey);
+
+      expect(await verifyDilithiumPrehash(DIGEST, DILITHIUM_PREHASH_OID.SHA256, signature, publicKey)).toBe(true);
+      expect(await hashSHA256(new TextEncoder().encode("abc"))).toEqual(DIGEST);
+    });
+
+    it("pureモードの署名とは区別される", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const pureSignature = await signDilithium(DIGEST, privateKey);
+      const prehashSignature = await signDilithiumPrehash(DIGEST, DILITHIUM_PREHASH_OID.SHA256, privateKey);
+
+      expect(await verifyDilithiumPrehash(DIGEST, DILITHIUM_PREHASH_OID.SHA256, pureSignature, publicKey)).toBe(false);
+      expect(await verifyDilithium(DIGEST, prehashSignature, publicKey)).toBe(false);
+    });
+
+    it("HashML-DSAのM'の形をしたメッセージへのpureモードの署名は事前ハッシュモードの署名として通らない", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      // M' = 0x01 || len(ctx)=0 || OID(DER) || PH(M)
+      const framed = new Uint8Array([0x01, 0x00, ...SHA256_OID_DER, ...DIGEST]);
+      const signature = await signDilithium(framed, privateKey);
+
+      expect(await verifyDilithium(framed, signature, publicKey)).toBe(true);
+      expect(await verifyDilithiumPrehash(DIGEST, DILITHIUM_PREHASH_OID.SHA256, signature, publicKey)).toBe(false);
+
+      const prehashSignature = await signDilithiumPrehash(DIGEST, DILITHIUM_PREHASH_OID.SHA256, privateKey);
+      expect(await verifyDilithium(framed, prehashSignature, publicKey)).toBe(false);
+    });
+
+    it("異なるOIDでは検証に失敗する", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const signature = await signDilithiumPrehash(DIGEST, DILITHIUM_PREHASH_OID.SHA256, privateKey);
+
+      // SHA3-256もダイジェスト長は32バイトだが、OIDが異なるため一致しない
+      expect(await verifyDilithiumPrehash(DIGEST, DILITHIUM_PREHASH_OID.SHA3_256, signature, publicKey)).toBe(false);
+    });
+
+    it("未対応のOIDやハッシュ値の長さの不一致は拒否される", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+
+      await expect(signDilithiumPrehash(DIGEST, "1.2.840.113549.2.5", privateKey)).rejects.toThrow(
+        /Unsupported hash function OID/
+      );
+      await expect(signDilithiumPrehash(DIGEST, DILITHIUM_PREHASH_OID.SHA512, privateKey)).rejects.toThrow(
+        /Invalid digest size/
+      );
+
+      const signature = await signDilithiumPrehash(DIGEST, DILITHIUM_PREHASH_OID.SHA256, privateKey);
+      expect(
+        await verifyDilithiumPrehash(DIGEST.slice(1), DILITHIUM_PREHASH_OID.SHA256, signature, publicKey)
+      ).toBe(false);
+    });
+
+    describe("SHA-512専用の関数", () => {
+      // SHA-512のOID（2.16.840.1.101.3.4.2.3）のDERエンコード
+      const SHA512_OID_DER = hexToBytes("0609608648016503040203");
+
+      it("SHA-512のハッシュ値に署名して検証でき、OIDを指定する関数と相互に検証できる", async () => {
+        const { publicKey, privateKey } = await generateDilithiumKeyPair();
+        const digest = await hashSHA512(new Uint8Array(1024 * 1024).fill(0x61));
+        const signature = await signDilithiumPrehashed(digest, privateKey);
+
+        expect(await verifyDilithiumPrehashed(digest, signature, publicKey)).toBe(true);
+        expect(await verifyDilithiumPrehash(digest, DILITHIUM_PREHASH_OID.SHA512, signature, publicKey)).toBe(true);
+
+        const generic = await signDilithiumPrehash(digest, DILITHIUM_PREHASH_OID.SHA512, privateKey);
+        expect(await verifyDilithiumPrehashed(digest, generic, publicKey)).toBe(true);
+      });
+
+      it("pureモードの署名とは区別される", async () => {
+        const { publicKey, privateKey } = await generateDilithiumKeyPair();
+        const digest = await hashSHA512(new TextEncoder().encode("abc"));
+        const signature = await signDilithiumPrehashed(digest, privateKey);
+
+        // M' = 0x01 || len(ctx)=0 || OID(DER) || PH(M)
+        const framed = new Uint8Array([0x01, 0x00, ...SHA512_OID_DER, ...digest]);
+        expect(await verifyDilithium(framed, signature, publicKey)).toBe(false);
+        expect(await verifyDilithiumPrehashed(digest, await signDilithium(framed, privateKey), publicKey)).toBe(false);
+        expect(await verifyDilithium(digest, signature, publicKey)).toBe(false);
+        expect(await verifyDilithiumPrehashed(digest, await signDilithium(digest, privateKey), publicKey)).toBe(false);
+      });
+
+      it("64バイトでないハッシュ値は拒否される", async () => {
+        const { publicKey, privateKey } = await generateDilithiumKeyPair();
+        const signature = await signDilithiumPrehashed(await hashSHA512(new Uint8Array(0)), privateKey);
+
+        await expect(signDilithiumPrehashed(DIGEST, privateKey)).rejects.toThrow(
+          "Invalid digest size for OID 2.16.840.1.101.3.4.2.3: expected 64 bytes, got 32"
+        );
+        expect(await verifyDilithiumPrehashed(DIGEST, signature, publicKey)).toBe(false);
+      });
+    });
+  });
+
+  describe("base64でエンコードされた入力での検証", () => {
+    it("base64の署名・公開鍵で検証できる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const signature = await signDilithium(new TextEncoder().encode("Encoded inputs"), privateKey);
+
+      expect(
+        await verifyDilithiumEncoded("Encoded inputs", bytesToBase64(signature), bytesToBase64(publicKey))
+      ).toBe(true);
+      expect(
+        await verifyDilithiumEncoded("Other message", bytesToBase64(signature), bytesToBase64(publicKey))
+      ).toBe(false);
+    });
+
+    it("ML-DSA-44・87の署名・公開鍵も検証できる", async () => {
+      for (const level of ["ML-DSA-44", "ML-DSA-87"] as const) {
+        const { publicKey, privateKey } = await generateDilithiumKeyPairForLevel(level);
+        const signature = await signDilithium(new TextEncoder().encode("Encoded inputs"), privateKey);
+
+        expect(
+          await verifyDilithiumEncoded("Encoded inputs", bytesToBase64(signature), bytesToBase64(publicKey))
+        ).toBe(true);
+      }
+    });
+
+    it("不正なbase64は分かりやすいエラーになる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const signature = await signDilithium(new TextEncoder().encode("Encoded inputs"), privateKey);
+
+      await expect(
+        verifyDilithiumEncoded("Encoded inputs", "not base64!", bytesToBase64(publicKey))
+      ).rejects.toThrow(/Invalid base64 in signature/);
+      await expect(
+        verifyDilithiumEncoded("Encoded inputs", bytesToBase64(signature), "@@@@")
+      ).rejects.toThrow(/Invalid base64 in public key/);
+    });
+
+    it("サイズが不正な入力は拒否される", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const signature = await signDilithium(new TextEncoder().encode("Encoded inputs"), privateKey);
+
+      await expect(
+        verifyDilithiumEncoded("Encoded inputs", bytesToBase64(signature.slice(1)), bytesToBase64(publicKey))
+      ).rejects.toThrow("Invalid signature size for ML-DSA-65: expected 3309 bytes, got 3308");
+      // 署名と公開鍵を取り違えた場合
+      await expect(
+        verifyDilithiumEncoded("Encoded inputs", bytesToBase64(publicKey), bytesToBase64(signature))
+      ).rejects.toThrow(/Invalid public key size/);
+      await expect(
+        verifyDilithiumEncoded("Encoded inputs", bytesToBase64(signature), bytesToBase64(publicKey.slice(1)))
+      ).rejects.toThrow(/Invalid public key size/);
+    });
+  });
+
+  describe("複数の候補の公開鍵での検証", () => {
+    it("署名した鍵のインデックスを返す", async () => {
+      const oldKey = await generateDilithiumKeyPair();
+      const newKey = await generateDilithiumKeyPair();
+      const message = new TextEncoder().encode("Key rotation");
+
+      const signature = await signDilithium(message, newKey.privateKey);
+      expect(
+        await verifyDilithiumAny(message, signature, [oldKey.publicKey, newKey.publicKey])
+      ).toBe(1);
+
+      const oldSignature = await signDilithium(message, oldKey.privateKey);
+      expect(
+        await verifyDilithiumAny(message, oldSignature, [oldKey.publicKey, newKey.publicKey])
+      ).toBe(0);
+    });
+
+    it("どの鍵でも検証できない場合はnullを返す", async () => {
+      const signer = await generateDilithiumKeyPair();
+      const other = await generateDilithiumKeyPair();
+      const message = new TextEncoder().encode("Key rotation");
+
+      const signature = await signDilithium(message, signer.privateKey);
+      expect(await verifyDilithiumAny(message, signature, [other.publicKey])).toBeNull();
+      expect(await verifyDilithiumAny(message, signature, [])).toBeNull();
+    });
+  });
+
+  describe("まとめての検証", () => {
+    it("組ごとの検証結果を返す", async () => {
+      const keypairs = await Promise.all([44, 65, 87].map((level) => generateDilithiumKeyPair(level as 44 | 65 | 87)));
+      const messages = keypairs.map((_, i) => new TextEncoder().encode(`Batch ${i}`));
+      const signatures = await Promise.all(keypairs.map((keypair, i) => signDilithium(messages[i], keypair.privateKey)));
+      const publicKeys = keypairs.map((keypair) => keypair.publicKey);
+
+      expect(await verifyDilithiumBatch(messages, signatures, publicKeys)).toEqual([true, true, true]);
+
+      // 2番目のメッセージを差し替え、3番目の署名を切り詰める
+      const tampered = [messages[0], new TextEncoder().encode("Tampered"), messages[2]];
+      const truncated = [signatures[0], signatures[1], signatures[2].slice(1)];
+      expect(await verifyDilithiumBatch(tampered, truncated, publicKeys)).toEqual([true, false, false]);
+    });
+
+    it("空の配列からは空の結果が得られる", async () => {
+      expect(await verifyDilithiumBatch([], [], [])).toEqual([]);
+    });
+
+    it("配列の長さが一致しない場合はエラーになる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const message = new TextEncoder().encode("Mismatch");
+      const signature = await signDilithium(message, privateKey);
+
+      await expect(verifyDilithiumBatch([message, message], [signature], [publicKey])).rejects.toThrow(
+        "Batch length mismatch: 2 messages, 1 signatures, 1 public keys"
+      );
+      await expect(
+        verifyDilithiumBatch([message], [signature], ["not bytes" as unknown as Uint8Array])
+      ).rejects.toThrow("Public key 0 is not a Uint8Array");
+    });
+  });
+
+  describe("シードからの決定的な鍵生成", () => {
+    const seedOf = (fill: number) => new Uint8Array(32).map((_, i) => (i + fill) & 0xff);
+
+    it("同じシードからは同じ鍵ペアが得られ、異なるシードからは異なる鍵ペアが得られる", async () => {
+      const a = await generateDilithiumKeyPairFromSeed(seedOf(1));
+      const b = await generateDilithiumKeyPairFromSeed(seedOf(1));
+      const c = await generateDilithiumKeyPairFromSeed(seedOf(2));
+
+      expect(a).toEqual(b);
+      expect(c.publicKey).not.toEqual(a.publicKey);
+    });
+
+    it("まとめて生成した鍵ペアはシードごとに生成した鍵ペアと一致する", async () => {
+      const seeds = [seedOf(1), seedOf(2), seedOf(3)];
+      const keypairs = await generateDilithiumKeyPairsFromSeeds(seeds);
+
+      expect(keypairs).toHaveLength(seeds.length);
+      for (const [i, seed] of seeds.entries()) {
+        expect(keypairs[i]).toEqual(await generateDilithiumKeyPairFromSeed(seed));
+      }
+
+      const message = new TextEncoder().encode("provisioned");
+      const signature = await signDilithium(message, keypairs[1].privateKey);
+      expect(await verifyDilithium(message, signature, keypairs[1].publicKey)).toBe(true);
+    });
+
+    it("空の配列からは空の配列が得られる", async () => {
+      expect(await generateDilithiumKeyPairsFromSeeds([])).toEqual([]);
+    });
+
+    it("不正なシードは拒否され、まとめて生成する場合は位置が示される", async () => {
+      await expect(generateDilithiumKeyPairFromSeed(new Uint8Array(31))).rejects.toThrow(
+        "Invalid seed size: expected 32, got 31"
+      );
+      await expect(generateDilithiumKeyPairFromSeed(new Uint8Array(32))).rejects.toThrow(
+        "Invalid seed: all bytes are zero"
+      );
+      await expect(
+        generateDilithiumKeyPairsFromSeeds([seedOf(1), new Uint8Array(32)])
+      ).rejects.toThrow("Seed 1: Invalid seed: all bytes are zero");
+    });
+  });
+
+  describe("レベルタグ付きの封筒形式の署名", () => {
+    const levels: DilithiumLevel[] = ["ML-DSA-44", "ML-DSA-65", "ML-DSA-87"];
+    const message = new TextEncoder().encode("enveloped signature");
+
+    it("各レベルの署名は同じレベルの公開鍵でのみ検証でき、他のレベルでは理由付きで拒否される", async () => {
+      const keypairs = await Promise.all(levels.map((level) => generateDilithiumKeyPairForLevel(level)));
+
+      for (const [i, signer] of levels.entries()) {
+        const envelope = await signDilithiumEnveloped(message, keypairs[i].privateKey);
+        expect(envelope[0]).toBe(Number(signer.slice("ML-DSA-".length)));
+
+        for (const [j, verifier] of levels.entries()) {
+          if (i === j) {
+            expect(await verifyDilithiumEnveloped(message, envelope, keypairs[j].publicKey)).toBe(true);
+            expect(
+              await verifyDilithiumEnveloped(new TextEncoder().encode("other"), envelope, keypairs[j].publicKey)
+            ).toBe(false);
+          } else {
+            await expect(verifyDilithiumEnveloped(message, envelope, keypairs[j].publicKey)).rejects.toThrow(
+              `ML-DSA level mismatch: signature is ${signer}, public key is ${verifier}`
+            );
+          }
+        }
+      }
+    });
+
+    it("ML-DSA-65の署名部分は通常のverifyでも検証できる", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const envelope = await signDilithiumEnveloped(message, privateKey);
+
+      expect(await verifyDilithium(message, envelope.slice(1), publicKey)).toBe(true);
+    });
+
+    it("レベルタグを書き換えた署名はレベルの不一致として拒否される", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPairForLevel("ML-DSA-65");
+      const envelope = await signDilithiumEnveloped(message, privateKey);
+
+      const relabeled = new Uint8Array(envelope);
+      relabeled[0] = 44;
+      await expect(verifyDilithiumEnveloped(message, relabeled, publicKey)).rejects.toThrow(
+        "ML-DSA level mismatch: signature is ML-DSA-44, public key is ML-DSA-65"
+      );
+
+      relabeled[0] = 0x01;
+      await expect(verifyDilithiumEnveloped(message, relabeled, publicKey)).rejects.toThrow(
+        "Unknown ML-DSA level tag in signature envelope: 0x01"
+      );
+    });
+
+    it("不正な形式の封筒・鍵は拒否される", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPairForLevel("ML-DSA-44");
+      const envelope = await signDilithiumEnveloped(message, privateKey);
+
+      await expect(verifyDilithiumEnveloped(message, new Uint8Array(0), publicKey)).rejects.toThrow(
+        "Empty signature envelope"
+      );
+      await expect(verifyDilithiumEnveloped(message, envelope.slice(0, -1), publicKey)).rejects.toThrow(
+        /Invalid ML-DSA-44 signature size/
+      );
+      await expect(verifyDilithiumEnveloped(message, envelope, publicKey.slice(1))).rejects.toThrow(
+        /does not match any ML-DSA level/
+      );
+      await expect(signDilithiumEnveloped(message, privateKey.slice(1))).rejects.toThrow(
+        /does not match any ML-DSA level/
+      );
+      await expect(generateDilithiumKeyPairForLevel("ML-DSA-99" as DilithiumLevel)).rejects.toThrow(
+        /Unknown ML-DSA parameter set/
+      );
+    });
+  });
+
+  describe("封筒形式の署名のコンテキスト文字列・鍵ハッシュ", () => {
+    const message = new TextEncoder().encode("enveloped signature");
+    const context = new TextEncoder().encode("example.com/invoice");
+
+    it("整形式の封筒からメタデータを読み取れる", async () => {
+      const { privateKey } = await generateDilithiumKeyPairForLevel("ML-DSA-87");
+
+      const plain = await signDilithiumEnveloped(message, privateKey);
+      expect(await readDilithiumSignatureEnvelope(plain)).toEqual({
+        algo: "ML-DSA",
+        level: "ML-DSA-87",
+        context: null,
+        has_keyhash: false,
+      });
+
+      const extended = await signDilithiumEnveloped(message, privateKey, { context, includeKeyHash: true });
+      expect(extended[0]).toBe(87 | 0x80);
+      expect(extended[1]).toBe(0x03);
+      const metadata = await readDilithiumSignatureEnvelope(extended);
+      expect(metadata.level).toBe("ML-DSA-87");
+      expect(metadata.has_keyhash).toBe(true);
+      expect(new Uint8Array(metadata.context!)).toEqual(context);
+    });
+
+    it("コンテキスト付きの署名は検証に成功した場合のみメタデータを返す", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const envelope = await signDilithiumEnveloped(message, privateKey, { context });
+
+      expect(await verifyDilithiumEnveloped(message, envelope, publicKey)).toBe(true);
+      const metadata = await verifyDilithiumEnvelopedAndExtract(message, envelope, publicKey);
+      expect(new Uint8Array(metadata.context!)).toEqual(context);
+      expect(metadata.has_keyhash).toBe(false);
+
+      await expect(
+        verifyDilithiumEnvelopedAndExtract(new TextEncoder().encode("other"), envelope, publicKey)
+      ).rejects.toThrow("Signature verification failed");
+    });
+
+    it("コンテキスト文字列を書き換えると検証に失敗する", async () => {
+      const { publicKey, privateKey } = await generateDilithiumKeyPair();
+      const envelope = await signDilithiumEnveloped(message, privateKey, { context });
+
+      // タグ (1バイト) || flags (1バイト) || len(ctx) (1バイト) の後にコンテキスト文字列が続く
+      const tampered = new Uint8Array(envelope);
+      tampered[3] ^= 0x01;
+      expect(await verifyDilithiumEnveloped(message, tampered, publicKey)).toBe(false);
+      await expect(verifyDilithiumEnvelopedAndExtract(message, tampered, publicKey)).rejects.toThrow(
+        "Signature verification failed"
+      );
+    });
+
+    it("鍵ハッシュが公開鍵と一致しない場合は拒否される", async () => {
+      const signer = await generateDilithiumKeyPair();
+      const other = await generateDilithiumKeyPair();
+      const envelope = await signDilithiumEnveloped(message, signer.privateKey, { includeKeyHash: true });
+
+      expect(await verifyDilithiumEnveloped(message, envelope, signer.publicKey)).toBe(true);
+      await expect(verifyDilithiumEnveloped(message, envelope, other.publicKey)).rejects.toThrow(
+        "Key hash in signature envelope does not match the public key"
+      );
+    });
+
+    it("不正な形式の封筒は拒否される", async () => {
+      const { privateKey } = await generateDilithiumKeyPair();
+      const envelope = await signDilithiumEnveloped(message, privateKey, { context });
+
+      await expect(readDilithiumSignatureEnvelope(new Uint8Array(0))).rejects.toThrow("Empty signature envelope");
+      await expect(readDilithiumSignatureEnvelope(envelope.slice(0, 1))).rejects.toThrow(/flags are missing/);
+      await expect(readDilithiumSignatureEnvelope(envelope.slice(0, 5))).rejects.toThrow(/context is missing/);
+      await expect(readDilithiumSignatureEnvelope(envelope.slice(0, -1))).rejects.toThrow(
+        /Invalid ML-DSA-65 signature size/
+      );
+
+      const badFlags = new Uint8Array(envelope);
+      badFlags[1] = 0x04;
+      await expect(readDilithiumSignatureEnvelope(badFlags)).rejects.toThrow(
+        "Invalid flags in signature envelope: 0x04"
+      );
+
+      await expect(
+        signDilithiumEnveloped(message, privateKey, { context: new Uint8Array(256) })
+      ).rejects.toThrow(/Context is too long/);
+    });
+  });
+
+  describe("鍵識別子（KID）", () => {
+    it("同じ公開鍵からは常に同じKIDを導出する", async () => {
+      const { publicKey } = await generateDilithiumKeyPair();
+      const kid = await getDilithiumKeyId(publicKey);
+
+      expect(kid).toMatch(/^[A-Za-z0-9_-]{22}$/);
+      expect(await getDilithiumKeyId(publicKey.slice())).toBe(kid);
+    });
+
+    it("異なる公開鍵からは異なるKIDを導出する", async () => {
+      const first = await generateDilithiumKeyPair();
+      const second = await generateDilithiumKeyPair();
+      const level87 = await generateDilithiumKeyPairForLevel("ML-DSA-87");
+
+      const kids = new Set([
+        await getDilithiumKeyId(first.publicKey),
+        await getDilithiumKeyId(second.publicKey),
+        await getDilithiumKeyId(level87.publicKey),
+      ]);
+      expect(kids.size).toBe(3);
+    });
+
+    it("公開鍵のサイズが不正な場合はエラーになる", async () => {
+      await expect(getDilithiumKeyId(new Uint8Array(32))).rejects.toThrow(/does not match any ML-DSA level/);
+    });
+  });
 });
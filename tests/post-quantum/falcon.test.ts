@@ -1,16 +1,31 @@
 import { describe, it, expect } from "vitest";
 import {
   initFalcon,
+  testFalcon,
   generateFalconKeyPair,
   signFalcon,
   verifyFalcon,
+  verifyFalconAny,
+  verifyFalconEncoded,
+  exportFalconPublicKeyJwk,
+  importFalconPublicKeyJwk,
+  getFalconKeyId,
+  generateFalconKeyPairFromSeed,
+  deriveFalconChildSeed,
+  deriveFalconPathSeed,
+  type FalconDegree,
 } from "../../src/post-quantum/falcon.js";
+import { bytesToBase64 } from "../../src/utils/format.js";
 
 describe("FALCON (FN-DSA)", () => {
   it("初期化が成功する", async () => {
     await expect(initFalcon()).resolves.not.toThrow();
   });
 
+  it("ヘルスチェックが成功する", async () => {
+    expect(await testFalcon()).toBe(true);
+  });
+
   it("鍵ペアを生成できる", async () => {
     const keypair = await generateFalconKeyPair();
     expect(keypair.publicKey).toBeInstanceOf(Uint8Array);
@@ -81,4 +96,220 @@ describe("FALCON (FN-DSA)", () => {
     expect(signature.length).toBeGreaterThan(0);
     expect(isValid).toBe(true);
   });
+
+  it("公開鍵をJWK経由で往復変換できる", async () => {
+    await initFalcon();
+
+    const { publicKey, privateKey } = await generateFalconKeyPair();
+
+    const jwk = await exportFalconPublicKeyJwk(publicKey);
+    expect(jwk.kty).toBe("AKP");
+    expect(jwk.alg).toBe("FN-DSA-512");
+    expect(jwk.pub).toMatch(/^[A-Za-z0-9_-]+$/);
+
+    // JSON文字列を経由しても復元できる
+    const restored = await importFalconPublicKeyJwk(JSON.parse(JSON.stringify(jwk)));
+    expect(restored).toEqual(publicKey);
+
+    // 復元した公開鍵で署名を検証できる
+    const message = new TextEncoder().encode("JWK round trip");
+    const signature = await signFalcon(message, privateKey);
+    expect(await verifyFalcon(message, signature, restored)).toBe(true);
+  });
+
+  it("アルゴリズムが異なるJWKは拒否される", async () => {
+    await initFalcon();
+
+    const { publicKey } = await generateFalconKeyPair();
+    const jwk = await exportFalconPublicKeyJwk(publicKey);
+
+    await expect(
+      importFalconPublicKeyJwk({ ...jwk, alg: "ES256" } as unknown as typeof jwk)
+    ).rejects.toThrow();
+  });
+
+  describe("FALCON-1024", () => {
+    it("FALCON-1024の鍵ペアで署名と検証ができる", async () => {
+      const { publicKey, privateKey } = await generateFalconKeyPair(1024);
+      const message = new TextEncoder().encode("Hello, FALCON-1024!");
+      const signature = await signFalcon(message, privateKey);
+
+      expect(publicKey.length).toBe(1793);
+      expect(await verifyFalcon(message, signature, publicKey)).toBe(true);
+      expect(await verifyFalcon(new TextEncoder().encode("Other"), signature, publicKey)).toBe(false);
+    });
+
+    it("次数を省略するとFALCON-512の鍵ペアになる", async () => {
+      const { publicKey } = await generateFalconKeyPair();
+      expect(publicKey.length).toBe(897);
+    });
+
+    it("次数が異なる公開鍵では検証に失敗する", async () => {
+      const falcon512 = await generateFalconKeyPair(512);
+      const falcon1024 = await generateFalconKeyPair(1024);
+      const message = new TextEncoder().encode("Degree mismatch");
+
+      const signature1024 = await signFalcon(message, falcon1024.privateKey);
+      // 署名の形式が公開鍵の次数と異なるため、falseかエラーになる
+      expect(await verifyFalcon(message, signature1024, falcon512.publicKey).catch(() => false)).toBe(false);
+      expect(await verifyFalconAny(message, signature1024, [falcon512.publicKey, falcon1024.publicKey])).toBe(1);
+    });
+
+    it("シードから決定的に生成でき、次数ごとに異なる鍵になる", async () => {
+      const seed = new Uint8Array(32).fill(0x24);
+      const first = await generateFalconKeyPairFromSeed(seed, 1024);
+      const second = await generateFalconKeyPairFromSeed(seed, 1024);
+      const falcon512 = await generateFalconKeyPairFromSeed(seed);
+
+      expect(first.publicKey).toEqual(second.publicKey);
+      expect(first.privateKey).toEqual(second.privateKey);
+      expect(falcon512.publicKey.length).toBe(897);
+    });
+
+    it("JWKのアルゴリズム名はFN-DSA-1024になる", async () => {
+      const { publicKey } = await generateFalconKeyPair(1024);
+      const jwk = await exportFalconPublicKeyJwk(publicKey);
+
+      expect(jwk.alg).toBe("FN-DSA-1024");
+      expect(await importFalconPublicKeyJwk(jwk)).toEqual(publicKey);
+
+      // FALCON-1024の公開鍵をFN-DSA-512として取り込むことはできない
+      await expect(importFalconPublicKeyJwk({ ...jwk, alg: "FN-DSA-512" })).rejects.toThrow(/Invalid public key/);
+    });
+
+    it("未対応の次数は拒否される", async () => {
+      await expect(generateFalconKeyPair(768 as FalconDegree)).rejects.toThrow(
+        "Unsupported FALCON degree: 768 (expected 512 or 1024)"
+      );
+    });
+  });
+
+  describe("階層的な鍵の導出", () => {
+    // 固定のルートシード
+    const rootSeed = new Uint8Array(32).fill(0x07);
+
+    it("同じシードからは同じ鍵ペアが生成される", async () => {
+      const first = await generateFalconKeyPairFromSeed(rootSeed);
+      const second = await generateFalconKeyPairFromSeed(rootSeed);
+
+      expect(first.publicKey).toEqual(second.publicKey);
+      expect(first.privateKey).toEqual(second.privateKey);
+    });
+
+    it("同じパスからは同じ子の鍵が導出される", async () => {
+      const first = await generateFalconKeyPairFromSeed(await deriveFalconPathSeed(rootSeed, [0, 3]));
+      const second = await generateFalconKeyPairFromSeed(
+        await deriveFalconChildSeed(await deriveFalconChildSeed(rootSeed, 0), 3)
+      );
+
+      expect(first.publicKey).toEqual(second.publicKey);
+
+      // 再生成した子の秘密鍵で署名し、もう一方の公開鍵で検証できる
+      const message = new TextEncoder().encode("HD key");
+      const signature = await signFalcon(message, first.privateKey);
+      expect(await verifyFalcon(message, signature, second.publicKey)).toBe(true);
+    });
+
+    it("異なるパスからは異なる鍵が導出される", async () => {
+      const sibling0 = await deriveFalconPathSeed(rootSeed, [0]);
+      const sibling1 = await deriveFalconPathSeed(rootSeed, [1]);
+      const nested = await deriveFalconPathSeed(rootSeed, [0, 1]);
+
+      expect(sibling0).not.toEqual(sibling1);
+      expect(nested).not.toEqual(sibling1);
+
+      const key0 = await generateFalconKeyPairFromSeed(sibling0);
+      const key1 = await generateFalconKeyPairFromSeed(sibling1);
+      expect(key0.publicKey).not.toEqual(key1.publicKey);
+    });
+
+    it("32バイト以外のシードは拒否される", async () => {
+      await expect(deriveFalconChildSeed(new Uint8Array(16), 0)).rejects.toThrow();
+      await expect(generateFalconKeyPairFromSeed(new Uint8Array(31))).rejects.toThrow();
+    });
+  });
+
+  describe("base64でエンコードされた入力での検証", () => {
+    it("base64の署名・公開鍵で検証できる", async () => {
+      const { publicKey, privateKey } = await generateFalconKeyPair();
+      const signature = await signFalcon(new TextEncoder().encode("Encoded inputs"), privateKey);
+
+      expect(
+        await verifyFalconEncoded("Encoded inputs", bytesToBase64(signature), bytesToBase64(publicKey))
+      ).toBe(true);
+      expect(
+        await verifyFalconEncoded("Other message", bytesToBase64(signature), bytesToBase64(publicKey))
+      ).toBe(false);
+    });
+
+    it("不正なbase64は分かりやすいエラーになる", async () => {
+      const { publicKey, privateKey } = await generateFalconKeyPair();
+      const signature = await signFalcon(new TextEncoder().encode("Encoded inputs"), privateKey);
+
+      await expect(
+        verifyFalconEncoded("Encoded inputs", "not base64!", bytesToBase64(publicKey))
+      ).rejects.toThrow(/Invalid base64 in signature/);
+      await expect(
+        verifyFalconEncoded("Encoded inputs", bytesToBase64(signature), "@@@@")
+      ).rejects.toThrow(/Invalid base64 in public key/);
+    });
+
+    it("形式が不正な公開鍵は拒否される", async () => {
+      const { publicKey, privateKey } = await generateFalconKeyPair();
+      const signature = await signFalcon(new TextEncoder().encode("Encoded inputs"), privateKey);
+
+      await expect(
+        verifyFalconEncoded("Encoded inputs", bytesToBase64(signature), bytesToBase64(publicKey.slice(1)))
+      ).rejects.toThrow(/Invalid public key/);
+    });
+  });
+
+  describe("複数の候補の公開鍵での検証", () => {
+    it("署名した鍵のインデックスを返す", async () => {
+      const oldKey = await generateFalconKeyPair();
+      const newKey = await generateFalconKeyPair();
+      const message = new TextEncoder().encode("Key rotation");
+
+      const signature = await signFalcon(message, newKey.privateKey);
+      expect(
+        await verifyFalconAny(message, signature, [oldKey.publicKey, newKey.publicKey])
+      ).toBe(1);
+
+      const oldSignature = await signFalcon(message, oldKey.privateKey);
+      expect(
+        await verifyFalconAny(message, oldSignature, [oldKey.publicKey, newKey.publicKey])
+      ).toBe(0);
+    });
+
+    it("どの鍵でも検証できない場合はnullを返す", async () => {
+      const signer = await generateFalconKeyPair();
+      const other = await generateFalconKeyPair();
+      const message = new TextEncoder().encode("Key rotation");
+
+      const signature = await signFalcon(message, signer.privateKey);
+      expect(await verifyFalconAny(message, signature, [other.publicKey])).toBeNull();
+      expect(await verifyFalconAny(message, signature, [])).toBeNull();
+    });
+  });
+
+  describe("鍵識別子（KID）", () => {
+    it("同じ公開鍵からは常に同じKIDを導出する", async () => {
+      const { publicKey } = await generateFalconKeyPair();
+      const kid = await getFalconKeyId(publicKey);
+
+      expect(kid).toMatch(/^[A-Za-z0-9_-]{22}$/);
+      expect(await getFalconKeyId(publicKey.slice())).toBe(kid);
+    });
+
+    it("異なる公開鍵からは異なるKIDを導出する", async () => {
+      const first = await generateFalconKeyPair();
+      const second = await generateFalconKeyPair();
+
+      expect(await getFalconKeyId(first.publicKey)).not.toBe(await getFalconKeyId(second.publicKey));
+    });
+
+    it("不正な公開鍵はエラーになる", async () => {
+      await expect(getFalconKeyId(new Uint8Array(32))).rejects.toThrow(/Invalid public key/);
+    });
+  });
 });
@@ -2,19 +2,58 @@
  * CRYSTALS-Kyber (ML-KEM) のテスト
  */
 
+import { readFileSync } from "node:fs";
 import { describe, it, expect } from "vitest";
 import {
   initKyber,
+  testKyber,
+  checkKyberEntropy,
   generateKyberKeyPair,
+  generateKyberKeyPairFromSeed,
+  generateKyberKeyPairsFromSeeds,
   encapsulateKyber,
+  encapsulateKyberDeterministic,
   decapsulateKyber,
+  encapsulateKyberForTransport,
+  readKyberTransport,
+  initiateKyberSession,
+  respondKyberSession,
+  createKyberEncryptStream,
+  createKyberDecryptStream,
+  sealKyberMulti,
+  openKyberMulti,
+  sealKyberSeekable,
+  getKyberSeekableHeaderSize,
+  getKyberSeekableChunkRange,
+  decryptKyberRange,
+  getKyberKeyId,
+  getSupportedKyberCiphertextVersions,
+  recommendKyberAead,
+  generateKyberHybridKeyPair,
+  hybridEncapsulateKyber,
+  hybridDecapsulateKyber,
 } from "../../src/post-quantum/kyber.js";
+import { bytesToHex, hexToBytes } from "../../src/utils/format.js";
+
+const kyberWasm: any = await import("../../wasm-src/kyber-wasm/pkg/kyber_wasm.js");
+// set_test_rng_modeはtest-rng featureを有効にしたビルドでのみ公開される
+//   wasm-pack build --target web -- --features test-rng
+const hasTestRng = typeof kyberWasm.set_test_rng_mode === "function";
+
+// OpenSSLで生成したML-KEM-768の既知解（生成手順はフィクスチャのsourceを参照）
+const OPENSSL_KAT = JSON.parse(
+  readFileSync(new URL("./fixtures/ml-kem-768-openssl.json", import.meta.url), "utf8")
+);
 
 describe("CRYSTALS-Kyber (ML-KEM)", () => {
   it("初期化が成功する", async () => {
     await expect(initKyber()).resolves.not.toThrow();
   });
 
+  it("ヘルスチェックが成功する", async () => {
+    expect(await testKyber()).toBe(true);
+  });
+
   it("鍵ペアを生成できる", async () => {
     await initKyber();
     const keypair = await generateKyberKeyPair();
@@ -100,4 +139,964 @@ describe("CRYSTALS-Kyber (ML-KEM)", () => {
     // 間違った秘密鍵では正しい共有秘密が復元できない
     expect(wrongDecapsulated).not.toEqual(sharedSecret);
   });
+
+  describe("乱数生成器の動作確認", () => {
+    it("通常の乱数ではtrueを返す", async () => {
+      expect(await checkKyberEntropy()).toBe(true);
+    });
+
+    it.skipIf(!hasTestRng)("乱数がすべて0の場合はfalseを返す", async () => {
+      await initKyber();
+      try {
+        kyberWasm.set_test_rng_mode("zero_bytes", new Uint8Array());
+        expect(await checkKyberEntropy()).toBe(false);
+      } finally {
+        kyberWasm.set_test_rng_mode("os", new Uint8Array());
+      }
+      expect(await checkKyberEntropy()).toBe(true);
+    });
+
+    it.skipIf(!hasTestRng)("シードから決定的に生成している場合も連続する鍵は異なる", async () => {
+      await initKyber();
+      try {
+        kyberWasm.set_test_rng_mode("deterministic", new Uint8Array(32).fill(0x5a));
+        expect(await checkKyberEntropy()).toBe(true);
+      } finally {
+        kyberWasm.set_test_rng_mode("os", new Uint8Array());
+      }
+    });
+  });
+
+  describe("不正なサイズの入力", () => {
+    it("サイズの異なる公開鍵ではカプセル化できない", async () => {
+      const { publicKey } = await generateKyberKeyPair();
+
+      await expect(encapsulateKyber(publicKey.slice(1))).rejects.toThrow(
+        `Invalid public key size: expected 800, 1184, or 1568, got ${publicKey.length - 1}`
+      );
+    });
+
+    it("サイズの異なる暗号文・鍵ではデカプセル化できない", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const { ciphertext } = await encapsulateKyber(publicKey);
+
+      await expect(
+        decapsulateKyber(ciphertext.slice(1), privateKey, publicKey)
+      ).rejects.toThrow(/Invalid ciphertext size/);
+      await expect(
+        decapsulateKyber(ciphertext, new Uint8Array([...privateKey, 0]), publicKey)
+      ).rejects.toThrow(/Invalid secret key size/);
+      await expect(
+        decapsulateKyber(ciphertext, privateKey, new Uint8Array(0))
+      ).rejects.toThrow(/Invalid public key size: expected 800, 1184, or 1568, got 0/);
+    });
+
+    it("wasmの関数はサイズの不一致をtry/catchで扱えるエラーとして返す", async () => {
+      await initKyber();
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+
+      const caught = (run: () => unknown): string => {
+        try {
+          run();
+        } catch (error) {
+          return String(error);
+        }
+        throw new Error("expected an error");
+      };
+
+      expect(caught(() => kyberWasm.encapsulate(new Uint8Array(10)))).toBe(
+        "Invalid public key size: expected 800, 1184, or 1568, got 10"
+      );
+      expect(caught(() => kyberWasm.decapsulate(new Uint8Array(10), privateKey, publicKey))).toBe(
+        "Invalid ciphertext size: expected 1088, got 10"
+      );
+    });
+  });
+
+  describe("パラメータセットの選択", () => {
+    // レベルごとの公開鍵・秘密鍵・暗号文のサイズ（FIPS 203）
+    const SIZES = {
+      512: { publicKey: 800, privateKey: 1632, ciphertext: 768 },
+      768: { publicKey: 1184, privateKey: 2400, ciphertext: 1088 },
+      1024: { publicKey: 1568, privateKey: 3168, ciphertext: 1568 },
+    } as const;
+
+    for (const level of [512, 768, 1024] as const) {
+      it(`ML-KEM-${level}の鍵ペアで往復できる`, async () => {
+        const { publicKey, privateKey } = await generateKyberKeyPair(level);
+        expect(publicKey.length).toBe(SIZES[level].publicKey);
+        expect(privateKey.length).toBe(SIZES[level].privateKey);
+
+        const { ciphertext, sharedSecret } = await encapsulateKyber(publicKey);
+        expect(ciphertext.length).toBe(SIZES[level].ciphertext);
+        expect(sharedSecret.length).toBe(32);
+        expect(await decapsulateKyber(ciphertext, privateKey, publicKey)).toEqual(sharedSecret);
+      });
+    }
+
+    it("レベルを省略するとML-KEM-768の鍵ペアになる", async () => {
+      await initKyber();
+
+      const keypair = kyberWasm.generate_keypair();
+      try {
+        expect(keypair.level).toBe(768);
+        expect(keypair.public_key.length).toBe(SIZES[768].publicKey);
+      } finally {
+        keypair.free();
+      }
+    });
+
+    it("対応していないレベルはエラーになる", async () => {
+      await expect(generateKyberKeyPair(256 as any)).rejects.toThrow(
+        "Unsupported ML-KEM level: 256 (expected 512, 768, or 1024)"
+      );
+    });
+
+    it("別のレベルの秘密鍵・暗号文との組み合わせはサイズの不一致として拒否する", async () => {
+      const small = await generateKyberKeyPair(512);
+      const large = await generateKyberKeyPair(1024);
+      const { ciphertext } = await encapsulateKyber(large.publicKey);
+
+      await expect(decapsulateKyber(ciphertext, small.privateKey, large.publicKey)).rejects.toThrow(
+        /Invalid secret key size: expected 3168, got 1632/
+      );
+      await expect(decapsulateKyber(ciphertext, small.privateKey, small.publicKey)).rejects.toThrow(
+        /Invalid ciphertext size: expected 768, got 1568/
+      );
+    });
+
+    it("デカプセル化器は鍵のレベルを保持する", async () => {
+      await initKyber();
+      const { publicKey, privateKey } = await generateKyberKeyPair(1024);
+      const { ciphertext, sharedSecret } = await encapsulateKyber(publicKey);
+
+      const decapsulator = new kyberWasm.KyberDecapsulator(privateKey, publicKey);
+      try {
+        expect(decapsulator.level).toBe(1024);
+        expect(decapsulator.decapsulate(ciphertext)).toEqual(sharedSecret);
+      } finally {
+        decapsulator.free();
+      }
+    });
+
+    it("対応しているパラメータセットの一覧に含まれる", async () => {
+      await initKyber();
+
+      expect(kyberWasm.features().parameter_sets).toEqual(["ML-KEM-512", "ML-KEM-768", "ML-KEM-1024"]);
+    });
+  });
+
+  describe("転送形式", () => {
+    it("転送形式から取り出した暗号文でデカプセル化できる", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const { transport, sharedSecret } = await encapsulateKyberForTransport(publicKey);
+
+      // version (1バイト) || length (4バイト、ビッグエンディアン) || 暗号文
+      expect(transport[0]).toBe(1);
+      expect(new DataView(transport.buffer, transport.byteOffset).getUint32(1)).toBe(1088);
+      expect(transport.length).toBe(5 + 1088);
+
+      const ciphertext = await readKyberTransport(transport);
+      expect(ciphertext).toEqual(transport.slice(5));
+      expect(await decapsulateKyber(ciphertext, privateKey, publicKey)).toEqual(sharedSecret);
+    });
+
+    it("未対応のバージョンは拒否される", async () => {
+      const { publicKey } = await generateKyberKeyPair();
+      const { transport } = await encapsulateKyberForTransport(publicKey);
+
+      const tampered = transport.slice();
+      tampered[0] = 2;
+      await expect(readKyberTransport(tampered)).rejects.toThrow(
+        "Unsupported transport version: expected 1, got 2"
+      );
+    });
+
+    it("長さが一致しない転送形式は拒否される", async () => {
+      const { publicKey } = await generateKyberKeyPair();
+      const { transport } = await encapsulateKyberForTransport(publicKey);
+
+      await expect(readKyberTransport(transport.slice(0, transport.length - 1))).rejects.toThrow(
+        /Invalid ciphertext length in transport data/
+      );
+      await expect(readKyberTransport(transport.slice(0, 4))).rejects.toThrow("Transport data is too short");
+    });
+  });
+
+  describe("鍵確認付きのセッション確立", () => {
+    it("正しい暗号文と鍵確認タグでは同じ共有秘密になる", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const { ciphertext, sharedSecret, confirmationTag } = await initiateKyberSession(publicKey);
+      expect(confirmationTag.length).toBe(32);
+
+      expect(await respondKyberSession(ciphertext, privateKey, publicKey, confirmationTag)).toEqual(sharedSecret);
+    });
+
+    it("改ざんされた暗号文ではセッションを確立できない", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const { ciphertext, confirmationTag } = await initiateKyberSession(publicKey);
+
+      // デカプセル化自体は暗黙的拒否で成功するが、鍵確認で検出される
+      const tampered = ciphertext.slice();
+      tampered[0] ^= 0x01;
+      await expect(respondKyberSession(tampered, privateKey, publicKey, confirmationTag)).rejects.toThrow(
+        /Key confirmation failed/
+      );
+    });
+
+    it("鍵確認タグが一致しない場合はセッションを確立できない", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const { ciphertext, confirmationTag } = await initiateKyberSession(publicKey);
+
+      const wrongTag = confirmationTag.slice();
+      wrongTag[31] ^= 0x80;
+      await expect(respondKyberSession(ciphertext, privateKey, publicKey, wrongTag)).rejects.toThrow(
+        /Key confirmation failed/
+      );
+
+      // 別のセッションの鍵確認タグや、切り詰めたタグも拒否される
+      const other = await initiateKyberSession(publicKey);
+      await expect(
+        respondKyberSession(ciphertext, privateKey, publicKey, other.confirmationTag)
+      ).rejects.toThrow(/Key confirmation failed/);
+      await expect(
+        respondKyberSession(ciphertext, privateKey, publicKey, confirmationTag.slice(0, 16))
+      ).rejects.toThrow(/Key confirmation failed/);
+    });
+  });
+
+  describe("ハイブリッドKEM（X25519 + ML-KEM）", () => {
+    it("カプセル化とデカプセル化で同じ共有秘密を導出する", async () => {
+      const keypair = await generateKyberHybridKeyPair();
+      expect(keypair.classicalPublicKey.length).toBe(32);
+      expect(keypair.classicalPrivateKey.length).toBe(32);
+      expect(keypair.pqPublicKey.length).toBe(1184);
+
+      const { ciphertext, sharedSecret } = await hybridEncapsulateKyber(
+        keypair.classicalPublicKey,
+        keypair.pqPublicKey
+      );
+      expect(ciphertext.length).toBe(32 + 1088);
+      expect(sharedSecret.length).toBe(32);
+
+      const recovered = await hybridDecapsulateKyber(
+        ciphertext,
+        keypair.classicalPrivateKey,
+        keypair.pqPrivateKey,
+        keypair.pqPublicKey
+      );
+      expect(recovered).toEqual(sharedSecret);
+    });
+
+    it("ML-KEMの部分の共有秘密とは異なる値になる", async () => {
+      const keypair = await generateKyberHybridKeyPair();
+      const { ciphertext, sharedSecret } = await hybridEncapsulateKyber(
+        keypair.classicalPublicKey,
+        keypair.pqPublicKey
+      );
+
+      const mlkemSecret = await decapsulateKyber(ciphertext.slice(32), keypair.pqPrivateKey, keypair.pqPublicKey);
+      expect(mlkemSecret).not.toEqual(sharedSecret);
+    });
+
+    it("X25519の一時公開鍵を改ざんすると共有秘密が一致しない", async () => {
+      const keypair = await generateKyberHybridKeyPair();
+      const { ciphertext, sharedSecret } = await hybridEncapsulateKyber(
+        keypair.classicalPublicKey,
+        keypair.pqPublicKey
+      );
+
+      const tampered = ciphertext.slice();
+      tampered[0] ^= 0x01;
+      const recovered = await hybridDecapsulateKyber(
+        tampered,
+        keypair.classicalPrivateKey,
+        keypair.pqPrivateKey,
+        keypair.pqPublicKey
+      );
+      expect(recovered).not.toEqual(sharedSecret);
+    });
+
+    it("ML-KEM-1024の鍵でも往復できる", async () => {
+      const keypair = await generateKyberHybridKeyPair(1024);
+      const { ciphertext, sharedSecret } = await hybridEncapsulateKyber(
+        keypair.classicalPublicKey,
+        keypair.pqPublicKey
+      );
+
+      expect(ciphertext.length).toBe(32 + 1568);
+      expect(
+        await hybridDecapsulateKyber(ciphertext, keypair.classicalPrivateKey, keypair.pqPrivateKey, keypair.pqPublicKey)
+      ).toEqual(sharedSecret);
+    });
+
+    it("X25519公開鍵が小位数の点の場合はエラーになる", async () => {
+      const keypair = await generateKyberHybridKeyPair();
+
+      await expect(hybridEncapsulateKyber(new Uint8Array(32), keypair.pqPublicKey)).rejects.toThrow(
+        "Invalid X25519 public key: low-order point"
+      );
+    });
+
+    it("鍵・暗号文のサイズが不正な場合はエラーになる", async () => {
+      const keypair = await generateKyberHybridKeyPair();
+
+      await expect(hybridEncapsulateKyber(new Uint8Array(31), keypair.pqPublicKey)).rejects.toThrow(
+        "Invalid X25519 public key size: expected 32, got 31"
+      );
+      await expect(
+        hybridDecapsulateKyber(new Uint8Array(16), keypair.classicalPrivateKey, keypair.pqPrivateKey, keypair.pqPublicKey)
+      ).rejects.toThrow("Invalid hybrid ciphertext size: expected 1120, got 16");
+    });
+
+    it("機能一覧でハイブリッド方式を報告する", async () => {
+      await initKyber();
+
+      expect(kyberWasm.features().hybrid).toBe("X25519");
+    });
+  });
+
+  describe("所有権を移す取り出し（take_*）", () => {
+    it("take_*はgetterと同じ内容を返し、取り出した後は空になる", async () => {
+      await initKyber();
+
+      const keypair = kyberWasm.generate_keypair();
+      try {
+        const publicKey = keypair.public_key;
+        const privateKey = keypair.private_key;
+
+        expect(keypair.take_public_key()).toEqual(publicKey);
+        expect(keypair.take_private_key()).toEqual(privateKey);
+
+        // 取り出した後はgetterも2回目のtake_*も空を返す
+        expect(keypair.public_key.length).toBe(0);
+        expect(keypair.private_key.length).toBe(0);
+        expect(keypair.take_public_key().length).toBe(0);
+        expect(keypair.take_private_key().length).toBe(0);
+      } finally {
+        keypair.free();
+      }
+    });
+
+    it("zeroizeは秘密鍵を消去し、公開鍵は残す", async () => {
+      await initKyber();
+
+      const keypair = kyberWasm.generate_keypair();
+      try {
+        const publicKey = keypair.public_key;
+
+        keypair.zeroize();
+        expect(keypair.private_key.length).toBe(0);
+        expect(keypair.public_key).toEqual(publicKey);
+      } finally {
+        keypair.free();
+      }
+    });
+
+    it("カプセル化結果の暗号文と共有秘密を取り出せる", async () => {
+      await initKyber();
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+
+      const encapsulation = kyberWasm.encapsulate(publicKey);
+      try {
+        const ciphertext = encapsulation.take_ciphertext();
+        const sharedSecret = encapsulation.take_shared_secret();
+
+        expect(await decapsulateKyber(ciphertext, privateKey, publicKey)).toEqual(sharedSecret);
+        expect(encapsulation.ciphertext.length).toBe(0);
+        expect(encapsulation.shared_secret.length).toBe(0);
+      } finally {
+        encapsulation.free();
+      }
+    });
+  });
+
+  describe("ストリーム暗号化", () => {
+    // チャンクの配列をストリームに通し、出力を1つのバイト列に連結する
+    async function pipeChunks(
+      chunks: Uint8Array[],
+      transform: TransformStream<Uint8Array, Uint8Array>
+    ): Promise<Uint8Array> {
+      const source = new ReadableStream<Uint8Array>({
+        start(controller) {
+          for (const chunk of chunks) {
+            controller.enqueue(chunk);
+          }
+          controller.close();
+        },
+      });
+
+      const parts: Uint8Array[] = [];
+      const reader = source.pipeThrough(transform).getReader();
+      for (;;) {
+        const { done, value } = await reader.read();
+        if (done) break;
+        parts.push(value);
+      }
+
+      const result = new Uint8Array(parts.reduce((sum, part) => sum + part.length, 0));
+      let offset = 0;
+      for (const part of parts) {
+        result.set(part, offset);
+        offset += part.length;
+      }
+      return result;
+    }
+
+    // バイト列を指定サイズごとに分割する
+    function split(data: Uint8Array, sizes: number[]): Uint8Array[] {
+      const chunks: Uint8Array[] = [];
+      let offset = 0;
+      let i = 0;
+      while (offset < data.length) {
+        const size = sizes[i++ % sizes.length];
+        chunks.push(data.slice(offset, offset + size));
+        offset += size;
+      }
+      return chunks;
+    }
+
+    const plaintext = new Uint8Array(100_000).map((_, i) => i % 251);
+
+    it("様々なサイズのチャンクを暗号化・復号できる", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+
+      const encrypted = await pipeChunks(
+        split(plaintext, [1, 17, 4096, 65536, 3]),
+        await createKyberEncryptStream(publicKey)
+      );
+
+      // 暗号化ストリームを暗号化時とは異なる境界で分割して復号する
+      const decrypted = await pipeChunks(
+        split(encrypted, [7, 1000, 33333]),
+        await createKyberDecryptStream(privateKey, publicKey)
+      );
+
+      expect(decrypted).toEqual(plaintext);
+    });
+
+    it("改ざんされたストリームは復号できない", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+
+      const encrypted = await pipeChunks([plaintext], await createKyberEncryptStream(publicKey));
+      encrypted[encrypted.length - 40] ^= 0x01;
+
+      await expect(
+        pipeChunks([encrypted], await createKyberDecryptStream(privateKey, publicKey))
+      ).rejects.toThrow();
+    });
+
+    it("12バイトの認証タグで往復できる", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+
+      const full = await pipeChunks(split(plaintext, [4096]), await createKyberEncryptStream(publicKey));
+      const truncated = await pipeChunks(split(plaintext, [4096]), await createKyberEncryptStream(publicKey, 12));
+
+      // ヘッダの2バイト目にタグの長さが記録され、フレームごとに4バイト短くなる
+      expect(full[1]).toBe(16);
+      expect(truncated[1]).toBe(12);
+      const frames = Math.ceil(plaintext.length / 4096) + 1;
+      expect(full.length - truncated.length).toBe(frames * 4);
+
+      const decrypted = await pipeChunks(
+        split(truncated, [7, 1000, 33333]),
+        await createKyberDecryptStream(privateKey, publicKey)
+      );
+      expect(decrypted).toEqual(plaintext);
+    });
+
+    it("12バイトの認証タグでも改ざんを検出する", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+
+      const encrypted = await pipeChunks([plaintext], await createKyberEncryptStream(publicKey, 12));
+      encrypted[encrypted.length - 30] ^= 0x01;
+
+      await expect(
+        pipeChunks([encrypted], await createKyberDecryptStream(privateKey, publicKey))
+      ).rejects.toThrow();
+    });
+
+    it("最小より短いタグは拒否される", async () => {
+      const { publicKey } = await generateKyberKeyPair();
+
+      await expect(createKyberEncryptStream(publicKey, 11)).rejects.toThrow(/Invalid tag size/);
+    });
+
+    it("切り詰められたストリームは復号できない", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+
+      const encrypted = await pipeChunks([plaintext], await createKyberEncryptStream(publicKey));
+
+      // 終端フレーム（ヘッダ5バイト + タグ16バイト）を取り除く
+      const truncated = encrypted.slice(0, encrypted.length - 21);
+
+      await expect(
+        pipeChunks([truncated], await createKyberDecryptStream(privateKey, publicKey))
+      ).rejects.toThrow();
+    });
+
+    it("対応しているすべてのバージョンのストリームを復号できる", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const current = await pipeChunks(split(plaintext, [4096]), await createKyberEncryptStream(publicKey));
+
+      // バージョン1はtag_sizeのないヘッダ（version || Kyber暗号文）で、タグは常に16バイト
+      const v1 = new Uint8Array(current.length - 1);
+      v1[0] = 1;
+      v1.set(current.subarray(2), 1);
+
+      const streams: Record<number, Uint8Array> = { 1: v1, 2: current };
+      for (const version of await getSupportedKyberCiphertextVersions()) {
+        expect(streams[version][0]).toBe(version);
+        const decrypted = await pipeChunks([streams[version]], await createKyberDecryptStream(privateKey, publicKey));
+        expect(decrypted).toEqual(plaintext);
+      }
+    });
+
+    it("未対応のバージョンのストリームはバージョン番号を含むエラーになる", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const encrypted = await pipeChunks([plaintext], await createKyberEncryptStream(publicKey));
+      encrypted[0] = 7;
+
+      await expect(
+        pipeChunks([encrypted], await createKyberDecryptStream(privateKey, publicKey))
+      ).rejects.toThrow(/Unsupported stream version: 7 \(supported versions: 2, 1\)/);
+    });
+  });
+
+  describe("複数受信者向けコンテナ", () => {
+    // version (1バイト) || tag_size (1バイト) || count (1バイト) の後に、
+    // Kyber暗号文 (1088バイト) || 封入した鍵 (48バイト) のスロットが並ぶ
+    const HEADER_SIZE = 3;
+    const SLOT_SIZE = 1088 + 48;
+    const message = new TextEncoder().encode("Hello, all recipients!");
+
+    it("すべての受信者が復号できる", async () => {
+      const recipients = await Promise.all([1, 2, 3].map(() => generateKyberKeyPair()));
+
+      const container = await sealKyberMulti(
+        recipients.map((r) => r.publicKey),
+        message
+      );
+      expect(container[2]).toBe(3);
+
+      for (const { publicKey, privateKey } of recipients) {
+        expect(await openKyberMulti(container, privateKey, publicKey)).toEqual(message);
+      }
+    });
+
+    it("受信者でない鍵では復号できない", async () => {
+      const recipient = await generateKyberKeyPair();
+      const outsider = await generateKyberKeyPair();
+
+      const container = await sealKyberMulti([recipient.publicKey], message);
+
+      await expect(openKyberMulti(container, outsider.privateKey, outsider.publicKey)).rejects.toThrow(
+        /No recipient slot/
+      );
+    });
+
+    it("1つのスロットを改ざんすると全受信者の復号が失敗する", async () => {
+      const recipients = await Promise.all([1, 2, 3].map(() => generateKyberKeyPair()));
+      const container = await sealKyberMulti(
+        recipients.map((r) => r.publicKey),
+        message
+      );
+
+      // 2番目の受信者のスロットの、封入した鍵の部分を書き換える
+      const tampered = new Uint8Array(container);
+      tampered[HEADER_SIZE + SLOT_SIZE + 1088 + 5] ^= 0x01;
+
+      for (const { publicKey, privateKey } of recipients) {
+        await expect(openKyberMulti(tampered, privateKey, publicKey)).rejects.toThrow();
+      }
+
+      // 改ざんされていないスロットの受信者も、本文の認証で失敗する
+      await expect(
+        openKyberMulti(tampered, recipients[0].privateKey, recipients[0].publicKey)
+      ).rejects.toThrow(/Container authentication failed/);
+    });
+
+    it("スロットのKyber暗号文を改ざんしても全受信者の復号が失敗する", async () => {
+      const recipients = await Promise.all([1, 2].map(() => generateKyberKeyPair()));
+      const container = await sealKyberMulti(
+        recipients.map((r) => r.publicKey),
+        message
+      );
+
+      const tampered = new Uint8Array(container);
+      tampered[HEADER_SIZE + 10] ^= 0x01;
+
+      for (const { publicKey, privateKey } of recipients) {
+        await expect(openKyberMulti(tampered, privateKey, publicKey)).rejects.toThrow();
+      }
+    });
+
+    it("受信者の数が不正な場合は拒否される", async () => {
+      await expect(sealKyberMulti([], message)).rejects.toThrow(/Invalid number of recipients/);
+    });
+
+    it("16バイト・12バイトの認証タグで往復できる", async () => {
+      const recipients = await Promise.all([1, 2].map(() => generateKyberKeyPair()));
+      const publicKeys = recipients.map((r) => r.publicKey);
+
+      const full = await sealKyberMulti(publicKeys, message, 16);
+      const truncated = await sealKyberMulti(publicKeys, message, 12);
+
+      // タグの長さはヘッダに記録され、本文が4バイト短くなる
+      expect(full[1]).toBe(16);
+      expect(truncated[1]).toBe(12);
+      expect(full.length - truncated.length).toBe(4);
+
+      for (const { publicKey, privateKey } of recipients) {
+        expect(await openKyberMulti(full, privateKey, publicKey)).toEqual(message);
+        expect(await openKyberMulti(truncated, privateKey, publicKey)).toEqual(message);
+      }
+    });
+
+    it("短縮したタグでも改ざんを検出する", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const container = await sealKyberMulti([publicKey], message, 12);
+
+      const tampered = new Uint8Array(container);
+      tampered[tampered.length - 1] ^= 0x01;
+      await expect(openKyberMulti(tampered, privateKey, publicKey)).rejects.toThrow(
+        /Container authentication failed/
+      );
+    });
+
+    it("最小より短いタグは拒否される", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+
+      await expect(sealKyberMulti([publicKey], message, 11)).rejects.toThrow(/Invalid tag size/);
+      await expect(sealKyberMulti([publicKey], message, 17)).rejects.toThrow(/Invalid tag size/);
+
+      // ヘッダのタグの長さを書き換えたコンテナも拒否される
+      const container = await sealKyberMulti([publicKey], message, 12);
+      container[1] = 8;
+      await expect(openKyberMulti(container, privateKey, publicKey)).rejects.toThrow(/Invalid tag size/);
+    });
+
+    it("現在のバージョンで作成し、未対応のバージョンはバージョン番号を含むエラーになる", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const versions = await getSupportedKyberCiphertextVersions();
+      expect(versions).toEqual([2, 1]);
+      const [current] = versions;
+
+      const container = await sealKyberMulti([publicKey], message);
+      expect(container[0]).toBe(current);
+      expect(await openKyberMulti(container, privateKey, publicKey)).toEqual(message);
+
+      for (const version of [0, 3, 255]) {
+        const unknown = new Uint8Array(container);
+        unknown[0] = version;
+        await expect(openKyberMulti(unknown, privateKey, publicKey)).rejects.toThrow(
+          new RegExp(`Unsupported container version: ${version} \\(supported versions: 2, 1\\)`)
+        );
+      }
+    });
+  });
+
+  describe("本文の認証付き暗号の選択", () => {
+    const message = new TextEncoder().encode("Hello, AEAD selection!");
+
+    it("推奨される暗号は2つの暗号のいずれかになる", async () => {
+      const recommended = await recommendKyberAead();
+      expect(["aes-256-gcm", "chacha20-poly1305"]).toContain(recommended);
+
+      // 作業量を指定しても同じ2つのいずれか
+      expect(["aes-256-gcm", "chacha20-poly1305"]).toContain(await recommendKyberAead(1));
+    });
+
+    it("推奨された暗号で封をしたコンテナを復号できる", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const recommended = await recommendKyberAead(16);
+
+      const container = await sealKyberMulti([publicKey], message, undefined, recommended);
+      expect(await openKyberMulti(container, privateKey, publicKey)).toEqual(message);
+
+      // "auto"は記録された推奨の暗号を使う
+      const auto = await sealKyberMulti([publicKey], message, undefined, "auto");
+      expect(auto[1]).toBe(container[1]);
+      expect(await openKyberMulti(auto, privateKey, publicKey)).toEqual(message);
+    });
+
+    it("暗号を明示的に選択でき、AES-256-GCMはタグの長さのバイトにフラグが付く", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+
+      const chacha = await sealKyberMulti([publicKey], message, 12, "chacha20-poly1305");
+      const aes = await sealKyberMulti([publicKey], message, 12, "aes-256-gcm");
+
+      expect(chacha[1]).toBe(12);
+      expect(aes[1]).toBe(0x80 | 12);
+      expect(aes.length).toBe(chacha.length);
+      expect(await openKyberMulti(chacha, privateKey, publicKey)).toEqual(message);
+      expect(await openKyberMulti(aes, privateKey, publicKey)).toEqual(message);
+
+      // 暗号のフラグを書き換えたコンテナは認証に失敗する
+      const swapped = new Uint8Array(aes);
+      swapped[1] = 12;
+      await expect(openKyberMulti(swapped, privateKey, publicKey)).rejects.toThrow(
+        /Container authentication failed/
+      );
+    });
+
+    it("省略時は以前の形式と同じChaCha20-Poly1305を使う", async () => {
+      const { publicKey } = await generateKyberKeyPair();
+
+      const container = await sealKyberMulti([publicKey], message);
+      expect(container[1]).toBe(16);
+    });
+
+    it("不明な暗号と範囲外の作業量は拒否される", async () => {
+      const { publicKey } = await generateKyberKeyPair();
+
+      await expect(sealKyberMulti([publicKey], message, undefined, "aes-128-gcm" as never)).rejects.toThrow(
+        /Unknown AEAD cipher/
+      );
+      await expect(recommendKyberAead(0)).rejects.toThrow(/Invalid work factor/);
+      await expect(recommendKyberAead(65537)).rejects.toThrow(/Invalid work factor/);
+    });
+  });
+
+  describe("ランダムアクセス可能な暗号化ファイル", () => {
+    // version (1バイト) || tag_size (1バイト) || chunk_size (4バイト) || plaintext_length (8バイト) || Kyber暗号文 (1088バイト)
+    const HEADER_SIZE = 14 + 1088;
+    const file = Uint8Array.from({ length: 10000 }, (_, i) => (i * 31 + 7) & 0xff);
+
+    // 暗号化ファイルから、平文の範囲の復号に必要な部分だけを取り出して復号する
+    async function readRange(
+      sealed: Uint8Array,
+      start: number,
+      length: number,
+      privateKey: Uint8Array,
+      publicKey: Uint8Array
+    ): Promise<Uint8Array> {
+      const header = sealed.slice(0, await getKyberSeekableHeaderSize());
+      const range = await getKyberSeekableChunkRange(header, start, length);
+      const chunkBytes = sealed.slice(range.offset, range.offset + range.length);
+      return decryptKyberRange(header, chunkBytes, start, length, privateKey, publicKey);
+    }
+
+    it("内部の範囲を復号すると平文の同じ範囲と一致する", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const sealed = await sealKyberSeekable(publicKey, file, 1024);
+      expect(await getKyberSeekableHeaderSize()).toBe(HEADER_SIZE);
+      expect(sealed.length).toBe(HEADER_SIZE + file.length + 10 * 16);
+
+      for (const [start, length] of [
+        [3000, 2500],
+        [1023, 2],
+        [9999, 1],
+        [0, file.length],
+        [5000, 0],
+      ]) {
+        expect(await readRange(sealed, start, length, privateKey, publicKey)).toEqual(
+          file.slice(start, start + length)
+        );
+      }
+    });
+
+    it("範囲を含むチャンクだけをダウンロードする", async () => {
+      const { publicKey } = await generateKyberKeyPair();
+      const sealed = await sealKyberSeekable(publicKey, file, 1024);
+      const header = sealed.slice(0, HEADER_SIZE);
+
+      // 3000〜5499バイト目はチャンク2〜5に含まれる
+      expect(await getKyberSeekableChunkRange(header, 3000, 2500)).toEqual({
+        offset: HEADER_SIZE + 2 * (1024 + 16),
+        length: 4 * (1024 + 16),
+      });
+      // 最後のチャンクは短い（10000 - 9 * 1024 = 784バイト）
+      expect(await getKyberSeekableChunkRange(header, 9500, 500)).toEqual({
+        offset: HEADER_SIZE + 9 * (1024 + 16),
+        length: 784 + 16,
+      });
+    });
+
+    it("切り詰めたタグでも範囲を復号できる", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const sealed = await sealKyberSeekable(publicKey, file, 1000, 12);
+      expect(sealed[1]).toBe(12);
+      expect(sealed.length).toBe(HEADER_SIZE + file.length + 10 * 12);
+
+      expect(await readRange(sealed, 4321, 1234, privateKey, publicKey)).toEqual(file.slice(4321, 4321 + 1234));
+    });
+
+    it("範囲に含まれるチャンクの改ざんを検出する", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const sealed = await sealKyberSeekable(publicKey, file, 1024);
+
+      const tampered = new Uint8Array(sealed);
+      tampered[HEADER_SIZE + 3 * (1024 + 16) + 10] ^= 0x01;
+
+      await expect(readRange(tampered, 3000, 2500, privateKey, publicKey)).rejects.toThrow(
+        /Chunk 3 authentication failed/
+      );
+      // 改ざんされたチャンクを含まない範囲は復号できる
+      expect(await readRange(tampered, 0, 2048, privateKey, publicKey)).toEqual(file.slice(0, 2048));
+    });
+
+    it("チャンクの入れ替えを検出する", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const sealed = await sealKyberSeekable(publicKey, file, 1024);
+      const chunkSize = 1024 + 16;
+
+      const swapped = new Uint8Array(sealed);
+      swapped.set(sealed.slice(HEADER_SIZE, HEADER_SIZE + chunkSize), HEADER_SIZE + chunkSize);
+      swapped.set(sealed.slice(HEADER_SIZE + chunkSize, HEADER_SIZE + 2 * chunkSize), HEADER_SIZE);
+
+      await expect(readRange(swapped, 0, 2048, privateKey, publicKey)).rejects.toThrow(/authentication failed/);
+    });
+
+    it("ヘッダのパラメータを書き換えると復号が失敗する", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const sealed = await sealKyberSeekable(publicKey, file, 1024);
+      const header = sealed.slice(0, HEADER_SIZE);
+      const chunkBytes = sealed.slice(HEADER_SIZE, HEADER_SIZE + 1024 + 16);
+
+      // 平文の長さを書き換えても、ファイル鍵が変わるためチャンクの認証に失敗する
+      const tampered = new Uint8Array(header);
+      tampered[HEADER_SIZE - 1088 - 1] ^= 0x01;
+      await expect(decryptKyberRange(tampered, chunkBytes, 0, 1024, privateKey, publicKey)).rejects.toThrow(
+        /authentication failed/
+      );
+    });
+
+    it("別の鍵では復号できない", async () => {
+      const { publicKey } = await generateKyberKeyPair();
+      const other = await generateKyberKeyPair();
+      const sealed = await sealKyberSeekable(publicKey, file, 1024);
+
+      await expect(readRange(sealed, 0, 100, other.privateKey, other.publicKey)).rejects.toThrow(
+        /authentication failed/
+      );
+    });
+
+    it("不正な範囲やチャンクの長さはエラーになる", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const sealed = await sealKyberSeekable(publicKey, file, 1024);
+      const header = sealed.slice(0, HEADER_SIZE);
+
+      await expect(getKyberSeekableChunkRange(header, 9000, 1001)).rejects.toThrow(/Range is out of bounds/);
+      await expect(
+        decryptKyberRange(header, sealed.slice(HEADER_SIZE, HEADER_SIZE + 100), 0, 100, privateKey, publicKey)
+      ).rejects.toThrow(/Invalid chunk bytes length/);
+      await expect(getKyberSeekableChunkRange(header.slice(0, 100), 0, 1)).rejects.toThrow(/header is too short/);
+    });
+
+    it("ヘッダの平文の長さを巨大な値に書き換えても位置の計算が桁あふれしない", async () => {
+      const { publicKey, privateKey } = await generateKyberKeyPair();
+      const sealed = await sealKyberSeekable(publicKey, file, 1024);
+
+      // chunk_size = 1、plaintext_length = 2^64 - 1 に書き換える
+      const header = sealed.slice(0, HEADER_SIZE);
+      header.set([0, 0, 0, 1], 2);
+      header.fill(0xff, 6, 14);
+
+      await expect(getKyberSeekableChunkRange(header, 2 ** 63, 1)).rejects.toThrow(/overflows a 64-bit integer/);
+      // 位置ではなく長さだけを使う復号は、桁あふれせずチャンクの認証で失敗する
+      await expect(decryptKyberRange(header, new Uint8Array(1 + 16), 2 ** 63, 1, privateKey, publicKey)).rejects.toThrow(
+        /authentication failed/
+      );
+    });
+
+    it("不正なチャンクのサイズは拒否される", async () => {
+      const { publicKey } = await generateKyberKeyPair();
+      await expect(sealKyberSeekable(publicKey, file, 0)).rejects.toThrow(/Invalid chunk size/);
+      await expect(sealKyberSeekable(publicKey, file, (1 << 24) + 1)).rejects.toThrow(/Invalid chunk size/);
+    });
+  });
+
+  describe("シードからの決定的な鍵生成", () => {
+    const seedOf = (fill: number) => new Uint8Array(64).map((_, i) => (i + fill) & 0xff);
+
+    it("同じシードからは同じ鍵ペアが得られ、異なるシードからは異なる鍵ペアが得られる", async () => {
+      const a = await generateKyberKeyPairFromSeed(seedOf(1));
+      const b = await generateKyberKeyPairFromSeed(seedOf(1));
+      const c = await generateKyberKeyPairFromSeed(seedOf(2));
+
+      expect(a).toEqual(b);
+      expect(c.publicKey).not.toEqual(a.publicKey);
+    });
+
+    it("まとめて生成した鍵ペアはシードごとに生成した鍵ペアと一致する", async () => {
+      const seeds = [seedOf(1), seedOf(2), seedOf(3)];
+      const keypairs = await generateKyberKeyPairsFromSeeds(seeds);
+
+      expect(keypairs).toHaveLength(seeds.length);
+      for (const [i, seed] of seeds.entries()) {
+        expect(keypairs[i]).toEqual(await generateKyberKeyPairFromSeed(seed));
+      }
+
+      const { ciphertext, sharedSecret } = await encapsulateKyber(keypairs[1].publicKey);
+      expect(
+        await decapsulateKyber(ciphertext, keypairs[1].privateKey, keypairs[1].publicKey)
+      ).toEqual(sharedSecret);
+    });
+
+    it("空の配列からは空の配列が得られる", async () => {
+      expect(await generateKyberKeyPairsFromSeeds([])).toEqual([]);
+    });
+
+    // ML-KEM-768のKeyGen_internal(d, z)・Encaps_internal(ek, m)の既知解
+    // OpenSSLのML-KEM-768で生成した d, z, m, ek, dk, c, K をフィクスチャから読み込み、すべての出力をバイト単位で照合する
+    it("OpenSSLで生成したML-KEM-768の既知解とすべての出力が一致する", async () => {
+      const { d, z, m, ek, dk, c, k } = OPENSSL_KAT;
+      const seed = new Uint8Array([...hexToBytes(d), ...hexToBytes(z)]);
+
+      const { publicKey, privateKey } = await generateKyberKeyPairFromSeed(seed);
+      expect(bytesToHex(publicKey)).toBe(ek);
+      expect(bytesToHex(privateKey)).toBe(dk);
+
+      const { ciphertext, sharedSecret } = await encapsulateKyberDeterministic(publicKey, hexToBytes(m));
+      expect(bytesToHex(ciphertext)).toBe(c);
+      expect(bytesToHex(sharedSecret)).toBe(k);
+      expect(bytesToHex(await decapsulateKyber(hexToBytes(c), hexToBytes(dk), hexToBytes(ek)))).toBe(k);
+    });
+
+    it("決定的なカプセル化は同じ入力から同じ結果を返し、乱数のサイズが不正な場合はエラーになる", async () => {
+      const { publicKey } = await generateKyberKeyPairFromSeed(seedOf(1));
+      const coins = new Uint8Array(32).fill(0x42);
+
+      expect(await encapsulateKyberDeterministic(publicKey, coins)).toEqual(
+        await encapsulateKyberDeterministic(publicKey, coins)
+      );
+      await expect(encapsulateKyberDeterministic(publicKey, new Uint8Array(31))).rejects.toThrow(
+        "Invalid coins size: expected 32, got 31"
+      );
+    });
+
+    it("不正なシードは拒否され、まとめて生成する場合は位置が示される", async () => {
+      await expect(generateKyberKeyPairFromSeed(new Uint8Array(63))).rejects.toThrow(
+        "Invalid seed size: expected 64, got 63"
+      );
+      await expect(generateKyberKeyPairFromSeed(new Uint8Array(64))).rejects.toThrow(
+        "Invalid seed: all bytes are zero"
+      );
+      await expect(
+        generateKyberKeyPairsFromSeeds([seedOf(1), new Uint8Array(64)])
+      ).rejects.toThrow("Seed 1: Invalid seed: all bytes are zero");
+    });
+  });
+
+  describe("鍵識別子（KID）", () => {
+    it("同じ公開鍵からは常に同じKIDを導出する", async () => {
+      const { publicKey } = await generateKyberKeyPair();
+      const kid = await getKyberKeyId(publicKey);
+
+      expect(kid).toMatch(/^[A-Za-z0-9_-]{22}$/);
+      expect(await getKyberKeyId(publicKey.slice())).toBe(kid);
+    });
+
+    it("異なる公開鍵からは異なるKIDを導出する", async () => {
+      const first = await generateKyberKeyPair();
+      const second = await generateKyberKeyPair();
+
+      expect(await getKyberKeyId(first.publicKey)).not.toBe(await getKyberKeyId(second.publicKey));
+    });
+
+    it("公開鍵のサイズが不正な場合はエラーになる", async () => {
+      await expect(getKyberKeyId(new Uint8Array(32))).rejects.toThrow(/Invalid public key size/);
+    });
+  });
 });
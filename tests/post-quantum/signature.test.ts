@@ -0,0 +1,86 @@
+/**
+ * 耐量子署名の共通インターフェースのテスト
+ */
+
+import { describe, it, expect, vi } from "vitest";
+import {
+  SIGNATURE_ALGORITHMS,
+  type SignatureAlgorithm,
+  generateSignatureKeyPair,
+  signWithAlgorithm,
+  verifyWithAlgorithm,
+} from "../../src/post-quantum/signature.js";
+
+const algorithms = Object.keys(SIGNATURE_ALGORITHMS) as SignatureAlgorithm[];
+
+describe("耐量子署名の共通インターフェース", () => {
+  for (const algorithm of algorithms) {
+    describe(algorithm, () => {
+      it("鍵生成・署名・検証の往復ができる", async () => {
+        const { publicKey, privateKey } = await generateSignatureKeyPair(algorithm);
+        const message = new TextEncoder().encode(`Hello, ${algorithm}!`);
+        const signature = await signWithAlgorithm(algorithm, message, privateKey);
+
+        expect(publicKey.length).toBe(SIGNATURE_ALGORITHMS[algorithm].publicKeySize);
+        expect(privateKey.length).toBe(SIGNATURE_ALGORITHMS[algorithm].privateKeySize);
+        expect(await verifyWithAlgorithm(algorithm, message, signature, publicKey)).toBe(true);
+      });
+
+      it("改ざんしたメッセージ・署名は無効になる", async () => {
+        const { publicKey, privateKey } = await generateSignatureKeyPair(algorithm);
+        const message = new TextEncoder().encode("Tamper test");
+        const signature = await signWithAlgorithm(algorithm, message, privateKey);
+
+        const tampered = signature.slice();
+        tampered[1] ^= 0x01;
+        expect(await verifyWithAlgorithm(algorithm, new TextEncoder().encode("Other"), signature, publicKey)).toBe(
+          false
+        );
+        expect(await verifyWithAlgorithm(algorithm, message, tampered, publicKey)).toBe(false);
+        expect(await verifyWithAlgorithm(algorithm, message, new Uint8Array(0), publicKey)).toBe(false);
+      });
+    });
+  }
+
+  it("指定したアルゴリズムと異なる鍵は拒否される", async () => {
+    const mlDsa44 = await generateSignatureKeyPair("ML-DSA-44");
+    const falcon512 = await generateSignatureKeyPair("FALCON-512");
+    const message = new TextEncoder().encode("Algorithm mismatch");
+
+    await expect(signWithAlgorithm("ML-DSA-65", message, mlDsa44.privateKey)).rejects.toThrow(
+      "Invalid ML-DSA-65 private key size: expected 4032, got 2560"
+    );
+
+    const signature = await signWithAlgorithm("FALCON-512", message, falcon512.privateKey);
+    expect(await verifyWithAlgorithm("FALCON-1024", message, signature, falcon512.publicKey)).toBe(false);
+    expect(await verifyWithAlgorithm("ML-DSA-44", message, signature, falcon512.publicKey)).toBe(false);
+  });
+
+  it("FALCONのモジュールの読み込みの失敗は無効な署名として扱わない", async () => {
+    // verifyFalconが初期化に失敗した場合を再現する（このテストの中だけモジュールを差し替える）
+    vi.resetModules();
+    vi.doMock("../../src/post-quantum/falcon.js", async (importOriginal) => ({
+      ...(await importOriginal<typeof import("../../src/post-quantum/falcon.js")>()),
+      verifyFalcon: async () => {
+        throw new Error("Failed to load FALCON wasm module");
+      },
+    }));
+    try {
+      const { verifyWithAlgorithm: verify } = await import("../../src/post-quantum/signature.js");
+      const publicKey = new Uint8Array(SIGNATURE_ALGORITHMS["FALCON-512"].publicKeySize);
+
+      await expect(verify("FALCON-512", new Uint8Array(1), new Uint8Array(1), publicKey)).rejects.toThrow(
+        "Failed to load FALCON wasm module"
+      );
+    } finally {
+      vi.doUnmock("../../src/post-quantum/falcon.js");
+      vi.resetModules();
+    }
+  });
+
+  it("未対応のアルゴリズムは拒否される", async () => {
+    await expect(generateSignatureKeyPair("SLH-DSA" as SignatureAlgorithm)).rejects.toThrow(
+      "Unsupported signature algorithm: SLH-DSA"
+    );
+  });
+});
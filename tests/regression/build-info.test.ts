@@ -0,0 +1,187 @@
+/**
+ * 各WebAssemblyモジュールのversion()・features()の確認
+ *
+ * version()がCargo.tomlのバージョンと一致し、features()が報告するスキーム・パラメータセットが
+ * 実際にそのビルドで使えることを確認します。
+ * ビルドから機能を外した（または追加した）のにfeatures()を更新し忘れた場合に検出します。
+ */
+
+import { readFileSync } from "node:fs";
+import { describe, expect, it } from "vitest";
+import {
+  decryptABE,
+  decryptKPABE,
+  encryptABE,
+  encryptKPABE,
+  extractABEKey,
+  extractKPABEKey,
+  generateABEKeyPair,
+  initABE,
+} from "../../src/asymmetric/abe.js";
+import {
+  decryptECIES,
+  decryptIBE,
+  encryptECIES,
+  encryptIBE,
+  extractIBEKey,
+  generateECIESKeyPair,
+  generateIBEKeyPair,
+  initIBE,
+} from "../../src/asymmetric/ibe.js";
+import {
+  generateDilithiumKeyPairForLevel,
+  initDilithium,
+  signDilithium,
+  signDilithiumEnveloped,
+  verifyDilithium,
+  verifyDilithiumEnveloped,
+  type DilithiumLevel,
+} from "../../src/post-quantum/dilithium.js";
+import {
+  generateFalconKeyPair,
+  initFalcon,
+  signFalcon,
+  verifyFalcon,
+  type FalconDegree,
+} from "../../src/post-quantum/falcon.js";
+import {
+  decapsulateKyber,
+  encapsulateKyber,
+  generateKyberKeyPair,
+  initKyber,
+  type KyberLevel,
+} from "../../src/post-quantum/kyber.js";
+
+const abeWasm: any = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+const ibeWasm: any = await import("../../wasm-src/ibe-wasm/pkg/ibe_wasm.js");
+const dilithiumWasm: any = await import("../../wasm-src/dilithium-wasm/pkg/dilithium_wasm.js");
+const falconWasm: any = await import("../../wasm-src/falcon-rust-wasm/pkg/falcon_rust_wasm.js");
+const kyberWasm: any = await import("../../wasm-src/kyber-wasm/pkg/kyber_wasm.js");
+
+const MESSAGE = new TextEncoder().encode("build info");
+
+/**
+ * クレートのCargo.tomlに記載されたバージョンを取得
+ */
+function crateVersion(crate: string): string {
+  const manifest = readFileSync(new URL(`../../wasm-src/${crate}/Cargo.toml`, import.meta.url), "utf8");
+  const match = manifest.match(/^version\s*=\s*"([^"]+)"/m);
+  if (!match) {
+    throw new Error(`version not found in ${crate}/Cargo.toml`);
+  }
+  return match[1];
+}
+
+describe("Build info", () => {
+  describe("ABE", () => {
+    it("should report the crate version", async () => {
+      await initABE();
+      expect(abeWasm.version()).toBe(crateVersion("abe-wasm"));
+    });
+
+    it("should report only the schemes that work in this build", async () => {
+      await initABE();
+      const features = abeWasm.features();
+      expect(features.schemes).toEqual(["CP-ABE", "KP-ABE"]);
+      expect(features.curve).toBe("BN254");
+
+      const { masterKey, publicParams } = await generateABEKeyPair();
+      const cpKey = await extractABEKey(masterKey, ["A", "B"]);
+      expect(await decryptABE(cpKey, await encryptABE(publicParams, "A,B", MESSAGE))).toEqual(MESSAGE);
+      const kpKey = await extractKPABEKey(masterKey, "A,B");
+      expect(await decryptKPABE(kpKey, await encryptKPABE(publicParams, ["A", "B"], MESSAGE))).toEqual(MESSAGE);
+    });
+  });
+
+  describe("IBE", () => {
+    it("should report the crate version", async () => {
+      await initIBE();
+      expect(ibeWasm.version()).toBe(crateVersion("ibe-wasm"));
+    });
+
+    it("should report only the schemes that work in this build", async () => {
+      await initIBE();
+      const features = ibeWasm.features();
+      expect(features.schemes).toEqual(["Boneh-Franklin", "ECIES"]);
+      expect(features.curve).toBe("BN254");
+
+      const { masterKey, publicParams } = await generateIBEKeyPair();
+      const privateKey = await extractIBEKey(masterKey, "alice@example.com");
+      const ciphertext = await encryptIBE(publicParams, "alice@example.com", MESSAGE);
+      expect(await decryptIBE(privateKey, ciphertext)).toEqual(MESSAGE);
+
+      const ecies = await generateECIESKeyPair();
+      expect(await decryptECIES(ecies.privateKey, await encryptECIES(ecies.publicKey, MESSAGE))).toEqual(MESSAGE);
+    });
+  });
+
+  describe("ML-DSA", () => {
+    const levels: DilithiumLevel[] = ["ML-DSA-44", "ML-DSA-65", "ML-DSA-87"];
+
+    it("should report the crate version", async () => {
+      await initDilithium();
+      expect(dilithiumWasm.version()).toBe(crateVersion("dilithium-wasm"));
+    });
+
+    it("should report every parameter set, including ML-DSA-44 and ML-DSA-87", async () => {
+      await initDilithium();
+      const features = dilithiumWasm.features();
+      expect(features.algorithm).toBe("ML-DSA");
+      expect(features.parameter_sets).toEqual(levels);
+      expect(features.enveloped_parameter_sets).toEqual(levels);
+    });
+
+    for (const level of levels) {
+      it(`should sign and verify with the reported ${level}`, async () => {
+        const { publicKey, privateKey } = await generateDilithiumKeyPairForLevel(level);
+        expect(await verifyDilithium(MESSAGE, await signDilithium(MESSAGE, privateKey), publicKey)).toBe(true);
+
+        const envelope = await signDilithiumEnveloped(MESSAGE, privateKey);
+        expect(await verifyDilithiumEnveloped(MESSAGE, envelope, publicKey)).toBe(true);
+      });
+    }
+  });
+
+  describe("FALCON", () => {
+    const degrees: FalconDegree[] = [512, 1024];
+
+    it("should report the crate version", async () => {
+      await initFalcon();
+      expect(falconWasm.version()).toBe(crateVersion("falcon-rust-wasm"));
+    });
+
+    it("should report both FALCON-512 and FALCON-1024", async () => {
+      await initFalcon();
+      const features = falconWasm.features();
+      expect(features.algorithm).toBe("FALCON");
+      expect(features.parameter_sets).toEqual(degrees.map((degree) => `FALCON-${degree}`));
+    });
+
+    for (const degree of degrees) {
+      it(`should sign and verify with the reported FALCON-${degree}`, async () => {
+        const { publicKey, privateKey } = await generateFalconKeyPair(degree);
+        expect(await verifyFalcon(MESSAGE, await signFalcon(MESSAGE, privateKey), publicKey)).toBe(true);
+      });
+    }
+  });
+
+  describe("ML-KEM", () => {
+    const levels: KyberLevel[] = [512, 768, 1024];
+
+    it("should report the crate version", async () => {
+      await initKyber();
+      expect(kyberWasm.version()).toBe(crateVersion("kyber-wasm"));
+    });
+
+    for (const level of levels) {
+      it(`should encapsulate and decapsulate with the reported ML-KEM-${level}`, async () => {
+        await initKyber();
+        expect(kyberWasm.features().parameter_sets).toContain(`ML-KEM-${level}`);
+
+        const { publicKey, privateKey } = await generateKyberKeyPair(level);
+        const { ciphertext, sharedSecret } = await encapsulateKyber(publicKey);
+        expect(await decapsulateKyber(ciphertext, privateKey, publicKey)).toEqual(sharedSecret);
+      });
+    }
+  });
+});
@@ -0,0 +1,301 @@
+/**
+ * ワイヤーフォーマットの回帰テスト（ゴールデンベクター）
+ *
+ * 決定的な操作（固定マスター鍵からの鍵生成、シードからの鍵生成、固定乱数での暗号化）の
+ * 出力を定数として固定し、リファクタリングなどで意図せずシリアライズ形式が
+ * 変わった場合に検出します。
+ * 大きな出力はSHA-256ダイジェストで固定しています。
+ *
+ * フォーマットを意図的に変更した場合の再生成手順:
+ *   1. 変更内容を確認し、既存の鍵・暗号文との互換性への影響を検討する
+ *   2. UPDATE_GOLDEN=1 npm test -- tests/regression を実行する
+ *   3. ログに出力された新しい値で GOLDEN を書き換え、変更理由をコミットに記載する
+ *
+ * ABEの暗号文のベクターはtest-rng featureを有効にしたビルドでのみ検証されます。
+ *   wasm-pack build --target web -- --features test-rng
+ */
+
+import { describe, expect, it } from "vitest";
+import {
+  decryptABE,
+  encryptABE,
+  encryptKPABE,
+  extractABEKey,
+  extractKPABEKey,
+  importABEMasterKey,
+  importABEPrivateKey,
+  importABEPublicParams,
+  upgradeABECiphertext,
+} from "../../src/asymmetric/abe.js";
+import {
+  decryptIBE,
+  decryptIBELegacy,
+  encryptIBEDeterministic,
+  extractIBEKey,
+  importIBEMasterKey,
+  importIBEPublicParams,
+  tryDecryptIBE,
+} from "../../src/asymmetric/ibe.js";
+import {
+  deriveFalconChildSeed,
+  exportFalconPublicKeyJwk,
+  generateFalconKeyPairFromSeed,
+} from "../../src/post-quantum/falcon.js";
+import { hashSHA256 } from "../../src/hash/sha256.js";
+import { bytesToHex, hexToBytes } from "../../src/utils/format.js";
+
+const abeWasm: any = await import("../../wasm-src/abe-wasm/pkg/abe_wasm.js");
+const hasTestRng = typeof abeWasm.set_test_rng_mode === "function";
+
+// 入力となる固定値
+const MASTER_KEY = hexToBytes("1111111111111111111111111111111111111111111111111111111111111111");
+const PUBLIC_PARAMS = hexToBytes(
+  "04087e5868e0460f8562cd4e4043bb41a7fb4615b9aa95de00f5c6ec1afe5bd8" +
+  "e904397124bf97bb30768a43bfe3e30e8b54e1e3630c0721758dd362befff366" +
+  "c0"
+);
+const IDENTITY = "alice@example.com";
+const MESSAGE = new TextEncoder().encode("golden vector");
+const MESSAGE_KEY = new Uint8Array(32).fill(0x22);
+const FALCON_SEED = new Uint8Array(32).fill(0x07);
+const RNG_SEED = new Uint8Array(32).fill(0x5a);
+
+// 固定された出力（hex）
+const GOLDEN: Record<string, string> = {
+  ibePrivateKey:
+    "041f167989907ff635c66a22796b23ed7969371860b899744db706be26d48e70" +
+    "b70d202b2ebb314b4a86236e9b4a4bf831749a12715eae97da86aa84dab2021e" +
+    "661cc899cb40a0a7b15451b2c4eb44203af9ed7ba8617860a88616dbedc53d1c" +
+    "742120633df43cf35bd45bd922f9888710c029f6d71ef5992f3bdf9936512e26" +
+    "1f00",
+  // AES-256-GCMの導入前に、旧方式（legacy）のhash-to-curveで合成IV方式により作成した暗号文（U || M ⊕ 対称鍵）
+  ibeLegacyXorCiphertext:
+    "041a89728d0c61dd049cdb27958c832a31953d2213199126fc5653fc7aaa6cc8" +
+    "35057f6f922e501a86f8b0419c26bbd64ee936f1a2cdd5d4a7bce7885fa24b0e" +
+    "23c502addc8fa825e1922260fd9d",
+  ibeRfc9380PrivateKey:
+    "04020cc7cc61068769ef4893b3a7e0dd8a70bfbdde7a98368089f03ff8dd055d" +
+    "150a758b88adacbfc17d4a91a8627fad20d2cd8ca0731c84b9bbf19eba629233" +
+    "6b047c759ad02e085713270ed02367151809a74d9dcaf9671f15bacde7f2599d" +
+    "3d1602ace8c935df41d8df4b098742db575bef72b0429dc0c489b13c3d45777f" +
+    "e500",
+  // 方式のID || U || nonce（Vの本体と認証タグはGCMの対称鍵のKDFのラベルに依存するため、復号で確認する）
+  ibeRfc9380DeterministicHeader:
+    "110407dde2b7ad4bc592e9343925dabaed8c27abe7f1032038f3e4e5406c9165" +
+    "1ad801ec4055d1aa3bd62790e623debf66489becfb7c7967d3df5f02fe2c6743" +
+    "7b29000000000000000000000000",
+  ibeRfc9380XorCiphertext:
+    "010407dde2b7ad4bc592e9343925dabaed8c27abe7f1032038f3e4e5406c9165" +
+    "1ad801ec4055d1aa3bd62790e623debf66489becfb7c7967d3df5f02fe2c6743" +
+    "7b29d1759e89ae5a0456b1a1c94a1b",
+  abePrivateKeySha256: "ff10fbe2e8d6334ead7f9a72bc64105d210daf8252c80a9f18a2aab78ba2c771",
+  abeCompactPrivateKeySha256: "1a6d50a8849f76baf13d4490d65e3d1eed9b981f7894567e4d64a74797e8d5a9",
+  kpabePrivateKeySha256: "78a2f83911190ef34cfc261ec945b5c9dd5ada77ef5300127478fb8a774f27f3",
+  // RFC 9380のハッシュの導入前（属性ハッシュバージョン1）にマスター鍵から生成した属性A,Bの秘密鍵
+  abeLegacyPrivateKey:
+    "01010401b8cd0efe944e12ec592c2021ee3cda5d4e6c2618d9c1bab53984c9c5" +
+    "b534e82047d2cc444a28df03c89278a42a0ce4b08fb398365e41ca8c7756da39" +
+    "24692505c66a3d755e4dd2cfe3ad67fb5c073358b49bd57a354ad0d9c6bcde1a" +
+    "ddf9ec19e28d563e8fd59417d510fdcef5c9e8a94d21607b460f6113e8fc7331" +
+    "be9a8b0004070b82710ad64888e64162ea5e830943bee54ed9ba83a1549cca3d" +
+    "38f7b6397d153c2e64fccd6f4b94ad796ef122dee14f5aee54e51009f00c3e0a" +
+    "92178dbcb70596a474cdbffb2bd3119619f43a691aa3d0e6884de8c7cd277499" +
+    "6ed955714f17dc9bf1c6500662f01f4e1112314d14dca4d3d3ab459578d1867d" +
+    "fd9e4e2de200",
+  // AES-256-GCMの導入前（平文のハッシュ付き・属性ハッシュバージョン1）の形式で固定乱数から作成した暗号文
+  abeHashedCiphertext:
+    "4601020000000000000000040d253369f5f1ab2ed4828fae5c6524f7f73bb7e6" +
+    "defb964e355ea9fb7249296b1d7f711ded505e4a68e7fc90ee74dad7782f2e57" +
+    "57a3edb362f5ceb2722c9d9a01410413a08ea8e6abc41afed13cfed011af7887" +
+    "9ba51b933d3de7dd7b0954c70b3de221f3d9b07462d4335b0ea69e3987024986" +
+    "11ec90ce27d08bcbe5eb5830773bbc211050900b95b7110c3de49597a02747a6" +
+    "183614e5a15fe6c81967636c835eee1241e4e2b5477e6d75da7aee7e85c8eebe" +
+    "60aa75bffd67fa412dcace35f8162f00014204042d777a854f3e61bbfdbdf473" +
+    "2d2c8daae7cfd50083a6c18c74dd524042dc1522f9d7f76c6b165c2f146e2ce5" +
+    "0f39c2042622f6fb5b5267a30bff530fbb737e19d4c9c4e4155fbcf6da16ff2e" +
+    "e77732e5082421bd9ba5f7607a94954283b74f13747c579855085527bff651fc" +
+    "61013c505fa54579590e6a722793bd2eabb8e700b135d97198dc5cb811bc73ff" +
+    "1434aa34ca263035a5d9503b003ed934fa4635021018f8b5e1979399e9bd4485" +
+    "c3",
+  falconPublicKeySha256: "7f264c51a105ee0b719d217a86ec60261c32855be334f8c8a3985190089d2f48",
+  falconPrivateKeySha256: "93ea45ed6efe5c922037ff93a05c54701f569028cfd5de48a752a7a22afdb90a",
+  falconChildSeed: "c3ebc6e7334d665dc32e66ef1c04c81fc3fd128ba12aa645420889fd4c1c6ab2",
+  falconPublicJwkSha256: "b4539bafe8bbbc049d61f93635c0c845bbc5214a46a282bca6a00c55b5fab113",
+};
+
+/**
+ * 出力を固定値と比較する
+ * UPDATE_GOLDENが設定されている場合は、再生成用に実際の値をログに出力します。
+ */
+function expectGolden(name: string, actual: Uint8Array): void {
+  const hex = bytesToHex(actual);
+  if (process.env.UPDATE_GOLDEN) {
+    console.log(`${name}: "${hex}"`);
+  }
+  expect(
+    hex,
+    `ゴールデンベクター "${name}" と一致しません。ワイヤーフォーマットが変更されています。` +
+      "意図した変更であれば、ファイル先頭の手順に従って再生成してください。"
+  ).toBe(GOLDEN[name]);
+}
+
+describe("Golden vectors", () => {
+  describe("IBE", () => {
+    // 旧方式（legacy）のhash-to-curveで作成した鍵は、方式を指定すれば引き続き同じ値になる
+    it("should extract the pinned private key", async () => {
+      const masterKey = await importIBEMasterKey(MASTER_KEY);
+      const privateKey = await extractIBEKey(masterKey, IDENTITY, "legacy");
+
+      expectGolden("ibePrivateKey", privateKey.key);
+    });
+
+    // 旧方式の暗号文は以前の形式（XOR）では作成しなくなったが、固定された暗号文は移行用の復号で引き続き復号できる
+    it("should decrypt the pinned legacy ciphertext from before AES-256-GCM payloads", async () => {
+      const masterKey = await importIBEMasterKey(MASTER_KEY);
+      const privateKey = await extractIBEKey(masterKey, IDENTITY, "legacy");
+      const ciphertext = hexToBytes(GOLDEN.ibeLegacyXorCiphertext);
+
+      expect(ciphertext[0]).toBe(0x04);
+      expect(await decryptIBELegacy(privateKey, ciphertext)).toEqual(MESSAGE);
+      await expect(decryptIBE(privateKey, ciphertext)).rejects.toThrow(/unauthenticated legacy format/);
+      expect(await tryDecryptIBE(privateKey, ciphertext)).toBeUndefined();
+    });
+
+    it("should encrypt deterministically with AES-256-GCM for the legacy method", async () => {
+      const publicParams = await importIBEPublicParams(PUBLIC_PARAMS);
+      const ciphertext = await encryptIBEDeterministic(publicParams, IDENTITY, MESSAGE, MESSAGE_KEY, "legacy");
+
+      // 方式のID (0x03) にAES-256-GCMのフラグを立て、nonceは0で固定する
+      expect(ciphertext[0]).toBe(0x13);
+      expect(ciphertext.length).toBe(1 + 65 + 12 + MESSAGE.length + 16);
+      expect(ciphertext.slice(1 + 65, 1 + 65 + 12)).toEqual(new Uint8Array(12));
+      // rは以前と同じ値から導出されるため、Uは固定された暗号文と一致する
+      expect(ciphertext.slice(1, 1 + 65)).toEqual(hexToBytes(GOLDEN.ibeLegacyXorCiphertext).slice(0, 65));
+      expect(await encryptIBEDeterministic(publicParams, IDENTITY, MESSAGE, MESSAGE_KEY, "legacy")).toEqual(ciphertext);
+
+      const masterKey = await importIBEMasterKey(MASTER_KEY);
+      const privateKey = await extractIBEKey(masterKey, IDENTITY, "legacy");
+      expect(await decryptIBE(privateKey, ciphertext)).toEqual(MESSAGE);
+    });
+
+    it("should extract the pinned private key with the default RFC 9380 hash-to-curve", async () => {
+      const masterKey = await importIBEMasterKey(MASTER_KEY);
+      const privateKey = await extractIBEKey(masterKey, IDENTITY);
+
+      expect(privateKey.hash_to_curve).toBe("rfc9380");
+      expectGolden("ibeRfc9380PrivateKey", privateKey.key);
+    });
+
+    it("should produce the pinned deterministic ciphertext with the default RFC 9380 hash-to-curve", async () => {
+      const publicParams = await importIBEPublicParams(PUBLIC_PARAMS);
+      const ciphertext = await encryptIBEDeterministic(publicParams, IDENTITY, MESSAGE, MESSAGE_KEY);
+
+      expectGolden("ibeRfc9380DeterministicHeader", ciphertext.slice(0, 1 + 65 + 12));
+      expect(ciphertext.length).toBe(1 + 65 + 12 + MESSAGE.length + 16);
+      expect(await encryptIBEDeterministic(publicParams, IDENTITY, MESSAGE, MESSAGE_KEY)).toEqual(ciphertext);
+
+      const masterKey = await importIBEMasterKey(MASTER_KEY);
+      const privateKey = await extractIBEKey(masterKey, IDENTITY);
+      expect(await decryptIBE(privateKey, ciphertext)).toEqual(MESSAGE);
+    });
+
+    // 方式のIDを持ちAES-256-GCMのフラグのない暗号文（Vが対称鍵とのXOR）は、移行用の復号でも受け付けない
+    it("should reject the pinned ciphertext with a method id but no AES-256-GCM flag", async () => {
+      const masterKey = await importIBEMasterKey(MASTER_KEY);
+      const privateKey = await extractIBEKey(masterKey, IDENTITY);
+      const ciphertext = hexToBytes(GOLDEN.ibeRfc9380XorCiphertext);
+
+      expect(ciphertext[0]).toBe(0x01);
+      await expect(decryptIBE(privateKey, ciphertext)).rejects.toThrow(/missing the AES-256-GCM flag/);
+      await expect(decryptIBELegacy(privateKey, ciphertext)).rejects.toThrow(/missing the AES-256-GCM flag/);
+    });
+  });
+
+  describe("ABE", () => {
+    it("should generate the pinned CP-ABE private keys", async () => {
+      const masterKey = await importABEMasterKey(MASTER_KEY);
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+
+      expectGolden("abePrivateKeySha256", await hashSHA256(privateKey.key));
+      expectGolden("abeCompactPrivateKeySha256", await hashSHA256(privateKey.compact().key));
+    });
+
+    it("should generate the pinned KP-ABE private key", async () => {
+      const masterKey = await importABEMasterKey(MASTER_KEY);
+      const privateKey = await extractKPABEKey(masterKey, "A,B");
+
+      expectGolden("kpabePrivateKeySha256", await hashSHA256(privateKey.key));
+    });
+
+    // 認証されていない以前の形式は通常の復号では受け付けず、移行（upgrade_ciphertext）でのみ復号できる
+    it("should migrate the pinned ciphertext from before AES-256-GCM payloads", async () => {
+      const legacyKey = await importABEPrivateKey(hexToBytes(GOLDEN.abeLegacyPrivateKey), ["A", "B"]);
+      const publicParams = await importABEPublicParams(PUBLIC_PARAMS);
+      const ciphertext = hexToBytes(GOLDEN.abeHashedCiphertext);
+
+      // 属性ラベル付きの形式 (0x06) に平文のハッシュのフラグ (0x40) が付き、GCMのフラグ (0x20) はない
+      expect(ciphertext[0]).toBe(0x46);
+      expect(ciphertext[1]).toBe(1);
+      await expect(decryptABE(legacyKey, ciphertext)).rejects.toThrow(/認証されていない形式/);
+
+      const upgraded = await upgradeABECiphertext(ciphertext, legacyKey, publicParams);
+      const masterKey = await importABEMasterKey(MASTER_KEY);
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      expect(await decryptABE(privateKey, upgraded)).toEqual(MESSAGE);
+    });
+
+    it("should reject the pinned legacy ciphertext with a key using the current attribute hash", async () => {
+      const masterKey = await importABEMasterKey(MASTER_KEY);
+      const privateKey = await extractABEKey(masterKey, ["A", "B"]);
+      const publicParams = await importABEPublicParams(PUBLIC_PARAMS);
+      const ciphertext = hexToBytes(GOLDEN.abeHashedCiphertext);
+
+      await expect(upgradeABECiphertext(ciphertext, privateKey, publicParams)).rejects.toThrow(
+        /属性ハッシュのバージョンが一致しません/
+      );
+    });
+
+    // AES-256-GCMの対称鍵のKDFのラベルを変更したため、暗号文のダイジェストは固定し直すまで同じ乱数での再現性を確認する
+    it.skipIf(!hasTestRng)("should produce the same ciphertexts with fixed randomness", async () => {
+      const publicParams = await importABEPublicParams(PUBLIC_PARAMS);
+      const masterKey = await importABEMasterKey(MASTER_KEY);
+
+      try {
+        abeWasm.set_test_rng_mode("deterministic", RNG_SEED);
+        const ciphertext = await encryptABE(publicParams, "A,B", MESSAGE);
+        abeWasm.set_test_rng_mode("deterministic", RNG_SEED);
+        expect(await encryptABE(publicParams, "A,B", MESSAGE)).toEqual(ciphertext);
+        expect(await decryptABE(await extractABEKey(masterKey, ["A", "B"]), ciphertext)).toEqual(MESSAGE);
+
+        abeWasm.set_test_rng_mode("deterministic", RNG_SEED);
+        const kpCiphertext = await encryptKPABE(publicParams, ["A", "B"], MESSAGE);
+        abeWasm.set_test_rng_mode("deterministic", RNG_SEED);
+        expect(await encryptKPABE(publicParams, ["A", "B"], MESSAGE)).toEqual(kpCiphertext);
+      } finally {
+        abeWasm.set_test_rng_mode("os", new Uint8Array());
+      }
+    });
+  });
+
+  describe("FALCON", () => {
+    it("should generate the pinned key pair from a seed", async () => {
+      const { publicKey, privateKey } = await generateFalconKeyPairFromSeed(FALCON_SEED);
+
+      expectGolden("falconPublicKeySha256", await hashSHA256(publicKey));
+      expectGolden("falconPrivateKeySha256", await hashSHA256(privateKey));
+    });
+
+    it("should derive the pinned child seed", async () => {
+      expectGolden("falconChildSeed", await deriveFalconChildSeed(FALCON_SEED, 0));
+    });
+
+    it("should export the pinned public key JWK", async () => {
+      const { publicKey } = await generateFalconKeyPairFromSeed(FALCON_SEED);
+      const jwk = await exportFalconPublicKeyJwk(publicKey);
+
+      expectGolden("falconPublicJwkSha256", await hashSHA256(new TextEncoder().encode(JSON.stringify(jwk))));
+    });
+  });
+
+  // ML-KEM・ML-DSAは決定的なAPIを公開していないため、現時点では対象外
+});
@@ -3,6 +3,7 @@ import {
   AES_IV_LENGTH,
   AES_KEY_LENGTH,
   AES_TAG_LENGTH,
+  cloneAESWithNewNonce,
   decryptAES,
   encryptAES,
   generateAESKey,
@@ -73,4 +74,20 @@ describe("AES", () => {
       expect(decrypted).toEqual(plaintext);
     }
   });
+
+  it("should produce different ciphertexts for the same plaintext under new nonces", () => {
+    const key = generateAESKey();
+    const plaintext = new TextEncoder().encode("Hello, World!");
+
+    const original = encryptAES(plaintext, key);
+    const cloned = cloneAESWithNewNonce(original, key);
+
+    // IVが異なれば暗号文も異なる
+    expect(cloned.iv).not.toEqual(original.iv);
+    expect(cloned.ciphertext).not.toEqual(original.ciphertext);
+
+    // どちらも同じ平文に復号できる
+    expect(decryptAES(original.ciphertext, key, original.iv, original.authTag)).toEqual(plaintext);
+    expect(decryptAES(cloned.ciphertext, key, cloned.iv, cloned.authTag)).toEqual(plaintext);
+  });
 });
@@ -3,6 +3,7 @@ import {
   CHACHA20_KEY_LENGTH,
   CHACHA20_NONCE_LENGTH,
   POLY1305_TAG_LENGTH,
+  cloneChaCha20WithNewNonce,
   decryptChaCha20,
   encryptChaCha20,
   generateChaCha20Key,
@@ -73,4 +74,24 @@ describe("ChaCha20", () => {
       expect(decrypted).toEqual(plaintext);
     }
   });
+
+  it("should produce different ciphertexts for the same plaintext under new nonces", () => {
+    const key = generateChaCha20Key();
+    const plaintext = new TextEncoder().encode("Hello, World!");
+
+    const original = encryptChaCha20(plaintext, key);
+    const cloned = cloneChaCha20WithNewNonce(original, key);
+
+    // ノンスが異なれば暗号文も異なる
+    expect(cloned.nonce).not.toEqual(original.nonce);
+    expect(cloned.ciphertext).not.toEqual(original.ciphertext);
+
+    // どちらも同じ平文に復号できる
+    expect(decryptChaCha20(original.ciphertext, key, original.nonce, original.authTag)).toEqual(
+      plaintext
+    );
+    expect(decryptChaCha20(cloned.ciphertext, key, cloned.nonce, cloned.authTag)).toEqual(
+      plaintext
+    );
+  });
 });
@@ -0,0 +1,59 @@
+/**
+ * バイト配列の16進数・Base64変換のテスト
+ */
+
+import { describe, it, expect } from "vitest";
+import { base64ToBytes, bytesToBase64, bytesToHex, hexToBytes } from "../../src/utils/format.js";
+
+describe("バイト配列の変換", () => {
+  // 0x00〜0xffのすべての値を含むバイト列
+  const ALL_BYTES = Uint8Array.from({ length: 256 }, (_, i) => i);
+
+  describe("16進数", () => {
+    it("往復変換できる", () => {
+      expect(bytesToHex(new Uint8Array([0x48, 0x65, 0x6c, 0x6c, 0x6f]))).toBe("48656c6c6f");
+      expect(hexToBytes(bytesToHex(ALL_BYTES))).toEqual(ALL_BYTES);
+      expect(hexToBytes("")).toEqual(new Uint8Array(0));
+    });
+
+    it("大文字と空白を受け付ける", () => {
+      expect(hexToBytes("DE AD be ef")).toEqual(new Uint8Array([0xde, 0xad, 0xbe, 0xef]));
+    });
+
+    it("奇数長の文字列は拒否される", () => {
+      expect(() => hexToBytes("abc")).toThrow("Invalid hex string: length must be even");
+    });
+
+    it("16進数以外の文字は位置を示して拒否される", () => {
+      expect(() => hexToBytes("zz")).toThrow("Invalid hex string: invalid character at position 0");
+      // parseIntでは"0g"が0になるが、変換前に拒否する
+      expect(() => hexToBytes("000g")).toThrow("Invalid hex string: invalid character at position 3");
+      expect(() => hexToBytes("0x12")).toThrow("Invalid hex string: invalid character at position 1");
+    });
+  });
+
+  describe("Base64", () => {
+    it("往復変換できる", () => {
+      expect(bytesToBase64(new TextEncoder().encode("Hello"))).toBe("SGVsbG8=");
+      expect(base64ToBytes(bytesToBase64(ALL_BYTES))).toEqual(ALL_BYTES);
+      expect(base64ToBytes("")).toEqual(new Uint8Array(0));
+      expect(base64ToBytes(" SGVsbG8=\n")).toEqual(new TextEncoder().encode("Hello"));
+    });
+
+    it("不正なBase64は拒否される", () => {
+      const invalid = [
+        "SGVsbG8", // パディングなし
+        "SGVsbG8==", // パディングが多い
+        "SGV$bG8=", // アルファベット以外の文字
+        "SGVs bG8=", // 途中の空白
+        "SGVsbG8-", // base64url
+        "=SGVsbG8",
+      ];
+      for (const value of invalid) {
+        expect(() => base64ToBytes(value), value).toThrow(
+          "Invalid base64 string: expected standard alphabet with padding"
+        );
+      }
+    });
+  });
+});
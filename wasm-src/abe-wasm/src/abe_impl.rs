@@ -3,39 +3,129 @@
 
 use miracl_core::bn254::{
     big::BIG,
-    ecp::ECP,
+    dbig::DBIG,
+    ecp::{self, ECP},
     ecp2::ECP2,
+    fp::FP,
     fp12::FP12,
+    fp2::FP2,
     pair,
     rom,
 };
+use miracl_core::hmac;
 use miracl_core::rand::RAND;
-use getrandom::getrandom;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
 
 /// WebAssembly環境用のRAND実装
+/// 通常はgetrandomから取得し、seedでシードを与えた場合はシードから決定的に生成する
 pub struct WasmRAND {
     buffer: Vec<u8>,
     pos: usize,
+    // 一度でもエントロピーの取得に失敗したか
+    failed: bool,
+    // seedで設定した決定的な生成器の状態（鍵, カウンタ）。Noneの場合はエントロピー源から取得する
+    deterministic: Option<([u8; 32], u64)>,
 }
 
+/// シードから決定的な生成器の鍵を導出する際のドメイン分離用ラベル
+const SEEDED_RAND_LABEL: &[u8] = b"abe-wasm seeded rand v1";
+
 impl WasmRAND {
     pub fn new() -> Self {
         WasmRAND {
             buffer: Vec::new(),
             pos: 0,
+            failed: false,
+            deterministic: None,
         }
     }
 
     fn refill(&mut self) {
+        use sha2::{Digest, Sha256};
+        
+        self.pos = 0;
+        // シードが設定されている場合は SHA-256(鍵 || カウンタ) のブロックを順に返す
+        if let Some((key, counter)) = &mut self.deterministic {
+            self.buffer = Sha256::new()
+                .chain_update(*key)
+                .chain_update(counter.to_be_bytes())
+                .finalize()
+                .to_vec();
+            *counter += 1;
+            return;
+        }
+        
         self.buffer = vec![0u8; 32];
-        if getrandom(&mut self.buffer).is_ok() {
-            self.pos = 0;
+        // 取得に失敗した場合や、すべて0のブロック（壊れたエントロピー源）は失敗として記録する
+        if !fill_entropy(&mut self.buffer) || self.buffer.iter().all(|&b| b == 0) {
+            self.failed = true;
+        }
+    }
+}
+
+/// 乱数生成の失敗（エントロピー源が利用できない、または出力が明らかに偏っている）
+#[derive(Debug, Clone, Copy)]
+pub struct RngFailure;
+
+/// エントロピー源からバッファを埋める。失敗した場合はfalseを返す
+#[cfg(not(feature = "test-rng"))]
+fn fill_entropy(buffer: &mut [u8]) -> bool {
+    getrandom::getrandom(buffer).is_ok()
+}
+
+/// エントロピー源からバッファを埋める（テスト用の乱数生成モードに従う）
+#[cfg(feature = "test-rng")]
+fn fill_entropy(buffer: &mut [u8]) -> bool {
+    crate::test_rng::fill(buffer)
+}
+
+impl WasmRAND {
+    /// 一度でもエントロピーの取得に失敗したか
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+}
+
+/// ML-KEMのカプセル化（pqcrypto-std）で使用するためのrand_core実装
+/// 取得に失敗した場合はfailedに記録されるため、呼び出し側で確認すること
+impl rand_core::RngCore for WasmRAND {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest.iter_mut() {
+            *byte = self.getbyte();
         }
     }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
 }
 
+impl rand_core::CryptoRng for WasmRAND {}
+
 impl RAND for WasmRAND {
-    fn seed(&mut self, _rawlen: usize, _raw: &[u8]) {
+    /// rawの先頭rawlenバイトをシードとして、以降の出力を決定的にする
+    /// 鍵は SHA-256(label || シード) で導出し、同じシードからは常に同じバイト列が得られる
+    /// シードが空の場合はエントロピー源（getrandom）からの取得に戻す
+    fn seed(&mut self, rawlen: usize, raw: &[u8]) {
+        use sha2::{Digest, Sha256};
+        
+        let raw = &raw[..rawlen.min(raw.len())];
+        self.deterministic = if raw.is_empty() {
+            None
+        } else {
+            let key = Sha256::new().chain_update(SEEDED_RAND_LABEL).chain_update(raw).finalize();
+            Some((key.into(), 0))
+        };
         self.refill();
     }
 
@@ -49,35 +139,286 @@ impl RAND for WasmRAND {
     }
 }
 
+/// 属性ハッシュキャッシュの最大エントリ数
+const ATTRIBUTE_CACHE_CAPACITY: usize = 256;
+
+/// 属性文字列から直列化済みのG2の点へのLRUキャッシュ
+/// key_genとencryptで同じ属性のハッシュ計算（スカラー倍算）を繰り返さないために使用
+struct AttributeHashCache {
+    entries: HashMap<String, Vec<u8>>,
+    // 先頭が最も長く使われていないエントリ
+    order: VecDeque<String>,
+}
+
+impl AttributeHashCache {
+    fn new() -> Self {
+        AttributeHashCache {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, attribute: &str) -> Option<Vec<u8>> {
+        let bytes = self.entries.get(attribute)?.clone();
+        self.touch(attribute);
+        Some(bytes)
+    }
+
+    fn insert(&mut self, attribute: &str, bytes: Vec<u8>) {
+        if self.entries.insert(attribute.to_string(), bytes).is_some() {
+            self.touch(attribute);
+            return;
+        }
+        self.order.push_back(attribute.to_string());
+        if self.order.len() > ATTRIBUTE_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    /// エントリを最も最近使われた位置に移動
+    fn touch(&mut self, attribute: &str) {
+        if let Some(pos) = self.order.iter().position(|a| a == attribute) {
+            if let Some(entry) = self.order.remove(pos) {
+                self.order.push_back(entry);
+            }
+        }
+    }
+}
+
+// スレッドモデルについて:
+// wasm32-unknown-unknown（atomicsなし）ではインスタンスは単一スレッドで動作し、
+// Web Workerごとに別々のインスタンス・線形メモリを持つため、キャッシュが共有されることはない。
+// atomicsを有効にしたビルドでもthread_localはスレッドごとに独立しているため、
+// スレッド間でデータ競合は起こらない（キャッシュがスレッドごとに温まるだけ）。
+// このためMutexではなくthread_local + RefCellを使用する。
+thread_local! {
+    static ATTRIBUTE_CACHE: RefCell<AttributeHashCache> = RefCell::new(AttributeHashCache::new());
+    // キャッシュから属性ハッシュを取得できた回数（性能測定用）
+    static ATTRIBUTE_CACHE_HITS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// これまでに属性ハッシュキャッシュから取得できた回数
+pub fn attribute_cache_hits() -> u64 {
+    ATTRIBUTE_CACHE_HITS.with(|hits| hits.get())
+}
+
+/// 属性ハッシュ（hash_attribute）の方式のバージョン
+/// ハッシュ方式を変更した場合は値を更新し、旧方式の鍵・暗号文を検出できるようにする
+/// 2〜4は旧形式（ヘッダなし）の暗号文の2バイト目（C0の先頭バイト）と区別できないため使わない
+pub const ATTRIBUTE_HASH_VERSION: u8 = 5;
+
+/// 旧方式（H(attr)·Q）の属性ハッシュのバージョン（復号のみ対応）
+pub const LEGACY_ATTRIBUTE_HASH_VERSION: u8 = 1;
+
+/// RFC 9380の構成で属性をG2にハッシュする際のドメイン分離タグ
+const HASH_TO_G2_DST: &[u8] = b"ABE-WASM-V01-CS01-with-BN254G2_XMD:SHA-256_SVDW_RO_";
+
+/// RFC 9380の構成で属性と有効期限をG1にハッシュする際のドメイン分離タグ
+const HASH_TO_G1_DST: &[u8] = b"ABE-WASM-V01-CS01-with-BN254G1_XMD:SHA-256_SVDW_RO_";
+
+/// Waters方式のLSSSで使うG2の点Wを導出する際のドメイン分離タグ
+const LSSS_BASE_DST: &[u8] = b"ABE-WASM-V01-CS02-LSSS-BASE-with-BN254G2_XMD:SHA-256_SVDW_RO_";
+
+/// 属性を曲線上の点に写像する方式（鍵・暗号文のヘッダの属性ハッシュバージョンで区別する）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeHash {
+    /// RFC 9380の構成（BN254G2_XMD:SHA-256_SVDW_RO_、有効期限はBN254G1）
+    /// 写像した点の生成元に対する離散対数は誰にも分からない
+    Rfc9380,
+    /// 旧方式: H(attr)·Q（Qは生成元）
+    /// 離散対数が既知になり、1つの鍵コンポーネントから他の属性の鍵コンポーネントを計算できてしまうため、
+    /// 既存の鍵・暗号文を復号するためだけに残している
+    Legacy,
+}
+
+impl AttributeHash {
+    /// ヘッダの属性ハッシュバージョンから方式を取得
+    pub fn from_version(version: u8) -> Option<AttributeHash> {
+        match version {
+            ATTRIBUTE_HASH_VERSION => Some(AttributeHash::Rfc9380),
+            LEGACY_ATTRIBUTE_HASH_VERSION => Some(AttributeHash::Legacy),
+            _ => None,
+        }
+    }
+
+    /// 方式の属性ハッシュバージョン
+    pub fn version(self) -> u8 {
+        match self {
+            AttributeHash::Rfc9380 => ATTRIBUTE_HASH_VERSION,
+            AttributeHash::Legacy => LEGACY_ATTRIBUTE_HASH_VERSION,
+        }
+    }
+}
+
+/// 属性の鍵保有証明のドメイン分離用ラベル
+const ATTRIBUTE_PROOF_LABEL: &[u8] = b"abe-wasm attribute proof v1";
+
+/// 属性の有効期限タグのドメイン分離用ラベル
+const ATTRIBUTE_EXPIRY_LABEL: &[u8] = b"abe-wasm attribute expiry v1";
+
+/// ペアリング値から旧形式（XOR）の対称鍵を導出するKDFのドメイン分離用ラベル
+const KDF_LABEL: &[u8] = b"abe-wasm kdf v1";
+
+/// ペアリング値からAES-256-GCMの対称鍵を導出するKDFのドメイン分離用ラベル
+/// 旧形式と異なるラベルにより、GCMのフラグを消した暗号文を旧形式として復号しても、GCMの対称鍵は得られない
+const GCM_KDF_LABEL: &[u8] = b"abe-wasm kdf aes-256-gcm v1";
+
+
+/// 復号時の対称鍵の導出方式
+#[derive(Clone, Copy)]
+pub enum KeyDerivation<'a> {
+    /// 暗号化の文脈に束縛したKDF（derive_key）
+    Context(&'a [u8]),
+    /// 文脈を含まない旧方式（hash_pairing_result）。旧形式の暗号文の移行用
+    Legacy,
+}
+
+impl KeyDerivation<'_> {
+    /// ペアリング値から、Vの暗号化方式に応じた対称鍵を導出
+    pub fn derive(self, p: &FP12, payload: Payload) -> [u8; 32] {
+        match self {
+            KeyDerivation::Context(context) => ABEImpl::derive_key(p, context, payload),
+            KeyDerivation::Legacy => ABEImpl::hash_pairing_result(p),
+        }
+    }
+}
+
+// 計算したペアリングの回数（性能測定用）
+thread_local! {
+    static PAIRING_COUNT: Cell<u64> = const { Cell::new(0) };
+}
+
+/// ペアリング e(Q, P) を計算（最終べき乗まで）
+/// 性能測定のため、計算した回数をスレッド（インスタンス）ごとに数える
+pub fn pairing(q: &ECP2, p: &ECP) -> FP12 {
+    PAIRING_COUNT.with(|count| count.set(count.get() + 1));
+    pair::fexp(&pair::ate(q, p))
+}
+
+/// これまでに計算したペアリングの回数
+pub fn pairing_count() -> u64 {
+    PAIRING_COUNT.with(|count| count.get())
+}
+
+/// Vコンポーネント（メッセージ本体）の暗号化方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Payload {
+    /// AES-256-GCM: V = nonce (12バイト) || 暗号文 || 認証タグ (16バイト)
+    Gcm,
+    /// 旧形式: V = M ⊕ 対称鍵（32バイトごとに繰り返す）。完全性の保護がなく、以前の暗号文の復号にのみ使用する
+    Xor,
+}
+
+/// 対称鍵でメッセージをAES-256-GCMで暗号化（nonceはランダムに選ぶ）
+pub fn seal_payload(hash_key: &[u8; 32], message: &[u8]) -> Result<Vec<u8>, RngFailure> {
+    crate::hybrid::seal_aead(hash_key, &[], message)
+}
+
+/// 対称鍵でVコンポーネントを復号化
+/// AES-256-GCMでVが短すぎる場合や認証タグが一致しない場合（改ざん・鍵の不一致）はNoneを返す
+pub fn open_payload(hash_key: &[u8; 32], v: &[u8], payload: Payload) -> Option<Vec<u8>> {
+    match payload {
+        Payload::Gcm => crate::hybrid::open_aead(hash_key, &[], v),
+        Payload::Xor => Some(v.iter().enumerate().map(|(i, &byte)| byte ^ hash_key[i % 32]).collect()),
+    }
+}
+
 /// CP-ABEスキームの実装
 pub struct ABEImpl;
 
 impl ABEImpl {
     /// ランダムなBIGを生成
-    pub fn random_big() -> BIG {
+    /// エントロピー源が失敗した場合や結果が0の場合は、弱い鍵を生成しないようエラーを返す
+    pub fn random_big() -> Result<BIG, RngFailure> {
         let mut rng = WasmRAND::new();
         let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
-        BIG::randomnum(&curve_order, &mut rng)
+        let r = BIG::randomnum(&curve_order, &mut rng);
+        if rng.failed || r.iszilch() {
+            return Err(RngFailure);
+        }
+        Ok(r)
     }
 
     /// 属性をハッシュ化してECP2に変換
+    /// 計算結果はキャッシュされ、key_genとencryptで共有される
+    /// キャッシュが借用中の場合（再入時）はパニックせず、キャッシュを使わずに計算する
     pub fn hash_attribute(attribute: &str) -> ECP2 {
-        // SHA-256を使用してハッシュ化
-        use sha2::{Sha256, Digest};
+        let cached = ATTRIBUTE_CACHE.with(|cache| {
+            cache.try_borrow_mut().ok().and_then(|mut cache| cache.get(attribute))
+        });
+        if let Some(bytes) = cached {
+            ATTRIBUTE_CACHE_HITS.with(|hits| hits.set(hits.get() + 1));
+            return ECP2::frombytes(&bytes);
+        }
         
-        let mut hasher = Sha256::new();
-        hasher.update(attribute.as_bytes());
-        let hash = hasher.finalize();
+        let point = Self::hash_attribute_uncached(attribute);
+        
+        let mut bytes = vec![0u8; 130];
+        point.tobytes(&mut bytes, false);
+        ATTRIBUTE_CACHE.with(|cache| {
+            if let Ok(mut cache) = cache.try_borrow_mut() {
+                cache.insert(attribute, bytes);
+            }
+        });
+        
+        point
+    }
+
+    /// 属性をハッシュ化してECP2に変換（キャッシュを使用しない）
+    /// RFC 9380のhash_to_curve（ランダムオラクル版）: 2つのFP2の元をShallue-van de Woestijne写像で点に移して足し、余因子を除く
+    pub fn hash_attribute_uncached(attribute: &str) -> ECP2 {
+        Self::hash_to_g2(HASH_TO_G2_DST, attribute.as_bytes())
+    }
+
+    /// RFC 9380のhash_to_curveでメッセージをG2の点に写像
+    fn hash_to_g2(dst: &[u8], message: &[u8]) -> ECP2 {
+        let u = Self::hash_to_field(dst, message, 4);
         
-        // ハッシュからBIGを作成
-        let mut h = BIG::frombytes(&hash);
+        let mut q = ECP2::map2point(&FP2::new_fps(&u[0], &u[1]));
+        q.add(&ECP2::map2point(&FP2::new_fps(&u[2], &u[3])));
+        q.cfp();
+        q.affine();
+        q
+    }
+
+    /// 指定した方式で属性をハッシュ化してECP2に変換（旧方式の鍵・暗号文の復号用）
+    pub fn hash_attribute_with(attribute: &str, hash: AttributeHash) -> ECP2 {
+        match hash {
+            AttributeHash::Rfc9380 => Self::hash_attribute(attribute),
+            AttributeHash::Legacy => Self::hash_attribute_legacy(attribute),
+        }
+    }
+
+    /// 旧方式で属性をECP2に変換（H(attr)·Q）
+    fn hash_attribute_legacy(attribute: &str) -> ECP2 {
+        ECP2::generator().mul(&Self::legacy_scalar(attribute.as_bytes()))
+    }
+
+    /// 旧方式のスカラー SHA-256(data) mod r
+    fn legacy_scalar(data: &[u8]) -> BIG {
+        use sha2::{Sha256, Digest};
+        
+        let mut h = BIG::frombytes(&Sha256::digest(data));
         let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
         h.rmod(&curve_order);
+        h
+    }
+
+    /// RFC 9380のhash_to_field（FPの元をcount個）
+    /// expand_message_xmd(SHA-256)の出力をL = ceil((ceil(log2(p)) + k) / 8)バイトずつ区切り、pで剰余を取る
+    fn hash_to_field(dst: &[u8], message: &[u8], count: usize) -> Vec<FP> {
+        let modulus = BIG::new_ints(&rom::MODULUS);
+        let modulus_bits = modulus.nbits();
+        let element_size = (modulus_bits + ecp::AESKEY * 8).div_ceil(8);
         
-        // ECP2の生成元を使用して点を生成
-        let mut q = ECP2::generator();
-        q = q.mul(&h);
-        q
+        let mut okm = vec![0u8; count * element_size];
+        hmac::xmd_expand(hmac::MC_SHA2, ecp::HASH_TYPE, &mut okm, count * element_size, dst, message);
+        
+        okm.chunks_exact(element_size)
+            .map(|chunk| FP::new_big(&DBIG::frombytes(chunk).ctdmod(&modulus, 8 * element_size - modulus_bits)))
+            .collect()
     }
 
     /// メッセージをハッシュ化（SHA-256）
@@ -89,6 +430,7 @@ impl ABEImpl {
     }
 
     /// ペアリング演算の結果をハッシュ化
+    /// 文脈を含まない旧方式の鍵導出。旧形式の暗号文の復号にのみ使用する
     pub fn hash_pairing_result(p: &FP12) -> [u8; 32] {
         let mut bytes = vec![0u8; 384];
         let mut p_copy = FP12::new_copy(p);
@@ -96,20 +438,44 @@ impl ABEImpl {
         Self::hash_message(&bytes)
     }
 
+    /// ペアリング値と暗号化の文脈から対称鍵を導出
+    /// SHA-256(label || len(context) (4バイト) || context || ペアリング値)
+    /// 同じペアリング値でも文脈（スキーム・属性など）やVの暗号化方式が異なれば別の鍵になる
+    pub fn derive_key(p: &FP12, context: &[u8], payload: Payload) -> [u8; 32] {
+        use sha2::{Sha256, Digest};
+        
+        let mut bytes = vec![0u8; 384];
+        let mut p_copy = FP12::new_copy(p);
+        p_copy.tobytes(&mut bytes);
+        
+        let mut hasher = Sha256::new();
+        hasher.update(match payload {
+            Payload::Gcm => GCM_KDF_LABEL,
+            Payload::Xor => KDF_LABEL,
+        });
+        hasher.update((context.len() as u32).to_be_bytes());
+        hasher.update(context);
+        hasher.update(&bytes);
+        hasher.finalize().into()
+    }
+
     /// Setup: マスター鍵ペアを生成
-    pub fn setup() -> (BIG, ECP) {
+    pub fn setup() -> Result<(BIG, ECP), RngFailure> {
         // マスター秘密鍵αをランダムに選択
-        let alpha = Self::random_big();
+        let alpha = Self::random_big()?;
         
         // 公開パラメータP_pub = αPを計算（PはECPの生成元）
         let p = ECP::generator();
         let p_pub = p.mul(&alpha);
         
-        (alpha, p_pub)
+        Ok((alpha, p_pub))
     }
 
     /// KeyGen: 属性セットから秘密鍵を生成
     /// 注意: 簡易実装。実際のCP-ABEでは、各属性に対応する鍵コンポーネントを生成
+    /// 鍵コンポーネントαH(attr)はユーザーごとの乱数を含まず、誰の鍵でも同じ値になるため結託耐性がない
+    /// （属性Aの鍵と属性Bの鍵を持つ2人が組み合わせると、A,Bの鍵と同じになる）
+    /// 結託耐性が必要な場合はブール式のポリシー（lsss_key_genの鍵で復号するLSSS）を使う
     pub fn key_gen(alpha: &BIG, attributes: &[String]) -> Vec<ECP2> {
         // 各属性に対応する秘密鍵コンポーネントを生成
         // 実際のCP-ABEでは、より複雑な構造が必要
@@ -129,9 +495,15 @@ impl ABEImpl {
 
     /// Encrypt: メッセージを暗号化
     /// 注意: 簡易実装。実際のCP-ABEでは、アクセスポリシーに基づいた複雑な構造が必要
-    pub fn encrypt(p_pub: &ECP, attributes: &[String], message: &[u8]) -> (ECP, Vec<u8>, Vec<ECP2>) {
+    /// key_genと同じく結託耐性がない（対称鍵は先頭の属性の鍵コンポーネントだけで導出できる）
+    pub fn encrypt(
+        p_pub: &ECP,
+        attributes: &[String],
+        message: &[u8],
+        context: &[u8],
+    ) -> Result<(ECP, Vec<u8>, Vec<ECP2>), RngFailure> {
         // ランダムなsを選択
-        let s = Self::random_big();
+        let s = Self::random_big()?;
         
         // C0 = sPを計算
         let p = ECP::generator();
@@ -150,46 +522,253 @@ impl ABEImpl {
         // 簡易実装: e(P_pub, H(attr_0))^sを使用
         if let Some(first_attr) = attributes.first() {
             let h_attr = Self::hash_attribute(first_attr);
-            let pairing = pair::ate(&h_attr, p_pub);
-            let pairing_final = pair::fexp(&pairing);
+            let pairing_final = pairing(&h_attr, p_pub);
             let pairing_s = pairing_final.pow(&s);
-            let hash_key = Self::hash_pairing_result(&pairing_s);
+            let hash_key = Self::derive_key(&pairing_s, context, Payload::Gcm);
             
-            // V = M ⊕ H(e(P_pub, H(attr))^s)を計算
-            let mut v = Vec::with_capacity(message.len());
-            for (i, &byte) in message.iter().enumerate() {
-                v.push(byte ^ hash_key[i % 32]);
-            }
+            // V = AES-256-GCM(KDF(e(P_pub, H(attr))^s, context), M)を計算
+            let v = seal_payload(&hash_key, message)?;
             
-            (c0, v, c_attrs)
+            Ok((c0, v, c_attrs))
         } else {
             // 属性がない場合は、メッセージをそのまま返す（簡易実装）
-            (c0, message.to_vec(), c_attrs)
+            Ok((c0, message.to_vec(), c_attrs))
         }
     }
 
+    /// MatchAttributes: 暗号文の属性コンポーネントごとに、対応する属性の位置を返す
+    /// C_attr = sH(b) と属性aは e(C_attr, P) = e(H(a), C0) のときに限り一致する（a = b）
+    /// ペアリングは暗号文の属性数 + 属性の数だけ計算する
+    pub fn match_attributes(attributes: &[String], c0: &ECP, c_attrs: &[ECP2], hash: AttributeHash) -> Vec<Option<usize>> {
+        let p = ECP::generator();
+        let candidates: Vec<FP12> = attributes
+            .iter()
+            .map(|attribute| pairing(&Self::hash_attribute_with(attribute, hash), c0))
+            .collect();
+        
+        c_attrs
+            .iter()
+            .map(|c_attr| {
+                let lhs = pairing(c_attr, &p);
+                candidates.iter().position(|candidate| lhs.equals(candidate))
+            })
+            .collect()
+    }
+
     /// Decrypt: 暗号文を復号化
-    /// 注意: 簡易実装。実際のCP-ABEでは、ポリシー満足性のチェックが必要
-    pub fn decrypt(key_components: &[ECP2], c0: &ECP, v: &[u8], c_attrs: &[ECP2]) -> Vec<u8> {
-        // 簡易実装: 最初の鍵コンポーネントを使用
-        if let (Some(key_comp), Some(c_attr)) = (key_components.first(), c_attrs.first()) {
+    /// key_componentsの先頭は、暗号文の先頭の属性（対称鍵を決める属性）の鍵コンポーネントである必要がある
+    /// ポリシーを満たすかの確認（match_attributes）は呼び出し側で行う
+    /// AES-256-GCMの認証タグが一致しない場合や、鍵コンポーネント・属性コンポーネントがない場合はNoneを返す
+    pub fn decrypt(
+        key_components: &[ECP2],
+        c0: &ECP,
+        v: &[u8],
+        c_attrs: &[ECP2],
+        kdf: KeyDerivation,
+        payload: Payload,
+    ) -> Option<Vec<u8>> {
+        // 先頭の鍵コンポーネントを使用
+        if let (Some(key_comp), Some(_)) = (key_components.first(), c_attrs.first()) {
             // e(key_comp, C0)を計算
-            let pairing = pair::ate(key_comp, c0);
-            let pairing_final = pair::fexp(&pairing);
-            let hash_key = Self::hash_pairing_result(&pairing_final);
+            let pairing_final = pairing(key_comp, c0);
+            let hash_key = kdf.derive(&pairing_final, payload);
             
-            // M = V ⊕ H(e(key_comp, C0))を計算
-            let mut message = Vec::with_capacity(v.len());
-            for (i, &byte) in v.iter().enumerate() {
-                message.push(byte ^ hash_key[i % 32]);
-            }
-            
-            message
+            // KDF(e(key_comp, C0), context)でVを復号化
+            open_payload(&hash_key, v, payload)
         } else {
-            // 鍵コンポーネントがない場合は、そのまま返す
-            v.to_vec()
+            // 鍵コンポーネント・属性コンポーネントがない場合は対称鍵を導出できず、Vを認証できないため復号しない
+            None
+        }
+    }
+}
+
+impl ABEImpl {
+    /// 鍵保有証明のチャレンジ値 c = H(label || attribute || challenge || Y || A) mod r を計算
+    fn attribute_proof_challenge(attribute: &str, challenge: &[u8], y: &FP12, a: &FP12) -> BIG {
+        use sha2::{Sha256, Digest};
+        
+        let mut y_bytes = vec![0u8; 384];
+        FP12::new_copy(y).tobytes(&mut y_bytes);
+        let mut a_bytes = vec![0u8; 384];
+        FP12::new_copy(a).tobytes(&mut a_bytes);
+        
+        // 可変長の入力は長さを前置して連結の曖昧さをなくす
+        let mut hasher = Sha256::new();
+        hasher.update(ATTRIBUTE_PROOF_LABEL);
+        hasher.update((attribute.len() as u32).to_be_bytes());
+        hasher.update(attribute.as_bytes());
+        hasher.update((challenge.len() as u32).to_be_bytes());
+        hasher.update(challenge);
+        hasher.update(&y_bytes);
+        hasher.update(&a_bytes);
+        let hash = hasher.finalize();
+        
+        let mut c = BIG::frombytes(&hash);
+        let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
+        c.rmod(&curve_order);
+        c
+    }
+
+    /// ProveAttribute: 属性の鍵コンポーネントK = αH(attr)を、Kを明かさずに保有していることを証明
+    /// Schnorr型の証明（Fiat-Shamir変換）:
+    ///   Y = e(K, P)、A = e(T, P)（Tはランダムな点）、c = H(..., Y, A)、Z = T + cK
+    /// challengeには検証者が指定した値を含め、証明の使い回しを防ぐ
+    pub fn prove_attribute(key_component: &ECP2, attribute: &str, challenge: &[u8]) -> Result<(FP12, ECP2), RngFailure> {
+        let p = ECP::generator();
+        
+        // 証明する命題 Y = e(K, P) = e(H(attr), P_pub)
+        let y = pairing(key_component, &p);
+        
+        // コミットメント A = e(T, P)
+        let t = ECP2::generator().mul(&Self::random_big()?);
+        let a = pairing(&t, &p);
+        
+        // レスポンス Z = T + cK
+        let c = Self::attribute_proof_challenge(attribute, challenge, &y, &a);
+        let mut z = t;
+        z.add(&key_component.mul(&c));
+        
+        Ok((a, z))
+    }
+
+    /// VerifyAttributeProof: e(Z, P) = A · Y^c を確認（Y = e(H(attr), P_pub)）
+    pub fn verify_attribute_proof(p_pub: &ECP, attribute: &str, challenge: &[u8], a: &FP12, z: &ECP2) -> bool {
+        let p = ECP::generator();
+        
+        // 公開情報から命題 Y = e(H(attr), P_pub) を計算
+        let h_attr = Self::hash_attribute(attribute);
+        let y = pairing(&h_attr, p_pub);
+        
+        let c = Self::attribute_proof_challenge(attribute, challenge, &y, a);
+        
+        let lhs = pairing(z, &p);
+        let mut rhs = FP12::new_copy(a);
+        rhs.mul(&y.pow(&c));
+        
+        lhs.equals(&rhs)
+    }
+
+    /// 属性と有効期限をハッシュ化してECPに変換
+    /// H(label || len(attr) (4バイト) || attr || expires_at (8バイト))
+    /// 現在の方式はRFC 9380のhash_to_curve（BN254G1）、旧方式はH(...)·P（Pは生成元）
+    fn hash_attribute_expiry(attribute: &str, expires_at: u64, hash: AttributeHash) -> ECP {
+        let mut message = ATTRIBUTE_EXPIRY_LABEL.to_vec();
+        message.extend_from_slice(&(attribute.len() as u32).to_be_bytes());
+        message.extend_from_slice(attribute.as_bytes());
+        message.extend_from_slice(&expires_at.to_be_bytes());
+        
+        match hash {
+            AttributeHash::Rfc9380 => {
+                let u = Self::hash_to_field(HASH_TO_G1_DST, &message, 2);
+                let mut q = ECP::map2point(&u[0]);
+                q.add(&ECP::map2point(&u[1]));
+                q.cfp();
+                q.affine();
+                q
+            }
+            AttributeHash::Legacy => ECP::generator().mul(&Self::legacy_scalar(&message)),
+        }
+    }
+
+    /// ExpiryTag: 属性の有効期限を鍵コンポーネントに束縛するタグ T = αH_exp(attr, expires_at) を生成
+    /// マスター秘密鍵なしでは別の有効期限のタグを作れないため、有効期限を書き換えられない
+    pub fn expiry_tag(alpha: &BIG, attribute: &str, expires_at: u64) -> ECP {
+        Self::hash_attribute_expiry(attribute, expires_at, AttributeHash::Rfc9380).mul(alpha)
+    }
+
+    /// VerifyExpiryTag: e(K, H_exp(attr, expires_at)) = e(H(attr), T) を確認（K = αH(attr)）
+    /// 公開パラメータなしで、鍵コンポーネントとタグが同じマスター鍵から生成されたことを検証できる
+    /// hashには秘密鍵のヘッダの属性ハッシュの方式を指定する
+    pub fn verify_expiry_tag(key_component: &ECP2, attribute: &str, expires_at: u64, tag: &ECP, hash: AttributeHash) -> bool {
+        if tag.is_infinity() {
+            return false;
+        }
+        
+        let h_expiry = Self::hash_attribute_expiry(attribute, expires_at, hash);
+        let lhs = pairing(key_component, &h_expiry);
+        let rhs = pairing(&Self::hash_attribute_with(attribute, hash), tag);
+        
+        lhs.equals(&rhs)
+    }
+}
+
+impl ABEImpl {
+    /// Waters方式のLSSSの鍵・暗号文で使うG2の点W（離散対数を誰も知らない点）
+    /// 属性とは異なるDSTでハッシュするため、どの属性名のH(attr)とも一致しない
+    fn lsss_base() -> ECP2 {
+        Self::hash_to_g2(LSSS_BASE_DST, &[])
+    }
+
+    /// LsssKeyGen: 属性セットからWaters方式のLSSSの秘密鍵を生成
+    /// ユーザーごとの乱数tで K = αQ + tW、L = tP、K_attr = tH(attr) とする（QはG2の生成元）
+    /// 鍵コンポーネントはすべて同じtに結び付くため、別々の鍵の属性を組み合わせても
+    /// 復号に必要な e(Q, P)^{αs} は得られない（結託耐性）
+    pub fn lsss_key_gen(alpha: &BIG, attributes: &[String]) -> Result<LsssKey, RngFailure> {
+        let t = Self::random_big()?;
+        
+        let mut k = ECP2::generator().mul(alpha);
+        k.add(&Self::lsss_base().mul(&t));
+        let l = ECP::generator().mul(&t);
+        let components = attributes
+            .iter()
+            .map(|attr| Self::hash_attribute(attr).mul(&t))
+            .collect();
+        
+        Ok(LsssKey { k, l, components })
+    }
+
+    /// EncryptLsss: LSSS行列の行ごとのシェアλ_i（秘密sの分散）から暗号文コンポーネントを生成
+    /// C0 = sP、行ごとに乱数r_iで C_i = λ_i W − r_i H(ρ(i))（G2）、D_i = r_i P（G1）
+    /// 戻り値は (メッセージの対称鍵の導出に使う e(Q, P_pub)^s, C0, 行ごとの (C_i, D_i))
+    pub fn encrypt_lsss(
+        p_pub: &ECP,
+        labels: &[String],
+        shares: &[BIG],
+        s: &BIG,
+    ) -> Result<(FP12, ECP, Vec<(ECP2, ECP)>), RngFailure> {
+        let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
+        let base = Self::lsss_base();
+        
+        let blinding = pairing(&ECP2::generator(), p_pub).pow(s);
+        let c0 = ECP::generator().mul(s);
+        
+        let mut rows = Vec::with_capacity(labels.len());
+        for (label, share) in labels.iter().zip(shares) {
+            let r = Self::random_big()?;
+            let mut c = base.mul(share);
+            c.add(&Self::hash_attribute(label).mul(&BIG::modneg(&r, &curve_order)));
+            rows.push((c, ECP::generator().mul(&r)));
         }
+        
+        Ok((blinding, c0, rows))
     }
+
+    /// DecryptLsss: 再構成の係数ω（Σ ω_i M_i = (1, 0, ..., 0)）を使って e(Q, P_pub)^s を復元
+    /// e(K, C0) · Π (e(C_i, L) · e(K_ρ(i), D_i))^{−ω_i}
+    ///   = e(Q, P)^{αs} e(W, P)^{ts} · Π e(W, P)^{−tλ_i ω_i} = e(Q, P)^{αs}
+    /// rowsは係数が0でない行の (ω_i, C_i, D_i, K_ρ(i))
+    pub fn decrypt_lsss(key: &ECP2, l: &ECP, c0: &ECP, rows: &[(BIG, ECP2, ECP, &ECP2)]) -> FP12 {
+        let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
+        
+        let mut result = pairing(key, c0);
+        for (coefficient, c, d, key_component) in rows {
+            let mut term = pairing(c, l);
+            term.mul(&pairing(key_component, d));
+            result.mul(&term.pow(&BIG::modneg(coefficient, &curve_order)));
+        }
+        
+        result
+    }
+}
+
+/// Waters方式のLSSSの秘密鍵（属性の順に鍵コンポーネントを並べる）
+pub struct LsssKey {
+    /// K = αQ + tW
+    pub k: ECP2,
+    /// L = tP
+    pub l: ECP,
+    /// 属性ごとの K_attr = tH(attr)
+    pub components: Vec<ECP2>,
 }
 
 /// KP-ABEスキームの実装
@@ -199,7 +778,7 @@ pub struct KPABEImpl;
 
 impl KPABEImpl {
     /// ランダムなBIGを生成
-    pub fn random_big() -> BIG {
+    pub fn random_big() -> Result<BIG, RngFailure> {
         ABEImpl::random_big()
     }
 
@@ -208,25 +787,21 @@ impl KPABEImpl {
         ABEImpl::hash_attribute(attribute)
     }
 
-    /// メッセージをハッシュ化（SHA-256）
-    pub fn hash_message(data: &[u8]) -> [u8; 32] {
-        ABEImpl::hash_message(data)
-    }
-
-    /// ペアリング演算の結果をハッシュ化
-    pub fn hash_pairing_result(p: &FP12) -> [u8; 32] {
-        ABEImpl::hash_pairing_result(p)
+    /// ペアリング値と暗号化の文脈から対称鍵を導出
+    pub fn derive_key(p: &FP12, context: &[u8], payload: Payload) -> [u8; 32] {
+        ABEImpl::derive_key(p, context, payload)
     }
 
     /// Setup: マスター鍵ペアを生成
     /// CP-ABEと同じ構造を使用
-    pub fn setup() -> (BIG, ECP) {
+    pub fn setup() -> Result<(BIG, ECP), RngFailure> {
         ABEImpl::setup()
     }
 
     /// KeyGen: ポリシー（属性リスト）から秘密鍵を生成
     /// KP-ABEでは、鍵生成時にポリシーを指定します
     /// 注意: 簡易実装。実際のKP-ABEでは、各属性に対応する鍵コンポーネントを生成
+    /// CP-ABEのkey_genと同じく、鍵コンポーネントがユーザーごとに乱数化されないため結託耐性がない
     pub fn key_gen(alpha: &BIG, policy: &[String]) -> Vec<ECP2> {
         // 各属性に対応する秘密鍵コンポーネントを生成
         // 実際のKP-ABEでは、より複雑な構造が必要
@@ -247,9 +822,14 @@ impl KPABEImpl {
     /// Encrypt: 属性セットからメッセージを暗号化
     /// KP-ABEでは、暗号化時に属性セットを指定します
     /// 注意: 簡易実装。実際のKP-ABEでは、属性セットに基づいた複雑な構造が必要
-    pub fn encrypt(p_pub: &ECP, attributes: &[String], message: &[u8]) -> (ECP, Vec<u8>, Vec<ECP2>) {
+    pub fn encrypt(
+        p_pub: &ECP,
+        attributes: &[String],
+        message: &[u8],
+        context: &[u8],
+    ) -> Result<(ECP, Vec<u8>, Vec<ECP2>), RngFailure> {
         // ランダムなsを選択
-        let s = Self::random_big();
+        let s = Self::random_big()?;
         
         // C0 = sPを計算
         let p = ECP::generator();
@@ -268,44 +848,42 @@ impl KPABEImpl {
         // 簡易実装: e(P_pub, H(attr_0))^sを使用
         if let Some(first_attr) = attributes.first() {
             let h_attr = Self::hash_attribute(first_attr);
-            let pairing = pair::ate(&h_attr, p_pub);
-            let pairing_final = pair::fexp(&pairing);
+            let pairing_final = pairing(&h_attr, p_pub);
             let pairing_s = pairing_final.pow(&s);
-            let hash_key = Self::hash_pairing_result(&pairing_s);
+            let hash_key = Self::derive_key(&pairing_s, context, Payload::Gcm);
             
-            // V = M ⊕ H(e(P_pub, H(attr))^s)を計算
-            let mut v = Vec::with_capacity(message.len());
-            for (i, &byte) in message.iter().enumerate() {
-                v.push(byte ^ hash_key[i % 32]);
-            }
+            // V = AES-256-GCM(KDF(e(P_pub, H(attr))^s, context), M)を計算
+            let v = seal_payload(&hash_key, message)?;
             
-            (c0, v, c_attrs)
+            Ok((c0, v, c_attrs))
         } else {
             // 属性がない場合は、メッセージをそのまま返す（簡易実装）
-            (c0, message.to_vec(), c_attrs)
+            Ok((c0, message.to_vec(), c_attrs))
         }
     }
 
     /// Decrypt: 暗号文を復号化
     /// 注意: 簡易実装。実際のKP-ABEでは、ポリシー満足性のチェックが必要
-    pub fn decrypt(key_components: &[ECP2], c0: &ECP, v: &[u8], c_attrs: &[ECP2]) -> Vec<u8> {
+    /// AES-256-GCMの認証タグが一致しない場合や、鍵コンポーネント・属性コンポーネントがない場合はNoneを返す
+    pub fn decrypt(
+        key_components: &[ECP2],
+        c0: &ECP,
+        v: &[u8],
+        c_attrs: &[ECP2],
+        kdf: KeyDerivation,
+        payload: Payload,
+    ) -> Option<Vec<u8>> {
         // 簡易実装: 最初の鍵コンポーネントを使用
-        if let (Some(key_comp), Some(c_attr)) = (key_components.first(), c_attrs.first()) {
+        if let (Some(key_comp), Some(_)) = (key_components.first(), c_attrs.first()) {
             // e(key_comp, C0)を計算
-            let pairing = pair::ate(key_comp, c0);
-            let pairing_final = pair::fexp(&pairing);
-            let hash_key = Self::hash_pairing_result(&pairing_final);
-            
-            // M = V ⊕ H(e(key_comp, C0))を計算
-            let mut message = Vec::with_capacity(v.len());
-            for (i, &byte) in v.iter().enumerate() {
-                message.push(byte ^ hash_key[i % 32]);
-            }
+            let pairing_final = pairing(key_comp, c0);
+            let hash_key = kdf.derive(&pairing_final, payload);
             
-            message
+            // KDF(e(key_comp, C0), context)でVを復号化
+            open_payload(&hash_key, v, payload)
         } else {
-            // 鍵コンポーネントがない場合は、そのまま返す
-            v.to_vec()
+            // 鍵コンポーネント・属性コンポーネントがない場合は対称鍵を導出できず、Vを認証できないため復号しない
+            None
         }
     }
 }
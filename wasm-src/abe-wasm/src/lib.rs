@@ -1,7 +1,26 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use wasm_bindgen::prelude::*;
+use zeroize::{Zeroize, Zeroizing};
 
 mod abe_impl;
-use abe_impl::{ABEImpl, KPABEImpl};
+#[path = "../../shared/pairing/events.rs"]
+mod events;
+#[path = "../../shared/pairing/hybrid.rs"]
+mod hybrid;
+mod lsss;
+mod policy;
+mod signed_params;
+#[cfg(feature = "test-rng")]
+#[path = "../../shared/pairing/test_rng.rs"]
+mod test_rng;
+use abe_impl::{
+    open_payload, seal_payload, ABEImpl, AttributeHash, KPABEImpl, KeyDerivation, LsssKey, Payload, RngFailure, WasmRAND,
+    ATTRIBUTE_HASH_VERSION, LEGACY_ATTRIBUTE_HASH_VERSION,
+};
+use events::{emit_event, muted};
+use lsss::LsssMatrix;
+use policy::{parse_valid_until, Policy, VALID_UNTIL_PREFIX};
 
 // wasm-bindgenの初期化
 #[wasm_bindgen(start)]
@@ -10,14 +29,840 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
-// 基本的なテスト関数
+/// クレートのバージョンを取得
 #[wasm_bindgen]
-pub fn add(a: u32, b: u32) -> u32 {
-    a + b
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+/// ビルドに含まれている機能（スキームと曲線）を取得
+#[wasm_bindgen]
+pub fn features() -> Result<JsValue, JsValue> {
+    let schemes = js_sys::Array::new();
+    schemes.push(&"CP-ABE".into());
+    schemes.push(&"KP-ABE".into());
+    
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"schemes".into(), &schemes)?;
+    js_sys::Reflect::set(&result, &"curve".into(), &"BN254".into())?;
+    
+    Ok(result.into())
+}
+
+/// これまでに計算したペアリングの回数を取得
+/// 性能測定用。復号の前段の確認で打ち切られた場合にペアリングが行われていないことの確認などに使う
+#[wasm_bindgen]
+pub fn pairing_count() -> u64 {
+    abe_impl::pairing_count()
+}
+
+/// これまでに属性ハッシュのキャッシュから取得できた回数を取得
+/// 性能測定用。同じ属性で鍵生成・暗号化を繰り返した場合にハッシュ計算が省かれていることの確認などに使う
+#[wasm_bindgen]
+pub fn attribute_cache_hits() -> u64 {
+    abe_impl::attribute_cache_hits()
+}
+
+/// 乱数生成の失敗をJSのエラーに変換し、rng_failedイベントを通知
+impl From<RngFailure> for JsValue {
+    fn from(_: RngFailure) -> JsValue {
+        let message = "乱数の生成に失敗しました: エントロピー源が利用できないため、鍵・暗号文を生成できません";
+        emit_event("rng_failed", message);
+        JsValue::from_str(message)
+    }
+}
+
+/// ハイブリッド暗号化のエラーメッセージ
+impl std::fmt::Display for hybrid::HybridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            hybrid::HybridError::InvalidPublicKeySize { expected, actual } => {
+                write!(f, "Kyberの公開鍵のサイズが不正です（期待値: {}、実際: {}）", expected, actual)
+            }
+            hybrid::HybridError::InvalidPrivateKeySize { expected, actual } => {
+                write!(f, "Kyberの秘密鍵のサイズが不正です（期待値: {}、実際: {}）", expected, actual)
+            }
+            hybrid::HybridError::Empty => write!(f, "ハイブリッド暗号文が空です"),
+            hybrid::HybridError::UnsupportedVersion { expected, actual } => {
+                write!(f, "未対応のハイブリッド暗号文のバージョンです（期待値: {}、実際: {}）", expected, actual)
+            }
+            hybrid::HybridError::TooShort => write!(f, "ハイブリッド暗号文が短すぎます"),
+            hybrid::HybridError::AuthenticationFailed => write!(
+                f,
+                "ハイブリッド暗号文の認証に失敗しました（ABEの秘密鍵と対応するKyberの秘密鍵の両方が必要です）"
+            ),
+        }
+    }
+}
+
+/// テスト専用: WasmRANDの乱数生成モードを切り替える
+/// test-rng featureを有効にしたビルドでのみ公開される（本番ビルドには含まれない）
+/// modeは "os"（通常）、"zero_bytes"（すべて0）、"fail"（取得失敗）、"deterministic"（seedから決定的に生成）のいずれか
+#[cfg(feature = "test-rng")]
+#[wasm_bindgen]
+pub fn set_test_rng_mode(mode: &str, seed: &[u8]) -> Result<(), JsValue> {
+    use test_rng::TestRngMode;
+    
+    let mode = match mode {
+        "os" => TestRngMode::Os,
+        "zero_bytes" => TestRngMode::ZeroBytes,
+        "fail" => TestRngMode::Fail,
+        "deterministic" => TestRngMode::Deterministic {
+            seed: seed.to_vec(),
+            counter: 0,
+        },
+        _ => return Err(JsValue::from_str(&format!("不明な乱数生成モードです: {}", mode))),
+    };
+    test_rng::set_mode(mode);
+    Ok(())
+}
+
+// 動作確認で暗号化する固定メッセージと属性
+const HEALTH_CHECK_MESSAGE: &[u8] = b"abe-wasm health check";
+const HEALTH_CHECK_ATTRIBUTE: &str = "health_check";
+
+/// モジュールが正しく動作しているかを確認
+/// CP-ABEとKP-ABEそれぞれで鍵生成→暗号化→復号の往復を行い、
+/// 改ざんした暗号文が元のメッセージに復号されないことも確認する
+#[wasm_bindgen]
+pub fn health_check() -> bool {
+    run_health_check(false) && !run_health_check(true)
+}
+
+/// 暗号論的に安全な乱数のバイト列をnバイト生成
+/// 鍵生成と同じエントロピー源（WasmRAND）を使用し、取得に失敗した場合はエラーを返す
+/// nonceやsaltなど、アプリケーション側で必要な乱数に使える（Math.randomの代わり）
+#[wasm_bindgen]
+pub fn random_bytes(n: usize) -> Result<Vec<u8>, JsValue> {
+    let mut rng = WasmRAND::new();
+    let mut bytes = vec![0u8; n];
+    rand_core::RngCore::fill_bytes(&mut rng, &mut bytes);
+    if rng.failed() {
+        return Err(RngFailure.into());
+    }
+    Ok(bytes)
+}
+
+/// 固定入力で往復を実行し、元のメッセージが復元できたかを返す
+/// corruptがtrueの場合は復号前に暗号文を1ビット反転させる
+fn run_health_check(corrupt: bool) -> bool {
+    let attributes = vec![HEALTH_CHECK_ATTRIBUTE.to_string()];
+    
+    // CP-ABE（乱数生成に失敗した場合は動作していないものとして扱う）
+    let Ok((alpha, p_pub)) = ABEImpl::setup() else {
+        return false;
+    };
+    let key_components = ABEImpl::key_gen(&alpha, &attributes);
+    let context = kdf_context(SCHEME_TAG_CP_ABE, 0, &attributes);
+    let Ok((c0, mut v, c_attrs)) = ABEImpl::encrypt(&p_pub, &attributes, HEALTH_CHECK_MESSAGE, &context) else {
+        return false;
+    };
+    if corrupt {
+        v[0] ^= 0x01;
+    }
+    let cp_ok = ABEImpl::decrypt(&key_components, &c0, &v, &c_attrs, KeyDerivation::Context(&context), Payload::Gcm)
+        .as_deref()
+        == Some(HEALTH_CHECK_MESSAGE);
+    
+    // KP-ABE
+    let Ok((alpha, p_pub)) = KPABEImpl::setup() else {
+        return false;
+    };
+    let key_components = KPABEImpl::key_gen(&alpha, &attributes);
+    let context = kdf_context(SCHEME_TAG_KP_ABE, 0, &attributes);
+    let Ok((c0, mut v, c_attrs)) = KPABEImpl::encrypt(&p_pub, &attributes, HEALTH_CHECK_MESSAGE, &context) else {
+        return false;
+    };
+    if corrupt {
+        v[0] ^= 0x01;
+    }
+    let kp_ok = KPABEImpl::decrypt(&key_components, &c0, &v, &c_attrs, KeyDerivation::Context(&context), Payload::Gcm)
+        .as_deref()
+        == Some(HEALTH_CHECK_MESSAGE);
+    
+    cp_ok && kp_ok
+}
+
+// 暗号文・秘密鍵の先頭に付与するスキーム識別タグ
+// CP-ABEとKP-ABEはバイト列の構造が同じため、タグで取り違えを検出する
+const SCHEME_TAG_CP_ABE: u8 = 0x01;
+const SCHEME_TAG_KP_ABE: u8 = 0x02;
+// 発行元（権限者）の公開パラメータに束縛したCP-ABE暗号文（秘密鍵はCP-ABEのものを使う）
+const SCHEME_TAG_CP_ABE_BOUND: u8 = 0x04;
+// 属性コンポーネントごとに属性名を保存したCP-ABE暗号文（秘密鍵はCP-ABEのものを使う）
+// 属性名で秘密鍵の属性と照合できるため、暗号文が必要とする以上の属性を持つ鍵でも復号できる
+const SCHEME_TAG_CP_ABE_LABELED: u8 = 0x06;
+// ブール式のアクセスポリシー（LSSS行列）のCP-ABE暗号文（Waters方式、秘密鍵はCP-ABEのもののLSSS鍵を使う）
+// encrypt_thresholdのしきい値（k-of-n）ポリシーもこの形式で暗号化する
+const SCHEME_TAG_CP_ABE_LSSS: u8 = 0x07;
+// 廃止した形式のタグ（しきい値 0x03、LSSS 0x05）。属性ごとのシェアを鍵コンポーネントαH(attr)だけで
+// 外せたため結託耐性がなかった。復号はINCOMPATIBLE_FORMATで拒否し、番号は再利用しない
+const RETIRED_SCHEME_TAGS: [u8; 2] = [0x03, 0x05];
+
+// 圧縮形式の秘密鍵を示すフラグ（スキーム識別タグに論理和で付与する）
+// 圧縮形式の鍵は保存用で、復号に使う前にdecompressで展開する
+const COMPACT_KEY_FLAG: u8 = 0x80;
+
+// 平文のハッシュ（SHA-256）を含む暗号文を示すフラグ（暗号文のスキーム識別タグに論理和で付与する）
+// Vの対象は M || SHA-256(M) で、復号後にハッシュを照合して誤った平文の復元を検出する
+// 現在はAES-256-GCMの認証タグで検出するため付与しない。フラグのない暗号文はハッシュを照合せずに復号する
+const PLAINTEXT_HASH_FLAG: u8 = 0x40;
+
+// VをAES-256-GCMで暗号化した暗号文を示すフラグ（暗号文のスキーム識別タグに論理和で付与する）
+// V = nonce (12バイト) || 暗号文 || 認証タグ (16バイト)
+// フラグのない暗号文（以前の形式）は、Vを対称鍵とのXORとして復号する
+const GCM_PAYLOAD_FLAG: u8 = 0x20;
+
+// AES-256-GCMの認証タグが一致しなかった場合のエラーメッセージ
+const PAYLOAD_AUTHENTICATION_FAILED: &str = "暗号文の認証タグが一致しません（暗号文が改ざんされているか、鍵が一致しません）";
+
+// 平文のハッシュのサイズ（SHA-256）
+const PLAINTEXT_HASH_SIZE: usize = 32;
+
+// G2の点の直列化サイズ（非圧縮・圧縮）
+const G2_SIZE: usize = 130;
+const G2_COMPRESSED_SIZE: usize = 65;
+
+/// スキーム識別タグを表示用の名前に変換
+fn scheme_name(tag: u8) -> &'static str {
+    match tag {
+        SCHEME_TAG_CP_ABE => "CP-ABE",
+        SCHEME_TAG_KP_ABE => "KP-ABE",
+        SCHEME_TAG_CP_ABE_BOUND => "CP-ABE（発行元束縛）",
+        SCHEME_TAG_CP_ABE_LSSS => "CP-ABE（LSSS）",
+        SCHEME_TAG_CP_ABE_LABELED => "CP-ABE（属性ラベル付き）",
+        _ => "不明なスキーム",
+    }
+}
+
+/// 復号失敗の分類
+/// JSのErrorオブジェクトのcodeプロパティに設定し、UIで原因ごとの表示ができるようにする
+#[derive(Clone, Copy)]
+enum DecryptErrorCode {
+    /// 秘密鍵の属性数が暗号文の属性数に満たない
+    CountMismatch,
+    /// 秘密鍵の属性がポリシー（時間制限を含む）を満たさない
+    PolicyNotSatisfied,
+    /// 暗号文または秘密鍵のバイト列が壊れている
+    Corrupt,
+    /// スキームまたは属性ハッシュのバージョンが一致しない
+    IncompatibleFormat,
+    /// 暗号文の発行元（権限者）と秘密鍵の発行元が一致しない
+    AuthorityMismatch,
+}
+
+impl DecryptErrorCode {
+    fn as_str(self) -> &'static str {
+        match self {
+            DecryptErrorCode::CountMismatch => "COUNT_MISMATCH",
+            DecryptErrorCode::PolicyNotSatisfied => "POLICY_NOT_SATISFIED",
+            DecryptErrorCode::Corrupt => "CORRUPT",
+            DecryptErrorCode::IncompatibleFormat => "INCOMPATIBLE_FORMAT",
+            DecryptErrorCode::AuthorityMismatch => "AUTHORITY_MISMATCH",
+        }
+    }
+}
+
+/// codeプロパティ付きの復号エラーを生成し、decrypt_failedイベントを通知
+fn decrypt_error(code: DecryptErrorCode, message: &str) -> JsValue {
+    emit_event("decrypt_failed", &format!("{}: {}", code.as_str(), message));
+    coded_error(code, message)
+}
+
+/// codeプロパティ付きのエラーを生成（イベントは通知しない）
+fn coded_error(code: DecryptErrorCode, message: &str) -> JsValue {
+    let error = js_sys::Error::new(message);
+    // Errorオブジェクトへのプロパティ設定は失敗しないため、結果は無視する
+    let _ = js_sys::Reflect::set(&error, &"code".into(), &code.as_str().into());
+    error.into()
+}
+
+// 暗号文・秘密鍵のヘッダサイズ（scheme (1バイト) || hash_version (1バイト)）
+const HEADER_SIZE: usize = 2;
+
+// 暗号文に含められる属性数の上限（num_attrsは1バイトで保存する）
+const MAX_ATTRIBUTES: usize = u8::MAX as usize;
+
+/// 暗号文の属性数が0でないか確認
+/// 暗号化側は属性のない暗号文を作らないため、0は壊れた（または改ざんされた）暗号文として扱う
+/// 属性がないと鍵コンポーネントを使わずにVがそのまま返されるため、復号前に必ず拒否する
+fn check_ciphertext_num_attrs(num_attrs: usize) -> Result<(), JsValue> {
+    if num_attrs == 0 {
+        return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文の属性数が0です"));
+    }
+    Ok(())
+}
+
+/// 暗号化する属性数が上限以下か確認
+fn check_max_attributes(num_attrs: usize) -> Result<(), JsValue> {
+    if num_attrs > MAX_ATTRIBUTES {
+        return Err(JsValue::from_str(&format!(
+            "属性が多すぎます（最大{}個、指定は{}個）",
+            MAX_ATTRIBUTES, num_attrs
+        )));
+    }
+    Ok(())
+}
+
+/// CP-ABEのポリシー文字列から属性を抽出（簡易実装: カンマ区切り）
+fn policy_attributes(policy: &str) -> Result<Vec<String>, JsValue> {
+    let attributes: Vec<String> = policy
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    
+    if attributes.is_empty() {
+        return Err(JsValue::from_str("ポリシーには少なくとも1つの属性が必要です"));
+    }
+    
+    Ok(attributes)
+}
+
+/// base64url形式の暗号文をバイト列に戻す
+/// 改行などの空白や末尾のパディング（=）は取り除いてから復号する
+fn decode_ciphertext_string(encoded: &str) -> Result<Vec<u8>, JsValue> {
+    let compact: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+    URL_SAFE_NO_PAD.decode(compact.trim_end_matches('=')).map_err(|e| {
+        decrypt_error(DecryptErrorCode::Corrupt, &format!("暗号文のbase64urlが不正です: {}", e))
+    })
+}
+
+/// to_base64で保存した鍵の文字列をバイト列に戻す
+/// 暗号文の文字列と同じく、空白や末尾のパディング（=）は取り除いてから復号する
+fn decode_key_string(what: &str, encoded: &str) -> Result<Vec<u8>, JsValue> {
+    let compact: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+    URL_SAFE_NO_PAD
+        .decode(compact.trim_end_matches('='))
+        .map_err(|e| JsValue::from_str(&format!("{}のbase64urlが不正です: {}", what, e)))
+}
+
+/// 検査付き演算（checked_*）の結果を長さとして取り出す
+/// オーバーフロー・アンダーフローした場合は壊れた入力として扱う
+fn checked_length(length: Option<usize>) -> Result<usize, JsValue> {
+    length.ok_or_else(|| decrypt_error(DecryptErrorCode::Corrupt, "暗号文または秘密鍵の長さが不正です"))
+}
+
+/// 暗号文・秘密鍵のヘッダを生成
+fn header(scheme: u8) -> Vec<u8> {
+    vec![scheme, ATTRIBUTE_HASH_VERSION]
+}
+
+/// VをAES-256-GCMで暗号化した暗号文のヘッダを生成
+fn sealed_header(scheme: u8) -> Vec<u8> {
+    header(scheme | GCM_PAYLOAD_FLAG)
+}
+
+/// 現在の形式（ヘッダ付き）の暗号文かどうか（平文のハッシュ・Vの暗号化方式・属性ハッシュのバージョンは問わない）
+/// 旧形式の2バイト目はC0の先頭バイト（0x02〜0x04）のため、対応する属性ハッシュバージョンと一致することはない
+fn is_current_format(ciphertext: &[u8], scheme: u8) -> bool {
+    ciphertext.len() >= HEADER_SIZE
+        && ciphertext[0] & !(PLAINTEXT_HASH_FLAG | GCM_PAYLOAD_FLAG) == scheme
+        && AttributeHash::from_version(ciphertext[1]).is_some()
+}
+
+/// 廃止した形式（RETIRED_SCHEME_TAGS）の暗号文か
+fn is_retired_format(ciphertext: &[u8]) -> bool {
+    RETIRED_SCHEME_TAGS.iter().any(|&scheme| is_current_format(ciphertext, scheme))
+}
+
+/// 廃止した形式の暗号文の復号・移行を拒否するエラー
+fn retired_format_error() -> JsValue {
+    decrypt_error(
+        DecryptErrorCode::IncompatibleFormat,
+        "結託耐性のない廃止した形式（しきい値・LSSS）の暗号文は復号できません。現在の形式で暗号化し直してください",
+    )
+}
+
+/// 暗号文のスキーム識別タグから平文のハッシュとAES-256-GCMのフラグを外し、
+/// 平文のハッシュのフラグが付いていたかとVの暗号化方式を返す
+fn strip_payload_flags(ciphertext: &[u8]) -> (Vec<u8>, bool, Payload) {
+    let mut unflagged = ciphertext.to_vec();
+    let Some(scheme) = unflagged.first_mut() else {
+        return (unflagged, false, Payload::Xor);
+    };
+    let hashed = *scheme & PLAINTEXT_HASH_FLAG != 0;
+    let payload = if *scheme & GCM_PAYLOAD_FLAG != 0 { Payload::Gcm } else { Payload::Xor };
+    *scheme &= !(PLAINTEXT_HASH_FLAG | GCM_PAYLOAD_FLAG);
+    (unflagged, hashed, payload)
+}
+
+/// 復号時に受け付ける暗号文の形式
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DecryptMode {
+    /// AES-256-GCMの暗号文のみ（decrypt・try_decryptなど）
+    Authenticated,
+    /// AES-256-GCMのフラグのない以前の形式も受け付ける（upgrade_ciphertextでの移行専用）
+    Migration,
+    /// ヘッダなしの旧形式を並べ替えた暗号文で、文脈を含まない旧方式で対称鍵を導出する（upgrade_ciphertextでの移行専用）
+    Headerless,
+}
+
+impl DecryptMode {
+    /// 暗号文のVの暗号化方式を、この形式で復号してよいか確認する
+    /// GCMのフラグ自体は認証されないため、通常の復号ではフラグのない（完全性の保護がない）暗号文を拒否する
+    fn check_payload(self, payload: Payload) -> Result<(), JsValue> {
+        if payload == Payload::Xor && self == DecryptMode::Authenticated {
+            return Err(decrypt_error(
+                DecryptErrorCode::IncompatibleFormat,
+                "AES-256-GCMの導入前の認証されていない形式の暗号文は復号できません。upgrade_ciphertextで現在の形式に移行してください",
+            ));
+        }
+        Ok(())
+    }
+
+    /// 対称鍵の導出方式
+    fn key_derivation(self, context: &[u8]) -> KeyDerivation<'_> {
+        match self {
+            DecryptMode::Headerless => KeyDerivation::Legacy,
+            DecryptMode::Authenticated | DecryptMode::Migration => KeyDerivation::Context(context),
+        }
+    }
+}
+
+/// Vの復号結果を取り出す（AES-256-GCMの認証タグが一致しなかった場合はCORRUPTのエラー）
+fn authenticated(message: Option<Vec<u8>>) -> Result<Vec<u8>, JsValue> {
+    message.ok_or_else(|| decrypt_error(DecryptErrorCode::Corrupt, PAYLOAD_AUTHENTICATION_FAILED))
+}
+
+/// 復号した M || SHA-256(M) のハッシュを照合し、平文を取り出す
+/// 一致しない場合は、暗号文が改ざんされているか、鍵が一致せずに誤った平文が復元されたものとして拒否する
+fn verify_plaintext_hash(mut decrypted: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+    use sha2::{Digest, Sha256};
+    
+    let Some(message_len) = decrypted.len().checked_sub(PLAINTEXT_HASH_SIZE) else {
+        return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文に平文のハッシュがありません"));
+    };
+    let hash = decrypted.split_off(message_len);
+    if !hybrid::constant_time_eq(&Sha256::digest(&decrypted), &hash) {
+        return Err(decrypt_error(
+            DecryptErrorCode::Corrupt,
+            "復号した平文がハッシュと一致しません（暗号文が改ざんされているか、鍵が一致しません）",
+        ));
+    }
+    Ok(decrypted)
+}
+
+/// ヘッダのスキーム識別タグが期待値と一致し、属性ハッシュバージョンに対応しているか確認し、属性ハッシュの方式を返す
+fn check_header(target: &str, bytes: &[u8], expected_scheme: u8) -> Result<AttributeHash, JsValue> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(decrypt_error(
+            DecryptErrorCode::Corrupt,
+            &format!("{}のヘッダがありません", target),
+        ));
+    }
+    
+    let scheme = bytes[0];
+    if scheme & COMPACT_KEY_FLAG != 0 {
+        return Err(decrypt_error(DecryptErrorCode::IncompatibleFormat, &format!(
+            "{}は圧縮形式です。decompressで展開してから使用してください",
+            target
+        )));
+    }
+    if scheme != expected_scheme {
+        return Err(decrypt_error(DecryptErrorCode::IncompatibleFormat, &format!(
+            "スキームが一致しません（wrong scheme）: {}は{}用ですが、{}で復号しようとしました",
+            target,
+            scheme_name(scheme),
+            scheme_name(expected_scheme)
+        )));
+    }
+    
+    // 対応していない属性のハッシュ方式では、復号結果が無意味なデータになるため拒否する
+    let hash_version = bytes[1];
+    AttributeHash::from_version(hash_version).ok_or_else(|| {
+        decrypt_error(DecryptErrorCode::IncompatibleFormat, &format!(
+            "属性ハッシュのバージョンに対応していません: {}はバージョン{}ですが、現在の実装はバージョン{}（旧方式のバージョン{}は復号のみ）に対応しています",
+            target,
+            hash_version,
+            ATTRIBUTE_HASH_VERSION,
+            LEGACY_ATTRIBUTE_HASH_VERSION
+        ))
+    })
+}
+
+/// 暗号文と秘密鍵のヘッダを確認し、両者の属性ハッシュの方式が一致する場合にその方式を返す
+/// 属性を異なる方式でハッシュした暗号文と秘密鍵の組み合わせは、ペアリングが一致せず復号できない
+fn check_headers(
+    ciphertext: &[u8],
+    ciphertext_scheme: u8,
+    private_key: &[u8],
+    key_scheme: u8,
+) -> Result<AttributeHash, JsValue> {
+    let hash = check_header("暗号文", ciphertext, ciphertext_scheme)?;
+    let key_hash = check_header("秘密鍵", private_key, key_scheme)?;
+    if hash != key_hash {
+        return Err(decrypt_error(DecryptErrorCode::IncompatibleFormat, &format!(
+            "属性ハッシュのバージョンが一致しません: 暗号文はバージョン{}ですが、秘密鍵はバージョン{}です（旧方式の秘密鍵は再発行が必要です）",
+            hash.version(),
+            key_hash.version()
+        )));
+    }
+    Ok(hash)
+}
+
+// ペアリングの値（GTの元、FP12）の直列化サイズ
+const GT_SIZE: usize = 384;
+
+// 属性の鍵保有証明のサイズ（A (GTの元) || Z (G2の点)）
+const ATTRIBUTE_PROOF_SIZE: usize = GT_SIZE + 130;
+
+// CP-ABE暗号文の時間制限フィールドのサイズ（UNIX時刻、ビッグエンディアン、0は時間制限なし）
+const VALID_UNTIL_SIZE: usize = 8;
+
+/// 属性リストから時間制限（valid_until:<UNIX時刻>）を取り出す
+/// 時間制限がない場合は0を返す
+fn extract_valid_until(attributes: &[String]) -> Result<u64, JsValue> {
+    let mut valid_until = 0;
+    for attr in attributes {
+        if let Some(timestamp) = parse_valid_until(attr).map_err(|e| JsValue::from_str(&e))? {
+            if timestamp == 0 {
+                return Err(JsValue::from_str("時間制限の時刻は1以上である必要があります"));
+            }
+            if valid_until != 0 {
+                return Err(JsValue::from_str("時間制限属性はポリシーに1つだけ指定できます"));
+            }
+            valid_until = timestamp;
+        }
+    }
+    Ok(valid_until)
+}
+
+/// 対称鍵の導出に使う文脈を生成
+/// scheme (1バイト) || valid_until (8バイト) || 先頭の属性（対称鍵を決める属性）
+/// 時間制限を文脈に含めるため、暗号文のvalid_untilを書き換えると正しく復号できなくなる
+/// KP-ABEには時間制限がないため、valid_untilには0を指定する
+fn kdf_context(scheme: u8, valid_until: u64, attributes: &[String]) -> Vec<u8> {
+    let mut context = vec![scheme];
+    context.extend_from_slice(&valid_until.to_be_bytes());
+    if let Some(first) = attributes.first() {
+        context.extend_from_slice(first.as_bytes());
+    }
+    context
+}
+
+/// 発行元に束縛した暗号文で対称鍵の導出に使う文脈
+/// kdf_context (scheme = SCHEME_TAG_CP_ABE_BOUND) || 発行元ID (32バイト)
+/// 発行元IDを文脈に含めるため、暗号文の発行元IDを書き換えると正しく復号できなくなる
+fn bound_kdf_context(valid_until: u64, attributes: &[String], authority: &[u8]) -> Vec<u8> {
+    let mut context = kdf_context(SCHEME_TAG_CP_ABE_BOUND, valid_until, attributes);
+    context.extend_from_slice(authority);
+    context
+}
+
+/// しきい値暗号化の属性リストとしきい値を確認
+/// 属性は1〜255個・重複なし・属性名は1〜255バイトで、時間制限属性は指定できない
+fn check_threshold_attributes(attributes: &[String], k: usize) -> Result<(), JsValue> {
+    if attributes.is_empty() {
+        return Err(JsValue::from_str("しきい値ポリシーには少なくとも1つの属性が必要です"));
+    }
+    check_max_attributes(attributes.len())?;
+    if k == 0 || k > attributes.len() {
+        return Err(JsValue::from_str(&format!(
+            "しきい値が不正です: 1以上{}以下を指定してください（指定は{}）",
+            attributes.len(),
+            k
+        )));
+    }
+    
+    for (index, attr) in attributes.iter().enumerate() {
+        if attr.is_empty() || attr.len() > u8::MAX as usize {
+            return Err(JsValue::from_str(&format!(
+                "属性名は1〜{}バイトで指定してください: 「{}」",
+                u8::MAX,
+                attr
+            )));
+        }
+        if parse_valid_until(attr).map_err(|e| JsValue::from_str(&e))?.is_some() {
+            return Err(JsValue::from_str("しきい値ポリシーには時間制限属性を指定できません"));
+        }
+        if attributes[..index].contains(attr) {
+            return Err(JsValue::from_str(&format!("属性が重複しています: 「{}」", attr)));
+        }
+    }
+    Ok(())
+}
+
+/// 属性ラベル付き暗号文の属性ごとのコンポーネント
+struct LabeledComponent<'a> {
+    attribute: &'a str,
+    c_attr: &'a [u8],
+}
+
+/// 解析した属性ラベル付き暗号文
+struct LabeledCiphertext<'a> {
+    valid_until: u64,
+    c0: &'a [u8],
+    components: Vec<LabeledComponent<'a>>,
+    v: &'a [u8],
+}
+
+/// 属性ラベル付き暗号文を解析（ヘッダは呼び出し側で確認済み）
+fn parse_labeled_ciphertext(ciphertext: &[u8]) -> Result<LabeledCiphertext<'_>, JsValue> {
+    let too_short = || decrypt_error(DecryptErrorCode::Corrupt, "暗号文が短すぎます");
+    
+    let rest = &ciphertext[HEADER_SIZE..];
+    let (&num_attrs, rest) = rest.split_first().ok_or_else(too_short)?;
+    check_ciphertext_num_attrs(num_attrs as usize)?;
+    let (valid_until, rest) = rest.split_first_chunk::<VALID_UNTIL_SIZE>().ok_or_else(too_short)?;
+    let (c0, mut rest) = rest.split_at_checked(65).ok_or_else(too_short)?;
+    
+    let mut components = Vec::with_capacity(num_attrs as usize);
+    for _ in 0..num_attrs {
+        let (&len, after_len) = rest.split_first().ok_or_else(too_short)?;
+        let (attribute, after_attr) = after_len.split_at_checked(len as usize).ok_or_else(too_short)?;
+        let (c_attr, after_component) = after_attr.split_at_checked(G2_SIZE).ok_or_else(too_short)?;
+        let attribute = std::str::from_utf8(attribute)
+            .map_err(|_| decrypt_error(DecryptErrorCode::Corrupt, "暗号文の属性名が不正です"))?;
+        components.push(LabeledComponent { attribute, c_attr });
+        rest = after_component;
+    }
+    if rest.is_empty() {
+        return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文のVコンポーネントが空または不正です"));
+    }
+    
+    Ok(LabeledCiphertext {
+        valid_until: u64::from_be_bytes(*valid_until),
+        c0,
+        components,
+        v: rest,
+    })
+}
+
+/// LSSSの暗号文で、復元した e(Q, P_pub)^s からメッセージの対称鍵を導出する文脈（scheme (1バイト) || 直列化した行列）
+/// 行列や行の暗号文コンポーネントを書き換えた暗号文は正しく復号できなくなる
+fn lsss_context(matrix: &[u8]) -> Vec<u8> {
+    [&[SCHEME_TAG_CP_ABE_LSSS], matrix].concat()
+}
+
+/// 暗号化時に復号に必要な属性のヒントとして返す属性名（ポリシーの葉）
+/// ブール式のポリシーは葉を出現順に、カンマ区切りのポリシーは属性をそのまま並べる
+fn hint_attributes(policy: &str) -> Result<Vec<String>, JsValue> {
+    if Policy::has_operators(policy) {
+        return Policy::parse(policy).map(|policy| policy.leaves()).map_err(|e| JsValue::from_str(&e));
+    }
+    policy_attributes(policy)
+}
+
+/// 発行元に束縛した暗号文の発行元IDを秘密鍵の発行元と照合し、発行元IDとCP-ABE暗号文の形式に戻したバイト列を返す
+/// 曲線演算の前に照合するため、別の権限者の鍵での復号の試行はペアリングなしで打ち切れる
+fn unbind_authority<'a>(private_key: &ABEPrivateKey, ciphertext: &'a [u8]) -> Result<(&'a [u8], Vec<u8>), JsValue> {
+    check_header("暗号文", ciphertext, SCHEME_TAG_CP_ABE_BOUND)?;
+    let (authority, rest) = ciphertext[HEADER_SIZE..]
+        .split_at_checked(AUTHORITY_ID_SIZE)
+        .ok_or_else(|| decrypt_error(DecryptErrorCode::Corrupt, "暗号文が短すぎます"))?;
+    
+    if private_key.authority.is_empty() {
+        return Err(decrypt_error(
+            DecryptErrorCode::AuthorityMismatch,
+            "秘密鍵の発行元が不明です。set_authorityで発行元の公開パラメータを設定してください",
+        ));
+    }
+    if private_key.authority != authority {
+        return Err(decrypt_error(
+            DecryptErrorCode::AuthorityMismatch,
+            "発行元が一致しません: 暗号文は秘密鍵とは別の権限者の公開パラメータで暗号化されています",
+        ));
+    }
+    
+    // 属性ハッシュのバージョンは暗号文のものを引き継ぐ
+    Ok((authority, [vec![SCHEME_TAG_CP_ABE, ciphertext[1]], rest.to_vec()].concat()))
+}
+
+/// 秘密鍵の属性の有効期限が切れているために復号できない場合のエラー
+fn expired_attributes_error(
+    private_key: &ABEPrivateKey,
+    expired: &[&AttributeExpiry],
+    current_time: Option<u64>,
+) -> JsValue {
+    let current_time = current_time.unwrap_or_default();
+    let descriptions: Vec<String> = expired
+        .iter()
+        .map(|expiry| format!(
+            "{}（有効期限{}、現在時刻{}）",
+            private_key.attributes.get(expiry.index).map_or("", String::as_str),
+            expiry.expires_at,
+            current_time
+        ))
+        .collect();
+    decrypt_error(DecryptErrorCode::PolicyNotSatisfied, &format!(
+        "秘密鍵の属性の有効期限が切れています: {}",
+        descriptions.join(", ")
+    ))
+}
+
+/// 暗号文の時間制限を確認
+/// 現在時刻が有効期限以前であり、秘密鍵が同じ時刻かそれ以前のvalid_until属性を持つ必要がある
+fn check_validity(
+    valid_until: u64,
+    key_attributes: &[String],
+    current_time: Option<u64>,
+) -> Result<(), JsValue> {
+    if valid_until == 0 {
+        return Ok(());
+    }
+    
+    let current_time = current_time.ok_or_else(|| {
+        decrypt_error(
+            DecryptErrorCode::PolicyNotSatisfied,
+            "時間制限付きの暗号文です。decrypt_atで現在時刻を指定してください",
+        )
+    })?;
+    
+    if current_time > valid_until {
+        return Err(decrypt_error(DecryptErrorCode::PolicyNotSatisfied, &format!(
+            "暗号文の有効期限が切れています: 有効期限{}、現在時刻{}",
+            valid_until, current_time
+        )));
+    }
+    
+    if !Policy::ValidUntil(valid_until).is_satisfied_by(key_attributes) {
+        return Err(decrypt_error(DecryptErrorCode::PolicyNotSatisfied, &format!(
+            "秘密鍵が時間制限を満たしていません: valid_until:{}以前の時間制限属性が必要です",
+            valid_until
+        )));
+    }
+    Ok(())
+}
+
+/// マスター秘密鍵のサイズ（BN254のスカラー）
+const MASTER_KEY_SIZE: usize = 32;
+
+// 公開パラメータ（非圧縮のG1の点）のサイズと先頭バイト
+const PUBLIC_PARAMS_SIZE: usize = 65;
+const UNCOMPRESSED_POINT_PREFIX: u8 = 0x04;
+
+// 発行元IDのサイズとドメイン分離用ラベル
+const AUTHORITY_ID_SIZE: usize = 32;
+const AUTHORITY_ID_LABEL: &[u8] = b"abe-wasm authority v1";
+
+/// 公開パラメータから発行元ID（SHA-256(label || 公開パラメータ)）を計算
+/// 同じマスター鍵から発行された秘密鍵と暗号文を対応付けるために使う
+fn authority_id(params: &[u8]) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+    
+    Sha256::new()
+        .chain_update(AUTHORITY_ID_LABEL)
+        .chain_update(params)
+        .finalize()
+        .to_vec()
+}
+
+/// マスター秘密鍵のスカラーから公開パラメータ（P_pub = αP）のバイト列を計算
+fn public_params_bytes(alpha: &miracl_core::bn254::big::BIG) -> Vec<u8> {
+    use miracl_core::bn254::ecp::ECP;
+    
+    let mut bytes = vec![0u8; PUBLIC_PARAMS_SIZE];
+    ECP::generator().mul(alpha).tobytes(&mut bytes, false);
+    bytes
+}
+
+/// 公開パラメータのように見えるバイト列か
+/// マスター秘密鍵と公開パラメータを取り違えた場合に、分かりやすいエラーを返すために使う
+fn looks_like_public_params(bytes: &[u8]) -> bool {
+    bytes.len() == PUBLIC_PARAMS_SIZE && bytes[0] == UNCOMPRESSED_POINT_PREFIX
+}
+
+/// マスター秘密鍵のスカラーを取り込む
+/// 0 < s < 曲線の位数 であることを、秘密の値に依存した分岐なしで検証する
+fn import_master_scalar(bytes: &[u8]) -> Result<miracl_core::bn254::big::BIG, JsValue> {
+    use miracl_core::bn254::{big::BIG, rom};
+    
+    if looks_like_public_params(bytes) {
+        return Err(JsValue::from_str(
+            "マスター鍵が不正です: 公開パラメータのように見えます（マスター鍵ではありません）",
+        ));
+    }
+    if bytes.len() != MASTER_KEY_SIZE {
+        return Err(JsValue::from_str("マスター鍵の長さが不正です"));
+    }
+    
+    let mut order = [0u8; MASTER_KEY_SIZE];
+    BIG::new_ints(&rom::CURVE_ORDER).tobytes(&mut order);
+    
+    // bytes - order を下位バイトから計算し、最終的に借りが残れば bytes < order
+    let mut borrow: u16 = 0;
+    let mut accumulated: u8 = 0;
+    for i in (0..MASTER_KEY_SIZE).rev() {
+        let diff = (bytes[i] as u16).wrapping_sub(order[i] as u16).wrapping_sub(borrow);
+        borrow = (diff >> 8) & 1;
+        accumulated |= bytes[i];
+    }
+    let nonzero = ((accumulated as u16).wrapping_neg() >> 8) & 1;
+    
+    if borrow & nonzero == 0 {
+        return Err(JsValue::from_str("マスター鍵が範囲外です（0より大きく曲線の位数未満である必要があります）"));
+    }
+    
+    Ok(BIG::frombytes(bytes))
+}
+
+/// 復元した点の検証エラーを生成し、point_rejectedイベントを通知
+/// 1つの失敗でイベントが1回だけ届くよう、decrypt_failedは通知しない
+fn rejected_point_error(reason: &str) -> JsValue {
+    emit_event("point_rejected", reason);
+    coded_error(DecryptErrorCode::Corrupt, reason)
+}
+
+/// 直列化されたG1の点を復元
+/// miracl_coreのfrombytesは曲線上にない点を無限遠点として返すため、無限遠点は常に拒否する
+/// さらに位数rの部分群の元であることを確認する（BN254のG1は余因子が1だが、G2と同じ検証を通す）
+/// strictモードでは、再直列化した結果が入力と一致しない非正規なエンコーディングも拒否する
+fn ecp_from_bytes(bytes: &[u8], strict: bool) -> Result<miracl_core::bn254::ecp::ECP, JsValue> {
+    use miracl_core::bn254::{ecp::ECP, pair};
+    
+    let point = ECP::frombytes(bytes);
+    if point.is_infinity() {
+        return Err(rejected_point_error("G1の点が不正です: 曲線上の点でないか、無限遠点です"));
+    }
+    if !pair::g1member(&point) {
+        return Err(rejected_point_error("G1の点が不正です: 位数rの部分群に含まれません"));
+    }
+    if strict {
+        let mut canonical = vec![0u8; bytes.len()];
+        point.tobytes(&mut canonical, false);
+        if canonical != bytes {
+            return Err(rejected_point_error("非正規なG1の点のエンコーディングです"));
+        }
+    }
+    Ok(point)
+}
+
+/// 直列化されたG2の点を復元
+/// 無限遠点（曲線上にない点を含む）と、余因子による小さい部分群の点は常に拒否する
+/// strictモードでは、再直列化した結果が入力と一致しない非正規なエンコーディングも拒否する
+fn ecp2_from_bytes(bytes: &[u8], strict: bool) -> Result<miracl_core::bn254::ecp2::ECP2, JsValue> {
+    use miracl_core::bn254::{ecp2::ECP2, pair};
+    
+    let point = ECP2::frombytes(bytes);
+    if point.is_infinity() {
+        return Err(rejected_point_error("G2の点が不正です: 曲線上の点でないか、無限遠点です"));
+    }
+    if !pair::g2member(&point) {
+        return Err(rejected_point_error("G2の点が不正です: 位数rの部分群に含まれません"));
+    }
+    if strict {
+        let mut canonical = vec![0u8; bytes.len()];
+        point.tobytes(&mut canonical, false);
+        if canonical != bytes {
+            return Err(rejected_point_error("非正規なG2の点のエンコーディングです"));
+        }
+    }
+    Ok(point)
+}
+
+/// 直列化されたG2の点が、位数rの部分群の有効な点か（エラーやイベントを伴わない確認用）
+fn is_valid_g2(point: &miracl_core::bn254::ecp2::ECP2) -> bool {
+    !point.is_infinity() && miracl_core::bn254::pair::g2member(point)
 }
 
 // ABE関連の型定義
+
+/// マスター秘密鍵
+/// バイト列の形式: スカラーα（0 < α < 曲線の位数）の32バイトのビッグエンディアン表現
+/// 上位の0も省略しない（例: α = 1 は 31バイトの0x00の後に0x01）
 #[wasm_bindgen]
+#[derive(Clone, Default)]
 pub struct ABEMasterKey {
     secret: Vec<u8>,
 }
@@ -31,13 +876,66 @@ impl ABEMasterKey {
         }
     }
 
+    /// 保存しておいたマスター秘密鍵を復元
+    /// 0や曲線の位数以上の値は拒否する
+    pub fn from_bytes(secret: &[u8]) -> Result<ABEMasterKey, JsValue> {
+        import_master_scalar(secret)?;
+        Ok(ABEMasterKey {
+            secret: secret.to_vec(),
+        })
+    }
+
     #[wasm_bindgen(getter)]
     pub fn secret(&self) -> Vec<u8> {
         self.secret.clone()
     }
+
+    /// マスター秘密鍵をbase64url（パディングなし）の文字列で返す
+    /// localStorageなどに保存し、from_base64で復元できる
+    pub fn to_base64(&self) -> String {
+        URL_SAFE_NO_PAD.encode(&self.secret)
+    }
+
+    /// to_base64で保存した文字列からマスター秘密鍵を復元
+    /// from_bytesと同じく、長さや値の範囲が不正な鍵は拒否する
+    pub fn from_base64(encoded: &str) -> Result<ABEMasterKey, JsValue> {
+        ABEMasterKey::from_bytes(&decode_key_string("マスター秘密鍵", encoded)?)
+    }
+
+    /// マスター秘密鍵のバイト列を取り出す（所有権を移すため、クローンしない）
+    /// 一度だけエクスポートする用途向けで、取り出した後は空になり、以降の操作ではエラーになる
+    /// 繰り返し参照する場合はgetterのsecretを使用すること
+    pub fn take_bytes(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.secret)
+    }
+
+    /// マスター秘密鍵をメモリから消去する
+    /// wasmの線形メモリはJSから読み取れるため、不要になった時点で呼び出すこと
+    /// 消去した後は空になり、以降の操作ではエラーになる（freeで解放した場合も自動で消去される）
+    pub fn zeroize(&mut self) {
+        self.secret.zeroize();
+    }
+
+    /// 同じ内容の複製を作る（JSではclone()）
+    /// 元のオブジェクトを保持したまま、Workerなどに複製を渡せる
+    /// take_bytesで取り出した後（空の状態）の複製は空になる
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> ABEMasterKey {
+        self.clone()
+    }
+}
+
+impl Drop for ABEMasterKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
+/// 公開パラメータ（P_pub = αP、PはBN254のG1の生成元 (-1, 1)）
+/// バイト列の形式: SEC1の非圧縮形式の65バイト（0x04 || x (32バイト) || y (32バイト)）
+/// x・yはFpの元の32バイトのビッグエンディアン表現
 #[wasm_bindgen]
+#[derive(Clone, Default)]
 pub struct ABEPublicParams {
     params: Vec<u8>,
 }
@@ -51,16 +949,123 @@ impl ABEPublicParams {
         }
     }
 
+    /// 保存しておいた公開パラメータを復元
+    /// 長さが不正な値や、非正規なエンコーディング・無限遠点は拒否する
+    pub fn from_bytes(params: &[u8]) -> Result<ABEPublicParams, JsValue> {
+        use miracl_core::bn254::{ecp::ECP, pair};
+        
+        if params.len() == MASTER_KEY_SIZE {
+            return Err(JsValue::from_str(
+                "公開パラメータが不正です: マスター秘密鍵のように見えます（公開パラメータではありません）",
+            ));
+        }
+        if params.len() != PUBLIC_PARAMS_SIZE {
+            return Err(JsValue::from_str("公開パラメータの長さが不正です"));
+        }
+        
+        let point = ECP::frombytes(params);
+        let mut canonical = vec![0u8; PUBLIC_PARAMS_SIZE];
+        point.tobytes(&mut canonical, false);
+        if point.is_infinity() || !pair::g1member(&point) || canonical != params {
+            return Err(JsValue::from_str("公開パラメータが有効な点ではありません"));
+        }
+        
+        Ok(ABEPublicParams {
+            params: params.to_vec(),
+        })
+    }
+
     #[wasm_bindgen(getter)]
     pub fn params(&self) -> Vec<u8> {
         self.params.clone()
     }
+
+    /// 公開パラメータをbase64url（パディングなし）の文字列で返す
+    /// localStorageなどに保存し、from_base64で復元できる
+    pub fn to_base64(&self) -> String {
+        URL_SAFE_NO_PAD.encode(&self.params)
+    }
+
+    /// to_base64で保存した文字列から公開パラメータを復元
+    /// from_bytesと同じく、長さが不正な値や有効な点でない値は拒否する
+    pub fn from_base64(encoded: &str) -> Result<ABEPublicParams, JsValue> {
+        ABEPublicParams::from_bytes(&decode_key_string("公開パラメータ", encoded)?)
+    }
+
+    /// 公開パラメータのバイト列を取り出す（所有権を移すため、クローンしない）
+    /// 一度だけエクスポートする用途向けで、取り出した後は空になり、以降の操作ではエラーになる
+    /// 繰り返し参照する場合はgetterのparamsを使用すること
+    pub fn take_bytes(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.params)
+    }
+
+    /// 同じ内容の複製を作る（JSではclone()）
+    /// 元のオブジェクトを保持したまま、Workerなどに複製を渡せる
+    /// take_bytesで取り出した後（空の状態）の複製は空になる
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> ABEPublicParams {
+        self.clone()
+    }
+}
+
+impl ABEPublicParams {
+    /// 暗号化や証明の検証に使うP_pubを復元
+    /// take_bytesで取り出した後の空の値や、位数rの部分群の有効な点でない値はエラーにする
+    fn point(&self) -> Result<miracl_core::bn254::ecp::ECP, JsValue> {
+        use miracl_core::bn254::{ecp::ECP, pair};
+        
+        if self.params.len() != PUBLIC_PARAMS_SIZE {
+            return Err(JsValue::from_str("公開パラメータの長さが不正です"));
+        }
+        let point = ECP::frombytes(&self.params);
+        if point.is_infinity() || !pair::g1member(&point) {
+            return Err(JsValue::from_str("公開パラメータが有効な点ではありません"));
+        }
+        Ok(point)
+    }
+}
+
+// 鍵識別子（KID）として使うSHA-256の先頭のバイト数（base64urlで22文字）
+const KEY_ID_SIZE: usize = 16;
+
+/// 公開パラメータから安定した鍵識別子（KID）を導出
+/// SHA-256(公開パラメータ) の先頭16バイトをbase64url（パディングなし）で表した22文字の文字列
+/// 他のモジュールのkey_idと同じ方式で、CP-ABE・KP-ABEのどちらの公開パラメータにも使える
+/// 発行元ID（authority_id）とは別の値で、発行元IDは暗号文への埋め込み用、KIDはアプリでの索引用
+#[wasm_bindgen]
+pub fn key_id(public_params: &[u8]) -> Result<String, JsValue> {
+    use sha2::{Digest, Sha256};
+    
+    ABEPublicParams::from_bytes(public_params)?;
+    let digest = Sha256::digest(public_params);
+    Ok(URL_SAFE_NO_PAD.encode(&digest[..KEY_ID_SIZE]))
+}
+
+/// 属性ごとの有効期限
+/// tagは有効期限を鍵コンポーネントに束縛するタグ（G1の点、65バイト）
+#[derive(Clone)]
+struct AttributeExpiry {
+    index: usize,
+    expires_at: u64,
+    tag: Vec<u8>,
+}
+
+/// 有効期限を確認した後の秘密鍵の属性と鍵コンポーネント
+struct ActiveKeyComponents {
+    attributes: Vec<String>,
+    components: Vec<miracl_core::bn254::ecp2::ECP2>,
 }
 
 #[wasm_bindgen]
+#[derive(Clone, Default)]
 pub struct ABEPrivateKey {
     key: Vec<u8>,
     attributes: Vec<String>,
+    expirations: Vec<AttributeExpiry>,
+    // 鍵を発行した権限者の発行元ID（不明な場合は空）
+    authority: Vec<u8>,
+    // ブール式のポリシー（LSSS）の復号に使うWaters方式の鍵（K || L || 属性ごとのK_attr、ない場合は空）
+    lsss_key: Vec<u8>,
 }
 
 #[wasm_bindgen]
@@ -70,7 +1075,39 @@ impl ABEPrivateKey {
         ABEPrivateKey {
             key: Vec::new(),
             attributes: Vec::new(),
+            expirations: Vec::new(),
+            authority: Vec::new(),
+            lsss_key: Vec::new(),
+        }
+    }
+
+    /// 保存しておいた秘密鍵を、鍵のバイト列と属性から復元
+    /// ヘッダと鍵コンポーネントの数のみを確認し、各コンポーネントが有効な点かは確認しない（describeで確認できる）
+    /// 注意: 属性の有効期限は復元されないため、有効期限付きの属性を持つ鍵の保存には使用しないこと（to_base64を使う）
+    /// 発行元も復元されないため、encrypt_boundの暗号文を復号する場合はset_authorityで設定する
+    /// LSSS鍵も復元されないため、ブール式・しきい値のポリシーの暗号文は復号できない（to_base64を使う）
+    pub fn from_bytes(key: &[u8], attributes: Vec<String>) -> Result<ABEPrivateKey, JsValue> {
+        if key.len() < HEADER_SIZE {
+            return Err(JsValue::from_str("秘密鍵のヘッダがありません"));
+        }
+        let scheme = key[0] & !COMPACT_KEY_FLAG;
+        if scheme != SCHEME_TAG_CP_ABE && scheme != SCHEME_TAG_KP_ABE {
+            return Err(JsValue::from_str("秘密鍵のスキームが不明です"));
+        }
+        
+        let private_key = ABEPrivateKey {
+            key: key.to_vec(),
+            attributes,
+            expirations: Vec::new(),
+            authority: Vec::new(),
+            lsss_key: Vec::new(),
+        };
+        let component_size = if private_key.is_compact() { G2_COMPRESSED_SIZE } else { G2_SIZE };
+        if key.len() - HEADER_SIZE != private_key.attributes.len() * component_size {
+            return Err(JsValue::from_str("秘密鍵の鍵コンポーネントの数が属性の数と一致しません"));
         }
+        
+        Ok(private_key)
     }
 
     #[wasm_bindgen(getter)]
@@ -78,35 +1115,383 @@ impl ABEPrivateKey {
         self.key.clone()
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn attributes(&self) -> Vec<String> {
-        self.attributes.clone()
+    /// 秘密鍵を、属性・有効期限・発行元を含めてbase64url（パディングなし）の文字列で返す
+    /// from_bytesと異なり、from_base64ですべてのフィールドを復元できる
+    /// 形式: version (1バイト) || 鍵の長さ (4バイト) || 鍵 || 属性数 (4バイト) || (属性名の長さ (4バイト) || UTF-8の属性名)*
+    ///       || 有効期限の数 (4バイト) || (属性の番号 (4バイト) || 有効期限 (8バイト) || タグ (65バイト))*
+    ///       || 発行元IDの長さ (1バイト) || 発行元ID || LSSS鍵の長さ (4バイト) || LSSS鍵
+    /// 長さと数値はビッグエンディアン
+    /// LSSS鍵のないバージョン1の文字列も読み込める（ブール式・しきい値のポリシーの暗号文は復号できない）
+    pub fn to_base64(&self) -> String {
+        let mut bytes = vec![PRIVATE_KEY_STRING_VERSION];
+        bytes.extend_from_slice(&(self.key.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.key);
+        bytes.extend_from_slice(&(self.attributes.len() as u32).to_be_bytes());
+        for attribute in &self.attributes {
+            bytes.extend_from_slice(&(attribute.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(attribute.as_bytes());
+        }
+        bytes.extend_from_slice(&(self.expirations.len() as u32).to_be_bytes());
+        for expiry in &self.expirations {
+            bytes.extend_from_slice(&(expiry.index as u32).to_be_bytes());
+            bytes.extend_from_slice(&expiry.expires_at.to_be_bytes());
+            bytes.extend_from_slice(&expiry.tag);
+        }
+        bytes.push(self.authority.len() as u8);
+        bytes.extend_from_slice(&self.authority);
+        bytes.extend_from_slice(&(self.lsss_key.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.lsss_key);
+        URL_SAFE_NO_PAD.encode(&bytes)
     }
-}
 
-// ABE実装（Miracl Coreを使用）
-// CP-ABE (Ciphertext-Policy Attribute-Based Encryption) スキームの実装
-#[wasm_bindgen]
-pub struct ABE {
-    // CP-ABEスキームの実装
-    // 今後、Miracl Coreのペアリング演算を使用
+    /// to_base64で保存した文字列から秘密鍵を復元
+    /// 各フィールドの長さを確認し、鍵コンポーネントの数はfrom_bytesと同じく属性の数と照合する
+    /// 有効期限のタグは復号時（decrypt_at）に鍵コンポーネントと照合される
+    pub fn from_base64(encoded: &str) -> Result<ABEPrivateKey, JsValue> {
+        let bytes = decode_key_string("秘密鍵", encoded)?;
+        let truncated = || JsValue::from_str("秘密鍵の文字列が短すぎます");
+        
+        let (&version, rest) = bytes.split_first().ok_or_else(truncated)?;
+        if version != PRIVATE_KEY_STRING_VERSION && version != LEGACY_PRIVATE_KEY_STRING_VERSION {
+            return Err(JsValue::from_str(&format!(
+                "秘密鍵の文字列のバージョン{}には対応していません",
+                version
+            )));
+        }
+        
+        let (key, rest) = split_length_prefixed(rest).ok_or_else(truncated)?;
+        let (num_attrs, mut rest) = split_u32(rest).ok_or_else(truncated)?;
+        let mut attributes = Vec::new();
+        for _ in 0..num_attrs {
+            let (attribute, after_attr) = split_length_prefixed(rest).ok_or_else(truncated)?;
+            let attribute = std::str::from_utf8(attribute)
+                .map_err(|_| JsValue::from_str("秘密鍵の属性名が不正です"))?;
+            attributes.push(attribute.to_string());
+            rest = after_attr;
+        }
+        let mut private_key = ABEPrivateKey::from_bytes(key, attributes)?;
+        
+        let (num_expirations, mut rest) = split_u32(rest).ok_or_else(truncated)?;
+        for _ in 0..num_expirations {
+            let (index, after_index) = split_u32(rest).ok_or_else(truncated)?;
+            let (expires_at, after_expiry) = after_index.split_first_chunk::<8>().ok_or_else(truncated)?;
+            let (tag, after_tag) = after_expiry.split_at_checked(EXPIRY_TAG_SIZE).ok_or_else(truncated)?;
+            if index as usize >= private_key.attributes.len() {
+                return Err(JsValue::from_str("秘密鍵の有効期限の対象となる属性がありません"));
+            }
+            private_key.expirations.push(AttributeExpiry {
+                index: index as usize,
+                expires_at: u64::from_be_bytes(*expires_at),
+                tag: tag.to_vec(),
+            });
+            rest = after_tag;
+        }
+        
+        let (&authority_len, rest) = rest.split_first().ok_or_else(truncated)?;
+        let (authority, rest) = rest.split_at_checked(authority_len as usize).ok_or_else(truncated)?;
+        if !(authority.is_empty() || authority.len() == AUTHORITY_ID_SIZE) {
+            return Err(JsValue::from_str("秘密鍵の発行元IDの長さが不正です"));
+        }
+        private_key.authority = authority.to_vec();
+        
+        let rest = if version == LEGACY_PRIVATE_KEY_STRING_VERSION {
+            rest
+        } else {
+            let (lsss_key, rest) = split_length_prefixed(rest).ok_or_else(truncated)?;
+            if !lsss_key.is_empty() && lsss_key.len() != LSSS_KEY_HEADER_SIZE + private_key.attributes.len() * G2_SIZE {
+                return Err(JsValue::from_str("秘密鍵のLSSS鍵の長さが属性の数と一致しません"));
+            }
+            private_key.lsss_key = lsss_key.to_vec();
+            rest
+        };
+        if !rest.is_empty() {
+            return Err(JsValue::from_str("秘密鍵の文字列の末尾に余分なデータがあります"));
+        }
+        
+        Ok(private_key)
+    }
+
+    /// 秘密鍵のバイト列を取り出す（所有権を移すため、クローンしない）
+    /// 一度だけエクスポートする用途向けで、取り出した後は空になり、以降の操作ではエラーになる
+    /// 繰り返し参照する場合はgetterのkeyを使用すること
+    pub fn take_bytes(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.key)
+    }
+
+    /// 直列化した鍵のサイズ（バイト数）
+    pub fn size_bytes(&self) -> usize {
+        self.key.len()
+    }
+
+    /// 圧縮形式の鍵かどうか
+    #[wasm_bindgen(getter)]
+    pub fn is_compact(&self) -> bool {
+        self.key.first().is_some_and(|scheme| scheme & COMPACT_KEY_FLAG != 0)
+    }
+
+    /// 鍵コンポーネントを圧縮形式のG2の点（各65バイト）に変換した保存用の鍵を返す
+    /// 圧縮形式の鍵は復号に使えないため、使用前にdecompressで展開する
+    pub fn compact(&self) -> Result<ABEPrivateKey, JsValue> {
+        if self.is_compact() {
+            return Ok(self.with_key(self.key.clone()));
+        }
+        self.convert_components(G2_SIZE, false)
+    }
+
+    /// compactで圧縮した鍵を復号に使える形式に展開する
+    pub fn decompress(&self) -> Result<ABEPrivateKey, JsValue> {
+        if !self.is_compact() {
+            return Ok(self.with_key(self.key.clone()));
+        }
+        self.convert_components(G2_COMPRESSED_SIZE, true)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attributes(&self) -> Vec<String> {
+        self.attributes.clone()
+    }
+
+    /// 鍵を発行した権限者の発行元ID（公開パラメータのSHA-256ベースのハッシュ、32バイト）
+    /// from_bytesで復元した鍵など、発行元が不明な場合は空
+    #[wasm_bindgen(getter)]
+    pub fn authority_id(&self) -> Vec<u8> {
+        self.authority.clone()
+    }
+
+    /// 鍵を発行した権限者の公開パラメータを設定（from_bytesで復元した鍵の発行元の復元用）
+    /// 発行元を設定した鍵だけが、encrypt_boundで同じ権限者に束縛された暗号文を復号できる
+    pub fn set_authority(&mut self, public_params: &ABEPublicParams) -> Result<(), JsValue> {
+        if public_params.params.len() != PUBLIC_PARAMS_SIZE {
+            return Err(JsValue::from_str("公開パラメータの長さが不正です"));
+        }
+        self.authority = authority_id(&public_params.params);
+        Ok(())
+    }
+
+    /// 属性の有効期限（UNIX時刻）を取得
+    /// 有効期限のない属性や、鍵に含まれない属性の場合はundefinedを返す
+    pub fn expires_at(&self, attribute: &str) -> Option<u64> {
+        self.expirations
+            .iter()
+            .find(|expiry| self.attributes.get(expiry.index).is_some_and(|a| a == attribute))
+            .map(|expiry| expiry.expires_at)
+    }
+
+    /// 鍵に含まれる属性と、対応する鍵コンポーネントのフィンガープリントの一覧を返す
+    /// 鍵が付与する権限の監査や、個々の鍵コンポーネントの破損の検出に使用する
+    /// 各要素は { attribute, fingerprint, valid, expires_at }（expires_atは有効期限付きの属性のみ）
+    pub fn describe(&self) -> Result<JsValue, JsValue> {
+        if self.key.len() < HEADER_SIZE {
+            return Err(JsValue::from_str("秘密鍵のヘッダがありません"));
+        }
+        
+        let component_size = if self.is_compact() { G2_COMPRESSED_SIZE } else { G2_SIZE };
+        let components = &self.key[HEADER_SIZE..];
+        if components.len() != self.attributes.len() * component_size {
+            return Err(JsValue::from_str("秘密鍵の鍵コンポーネントの数が属性の数と一致しません"));
+        }
+        
+        let entries = js_sys::Array::new();
+        for (attribute, component) in self.attributes.iter().zip(components.chunks_exact(component_size)) {
+            let (fingerprint, valid) = component_fingerprint(component);
+            
+            let entry = js_sys::Object::new();
+            js_sys::Reflect::set(&entry, &"attribute".into(), &attribute.into())?;
+            js_sys::Reflect::set(&entry, &"fingerprint".into(), &fingerprint.into())?;
+            js_sys::Reflect::set(&entry, &"valid".into(), &valid.into())?;
+            if let Some(expires_at) = self.expires_at(attribute) {
+                js_sys::Reflect::set(&entry, &"expires_at".into(), &JsValue::from(expires_at))?;
+            }
+            entries.push(&entry);
+        }
+        
+        Ok(entries.into())
+    }
+
+    /// 秘密鍵をメモリから消去する
+    /// wasmの線形メモリはJSから読み取れるため、不要になった時点で呼び出すこと
+    /// 消去した後は空になり、以降の操作ではエラーになる（freeで解放した場合も自動で消去される）
+    pub fn zeroize(&mut self) {
+        self.key.zeroize();
+        self.lsss_key.zeroize();
+    }
+
+    /// 同じ内容の複製を作る（JSではclone()）
+    /// 元のオブジェクトを保持したまま、Workerなどに複製を渡せる
+    /// take_bytesで取り出した後（空の状態）の複製は空になる
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> ABEPrivateKey {
+        self.clone()
+    }
+}
+
+impl Drop for ABEPrivateKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+// 秘密鍵の文字列形式（to_base64）のバージョン
+const PRIVATE_KEY_STRING_VERSION: u8 = 0x02;
+
+// LSSS鍵を含まない、以前の秘密鍵の文字列形式のバージョン（読み込みのみ対応）
+const LEGACY_PRIVATE_KEY_STRING_VERSION: u8 = 0x01;
+
+// 秘密鍵のLSSS鍵の属性ごとのコンポーネントより前の部分（K (G2の点) || L (G1の点)）のサイズ
+const LSSS_KEY_HEADER_SIZE: usize = G2_SIZE + 65;
+
+// 有効期限のタグ（G1の点）のサイズ
+const EXPIRY_TAG_SIZE: usize = 65;
+
+/// 先頭の4バイト（ビッグエンディアン）を数値として取り出し、残りと合わせて返す
+fn split_u32(bytes: &[u8]) -> Option<(u32, &[u8])> {
+    let (value, rest) = bytes.split_first_chunk::<4>()?;
+    Some((u32::from_be_bytes(*value), rest))
+}
+
+/// 長さ (4バイト) || データ の形式からデータを取り出し、残りと合わせて返す
+fn split_length_prefixed(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (len, rest) = split_u32(bytes)?;
+    rest.split_at_checked(len as usize)
+}
+
+// 鍵コンポーネントのフィンガープリントのサイズ（バイト数）
+const FINGERPRINT_SIZE: usize = 8;
+
+/// 鍵コンポーネントのフィンガープリント（SHA-256の先頭8バイトのhex）と、G2の点として有効かを返す
+/// 有効な点は圧縮形式に正規化してからハッシュするため、compact・decompressの前後で変わらない
+/// 点として復元できない（破損した）コンポーネントは、直列化されたバイト列をそのままハッシュする
+fn component_fingerprint(component: &[u8]) -> (String, bool) {
+    use miracl_core::bn254::ecp2::ECP2;
+    use sha2::{Digest, Sha256};
+    
+    let point = ECP2::frombytes(component);
+    let valid = is_valid_g2(&point);
+    let digest = if valid {
+        let mut compressed = vec![0u8; G2_COMPRESSED_SIZE];
+        point.tobytes(&mut compressed, true);
+        Sha256::digest(&compressed)
+    } else {
+        Sha256::digest(component)
+    };
+    
+    let fingerprint = digest[..FINGERPRINT_SIZE]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    (fingerprint, valid)
+}
+
+impl ABEPrivateKey {
+    /// 現在時刻の時点で有効期限が切れている属性
+    /// 有効期限付きの属性を持つ鍵では、現在時刻の指定が必要
+    fn expired_attributes(&self, current_time: Option<u64>) -> Result<Vec<&AttributeExpiry>, JsValue> {
+        if self.expirations.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let current_time = current_time.ok_or_else(|| {
+            decrypt_error(
+                DecryptErrorCode::PolicyNotSatisfied,
+                "属性に有効期限がある秘密鍵です。decrypt_atで現在時刻を指定してください",
+            )
+        })?;
+        
+        Ok(self
+            .expirations
+            .iter()
+            .filter(|expiry| current_time > expiry.expires_at)
+            .collect())
+    }
+
+    /// 属性を引き継いで鍵のバイト列だけを差し替える
+    fn with_key(&self, key: Vec<u8>) -> ABEPrivateKey {
+        ABEPrivateKey {
+            key,
+            attributes: self.attributes.clone(),
+            expirations: self.expirations.clone(),
+            authority: self.authority.clone(),
+            lsss_key: self.lsss_key.clone(),
+        }
+    }
+
+    /// 鍵コンポーネントの直列化形式を変換（圧縮 ⇔ 非圧縮）
+    /// component_sizeは変換元のコンポーネントサイズ、to_uncompressedは変換先が非圧縮形式かどうか
+    fn convert_components(&self, component_size: usize, to_uncompressed: bool) -> Result<ABEPrivateKey, JsValue> {
+        use miracl_core::bn254::ecp2::ECP2;
+        
+        if self.key.len() < HEADER_SIZE {
+            return Err(JsValue::from_str("秘密鍵のヘッダがありません"));
+        }
+        let scheme = self.key[0] & !COMPACT_KEY_FLAG;
+        if scheme != SCHEME_TAG_CP_ABE && scheme != SCHEME_TAG_KP_ABE {
+            return Err(JsValue::from_str("秘密鍵のスキームが不明です"));
+        }
+        
+        let components = &self.key[HEADER_SIZE..];
+        if !components.len().is_multiple_of(component_size) {
+            return Err(JsValue::from_str("秘密鍵の鍵コンポーネントの長さが不正です"));
+        }
+        
+        let (flag, output_size) = if to_uncompressed {
+            (0, G2_SIZE)
+        } else {
+            (COMPACT_KEY_FLAG, G2_COMPRESSED_SIZE)
+        };
+        
+        let mut key = vec![scheme | flag, self.key[1]];
+        for chunk in components.chunks_exact(component_size) {
+            let point = ECP2::frombytes(chunk);
+            if !is_valid_g2(&point) {
+                return Err(JsValue::from_str("秘密鍵に不正な鍵コンポーネントが含まれています"));
+            }
+            
+            let mut bytes = vec![0u8; output_size];
+            point.tobytes(&mut bytes, !to_uncompressed);
+            key.extend_from_slice(&bytes);
+        }
+        
+        Ok(self.with_key(key))
+    }
+}
+
+// ABE実装（Miracl Coreを使用）
+// CP-ABE (Ciphertext-Policy Attribute-Based Encryption) スキームの実装
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct ABE {
+    // CP-ABEスキームの実装
+    // 今後、Miracl Coreのペアリング演算を使用
+    // 非正規な点のエンコーディングを拒否するか
+    strict: bool,
 }
 
 #[wasm_bindgen]
 impl ABE {
     #[wasm_bindgen(constructor)]
     pub fn new() -> ABE {
-        ABE {}
+        ABE { strict: false }
+    }
+
+    /// strictモードを取得
+    #[wasm_bindgen(getter)]
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// strictモードを設定
+    /// 有効にすると、復号時に非正規な点のエンコーディングを拒否する
+    #[wasm_bindgen(setter)]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
     }
 
     /// マスター鍵ペアを生成
     /// CP-ABEスキームのSetupアルゴリズム
     #[wasm_bindgen]
     pub fn setup(&self) -> Result<JsValue, JsValue> {
-        use miracl_core::bn254::ecp::ECP;
-        
         // マスター鍵ペアを生成
-        let (alpha, p_pub) = ABEImpl::setup();
+        let (alpha, p_pub) = ABEImpl::setup()?;
         
         // マスター秘密鍵をバイト列に変換
         let mut master_key_bytes = vec![0u8; 32];
@@ -120,90 +1505,539 @@ impl ABE {
             secret: master_key_bytes,
         };
         
-        let public_params = ABEPublicParams {
-            params: public_params_bytes,
-        };
+        let public_params = ABEPublicParams {
+            params: public_params_bytes,
+        };
+        
+        // JsValueとして返す
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"master_key".into(), &master_key.into())?;
+        js_sys::Reflect::set(&result, &"public_params".into(), &public_params.into())?;
+        
+        Ok(result.into())
+    }
+
+    /// マスター秘密鍵から公開パラメータ（P_pub = αP）を計算
+    /// 保存しておいたマスター秘密鍵だけから公開パラメータを復元する場合や、
+    /// 他言語の実装とエンコーディングを突き合わせる場合に使う（KP-ABEの鍵にも使える）
+    #[wasm_bindgen]
+    pub fn derive_public_params(&self, master_key: &ABEMasterKey) -> Result<ABEPublicParams, JsValue> {
+        let alpha = import_master_scalar(&master_key.secret)?;
+        Ok(ABEPublicParams {
+            params: public_params_bytes(&alpha),
+        })
+    }
+
+    /// 権限者のDilithium（ML-DSA-65）の秘密鍵で公開パラメータに署名し、署名付き公開パラメータを作成
+    /// 中間者による公開パラメータの差し替えを防ぐため、配布時にはこの形式を使い、
+    /// 受信側はverify_public_paramsで権限者の公開鍵を使って検証する
+    /// 形式: version (1バイト) || 公開パラメータ (65バイト) || 署名
+    #[wasm_bindgen]
+    pub fn sign_public_params(
+        &self,
+        public_params: &ABEPublicParams,
+        authority_private_key: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        // 署名前に、公開パラメータが正しく復元できる値か確認する
+        ABEPublicParams::from_bytes(&public_params.params)?;
+        signed_params::check_private_key(authority_private_key).map_err(|e| JsValue::from_str(&e))?;
+        
+        Ok(signed_params::sign(&public_params.params, authority_private_key)?)
+    }
+
+    /// 署名付き公開パラメータを権限者のDilithiumの公開鍵で検証し、公開パラメータを取り出す
+    /// 署名が一致しない（改ざんされている・権限者の鍵が異なる）場合はエラーを返す
+    #[wasm_bindgen]
+    pub fn verify_public_params(
+        &self,
+        signed_params: &[u8],
+        authority_public_key: &[u8],
+    ) -> Result<ABEPublicParams, JsValue> {
+        let params = signed_params::verify(signed_params, PUBLIC_PARAMS_SIZE, authority_public_key)
+            .map_err(|e| JsValue::from_str(&e))?;
+        
+        ABEPublicParams::from_bytes(params)
+    }
+
+    /// 属性セットから秘密鍵を生成
+    /// CP-ABEスキームのKeyGenアルゴリズム
+    /// 属性ごとの鍵コンポーネントαH(attr)は決定的で結託耐性がない（カンマ区切りのポリシーの復号に使う）
+    /// ブール式・しきい値のポリシーの復号に使うLSSS鍵は呼び出しごとの乱数で生成するため、
+    /// 同じ属性でも呼び出しごとに異なり、別々に発行した鍵の属性を組み合わせても復号できない
+    #[wasm_bindgen]
+    pub fn key_gen(
+        &self,
+        master_key: &ABEMasterKey,
+        attributes: Vec<String>,
+    ) -> Result<ABEPrivateKey, JsValue> {
+        // マスター秘密鍵をBIGに変換
+        let alpha = import_master_scalar(&master_key.secret)?;
+        
+        // 秘密鍵コンポーネントを生成
+        let key_components = ABEImpl::key_gen(&alpha, &attributes);
+        
+        // 鍵コンポーネントをバイト列に変換（header (2バイト) || 鍵コンポーネントの形式）
+        let mut key_bytes = header(SCHEME_TAG_CP_ABE);
+        for key_comp in &key_components {
+            let mut comp_bytes = vec![0u8; 130];
+            key_comp.tobytes(&mut comp_bytes, false);
+            key_bytes.extend_from_slice(&comp_bytes);
+        }
+        
+        // ブール式のポリシー（LSSS）の復号に使うWaters方式の鍵（ユーザーごとの乱数で結託を防ぐ）
+        let lsss_key = ABEImpl::lsss_key_gen(&alpha, &attributes)?;
+        let mut lsss_key_bytes = vec![0u8; LSSS_KEY_HEADER_SIZE];
+        lsss_key.k.tobytes(&mut lsss_key_bytes[..G2_SIZE], false);
+        lsss_key.l.tobytes(&mut lsss_key_bytes[G2_SIZE..], false);
+        for component in &lsss_key.components {
+            let mut comp_bytes = vec![0u8; G2_SIZE];
+            component.tobytes(&mut comp_bytes, false);
+            lsss_key_bytes.extend_from_slice(&comp_bytes);
+        }
+        
+        Ok(ABEPrivateKey {
+            key: key_bytes,
+            attributes,
+            expirations: Vec::new(),
+            authority: authority_id(&public_params_bytes(&alpha)),
+            lsss_key: lsss_key_bytes,
+        })
+    }
+
+    /// 属性ごとに有効期限を付けて秘密鍵を生成
+    /// expires_atは属性と同じ順序のUNIX時刻で、0はその属性に有効期限がないことを表す
+    /// 有効期限はタグで鍵コンポーネントに束縛され、復号時（decrypt_at）に現在時刻と照合される
+    /// 有効期限が切れた属性は、鍵に含まれていないものとして扱われる
+    #[wasm_bindgen]
+    pub fn key_gen_with_expiry(
+        &self,
+        master_key: &ABEMasterKey,
+        attributes: Vec<String>,
+        expires_at: Vec<u64>,
+    ) -> Result<ABEPrivateKey, JsValue> {
+        if expires_at.len() != attributes.len() {
+            return Err(JsValue::from_str(&format!(
+                "有効期限の数が属性の数と一致しません: 属性{}個、有効期限{}個",
+                attributes.len(),
+                expires_at.len()
+            )));
+        }
+        
+        let mut private_key = self.key_gen(master_key, attributes)?;
+        let alpha = import_master_scalar(&master_key.secret)?;
+        
+        // 有効期限のある属性ごとにタグを生成
+        for (index, &expiry) in expires_at.iter().enumerate() {
+            if expiry == 0 {
+                continue;
+            }
+            let tag = ABEImpl::expiry_tag(&alpha, &private_key.attributes[index], expiry);
+            let mut tag_bytes = vec![0u8; 65];
+            tag.tobytes(&mut tag_bytes, false);
+            private_key.expirations.push(AttributeExpiry {
+                index,
+                expires_at: expiry,
+                tag: tag_bytes,
+            });
+        }
+        
+        Ok(private_key)
+    }
+
+    /// メッセージを暗号化
+    /// CP-ABEスキームのEncryptアルゴリズム
+    /// ポリシーはカンマ区切りの属性リスト（例: "A,B"）か、AND・OR・括弧・"k of (...)"を使ったブール式
+    /// （例: "(doctor AND cardiology) OR admin"）で指定する
+    /// ブール式のポリシーはLSSS行列（行ごとに属性のラベルが付く）に変換し、秘密を行ごとのシェアに分散して暗号化する
+    /// ポリシーを満たす属性を持つ秘密鍵で復号できる（"a OR b" の暗号文は b だけを持つ鍵でも復号できる）
+    /// "2 of (a, b, c, d)" のようなしきい値は、行列の中でShamirの秘密分散として表現され、
+    /// 一致する属性のk個の行から復元する（k-1個では復号できない）
+    /// 注意: 簡易実装。カンマ区切りのポリシーは属性のリストとして扱う
+    #[wasm_bindgen]
+    pub fn encrypt(
+        &self,
+        public_params: &ABEPublicParams,
+        policy: &str,
+        message: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        self.encrypt_cp(public_params, policy, message, false)
+    }
+
+    /// 暗号文を発行元（public_paramsの権限者）に束縛して暗号化
+    /// 公開パラメータの発行元IDを暗号文に埋め込み、復号時に秘密鍵の発行元と照合する
+    /// 別の権限者が同じ属性名で発行した鍵は、属性が一致していてもAUTHORITY_MISMATCHで拒否される
+    /// （束縛しない暗号文では、別の権限者の鍵での復号は認証タグの照合でCORRUPTとして拒否される）
+    /// 形式: header (2バイト) || 発行元ID (32バイト) || num_attrs (1バイト) || valid_until (8バイト) || C0 (65バイト) || V (可変長) || C_attrs
+    #[wasm_bindgen]
+    pub fn encrypt_bound(
+        &self,
+        public_params: &ABEPublicParams,
+        policy: &str,
+        message: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        self.encrypt_cp(public_params, policy, message, true)
+    }
+
+    /// メッセージを暗号化し、復号に必要な属性のヒントを併せて返す
+    /// ヒントはポリシーの葉（属性名）をそのまま並べたもので、ポリシー以上の情報は含まない
+    /// 戻り値は { ciphertext, required_attributes } 形式のオブジェクト
+    #[wasm_bindgen]
+    pub fn encrypt_with_hint(
+        &self,
+        public_params: &ABEPublicParams,
+        policy: &str,
+        message: &[u8],
+    ) -> Result<JsValue, JsValue> {
+        let ciphertext = self.encrypt(public_params, policy, message)?;
+        
+        // 重複を除き、ポリシーに現れた順に並べる
+        let required_attributes = js_sys::Array::new();
+        let mut seen: Vec<String> = Vec::new();
+        for attribute in hint_attributes(policy)? {
+            if !seen.contains(&attribute) {
+                required_attributes.push(&attribute.as_str().into());
+                seen.push(attribute);
+            }
+        }
+        
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"ciphertext".into(), &js_sys::Uint8Array::from(ciphertext.as_slice()))?;
+        js_sys::Reflect::set(&result, &"required_attributes".into(), &required_attributes)?;
+        
+        Ok(result.into())
+    }
+
+    /// 属性のリストからしきい値（k-of-n）ポリシーを組み立てて暗号化
+    /// n個の属性のうち少なくともk個を持つ秘密鍵で復号できる（decrypt・decrypt_atでそのまま復号できる）
+    /// "k of (attr_1, ..., attr_n)" のブール式と同じく、LSSS行列の形式（encryptを参照）で暗号化する
+    /// 属性名は平文で含まれる（ポリシーは秘密ではない）
+    #[wasm_bindgen]
+    pub fn encrypt_threshold(
+        &self,
+        public_params: &ABEPublicParams,
+        attributes: Vec<String>,
+        k: usize,
+        message: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let p_pub = public_params.point()?;
+        
+        let attributes: Vec<String> = attributes.iter().map(|a| a.trim().to_string()).collect();
+        check_threshold_attributes(&attributes, k)?;
+        
+        let policy = Policy::Threshold {
+            k,
+            children: attributes.into_iter().map(Policy::Attribute).collect(),
+        };
+        self.encrypt_lsss(&p_pub, &policy, message)
+    }
+
+    /// 旧形式（ヘッダなし）・AES-256-GCMの導入前の形式の暗号文を現在の形式に移行
+    /// 以前の形式として復号し、秘密鍵の属性をポリシーとして現在の形式で暗号化し直す
+    /// 既にAES-256-GCMの暗号文はそのまま返すため、移行ジョブを繰り返し実行しても安全
+    /// 注意: 以前の形式は認証されていないため、誤った鍵を渡すと無意味なデータが暗号化し直される
+    /// 旧方式の属性ハッシュ（バージョン1）の暗号文は旧方式の秘密鍵で復号し、現在の属性ハッシュで暗号化し直すため、
+    /// 移行後の暗号文の復号には再発行した秘密鍵が必要
+    #[wasm_bindgen]
+    pub fn upgrade_ciphertext(
+        &self,
+        old_bytes: &[u8],
+        private_key: &ABEPrivateKey,
+        public_params: &ABEPublicParams,
+    ) -> Result<Vec<u8>, JsValue> {
+        if is_retired_format(old_bytes) {
+            return Err(retired_format_error());
+        }
+        if is_current_format(old_bytes, SCHEME_TAG_CP_ABE)
+            || is_current_format(old_bytes, SCHEME_TAG_CP_ABE_LABELED)
+            || is_current_format(old_bytes, SCHEME_TAG_CP_ABE_BOUND)
+            || is_current_format(old_bytes, SCHEME_TAG_CP_ABE_LSSS)
+        {
+            if old_bytes[0] & GCM_PAYLOAD_FLAG != 0 {
+                return Ok(old_bytes.to_vec());
+            }
+            // AES-256-GCMの導入前の形式（Vが対称鍵とのXOR）は、復号して新しい乱数で暗号化し直す
+            let message = self.decrypt_with_time(private_key, old_bytes, None, DecryptMode::Migration)?;
+            return self.encrypt(public_params, &private_key.attributes.join(","), &message);
+        }
+        if old_bytes.is_empty() {
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文が空です"));
+        }
+        
+        // 旧形式（num_attrs (1バイト) || C0 (65バイト) || V (可変長) || C_attrs）を
+        // 時間制限なし（valid_until = 0）・旧方式の属性ハッシュの現在の形式に並べ替えて復号する
+        let mut framed = vec![SCHEME_TAG_CP_ABE, LEGACY_ATTRIBUTE_HASH_VERSION];
+        framed.push(old_bytes[0]);
+        framed.extend_from_slice(&0u64.to_be_bytes());
+        framed.extend_from_slice(&old_bytes[1..]);
+        // 旧形式の対称鍵は文脈を含まない方式で導出されている
+        let message = self.decrypt_with_time(private_key, &framed, None, DecryptMode::Headerless)?;
+        
+        // 新しい乱数で暗号化し直す
+        self.encrypt(public_params, &private_key.attributes.join(","), &message)
+    }
+
+    /// メッセージを暗号化し、base64url（パディングなし）の文字列で返す
+    /// URLやQRコードに埋め込むためのもので、decrypt_from_stringで復号できる
+    #[wasm_bindgen]
+    pub fn encrypt_to_string(
+        &self,
+        public_params: &ABEPublicParams,
+        policy: &str,
+        message: &[u8],
+    ) -> Result<String, JsValue> {
+        let ciphertext = self.encrypt(public_params, policy, message)?;
+        Ok(URL_SAFE_NO_PAD.encode(ciphertext))
+    }
+
+    /// base64url文字列の暗号文を復号化
+    /// 空白やパディング（=）が含まれていても受け付ける
+    /// 時間制限付きの暗号文の場合はcurrent_timeに現在時刻を指定する（decrypt_atと同じ）
+    #[wasm_bindgen]
+    pub fn decrypt_from_string(
+        &self,
+        private_key: &ABEPrivateKey,
+        encoded: &str,
+        current_time: Option<u64>,
+    ) -> Result<Vec<u8>, JsValue> {
+        let ciphertext = decode_ciphertext_string(encoded)?;
+        self.decrypt_with_time(private_key, &ciphertext, current_time, DecryptMode::Authenticated)
+    }
+
+    /// 暗号文を復号化
+    /// CP-ABEスキームのDecryptアルゴリズム
+    /// 時間制限付きの暗号文はdecrypt_atで復号する
+    #[wasm_bindgen]
+    pub fn decrypt(
+        &self,
+        private_key: &ABEPrivateKey,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        self.decrypt_with_time(private_key, ciphertext, None, DecryptMode::Authenticated)
+    }
+
+    /// 現在時刻を指定して暗号文を復号化
+    /// 時間制限（valid_until）付きの暗号文は、有効期限以前のみ復号できる
+    #[wasm_bindgen]
+    pub fn decrypt_at(
+        &self,
+        private_key: &ABEPrivateKey,
+        ciphertext: &[u8],
+        current_time: u64,
+    ) -> Result<Vec<u8>, JsValue> {
+        self.decrypt_with_time(private_key, ciphertext, Some(current_time), DecryptMode::Authenticated)
+    }
+
+    /// ポスト量子ハイブリッド方式でメッセージを暗号化
+    /// メッセージの対称鍵をペアリング層（CP-ABE）の鍵シェアとML-KEM（Kyber）の共有秘密の両方から導出するため、
+    /// BN254のペアリングが破られてもKyberの秘密鍵がなければ復号できない
+    /// 復号にはポリシーを満たす秘密鍵と、kem_public_keyに対応するKyberの秘密鍵の両方が必要
+    #[wasm_bindgen]
+    pub fn encrypt_hybrid(
+        &self,
+        public_params: &ABEPublicParams,
+        policy: &str,
+        kem_public_key: &[u8],
+        message: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        hybrid::check_kem_public_key(kem_public_key).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        
+        // ランダムな鍵シェアをペアリング層で暗号化
+        let key_share = hybrid::random_key_share()?;
+        let pairing_ciphertext = self.encrypt(public_params, policy, key_share.as_slice())?;
+        
+        Ok(hybrid::seal(kem_public_key, &key_share, &pairing_ciphertext, message)?)
+    }
+
+    /// 暗号文の復号を試み、失敗した場合はエラーの代わりにundefinedを返す
+    /// 受信した暗号文を手元のすべての鍵で順に試すような用途向けで、失敗してもdecrypt_failedイベントは通知しない
+    /// ポリシーを満たさない鍵や壊れた暗号文はundefinedになる
+    /// 別のマスター鍵で発行された鍵は、暗号文のAES-256-GCMの認証タグの照合で検出してundefinedになる
+    /// 認証タグのない以前の形式の暗号文は鍵の正否を判定できないため、常にundefinedになる（upgrade_ciphertextで移行する）
+    #[wasm_bindgen]
+    pub fn try_decrypt(&self, private_key: &ABEPrivateKey, ciphertext: &[u8]) -> Option<Vec<u8>> {
+        muted(|| self.decrypt(private_key, ciphertext)).ok()
+    }
+
+    /// encrypt_hybridで暗号化した暗号文の復号を試み、失敗した場合はエラーの代わりにundefinedを返す
+    /// AES-256-GCMの認証タグで成否を判定するため、鍵やKyberの鍵が異なる場合、ポリシーを満たさない場合、
+    /// 暗号文が壊れている場合はいずれもundefinedになる（decrypt_failedイベントは通知しない）
+    #[wasm_bindgen]
+    pub fn try_decrypt_hybrid(
+        &self,
+        private_key: &ABEPrivateKey,
+        kem_private_key: &[u8],
+        kem_public_key: &[u8],
+        ciphertext: &[u8],
+        current_time: Option<u64>,
+    ) -> Option<Vec<u8>> {
+        muted(|| self.decrypt_hybrid(private_key, kem_private_key, kem_public_key, ciphertext, current_time)).ok()
+    }
+
+    /// encrypt_hybridで暗号化した暗号文を復号化
+    /// 時間制限付きのポリシーの場合はcurrent_timeに現在時刻を指定する（decrypt_atと同じ）
+    /// Kyberの鍵が誤っている場合は認証に失敗し、エラーを返す
+    #[wasm_bindgen]
+    pub fn decrypt_hybrid(
+        &self,
+        private_key: &ABEPrivateKey,
+        kem_private_key: &[u8],
+        kem_public_key: &[u8],
+        ciphertext: &[u8],
+        current_time: Option<u64>,
+    ) -> Result<Vec<u8>, JsValue> {
+        let parts = hybrid::parse(ciphertext).map_err(|e| decrypt_error(DecryptErrorCode::Corrupt, &e.to_string()))?;
+        
+        // ペアリング層から鍵シェアを復号
+        let key_share = Zeroizing::new(self.decrypt_with_time(private_key, parts.pairing_ciphertext, current_time, DecryptMode::Authenticated)?);
+        if key_share.len() != hybrid::KEY_SHARE_SIZE {
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "ハイブリッド暗号文の鍵シェアが不正です"));
+        }
+        
+        hybrid::open(ciphertext, &key_share, kem_private_key, kem_public_key)
+            .map_err(|e| decrypt_error(DecryptErrorCode::Corrupt, &e.to_string()))
+    }
+
+    /// 秘密鍵が属性の鍵コンポーネントを保有していることの証明を生成
+    /// 鍵そのものを明かさずに、検証者に属性の保有を示すために使用する
+    /// challengeには検証者が指定した値（ノンスなど）を渡し、証明の再利用を防ぐ
+    #[wasm_bindgen]
+    pub fn prove_attribute(
+        &self,
+        private_key: &ABEPrivateKey,
+        attribute: &str,
+        challenge: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        // 属性に対応する鍵コンポーネントを探す
+        let index = private_key
+            .attributes
+            .iter()
+            .position(|a| a == attribute)
+            .ok_or_else(|| {
+                JsValue::from_str(&format!("秘密鍵に属性「{}」が含まれていません", attribute))
+            })?;
+        
+        let start = HEADER_SIZE + index * 130;
+        let end = start + 130;
+        if private_key.key.len() < end {
+            return Err(JsValue::from_str("秘密鍵の鍵コンポーネントが範囲外です"));
+        }
+        let key_component = ecp2_from_bytes(&private_key.key[start..end], self.strict)?;
+        
+        // 証明を生成（A (384バイト) || Z (130バイト)の形式）
+        let (mut a, z) = ABEImpl::prove_attribute(&key_component, attribute, challenge)?;
         
-        // JsValueとして返す
-        let result = js_sys::Object::new();
-        js_sys::Reflect::set(&result, &"master_key".into(), &master_key.into())?;
-        js_sys::Reflect::set(&result, &"public_params".into(), &public_params.into())?;
+        let mut proof = vec![0u8; ATTRIBUTE_PROOF_SIZE];
+        a.tobytes(&mut proof[..GT_SIZE]);
+        z.tobytes(&mut proof[GT_SIZE..], false);
         
-        Ok(result.into())
+        Ok(proof)
     }
 
-    /// 属性セットから秘密鍵を生成
-    /// CP-ABEスキームのKeyGenアルゴリズム
+    /// 属性の鍵保有証明を検証
     #[wasm_bindgen]
-    pub fn key_gen(
+    pub fn verify_attribute_proof(
         &self,
-        master_key: &ABEMasterKey,
-        attributes: Vec<String>,
-    ) -> Result<ABEPrivateKey, JsValue> {
-        use miracl_core::bn254::{big::BIG, ecp2::ECP2};
+        public_params: &ABEPublicParams,
+        attribute: &str,
+        challenge: &[u8],
+        proof: &[u8],
+    ) -> Result<bool, JsValue> {
+        use miracl_core::bn254::{ecp2::ECP2, fp12::FP12};
         
-        // マスター秘密鍵をBIGに変換
-        if master_key.secret.len() != 32 {
-            return Err(JsValue::from_str("マスター鍵の長さが不正です"));
-        }
-        let alpha = BIG::frombytes(&master_key.secret);
+        // 公開パラメータをECPに変換
+        let p_pub = public_params.point()?;
         
-        // 秘密鍵コンポーネントを生成
-        let key_components = ABEImpl::key_gen(&alpha, &attributes);
+        if proof.len() != ATTRIBUTE_PROOF_SIZE {
+            return Ok(false);
+        }
         
-        // 鍵コンポーネントをバイト列に変換
-        let mut key_bytes = Vec::new();
-        for key_comp in &key_components {
-            let mut comp_bytes = vec![0u8; 130];
-            key_comp.tobytes(&mut comp_bytes, false);
-            key_bytes.extend_from_slice(&comp_bytes);
+        let a = FP12::frombytes(&proof[..GT_SIZE]);
+        let z = ECP2::frombytes(&proof[GT_SIZE..]);
+        if !is_valid_g2(&z) {
+            return Ok(false);
         }
         
-        Ok(ABEPrivateKey {
-            key: key_bytes,
-            attributes,
-        })
+        Ok(ABEImpl::verify_attribute_proof(&p_pub, attribute, challenge, &a, &z))
     }
 
-    /// メッセージを暗号化
-    /// CP-ABEスキームのEncryptアルゴリズム
-    /// 注意: 簡易実装。ポリシーは属性のリストとして扱う
+    /// 属性セットがポリシーを満たすかを評価
+    /// 復号を行わずにアクセス可否を確認するためのもの（UIでの事前確認など）
+    /// ポリシーはAND・OR・NOT・閾値（例: "2 of (A, B, C)"）を使用できる
     #[wasm_bindgen]
-    pub fn encrypt(
+    pub fn attributes_satisfy(policy: &str, attributes: Vec<String>) -> Result<bool, JsValue> {
+        let policy = Policy::parse(policy)
+            .map_err(|e| JsValue::from_str(&format!("ポリシーの解析に失敗しました: {}", e)))?;
+        
+        Ok(policy.is_satisfied_by(&attributes))
+    }
+
+    /// 入れ子の深さの上限を指定して、属性セットがポリシーを満たすかを評価
+    /// 信頼できない入力のポリシーに対して、既定値（64段）より厳しい上限をかける場合に使用する
+    #[wasm_bindgen]
+    pub fn attributes_satisfy_with_max_depth(
+        policy: &str,
+        attributes: Vec<String>,
+        max_depth: usize,
+    ) -> Result<bool, JsValue> {
+        let policy = Policy::parse_with_max_depth(policy, max_depth)
+            .map_err(|e| JsValue::from_str(&format!("ポリシーの解析に失敗しました: {}", e)))?;
+        
+        Ok(policy.is_satisfied_by(&attributes))
+    }
+
+    /// 設定（strictモードなど）を引き継いだ複製を作る（JSではclone()）
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> ABE {
+        self.clone()
+    }
+}
+
+impl ABE {
+    /// CP-ABEの暗号化（encrypt・encrypt_boundの共通処理）
+    /// bind_authorityがtrueの場合は、公開パラメータの発行元IDを暗号文と対称鍵の導出に含める
+    /// falseの場合は属性ラベル付きの形式（encrypt_labeled）で暗号化する
+    fn encrypt_cp(
         &self,
         public_params: &ABEPublicParams,
         policy: &str,
         message: &[u8],
+        bind_authority: bool,
     ) -> Result<Vec<u8>, JsValue> {
-        use miracl_core::bn254::ecp::ECP;
-        
         // 公開パラメータをECPに変換
-        if public_params.params.len() < 65 {
-            return Err(JsValue::from_str("公開パラメータの長さが不正です"));
+        let p_pub = public_params.point()?;
+        
+        // ブール式のポリシーはLSSS行列として暗号化する
+        if Policy::has_operators(policy) {
+            if bind_authority {
+                return Err(JsValue::from_str("発行元に束縛した暗号化は、ブール式のポリシーに対応していません"));
+            }
+            let policy = Policy::parse(policy).map_err(|e| JsValue::from_str(&e))?;
+            return self.encrypt_lsss(&p_pub, &policy, message);
         }
-        let p_pub = ECP::frombytes(&public_params.params);
         
-        // ポリシーから属性を抽出（簡易実装: カンマ区切り）
-        let attributes: Vec<String> = policy
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
+        // ポリシーから属性を抽出
+        let attributes = policy_attributes(policy)?;
         
-        if attributes.is_empty() {
-            return Err(JsValue::from_str("ポリシーには少なくとも1つの属性が必要です"));
-        }
+        check_max_attributes(attributes.len())?;
         
-        // メッセージを暗号化
-        let (c0, v, c_attrs) = ABEImpl::encrypt(&p_pub, &attributes, message);
+        // 時間制限属性を取り出す
+        let valid_until = extract_valid_until(&attributes)?;
         
-        // 暗号文をバイト列に変換（num_attrs (1バイト) || C0 (65バイト) || V (可変長) || C_attrsの形式）
-        let num_attrs = c_attrs.len();
-        if num_attrs > 255 {
-            return Err(JsValue::from_str("属性が多すぎます（最大255個）"));
+        // 発行元に束縛しない暗号文は、属性コンポーネントごとに属性名を保存する
+        if !bind_authority {
+            return self.encrypt_labeled(&p_pub, &attributes, valid_until, message);
         }
         
-        let mut ciphertext = vec![num_attrs as u8]; // 属性数を先頭に保存
+        // メッセージを暗号化（対称鍵はスキーム・時間制限・属性・発行元に束縛する）
+        let authority = authority_id(&public_params.params);
+        let context = bound_kdf_context(valid_until, &attributes, &authority);
+        let (c0, v, c_attrs) = ABEImpl::encrypt(&p_pub, &attributes, message, &context)?;
+        
+        // 暗号文をバイト列に変換（header (2バイト) || 発行元ID (32バイト) || num_attrs (1バイト) || valid_until (8バイト) || C0 (65バイト) || V (可変長) || C_attrsの形式）
+        let num_attrs = c_attrs.len();
+        let mut ciphertext = [sealed_header(SCHEME_TAG_CP_ABE_BOUND), authority].concat();
+        ciphertext.push(num_attrs as u8); // ヘッダの後に属性数を保存
+        ciphertext.extend_from_slice(&valid_until.to_be_bytes());
         
         // C0を追加
         let mut c0_bytes = vec![0u8; 65];
@@ -223,48 +2057,135 @@ impl ABE {
         Ok(ciphertext)
     }
 
-    /// 暗号文を復号化
-    /// CP-ABEスキームのDecryptアルゴリズム
-    /// 注意: 簡易実装。実際のCP-ABEでは、ポリシー満足性のチェックが必要
-    #[wasm_bindgen]
-    pub fn decrypt(
+    /// カンマ区切りの属性リストを属性ラベル付きの形式で暗号化（encryptの共通処理から呼ばれる）
+    /// 暗号文の形式:
+    ///   header (2バイト) || num_attrs (1バイト) || valid_until (8バイト) || C0 (65バイト) ||
+    ///   num_attrs × (len(attr) (1バイト) || attr || C_attr (130バイト)) || V (可変長)
+    /// 属性名は平文で含まれる（ポリシーは秘密ではない）
+    fn encrypt_labeled(
+        &self,
+        p_pub: &miracl_core::bn254::ecp::ECP,
+        attributes: &[String],
+        valid_until: u64,
+        message: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        for attr in attributes {
+            if attr.is_empty() || attr.len() > u8::MAX as usize {
+                return Err(JsValue::from_str(&format!(
+                    "属性名は1〜{}バイトで指定してください: 「{}」",
+                    u8::MAX,
+                    attr
+                )));
+            }
+        }
+        
+        let context = kdf_context(SCHEME_TAG_CP_ABE_LABELED, valid_until, attributes);
+        let (c0, v, c_attrs) = ABEImpl::encrypt(p_pub, attributes, message, &context)?;
+        
+        let mut ciphertext = sealed_header(SCHEME_TAG_CP_ABE_LABELED);
+        ciphertext.push(c_attrs.len() as u8);
+        ciphertext.extend_from_slice(&valid_until.to_be_bytes());
+        let mut c0_bytes = vec![0u8; 65];
+        c0.tobytes(&mut c0_bytes, false);
+        ciphertext.extend_from_slice(&c0_bytes);
+        
+        for (attr, c_attr) in attributes.iter().zip(&c_attrs) {
+            ciphertext.push(attr.len() as u8);
+            ciphertext.extend_from_slice(attr.as_bytes());
+            let mut attr_bytes = vec![0u8; G2_SIZE];
+            c_attr.tobytes(&mut attr_bytes, false);
+            ciphertext.extend_from_slice(&attr_bytes);
+        }
+        ciphertext.extend_from_slice(&v);
+        
+        Ok(ciphertext)
+    }
+
+    /// 暗号文を復号化（decrypt・decrypt_at・upgrade_ciphertextの共通処理）
+    /// AES-256-GCMの暗号文は認証タグを、平文のハッシュ付きの暗号文は復号後にハッシュを照合してから平文を返す
+    /// modeがAuthenticatedの場合、AES-256-GCMのフラグのない暗号文は復号せずにエラーを返す
+    fn decrypt_with_time(
         &self,
         private_key: &ABEPrivateKey,
         ciphertext: &[u8],
+        current_time: Option<u64>,
+        mode: DecryptMode,
     ) -> Result<Vec<u8>, JsValue> {
-        use miracl_core::bn254::{ecp::ECP, ecp2::ECP2};
-        
-        if ciphertext.len() < 66 {
-            return Err(JsValue::from_str("暗号文が短すぎます"));
+        let (ciphertext, hashed, payload) = strip_payload_flags(ciphertext);
+        mode.check_payload(payload)?;
+        let decrypted = self.decrypt_unflagged(private_key, &ciphertext, current_time, mode, payload)?;
+        if hashed {
+            return verify_plaintext_hash(decrypted);
+        }
+        Ok(decrypted)
+    }
+
+    /// 平文のハッシュ・AES-256-GCMのフラグを外した暗号文を、payloadの方式でVを復号化
+    /// modeがHeaderlessの場合は旧方式で対称鍵を導出する（旧形式の暗号文の移行用）
+    /// 暗号文の属性コンポーネントを秘密鍵の属性と照合し、すべての属性を持つ場合だけ復号する
+    fn decrypt_unflagged(
+        &self,
+        private_key: &ABEPrivateKey,
+        ciphertext: &[u8],
+        current_time: Option<u64>,
+        mode: DecryptMode,
+        payload: Payload,
+    ) -> Result<Vec<u8>, JsValue> {
+        if is_retired_format(ciphertext) {
+            return Err(retired_format_error());
+        }
+        if ciphertext.first() == Some(&SCHEME_TAG_CP_ABE_LSSS) {
+            return self.decrypt_lsss(private_key, ciphertext, current_time, payload);
+        }
+        if ciphertext.first() == Some(&SCHEME_TAG_CP_ABE_LABELED) {
+            return self.decrypt_labeled(private_key, ciphertext, current_time, payload);
         }
         
-        // 暗号文を解析（num_attrs (1バイト) || C0 (65バイト) || V (可変長) || C_attrsの形式）
-        let ciphertext_num_attrs = ciphertext[0] as usize;
-        let c0_start = 1;
+        // 発行元に束縛した暗号文は、発行元を照合してからCP-ABE暗号文の形式に戻して復号する
+        let unbound;
+        let (ciphertext, authority) = if ciphertext.first() == Some(&SCHEME_TAG_CP_ABE_BOUND) {
+            let (authority, framed) = unbind_authority(private_key, ciphertext)?;
+            unbound = framed;
+            (unbound.as_slice(), Some(authority))
+        } else {
+            (ciphertext, None)
+        };
+        
+        // 曲線演算の前に、属性の文字列とメタデータだけで復号できないと確定する場合を除外する
+        let (valid_until, hash) = self.prescreen(private_key, ciphertext, current_time)?;
+        
+        // 暗号文を解析（header (2バイト) || num_attrs (1バイト) || valid_until (8バイト) || C0 (65バイト) || V (可変長) || C_attrsの形式）
+        let ciphertext_num_attrs = ciphertext[HEADER_SIZE] as usize;
+        let c0_start = HEADER_SIZE + 1 + VALID_UNTIL_SIZE;
         let c0_end = c0_start + 65;
         
-        if ciphertext.len() < c0_end {
-            return Err(JsValue::from_str("暗号文にC0コンポーネントがありません"));
-        }
+        let c0 = ecp_from_bytes(&ciphertext[c0_start..c0_end], self.strict)?;
         
-        let c0 = ECP::frombytes(&ciphertext[c0_start..c0_end]);
+        // 秘密鍵コンポーネントを抽出
+        let mut key_components = Vec::new();
+        let key_bytes = &private_key.key[HEADER_SIZE..];
+        let key_component_size = 130;
+        let keys_size = checked_length(private_key.attributes.len().checked_mul(key_component_size))?;
         
-        // 暗号化時の属性数と秘密鍵の属性数を比較
-        let key_num_attrs = private_key.attributes.len();
+        if key_bytes.len() < keys_size {
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "秘密鍵に鍵コンポーネントが不足しています"));
+        }
         
-        if ciphertext_num_attrs != key_num_attrs {
-            return Err(JsValue::from_str(&format!(
-                "属性が一致しません: 暗号文は{}個の属性を必要としますが、秘密鍵は{}個の属性を持っています。暗号化時に使用した属性と鍵生成時に使用した属性が一致する必要があります。",
-                ciphertext_num_attrs,
-                key_num_attrs
-            )));
+        for chunk in key_bytes[..keys_size].chunks_exact(key_component_size) {
+            let key_comp = ecp2_from_bytes(chunk, self.strict)?;
+            key_components.push(key_comp);
         }
         
+        // 有効期限のタグを検証し、有効期限が切れた属性を除く
+        let active = self.active_key_components(private_key, key_components, current_time, hash)?;
+        
+        // 長さの計算はすべてオーバーフロー・アンダーフローを検査して行う
         let attr_component_size = 130;
-        let expected_min_size = c0_end + ciphertext_num_attrs * attr_component_size;
+        let attrs_size = checked_length(ciphertext_num_attrs.checked_mul(attr_component_size))?;
+        let expected_min_size = checked_length(c0_end.checked_add(attrs_size))?;
         
         if ciphertext.len() < expected_min_size {
-            return Err(JsValue::from_str(&format!(
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, &format!(
                 "暗号文が不正です: 最低{}バイト必要ですが、{}バイトしかありません",
                 expected_min_size,
                 ciphertext.len()
@@ -272,75 +2193,402 @@ impl ABE {
         }
         
         // Vを抽出（C0の後、属性コンポーネントの前）
+        // 上の長さ検査により ciphertext.len() >= c0_end + attrs_size が保証されている
         let v_start = c0_end;
-        let v_end = ciphertext.len() - (ciphertext_num_attrs * attr_component_size);
+        let v_end = checked_length(ciphertext.len().checked_sub(attrs_size))?;
         
         if v_end <= v_start {
-            return Err(JsValue::from_str("暗号文のVコンポーネントが空または不正です"));
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文のVコンポーネントが空または不正です"));
         }
         
         let v = &ciphertext[v_start..v_end];
         
-        // 属性コンポーネントを抽出
+        // 属性コンポーネントを抽出（残りの長さはちょうど属性数 × コンポーネントサイズ）
         let mut c_attrs = Vec::new();
-        for i in 0..ciphertext_num_attrs {
-            let start = v_end + (i * attr_component_size);
-            let end = start + attr_component_size;
-            if end > ciphertext.len() {
-                return Err(JsValue::from_str("暗号文の属性コンポーネントが範囲外です"));
-            }
-            let c_attr = ECP2::frombytes(&ciphertext[start..end]);
+        for chunk in ciphertext[v_end..].chunks_exact(attr_component_size) {
+            let c_attr = ecp2_from_bytes(chunk, self.strict)?;
             c_attrs.push(c_attr);
         }
         
-        // 秘密鍵コンポーネントを抽出
-        let mut key_components = Vec::new();
-        let key_bytes = &private_key.key;
-        let key_component_size = 130;
+        // 属性コンポーネントを秘密鍵の有効な属性と照合し、暗号文のすべての属性を持つか確認する
+        // 時間制限属性（valid_until:<暗号文のvalid_until>）のコンポーネントだけは、check_validityで確認済みのため
+        // 秘密鍵が同じ属性を持たなくてよい。候補の末尾に加えて照合し、それ以外の属性はすべて鍵の属性と一致する必要がある
+        let mut candidates = active.attributes.clone();
+        if valid_until != 0 {
+            candidates.push(format!("{}{}", VALID_UNTIL_PREFIX, valid_until));
+        }
+        let matched = ABEImpl::match_attributes(&candidates, &c0, &c_attrs, hash);
+        let unmatched = matched.iter().filter(|key_index| key_index.is_none()).count();
+        let key_index = match matched.first() {
+            Some(&Some(key_index)) if unmatched == 0 && key_index < active.attributes.len() => key_index,
+            _ => {
+                return Err(decrypt_error(DecryptErrorCode::PolicyNotSatisfied, &format!(
+                    "ポリシーを満たしていません: 暗号文の{}個の属性のうち{}個が秘密鍵の有効な属性（{}）と一致しません",
+                    ciphertext_num_attrs,
+                    unmatched,
+                    active.attributes.join(", ")
+                )));
+            }
+        };
         
-        if key_bytes.len() < key_num_attrs * key_component_size {
-            return Err(JsValue::from_str("秘密鍵に鍵コンポーネントが不足しています"));
+        // 暗号文の先頭の属性に一致した鍵コンポーネントで復号化
+        let attribute = std::slice::from_ref(&active.attributes[key_index]);
+        let context = match authority {
+            Some(authority) => bound_kdf_context(valid_until, attribute, authority),
+            None => kdf_context(SCHEME_TAG_CP_ABE, valid_until, attribute),
+        };
+        let key_component = std::slice::from_ref(&active.components[key_index]);
+        authenticated(ABEImpl::decrypt(key_component, &c0, v, &c_attrs, mode.key_derivation(&context), payload))
+    }
+
+    /// 属性ラベル付きの暗号文を復号化（encrypt_labeledの逆）
+    /// ペアリングの前に、暗号文の属性名と秘密鍵の有効な属性を照合して、暗号文のすべての属性を持つか確認する
+    /// 暗号文が必要とする以上の属性を持つ鍵でもよく、先頭の属性の鍵コンポーネントだけを使って復号する
+    fn decrypt_labeled(
+        &self,
+        private_key: &ABEPrivateKey,
+        ciphertext: &[u8],
+        current_time: Option<u64>,
+        payload: Payload,
+    ) -> Result<Vec<u8>, JsValue> {
+        let hash = check_headers(ciphertext, SCHEME_TAG_CP_ABE_LABELED, &private_key.key, SCHEME_TAG_CP_ABE)?;
+        
+        let parsed = parse_labeled_ciphertext(ciphertext)?;
+        check_validity(parsed.valid_until, &private_key.attributes, current_time)?;
+        
+        // 秘密鍵が持たない属性を探す（時間制限属性はcheck_validityで確認済みのため除く）
+        // 有効期限が切れた属性は持っていないものとして扱う
+        let expired = private_key.expired_attributes(current_time)?;
+        let is_held = |attribute: &str| {
+            private_key
+                .attributes
+                .iter()
+                .enumerate()
+                .any(|(index, attr)| attr == attribute && !expired.iter().any(|expiry| expiry.index == index))
+        };
+        let mut missing: Vec<&str> = Vec::new();
+        for component in &parsed.components {
+            let is_time_limit = parse_valid_until(component.attribute)
+                .map_err(|e| decrypt_error(DecryptErrorCode::Corrupt, &e))?
+                .is_some();
+            if !is_time_limit && !is_held(component.attribute) && !missing.contains(&component.attribute) {
+                missing.push(component.attribute);
+            }
+        }
+        if missing.iter().any(|attribute| private_key.attributes.iter().any(|attr| attr == attribute)) {
+            return Err(expired_attributes_error(private_key, &expired, current_time));
+        }
+        if !missing.is_empty() {
+            return Err(decrypt_error(DecryptErrorCode::PolicyNotSatisfied, &format!(
+                "ポリシーを満たしていません: 秘密鍵の有効な属性に暗号文の属性（{}）がありません",
+                missing.join(", ")
+            )));
         }
         
-        for i in 0..key_num_attrs {
-            let start = i * key_component_size;
-            let end = start + key_component_size;
-            if end > key_bytes.len() {
-                return Err(JsValue::from_str("秘密鍵の鍵コンポーネントが範囲外です"));
+        // 有効期限のタグを検証し、先頭の属性（対称鍵を決める属性）の鍵コンポーネントを選ぶ
+        let key_components = self.key_components(private_key)?;
+        let active = self.active_key_components(private_key, key_components, current_time, hash)?;
+        let first = &parsed.components[0];
+        let key_index = active
+            .attributes
+            .iter()
+            .position(|attr| attr == first.attribute)
+            .ok_or_else(|| decrypt_error(DecryptErrorCode::PolicyNotSatisfied, &format!(
+                "ポリシーを満たしていません: 秘密鍵の有効な属性に暗号文の属性（{}）がありません",
+                first.attribute
+            )))?;
+        
+        let c0 = ecp_from_bytes(parsed.c0, self.strict)?;
+        // 復号には先頭の属性コンポーネントだけを使うが、残りも曲線上の点か確認して壊れた暗号文を拒否する
+        let mut c_attrs = Vec::with_capacity(parsed.components.len());
+        for component in &parsed.components {
+            let c_attr = ecp2_from_bytes(component.c_attr, self.strict)?;
+            if c_attr.is_infinity() {
+                return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文の属性コンポーネントが曲線上の点ではありません"));
             }
-            let key_comp = ECP2::frombytes(&key_bytes[start..end]);
-            key_components.push(key_comp);
+            c_attrs.push(c_attr);
         }
         
-        // 暗号文を復号化
-        let message = ABEImpl::decrypt(&key_components, &c0, v, &c_attrs);
+        let attribute = std::slice::from_ref(&active.attributes[key_index]);
+        let context = kdf_context(SCHEME_TAG_CP_ABE_LABELED, parsed.valid_until, attribute);
+        let key_component = std::slice::from_ref(&active.components[key_index]);
+        authenticated(ABEImpl::decrypt(key_component, &c0, parsed.v, &c_attrs[..1], KeyDerivation::Context(&context), payload))
+    }
+
+    /// ブール式のポリシーをLSSS行列として暗号化（encrypt・encrypt_thresholdから呼ばれる）
+    /// 暗号文の形式:
+    ///   header (2バイト) || C0 (65バイト) || 直列化したLSSS行列 (lsssモジュールを参照) || V (可変長)
+    /// 属性名と行列は平文で含まれる（ポリシーは秘密ではない）
+    fn encrypt_lsss(
+        &self,
+        p_pub: &miracl_core::bn254::ecp::ECP,
+        policy: &Policy,
+        message: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        lsss::check_encryptable(policy).map_err(|e| JsValue::from_str(&e))?;
+        check_max_attributes(policy.leaves().len())?;
+        
+        // 秘密sを行列の行ごとのシェアλ_iに分散し、行ごとにWaters方式の暗号文コンポーネント (C_i, D_i) を作る
+        let matrix = LsssMatrix::from_policy(policy);
+        let secret = ABEImpl::random_big()?;
+        let shares = matrix.share(&secret)?;
+        let (blinding, c0, rows) = ABEImpl::encrypt_lsss(p_pub, &matrix.labels, &shares, &secret)?;
+        let row_components: Vec<Vec<u8>> = rows
+            .iter()
+            .map(|(c, d)| {
+                let mut bytes = vec![0u8; lsss::ROW_COMPONENTS_SIZE];
+                c.tobytes(&mut bytes[..G2_SIZE], false);
+                d.tobytes(&mut bytes[G2_SIZE..], false);
+                bytes
+            })
+            .collect();
+        let matrix_bytes = matrix.to_bytes(&row_components);
+        
+        let mut ciphertext = sealed_header(SCHEME_TAG_CP_ABE_LSSS);
+        let mut c0_bytes = vec![0u8; 65];
+        c0.tobytes(&mut c0_bytes, false);
+        ciphertext.extend_from_slice(&c0_bytes);
+        ciphertext.extend_from_slice(&matrix_bytes);
+        
+        // V = AES-256-GCM(KDF(e(Q, P_pub)^s, LSSS行列), M)を計算
+        let hash_key = ABEImpl::derive_key(&blinding, &lsss_context(&matrix_bytes), Payload::Gcm);
+        ciphertext.extend(seal_payload(&hash_key, message)?);
+        
+        Ok(ciphertext)
+    }
+
+    /// LSSSの暗号文を復号化（encrypt_lsssの逆）
+    /// ペアリングの前に、鍵の属性の行から再構成の係数ωを計算し（解がなければポリシーを満たさない）、
+    /// ω_i ≠ 0 の行と秘密鍵のLSSS鍵から e(Q, P_pub)^s を復元する
+    fn decrypt_lsss(
+        &self,
+        private_key: &ABEPrivateKey,
+        ciphertext: &[u8],
+        current_time: Option<u64>,
+        payload: Payload,
+    ) -> Result<Vec<u8>, JsValue> {
+        let hash = check_headers(ciphertext, SCHEME_TAG_CP_ABE_LSSS, &private_key.key, SCHEME_TAG_CP_ABE)?;
+        
+        let (c0_bytes, matrix_and_v) = ciphertext[HEADER_SIZE..]
+            .split_at_checked(65)
+            .ok_or_else(|| decrypt_error(DecryptErrorCode::Corrupt, "暗号文が短すぎます"))?;
+        let (parsed, v) = lsss::parse(matrix_and_v).map_err(|e| decrypt_error(DecryptErrorCode::Corrupt, &e))?;
+        if v.is_empty() {
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文のVコンポーネントが空または不正です"));
+        }
+        let matrix_bytes = &matrix_and_v[..matrix_and_v.len() - v.len()];
+        let c0 = ecp_from_bytes(c0_bytes, self.strict)?;
+        
+        // 秘密鍵コンポーネントを抽出し、有効期限が切れた属性を除く
+        let key_components = self.key_components(private_key)?;
+        let active = self.active_key_components(private_key, key_components, current_time, hash)?;
+        
+        let coefficients = parsed.matrix.reconstruction_coefficients(&active.attributes).ok_or_else(|| {
+            decrypt_error(DecryptErrorCode::PolicyNotSatisfied, &format!(
+                "ポリシーを満たしていません: 秘密鍵の有効な属性（{}）では暗号文のポリシーを満たせません",
+                active.attributes.join(", ")
+            ))
+        })?;
+        
+        // 係数が0でない行の (ω_i, C_i, D_i, K_ρ(i))
+        let lsss_key = self.lsss_key(private_key)?;
+        let mut rows = Vec::with_capacity(coefficients.len());
+        for (row, coefficient) in coefficients {
+            let attribute = &parsed.matrix.labels[row];
+            let key_index = private_key
+                .attributes
+                .iter()
+                .position(|a| a == attribute)
+                .ok_or_else(|| decrypt_error(DecryptErrorCode::PolicyNotSatisfied, "ポリシーを満たしていません"))?;
+            let (c, d) = parsed.row_components[row].split_at(G2_SIZE);
+            rows.push((
+                coefficient,
+                ecp2_from_bytes(c, self.strict)?,
+                ecp_from_bytes(d, self.strict)?,
+                &lsss_key.components[key_index],
+            ));
+        }
+        let blinding = ABEImpl::decrypt_lsss(&lsss_key.k, &lsss_key.l, &c0, &rows);
+        
+        let hash_key = ABEImpl::derive_key(&blinding, &lsss_context(matrix_bytes), payload);
+        authenticated(open_payload(&hash_key, v, payload))
+    }
+
+    /// 秘密鍵からWaters方式のLSSS鍵（K || L || 属性ごとのK_attr）を抽出
+    /// LSSS鍵のない秘密鍵（この形式より前に発行した鍵、from_bytesで復元した鍵）はINCOMPATIBLE_FORMATで拒否する
+    fn lsss_key(&self, private_key: &ABEPrivateKey) -> Result<LsssKey, JsValue> {
+        if private_key.lsss_key.is_empty() {
+            return Err(decrypt_error(
+                DecryptErrorCode::IncompatibleFormat,
+                "秘密鍵にブール式のポリシーの復号に必要なLSSS鍵がありません（key_genで再発行するか、to_base64の文字列から復元してください）",
+            ));
+        }
+        let expected = checked_length(
+            private_key
+                .attributes
+                .len()
+                .checked_mul(G2_SIZE)
+                .and_then(|size| size.checked_add(LSSS_KEY_HEADER_SIZE)),
+        )?;
+        if private_key.lsss_key.len() != expected {
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "秘密鍵のLSSS鍵の長さが属性の数と一致しません"));
+        }
+        
+        let (k, rest) = private_key.lsss_key.split_at(G2_SIZE);
+        let (l, components) = rest.split_at(LSSS_KEY_HEADER_SIZE - G2_SIZE);
+        Ok(LsssKey {
+            k: ecp2_from_bytes(k, self.strict)?,
+            l: ecp_from_bytes(l, self.strict)?,
+            components: components
+                .chunks_exact(G2_SIZE)
+                .map(|chunk| ecp2_from_bytes(chunk, self.strict))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// 秘密鍵（非圧縮形式）から属性ごとの鍵コンポーネントを抽出
+    fn key_components(&self, private_key: &ABEPrivateKey) -> Result<Vec<miracl_core::bn254::ecp2::ECP2>, JsValue> {
+        let key_bytes = &private_key.key[HEADER_SIZE..];
+        let keys_size = checked_length(private_key.attributes.len().checked_mul(G2_SIZE))?;
+        if key_bytes.len() < keys_size {
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "秘密鍵に鍵コンポーネントが不足しています"));
+        }
+        key_bytes[..keys_size]
+            .chunks_exact(G2_SIZE)
+            .map(|chunk| ecp2_from_bytes(chunk, self.strict))
+            .collect()
+    }
+
+    /// 曲線演算を行わずに、復号できないことが確定する暗号文と秘密鍵の組み合わせを拒否する
+    /// ヘッダ・属性数・時間制限・属性ごとの有効期限（表記上の値）だけを確認するため、
+    /// 誤った鍵での復号の試行をペアリングなしで安価に打ち切れる
+    /// 戻り値は暗号文の時間制限（valid_until）と属性ハッシュの方式
+    fn prescreen(
+        &self,
+        private_key: &ABEPrivateKey,
+        ciphertext: &[u8],
+        current_time: Option<u64>,
+    ) -> Result<(u64, AttributeHash), JsValue> {
+        if ciphertext.len() < HEADER_SIZE + 1 + VALID_UNTIL_SIZE + 65 {
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文が短すぎます"));
+        }
+        
+        // 暗号文と秘密鍵のヘッダ（スキーム・属性ハッシュバージョン）を確認
+        let hash = check_headers(ciphertext, SCHEME_TAG_CP_ABE, &private_key.key, SCHEME_TAG_CP_ABE)?;
+        
+        let ciphertext_num_attrs = ciphertext[HEADER_SIZE] as usize;
+        check_ciphertext_num_attrs(ciphertext_num_attrs)?;
+        
+        // 時間制限を確認
+        let valid_until_start = HEADER_SIZE + 1;
+        let mut valid_until_bytes = [0u8; VALID_UNTIL_SIZE];
+        valid_until_bytes.copy_from_slice(&ciphertext[valid_until_start..valid_until_start + VALID_UNTIL_SIZE]);
+        let valid_until = u64::from_be_bytes(valid_until_bytes);
+        check_validity(valid_until, &private_key.attributes, current_time)?;
         
-        Ok(message)
+        // 暗号化時の属性数と、有効期限が切れた属性を除いた秘密鍵の属性数を比較
+        // 秘密鍵は暗号文の属性をすべて持つ必要があるため、暗号文より属性が少ない鍵では復号できない
+        // 有効期限のタグの検証（ペアリング）は、この確認を通過した後に行う
+        let expired = private_key.expired_attributes(current_time)?;
+        let key_num_attrs = private_key.attributes.len().saturating_sub(expired.len());
+        
+        if ciphertext_num_attrs > key_num_attrs && !expired.is_empty() {
+            return Err(expired_attributes_error(private_key, &expired, current_time));
+        }
+        if ciphertext_num_attrs > key_num_attrs {
+            return Err(decrypt_error(DecryptErrorCode::CountMismatch, &format!(
+                "属性が一致しません: 暗号文は{}個の属性を必要としますが、秘密鍵は{}個の属性しか持っていません。暗号化時に使用した属性をすべて持つ鍵が必要です。",
+                ciphertext_num_attrs,
+                key_num_attrs
+            )));
+        }
+        
+        Ok((valid_until, hash))
+    }
+
+    /// 属性ごとの有効期限のタグを検証し、有効期限内の属性と鍵コンポーネントだけを返す
+    /// タグと一致しない（書き換えられた）有効期限は、壊れた秘密鍵として拒否する
+    fn active_key_components(
+        &self,
+        private_key: &ABEPrivateKey,
+        key_components: Vec<miracl_core::bn254::ecp2::ECP2>,
+        current_time: Option<u64>,
+        hash: AttributeHash,
+    ) -> Result<ActiveKeyComponents, JsValue> {
+        for expiry in &private_key.expirations {
+            let (Some(attribute), Some(key_component)) =
+                (private_key.attributes.get(expiry.index), key_components.get(expiry.index))
+            else {
+                return Err(decrypt_error(DecryptErrorCode::Corrupt, "秘密鍵の有効期限の対象となる属性がありません"));
+            };
+            
+            let tag = ecp_from_bytes(&expiry.tag, self.strict)?;
+            if !ABEImpl::verify_expiry_tag(key_component, attribute, expiry.expires_at, &tag, hash) {
+                return Err(decrypt_error(DecryptErrorCode::Corrupt, &format!(
+                    "属性「{}」の有効期限が鍵コンポーネントと一致しません",
+                    attribute
+                )));
+            }
+        }
+        
+        let expired_indices: Vec<usize> = private_key
+            .expired_attributes(current_time)?
+            .iter()
+            .map(|expiry| expiry.index)
+            .collect();
+        let (attributes, components) = private_key
+            .attributes
+            .iter()
+            .cloned()
+            .zip(key_components)
+            .enumerate()
+            .filter(|(index, _)| !expired_indices.contains(index))
+            .map(|(_, pair)| pair)
+            .unzip();
+        
+        Ok(ActiveKeyComponents {
+            attributes,
+            components,
+        })
     }
 }
 
 // KP-ABE実装（Miracl Coreを使用）
 // KP-ABE (Key-Policy Attribute-Based Encryption) スキームの実装
 #[wasm_bindgen]
+#[derive(Clone, Default)]
 pub struct KPABE {
     // KP-ABEスキームの実装
+    // 非正規な点のエンコーディングを拒否するか
+    strict: bool,
 }
 
 #[wasm_bindgen]
 impl KPABE {
     #[wasm_bindgen(constructor)]
     pub fn new() -> KPABE {
-        KPABE {}
+        KPABE { strict: false }
+    }
+
+    /// strictモードを取得
+    #[wasm_bindgen(getter)]
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// strictモードを設定
+    /// 有効にすると、復号時に非正規な点のエンコーディングを拒否する
+    #[wasm_bindgen(setter)]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
     }
 
     /// マスター鍵ペアを生成
     /// KP-ABEスキームのSetupアルゴリズム
     #[wasm_bindgen]
     pub fn setup(&self) -> Result<JsValue, JsValue> {
-        use miracl_core::bn254::ecp::ECP;
-        
         // マスター鍵ペアを生成
-        let (alpha, p_pub) = KPABEImpl::setup();
+        let (alpha, p_pub) = KPABEImpl::setup()?;
         
         // マスター秘密鍵をバイト列に変換
         let mut master_key_bytes = vec![0u8; 32];
@@ -375,13 +2623,8 @@ impl KPABE {
         master_key: &ABEMasterKey,
         policy: &str,
     ) -> Result<ABEPrivateKey, JsValue> {
-        use miracl_core::bn254::{big::BIG, ecp2::ECP2};
-        
         // マスター秘密鍵をBIGに変換
-        if master_key.secret.len() != 32 {
-            return Err(JsValue::from_str("マスター鍵の長さが不正です"));
-        }
-        let alpha = BIG::frombytes(&master_key.secret);
+        let alpha = import_master_scalar(&master_key.secret)?;
         
         // ポリシーから属性を抽出（簡易実装: カンマ区切り）
         let policy_attributes: Vec<String> = policy
@@ -397,8 +2640,8 @@ impl KPABE {
         // 秘密鍵コンポーネントを生成
         let key_components = KPABEImpl::key_gen(&alpha, &policy_attributes);
         
-        // 鍵コンポーネントをバイト列に変換
-        let mut key_bytes = Vec::new();
+        // 鍵コンポーネントをバイト列に変換（header (2バイト) || 鍵コンポーネントの形式）
+        let mut key_bytes = header(SCHEME_TAG_KP_ABE);
         for key_comp in &key_components {
             let mut comp_bytes = vec![0u8; 130];
             key_comp.tobytes(&mut comp_bytes, false);
@@ -408,6 +2651,9 @@ impl KPABE {
         Ok(ABEPrivateKey {
             key: key_bytes,
             attributes: policy_attributes,
+            expirations: Vec::new(),
+            authority: authority_id(&public_params_bytes(&alpha)),
+            lsss_key: Vec::new(),
         })
     }
 
@@ -421,28 +2667,22 @@ impl KPABE {
         attributes: Vec<String>,
         message: &[u8],
     ) -> Result<Vec<u8>, JsValue> {
-        use miracl_core::bn254::ecp::ECP;
-        
         // 公開パラメータをECPに変換
-        if public_params.params.len() < 65 {
-            return Err(JsValue::from_str("公開パラメータの長さが不正です"));
-        }
-        let p_pub = ECP::frombytes(&public_params.params);
+        let p_pub = public_params.point()?;
         
         if attributes.is_empty() {
             return Err(JsValue::from_str("属性セットには少なくとも1つの属性が必要です"));
         }
+        check_max_attributes(attributes.len())?;
         
-        // メッセージを暗号化
-        let (c0, v, c_attrs) = KPABEImpl::encrypt(&p_pub, &attributes, message);
+        // メッセージを暗号化（対称鍵はスキーム・属性に束縛する）
+        let context = kdf_context(SCHEME_TAG_KP_ABE, 0, &attributes);
+        let (c0, v, c_attrs) = KPABEImpl::encrypt(&p_pub, &attributes, message, &context)?;
         
-        // 暗号文をバイト列に変換（num_attrs (1バイト) || C0 (65バイト) || V (可変長) || C_attrsの形式）
+        // 暗号文をバイト列に変換（header (2バイト) || num_attrs (1バイト) || C0 (65バイト) || V (可変長) || C_attrsの形式）
         let num_attrs = c_attrs.len();
-        if num_attrs > 255 {
-            return Err(JsValue::from_str("属性が多すぎます（最大255個）"));
-        }
-        
-        let mut ciphertext = vec![num_attrs as u8]; // 属性数を先頭に保存
+        let mut ciphertext = sealed_header(SCHEME_TAG_KP_ABE);
+        ciphertext.push(num_attrs as u8); // ヘッダの後に属性数を保存
         
         // C0を追加
         let mut c0_bytes = vec![0u8; 65];
@@ -462,6 +2702,63 @@ impl KPABE {
         Ok(ciphertext)
     }
 
+    /// 旧形式（ヘッダなし）・AES-256-GCMの導入前の形式の暗号文を現在の形式に移行
+    /// 以前の形式として復号し、秘密鍵のポリシーの属性で現在の形式に暗号化し直す
+    /// 既にAES-256-GCMの暗号文はそのまま返すため、移行ジョブを繰り返し実行しても安全
+    /// 注意: 以前の形式は認証されていないため、誤った鍵を渡すと無意味なデータが暗号化し直される
+    /// 旧方式の属性ハッシュ（バージョン1）の暗号文は旧方式の秘密鍵で復号し、現在の属性ハッシュで暗号化し直すため、
+    /// 移行後の暗号文の復号には再発行した秘密鍵が必要
+    #[wasm_bindgen]
+    pub fn upgrade_ciphertext(
+        &self,
+        old_bytes: &[u8],
+        private_key: &ABEPrivateKey,
+        public_params: &ABEPublicParams,
+    ) -> Result<Vec<u8>, JsValue> {
+        if is_current_format(old_bytes, SCHEME_TAG_KP_ABE) {
+            if old_bytes[0] & GCM_PAYLOAD_FLAG != 0 {
+                return Ok(old_bytes.to_vec());
+            }
+            // AES-256-GCMの導入前の形式（Vが対称鍵とのXOR）は、復号して新しい乱数で暗号化し直す
+            let message = self.decrypt_framed(private_key, old_bytes, DecryptMode::Migration)?;
+            return self.encrypt(public_params, private_key.attributes.clone(), &message);
+        }
+        
+        // 旧形式はヘッダを除いた現在の形式と同じ構造（属性は旧方式でハッシュされている）
+        let mut framed = vec![SCHEME_TAG_KP_ABE, LEGACY_ATTRIBUTE_HASH_VERSION];
+        framed.extend_from_slice(old_bytes);
+        // 旧形式の対称鍵は文脈を含まない方式で導出されている
+        let message = self.decrypt_framed(private_key, &framed, DecryptMode::Headerless)?;
+        
+        // 新しい乱数で暗号化し直す
+        self.encrypt(public_params, private_key.attributes.clone(), &message)
+    }
+
+    /// 属性セットからメッセージを暗号化し、base64url（パディングなし）の文字列で返す
+    /// URLやQRコードに埋め込むためのもので、decrypt_from_stringで復号できる
+    #[wasm_bindgen]
+    pub fn encrypt_to_string(
+        &self,
+        public_params: &ABEPublicParams,
+        attributes: Vec<String>,
+        message: &[u8],
+    ) -> Result<String, JsValue> {
+        let ciphertext = self.encrypt(public_params, attributes, message)?;
+        Ok(URL_SAFE_NO_PAD.encode(ciphertext))
+    }
+
+    /// base64url文字列の暗号文を復号化
+    /// 空白やパディング（=）が含まれていても受け付ける
+    #[wasm_bindgen]
+    pub fn decrypt_from_string(
+        &self,
+        private_key: &ABEPrivateKey,
+        encoded: &str,
+    ) -> Result<Vec<u8>, JsValue> {
+        let ciphertext = decode_ciphertext_string(encoded)?;
+        self.decrypt(private_key, &ciphertext)
+    }
+
     /// 暗号文を復号化
     /// KP-ABEスキームのDecryptアルゴリズム
     /// 注意: 簡易実装。実際のKP-ABEでは、ポリシー満足性のチェックが必要
@@ -471,39 +2768,82 @@ impl KPABE {
         private_key: &ABEPrivateKey,
         ciphertext: &[u8],
     ) -> Result<Vec<u8>, JsValue> {
-        use miracl_core::bn254::{ecp::ECP, ecp2::ECP2};
-        
-        if ciphertext.len() < 66 {
-            return Err(JsValue::from_str("暗号文が短すぎます"));
+        self.decrypt_framed(private_key, ciphertext, DecryptMode::Authenticated)
+    }
+
+    /// 設定（strictモードなど）を引き継いだ複製を作る（JSではclone()）
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> KPABE {
+        self.clone()
+    }
+}
+
+impl KPABE {
+    /// 暗号文を復号化（decrypt・upgrade_ciphertextの共通処理）
+    /// AES-256-GCMの暗号文は認証タグを、平文のハッシュ付きの暗号文は復号後にハッシュを照合してから平文を返す
+    /// modeがAuthenticatedの場合、AES-256-GCMのフラグのない暗号文は復号せずにエラーを返す
+    fn decrypt_framed(
+        &self,
+        private_key: &ABEPrivateKey,
+        ciphertext: &[u8],
+        mode: DecryptMode,
+    ) -> Result<Vec<u8>, JsValue> {
+        let (ciphertext, hashed, payload) = strip_payload_flags(ciphertext);
+        mode.check_payload(payload)?;
+        let decrypted = self.decrypt_unflagged(private_key, &ciphertext, mode, payload)?;
+        if hashed {
+            return verify_plaintext_hash(decrypted);
+        }
+        Ok(decrypted)
+    }
+
+    /// 平文のハッシュ・AES-256-GCMのフラグを外した暗号文を、payloadの方式でVを復号化
+    /// modeがHeaderlessの場合は旧方式で対称鍵を導出する（旧形式の暗号文の移行用）
+    fn decrypt_unflagged(
+        &self,
+        private_key: &ABEPrivateKey,
+        ciphertext: &[u8],
+        mode: DecryptMode,
+        payload: Payload,
+    ) -> Result<Vec<u8>, JsValue> {
+        if ciphertext.len() < HEADER_SIZE + 66 {
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文が短すぎます"));
         }
         
-        // 暗号文を解析（num_attrs (1バイト) || C0 (65バイト) || V (可変長) || C_attrsの形式）
-        let ciphertext_num_attrs = ciphertext[0] as usize;
-        let c0_start = 1;
+        // 暗号文と秘密鍵のヘッダ（スキーム・属性ハッシュバージョン）を確認
+        check_headers(ciphertext, SCHEME_TAG_KP_ABE, &private_key.key, SCHEME_TAG_KP_ABE)?;
+        
+        // 暗号文を解析（header (2バイト) || num_attrs (1バイト) || C0 (65バイト) || V (可変長) || C_attrsの形式）
+        let ciphertext_num_attrs = ciphertext[HEADER_SIZE] as usize;
+        check_ciphertext_num_attrs(ciphertext_num_attrs)?;
+        let c0_start = HEADER_SIZE + 1;
         let c0_end = c0_start + 65;
         
         if ciphertext.len() < c0_end {
-            return Err(JsValue::from_str("暗号文にC0コンポーネントがありません"));
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文にC0コンポーネントがありません"));
         }
         
-        let c0 = ECP::frombytes(&ciphertext[c0_start..c0_end]);
-        
         // 暗号化時の属性数と秘密鍵の属性数（ポリシー）を比較
+        // 曲線演算の前に確認し、誤った鍵での復号の試行を安価に打ち切る
         let key_num_attrs = private_key.attributes.len();
         
         if ciphertext_num_attrs != key_num_attrs {
-            return Err(JsValue::from_str(&format!(
+            return Err(decrypt_error(DecryptErrorCode::CountMismatch, &format!(
                 "属性が一致しません: 暗号文は{}個の属性を使用していますが、秘密鍵のポリシーは{}個の属性を持っています。暗号化時に使用した属性と鍵生成時に使用したポリシーの属性が一致する必要があります。",
                 ciphertext_num_attrs,
                 key_num_attrs
             )));
         }
         
+        let c0 = ecp_from_bytes(&ciphertext[c0_start..c0_end], self.strict)?;
+        
+        // 長さの計算はすべてオーバーフロー・アンダーフローを検査して行う
         let attr_component_size = 130;
-        let expected_min_size = c0_end + ciphertext_num_attrs * attr_component_size;
+        let attrs_size = checked_length(ciphertext_num_attrs.checked_mul(attr_component_size))?;
+        let expected_min_size = checked_length(c0_end.checked_add(attrs_size))?;
         
         if ciphertext.len() < expected_min_size {
-            return Err(JsValue::from_str(&format!(
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, &format!(
                 "暗号文が不正です: 最低{}バイト必要ですが、{}バイトしかありません",
                 expected_min_size,
                 ciphertext.len()
@@ -511,50 +2851,41 @@ impl KPABE {
         }
         
         // Vを抽出（C0の後、属性コンポーネントの前）
+        // 上の長さ検査により ciphertext.len() >= c0_end + attrs_size が保証されている
         let v_start = c0_end;
-        let v_end = ciphertext.len() - (ciphertext_num_attrs * attr_component_size);
+        let v_end = checked_length(ciphertext.len().checked_sub(attrs_size))?;
         
         if v_end <= v_start {
-            return Err(JsValue::from_str("暗号文のVコンポーネントが空または不正です"));
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "暗号文のVコンポーネントが空または不正です"));
         }
         
         let v = &ciphertext[v_start..v_end];
         
-        // 属性コンポーネントを抽出
+        // 属性コンポーネントを抽出（残りの長さはちょうど属性数 × コンポーネントサイズ）
         let mut c_attrs = Vec::new();
-        for i in 0..ciphertext_num_attrs {
-            let start = v_end + (i * attr_component_size);
-            let end = start + attr_component_size;
-            if end > ciphertext.len() {
-                return Err(JsValue::from_str("暗号文の属性コンポーネントが範囲外です"));
-            }
-            let c_attr = ECP2::frombytes(&ciphertext[start..end]);
+        for chunk in ciphertext[v_end..].chunks_exact(attr_component_size) {
+            let c_attr = ecp2_from_bytes(chunk, self.strict)?;
             c_attrs.push(c_attr);
         }
         
         // 秘密鍵コンポーネントを抽出
         let mut key_components = Vec::new();
-        let key_bytes = &private_key.key;
+        let key_bytes = &private_key.key[HEADER_SIZE..];
         let key_component_size = 130;
+        let keys_size = checked_length(key_num_attrs.checked_mul(key_component_size))?;
         
-        if key_bytes.len() < key_num_attrs * key_component_size {
-            return Err(JsValue::from_str("秘密鍵に鍵コンポーネントが不足しています"));
+        if key_bytes.len() < keys_size {
+            return Err(decrypt_error(DecryptErrorCode::Corrupt, "秘密鍵に鍵コンポーネントが不足しています"));
         }
         
-        for i in 0..key_num_attrs {
-            let start = i * key_component_size;
-            let end = start + key_component_size;
-            if end > key_bytes.len() {
-                return Err(JsValue::from_str("秘密鍵の鍵コンポーネントが範囲外です"));
-            }
-            let key_comp = ECP2::frombytes(&key_bytes[start..end]);
+        for chunk in key_bytes[..keys_size].chunks_exact(key_component_size) {
+            let key_comp = ecp2_from_bytes(chunk, self.strict)?;
             key_components.push(key_comp);
         }
         
         // 暗号文を復号化
-        let message = KPABEImpl::decrypt(&key_components, &c0, v, &c_attrs);
-        
-        Ok(message)
+        let context = kdf_context(SCHEME_TAG_KP_ABE, 0, &private_key.attributes);
+        authenticated(KPABEImpl::decrypt(&key_components, &c0, v, &c_attrs, mode.key_derivation(&context), payload))
     }
 }
 
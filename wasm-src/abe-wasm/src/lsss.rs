@@ -0,0 +1,266 @@
+// ブール式のアクセスポリシーを線形秘密分散方式（LSSS）の行列に変換する内部モジュール
+// 行列Mの各行には属性のラベルが付き、秘密sはλ = M·(s, y_2, ..., y_d)（y_iは乱数）として行ごとに分散する
+// 属性セットがポリシーを満たすのは、その属性の行の線形結合で (1, 0, ..., 0) を作れる場合に限られ、
+// そのときの係数ω（Σ ω_i M_i = (1, 0, ..., 0)）を使うと s = Σ ω_i λ_i で秘密を復元できる
+//
+// 変換はLewko-Watersの方法をしきい値ゲートに一般化したもの:
+//   根のベクトルは (1)。k-of-nのゲートでは新しい列をk-1個追加し、子i（1始まり）のベクトルを
+//   親のベクトル || (i, i^2, ..., i^{k-1}) とする（子のシェアは親のシェアを定数項とするk-1次多項式の f(i) になる）
+//   ANDはn-of-n、ORは1-of-n（子は親と同じベクトル）、"k of (...)" はk-of-nとして扱う
+// 列数は 1 + Σ(k-1) で、葉の数を超えない
+//
+// 直列化した行列の形式:
+//   lsss := 行数 (1バイト) || 列数 (1バイト) || 行数 × row
+//   row  := len(attr) (1バイト) || attr || 列数 × 要素 (32バイト) || C_i (130バイト) || D_i (65バイト)
+// 要素は曲線の位数を法とする値のビッグエンディアン表現
+// C_i・D_iは行のシェアを隠すWaters方式の暗号文コンポーネント（ABEImpl::encrypt_lsssを参照）
+
+use miracl_core::bn254::{big::BIG, rom};
+
+use crate::abe_impl::{ABEImpl, RngFailure};
+use crate::policy::Policy;
+
+/// 行列の要素のサイズ
+const ELEMENT_SIZE: usize = 32;
+
+/// 行の暗号文コンポーネントのサイズ（C_i (G2の点) || D_i (G1の点)）
+pub const ROW_COMPONENTS_SIZE: usize = 130 + 65;
+
+/// 暗号化できるポリシーか確認
+/// NOTと時間制限属性は線形秘密分散で表現できないため拒否する
+/// 属性名は1〜255バイト、ゲートの子は255個までとする（いずれも1バイトで保存する）
+pub fn check_encryptable(policy: &Policy) -> Result<(), String> {
+    match policy {
+        Policy::Attribute(name) => {
+            if name.is_empty() || name.len() > u8::MAX as usize {
+                return Err(format!("属性名は1〜{}バイトで指定してください: 「{}」", u8::MAX, name));
+            }
+            Ok(())
+        }
+        Policy::ValidUntil(_) => Err("ブール式のポリシーには時間制限属性を指定できません".to_string()),
+        Policy::Not(_) => Err("ブール式のポリシーの暗号化ではNOTを使用できません（AND・OR・k of (...)のみ）".to_string()),
+        Policy::And(children) | Policy::Or(children) | Policy::Threshold { children, .. } => {
+            if children.len() > u8::MAX as usize {
+                return Err(format!("1つの演算子に指定できる条件は{}個までです", u8::MAX));
+            }
+            children.iter().try_for_each(check_encryptable)
+        }
+    }
+}
+
+/// ゲートのしきい値と子ポリシー（葉の場合はNone）
+fn gate(policy: &Policy) -> Option<(usize, &[Policy])> {
+    match policy {
+        Policy::And(children) => Some((children.len(), children)),
+        Policy::Or(children) => Some((1, children)),
+        Policy::Threshold { k, children } => Some((*k, children)),
+        _ => None,
+    }
+}
+
+/// 属性のラベル付きのLSSS行列
+pub struct LsssMatrix {
+    /// 行のラベル（属性名）
+    pub labels: Vec<String>,
+    /// 行ベクトル（要素は曲線の位数を法とする値、列数はすべての行で同じ）
+    pub rows: Vec<Vec<BIG>>,
+}
+
+impl LsssMatrix {
+    /// ポリシーをLSSS行列に変換する（ポリシーはcheck_encryptableで確認済み）
+    pub fn from_policy(policy: &Policy) -> LsssMatrix {
+        let mut matrix = LsssMatrix {
+            labels: Vec::new(),
+            rows: Vec::new(),
+        };
+        let mut columns = 1;
+        matrix.push_node(policy, vec![BIG::new_int(1)], &mut columns);
+
+        // 後から追加した列の分を0で埋めて、すべての行の列数をそろえる
+        for row in &mut matrix.rows {
+            row.resize(columns, BIG::new());
+        }
+        matrix
+    }
+
+    fn push_node(&mut self, policy: &Policy, vector: Vec<BIG>, columns: &mut usize) {
+        if let Some((k, children)) = gate(policy) {
+            let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
+            let first_column = *columns;
+            *columns += k - 1;
+
+            for (i, child) in children.iter().enumerate() {
+                // 親のベクトル（途中で列が増えていれば0で埋める）|| (x, x^2, ..., x^{k-1})、x = i + 1
+                let x = BIG::new_int(i as isize + 1);
+                let mut child_vector = vector.clone();
+                child_vector.resize(first_column, BIG::new());
+                let mut power = BIG::new_int(1);
+                for _ in 1..k {
+                    power = BIG::modmul(&power, &x, &curve_order);
+                    child_vector.push(power);
+                }
+                self.push_node(child, child_vector, columns);
+            }
+        } else if let Policy::Attribute(name) = policy {
+            self.labels.push(name.clone());
+            self.rows.push(vector);
+        }
+    }
+
+    /// 列数
+    pub fn columns(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    /// 秘密sを行ごとのシェア λ = M·(s, y_2, ..., y_d) に分散する
+    pub fn share(&self, secret: &BIG) -> Result<Vec<BIG>, RngFailure> {
+        let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
+
+        let mut v = vec![BIG::new_copy(secret)];
+        for _ in 1..self.columns() {
+            v.push(ABEImpl::random_big()?);
+        }
+
+        Ok(self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter().zip(&v).fold(BIG::new(), |acc, (m, v)| {
+                    BIG::modadd(&acc, &BIG::modmul(m, v, &curve_order), &curve_order)
+                })
+            })
+            .collect())
+    }
+
+    /// 属性セットで秘密を復元するための係数を計算する
+    /// 属性がラベルの行だけを使い、Σ ω_i M_i = (1, 0, ..., 0) となる (行の番号, ω_i) を返す（ω_i = 0の行は含まない）
+    /// 属性セットがポリシーを満たさない（解がない）場合はNoneを返す
+    /// 行列とラベルは公開されているため、掃き出し法の計算がサイドチャネル安全でなくても問題ない
+    pub fn reconstruction_coefficients(&self, attributes: &[String]) -> Option<Vec<(usize, BIG)>> {
+        let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
+        let candidates: Vec<usize> = (0..self.rows.len())
+            .filter(|&i| attributes.contains(&self.labels[i]))
+            .collect();
+        let columns = self.columns();
+
+        // 転置した連立方程式 M_I^T ω = (1, 0, ..., 0) の拡大係数行列（列数 × (候補の行数 + 1)）
+        let unknowns = candidates.len();
+        let mut system: Vec<Vec<BIG>> = (0..columns)
+            .map(|c| {
+                let mut equation: Vec<BIG> = candidates.iter().map(|&i| self.rows[i][c]).collect();
+                equation.push(BIG::new_int(if c == 0 { 1 } else { 0 }));
+                equation
+            })
+            .collect();
+
+        // 掃き出し法で簡約化し、各式のピボットの未知数を記録する
+        let mut pivots = Vec::new();
+        let mut rank = 0;
+        for unknown in 0..unknowns {
+            let Some(pivot_row) = (rank..columns).find(|&r| !system[r][unknown].iszilch()) else {
+                continue;
+            };
+            system.swap(rank, pivot_row);
+
+            let mut inverse = system[rank][unknown];
+            inverse.invmodp(&curve_order);
+            for value in &mut system[rank] {
+                *value = BIG::modmul(value, &inverse, &curve_order);
+            }
+
+            for r in 0..columns {
+                if r == rank || system[r][unknown].iszilch() {
+                    continue;
+                }
+                let factor = BIG::modneg(&system[r][unknown], &curve_order);
+                let pivot = system[rank].clone();
+                for (value, p) in system[r].iter_mut().zip(&pivot).skip(unknown) {
+                    *value = BIG::modadd(value, &BIG::modmul(&factor, p, &curve_order), &curve_order);
+                }
+            }
+            pivots.push(unknown);
+            rank += 1;
+        }
+
+        // ピボットのない式の右辺が0でなければ解がない
+        if system[rank..].iter().any(|equation| !equation[unknowns].iszilch()) {
+            return None;
+        }
+
+        // 自由な未知数は0とし、ピボットの未知数に右辺の値を割り当てる
+        Some(
+            pivots
+                .iter()
+                .zip(&system)
+                .filter(|(_, equation)| !equation[unknowns].iszilch())
+                .map(|(&unknown, equation)| (candidates[unknown], equation[unknowns]))
+                .collect(),
+        )
+    }
+
+    /// 行の暗号文コンポーネント（行の順、各ROW_COMPONENTS_SIZEバイト）と合わせて行列を直列化する
+    pub fn to_bytes(&self, row_components: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = vec![self.rows.len() as u8, self.columns() as u8];
+        for ((label, row), components) in self.labels.iter().zip(&self.rows).zip(row_components) {
+            bytes.push(label.len() as u8);
+            bytes.extend_from_slice(label.as_bytes());
+            for element in row {
+                let mut element_bytes = [0u8; ELEMENT_SIZE];
+                element.tobytes(&mut element_bytes);
+                bytes.extend_from_slice(&element_bytes);
+            }
+            bytes.extend_from_slice(components);
+        }
+        bytes
+    }
+}
+
+/// 暗号文から解析したLSSS行列
+pub struct ParsedMatrix<'a> {
+    pub matrix: LsssMatrix,
+    /// 行の暗号文コンポーネント（行の順）
+    pub row_components: Vec<&'a [u8]>,
+}
+
+/// 直列化した行列を解析し、行列と残りのバイト列を返す
+pub fn parse(bytes: &[u8]) -> Result<(ParsedMatrix<'_>, &[u8]), String> {
+    let too_short = || "LSSS行列が短すぎます".to_string();
+    let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
+
+    let (&[num_rows, columns], mut rest) = bytes.split_first_chunk::<2>().ok_or_else(too_short)?;
+    let (num_rows, columns) = (num_rows as usize, columns as usize);
+    if num_rows == 0 || columns == 0 {
+        return Err("LSSS行列の大きさが不正です".to_string());
+    }
+
+    let mut matrix = LsssMatrix {
+        labels: Vec::with_capacity(num_rows),
+        rows: Vec::with_capacity(num_rows),
+    };
+    let mut row_components = Vec::with_capacity(num_rows);
+    for _ in 0..num_rows {
+        let (&len, after_len) = rest.split_first().ok_or_else(too_short)?;
+        let (label, after_label) = after_len.split_at_checked(len as usize).ok_or_else(too_short)?;
+        let label = std::str::from_utf8(label).map_err(|_| "LSSS行列の属性名が不正です".to_string())?;
+
+        let (elements, after_elements) = after_label.split_at_checked(columns * ELEMENT_SIZE).ok_or_else(too_short)?;
+        let row = elements
+            .chunks_exact(ELEMENT_SIZE)
+            .map(|chunk| {
+                let element = BIG::frombytes(chunk);
+                if BIG::comp(&element, &curve_order) >= 0 {
+                    return Err("LSSS行列の要素が曲線の位数以上です".to_string());
+                }
+                Ok(element)
+            })
+            .collect::<Result<Vec<BIG>, String>>()?;
+
+        let (components, after_row) = after_elements.split_at_checked(ROW_COMPONENTS_SIZE).ok_or_else(too_short)?;
+        matrix.labels.push(label.to_string());
+        matrix.rows.push(row);
+        row_components.push(components);
+        rest = after_row;
+    }
+
+    Ok((ParsedMatrix { matrix, row_components }, rest))
+}
@@ -0,0 +1,321 @@
+// アクセスポリシーの内部モジュール
+// ポリシー文字列の解析と、属性セットがポリシーを満たすかの評価を行う
+//
+// 構文:
+//   policy    := list
+//   list      := or_expr ("," or_expr)*          カンマ区切りはANDとして扱う（従来形式）
+//   or_expr   := and_expr ("OR" and_expr)*
+//   and_expr  := unary ("AND" unary)*
+//   unary     := "NOT" unary | primary
+//   primary   := "(" list ")" | k "of" "(" or_expr ("," or_expr)* ")" | attribute
+// キーワード（AND, OR, NOT, of）は大文字・小文字を区別しない
+// "valid_until:<UNIX時刻>" は時間制限の葉で、同じ時刻かそれ以前の valid_until 属性を持つ鍵が満たす
+// 括弧・閾値・NOTの入れ子の深さには上限があり、悪意のある深い入れ子でスタックが溢れないようにしている
+
+/// 時間制限属性の接頭辞（例: "valid_until:1735689600"）
+pub const VALID_UNTIL_PREFIX: &str = "valid_until:";
+
+/// ポリシーの入れ子の深さの既定の上限
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// 時間制限属性からUNIX時刻（秒）を取り出す
+/// 時間制限属性でない場合はNoneを返す
+pub fn parse_valid_until(attribute: &str) -> Result<Option<u64>, String> {
+    match attribute.strip_prefix(VALID_UNTIL_PREFIX) {
+        Some(timestamp) => timestamp
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("時間制限属性の時刻が不正です: {}", attribute)),
+        None => Ok(None),
+    }
+}
+
+/// アクセスポリシーの構文木
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Policy {
+    /// 単一の属性
+    Attribute(String),
+    /// 時間制限（指定時刻以前に失効する鍵のみが満たす）
+    ValidUntil(u64),
+    /// すべての子ポリシーを満たす
+    And(Vec<Policy>),
+    /// いずれかの子ポリシーを満たす
+    Or(Vec<Policy>),
+    /// 子ポリシーを満たさない
+    Not(Box<Policy>),
+    /// n個の子ポリシーのうちk個以上を満たす
+    Threshold { k: usize, children: Vec<Policy> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    LParen,
+    RParen,
+    Comma,
+    Word(String),
+}
+
+/// ポリシー文字列をトークンに分割
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+
+    for ch in input.chars() {
+        let token = match ch {
+            '(' => Some(Token::LParen),
+            ')' => Some(Token::RParen),
+            ',' => Some(Token::Comma),
+            c if c.is_whitespace() => None,
+            c => {
+                word.push(c);
+                continue;
+            }
+        };
+
+        if !word.is_empty() {
+            tokens.push(Token::Word(std::mem::take(&mut word)));
+        }
+        if let Some(token) = token {
+            tokens.push(token);
+        }
+    }
+
+    if !word.is_empty() {
+        tokens.push(Token::Word(word));
+    }
+
+    tokens
+}
+
+impl Token {
+    /// エラーメッセージ用の表記
+    fn describe(&self) -> String {
+        match self {
+            Token::LParen => "「(」".to_string(),
+            Token::RParen => "「)」".to_string(),
+            Token::Comma => "「,」".to_string(),
+            Token::Word(word) => format!("「{}」", word),
+        }
+    }
+}
+
+/// キーワードかどうかを判定（大文字・小文字を区別しない）
+fn is_keyword(token: Option<&Token>, keyword: &str) -> bool {
+    matches!(token, Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+}
+
+/// 再帰下降パーサ
+/// 再帰の深さをdepthで数え、max_depthを超えた時点でエラーにする
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// 入れ子を1段深くする（上限を超える場合はエラー）
+    fn descend(&mut self) -> Result<(), String> {
+        if self.depth >= self.max_depth {
+            return Err(format!("ポリシーの入れ子が深すぎます（上限: {}）", self.max_depth));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// 開き括弧に対応する閉じ括弧を読む
+    /// 閉じられないままポリシーが終了した場合は、括弧の対応が取れていないエラーにする
+    fn expect_close(&mut self) -> Result<(), String> {
+        match self.next() {
+            Some(Token::RParen) => Ok(()),
+            Some(token) => Err(format!("「)」が必要ですが、{}がありました", token.describe())),
+            None => Err("括弧の対応が取れていません: 「(」に対応する「)」がありません".to_string()),
+        }
+    }
+
+    /// list := or_expr ("," or_expr)*
+    fn parse_list(&mut self) -> Result<Policy, String> {
+        let mut children = vec![self.parse_or()?];
+        while self.peek() == Some(&Token::Comma) {
+            self.next();
+            children.push(self.parse_or()?);
+        }
+        Ok(Self::collapse(children, Policy::And))
+    }
+
+    /// or_expr := and_expr ("OR" and_expr)*
+    fn parse_or(&mut self) -> Result<Policy, String> {
+        let mut children = vec![self.parse_and()?];
+        while is_keyword(self.peek(), "OR") {
+            self.next();
+            children.push(self.parse_and()?);
+        }
+        Ok(Self::collapse(children, Policy::Or))
+    }
+
+    /// and_expr := unary ("AND" unary)*
+    fn parse_and(&mut self) -> Result<Policy, String> {
+        let mut children = vec![self.parse_unary()?];
+        while is_keyword(self.peek(), "AND") {
+            self.next();
+            children.push(self.parse_unary()?);
+        }
+        Ok(Self::collapse(children, Policy::And))
+    }
+
+    /// unary := "NOT" unary | primary
+    fn parse_unary(&mut self) -> Result<Policy, String> {
+        if is_keyword(self.peek(), "NOT") {
+            self.next();
+            self.descend()?;
+            let child = self.parse_unary()?;
+            self.depth -= 1;
+            return Ok(Policy::Not(Box::new(child)));
+        }
+        self.parse_primary()
+    }
+
+    /// primary := "(" list ")" | k "of" "(" or_expr ("," or_expr)* ")" | attribute
+    fn parse_primary(&mut self) -> Result<Policy, String> {
+        match self.next() {
+            Some(Token::LParen) => {
+                self.descend()?;
+                let policy = self.parse_list()?;
+                self.expect_close()?;
+                self.depth -= 1;
+                Ok(policy)
+            }
+            Some(Token::Word(word)) if is_keyword(self.peek(), "of") && self.peek_at(1) == Some(&Token::LParen) => {
+                let k: usize = word
+                    .parse()
+                    .map_err(|_| format!("閾値が数値ではありません: {}", word))?;
+                self.next(); // of
+                self.next(); // (
+                self.descend()?;
+
+                let mut children = vec![self.parse_or()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.next();
+                    children.push(self.parse_or()?);
+                }
+                self.expect_close()?;
+                self.depth -= 1;
+
+                if k == 0 || k > children.len() {
+                    return Err(format!(
+                        "閾値が不正です: {}個の条件に対して{}個を要求しています",
+                        children.len(),
+                        k
+                    ));
+                }
+                Ok(Policy::Threshold { k, children })
+            }
+            Some(Token::Word(word)) => {
+                if ["AND", "OR", "NOT"].iter().any(|kw| word.eq_ignore_ascii_case(kw)) {
+                    return Err(format!("属性が必要ですが、キーワード{}がありました", word));
+                }
+                match parse_valid_until(&word)? {
+                    Some(timestamp) => Ok(Policy::ValidUntil(timestamp)),
+                    None => Ok(Policy::Attribute(word)),
+                }
+            }
+            Some(Token::RParen) if self.depth == 0 => {
+                Err("括弧の対応が取れていません: 対応する「(」のない「)」があります".to_string())
+            }
+            Some(token) => Err(format!("属性が必要ですが、{}がありました", token.describe())),
+            None => Err("属性が必要ですが、ポリシーが終了しました".to_string()),
+        }
+    }
+
+    /// 子が1つだけの場合はそのまま返す
+    fn collapse(mut children: Vec<Policy>, gate: fn(Vec<Policy>) -> Policy) -> Policy {
+        if children.len() == 1 {
+            children.remove(0)
+        } else {
+            gate(children)
+        }
+    }
+}
+
+impl Policy {
+    /// ポリシー文字列を解析（入れ子の深さの上限は既定値）
+    pub fn parse(input: &str) -> Result<Policy, String> {
+        Self::parse_with_max_depth(input, DEFAULT_MAX_DEPTH)
+    }
+
+    /// 入れ子の深さの上限を指定してポリシー文字列を解析
+    /// 括弧・閾値・NOTがそれぞれ1段と数える（例: "((A))" は2段）
+    pub fn parse_with_max_depth(input: &str, max_depth: usize) -> Result<Policy, String> {
+        let tokens = tokenize(input);
+        if tokens.is_empty() {
+            return Err("ポリシーには少なくとも1つの属性が必要です".to_string());
+        }
+
+        let mut parser = Parser { tokens, pos: 0, depth: 0, max_depth };
+        let policy = parser.parse_list()?;
+
+        match parser.peek() {
+            Some(Token::RParen) => {
+                return Err("括弧の対応が取れていません: 対応する「(」のない「)」があります".to_string());
+            }
+            Some(token) => {
+                return Err(format!("ポリシーの末尾に不要なトークンがあります: {}", token.describe()));
+            }
+            None => {}
+        }
+        Ok(policy)
+    }
+
+    /// ポリシー文字列がブール式の構文（括弧・AND・OR・NOT）を含むかどうか
+    /// 含まない場合は従来のカンマ区切りの属性リストとして扱える
+    pub fn has_operators(input: &str) -> bool {
+        tokenize(input).iter().any(|token| match token {
+            Token::LParen | Token::RParen => true,
+            Token::Word(word) => ["AND", "OR", "NOT"].iter().any(|kw| word.eq_ignore_ascii_case(kw)),
+            Token::Comma => false,
+        })
+    }
+
+    /// ポリシーの葉（属性名・時間制限属性）を出現順に列挙
+    pub fn leaves(&self) -> Vec<String> {
+        match self {
+            Policy::Attribute(name) => vec![name.clone()],
+            Policy::ValidUntil(limit) => vec![format!("{}{}", VALID_UNTIL_PREFIX, limit)],
+            Policy::And(children) | Policy::Or(children) | Policy::Threshold { children, .. } => {
+                children.iter().flat_map(Policy::leaves).collect()
+            }
+            Policy::Not(child) => child.leaves(),
+        }
+    }
+
+    /// 属性セットがポリシーを満たすかを評価
+    pub fn is_satisfied_by(&self, attributes: &[String]) -> bool {
+        match self {
+            Policy::Attribute(name) => attributes.iter().any(|a| a == name),
+            Policy::ValidUntil(limit) => attributes
+                .iter()
+                .filter_map(|a| parse_valid_until(a).ok().flatten())
+                .any(|timestamp| timestamp <= *limit),
+            Policy::And(children) => children.iter().all(|c| c.is_satisfied_by(attributes)),
+            Policy::Or(children) => children.iter().any(|c| c.is_satisfied_by(attributes)),
+            Policy::Not(child) => !child.is_satisfied_by(attributes),
+            Policy::Threshold { k, children } => {
+                children.iter().filter(|c| c.is_satisfied_by(attributes)).count() >= *k
+            }
+        }
+    }
+}
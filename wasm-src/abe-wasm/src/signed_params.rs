@@ -0,0 +1,89 @@
+// 署名付き公開パラメータの内部モジュール
+// 中間者が公開パラメータを差し替えられないよう、権限者（鍵を発行する機関）の
+// ML-DSA-65（Dilithium）の秘密鍵で公開パラメータに署名し、クライアントが配布元を検証できるようにする
+//
+// 署名付き公開パラメータの形式:
+//   signed := version (1バイト) || 公開パラメータ (65バイト) || 署名 (ML-DSA-65)
+// 署名対象は label || 公開パラメータ で、他の用途の署名と取り違えないようにする
+// （dilithium-wasmのverify(label || 公開パラメータ, 署名, 公開鍵)でも同じ結果になる）
+
+use pqcrypto_std::mldsa::mldsa65::{PrivateKey, PublicKey, PRIVKEY_SIZE, PUBKEY_SIZE, SIG_SIZE};
+use pqcrypto_std::mldsa::{SigningKey, VerifyingKey};
+
+use crate::abe_impl::{RngFailure, WasmRAND};
+
+// 署名付き公開パラメータの形式のバージョン
+const SIGNED_PARAMS_VERSION: u8 = 1;
+
+// 署名対象のドメイン分離用ラベル
+const SIGNED_PARAMS_LABEL: &[u8] = b"abe-wasm public params v1";
+
+// 署名対象のメッセージ（label || 公開パラメータ）
+fn signed_message(params: &[u8]) -> Vec<u8> {
+    [SIGNED_PARAMS_LABEL, params].concat()
+}
+
+// 権限者の秘密鍵の形式を確認（乱数生成の失敗とは別に、呼び出し側で分かりやすいエラーを返すため）
+pub fn check_private_key(private_key: &[u8]) -> Result<(), String> {
+    if private_key.len() != PRIVKEY_SIZE {
+        return Err(format!(
+            "権限者のDilithiumの秘密鍵のサイズが不正です（期待値: {}、実際: {}）",
+            PRIVKEY_SIZE,
+            private_key.len()
+        ));
+    }
+    Ok(())
+}
+
+// 公開パラメータに署名し、署名付き公開パラメータを作成
+// private_keyのサイズはcheck_private_keyで確認済みであること
+pub fn sign(params: &[u8], private_key: &[u8]) -> Result<Vec<u8>, RngFailure> {
+    let mut sk_array = [0u8; PRIVKEY_SIZE];
+    sk_array.copy_from_slice(private_key);
+    let sk = PrivateKey::decode(&sk_array);
+    
+    let mut rng = WasmRAND::new();
+    let mut signature = [0u8; SIG_SIZE];
+    sk.sign(&mut signature, &mut rng, &signed_message(params));
+    if rng.failed() {
+        return Err(RngFailure);
+    }
+    
+    let mut signed = Vec::with_capacity(1 + params.len() + SIG_SIZE);
+    signed.push(SIGNED_PARAMS_VERSION);
+    signed.extend_from_slice(params);
+    signed.extend_from_slice(&signature);
+    Ok(signed)
+}
+
+// 署名付き公開パラメータの署名を検証し、公開パラメータのバイト列を返す
+pub fn verify<'a>(signed: &'a [u8], params_size: usize, public_key: &[u8]) -> Result<&'a [u8], String> {
+    let pk_array: &[u8; PUBKEY_SIZE] = public_key.try_into().map_err(|_| {
+        format!(
+            "権限者のDilithiumの公開鍵のサイズが不正です（期待値: {}、実際: {}）",
+            PUBKEY_SIZE,
+            public_key.len()
+        )
+    })?;
+    
+    let Some((&version, rest)) = signed.split_first() else {
+        return Err("署名付き公開パラメータが空です".to_string());
+    };
+    if version != SIGNED_PARAMS_VERSION {
+        return Err(format!("未対応の署名付き公開パラメータのバージョンです: {}", version));
+    }
+    let length_error = || {
+        format!(
+            "署名付き公開パラメータの長さが不正です（期待値: {}、実際: {}）",
+            1 + params_size + SIG_SIZE,
+            signed.len()
+        )
+    };
+    let (params, signature) = rest.split_at_checked(params_size).ok_or_else(length_error)?;
+    let signature: &[u8; SIG_SIZE] = signature.try_into().map_err(|_| length_error())?;
+    
+    PublicKey::decode(pk_array)
+        .verify(&signed_message(params), signature)
+        .map_err(|_| "公開パラメータの署名の検証に失敗しました（改ざんされているか、権限者の鍵が異なります）".to_string())?;
+    Ok(params)
+}
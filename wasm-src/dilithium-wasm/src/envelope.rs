@@ -0,0 +1,308 @@
+// 封筒形式（レベルタグ付き）の署名の内部モジュール
+// ML-DSAの署名にパラメータセット（レベル）を示すタグを付け、検証時に公開鍵のレベルと照合する
+// 別のレベルの署名と公開鍵の組み合わせは、サイズの不一致によるfalseではなく、明示的なエラーとして拒否する
+//
+// 封筒形式:
+//   envelope := level (1バイト) || 署名
+//             | (level | 0x80) (1バイト) || flags (1バイト) || [len(ctx) (1バイト) || ctx] || [鍵ハッシュ (64バイト)] || 署名
+//   level    := 44（ML-DSA-44）| 65（ML-DSA-65）| 87（ML-DSA-87）
+//   flags    := 0x01（コンテキスト文字列あり）| 0x02（鍵ハッシュあり）の組み合わせ
+// 署名部分は各レベルの通常の署名と同じで、FIPS 204のpureモードの M' = 0x00 || len(ctx) || ctx || M に署名する
+// コンテキスト文字列がない場合は空のコンテキスト文字列として扱う（署名部分はverifyでそのまま検証できる）
+// 鍵ハッシュはFIPS 204のtr = SHAKE256(公開鍵, 64バイト)で、秘密鍵に含まれる値をそのまま使う
+// 署名はコンテキスト文字列を含むM'に対するものなので、コンテキスト文字列を書き換えると検証に失敗する
+
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::Shake256;
+use wasm_bindgen::prelude::*;
+
+use crate::level::Level;
+use crate::rng::KeyRng;
+
+// 拡張封筒形式のタグ（レベルタグの最上位ビットを立てる）
+const EXTENDED_TAG_FLAG: u8 = 0x80;
+
+// 拡張封筒形式のflags
+const FLAG_CONTEXT: u8 = 0x01;
+const FLAG_KEY_HASH: u8 = 0x02;
+
+// コンテキスト文字列の最大サイズ（FIPS 204）
+const MAX_CONTEXT_SIZE: usize = 255;
+
+// 鍵ハッシュ（tr）のサイズと、秘密鍵内での位置（rho (32バイト) || K (32バイト) || tr）
+const KEY_HASH_SIZE: usize = 64;
+const PRIVATE_KEY_HASH_OFFSET: usize = 64;
+
+// pureモードのドメイン分離（FIPS 204 Algorithm 2 の M' の先頭バイト）
+const PURE_ML_DSA_DOMAIN: u8 = 0x00;
+
+// 封筒形式の署名を解析した結果
+struct Envelope<'a> {
+    level: Level,
+    context: Option<&'a [u8]>,
+    key_hash: Option<&'a [u8]>,
+    signature: &'a [u8],
+}
+
+/**
+ * メッセージに署名し、レベルタグ付きの封筒形式で返す
+ * レベルは秘密鍵のサイズから判定する
+ * コンテキスト文字列か鍵ハッシュを指定した場合は拡張封筒形式になる
+ *
+ * @param message 署名するメッセージ（バイト配列）
+ * @param private_key ML-DSA-44・65・87のいずれかの秘密鍵
+ * @param context コンテキスト文字列（最大255バイト、省略時はなし）。検証時に同じ値が必要になる
+ * @param include_key_hash 署名した鍵の鍵ハッシュを含めるか（省略時はfalse）
+ * @returns 封筒形式の署名（level (1バイト) || 署名、または拡張封筒形式）
+ */
+#[wasm_bindgen]
+pub fn sign_enveloped(
+    message: &[u8],
+    private_key: &[u8],
+    context: Option<Vec<u8>>,
+    include_key_hash: Option<bool>,
+) -> Result<Vec<u8>, JsValue> {
+    let level = Level::of_private_key(private_key).map_err(|e| JsValue::from_str(&e))?;
+    let include_key_hash = include_key_hash.unwrap_or(false);
+    
+    if let Some(context) = &context {
+        check_context(context).map_err(|e| JsValue::from_str(&e))?;
+    }
+    
+    let mut envelope = Vec::new();
+    if context.is_none() && !include_key_hash {
+        envelope.push(level.tag());
+    } else {
+        let mut flags = 0;
+        if context.is_some() {
+            flags |= FLAG_CONTEXT;
+        }
+        if include_key_hash {
+            flags |= FLAG_KEY_HASH;
+        }
+        envelope.push(level.tag() | EXTENDED_TAG_FLAG);
+        envelope.push(flags);
+        
+        if let Some(context) = &context {
+            envelope.push(context.len() as u8);
+            envelope.extend_from_slice(context);
+        }
+        if include_key_hash {
+            envelope.extend_from_slice(&private_key[PRIVATE_KEY_HASH_OFFSET..PRIVATE_KEY_HASH_OFFSET + KEY_HASH_SIZE]);
+        }
+    }
+    
+    let message = signed_message(context.as_deref().unwrap_or_default(), message);
+    let signature = level
+        .sign(&message, private_key, &mut KeyRng)
+        .map_err(|e| JsValue::from_str(&e))?;
+    envelope.extend_from_slice(&signature);
+    
+    Ok(envelope)
+}
+
+/**
+ * 封筒形式の署名を検証
+ * 署名のレベルタグと公開鍵のレベルが異なる場合は、falseではなくエラーを返す
+ * （別のレベルの署名を検証しようとしていることを、署名の偽造と区別できるようにするため）
+ * 鍵ハッシュを含む署名で、鍵ハッシュが公開鍵と一致しない場合もエラーを返す
+ *
+ * @param message 元のメッセージ（バイト配列）
+ * @param envelope sign_envelopedで生成した封筒形式の署名
+ * @param public_key ML-DSA-44・65・87のいずれかの公開鍵
+ * @returns 検証結果（true: 有効、false: 無効）。レベル・鍵ハッシュの不一致や形式が不正な場合はエラー
+ */
+#[wasm_bindgen]
+pub fn verify_enveloped(message: &[u8], envelope: &[u8], public_key: &[u8]) -> Result<bool, JsValue> {
+    check_envelope(envelope, public_key)
+        .map(|parsed| verify_parsed(&parsed, message, public_key))
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+/**
+ * 封筒形式の署名のメタデータを構造だけから読み取る
+ * 署名は検証しないため、返した値（特にコンテキスト文字列）は検証に成功するまで信頼しないこと
+ * 検証と同時に読み取る場合はverify_and_extractを使用する
+ *
+ * @param envelope 封筒形式の署名
+ * @returns { algo: "ML-DSA", level: "ML-DSA-44"など, context: コンテキスト文字列（Uint8Array、ない場合はnull）, has_keyhash: 鍵ハッシュの有無 }
+ */
+#[wasm_bindgen]
+pub fn read_signature_envelope(envelope: &[u8]) -> Result<JsValue, JsValue> {
+    let parsed = parse_envelope(envelope)
+        .and_then(|parsed| check_signature_size(parsed.level, parsed.signature).map(|_| parsed))
+        .map_err(|e| JsValue::from_str(&e))?;
+    envelope_metadata(&parsed)
+}
+
+/**
+ * 封筒形式の署名を検証し、成功した場合のみメタデータを返す
+ * 返した値はread_signature_envelopeと同じ形式
+ *
+ * @param message 元のメッセージ（バイト配列）
+ * @param envelope 封筒形式の署名
+ * @param public_key ML-DSA-44・65・87のいずれかの公開鍵
+ * @returns 署名のメタデータ。署名が無効な場合、またはverify_envelopedがエラーになる場合はエラー
+ */
+#[wasm_bindgen]
+pub fn verify_and_extract(message: &[u8], envelope: &[u8], public_key: &[u8]) -> Result<JsValue, JsValue> {
+    let parsed = check_envelope(envelope, public_key).map_err(|e| JsValue::from_str(&e))?;
+    if !verify_parsed(&parsed, message, public_key) {
+        return Err(JsValue::from_str("Signature verification failed"));
+    }
+    envelope_metadata(&parsed)
+}
+
+// いずれかのレベルのML-DSA公開鍵のサイズか確認（key_idで使用）
+pub(crate) fn check_public_key(public_key: &[u8]) -> Result<(), String> {
+    Level::of_public_key(public_key).map(|_| ())
+}
+
+// 封筒形式の署名のタグ・flags・コンテキスト文字列・鍵ハッシュを読み取る（署名のサイズは確認しない）
+fn parse_envelope(envelope: &[u8]) -> Result<Envelope<'_>, String> {
+    let Some((&tag, rest)) = envelope.split_first() else {
+        return Err("Empty signature envelope".to_string());
+    };
+    let level = Level::from_tag(tag & !EXTENDED_TAG_FLAG)
+        .ok_or_else(|| format!("Unknown ML-DSA level tag in signature envelope: 0x{:02x}", tag))?;
+    
+    if tag & EXTENDED_TAG_FLAG == 0 {
+        return Ok(Envelope {
+            level,
+            context: None,
+            key_hash: None,
+            signature: rest,
+        });
+    }
+    
+    let Some((&flags, mut rest)) = rest.split_first() else {
+        return Err("Truncated signature envelope: flags are missing".to_string());
+    };
+    // flagsが0の拡張封筒形式は通常の形式と同じ意味になるため、表現を1つにするために拒否する
+    if flags == 0 || flags & !(FLAG_CONTEXT | FLAG_KEY_HASH) != 0 {
+        return Err(format!("Invalid flags in signature envelope: 0x{:02x}", flags));
+    }
+    
+    let mut context = None;
+    if flags & FLAG_CONTEXT != 0 {
+        let Some((&length, after_length)) = rest.split_first() else {
+            return Err("Truncated signature envelope: context is missing".to_string());
+        };
+        if after_length.len() < length as usize {
+            return Err("Truncated signature envelope: context is missing".to_string());
+        }
+        let (value, after_context) = after_length.split_at(length as usize);
+        context = Some(value);
+        rest = after_context;
+    }
+    
+    let mut key_hash = None;
+    if flags & FLAG_KEY_HASH != 0 {
+        if rest.len() < KEY_HASH_SIZE {
+            return Err("Truncated signature envelope: key hash is missing".to_string());
+        }
+        let (value, after_key_hash) = rest.split_at(KEY_HASH_SIZE);
+        key_hash = Some(value);
+        rest = after_key_hash;
+    }
+    
+    Ok(Envelope {
+        level,
+        context,
+        key_hash,
+        signature: rest,
+    })
+}
+
+// 署名部分のサイズがレベルの署名のサイズと一致するか確認
+fn check_signature_size(level: Level, signature: &[u8]) -> Result<(), String> {
+    if signature.len() != level.signature_size() {
+        return Err(format!(
+            "Invalid {} signature size: expected {}, got {}",
+            level.name(),
+            level.signature_size(),
+            signature.len()
+        ));
+    }
+    Ok(())
+}
+
+// 封筒形式の署名のレベルタグ・公開鍵のレベル・署名のサイズ・鍵ハッシュを確認し、解析結果を返す
+fn check_envelope<'a>(envelope: &'a [u8], public_key: &[u8]) -> Result<Envelope<'a>, String> {
+    let parsed = parse_envelope(envelope)?;
+    
+    let key_level = Level::of_public_key(public_key)?;
+    if parsed.level != key_level {
+        return Err(format!(
+            "ML-DSA level mismatch: signature is {}, public key is {}",
+            parsed.level.name(),
+            key_level.name()
+        ));
+    }
+    
+    check_signature_size(parsed.level, parsed.signature)?;
+    
+    if let Some(key_hash) = parsed.key_hash {
+        if key_hash != key_hash_of(public_key) {
+            return Err("Key hash in signature envelope does not match the public key".to_string());
+        }
+    }
+    
+    Ok(parsed)
+}
+
+// 公開鍵の鍵ハッシュ（FIPS 204のtr = SHAKE256(公開鍵, 64バイト)）
+fn key_hash_of(public_key: &[u8]) -> [u8; KEY_HASH_SIZE] {
+    let mut hasher = Shake256::default();
+    hasher.update(public_key);
+    let mut key_hash = [0u8; KEY_HASH_SIZE];
+    hasher.finalize_xof().read(&mut key_hash);
+    key_hash
+}
+
+// コンテキスト文字列のサイズを確認（FIPS 204の上限は255バイト）
+pub(crate) fn check_context(context: &[u8]) -> Result<(), String> {
+    if context.len() > MAX_CONTEXT_SIZE {
+        return Err(format!(
+            "Context is too long: maximum {} bytes, got {}",
+            MAX_CONTEXT_SIZE,
+            context.len()
+        ));
+    }
+    Ok(())
+}
+
+// pureモードの署名対象のメッセージ M' = 0x00 || len(ctx) || ctx || M
+// コンテキスト文字列を指定しない署名（signなど）は空のコンテキスト文字列で M' = 0x00 || 0x00 || M になる
+// コンテキスト文字列のサイズはcheck_contextで確認済みであること
+pub(crate) fn signed_message(context: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut prefixed = Vec::with_capacity(2 + context.len() + message.len());
+    prefixed.push(PURE_ML_DSA_DOMAIN);
+    prefixed.push(context.len() as u8);
+    prefixed.extend_from_slice(context);
+    prefixed.extend_from_slice(message);
+    prefixed
+}
+
+// 解析済みの封筒形式の署名を検証（形式はcheck_envelopeで確認済み）
+fn verify_parsed(parsed: &Envelope, message: &[u8], public_key: &[u8]) -> bool {
+    parsed
+        .level
+        .verify(&signed_message(parsed.context.unwrap_or_default(), message), parsed.signature, public_key)
+}
+
+// 封筒形式の署名のメタデータをJSのオブジェクトに変換
+fn envelope_metadata(parsed: &Envelope) -> Result<JsValue, JsValue> {
+    let context = match parsed.context {
+        Some(context) => js_sys::Uint8Array::from(context).into(),
+        None => JsValue::NULL,
+    };
+    
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"algo".into(), &"ML-DSA".into())?;
+    js_sys::Reflect::set(&result, &"level".into(), &parsed.level.name().into())?;
+    js_sys::Reflect::set(&result, &"context".into(), &context)?;
+    js_sys::Reflect::set(&result, &"has_keyhash".into(), &parsed.key_hash.is_some().into())?;
+    
+    Ok(result.into())
+}
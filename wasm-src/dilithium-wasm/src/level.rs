@@ -0,0 +1,160 @@
+// ML-DSAのパラメータセット（レベル）の内部モジュール
+// 鍵ペアの生成時にML-DSA-44・ML-DSA-65・ML-DSA-87を選択できるようにする
+// 鍵と署名にはレベルのタグを付けない。公開鍵・秘密鍵・署名のサイズはそれぞれレベルごとに異なるため、
+// 署名では秘密鍵のサイズから、検証では公開鍵のサイズからレベルを判別する
+//
+//   レベル       公開鍵   秘密鍵   署名
+//   ML-DSA-44     1312     2560    2420
+//   ML-DSA-65     1952     4032    3309
+//   ML-DSA-87     2592     4896    4627
+//
+// 封筒形式の署名（envelope.rs）は、署名にレベルのタグを付けて公開鍵のレベルと照合する
+
+use pqcrypto_std::mldsa::{SigningKey, VerifyingKey};
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+// ML-DSAのパラメータセット（レベル）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    MlDsa44,
+    MlDsa65,
+    MlDsa87,
+}
+
+// レベルに対応するpqcrypto-stdのモジュールを$mとして$bodyを評価する
+macro_rules! with_level {
+    ($level:expr, $m:ident => $body:expr) => {
+        match $level {
+            Level::MlDsa44 => {
+                use pqcrypto_std::mldsa::mldsa44 as $m;
+                $body
+            }
+            Level::MlDsa65 => {
+                use pqcrypto_std::mldsa::mldsa65 as $m;
+                $body
+            }
+            Level::MlDsa87 => {
+                use pqcrypto_std::mldsa::mldsa87 as $m;
+                $body
+            }
+        }
+    };
+}
+
+impl Level {
+    pub const ALL: [Level; 3] = [Level::MlDsa44, Level::MlDsa65, Level::MlDsa87];
+
+    // レベルを指定しない場合のレベル（以前から生成していたML-DSA-65）
+    pub const DEFAULT: Level = Level::MlDsa65;
+
+    // レベルの番号（44・65・87）。封筒形式のレベルタグにも使う
+    pub fn tag(self) -> u8 {
+        match self {
+            Level::MlDsa44 => 44,
+            Level::MlDsa65 => 65,
+            Level::MlDsa87 => 87,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::MlDsa44 => "ML-DSA-44",
+            Level::MlDsa65 => "ML-DSA-65",
+            Level::MlDsa87 => "ML-DSA-87",
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Level> {
+        Level::ALL.into_iter().find(|level| level.tag() == tag)
+    }
+
+    pub fn from_name(name: &str) -> Option<Level> {
+        Level::ALL.into_iter().find(|level| level.name() == name)
+    }
+
+    // generate_keypairのlevel引数（44・65・87）からレベルを選ぶ
+    pub fn from_number(level: u8) -> Result<Level, String> {
+        Level::from_tag(level)
+            .ok_or_else(|| format!("Unsupported ML-DSA level: {} (expected 44, 65, or 87)", level))
+    }
+
+    pub fn public_key_size(self) -> usize {
+        with_level!(self, m => m::PUBKEY_SIZE)
+    }
+
+    pub fn private_key_size(self) -> usize {
+        with_level!(self, m => m::PRIVKEY_SIZE)
+    }
+
+    pub fn signature_size(self) -> usize {
+        with_level!(self, m => m::SIG_SIZE)
+    }
+
+    // 鍵のサイズからレベルを判定（各レベルの鍵のサイズはすべて異なる）
+    pub fn of_public_key(public_key: &[u8]) -> Result<Level, String> {
+        Level::ALL
+            .into_iter()
+            .find(|level| level.public_key_size() == public_key.len())
+            .ok_or_else(|| format!("Invalid public key size: {} bytes does not match any ML-DSA level", public_key.len()))
+    }
+
+    pub fn of_private_key(private_key: &[u8]) -> Result<Level, String> {
+        Level::ALL
+            .into_iter()
+            .find(|level| level.private_key_size() == private_key.len())
+            .ok_or_else(|| format!("Invalid private key size: {} bytes does not match any ML-DSA level", private_key.len()))
+    }
+
+    // 鍵ペア（公開鍵, 秘密鍵）を生成
+    pub fn keygen(self, rng: &mut (impl RngCore + CryptoRng)) -> (Vec<u8>, Vec<u8>) {
+        with_level!(self, m => {
+            let mut vk_bytes = [0u8; m::PUBKEY_SIZE];
+            let sk = m::PrivateKey::keygen(&mut vk_bytes, rng);
+            
+            let mut sk_bytes = Zeroizing::new([0u8; m::PRIVKEY_SIZE]);
+            sk.encode(&mut *sk_bytes);
+            
+            (vk_bytes.to_vec(), sk_bytes.to_vec())
+        })
+    }
+
+    // メッセージに署名（秘密鍵のサイズがこのレベルと一致しない場合はエラー）
+    pub fn sign(
+        self,
+        message: &[u8],
+        private_key: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, String> {
+        with_level!(self, m => {
+            let sk_array = Zeroizing::new(
+                <[u8; m::PRIVKEY_SIZE]>::try_from(private_key).map_err(|_| {
+                    format!(
+                        "Invalid {} private key size: expected {}, got {}",
+                        self.name(),
+                        m::PRIVKEY_SIZE,
+                        private_key.len()
+                    )
+                })?,
+            );
+            let sk = m::PrivateKey::decode(&*sk_array);
+            
+            let mut sig_bytes = [0u8; m::SIG_SIZE];
+            sk.sign(&mut sig_bytes, rng, message);
+            Ok(sig_bytes.to_vec())
+        })
+    }
+
+    // 署名を検証（公開鍵・署名のサイズがこのレベルと一致しない場合は無効として扱う）
+    pub fn verify(self, message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+        with_level!(self, m => {
+            let (Ok(vk_array), Ok(sig_array)) = (
+                <&[u8; m::PUBKEY_SIZE]>::try_from(public_key),
+                <&[u8; m::SIG_SIZE]>::try_from(signature),
+            ) else {
+                return false;
+            };
+            m::PublicKey::decode(vk_array).verify(message, sig_array).is_ok()
+        })
+    }
+}
@@ -1,7 +1,18 @@
 use wasm_bindgen::prelude::*;
-use pqcrypto_std::mldsa::mldsa65::{PrivateKey, PublicKey, PRIVKEY_SIZE, PUBKEY_SIZE, SIG_SIZE};
-use pqcrypto_std::mldsa::{SigningKey, VerifyingKey};
-use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use zeroize::Zeroize;
+
+mod envelope;
+mod level;
+#[path = "../../shared/pq/rng.rs"]
+mod rng;
+#[cfg(feature = "test-rng")]
+#[path = "../../shared/pq/test_rng.rs"]
+mod test_rng;
+use level::Level;
+use rng::{KeyRng, SeedRng};
 
 // wasm-bindgenの初期化
 #[wasm_bindgen(start)]
@@ -10,125 +21,968 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
+/**
+ * テスト専用: 鍵生成・署名に使う乱数生成モードを切り替える
+ * test-rng featureを有効にしたビルドでのみ公開される（本番ビルドには含まれない）
+ * 
+ * @param mode "os"（通常）、"zero_bytes"（すべて0）、"deterministic"（seedから決定的に生成）のいずれか
+ * @param seed deterministicモードのシード（他のモードでは無視される）
+ */
+#[cfg(feature = "test-rng")]
+#[wasm_bindgen]
+pub fn set_test_rng_mode(mode: &str, seed: &[u8]) -> Result<(), JsValue> {
+    use test_rng::TestRngMode;
+    
+    let mode = match mode {
+        "os" => TestRngMode::Os,
+        "zero_bytes" => TestRngMode::ZeroBytes,
+        "deterministic" => TestRngMode::Deterministic {
+            seed: seed.to_vec(),
+            counter: 0,
+        },
+        _ => return Err(JsValue::from_str(&format!("Unknown RNG mode: {}", mode))),
+    };
+    test_rng::set_mode(mode);
+    Ok(())
+}
+
 // Dilithium鍵ペアの型定義
 #[wasm_bindgen]
+#[derive(Clone)]
 pub struct DilithiumKeyPair {
     public_key: Vec<u8>,
     private_key: Vec<u8>,
+    level: Level,
 }
 
 #[wasm_bindgen]
 impl DilithiumKeyPair {
+    /**
+     * 鍵ペアのパラメータセット（44・65・87）
+     * take_public_key・take_private_keyで取り出した後も変わらない
+     */
+    #[wasm_bindgen(getter)]
+    pub fn level(&self) -> u8 {
+        self.level.tag()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn public_key(&self) -> Vec<u8> {
         self.public_key.clone()
     }
 
+    /**
+     * 公開鍵を取り出す（所有権を移すため、クローンしない）
+     * 一度だけエクスポートする用途向けで、取り出した後の公開鍵は空になる
+     * 繰り返し参照する場合はgetterのpublic_keyを使用すること
+     * 
+     * @returns 公開鍵（2回目以降は空）
+     */
+    pub fn take_public_key(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.public_key)
+    }
+
     #[wasm_bindgen(getter)]
     pub fn private_key(&self) -> Vec<u8> {
         self.private_key.clone()
     }
+
+    /**
+     * 秘密鍵を取り出す（所有権を移すため、クローンしない）
+     * 一度だけエクスポートする用途向けで、取り出した後の秘密鍵は空になる
+     * 繰り返し参照する場合はgetterのprivate_keyを使用すること
+     * 
+     * @returns 秘密鍵（2回目以降は空）
+     */
+    pub fn take_private_key(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.private_key)
+    }
+
+    /**
+     * 同じ内容の複製を作る（JSではclone()）
+     * 元の鍵ペアを保持したまま、Workerなどに複製を渡せる
+     * take_public_key・take_private_keyで取り出した後の鍵は空のまま複製される
+     * 
+     * @returns 複製
+     */
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> DilithiumKeyPair {
+        self.clone()
+    }
+
+    /**
+     * 秘密鍵をメモリから消去する
+     * wasmの線形メモリはJSから読み取れるため、秘密鍵が不要になった時点で呼び出すこと
+     * 消去した後の秘密鍵は空になる（freeで解放した場合も自動で消去される）
+     */
+    pub fn zeroize(&mut self) {
+        self.private_key.zeroize();
+    }
+}
+
+impl Drop for DilithiumKeyPair {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
 /**
  * CRYSTALS-Dilithium鍵ペアを生成
- * ML-DSA-65を使用（NIST標準化されたDilithium、推奨レベル）
+ * ML-DSA（NIST標準化されたDilithium）を使用
+ * 署名・検証では鍵のサイズからパラメータセットを判別するため、レベルを再度指定する必要はない
  * 
+ * @param level パラメータセット（44・65・87、省略時は推奨レベルの65）
  * @returns 公開鍵と秘密鍵のペア
  */
 #[wasm_bindgen]
-pub fn generate_keypair() -> DilithiumKeyPair {
-    // 乱数生成器を作成
-    let mut rng = OsRng;
-    
-    // 公開鍵のバッファを準備
-    let mut vk_bytes = [0u8; PUBKEY_SIZE];
-    
+pub fn generate_keypair(level: Option<u8>) -> Result<DilithiumKeyPair, JsValue> {
+    Ok(keypair_with_rng(level_or_default(level)?, &mut KeyRng))
+}
+
+// level引数（44・65・87、省略時は推奨レベル）からレベルを選ぶ
+fn level_or_default(level: Option<u8>) -> Result<Level, JsValue> {
+    match level {
+        Some(level) => Level::from_number(level).map_err(|e| JsValue::from_str(&e)),
+        None => Ok(Level::DEFAULT),
+    }
+}
+
+// 指定した乱数生成器で鍵ペアを生成（generate_keypairとシードからの鍵生成で共通）
+fn keypair_with_rng(level: Level, rng: &mut (impl RngCore + CryptoRng)) -> DilithiumKeyPair {
     // ML-DSAの鍵ペアを生成
-    let sk = PrivateKey::keygen(&mut vk_bytes, &mut rng);
-    
-    // 秘密鍵をバイト配列に変換
-    let mut sk_bytes = [0u8; PRIVKEY_SIZE];
-    sk.encode(&mut sk_bytes);
+    let (public_key, private_key) = level.keygen(rng);
     
     DilithiumKeyPair {
-        public_key: vk_bytes.to_vec(),
-        private_key: sk_bytes.to_vec(),
+        public_key,
+        private_key,
+        level,
+    }
+}
+
+// シードのサイズ（FIPS 204のML-DSA.KeyGen_internalに渡すξと同じ32バイト）
+const SEED_SIZE: usize = 32;
+
+// シードの形式を確認
+// すべて0のシードは初期化漏れのバッファを渡した可能性が高いため拒否する
+fn check_seed(seed: &[u8]) -> Result<(), String> {
+    if seed.len() != SEED_SIZE {
+        return Err(format!("Invalid seed size: expected {}, got {}", SEED_SIZE, seed.len()));
     }
+    if seed.iter().all(|&b| b == 0) {
+        return Err("Invalid seed: all bytes are zero".to_string());
+    }
+    Ok(())
+}
+
+/**
+ * シードから決定的にDilithium鍵ペアを生成
+ * 同じシードからは常に同じ鍵ペアが得られる（FIPS 204のシードξから生成した鍵ペアと一致する）
+ * シードは秘密鍵と同等に扱うこと
+ * 
+ * @param seed 鍵生成シード（32バイト、すべて0は不可）
+ * @returns 公開鍵と秘密鍵のペア
+ */
+#[wasm_bindgen]
+pub fn generate_keypair_from_seed(seed: &[u8]) -> Result<DilithiumKeyPair, JsValue> {
+    check_seed(seed).map_err(|e| JsValue::from_str(&e))?;
+    SeedRng::with_seed(seed, |rng| keypair_with_rng(Level::DEFAULT, rng)).map_err(|e| JsValue::from_str(&e))
+}
+
+/**
+ * 複数のシードからまとめて決定的にDilithium鍵ペアを生成
+ * 大量の鍵を再現可能な形で払い出す用途向けで、wasmの呼び出しが1回で済む
+ * 鍵の生成前にすべてのシードを確認し、1つでも不正なものがあれば何も生成せずにエラーを返す
+ * 
+ * @param seeds 鍵生成シード（それぞれ32バイト、すべて0は不可）の配列
+ * @returns シードと同じ順の鍵ペアの配列（各要素はgenerate_keypair_from_seedの結果と一致する）
+ */
+#[wasm_bindgen]
+pub fn generate_keypairs_from_seeds(seeds: js_sys::Array) -> Result<Vec<DilithiumKeyPair>, JsValue> {
+    use wasm_bindgen::JsCast;
+    
+    let seeds = seeds
+        .iter()
+        .enumerate()
+        .map(|(index, seed)| {
+            let seed = seed
+                .dyn_into::<js_sys::Uint8Array>()
+                .map_err(|_| format!("Seed {} is not a Uint8Array", index))?
+                .to_vec();
+            check_seed(&seed).map_err(|e| format!("Seed {}: {}", index, e))?;
+            Ok(seed)
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|e| JsValue::from_str(&e))?;
+    
+    seeds
+        .iter()
+        .map(|seed| SeedRng::with_seed(seed, |rng| keypair_with_rng(Level::DEFAULT, rng)))
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|e| JsValue::from_str(&e))
 }
 
 /**
  * メッセージに署名
+ * FIPS 204のpureモード（空のコンテキスト文字列）で M' = 0x00 || 0x00 || M に署名する
+ * パラメータセットは秘密鍵のサイズから判別する
  * 
  * @param message 署名するメッセージ（バイト配列）
- * @param private_key 秘密鍵（バイト配列）
+ * @param private_key 秘密鍵（バイト配列、ML-DSA-44・65・87のいずれかのサイズ）
+ * @returns 署名（バイト配列）
+ * @throws 秘密鍵のサイズがどのレベルとも一致しない場合
+ */
+#[wasm_bindgen]
+pub fn sign(message: &[u8], private_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+    sign_message(message, private_key).map_err(|e| JsValue::from_str(&e))
+}
+
+// 決定的署名で乱数rndの代わりに使う値（FIPS 204の決定的な変種では rnd = {0}^32）
+const DETERMINISTIC_RND: [u8; 32] = [0u8; 32];
+
+/**
+ * メッセージに決定的に署名
+ * FIPS 204の決定的な変種（乱数rndをすべて0にする）で、同じ鍵・メッセージからは常に同じ署名が得られる
+ * 再現可能なテストベクタの作成や、乱数生成器を信頼できない環境向け
+ * 署名の形式は通常の署名と同じで、verifyでそのまま検証できる
+ * 注意: 署名から同じメッセージに署名したことが分かる。通常はsign（ヘッジ付きの署名）を使うこと
+ * 
+ * @param message 署名するメッセージ（バイト配列）
+ * @param private_key 秘密鍵（バイト配列、ML-DSA-44・65・87のいずれかのサイズ）
  * @returns 署名（バイト配列）
+ * @throws 秘密鍵のサイズがどのレベルとも一致しない場合
  */
 #[wasm_bindgen]
-pub fn sign(message: &[u8], private_key: &[u8]) -> Vec<u8> {
-    // 秘密鍵のサイズをチェック
-    if private_key.len() != PRIVKEY_SIZE {
-        wasm_bindgen::throw_str(&format!(
-            "Invalid private key size: expected {}, got {}",
-            PRIVKEY_SIZE,
-            private_key.len()
+pub fn sign_deterministic(message: &[u8], private_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+    Level::of_private_key(private_key)
+        .and_then(|level| {
+            let message = envelope::signed_message(&[], message);
+            SeedRng::with_seed(&DETERMINISTIC_RND, |rng| level.sign(&message, private_key, rng))?
+        })
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+// pureモード（空のコンテキスト文字列）でメッセージに署名
+fn sign_message(message: &[u8], private_key: &[u8]) -> Result<Vec<u8>, String> {
+    sign_internal(&envelope::signed_message(&[], message), private_key)
+}
+
+// 組み立て済みのM'をそのままML-DSA.Sign_internalに渡して署名
+// 秘密鍵のサイズからパラメータセットを判別する（どのレベルとも一致しない場合はエラー）
+fn sign_internal(formatted: &[u8], private_key: &[u8]) -> Result<Vec<u8>, String> {
+    Level::of_private_key(private_key).and_then(|level| level.sign(formatted, private_key, &mut KeyRng))
+}
+
+/**
+ * 署名を検証
+ * signと同じpureモード（空のコンテキスト文字列）の署名として検証する
+ * パラメータセットは公開鍵のサイズから判別する
+ * 入力の不正と署名の不一致を区別する必要がある場合はverify_detailedを使う
+ * 
+ * @param message 元のメッセージ（バイト配列）
+ * @param signature 署名（バイト配列）
+ * @param public_key 公開鍵（バイト配列、ML-DSA-44・65・87のいずれかのサイズ）
+ * @returns 検証結果（true: 有効、false: 無効。公開鍵・署名のサイズが不正な場合もfalse）
+ */
+#[wasm_bindgen]
+pub fn verify(message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+    verify_message(message, signature, public_key).unwrap_or(false)
+}
+
+/**
+ * 署名を検証し、入力の不正と署名の不一致を区別して返す
+ * 
+ * @param message 元のメッセージ（バイト配列）
+ * @param signature 署名（バイト配列）
+ * @param public_key 公開鍵（バイト配列、ML-DSA-44・65・87のいずれかのサイズ）
+ * @returns status（"valid"・"invalid"・"malformed_input"）とerror（malformed_inputの場合の理由、それ以外はnull）を持つオブジェクト
+ */
+#[wasm_bindgen]
+pub fn verify_detailed(message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<JsValue, JsValue> {
+    let (status, error) = match verify_message(message, signature, public_key) {
+        Ok(true) => ("valid", JsValue::NULL),
+        Ok(false) => ("invalid", JsValue::NULL),
+        Err(e) => ("malformed_input", JsValue::from_str(&e)),
+    };
+    
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"status".into(), &status.into())?;
+    js_sys::Reflect::set(&result, &"error".into(), &error)?;
+    
+    Ok(result.into())
+}
+
+// pureモード（空のコンテキスト文字列）の署名を検証
+fn verify_message(message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool, String> {
+    verify_internal(&envelope::signed_message(&[], message), signature, public_key)
+}
+
+// 組み立て済みのM'に対する署名を検証（ML-DSA.Verify_internal）
+// 公開鍵のサイズからパラメータセットを判別する
+// 公開鍵・署名のサイズが不正な場合はエラー、署名が一致しない場合はOk(false)
+fn verify_internal(formatted: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool, String> {
+    let level = Level::of_public_key(public_key)?;
+    if signature.len() != level.signature_size() {
+        return Err(format!(
+            "Invalid {} signature size: expected {}, got {}",
+            level.name(),
+            level.signature_size(),
+            signature.len()
         ));
     }
+    Ok(level.verify(formatted, signature, public_key))
+}
+
+/**
+ * コンテキスト文字列を指定してメッセージに署名
+ * FIPS 204のpureモードの M' = 0x00 || len(ctx) || ctx || M に署名し、署名をアプリケーションの用途に束縛する
+ * 異なるコンテキスト文字列では検証に失敗する
+ * 空のコンテキスト文字列を指定した場合はsignと同じ署名になり、verifyでも検証できる
+ * 
+ * @param message 署名するメッセージ（バイト配列）
+ * @param context コンテキスト文字列（最大255バイト、空でもよい）
+ * @param private_key 秘密鍵（バイト配列、ML-DSA-44・65・87のいずれかのサイズ）
+ * @returns 署名（バイト配列、signと同じサイズ）
+ */
+#[wasm_bindgen]
+pub fn sign_with_context(message: &[u8], context: &[u8], private_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+    envelope::check_context(context)
+        .and_then(|()| sign_internal(&envelope::signed_message(context, message), private_key))
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+/**
+ * コンテキスト文字列を指定して署名を検証
+ * 
+ * @param message 元のメッセージ（バイト配列）
+ * @param context 署名時に指定したコンテキスト文字列
+ * @param signature sign_with_contextで生成した署名
+ * @param public_key 公開鍵（バイト配列、ML-DSA-44・65・87のいずれかのサイズ）
+ * @returns 検証結果（true: 有効、false: 無効。コンテキスト文字列が255バイトを超える場合もfalse）
+ */
+#[wasm_bindgen]
+pub fn verify_with_context(message: &[u8], context: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+    if envelope::check_context(context).is_err() {
+        return false;
+    }
     
-    // 固定サイズ配列に変換
-    let mut sk_array = [0u8; PRIVKEY_SIZE];
-    sk_array.copy_from_slice(private_key);
+    verify_internal(&envelope::signed_message(context, message), signature, public_key).unwrap_or(false)
+}
+
+/**
+ * base64でエンコードされた署名・公開鍵で署名を検証
+ * 鍵や署名を文字列で保持しているアプリ向けに、復号処理を内部で行う
+ * パラメータセットは公開鍵のサイズから判別する
+ * 
+ * @param message_utf8 元のメッセージ（UTF-8文字列）
+ * @param signature_b64 署名（base64、標準のアルファベット・パディング付き）
+ * @param public_key_b64 公開鍵（base64、標準のアルファベット・パディング付き、ML-DSA-44・65・87のいずれか）
+ * @returns 検証結果（true: 有効、false: 無効）。base64やサイズが不正な場合はエラー
+ */
+#[wasm_bindgen]
+pub fn verify_encoded(message_utf8: &str, signature_b64: &str, public_key_b64: &str) -> Result<bool, JsValue> {
+    let signature = decode_base64("signature", signature_b64)?;
+    let public_key = decode_base64("public key", public_key_b64)?;
     
-    // 秘密鍵を復元
-    let sk = PrivateKey::decode(&sk_array);
+    let level = Level::of_public_key(&public_key).map_err(|e| JsValue::from_str(&e))?;
+    if signature.len() != level.signature_size() {
+        return Err(JsValue::from_str(&format!(
+            "Invalid signature size for {}: expected {} bytes, got {}",
+            level.name(),
+            level.signature_size(),
+            signature.len()
+        )));
+    }
     
-    // 乱数生成器を作成
-    let mut rng = OsRng;
+    Ok(verify(message_utf8.as_bytes(), &signature, &public_key))
+}
+
+// 前後の空白を除いてbase64を復号する
+fn decode_base64(name: &str, encoded: &str) -> Result<Vec<u8>, JsValue> {
+    STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| JsValue::from_str(&format!("Invalid base64 in {}: {}", name, e)))
+}
+
+/**
+ * 複数の候補の公開鍵で署名を検証
+ * 鍵のローテーション期間中など、どの鍵で署名されたか分からない場合に使用する
+ * 
+ * @param message 元のメッセージ（バイト配列）
+ * @param signature 署名（バイト配列）
+ * @param public_keys 候補の公開鍵の配列（Uint8Array以外の要素は一致しないものとして扱う）
+ * @returns 署名を検証できた最初の公開鍵のインデックス（どれも一致しない場合はundefined）
+ */
+#[wasm_bindgen]
+pub fn verify_any(message: &[u8], signature: &[u8], public_keys: js_sys::Array) -> Option<usize> {
+    use wasm_bindgen::JsCast;
     
-    // 署名のバッファを準備
-    let mut sig_bytes = [0u8; SIG_SIZE];
+    let public_keys = public_keys.iter().map(|key| {
+        key.dyn_into::<js_sys::Uint8Array>()
+            .map(|key| key.to_vec())
+            .unwrap_or_default()
+    });
+    first_verifying_key(message, signature, public_keys)
+}
+
+/**
+ * 複数の署名をまとめて検証
+ * 多数の署名を検証する場合に、JSとWasmの間の呼び出しとデータの受け渡しを1回にまとめる
+ * messages・signatures・public_keysの同じ位置の要素を1組として検証する
+ * 
+ * @param messages メッセージの配列（Uint8Array）
+ * @param signatures 署名の配列（Uint8Array）
+ * @param public_keys 公開鍵の配列（Uint8Array、組ごとに異なるレベルでもよい）
+ * @returns 組ごとの検証結果（1: 有効、0: 無効。公開鍵・署名のサイズが不正な組も0）
+ * @throws 配列の長さが一致しない場合、Uint8Array以外の要素がある場合
+ */
+#[wasm_bindgen]
+pub fn verify_batch(
+    messages: js_sys::Array,
+    signatures: js_sys::Array,
+    public_keys: js_sys::Array,
+) -> Result<Vec<u8>, JsValue> {
+    if messages.length() != signatures.length() || messages.length() != public_keys.length() {
+        return Err(JsValue::from_str(&format!(
+            "Batch length mismatch: {} messages, {} signatures, {} public keys",
+            messages.length(),
+            signatures.length(),
+            public_keys.length()
+        )));
+    }
     
-    // 署名を生成
-    sk.sign(&mut sig_bytes, &mut rng, message);
+    let messages = byte_arrays("Message", &messages)?;
+    let signatures = byte_arrays("Signature", &signatures)?;
+    let public_keys = byte_arrays("Public key", &public_keys)?;
     
-    sig_bytes.to_vec()
+    Ok(messages
+        .iter()
+        .zip(&signatures)
+        .zip(&public_keys)
+        .map(|((message, signature), public_key)| verify(message, signature, public_key) as u8)
+        .collect())
+}
+
+// JSの配列の各要素をバイト列に変換（Uint8Array以外の要素がある場合はその位置を示すエラー）
+fn byte_arrays(name: &str, array: &js_sys::Array) -> Result<Vec<Vec<u8>>, JsValue> {
+    use wasm_bindgen::JsCast;
+    
+    array
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            item.dyn_into::<js_sys::Uint8Array>()
+                .map(|item| item.to_vec())
+                .map_err(|_| JsValue::from_str(&format!("{} {} is not a Uint8Array", name, index)))
+        })
+        .collect()
+}
+
+// 署名を検証できた最初の公開鍵のインデックスを返す
+fn first_verifying_key(
+    message: &[u8],
+    signature: &[u8],
+    public_keys: impl IntoIterator<Item = Vec<u8>>,
+) -> Option<usize> {
+    public_keys
+        .into_iter()
+        .position(|public_key| verify(message, signature, &public_key))
 }
 
 /**
- * 署名を検証
+ * クレートのバージョンを取得
+ * 
+ * @returns Cargo.tomlのバージョン文字列
+ */
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+/**
+ * ビルドに含まれている機能を取得
+ * 
+ * @returns アルゴリズム名と利用可能なパラメータセットを持つオブジェクト
+ */
+#[wasm_bindgen]
+pub fn features() -> Result<JsValue, JsValue> {
+    let parameter_sets = js_sys::Array::new();
+    for level in Level::ALL {
+        parameter_sets.push(&level.name().into());
+    }
+    
+    // 封筒形式の署名（sign_enveloped・verify_enveloped）で使えるパラメータセット
+    let enveloped_parameter_sets = js_sys::Array::new();
+    for name in ["ML-DSA-44", "ML-DSA-65", "ML-DSA-87"] {
+        enveloped_parameter_sets.push(&name.into());
+    }
+    
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"algorithm".into(), &"ML-DSA".into())?;
+    js_sys::Reflect::set(&result, &"parameter_sets".into(), &parameter_sets)?;
+    js_sys::Reflect::set(&result, &"hybrid".into(), &"Ed25519".into())?;
+    js_sys::Reflect::set(&result, &"enveloped_parameter_sets".into(), &enveloped_parameter_sets)?;
+    
+    Ok(result.into())
+}
+
+// JWKの鍵タイプ（draft-ietf-cose-dilithium の AKP 形式）
+// algにはパラメータセット名（ML-DSA-44・ML-DSA-65・ML-DSA-87）を入れる
+const JWK_KEY_TYPE: &str = "AKP";
+
+/**
+ * ML-DSA公開鍵をJWK形式に変換
+ * パラメータセットは公開鍵のサイズから判別し、algに設定する
+ * 
+ * @param public_key 公開鍵（バイト配列、ML-DSA-44・65・87のいずれかのサイズ）
+ * @returns kty・alg・pub（base64url）を持つJWKオブジェクト
+ */
+#[wasm_bindgen]
+pub fn public_key_to_jwk(public_key: &[u8]) -> Result<JsValue, JsValue> {
+    let level = Level::of_public_key(public_key).map_err(|e| JsValue::from_str(&e))?;
+    
+    let jwk = js_sys::Object::new();
+    js_sys::Reflect::set(&jwk, &"kty".into(), &JWK_KEY_TYPE.into())?;
+    js_sys::Reflect::set(&jwk, &"alg".into(), &level.name().into())?;
+    js_sys::Reflect::set(&jwk, &"pub".into(), &URL_SAFE_NO_PAD.encode(public_key).into())?;
+    
+    Ok(jwk.into())
+}
+
+/**
+ * JWK形式からML-DSA公開鍵を取り出す
+ * 公開鍵のサイズがalgのパラメータセットと一致しない場合はエラーになる
+ * 
+ * @param jwk public_key_to_jwkが返す形式のJWKオブジェクト
+ * @returns 公開鍵（バイト配列）
+ */
+#[wasm_bindgen]
+pub fn public_key_from_jwk(jwk: JsValue) -> Result<Vec<u8>, JsValue> {
+    let kty = jwk_string_member(&jwk, "kty")?;
+    if kty != JWK_KEY_TYPE {
+        return Err(JsValue::from_str(&format!("Unsupported JWK key type: {}", kty)));
+    }
+    
+    let alg = jwk_string_member(&jwk, "alg")?;
+    let level = Level::from_name(&alg)
+        .ok_or_else(|| JsValue::from_str(&format!("Unsupported JWK algorithm: {}", alg)))?;
+    
+    let public_key = URL_SAFE_NO_PAD
+        .decode(jwk_string_member(&jwk, "pub")?)
+        .map_err(|e| JsValue::from_str(&format!("Invalid base64url in JWK: {}", e)))?;
+    
+    if public_key.len() != level.public_key_size() {
+        return Err(JsValue::from_str(&format!(
+            "Invalid public key size for {}: expected {} bytes, got {}",
+            level.name(),
+            level.public_key_size(),
+            public_key.len()
+        )));
+    }
+    
+    Ok(public_key)
+}
+
+// JWKの文字列メンバーを取得
+fn jwk_string_member(jwk: &JsValue, name: &str) -> Result<String, JsValue> {
+    js_sys::Reflect::get(jwk, &name.into())?
+        .as_string()
+        .ok_or_else(|| JsValue::from_str(&format!("JWK member \"{}\" is missing or not a string", name)))
+}
+
+// 鍵識別子（KID）として使うSHA-256の先頭のバイト数（base64urlで22文字）
+const KEY_ID_SIZE: usize = 16;
+
+/**
+ * 公開鍵から安定した鍵識別子（KID）を導出
+ * SHA-256(公開鍵) の先頭16バイトをbase64url（パディングなし）で表した22文字の文字列（他のモジュールのkey_idと同じ方式）
+ * 
+ * @param public_key ML-DSA-44・65・87のいずれかの公開鍵（バイト配列）
+ * @returns 鍵識別子（22文字）
+ */
+#[wasm_bindgen]
+pub fn key_id(public_key: &[u8]) -> Result<String, JsValue> {
+    use sha2::{Digest, Sha256};
+    
+    envelope::check_public_key(public_key).map_err(|e| JsValue::from_str(&e))?;
+    let digest = Sha256::digest(public_key);
+    Ok(URL_SAFE_NO_PAD.encode(&digest[..KEY_ID_SIZE]))
+}
+
+// ハイブリッド署名で使用するEd25519の鍵・署名サイズ
+const ED25519_SECRET_KEY_SIZE: usize = 32;
+const ED25519_PUBLIC_KEY_SIZE: usize = 32;
+const ED25519_SIGNATURE_SIZE: usize = 64;
+
+/**
+ * ハイブリッド署名（Ed25519 + ML-DSA）の鍵ペアを生成
+ * どちらか一方の方式が破られても安全性を保つため、古典署名と耐量子署名を併用する
+ * 署名・検証ではML-DSAの部分の鍵のサイズからパラメータセットを判別する
+ * 
+ * @param level ML-DSAのパラメータセット（44・65・87、省略時は65）
+ * @returns 公開鍵（Ed25519公開鍵 || ML-DSA公開鍵）と秘密鍵（Ed25519秘密鍵 || ML-DSA秘密鍵）のペア
+ */
+#[wasm_bindgen]
+pub fn hybrid_generate_keypair(level: Option<u8>) -> Result<DilithiumKeyPair, JsValue> {
+    use ed25519_dalek::SigningKey as Ed25519SigningKey;
+    
+    let level = level_or_default(level)?;
+    
+    // Ed25519の鍵ペアを生成
+    let mut rng = KeyRng;
+    let ed_sk = Ed25519SigningKey::generate(&mut rng);
+    
+    // ML-DSAの鍵ペアを生成
+    let pq = keypair_with_rng(level, &mut rng);
+    
+    let mut public_key = ed_sk.verifying_key().to_bytes().to_vec();
+    public_key.extend_from_slice(&pq.public_key);
+    
+    let mut private_key = ed_sk.to_bytes().to_vec();
+    private_key.extend_from_slice(&pq.private_key);
+    
+    Ok(DilithiumKeyPair {
+        public_key,
+        private_key,
+        level,
+    })
+}
+
+/**
+ * ハイブリッド署名を生成
+ * 
+ * @param message 署名するメッセージ（バイト配列）
+ * @param private_key hybrid_generate_keypairで生成した秘密鍵
+ * @returns 署名（Ed25519署名 || ML-DSA署名）
+ * @throws 秘密鍵のサイズが一致しない場合
+ */
+#[wasm_bindgen]
+pub fn hybrid_sign(message: &[u8], private_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+    use ed25519_dalek::{Signer, SigningKey as Ed25519SigningKey};
+    
+    // 秘密鍵をEd25519の秘密鍵とML-DSAの秘密鍵に分割し、ML-DSAの部分のサイズからレベルを判別する
+    let (ed_sk_array, pq_private_key) = private_key
+        .split_first_chunk::<ED25519_SECRET_KEY_SIZE>()
+        .filter(|(_, pq_private_key)| Level::of_private_key(pq_private_key).is_ok())
+        .ok_or_else(|| {
+            JsValue::from_str(&format!(
+                "Invalid hybrid private key size: expected one of {} bytes, got {}",
+                Level::ALL.map(|level| (ED25519_SECRET_KEY_SIZE + level.private_key_size()).to_string()).join(", "),
+                private_key.len()
+            ))
+        })?;
+    let ed_sk = Ed25519SigningKey::from_bytes(ed_sk_array);
+    
+    // 両方の方式で同じメッセージに署名して連結
+    let mut signature = ed_sk.sign(message).to_bytes().to_vec();
+    signature.extend_from_slice(&sign_message(message, pq_private_key).map_err(|e| JsValue::from_str(&e))?);
+    
+    Ok(signature)
+}
+
+/**
+ * ハイブリッド署名を検証
+ * Ed25519署名とML-DSA署名の両方が有効な場合のみ成功する
  * 
  * @param message 元のメッセージ（バイト配列）
- * @param signature 署名（バイト配列）
- * @param public_key 公開鍵（バイト配列）
+ * @param signature hybrid_signで生成した署名
+ * @param public_key hybrid_generate_keypairで生成した公開鍵
  * @returns 検証結果（true: 有効、false: 無効）
  */
 #[wasm_bindgen]
-pub fn verify(message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
-    // サイズチェック
-    if public_key.len() != PUBKEY_SIZE {
+pub fn hybrid_verify(message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+    use ed25519_dalek::{Signature as Ed25519Signature, Verifier, VerifyingKey as Ed25519VerifyingKey};
+    
+    // Ed25519とML-DSAの部分に分割
+    let (Some((ed_pk_array, pq_public_key)), Some((ed_sig_array, pq_signature))) = (
+        public_key.split_first_chunk::<ED25519_PUBLIC_KEY_SIZE>(),
+        signature.split_first_chunk::<ED25519_SIGNATURE_SIZE>(),
+    ) else {
         return false;
+    };
+    
+    // サイズチェック（ML-DSAの部分のレベルは公開鍵のサイズから判別する）
+    match Level::of_public_key(pq_public_key) {
+        Ok(level) if pq_signature.len() == level.signature_size() => {}
+        _ => return false,
     }
     
-    if signature.len() != SIG_SIZE {
-        return false;
+    // Ed25519署名を検証
+    let ed_pk = match Ed25519VerifyingKey::from_bytes(ed_pk_array) {
+        Ok(pk) => pk,
+        Err(_) => return false,
+    };
+    let ed_sig = Ed25519Signature::from_bytes(ed_sig_array);
+    
+    let ed_valid = ed_pk.verify(message, &ed_sig).is_ok();
+    
+    // ML-DSA署名を検証（タイミング差を避けるため、Ed25519の結果によらず常に検証する）
+    let pq_valid = verify(message, pq_signature, pq_public_key);
+    
+    ed_valid && pq_valid
+}
+
+// WebAuthn風チャレンジ署名で受け付けるチャレンジの最小サイズ（WebAuthnの推奨値）
+const MIN_CHALLENGE_SIZE: usize = 16;
+
+// client dataのtype（WebAuthnの認証と同じ値）
+const CLIENT_DATA_TYPE: &str = "webauthn.get";
+
+// authenticator dataのフラグ（UP: ユーザー存在確認済み）
+const AUTHENTICATOR_FLAG_USER_PRESENT: u8 = 0x01;
+
+// WebAuthn風の署名対象（authenticator data || SHA-256(client data JSON)）を構築
+//   client data JSON  := {"type":"webauthn.get","challenge":<base64url>,"origin":<origin>}
+//   authenticator data := SHA-256(origin) (32バイト) || flags (1バイト) || signCount (4バイト、常に0)
+// 注意: 簡易実装。RP IDの代わりにoriginをハッシュし、署名カウンタは使用しない
+fn challenge_signed_data(challenge: &[u8], origin: &str) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+    
+    let client_data_json = format!(
+        "{{\"type\":\"{}\",\"challenge\":\"{}\",\"origin\":{}}}",
+        CLIENT_DATA_TYPE,
+        URL_SAFE_NO_PAD.encode(challenge),
+        json_string(origin)
+    );
+    
+    let mut signed_data = Sha256::digest(origin.as_bytes()).to_vec();
+    signed_data.push(AUTHENTICATOR_FLAG_USER_PRESENT);
+    signed_data.extend_from_slice(&0u32.to_be_bytes());
+    signed_data.extend_from_slice(&Sha256::digest(client_data_json.as_bytes()));
+    signed_data
+}
+
+// 文字列をJSONの文字列リテラルにエスケープ
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/**
+ * WebAuthn風のチャレンジに署名
+ * パスワードレス認証のデモ用に、チャレンジとオリジンを束縛した署名を生成する
+ * 
+ * @param challenge サーバーが発行したチャレンジ（16バイト以上）
+ * @param origin 認証を要求したオリジン
+ * @param private_key 秘密鍵（バイト配列）
+ * @returns 署名（バイト配列）
+ * @throws チャレンジが短すぎる場合、または秘密鍵のサイズが不正な場合
+ */
+#[wasm_bindgen]
+pub fn sign_challenge(challenge: &[u8], origin: &str, private_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+    if challenge.len() < MIN_CHALLENGE_SIZE {
+        return Err(JsValue::from_str(&format!(
+            "Challenge is too short: expected at least {} bytes, got {}",
+            MIN_CHALLENGE_SIZE,
+            challenge.len()
+        )));
     }
     
-    // 固定サイズ配列に変換
-    let mut vk_array = [0u8; PUBKEY_SIZE];
-    vk_array.copy_from_slice(public_key);
+    sign_message(&challenge_signed_data(challenge, origin), private_key).map_err(|e| JsValue::from_str(&e))
+}
+
+/**
+ * WebAuthn風のチャレンジ署名を検証
+ * チャレンジとオリジンから署名対象を再構築して検証する
+ * 
+ * @param challenge サーバーが発行したチャレンジ
+ * @param origin 期待するオリジン
+ * @param signature sign_challengeで生成した署名
+ * @param public_key 公開鍵（バイト配列）
+ * @returns 検証結果（true: 有効、false: 無効）
+ */
+#[wasm_bindgen]
+pub fn verify_challenge(challenge: &[u8], origin: &str, signature: &[u8], public_key: &[u8]) -> bool {
+    if challenge.len() < MIN_CHALLENGE_SIZE {
+        return false;
+    }
     
-    let mut sig_array = [0u8; SIG_SIZE];
-    sig_array.copy_from_slice(signature);
+    verify(&challenge_signed_data(challenge, origin), signature, public_key)
+}
+
+// HashML-DSAのドメイン分離（FIPS 204 Algorithm 4 の M' の先頭バイト）
+// pureモードは0x00、事前ハッシュ（HashML-DSA）モードは0x01
+const HASH_ML_DSA_DOMAIN: u8 = 0x01;
+
+// NISTのハッシュ関数のOID（2.16.840.1.101.3.4.2.x）の共通部分のDERエンコード
+// 末尾に最後の要素（x）を1バイト付け足すと完全なOIDになる
+const NIST_HASH_OID_PREFIX: &str = "2.16.840.1.101.3.4.2.";
+const NIST_HASH_OID_DER_PREFIX: [u8; 10] = [0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02];
+
+// HashML-DSAで使用できるハッシュ関数（OIDの最後の要素, ダイジェスト長）
+// SHAKE128/SHAKE256はFIPS 204の規定どおりそれぞれ256/512ビットの出力を使う
+const PREHASH_ALGORITHMS: [(u8, usize); 12] = [
+    (1, 32),  // SHA-256
+    (2, 48),  // SHA-384
+    (3, 64),  // SHA-512
+    (4, 28),  // SHA-224
+    (5, 28),  // SHA-512/224
+    (6, 32),  // SHA-512/256
+    (7, 28),  // SHA3-224
+    (8, 32),  // SHA3-256
+    (9, 48),  // SHA3-384
+    (10, 64), // SHA3-512
+    (11, 32), // SHAKE128
+    (12, 64), // SHAKE256
+];
+
+// HashML-DSAの署名対象 M' = 0x01 || len(ctx) || ctx || OID(DER) || PH(M) を構築
+// コンテキスト文字列は空（len(ctx) = 0）とする
+// 構築したM'はsign_internal・verify_internalにそのまま渡す（signに渡すとpureモードのM'で包まれてしまう）
+fn hash_ml_dsa_message(digest: &[u8], oid: &str) -> Result<Vec<u8>, String> {
+    let (last_arc, digest_size) = oid
+        .trim()
+        .strip_prefix(NIST_HASH_OID_PREFIX)
+        .and_then(|arc| arc.parse::<u8>().ok())
+        .and_then(|arc| PREHASH_ALGORITHMS.iter().find(|(a, _)| *a == arc))
+        .copied()
+        .ok_or_else(|| format!("Unsupported hash function OID: {}", oid))?;
     
-    // 公開鍵を復元
-    let vk = PublicKey::decode(&vk_array);
+    if digest.len() != digest_size {
+        return Err(format!(
+            "Invalid digest size for OID {}: expected {} bytes, got {}",
+            oid.trim(),
+            digest_size,
+            digest.len()
+        ));
+    }
     
-    // 署名を検証
-    vk.verify(message, &sig_array).is_ok()
+    let mut message = vec![HASH_ML_DSA_DOMAIN, 0x00];
+    message.extend_from_slice(&NIST_HASH_OID_DER_PREFIX);
+    message.push(last_arc);
+    message.extend_from_slice(digest);
+    Ok(message)
 }
 
-// 基本的なテスト関数
+/**
+ * 事前に計算したハッシュ値に署名（HashML-DSA）
+ * FIPS 204の事前ハッシュモードで、ハッシュ関数のOIDを署名対象に含める
+ * 大きなデータを呼び出し側でハッシュしてから署名する場合や、他の実装との相互運用に使用する
+ * 
+ * @param digest メッセージのハッシュ値（バイト配列）
+ * @param oid ハッシュ関数のOID（例: SHA-256は"2.16.840.1.101.3.4.2.1"）
+ * @param private_key 秘密鍵（バイト配列）
+ * @returns 署名（バイト配列）
+ * @throws 未対応のOID、ハッシュ値の長さが不正な場合、または秘密鍵のサイズが不正な場合
+ */
 #[wasm_bindgen]
-pub fn add(a: u32, b: u32) -> u32 {
-    a + b
+pub fn sign_prehash(digest: &[u8], oid: &str, private_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+    hash_ml_dsa_message(digest, oid)
+        .and_then(|message| sign_internal(&message, private_key))
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+/**
+ * 事前ハッシュモード（HashML-DSA）の署名を検証
+ * 
+ * @param digest メッセージのハッシュ値（バイト配列）
+ * @param oid 署名時に指定したハッシュ関数のOID
+ * @param signature 署名（バイト配列）
+ * @param public_key 公開鍵（バイト配列）
+ * @returns 検証結果（true: 有効、false: 無効。未対応のOIDやハッシュ値の長さが不正な場合もfalse）
+ */
+#[wasm_bindgen]
+pub fn verify_prehash(digest: &[u8], oid: &str, signature: &[u8], public_key: &[u8]) -> bool {
+    match hash_ml_dsa_message(digest, oid) {
+        Ok(message) => verify_internal(&message, signature, public_key).unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+// sign_prehashed・verify_prehashedで署名対象に埋め込むハッシュ関数のOID（SHA-512）
+const SHA512_OID: &str = "2.16.840.1.101.3.4.2.3";
+
+/**
+ * SHA-512のハッシュ値に署名（HashML-DSA with SHA-512）
+ * 大きなファイルをJS側でストリーミングしながらSHA-512でハッシュし、ハッシュ値だけをWasmに渡す用途向け
+ * 署名対象は M' = 0x01 || 0x00 || OID(SHA-512, 2.16.840.1.101.3.4.2.3) || ハッシュ値 で、
+ * sign_prehashにSHA-512のOIDを指定した場合と同じ規格どおりのHashML-DSA署名になる
+ * 
+ * @param digest メッセージのSHA-512ハッシュ値（64バイト）
+ * @param private_key 秘密鍵（バイト配列）
+ * @returns 署名（バイト配列）
+ */
+#[wasm_bindgen]
+pub fn sign_prehashed(digest: &[u8], private_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+    hash_ml_dsa_message(digest, SHA512_OID)
+        .and_then(|message| sign_internal(&message, private_key))
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+/**
+ * sign_prehashedの署名を検証（HashML-DSA with SHA-512）
+ * 
+ * @param digest メッセージのSHA-512ハッシュ値（64バイト）
+ * @param signature 署名（バイト配列）
+ * @param public_key 公開鍵（バイト配列）
+ * @returns 検証結果（true: 有効、false: 無効。ハッシュ値が64バイトでない場合もfalse）
+ */
+#[wasm_bindgen]
+pub fn verify_prehashed(digest: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+    verify_prehash(digest, SHA512_OID, signature, public_key)
+}
+
+// 動作確認で署名する固定メッセージ
+const HEALTH_CHECK_MESSAGE: &[u8] = b"dilithium-wasm health check";
+
+/**
+ * モジュールが正しく動作しているかを確認
+ * すべてのパラメータセットで鍵生成→署名→検証の往復を行い、改ざんした署名が拒否されることも確認する
+ * 
+ * @returns 正常に動作していればtrue
+ */
+#[wasm_bindgen]
+pub fn health_check() -> bool {
+    Level::ALL
+        .into_iter()
+        .all(|level| run_health_check(level, false) && !run_health_check(level, true))
+}
+
+// 固定メッセージで往復を実行し、検証に成功したかを返す
+// corruptがtrueの場合は検証の前に署名を1ビット反転させる
+fn run_health_check(level: Level, corrupt: bool) -> bool {
+    let keypair = keypair_with_rng(level, &mut KeyRng);
+    let Ok(mut signature) = sign_message(HEALTH_CHECK_MESSAGE, &keypair.private_key) else {
+        return false;
+    };
+    if corrupt {
+        signature[0] ^= 0x01;
+    }
+    verify(HEALTH_CHECK_MESSAGE, &signature, &keypair.public_key)
+}
+
+/**
+ * 乱数生成器が正常に動作しているかを確認
+ * 鍵ペアを2回生成し、両者が異なること・すべて0でないことを確かめる
+ * 乱数がすべて0になる、固定のシードから生成されているなどの不具合を起動時に検出するためのもので、
+ * falseの場合は鍵を生成・使用しないこと
+ * 
+ * @returns 乱数生成器が正常と判断できればtrue
+ */
+#[wasm_bindgen]
+pub fn entropy_sanity_check() -> bool {
+    // 乱数生成器の出力そのものがすべて0でないか
+    let mut sample = [0u8; 32];
+    KeyRng.fill_bytes(&mut sample);
+    if sample.iter().all(|&b| b == 0) {
+        return false;
+    }
+    
+    let first = keypair_with_rng(Level::DEFAULT, &mut KeyRng);
+    let second = keypair_with_rng(Level::DEFAULT, &mut KeyRng);
+    
+    let is_nonzero = |bytes: &[u8]| bytes.iter().any(|&b| b != 0);
+    is_nonzero(&first.public_key)
+        && is_nonzero(&first.private_key)
+        && first.public_key != second.public_key
+        && first.private_key != second.private_key
 }
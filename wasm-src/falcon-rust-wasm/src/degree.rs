@@ -0,0 +1,126 @@
+// FALCONのパラメータセット（次数）の内部モジュール
+// 鍵ペアの生成時にFALCON-512（NISTレベル1）・FALCON-1024（NISTレベル5）を選択できるようにする
+// 鍵と署名に独自のタグは付けない。FALCONの公開鍵・秘密鍵・署名は先頭のヘッダ（1バイト）に次数を含むため、
+// 署名では秘密鍵を、検証では公開鍵を復元できた次数を使う
+//
+//   次数           公開鍵   秘密鍵
+//   FALCON-512       897     1281
+//   FALCON-1024     1793     2305
+// 署名は可変長（圧縮形式）のため、サイズは復元時に確認する
+
+// FALCONのパラメータセット（次数）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Degree {
+    Falcon512,
+    Falcon1024,
+}
+
+// 次数に対応するfalcon-rustのモジュールを$mとして$bodyを評価する
+macro_rules! with_degree {
+    ($degree:expr, $m:ident => $body:expr) => {
+        match $degree {
+            Degree::Falcon512 => {
+                use falcon_rust::falcon512 as $m;
+                $body
+            }
+            Degree::Falcon1024 => {
+                use falcon_rust::falcon1024 as $m;
+                $body
+            }
+        }
+    };
+}
+
+impl Degree {
+    pub const ALL: [Degree; 2] = [Degree::Falcon512, Degree::Falcon1024];
+
+    // 次数を指定しない場合の次数（以前から生成していたFALCON-512）
+    pub const DEFAULT: Degree = Degree::Falcon512;
+
+    // generate_keypairのdegree引数（512・1024）から次数を選ぶ
+    pub fn from_number(degree: u16) -> Result<Degree, String> {
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.number() == degree)
+            .ok_or_else(|| format!("Unsupported FALCON degree: {} (expected 512 or 1024)", degree))
+    }
+
+    pub fn number(self) -> u16 {
+        match self {
+            Degree::Falcon512 => 512,
+            Degree::Falcon1024 => 1024,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Degree::Falcon512 => "FALCON-512",
+            Degree::Falcon1024 => "FALCON-1024",
+        }
+    }
+
+    // JWKのアルゴリズム名（draft-ietf-cose-falcon）
+    pub fn jwk_algorithm(self) -> &'static str {
+        match self {
+            Degree::Falcon512 => "FN-DSA-512",
+            Degree::Falcon1024 => "FN-DSA-1024",
+        }
+    }
+
+    pub fn from_jwk_algorithm(algorithm: &str) -> Option<Degree> {
+        Self::ALL.into_iter().find(|degree| degree.jwk_algorithm() == algorithm)
+    }
+
+    // 公開鍵を復元できる次数を探す
+    pub fn of_public_key(public_key: &[u8]) -> Result<Degree, String> {
+        Self::ALL
+            .into_iter()
+            .find(|degree| degree.check_public_key(public_key).is_ok())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid public key: {} bytes is not a FALCON-512 or FALCON-1024 public key",
+                    public_key.len()
+                )
+            })
+    }
+
+    // この次数の公開鍵として復元できるかを確認
+    pub fn check_public_key(self, public_key: &[u8]) -> Result<(), String> {
+        with_degree!(self, m => m::PublicKey::from_bytes(public_key)
+            .map(|_| ())
+            .map_err(|e| format!("Invalid public key: {:?}", e)))
+    }
+
+    // シードから鍵ペア（公開鍵, 秘密鍵）を生成
+    pub fn keygen(self, seed: [u8; 32]) -> (Vec<u8>, Vec<u8>) {
+        with_degree!(self, m => {
+            // 返り値は(SecretKey, PublicKey)の順
+            let (sk, pk) = m::keygen(seed);
+            (pk.to_bytes(), sk.to_bytes())
+        })
+    }
+
+    // メッセージに署名（秘密鍵をこの次数の鍵として復元できない場合はエラー）
+    pub fn sign(self, message: &[u8], private_key: &[u8]) -> Result<Vec<u8>, String> {
+        with_degree!(self, m => {
+            let sk = m::SecretKey::from_bytes(private_key)
+                .map_err(|e| format!("Invalid secret key: {:?}", e))?;
+            
+            // 署名を生成（signは直接Signatureを返す）
+            Ok(m::sign(message, &sk).to_bytes())
+        })
+    }
+
+    // 署名を検証（公開鍵・署名をこの次数の形式として復元できない場合はエラー）
+    pub fn verify(self, message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool, String> {
+        with_degree!(self, m => {
+            let pk = m::PublicKey::from_bytes(public_key)
+                .map_err(|e| format!("Invalid public key: {:?}", e))?;
+            let sig = m::Signature::from_bytes(signature)
+                .map_err(|e| format!("Invalid signature: {:?}", e))?;
+            
+            // 署名を検証（verifyはboolを返す）
+            Ok(m::verify(message, &sig, &pk))
+        })
+    }
+}
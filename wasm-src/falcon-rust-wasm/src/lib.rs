@@ -1,7 +1,12 @@
 use wasm_bindgen::prelude::*;
-use falcon_rust::falcon512::{keygen, sign, verify, PublicKey, SecretKey};
 use rand::rngs::OsRng;
 use rand::RngCore;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use zeroize::{Zeroize, Zeroizing};
+
+mod degree;
+use degree::Degree;
 
 // wasm-bindgenの初期化
 #[wasm_bindgen(start)]
@@ -11,90 +16,410 @@ pub fn init() {
 
 // FALCON鍵ペアの型定義
 #[wasm_bindgen]
+#[derive(Clone)]
 pub struct FalconKeyPair {
     public_key: Vec<u8>,
     private_key: Vec<u8>,
+    degree: Degree,
 }
 
 #[wasm_bindgen]
 impl FalconKeyPair {
+    /**
+     * 鍵ペアのパラメータセット（512・1024）
+     */
+    #[wasm_bindgen(getter)]
+    pub fn degree(&self) -> u16 {
+        self.degree.number()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn public_key(&self) -> Vec<u8> {
         self.public_key.clone()
     }
 
+    /**
+     * 公開鍵を取り出す（所有権を移すため、クローンしない）
+     * 一度だけエクスポートする用途向けで、取り出した後の公開鍵は空になる
+     * 繰り返し参照する場合はgetterのpublic_keyを使用すること
+     * 
+     * @returns 公開鍵（2回目以降は空）
+     */
+    pub fn take_public_key(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.public_key)
+    }
+
     #[wasm_bindgen(getter)]
     pub fn private_key(&self) -> Vec<u8> {
         self.private_key.clone()
     }
+
+    /**
+     * 秘密鍵を取り出す（所有権を移すため、クローンしない）
+     * 一度だけエクスポートする用途向けで、取り出した後の秘密鍵は空になる
+     * 繰り返し参照する場合はgetterのprivate_keyを使用すること
+     * 
+     * @returns 秘密鍵（2回目以降は空）
+     */
+    pub fn take_private_key(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.private_key)
+    }
+
+    /**
+     * 同じ内容の複製を作る（JSではclone()）
+     * 元の鍵ペアを保持したまま、Workerなどに複製を渡せる
+     * take_public_key・take_private_keyで取り出した後の鍵は空のまま複製される
+     * 
+     * @returns 複製
+     */
+    /**
+     * 秘密鍵をメモリから消去する
+     * wasmの線形メモリはJSから読み取れるため、秘密鍵が不要になった時点で呼び出すこと
+     * 消去した後の秘密鍵は空になる（freeで解放した場合も自動で消去される）
+     */
+    pub fn zeroize(&mut self) {
+        self.private_key.zeroize();
+    }
+
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> FalconKeyPair {
+        self.clone()
+    }
+}
+
+impl Drop for FalconKeyPair {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
 /**
- * FALCON-512鍵ペアを生成
+ * FALCON鍵ペアを生成
+ * 署名・検証では鍵からパラメータセットを判別するため、次数を再度指定する必要はない
  * 
+ * @param degree パラメータセット（512・1024、省略時は512）
  * @returns 公開鍵と秘密鍵のペア
  */
 #[wasm_bindgen]
-pub fn generate_keypair() -> Result<FalconKeyPair, JsValue> {
+pub fn generate_keypair(degree: Option<u16>) -> Result<FalconKeyPair, JsValue> {
     // 乱数生成器を作成
     let mut rng = OsRng;
-    let mut seed = [0u8; 32];
-    rng.fill_bytes(&mut seed);
+    let mut seed = Zeroizing::new([0u8; SEED_SIZE]);
+    rng.fill_bytes(&mut *seed);
+    
+    keypair_from_seed(&*seed, degree)
+}
+
+// degree引数から次数を選ぶ（省略時はFALCON-512）
+fn resolve_degree(degree: Option<u16>) -> Result<Degree, JsValue> {
+    match degree {
+        Some(degree) => Degree::from_number(degree).map_err(|e| JsValue::from_str(&e)),
+        None => Ok(Degree::DEFAULT),
+    }
+}
+
+// 鍵生成シードのサイズ
+const SEED_SIZE: usize = 32;
+
+// 子シードの導出（HKDF）のドメイン分離用ソルト
+const CHILD_SEED_SALT: &[u8] = b"falcon-rust-wasm child seed v1";
+
+/**
+ * シードから決定的にFALCON鍵ペアを生成
+ * 同じシードと次数からは常に同じ鍵ペアが得られる
+ * 
+ * derive_child_seedと組み合わせると、階層的な鍵ツリーを構築できる:
+ *   root       = 乱数32バイト（安全に保管する）
+ *   child      = derive_child_seed(root, i)  → keypair_from_seed(child)
+ *   grandchild = derive_child_seed(child, j) → keypair_from_seed(grandchild)
+ * 親のシードを保持していれば、任意の子孫の鍵ペアをいつでも再生成できる
+ * 
+ * @param seed 鍵生成シード（32バイト）
+ * @param degree パラメータセット（512・1024、省略時は512）
+ * @returns 公開鍵と秘密鍵のペア
+ */
+#[wasm_bindgen]
+pub fn keypair_from_seed(seed: &[u8], degree: Option<u16>) -> Result<FalconKeyPair, JsValue> {
+    let degree = resolve_degree(degree)?;
+    let mut seed: [u8; SEED_SIZE] = seed.try_into().map_err(|_| {
+        JsValue::from_str(&format!(
+            "Invalid seed size: expected {}, got {}",
+            SEED_SIZE,
+            seed.len()
+        ))
+    })?;
     
-    // FALCON-512の鍵ペアを生成（返り値は(SecretKey, PublicKey)の順）
-    let (sk, pk) = keygen(seed);
+    // 指定した次数の鍵ペアを生成
+    let (public_key, private_key) = degree.keygen(seed);
+    seed.zeroize();
     
     Ok(FalconKeyPair {
-        public_key: pk.to_bytes(),
-        private_key: sk.to_bytes(),
+        public_key,
+        private_key,
+        degree,
     })
 }
 
+/**
+ * 親シードとインデックスから子シードを導出（HKDF-SHA256）
+ * 子シードから親シードや兄弟のシードを求めることはできないため、兄弟の鍵は互いに独立している
+ * 注意: BIP32の非強化導出と異なり、公開鍵だけから子の公開鍵を導出することはできない
+ * 
+ * @param parent_seed 親シード（32バイト）
+ * @param index 子のインデックス
+ * @returns 子シード（32バイト、keypair_from_seedに渡せる）
+ */
+#[wasm_bindgen]
+pub fn derive_child_seed(parent_seed: &[u8], index: u32) -> Result<Vec<u8>, JsValue> {
+    use hkdf::Hkdf;
+    use sha2::Sha256;
+    
+    if parent_seed.len() != SEED_SIZE {
+        return Err(JsValue::from_str(&format!(
+            "Invalid parent seed size: expected {}, got {}",
+            SEED_SIZE,
+            parent_seed.len()
+        )));
+    }
+    
+    // info にインデックスを入れ、同じ親から異なる子シードを導出する
+    let hkdf = Hkdf::<Sha256>::new(Some(CHILD_SEED_SALT), parent_seed);
+    let mut child_seed = vec![0u8; SEED_SIZE];
+    hkdf.expand(&index.to_be_bytes(), &mut child_seed)
+        .map_err(|_| JsValue::from_str("Failed to derive child seed"))?;
+    
+    Ok(child_seed)
+}
+
 /**
  * メッセージに署名
+ * パラメータセットは秘密鍵から判別する
  * 
  * @param message 署名するメッセージ（バイト配列）
- * @param private_key 秘密鍵（バイト配列）
+ * @param private_key 秘密鍵（バイト配列、FALCON-512・FALCON-1024のいずれか）
  * @returns 署名（バイト配列）
  */
 #[wasm_bindgen]
 pub fn sign_message(message: &[u8], private_key: &[u8]) -> Result<Vec<u8>, JsValue> {
-    // 秘密鍵を復元
-    let sk = SecretKey::from_bytes(private_key)
-        .map_err(|e| JsValue::from_str(&format!("Invalid secret key: {:?}", e)))?;
-    
-    // 署名を生成（signは直接Signatureを返す）
-    let signature = sign(message, &sk);
-    
-    Ok(signature.to_bytes())
+    // 秘密鍵を復元できた次数で署名
+    Degree::ALL
+        .into_iter()
+        .find_map(|degree| degree.sign(message, private_key).ok())
+        .ok_or_else(|| {
+            JsValue::from_str(&format!(
+                "Invalid secret key: {} bytes is not a FALCON-512 or FALCON-1024 secret key",
+                private_key.len()
+            ))
+        })
 }
 
 /**
  * 署名を検証
+ * パラメータセットは公開鍵から判別する
  * 
  * @param message 元のメッセージ（バイト配列）
  * @param signature 署名（バイト配列）
- * @param public_key 公開鍵（バイト配列）
+ * @param public_key 公開鍵（バイト配列、FALCON-512・FALCON-1024のいずれか）
  * @returns 検証結果（true: 有効、false: 無効）
  */
 #[wasm_bindgen]
 pub fn verify_signature(message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool, JsValue> {
-    use falcon_rust::falcon512::Signature;
+    Degree::of_public_key(public_key)
+        .and_then(|degree| degree.verify(message, signature, public_key))
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+/**
+ * base64でエンコードされた署名・公開鍵で署名を検証
+ * 鍵や署名を文字列で保持しているアプリ向けに、復号処理を内部で行う
+ * 
+ * @param message_utf8 元のメッセージ（UTF-8文字列）
+ * @param signature_b64 署名（base64、標準のアルファベット・パディング付き）
+ * @param public_key_b64 公開鍵（base64、標準のアルファベット・パディング付き）
+ * @returns 検証結果（true: 有効、false: 無効）。base64や鍵・署名の形式が不正な場合はエラー
+ */
+#[wasm_bindgen]
+pub fn verify_encoded(message_utf8: &str, signature_b64: &str, public_key_b64: &str) -> Result<bool, JsValue> {
+    let signature = decode_base64("signature", signature_b64)?;
+    let public_key = decode_base64("public key", public_key_b64)?;
     
-    // 公開鍵を復元
-    let pk = PublicKey::from_bytes(public_key)
-        .map_err(|e| JsValue::from_str(&format!("Invalid public key: {:?}", e)))?;
+    // 署名は可変長のため、サイズは復元時に確認する
+    verify_signature(message_utf8.as_bytes(), &signature, &public_key)
+}
+
+// 前後の空白を除いてbase64を復号する
+fn decode_base64(name: &str, encoded: &str) -> Result<Vec<u8>, JsValue> {
+    STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| JsValue::from_str(&format!("Invalid base64 in {}: {}", name, e)))
+}
+
+/**
+ * 複数の候補の公開鍵で署名を検証
+ * 鍵のローテーション期間中など、どの鍵で署名されたか分からない場合に使用する
+ * 
+ * @param message 元のメッセージ（バイト配列）
+ * @param signature 署名（バイト配列）
+ * @param public_keys 候補の公開鍵の配列（Uint8Array以外や不正な公開鍵は一致しないものとして扱う）
+ * @returns 署名を検証できた最初の公開鍵のインデックス（どれも一致しない場合はundefined）
+ */
+#[wasm_bindgen]
+pub fn verify_any(message: &[u8], signature: &[u8], public_keys: js_sys::Array) -> Option<usize> {
+    use wasm_bindgen::JsCast;
+    
+    let public_keys = public_keys.iter().map(|key| {
+        key.dyn_into::<js_sys::Uint8Array>()
+            .map(|key| key.to_vec())
+            .unwrap_or_default()
+    });
+    first_verifying_key(message, signature, public_keys)
+}
+
+// 署名を検証できた最初の公開鍵のインデックスを返す
+// 候補の公開鍵の次数は混在していてもよい（署名と次数が異なる公開鍵は一致しない）
+fn first_verifying_key(
+    message: &[u8],
+    signature: &[u8],
+    public_keys: impl IntoIterator<Item = Vec<u8>>,
+) -> Option<usize> {
+    public_keys
+        .into_iter()
+        .position(|public_key| verify_signature(message, signature, &public_key).unwrap_or(false))
+}
+
+/**
+ * クレートのバージョンを取得
+ * 
+ * @returns Cargo.tomlのバージョン文字列
+ */
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+/**
+ * ビルドに含まれている機能を取得
+ * 
+ * @returns アルゴリズム名と利用可能なパラメータセットを持つオブジェクト
+ */
+#[wasm_bindgen]
+pub fn features() -> Result<JsValue, JsValue> {
+    let parameter_sets = js_sys::Array::new();
+    for degree in Degree::ALL {
+        parameter_sets.push(&degree.name().into());
+    }
     
-    // 署名を復元
-    let sig = Signature::from_bytes(signature)
-        .map_err(|e| JsValue::from_str(&format!("Invalid signature: {:?}", e)))?;
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"algorithm".into(), &"FALCON".into())?;
+    js_sys::Reflect::set(&result, &"parameter_sets".into(), &parameter_sets)?;
     
-    // 署名を検証（verifyはboolを返す）
-    Ok(verify(message, &sig, &pk))
+    Ok(result.into())
 }
 
-// 基本的なテスト関数
+// JWKの鍵タイプ（draft-ietf-cose-falcon の AKP 形式）
+// アルゴリズム名は次数ごとに異なる（FN-DSA-512・FN-DSA-1024）
+const JWK_KEY_TYPE: &str = "AKP";
+
+/**
+ * FALCON公開鍵をJWK形式に変換
+ * 
+ * @param public_key 公開鍵（バイト配列、FALCON-512・FALCON-1024のいずれか）
+ * @returns kty・alg・pub（base64url）を持つJWKオブジェクト
+ */
 #[wasm_bindgen]
-pub fn add(a: u32, b: u32) -> u32 {
-    a + b
+pub fn public_key_to_jwk(public_key: &[u8]) -> Result<JsValue, JsValue> {
+    // 公開鍵として復元できることを確認し、次数からアルゴリズム名を決める
+    let degree = Degree::of_public_key(public_key).map_err(|e| JsValue::from_str(&e))?;
+    
+    let jwk = js_sys::Object::new();
+    js_sys::Reflect::set(&jwk, &"kty".into(), &JWK_KEY_TYPE.into())?;
+    js_sys::Reflect::set(&jwk, &"alg".into(), &degree.jwk_algorithm().into())?;
+    js_sys::Reflect::set(&jwk, &"pub".into(), &URL_SAFE_NO_PAD.encode(public_key).into())?;
+    
+    Ok(jwk.into())
+}
+
+/**
+ * JWK形式からFALCON公開鍵を取り出す
+ * 
+ * @param jwk public_key_to_jwkが返す形式のJWKオブジェクト
+ * @returns 公開鍵（バイト配列）
+ */
+#[wasm_bindgen]
+pub fn public_key_from_jwk(jwk: JsValue) -> Result<Vec<u8>, JsValue> {
+    let kty = jwk_string_member(&jwk, "kty")?;
+    if kty != JWK_KEY_TYPE {
+        return Err(JsValue::from_str(&format!("Unsupported JWK key type: {}", kty)));
+    }
+    
+    let alg = jwk_string_member(&jwk, "alg")?;
+    let degree = Degree::from_jwk_algorithm(&alg)
+        .ok_or_else(|| JsValue::from_str(&format!("Unsupported JWK algorithm: {}", alg)))?;
+    
+    let public_key = URL_SAFE_NO_PAD
+        .decode(jwk_string_member(&jwk, "pub")?)
+        .map_err(|e| JsValue::from_str(&format!("Invalid base64url in JWK: {}", e)))?;
+    
+    // algの次数の公開鍵として復元できることを確認
+    degree.check_public_key(&public_key).map_err(|e| JsValue::from_str(&e))?;
+    
+    Ok(public_key)
+}
+
+// JWKの文字列メンバーを取得
+fn jwk_string_member(jwk: &JsValue, name: &str) -> Result<String, JsValue> {
+    js_sys::Reflect::get(jwk, &name.into())?
+        .as_string()
+        .ok_or_else(|| JsValue::from_str(&format!("JWK member \"{}\" is missing or not a string", name)))
+}
+
+// 鍵識別子（KID）として使うSHA-256の先頭のバイト数（base64urlで22文字）
+const KEY_ID_SIZE: usize = 16;
+
+/**
+ * 公開鍵から安定した鍵識別子（KID）を導出
+ * SHA-256(公開鍵) の先頭16バイトをbase64url（パディングなし）で表した22文字の文字列（他のモジュールのkey_idと同じ方式）
+ * 
+ * @param public_key FALCON公開鍵（バイト配列、FALCON-512・FALCON-1024のいずれか）
+ * @returns 鍵識別子（22文字）
+ */
+#[wasm_bindgen]
+pub fn key_id(public_key: &[u8]) -> Result<String, JsValue> {
+    use sha2::{Digest, Sha256};
+    
+    Degree::of_public_key(public_key).map_err(|e| JsValue::from_str(&e))?;
+    let digest = Sha256::digest(public_key);
+    Ok(URL_SAFE_NO_PAD.encode(&digest[..KEY_ID_SIZE]))
+}
+
+// 動作確認で使用する固定の鍵生成シードとメッセージ
+const HEALTH_CHECK_SEED: [u8; 32] = [0x42; 32];
+const HEALTH_CHECK_MESSAGE: &[u8] = b"falcon-rust-wasm health check";
+
+/**
+ * モジュールが正しく動作しているかを確認
+ * すべての次数で固定シードでの鍵生成→署名→検証の往復を行い、改ざんした署名が拒否されることも確認する
+ * 
+ * @returns 正常に動作していればtrue
+ */
+#[wasm_bindgen]
+pub fn health_check() -> bool {
+    Degree::ALL
+        .into_iter()
+        .all(|degree| run_health_check(degree, false) && !run_health_check(degree, true))
+}
+
+// 固定入力で往復を実行し、検証に成功したかを返す
+// corruptがtrueの場合は検証の前に署名を1ビット反転させる
+fn run_health_check(degree: Degree, corrupt: bool) -> bool {
+    let (public_key, private_key) = degree.keygen(HEALTH_CHECK_SEED);
+    let Ok(mut signature) = degree.sign(HEALTH_CHECK_MESSAGE, &private_key) else {
+        return false;
+    };
+    if corrupt {
+        // ヘッダ（1バイト）直後のnonceを反転させる（末尾は無視されるパディングの場合がある）
+        signature[1] ^= 0x01;
+    }
+    
+    degree.verify(HEALTH_CHECK_MESSAGE, &signature, &public_key).unwrap_or(false)
 }
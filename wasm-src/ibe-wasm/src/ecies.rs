@@ -0,0 +1,98 @@
+// ECIES方式の公開鍵暗号化の内部モジュール
+// アイデンティティを使わない通常の公開鍵暗号として、BN254のG1の群だけで暗号化する（ペアリングは使わない）
+// 受信者の鍵ペアは秘密鍵x（スカラー）と公開鍵Q = xP（PはG1の生成元）
+// 暗号化ごとに一時的なスカラーrを選び、共有点 rQ = xR（R = rP）から対称鍵を導出する
+//
+// 暗号文の形式:
+//   ecies := version (1バイト) || R (65バイト、非圧縮のG1の点) || body
+//   body  := nonce (12バイト) || AES-256-GCM(dem_key, メッセージ, 追加認証データ = version || R) || タグ (16バイト)
+// dem_keyは SHA-256(label || R || 共有点 (65バイト、非圧縮形式)) で導出する
+// 対称暗号部分（AES-256-GCM）はハイブリッド暗号化と共通
+
+use miracl_core::bn254::{big::BIG, ecp::ECP, pair};
+use sha2::{Digest, Sha256};
+
+use crate::hybrid::{open_aead, seal_aead, AEAD_NONCE_SIZE, AEAD_TAG_SIZE};
+use crate::ibe_impl::{IBEImpl, RngFailure};
+
+// ECIES暗号文の形式のバージョン
+// バージョン1（SHA-256の鍵ストリームとHMAC-SHA256）の暗号文は復号できない
+const ECIES_VERSION: u8 = 2;
+
+// 非圧縮形式のG1の点のサイズ
+const POINT_SIZE: usize = 65;
+
+// DEM鍵の導出のドメイン分離用ラベル
+const DEM_KEY_LABEL: &[u8] = b"ibe-wasm ecies dem v2";
+
+// G1の点を非圧縮形式のバイト列に変換
+fn point_bytes(point: &ECP) -> [u8; POINT_SIZE] {
+    let mut bytes = [0u8; POINT_SIZE];
+    point.tobytes(&mut bytes, false);
+    bytes
+}
+
+// 一時公開鍵Rと共有点からDEM鍵を導出
+fn derive_key(ephemeral: &[u8], shared_point: &ECP) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(DEM_KEY_LABEL);
+    hasher.update(ephemeral);
+    hasher.update(point_bytes(shared_point));
+    hasher.finalize().into()
+}
+
+// 鍵ペア（秘密鍵x, 公開鍵Q = xP）を生成
+pub fn generate_keypair() -> Result<(BIG, ECP), RngFailure> {
+    let x = IBEImpl::random_big()?;
+    Ok((x, ECP::generator().mul(&x)))
+}
+
+// 公開鍵でメッセージを暗号化
+// 公開鍵は曲線上の点（無限遠点でない）であることを呼び出し側で確認すること
+pub fn seal(public_key: &ECP, message: &[u8]) -> Result<Vec<u8>, RngFailure> {
+    let r = IBEImpl::random_big()?;
+    let ephemeral = point_bytes(&ECP::generator().mul(&r));
+    let shared_point = public_key.mul(&r);
+    
+    let dem_key = derive_key(&ephemeral, &shared_point);
+    
+    let mut ciphertext = Vec::with_capacity(1 + POINT_SIZE + AEAD_NONCE_SIZE + message.len() + AEAD_TAG_SIZE);
+    ciphertext.push(ECIES_VERSION);
+    ciphertext.extend_from_slice(&ephemeral);
+    
+    let body = seal_aead(&dem_key, &ciphertext, message)?;
+    ciphertext.extend_from_slice(&body);
+    
+    Ok(ciphertext)
+}
+
+// 秘密鍵で暗号文を復号
+// 秘密鍵が誤っている場合や暗号文が壊れている場合は、認証タグの不一致として検出される
+pub fn open(private_key: &BIG, ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+    let (&version, rest) = ciphertext
+        .split_first()
+        .ok_or_else(|| "ECIES ciphertext is empty".to_string())?;
+    if version != ECIES_VERSION {
+        return Err(format!(
+            "Unsupported ECIES ciphertext version: expected {}, got {}",
+            ECIES_VERSION, version
+        ));
+    }
+    if rest.len() < POINT_SIZE + AEAD_NONCE_SIZE + AEAD_TAG_SIZE {
+        return Err("ECIES ciphertext is too short".to_string());
+    }
+    
+    // 一時公開鍵Rは正規な非圧縮形式の、位数rの部分群の点に限る
+    let ephemeral = &rest[..POINT_SIZE];
+    let r_point = ECP::frombytes(ephemeral);
+    if r_point.is_infinity() || !pair::g1member(&r_point) || point_bytes(&r_point) != ephemeral {
+        return Err("Invalid ephemeral public key in ECIES ciphertext".to_string());
+    }
+    let shared_point = r_point.mul(private_key);
+    
+    let dem_key = derive_key(ephemeral, &shared_point);
+    
+    let (header, body) = ciphertext.split_at(1 + POINT_SIZE);
+    open_aead(&dem_key, header, body)
+        .ok_or_else(|| "ECIES ciphertext authentication failed: wrong private key or corrupted ciphertext".to_string())
+}
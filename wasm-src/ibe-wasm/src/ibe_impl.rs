@@ -3,40 +3,128 @@
 
 use miracl_core::bn254::{
     big::BIG,
-    ecp::ECP,
+    dbig::DBIG,
+    ecp::{self, ECP},
     ecp2::ECP2,
+    fp::FP,
     fp12::FP12,
+    fp2::FP2,
     pair,
     rom,
 };
+use miracl_core::gcm;
+use miracl_core::hmac;
 use miracl_core::rand::RAND;
-use getrandom::getrandom;
 
 /// WebAssembly環境用のRAND実装
+/// 通常はgetrandomから取得し、seedでシードを与えた場合はシードから決定的に生成する
 pub struct WasmRAND {
     buffer: Vec<u8>,
     pos: usize,
+    // 一度でもエントロピーの取得に失敗したか
+    failed: bool,
+    // seedで設定した決定的な生成器の状態（鍵, カウンタ）。Noneの場合はエントロピー源から取得する
+    deterministic: Option<([u8; 32], u64)>,
 }
 
+/// シードから決定的な生成器の鍵を導出する際のドメイン分離用ラベル
+const SEEDED_RAND_LABEL: &[u8] = b"ibe-wasm seeded rand v1";
+
 impl WasmRAND {
     pub fn new() -> Self {
         WasmRAND {
             buffer: Vec::new(),
             pos: 0,
+            failed: false,
+            deterministic: None,
         }
     }
 
     fn refill(&mut self) {
+        use sha2::{Digest, Sha256};
+        
+        self.pos = 0;
+        // シードが設定されている場合は SHA-256(鍵 || カウンタ) のブロックを順に返す
+        if let Some((key, counter)) = &mut self.deterministic {
+            self.buffer = Sha256::new()
+                .chain_update(*key)
+                .chain_update(counter.to_be_bytes())
+                .finalize()
+                .to_vec();
+            *counter += 1;
+            return;
+        }
+        
         self.buffer = vec![0u8; 32];
-        if getrandom(&mut self.buffer).is_ok() {
-            self.pos = 0;
+        // 取得に失敗した場合や、すべて0のブロック（壊れたエントロピー源）は失敗として記録する
+        if !fill_entropy(&mut self.buffer) || self.buffer.iter().all(|&b| b == 0) {
+            self.failed = true;
+        }
+    }
+}
+
+/// 乱数生成の失敗（エントロピー源が利用できない、または出力が明らかに偏っている）
+#[derive(Debug, Clone, Copy)]
+pub struct RngFailure;
+
+/// エントロピー源からバッファを埋める。失敗した場合はfalseを返す
+#[cfg(not(feature = "test-rng"))]
+fn fill_entropy(buffer: &mut [u8]) -> bool {
+    getrandom::getrandom(buffer).is_ok()
+}
+
+/// エントロピー源からバッファを埋める（テスト用の乱数生成モードに従う）
+#[cfg(feature = "test-rng")]
+fn fill_entropy(buffer: &mut [u8]) -> bool {
+    crate::test_rng::fill(buffer)
+}
+
+impl WasmRAND {
+    /// 一度でもエントロピーの取得に失敗したか
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+}
+
+/// ML-KEMのカプセル化（pqcrypto-std）で使用するためのrand_core実装
+/// 取得に失敗した場合はfailedに記録されるため、呼び出し側で確認すること
+impl rand_core::RngCore for WasmRAND {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest.iter_mut() {
+            *byte = self.getbyte();
         }
     }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
 }
 
+impl rand_core::CryptoRng for WasmRAND {}
+
 impl RAND for WasmRAND {
-    fn seed(&mut self, _rawlen: usize, _raw: &[u8]) {
-        // WebAssembly環境では、getrandomを使用
+    /// rawの先頭rawlenバイトをシードとして、以降の出力を決定的にする
+    /// 鍵は SHA-256(label || シード) で導出し、同じシードからは常に同じバイト列が得られる
+    /// シードが空の場合はエントロピー源（getrandom）からの取得に戻す
+    fn seed(&mut self, rawlen: usize, raw: &[u8]) {
+        use sha2::{Digest, Sha256};
+        
+        let raw = &raw[..rawlen.min(raw.len())];
+        self.deterministic = if raw.is_empty() {
+            None
+        } else {
+            let key = Sha256::new().chain_update(SEEDED_RAND_LABEL).chain_update(raw).finalize();
+            Some((key.into(), 0))
+        };
         self.refill();
     }
 
@@ -50,24 +138,165 @@ impl RAND for WasmRAND {
     }
 }
 
+/// ペアリング値から旧形式（XOR）の対称鍵を導出するKDFのドメイン分離用ラベル
+const KDF_LABEL: &[u8] = b"ibe-wasm kdf v1";
+
+/// ペアリング値からAES-256-GCMの対称鍵を導出するKDFのドメイン分離用ラベル
+/// 旧形式と異なるラベルにより、GCMのフラグを消した暗号文を旧形式として復号しても、GCMの鍵ストリームは得られない
+const GCM_KDF_LABEL: &[u8] = b"ibe-wasm kdf aes-256-gcm v1";
+
+/// RFC 9380の構成でアイデンティティをG2にハッシュする際のドメイン分離タグ
+const HASH_TO_CURVE_DST: &[u8] = b"IBE-WASM-V01-CS01-with-BN254G2_XMD:SHA-256_SVDW_RO_";
+
+/// try-and-incrementでアイデンティティをハッシュする際のドメイン分離用ラベル
+const TRY_AND_INCREMENT_LABEL: &[u8] = b"ibe-wasm try-and-increment v1";
+
+/// アイデンティティの事前ハッシュ（SHA-256）のサイズ
+pub const IDENTITY_DIGEST_SIZE: usize = 32;
+
+/// AES-256-GCMのnonceのサイズ（Vコンポーネントの先頭に置く）
+pub const GCM_NONCE_SIZE: usize = 12;
+
+/// AES-256-GCMの認証タグのサイズ（Vコンポーネントの末尾に置く）
+pub const GCM_TAG_SIZE: usize = 16;
+
+/// Vコンポーネント（メッセージ本体）の暗号化方式
+/// 対称鍵の導出にも使い、方式ごとに異なる鍵を導出する
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Payload {
+    /// AES-256-GCM: V = nonce (12バイト) || 暗号文 || 認証タグ (16バイト)
+    Gcm,
+    /// 旧形式: V = M ⊕ 対称鍵（32バイトごとに繰り返す）。完全性の保護がなく、以前の暗号文の移行にのみ使用する
+    Xor,
+}
+
+/// アイデンティティをG2の点に写像する方式（hash-to-curve）
+/// 他の実装と鍵・暗号文を相互に利用するには、相手と同じ方式を選ぶ必要がある
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HashToCurve {
+    /// RFC 9380の構成（BN254G2_XMD:SHA-256_SVDW_RO_）
+    /// expand_message_xmd(SHA-256)で2つのFP2の元を導出し、Shallue-van de Woestijne写像で点に移して足し、余因子を除く
+    #[default]
+    Rfc9380,
+    /// try-and-increment: SHA-512(label || ID)をx座標の候補とし、曲線上の点になるまで1ずつ増やして余因子を除く
+    TryAndIncrement,
+    /// 旧方式: H(ID)·Q（Qは生成元）
+    /// H(ID)の離散対数が既知になり、1つの秘密鍵から他のアイデンティティの秘密鍵を計算できてしまうため、
+    /// 既存の鍵・暗号文との互換性のためだけに残している
+    Legacy,
+}
+
+impl HashToCurve {
+    /// 名前から方式を取得（"rfc9380"・"try_and_increment"・"legacy"）
+    pub fn from_name(name: &str) -> Option<HashToCurve> {
+        match name {
+            "rfc9380" => Some(HashToCurve::Rfc9380),
+            "try_and_increment" => Some(HashToCurve::TryAndIncrement),
+            "legacy" => Some(HashToCurve::Legacy),
+            _ => None,
+        }
+    }
+
+    /// 方式の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            HashToCurve::Rfc9380 => "rfc9380",
+            HashToCurve::TryAndIncrement => "try_and_increment",
+            HashToCurve::Legacy => "legacy",
+        }
+    }
+}
+
 /// Boneh-Franklin IBEスキームの実装
 pub struct IBEImpl;
 
 impl IBEImpl {
     /// ランダムなBIGを生成
-    pub fn random_big() -> BIG {
+    /// エントロピー源が失敗した場合や結果が0の場合は、弱い鍵を生成しないようエラーを返す
+    pub fn random_big() -> Result<BIG, RngFailure> {
         let mut rng = WasmRAND::new();
         let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
-        BIG::randomnum(&curve_order, &mut rng)
+        let r = BIG::randomnum(&curve_order, &mut rng);
+        if rng.failed || r.iszilch() {
+            return Err(RngFailure);
+        }
+        Ok(r)
     }
 
-    /// アイデンティティをハッシュ化してECP2に変換
-    pub fn hash_identity(identity: &str) -> ECP2 {
+    /// アイデンティティを事前ハッシュする: SHA-256(ID)
+    pub fn prehash_identity(identity: &[u8]) -> [u8; IDENTITY_DIGEST_SIZE] {
+        use sha2::{Sha256, Digest};
+        
+        Sha256::digest(identity).into()
+    }
+
+    /// アイデンティティを写像・鍵導出・合成IVに渡すバイト列に変換
+    /// 旧方式以外は32バイトに事前ハッシュし、アイデンティティの長さが写像のコストに影響しないようにする
+    /// （旧方式は既存の鍵・暗号文との互換性のため、アイデンティティをそのまま使う）
+    /// 鍵の抽出と暗号化・復号で必ず同じ変換を通すこと
+    pub fn identity_input(identity: &str, method: HashToCurve) -> Vec<u8> {
+        match method {
+            HashToCurve::Legacy => identity.as_bytes().to_vec(),
+            _ => Self::prehash_identity(identity.as_bytes()).to_vec(),
+        }
+    }
+
+    /// アイデンティティ（identity_inputで変換したもの）をハッシュ化してECP2に変換
+    pub fn hash_identity(identity: &[u8], method: HashToCurve) -> ECP2 {
+        match method {
+            HashToCurve::Rfc9380 => Self::hash_identity_rfc9380(identity),
+            HashToCurve::TryAndIncrement => Self::hash_identity_try_and_increment(identity),
+            HashToCurve::Legacy => Self::hash_identity_legacy(identity),
+        }
+    }
+
+    /// RFC 9380のhash_to_curve（ランダムオラクル版）でアイデンティティをG2に写像
+    fn hash_identity_rfc9380(identity: &[u8]) -> ECP2 {
+        let [u0, u1] = Self::hash_to_field_fp2(identity);
+        
+        let mut q = ECP2::map2point(&u0);
+        q.add(&ECP2::map2point(&u1));
+        q.cfp();
+        q.affine();
+        q
+    }
+
+    /// RFC 9380のhash_to_field（m = 2, count = 2）
+    /// expand_message_xmd(SHA-256)の出力をL = ceil((ceil(log2(p)) + k) / 8)バイトずつ区切り、pで剰余を取る
+    fn hash_to_field_fp2(message: &[u8]) -> [FP2; 2] {
+        let modulus = BIG::new_ints(&rom::MODULUS);
+        let modulus_bits = modulus.nbits();
+        let element_size = (modulus_bits + ecp::AESKEY * 8).div_ceil(8);
+        
+        let mut okm = vec![0u8; 4 * element_size];
+        hmac::xmd_expand(hmac::MC_SHA2, ecp::HASH_TYPE, &mut okm, 4 * element_size, HASH_TO_CURVE_DST, message);
+        
+        let e: Vec<FP> = okm
+            .chunks_exact(element_size)
+            .map(|chunk| FP::new_big(&DBIG::frombytes(chunk).ctdmod(&modulus, 8 * element_size - modulus_bits)))
+            .collect();
+        [FP2::new_fps(&e[0], &e[1]), FP2::new_fps(&e[2], &e[3])]
+    }
+
+    /// try-and-incrementでアイデンティティをG2に写像
+    fn hash_identity_try_and_increment(identity: &[u8]) -> ECP2 {
+        use sha2::{Sha512, Digest};
+        
+        let mut hasher = Sha512::new();
+        hasher.update(TRY_AND_INCREMENT_LABEL);
+        hasher.update(identity);
+        
+        // mapitはハッシュをpで剰余を取ってx座標の候補とし、曲線上の点を探してから余因子を除く
+        ECP2::mapit(&hasher.finalize())
+    }
+
+    /// 旧方式でアイデンティティをG2に写像（H(ID)·Q）
+    fn hash_identity_legacy(identity: &[u8]) -> ECP2 {
         // SHA-256を使用してハッシュ化
         use sha2::{Sha256, Digest};
         
         let mut hasher = Sha256::new();
-        hasher.update(identity.as_bytes());
+        hasher.update(identity);
         let hash = hasher.finalize();
         
         // ハッシュからBIGを作成
@@ -81,54 +310,187 @@ impl IBEImpl {
         q
     }
 
-    /// メッセージをハッシュ化（SHA-256）
-    pub fn hash_message(data: &[u8]) -> [u8; 32] {
+    /// ペアリング値と暗号化の文脈（アイデンティティ）から対称鍵を導出
+    /// SHA-256(label || len(context) (4バイト) || context || ペアリング値)
+    /// 同じペアリング値でもアイデンティティやVの暗号化方式が異なれば別の鍵になる
+    pub fn derive_key(p: &FP12, context: &[u8], payload: Payload) -> [u8; 32] {
         use sha2::{Sha256, Digest};
-        let mut hasher = Sha256::new();
-        hasher.update(data);
-        hasher.finalize().into()
-    }
-
-    /// ペアリング演算の結果をハッシュ化
-    pub fn hash_pairing_result(p: &FP12) -> [u8; 32] {
+        
         let mut bytes = vec![0u8; 384]; // FP12のサイズ
         let mut p_copy = FP12::new_copy(p);
         p_copy.tobytes(&mut bytes);
-        Self::hash_message(&bytes)
+        
+        let mut hasher = Sha256::new();
+        hasher.update(match payload {
+            Payload::Gcm => GCM_KDF_LABEL,
+            Payload::Xor => KDF_LABEL,
+        });
+        hasher.update((context.len() as u32).to_be_bytes());
+        hasher.update(context);
+        hasher.update(&bytes);
+        hasher.finalize().into()
     }
 
     /// Setup: マスター鍵ペアを生成
-    pub fn setup() -> (BIG, ECP) {
+    pub fn setup() -> Result<(BIG, ECP), RngFailure> {
+        Self::setup_with(&mut WasmRAND::new())
+    }
+
+    /// Setup: シードから決定的にマスター鍵ペアを生成
+    /// 同じシードからは常に同じマスター秘密鍵sが得られる（他の実装とのテストベクタや鍵セレモニー向け）
+    pub fn setup_from_seed(seed: &[u8]) -> Result<(BIG, ECP), RngFailure> {
+        let mut rng = WasmRAND::new();
+        rng.seed(seed.len(), seed);
+        Self::setup_with(&mut rng)
+    }
+
+    /// Setup: 指定した乱数生成器でマスター鍵ペアを生成
+    fn setup_with(rng: &mut WasmRAND) -> Result<(BIG, ECP), RngFailure> {
         // マスター秘密鍵sをランダムに選択
-        let s = Self::random_big();
+        let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
+        let s = BIG::randomnum(&curve_order, rng);
+        if rng.failed || s.iszilch() {
+            return Err(RngFailure);
+        }
         
         // 公開パラメータP_pub = sPを計算（PはECPの生成元）
         let p = ECP::generator();
         let p_pub = p.mul(&s);
         
-        (s, p_pub)
+        Ok((s, p_pub))
     }
 
     /// Extract: アイデンティティから秘密鍵を抽出
-    pub fn extract(s: &BIG, identity: &str) -> ECP2 {
+    pub fn extract(s: &BIG, identity: &[u8], method: HashToCurve) -> ECP2 {
         // アイデンティティIDをハッシュ化してH(ID)を計算
-        let h_id = Self::hash_identity(identity);
+        let h_id = Self::hash_identity(identity, method);
         
         // 秘密鍵d_ID = sH(ID)を計算
         h_id.mul(s)
     }
 
-    /// Encrypt: メッセージを暗号化
-    pub fn encrypt(p_pub: &ECP, identity: &str, message: &[u8]) -> (ECP, Vec<u8>) {
-        // ランダムなrを選択
-        let r = Self::random_big();
+    /// 合成IV用のrを導出: r = HMAC-SHA256(message_key, len(ID) || ID || M) mod q
+    /// 同じ鍵・アイデンティティ・メッセージからは常に同じrが得られる
+    pub fn synthetic_r(message_key: &[u8], identity: &[u8], message: &[u8]) -> BIG {
+        // アイデンティティとメッセージの境界が曖昧にならないよう長さを前置する
+        let mut input = Vec::with_capacity(8 + identity.len() + message.len());
+        input.extend_from_slice(&(identity.len() as u64).to_be_bytes());
+        input.extend_from_slice(identity);
+        input.extend_from_slice(message);
+        
+        let mut tag = [0u8; hmac::SHA256];
+        hmac::hmac1(hmac::MC_SHA2, hmac::SHA256, &mut tag, hmac::SHA256, message_key, &input);
+        
+        let mut r = BIG::frombytes(&tag);
+        let curve_order = BIG::new_ints(&rom::CURVE_ORDER);
+        r.rmod(&curve_order);
+        r
+    }
+
+    /// Encrypt: メッセージをAES-256-GCMで暗号化
+    /// V = nonce (12バイト) || 暗号文 || 認証タグ (16バイト)
+    /// 認証タグはUも追加認証データとして含むため、U || Vのどこを改ざんしても復号時に検出できる
+    pub fn encrypt(
+        p_pub: &ECP,
+        identity: &[u8],
+        message: &[u8],
+        method: HashToCurve,
+    ) -> Result<(ECP, Vec<u8>), RngFailure> {
+        // ランダムなrとnonceを選択
+        let r = Self::random_big()?;
+        let nonce = Self::random_nonce()?;
+        Ok(Self::encrypt_with_r(p_pub, identity, message, &r, &nonce, method))
+    }
+
+    /// Encrypt: 指定したrとnonceでメッセージをAES-256-GCMで暗号化
+    /// 対称鍵はrごとに異なるため、rを使い回さない限りnonceが重複しても鍵とnonceの組は重複しない
+    pub fn encrypt_with_r(
+        p_pub: &ECP,
+        identity: &[u8],
+        message: &[u8],
+        r: &BIG,
+        nonce: &[u8; GCM_NONCE_SIZE],
+        method: HashToCurve,
+    ) -> (ECP, Vec<u8>) {
+        let (u, hash_key) = Self::encapsulate(p_pub, identity, r, method);
         
+        // V = nonce || AES-256-GCM(KDF(e(P_pub, H(ID))^r, ID), M, 追加認証データ = U) || タグ
+        let mut v = vec![0u8; GCM_NONCE_SIZE + message.len() + GCM_TAG_SIZE];
+        let (nonce_out, rest) = v.split_at_mut(GCM_NONCE_SIZE);
+        let (body, tag) = rest.split_at_mut(message.len());
+        nonce_out.copy_from_slice(nonce);
+        gcm::encrypt(body, tag, &hash_key, nonce, &Self::associated_data(&u), message);
+        
+        (u, v)
+    }
+
+    /// Decrypt: AES-256-GCMの暗号文を復号化
+    /// identityには秘密鍵の抽出に使ったアイデンティティ（identity_inputで変換したもの）を指定する
+    /// Vが短すぎる場合や認証タグが一致しない場合（改ざん・鍵の不一致）はNoneを返す
+    pub fn decrypt(d_id: &ECP2, identity: &[u8], u: &ECP, v: &[u8]) -> Option<Vec<u8>> {
+        let mut message = vec![0u8; v.len().checked_sub(GCM_NONCE_SIZE + GCM_TAG_SIZE)?];
+        Self::decrypt_into(d_id, identity, u, v, &mut message).then_some(message)
+    }
+
+    /// Decrypt: AES-256-GCMの暗号文を復号化し、呼び出し側のバッファに書き込む
+    /// outの長さはVからnonceとタグを除いた長さであること
+    /// 認証タグが一致しない場合はoutを0で埋めてfalseを返す（検証前の平文を残さない）
+    pub fn decrypt_into(d_id: &ECP2, identity: &[u8], u: &ECP, v: &[u8], out: &mut [u8]) -> bool {
+        if v.len() != GCM_NONCE_SIZE + out.len() + GCM_TAG_SIZE {
+            return false;
+        }
+        let (nonce, rest) = v.split_at(GCM_NONCE_SIZE);
+        let (body, tag) = rest.split_at(out.len());
+        
+        let hash_key = Self::decapsulate(d_id, identity, u, Payload::Gcm);
+        
+        let mut expected_tag = [0u8; GCM_TAG_SIZE];
+        gcm::decrypt(out, &mut expected_tag, &hash_key, nonce, &Self::associated_data(u), body);
+        if !crate::hybrid::constant_time_eq(&expected_tag, tag) {
+            out.fill(0);
+            return false;
+        }
+        true
+    }
+
+    /// Decrypt: 旧形式（対称鍵を繰り返したXOR）の暗号文を復号化
+    /// 旧形式での暗号化はできない（以前に作られた暗号文の移行のためだけに残している）
+    /// 完全性の保護がないため、鍵が異なる場合や暗号文が壊れている場合も無関係なバイト列が返る
+    pub fn decrypt_xor(d_id: &ECP2, identity: &[u8], u: &ECP, v: &[u8]) -> Vec<u8> {
+        let hash_key = Self::decapsulate(d_id, identity, u, Payload::Xor);
+        
+        // M = V ⊕ KDF(e(d_ID, U), ID)を計算
+        v.iter().enumerate().map(|(i, &c)| c ^ hash_key[i % 32]).collect()
+    }
+
+    /// AES-256-GCMの追加認証データ（Uの非圧縮形式の65バイト）
+    fn associated_data(u: &ECP) -> [u8; 65] {
+        let mut u_bytes = [0u8; 65];
+        u.tobytes(&mut u_bytes, false);
+        u_bytes
+    }
+
+    /// ランダムなAES-GCMのnonceを生成
+    fn random_nonce() -> Result<[u8; GCM_NONCE_SIZE], RngFailure> {
+        let mut rng = WasmRAND::new();
+        let mut nonce = [0u8; GCM_NONCE_SIZE];
+        for byte in nonce.iter_mut() {
+            *byte = rng.getbyte();
+        }
+        if rng.failed {
+            return Err(RngFailure);
+        }
+        Ok(nonce)
+    }
+
+    /// U = rPと対称鍵KDF(e(P_pub, H(ID))^r, ID)を計算
+    fn encapsulate(p_pub: &ECP, identity: &[u8], r: &BIG, method: HashToCurve) -> (ECP, [u8; 32]) {
         // U = rPを計算
         let p = ECP::generator();
-        let u = p.mul(&r);
+        let u = p.mul(r);
         
         // H(ID)を計算
-        let h_id = Self::hash_identity(identity);
+        let h_id = Self::hash_identity(identity, method);
         
         // e(P_pub, H(ID))^rを計算
         // Boneh-Franklinスキームでは、e(P_pub, H(ID))^r を計算する必要がある
@@ -140,35 +502,18 @@ impl IBEImpl {
         let pairing_final = pair::fexp(&pairing);
         
         // r乗する: e(P_pub, H(ID))^r
-        let pairing_r = pairing_final.pow(&r);
+        let pairing_r = pairing_final.pow(r);
         
-        // H(e(P_pub, H(ID))^r)を計算
-        let hash_key = Self::hash_pairing_result(&pairing_r);
-        
-        // V = M ⊕ H(e(P_pub, H(ID))^r)を計算
-        let mut v = Vec::with_capacity(message.len());
-        for (i, &byte) in message.iter().enumerate() {
-            v.push(byte ^ hash_key[i % 32]);
-        }
-        
-        (u, v)
+        // KDF(e(P_pub, H(ID))^r, ID)を計算（新しい暗号文は常にAES-256-GCM）
+        (u, Self::derive_key(&pairing_r, identity, Payload::Gcm))
     }
 
-    /// Decrypt: 暗号文を復号化
-    pub fn decrypt(d_id: &ECP2, u: &ECP, v: &[u8]) -> Vec<u8> {
+    /// 秘密鍵とUから、Vの暗号化方式に応じた対称鍵KDF(e(d_ID, U), ID)を計算
+    fn decapsulate(d_id: &ECP2, identity: &[u8], u: &ECP, payload: Payload) -> [u8; 32] {
         // e(d_ID, U)を計算
         let pairing = pair::ate(d_id, u);
         let pairing_final = pair::fexp(&pairing);
         
-        // H(e(d_ID, U))を計算
-        let hash_key = Self::hash_pairing_result(&pairing_final);
-        
-        // M = V ⊕ H(e(d_ID, U))を計算
-        let mut message = Vec::with_capacity(v.len());
-        for (i, &byte) in v.iter().enumerate() {
-            message.push(byte ^ hash_key[i % 32]);
-        }
-        
-        message
+        Self::derive_key(&pairing_final, identity, payload)
     }
 }
@@ -1,7 +1,19 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use wasm_bindgen::prelude::*;
+use zeroize::{Zeroize, Zeroizing};
 
+mod ecies;
+#[path = "../../shared/pairing/events.rs"]
+mod events;
+#[path = "../../shared/pairing/hybrid.rs"]
+mod hybrid;
 mod ibe_impl;
-use ibe_impl::IBEImpl;
+#[cfg(feature = "test-rng")]
+#[path = "../../shared/pairing/test_rng.rs"]
+mod test_rng;
+use events::{emit_event, muted};
+use ibe_impl::{HashToCurve, IBEImpl, RngFailure, WasmRAND, GCM_NONCE_SIZE, GCM_TAG_SIZE, IDENTITY_DIGEST_SIZE};
 
 // wasm-bindgenの初期化
 #[wasm_bindgen(start)]
@@ -10,14 +22,395 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
-// 基本的なテスト関数
+/// クレートのバージョンを取得
 #[wasm_bindgen]
-pub fn add(a: u32, b: u32) -> u32 {
-    a + b
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+/// ビルドに含まれている機能（スキームと曲線）を取得
+#[wasm_bindgen]
+pub fn features() -> Result<JsValue, JsValue> {
+    let schemes = js_sys::Array::new();
+    schemes.push(&"Boneh-Franklin".into());
+    schemes.push(&"ECIES".into());
+    
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"schemes".into(), &schemes)?;
+    js_sys::Reflect::set(&result, &"curve".into(), &"BN254".into())?;
+    
+    Ok(result.into())
+}
+
+/// 乱数生成の失敗をJSのエラーに変換し、rng_failedイベントを通知
+impl From<RngFailure> for JsValue {
+    fn from(_: RngFailure) -> JsValue {
+        let message = "Random number generation failed: the entropy source is unavailable, refusing to generate keys or ciphertexts";
+        emit_event("rng_failed", message);
+        JsValue::from_str(message)
+    }
+}
+
+/// ハイブリッド暗号化のエラーメッセージ
+impl std::fmt::Display for hybrid::HybridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            hybrid::HybridError::InvalidPublicKeySize { expected, actual } => {
+                write!(f, "Invalid Kyber public key size: expected {}, got {}", expected, actual)
+            }
+            hybrid::HybridError::InvalidPrivateKeySize { expected, actual } => {
+                write!(f, "Invalid Kyber private key size: expected {}, got {}", expected, actual)
+            }
+            hybrid::HybridError::Empty => write!(f, "Hybrid ciphertext is empty"),
+            hybrid::HybridError::UnsupportedVersion { expected, actual } => {
+                write!(f, "Unsupported hybrid ciphertext version: expected {}, got {}", expected, actual)
+            }
+            hybrid::HybridError::TooShort => write!(f, "Hybrid ciphertext is too short"),
+            hybrid::HybridError::AuthenticationFailed => write!(
+                f,
+                "Hybrid ciphertext authentication failed: both the IBE private key and the matching Kyber private key are required"
+            ),
+        }
+    }
+}
+
+/// テスト専用: WasmRANDの乱数生成モードを切り替える
+/// test-rng featureを有効にしたビルドでのみ公開される（本番ビルドには含まれない）
+/// modeは "os"（通常）、"zero_bytes"（すべて0）、"fail"（取得失敗）、"deterministic"（seedから決定的に生成）のいずれか
+#[cfg(feature = "test-rng")]
+#[wasm_bindgen]
+pub fn set_test_rng_mode(mode: &str, seed: &[u8]) -> Result<(), JsValue> {
+    use test_rng::TestRngMode;
+    
+    let mode = match mode {
+        "os" => TestRngMode::Os,
+        "zero_bytes" => TestRngMode::ZeroBytes,
+        "fail" => TestRngMode::Fail,
+        "deterministic" => TestRngMode::Deterministic {
+            seed: seed.to_vec(),
+            counter: 0,
+        },
+        _ => return Err(JsValue::from_str(&format!("Unknown RNG mode: {}", mode))),
+    };
+    test_rng::set_mode(mode);
+    Ok(())
+}
+
+// 動作確認で暗号化する固定メッセージとアイデンティティ
+const HEALTH_CHECK_MESSAGE: &[u8] = b"ibe-wasm health check";
+const HEALTH_CHECK_IDENTITY: &str = "health-check@example.com";
+
+/// モジュールが正しく動作しているかを確認
+/// 鍵生成→鍵抽出→暗号化→復号の往復を行い、
+/// 改ざんした暗号文が元のメッセージに復号されないことも確認する
+#[wasm_bindgen]
+pub fn health_check() -> bool {
+    run_health_check(false) && !run_health_check(true)
+}
+
+/// 暗号論的に安全な乱数のバイト列をnバイト生成
+/// 鍵生成と同じエントロピー源（WasmRAND）を使用し、取得に失敗した場合はエラーを返す
+/// nonceやsaltなど、アプリケーション側で必要な乱数に使える（Math.randomの代わり）
+#[wasm_bindgen]
+pub fn random_bytes(n: usize) -> Result<Vec<u8>, JsValue> {
+    let mut rng = WasmRAND::new();
+    let mut bytes = vec![0u8; n];
+    rand_core::RngCore::fill_bytes(&mut rng, &mut bytes);
+    if rng.failed() {
+        return Err(RngFailure.into());
+    }
+    Ok(bytes)
+}
+
+/// 固定入力で往復を実行し、元のメッセージが復元できたかを返す
+/// corruptがtrueの場合は復号前に暗号文を1ビット反転させる
+fn run_health_check(corrupt: bool) -> bool {
+    // 乱数生成に失敗した場合は動作していないものとして扱う
+    let Ok((s, p_pub)) = IBEImpl::setup() else {
+        return false;
+    };
+    let identity = IBEImpl::identity_input(HEALTH_CHECK_IDENTITY, HashToCurve::default());
+    let d_id = IBEImpl::extract(&s, &identity, HashToCurve::default());
+    let Ok((u, mut v)) = IBEImpl::encrypt(&p_pub, &identity, HEALTH_CHECK_MESSAGE, HashToCurve::default()) else {
+        return false;
+    };
+    if corrupt {
+        v[0] ^= 0x01;
+    }
+    IBEImpl::decrypt(&d_id, &identity, &u, &v).as_deref() == Some(HEALTH_CHECK_MESSAGE)
+}
+
+// 暗号文のUコンポーネント（非圧縮のECP）のサイズ
+const U_COMPONENT_SIZE: usize = 65;
+
+// 暗号文のVコンポーネントの最小サイズ
+// 空のVは「空の平文」と「壊れた暗号文」の区別がつかないため、1バイト以上を必須とする
+const MIN_V_COMPONENT_SIZE: usize = 1;
+
+// 暗号文の先頭に記録するhash-to-curveの方式のID
+// 旧形式（XOR）の旧方式（legacy）の暗号文はIDを持たず、Uコンポーネントの先頭（非圧縮形式の0x04）から始まる
+// IDはAES-256-GCMを使う暗号文（GCMのフラグ付き）にのみ使い、フラグのないIDは拒否する
+const HASH_TO_CURVE_ID_RFC9380: u8 = 0x01;
+const HASH_TO_CURVE_ID_TRY_AND_INCREMENT: u8 = 0x02;
+const HASH_TO_CURVE_ID_LEGACY: u8 = 0x03;
+
+// hash-to-curveの方式のIDに立てる、VコンポーネントがAES-256-GCMで暗号化されていることを示すフラグ
+// 新しく作る暗号文には常にフラグを立てる（旧形式（対称鍵を繰り返したXOR）はdecrypt_legacyでの移行のみ対応）
+// フラグ自体は認証されないため、フラグのない暗号文は通常の復号では受け付けない
+const GCM_PAYLOAD_FLAG: u8 = 0x10;
+
+/// 暗号文に記録するhash-to-curveの方式のID
+fn hash_to_curve_id(method: HashToCurve) -> u8 {
+    match method {
+        HashToCurve::Rfc9380 => HASH_TO_CURVE_ID_RFC9380,
+        HashToCurve::TryAndIncrement => HASH_TO_CURVE_ID_TRY_AND_INCREMENT,
+        HashToCurve::Legacy => HASH_TO_CURVE_ID_LEGACY,
+    }
+}
+
+/// 暗号文の先頭からhash-to-curveの方式とAES-256-GCMのフラグを読み取り、残り（U || V）を返す
+fn split_hash_to_curve(ciphertext: &[u8]) -> Result<(HashToCurve, bool, &[u8]), JsValue> {
+    match ciphertext.split_first() {
+        // 旧形式の旧方式の暗号文、または空の暗号文（長さの検査でエラーになる）
+        Some((&UNCOMPRESSED_POINT_PREFIX, _)) | None => Ok((HashToCurve::Legacy, false, ciphertext)),
+        Some((&id, rest)) => {
+            let method = match id & !GCM_PAYLOAD_FLAG {
+                HASH_TO_CURVE_ID_RFC9380 => HashToCurve::Rfc9380,
+                HASH_TO_CURVE_ID_TRY_AND_INCREMENT => HashToCurve::TryAndIncrement,
+                HASH_TO_CURVE_ID_LEGACY => HashToCurve::Legacy,
+                _ => return Err(decrypt_error(&format!("Unknown hash-to-curve method id in ciphertext: {}", id))),
+            };
+            // 方式のIDを持つ暗号文はGCMのフラグ付きでしか受け付けない（フラグのない旧形式はヘッダなしの0x04から始まるものだけ）
+            if id & GCM_PAYLOAD_FLAG == 0 {
+                return Err(decrypt_error(&format!(
+                    "Ciphertext with hash-to-curve method id {} is missing the AES-256-GCM flag",
+                    id
+                )));
+            }
+            Ok((method, true, rest))
+        }
+    }
+}
+
+/// hash-to-curveの方式を名前から取得
+fn parse_hash_to_curve(name: &str) -> Result<HashToCurve, JsValue> {
+    HashToCurve::from_name(name).ok_or_else(|| {
+        JsValue::from_str(&format!(
+            "Unknown hash-to-curve method: {} (expected \"rfc9380\", \"try_and_increment\" or \"legacy\")",
+            name
+        ))
+    })
+}
+
+/// 事前ハッシュしたアイデンティティを、写像・鍵導出に渡すバイト列として取り込む
+/// 旧方式はアイデンティティをそのまま使うため、事前ハッシュからは鍵の抽出・暗号化ができない
+fn prehashed_identity_input(identity_digest: &[u8], method: HashToCurve) -> Result<Vec<u8>, JsValue> {
+    if method == HashToCurve::Legacy {
+        return Err(JsValue::from_str("Pre-hashed identities are not supported by the legacy hash-to-curve method"));
+    }
+    if identity_digest.len() != IDENTITY_DIGEST_SIZE {
+        return Err(JsValue::from_str(&format!(
+            "Invalid identity digest length: expected {}, got {}",
+            IDENTITY_DIGEST_SIZE,
+            identity_digest.len()
+        )));
+    }
+    Ok(identity_digest.to_vec())
+}
+
+/// アイデンティティを事前ハッシュ（SHA-256）した32バイトを取得
+/// extract_prehashed・encrypt_prehashedに渡すと、アイデンティティそのものを渡した場合と同じ鍵・暗号文になる
+/// 長いアイデンティティを繰り返し使う場合は、一度だけハッシュして保存しておける
+#[wasm_bindgen]
+pub fn prehash_identity(identity: &str) -> Vec<u8> {
+    IBEImpl::prehash_identity(identity.as_bytes()).to_vec()
+}
+
+// 合成IV方式で使用するメッセージ鍵の最小サイズ（HMAC-SHA256の出力長）
+const MIN_MESSAGE_KEY_SIZE: usize = 32;
+
+// AES-256-GCMの認証タグが一致しなかった場合のエラーメッセージ
+const AUTHENTICATION_FAILED: &str =
+    "IBE ciphertext authentication failed: wrong private key or corrupted ciphertext";
+
+// AES-256-GCMのフラグのない（完全性の保護がない）暗号文を通常の復号に渡した場合のエラーメッセージ
+const UNAUTHENTICATED_FORMAT: &str =
+    "IBE ciphertext uses the unauthenticated legacy format; migrate it with decrypt_legacy";
+
+/// 復号エラーを生成し、decrypt_failedイベントを通知
+fn decrypt_error(reason: &str) -> JsValue {
+    emit_event("decrypt_failed", reason);
+    JsValue::from_str(reason)
+}
+
+/// base64url形式の暗号文をバイト列に戻す
+/// 改行などの空白や末尾のパディング（=）は取り除いてから復号する
+fn decode_ciphertext_string(encoded: &str) -> Result<Vec<u8>, JsValue> {
+    let compact: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+    URL_SAFE_NO_PAD
+        .decode(compact.trim_end_matches('='))
+        .map_err(|e| decrypt_error(&format!("Invalid base64url ciphertext: {}", e)))
+}
+
+/// 点の検証エラーを生成し、point_rejectedイベントを通知
+fn rejected_point_error(reason: &str) -> JsValue {
+    emit_event("point_rejected", reason);
+    JsValue::from_str(reason)
+}
+
+// 直列化された群要素のサイズ（非圧縮形式、鍵・暗号文と同じバッファサイズ）
+const G1_POINT_SIZE: usize = 65;
+const G2_POINT_SIZE: usize = 130;
+
+/// 直列化された群要素（G1またはG2）が曲線上の有効な点か確認
+/// 長さでG1とG2を判別し、ecp_from_bytes・ecp2_from_bytesと同じ検証（無限遠点・部分群）を行う
+fn validate_point(point_bytes: &[u8]) -> Result<(), JsValue> {
+    match point_bytes.len() {
+        G1_POINT_SIZE => ecp_from_bytes(point_bytes, false).map(|_| ()),
+        G2_POINT_SIZE => ecp2_from_bytes(point_bytes, false).map(|_| ()),
+        len => Err(rejected_point_error(&format!(
+            "Invalid point length: expected {} (G1) or {} (G2), got {}",
+            G1_POINT_SIZE, G2_POINT_SIZE, len
+        ))),
+    }
+}
+
+/// マスター秘密鍵のサイズ（BN254のスカラー）
+const MASTER_KEY_SIZE: usize = 32;
+
+/// setup_from_seedに渡すシードのサイズ
+const SETUP_SEED_SIZE: usize = 32;
+
+// 非圧縮形式の点の先頭バイト
+const UNCOMPRESSED_POINT_PREFIX: u8 = 0x04;
+
+/// 公開パラメータ（非圧縮のG1の点）のように見えるバイト列か
+/// マスター秘密鍵と公開パラメータを取り違えた場合に、分かりやすいエラーを返すために使う
+fn looks_like_public_params(bytes: &[u8]) -> bool {
+    bytes.len() == U_COMPONENT_SIZE && bytes[0] == UNCOMPRESSED_POINT_PREFIX
+}
+
+/// マスター秘密鍵のスカラーを取り込む
+/// 0 < s < 曲線の位数 であることを、秘密の値に依存した分岐なしで検証する
+fn import_master_scalar(bytes: &[u8]) -> Result<miracl_core::bn254::big::BIG, JsValue> {
+    use miracl_core::bn254::big::BIG;
+    
+    if looks_like_public_params(bytes) {
+        return Err(JsValue::from_str("Invalid master key: looks like public params, not master key"));
+    }
+    if bytes.len() != MASTER_KEY_SIZE {
+        return Err(JsValue::from_str("Invalid master key length"));
+    }
+    if !scalar_in_range(bytes) {
+        return Err(JsValue::from_str("Master key out of range: must satisfy 0 < s < curve order"));
+    }
+    
+    Ok(BIG::frombytes(bytes))
+}
+
+/// 32バイトのスカラーが 0 < s < 曲線の位数 を満たすか、秘密の値に依存した分岐なしで確認
+fn scalar_in_range(bytes: &[u8]) -> bool {
+    use miracl_core::bn254::{big::BIG, rom};
+    
+    let mut order = [0u8; MASTER_KEY_SIZE];
+    BIG::new_ints(&rom::CURVE_ORDER).tobytes(&mut order);
+    
+    // bytes - order を下位バイトから計算し、最終的に借りが残れば bytes < order
+    let mut borrow: u16 = 0;
+    let mut accumulated: u8 = 0;
+    for i in (0..MASTER_KEY_SIZE).rev() {
+        let diff = (bytes[i] as u16).wrapping_sub(order[i] as u16).wrapping_sub(borrow);
+        borrow = (diff >> 8) & 1;
+        accumulated |= bytes[i];
+    }
+    let nonzero = ((accumulated as u16).wrapping_neg() >> 8) & 1;
+    
+    borrow & nonzero == 1
+}
+
+/// 直列化されたG1の点を復元
+/// miracl_coreのfrombytesは曲線上にない点を無限遠点として返すため、無限遠点は常に拒否する
+/// さらに位数rの部分群の元であることを確認する（BN254のG1は余因子が1だが、G2と同じ検証を通す）
+/// strictモードでは、再直列化した結果が入力と一致しない非正規なエンコーディングも拒否する
+fn ecp_from_bytes(bytes: &[u8], strict: bool) -> Result<miracl_core::bn254::ecp::ECP, JsValue> {
+    use miracl_core::bn254::{ecp::ECP, pair};
+    
+    let point = ECP::frombytes(bytes);
+    if point.is_infinity() {
+        return Err(rejected_point_error("Invalid G1 point: not on the curve or point at infinity"));
+    }
+    if !pair::g1member(&point) {
+        return Err(rejected_point_error("Invalid G1 point: not in the prime-order subgroup"));
+    }
+    if strict {
+        let mut canonical = vec![0u8; bytes.len()];
+        point.tobytes(&mut canonical, false);
+        if canonical != bytes {
+            return Err(rejected_point_error("Non-canonical G1 point encoding"));
+        }
+    }
+    Ok(point)
+}
+
+/// 直列化されたG2の点を復元
+/// 無限遠点（曲線上にない点を含む）と、余因子による小さい部分群の点は常に拒否する
+/// strictモードでは、再直列化した結果が入力と一致しない非正規なエンコーディングも拒否する
+fn ecp2_from_bytes(bytes: &[u8], strict: bool) -> Result<miracl_core::bn254::ecp2::ECP2, JsValue> {
+    use miracl_core::bn254::{ecp2::ECP2, pair};
+    
+    let point = ECP2::frombytes(bytes);
+    if point.is_infinity() {
+        return Err(rejected_point_error("Invalid G2 point: not on the curve or point at infinity"));
+    }
+    if !pair::g2member(&point) {
+        return Err(rejected_point_error("Invalid G2 point: not in the prime-order subgroup"));
+    }
+    if strict {
+        let mut canonical = vec![0u8; bytes.len()];
+        point.tobytes(&mut canonical, false);
+        if canonical != bytes {
+            return Err(rejected_point_error("Non-canonical G2 point encoding"));
+        }
+    }
+    Ok(point)
+}
+
+/// setup・setup_from_seedの結果（master_keyとpublic_paramsを持つオブジェクト）を作る
+fn master_key_pair(
+    s: &miracl_core::bn254::big::BIG,
+    p_pub: &miracl_core::bn254::ecp::ECP,
+) -> Result<JsValue, JsValue> {
+    // マスター秘密鍵をバイト列に変換
+    let mut master_key_bytes = vec![0u8; 32];
+    s.tobytes(&mut master_key_bytes);
+    
+    // 公開パラメータをバイト列に変換
+    let mut public_params_bytes = vec![0u8; 65];
+    p_pub.tobytes(&mut public_params_bytes, false);
+    
+    let master_key = IBEMasterKey {
+        secret: master_key_bytes,
+    };
+    
+    let public_params = IBEPublicParams {
+        params: public_params_bytes,
+    };
+    
+    // JsValueとして返す
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"master_key".into(), &master_key.into())?;
+    js_sys::Reflect::set(&result, &"public_params".into(), &public_params.into())?;
+    
+    Ok(result.into())
 }
 
 // IBE関連の型定義
+
+/// マスター秘密鍵
+/// バイト列の形式: スカラーs（0 < s < 曲線の位数）の32バイトのビッグエンディアン表現
+/// 上位の0も省略しない（例: s = 1 は 31バイトの0x00の後に0x01）
 #[wasm_bindgen]
+#[derive(Clone, Default)]
 pub struct IBEMasterKey {
     secret: Vec<u8>,
 }
@@ -31,13 +424,54 @@ impl IBEMasterKey {
         }
     }
 
+    /// 保存しておいたマスター秘密鍵を復元
+    /// 0や曲線の位数以上の値は拒否する
+    pub fn from_bytes(secret: &[u8]) -> Result<IBEMasterKey, JsValue> {
+        import_master_scalar(secret)?;
+        Ok(IBEMasterKey {
+            secret: secret.to_vec(),
+        })
+    }
+
     #[wasm_bindgen(getter)]
     pub fn secret(&self) -> Vec<u8> {
         self.secret.clone()
     }
+
+    /// マスター秘密鍵のバイト列を取り出す（所有権を移すため、クローンしない）
+    /// 一度だけエクスポートする用途向けで、取り出した後は空になり、以降の操作ではエラーになる
+    /// 繰り返し参照する場合はgetterのsecretを使用すること
+    pub fn take_bytes(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.secret)
+    }
+
+    /// マスター秘密鍵をメモリから消去する
+    /// wasmの線形メモリはJSから読み取れるため、不要になった時点で呼び出すこと
+    /// 消去した後は空になり、以降の操作ではエラーになる（freeで解放した場合も自動で消去される）
+    pub fn zeroize(&mut self) {
+        self.secret.zeroize();
+    }
+
+    /// 同じ内容の複製を作る（JSではclone()）
+    /// 元のオブジェクトを保持したまま、Workerなどに複製を渡せる
+    /// take_bytesで取り出した後（空の状態）の複製は空になる
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> IBEMasterKey {
+        self.clone()
+    }
 }
 
+impl Drop for IBEMasterKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// 公開パラメータ（P_pub = sP、PはBN254のG1の生成元 (-1, 1)）
+/// バイト列の形式: SEC1の非圧縮形式の65バイト（0x04 || x (32バイト) || y (32バイト)）
+/// x・yはFpの元の32バイトのビッグエンディアン表現
 #[wasm_bindgen]
+#[derive(Clone, Default)]
 pub struct IBEPublicParams {
     params: Vec<u8>,
 }
@@ -51,15 +485,141 @@ impl IBEPublicParams {
         }
     }
 
+    /// 保存しておいた公開パラメータを復元
+    /// 長さが不正な値や、非正規なエンコーディング・無限遠点は拒否する
+    pub fn from_bytes(params: &[u8]) -> Result<IBEPublicParams, JsValue> {
+        use miracl_core::bn254::{ecp::ECP, pair};
+        
+        if params.len() == MASTER_KEY_SIZE {
+            return Err(JsValue::from_str("Invalid public params: looks like a master key, not public params"));
+        }
+        if params.len() != U_COMPONENT_SIZE {
+            return Err(JsValue::from_str(&format!(
+                "Invalid public params length: expected {}, got {}",
+                U_COMPONENT_SIZE,
+                params.len()
+            )));
+        }
+        
+        let point = ECP::frombytes(params);
+        let mut canonical = vec![0u8; U_COMPONENT_SIZE];
+        point.tobytes(&mut canonical, false);
+        if point.is_infinity() || !pair::g1member(&point) || canonical != params {
+            return Err(JsValue::from_str("Public params are not a valid curve point"));
+        }
+        
+        Ok(IBEPublicParams {
+            params: params.to_vec(),
+        })
+    }
+
     #[wasm_bindgen(getter)]
     pub fn params(&self) -> Vec<u8> {
         self.params.clone()
     }
+
+    /// 公開パラメータのバイト列を取り出す（所有権を移すため、クローンしない）
+    /// 一度だけエクスポートする用途向けで、取り出した後は空になり、以降の操作ではエラーになる
+    /// 繰り返し参照する場合はgetterのparamsを使用すること
+    pub fn take_bytes(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.params)
+    }
+
+    /// 同じ内容の複製を作る（JSではclone()）
+    /// 元のオブジェクトを保持したまま、Workerなどに複製を渡せる
+    /// take_bytesで取り出した後（空の状態）の複製は空になる
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> IBEPublicParams {
+        self.clone()
+    }
+}
+
+impl IBEPublicParams {
+    /// 暗号化に使うP_pubを復元
+    /// take_bytesで取り出した後の空の値や、曲線上の有効な点でない値はエラーにする
+    fn point(&self) -> Result<miracl_core::bn254::ecp::ECP, JsValue> {
+        if self.params.len() != U_COMPONENT_SIZE {
+            return Err(JsValue::from_str("Invalid public params length"));
+        }
+        ecp_from_bytes(&self.params, false)
+    }
+}
+
+// 鍵識別子（KID）として使うSHA-256の先頭のバイト数（base64urlで22文字）
+const KEY_ID_SIZE: usize = 16;
+
+/// 公開パラメータから安定した鍵識別子（KID）を導出
+/// SHA-256(公開パラメータ) の先頭16バイトをbase64url（パディングなし）で表した22文字の文字列
+/// Kyber・Dilithium・FALCONの公開鍵のkey_idと同じ方式で、権限者の索引やヘッダでの参照に使える
+/// 公開パラメータとして不正な値（マスター鍵など）はfrom_bytesと同じく拒否する
+#[wasm_bindgen]
+pub fn key_id(public_params: &[u8]) -> Result<String, JsValue> {
+    use sha2::{Digest, Sha256};
+    
+    IBEPublicParams::from_bytes(public_params)?;
+    let digest = Sha256::digest(public_params);
+    Ok(URL_SAFE_NO_PAD.encode(&digest[..KEY_ID_SIZE]))
+}
+
+/// ECIES方式の鍵ペアを生成（アイデンティティを使わない通常の公開鍵暗号化用）
+/// private_keyはスカラーx（0 < x < 曲線の位数）の32バイト、public_keyはQ = xPの非圧縮形式の65バイト
+/// IBEの公開パラメータと同じBN254のG1の群を使うが、ペアリングは使わない
+#[wasm_bindgen]
+pub fn ecies_generate_keypair() -> Result<JsValue, JsValue> {
+    let (x, q) = ecies::generate_keypair()?;
+    
+    let mut private_key = vec![0u8; MASTER_KEY_SIZE];
+    x.tobytes(&mut private_key);
+    let mut public_key = vec![0u8; U_COMPONENT_SIZE];
+    q.tobytes(&mut public_key, false);
+    
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"private_key".into(), &js_sys::Uint8Array::from(private_key.as_slice()))?;
+    js_sys::Reflect::set(&result, &"public_key".into(), &js_sys::Uint8Array::from(public_key.as_slice()))?;
+    private_key.zeroize();
+    
+    Ok(result.into())
+}
+
+/// ECIES方式でメッセージを暗号化
+/// 暗号化ごとに一時鍵を生成し、共有点から導出した鍵でAES-256-GCMにより暗号化と認証を行う
+#[wasm_bindgen]
+pub fn ecies_encrypt(public_key: &[u8], message: &[u8]) -> Result<Vec<u8>, JsValue> {
+    if public_key.len() != U_COMPONENT_SIZE {
+        return Err(JsValue::from_str(&format!(
+            "Invalid ECIES public key length: expected {}, got {}",
+            U_COMPONENT_SIZE,
+            public_key.len()
+        )));
+    }
+    let q = ecp_from_bytes(public_key, true)?;
+    
+    Ok(ecies::seal(&q, message)?)
+}
+
+/// ecies_encryptで暗号化した暗号文を復号化
+/// 秘密鍵が誤っている場合や暗号文が壊れている場合は認証に失敗し、エラーを返す
+#[wasm_bindgen]
+pub fn ecies_decrypt(private_key: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, JsValue> {
+    use miracl_core::bn254::big::BIG;
+    
+    if private_key.len() != MASTER_KEY_SIZE || !scalar_in_range(private_key) {
+        return Err(JsValue::from_str("Invalid ECIES private key: must be a 32-byte scalar with 0 < x < curve order"));
+    }
+    
+    ecies::open(&BIG::frombytes(private_key), ciphertext).map_err(|e| decrypt_error(&e))
 }
 
 #[wasm_bindgen]
+#[derive(Clone, Default)]
 pub struct IBEPrivateKey {
     key: Vec<u8>,
+    // 秘密鍵を抽出したアイデンティティ（事前ハッシュから抽出した場合は空）
+    identity: String,
+    // 写像・鍵導出に使うアイデンティティ（旧方式以外は事前ハッシュ、復号時の鍵導出に使用）
+    identity_input: Vec<u8>,
+    // 鍵の抽出に使ったhash-to-curveの方式（同じ方式の暗号文だけを復号できる）
+    hash_to_curve: HashToCurve,
 }
 
 #[wasm_bindgen]
@@ -68,6 +628,9 @@ impl IBEPrivateKey {
     pub fn new() -> IBEPrivateKey {
         IBEPrivateKey {
             key: Vec::new(),
+            identity: String::new(),
+            identity_input: Vec::new(),
+            hash_to_curve: HashToCurve::default(),
         }
     }
 
@@ -75,54 +638,147 @@ impl IBEPrivateKey {
     pub fn key(&self) -> Vec<u8> {
         self.key.clone()
     }
+
+    /// 秘密鍵のバイト列を取り出す（所有権を移すため、クローンしない）
+    /// 一度だけエクスポートする用途向けで、取り出した後は空になり、以降の操作ではエラーになる
+    /// 繰り返し参照する場合はgetterのkeyを使用すること
+    pub fn take_bytes(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.key)
+    }
+
+    /// 秘密鍵を抽出したアイデンティティ（extract_prehashedで抽出した場合は空文字列）
+    #[wasm_bindgen(getter)]
+    pub fn identity(&self) -> String {
+        self.identity.clone()
+    }
+
+    /// 秘密鍵の抽出に使ったhash-to-curveの方式（"rfc9380"・"try_and_increment"・"legacy"）
+    #[wasm_bindgen(getter)]
+    pub fn hash_to_curve(&self) -> String {
+        self.hash_to_curve.name().to_string()
+    }
+
+    /// 秘密鍵をメモリから消去する
+    /// wasmの線形メモリはJSから読み取れるため、不要になった時点で呼び出すこと
+    /// 消去した後は空になり、以降の操作ではエラーになる（freeで解放した場合も自動で消去される）
+    pub fn zeroize(&mut self) {
+        self.key.zeroize();
+    }
+
+    /// 同じ内容の複製を作る（JSではclone()）
+    /// 元のオブジェクトを保持したまま、Workerなどに複製を渡せる
+    /// take_bytesで取り出した後（空の状態）の複製は空になる
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> IBEPrivateKey {
+        self.clone()
+    }
+}
+
+impl Drop for IBEPrivateKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
 // IBE実装（Miracl Coreを使用）
 // 注意: 現在は基本的な構造のみ。Miracl CoreのAPIを確認しながら段階的に実装を進めます。
 #[wasm_bindgen]
+#[derive(Clone, Default)]
 pub struct IBE {
     // Boneh-Franklin IBEスキームの実装
     // 今後、Miracl Coreのペアリング演算を使用
+    // 非正規な点のエンコーディングを拒否するか
+    strict: bool,
+    // 鍵の抽出と暗号化に使うhash-to-curveの方式
+    hash_to_curve: HashToCurve,
 }
 
 #[wasm_bindgen]
 impl IBE {
     #[wasm_bindgen(constructor)]
     pub fn new() -> IBE {
-        IBE {}
+        IBE {
+            strict: false,
+            hash_to_curve: HashToCurve::default(),
+        }
+    }
+
+    /// 鍵の抽出と暗号化に使うhash-to-curveの方式を取得
+    #[wasm_bindgen(getter)]
+    pub fn hash_to_curve(&self) -> String {
+        self.hash_to_curve.name().to_string()
+    }
+
+    /// 鍵の抽出と暗号化に使うhash-to-curveの方式を設定
+    /// "rfc9380"（既定）・"try_and_increment"・"legacy"のいずれかで、相互運用する相手の実装に合わせて選ぶ
+    /// 方式は秘密鍵と暗号文に記録され、復号時には記録された方式同士が一致する必要がある
+    /// "legacy"は1つの秘密鍵から他のアイデンティティの秘密鍵を計算できてしまうため、既存の鍵・暗号文の移行用にのみ使うこと
+    pub fn set_hash_to_curve(&mut self, method: &str) -> Result<(), JsValue> {
+        self.hash_to_curve = parse_hash_to_curve(method)?;
+        Ok(())
+    }
+
+    /// strictモードを取得
+    #[wasm_bindgen(getter)]
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// strictモードを設定
+    /// 有効にすると、復号時に非正規な点のエンコーディングを拒否する
+    #[wasm_bindgen(setter)]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
     }
 
     /// マスター鍵ペアを生成
     /// Boneh-Franklin IBEスキームのSetupアルゴリズム
     #[wasm_bindgen]
     pub fn setup(&self) -> Result<JsValue, JsValue> {
-        use miracl_core::bn254::ecp::ECP;
-        
         // マスター鍵ペアを生成
-        let (s, p_pub) = IBEImpl::setup();
+        let (s, p_pub) = IBEImpl::setup()?;
         
-        // マスター秘密鍵をバイト列に変換
-        let mut master_key_bytes = vec![0u8; 32];
-        s.tobytes(&mut master_key_bytes);
+        master_key_pair(&s, &p_pub)
+    }
+
+    /// シード（32バイト）から決定的にマスター鍵ペアを生成
+    /// 同じシードからは常に同じマスター秘密鍵・公開パラメータが得られるため、
+    /// 再現可能なテストや他の実装とのテストベクタ、鍵セレモニーに使える
+    /// シードはマスター秘密鍵と同等に扱うこと（すべて0のシードは拒否する）
+    #[wasm_bindgen]
+    pub fn setup_from_seed(&self, seed: &[u8]) -> Result<JsValue, JsValue> {
+        if seed.len() != SETUP_SEED_SIZE {
+            return Err(JsValue::from_str(&format!(
+                "Invalid seed length: expected {}, got {}",
+                SETUP_SEED_SIZE,
+                seed.len()
+            )));
+        }
+        if seed.iter().all(|&b| b == 0) {
+            return Err(JsValue::from_str("Invalid seed: all-zero seed is not allowed"));
+        }
         
-        // 公開パラメータをバイト列に変換
-        let mut public_params_bytes = vec![0u8; 65];
-        p_pub.tobytes(&mut public_params_bytes, false);
+        let (s, p_pub) = IBEImpl::setup_from_seed(seed)?;
         
-        let master_key = IBEMasterKey {
-            secret: master_key_bytes,
-        };
+        master_key_pair(&s, &p_pub)
+    }
+
+    /// マスター秘密鍵から公開パラメータ（P_pub = sP）を計算
+    /// 保存しておいたマスター秘密鍵だけから公開パラメータを復元する場合や、
+    /// 他言語の実装とエンコーディングを突き合わせる場合に使う
+    #[wasm_bindgen]
+    pub fn derive_public_params(&self, master_key: &IBEMasterKey) -> Result<IBEPublicParams, JsValue> {
+        use miracl_core::bn254::ecp::ECP;
         
-        let public_params = IBEPublicParams {
-            params: public_params_bytes,
-        };
+        let s = import_master_scalar(&master_key.secret)?;
+        let p_pub = ECP::generator().mul(&s);
         
-        // JsValueとして返す
-        let result = js_sys::Object::new();
-        js_sys::Reflect::set(&result, &"master_key".into(), &master_key.into())?;
-        js_sys::Reflect::set(&result, &"public_params".into(), &public_params.into())?;
+        let mut public_params_bytes = vec![0u8; U_COMPONENT_SIZE];
+        p_pub.tobytes(&mut public_params_bytes, false);
         
-        Ok(result.into())
+        Ok(IBEPublicParams {
+            params: public_params_bytes,
+        })
     }
 
     /// アイデンティティから秘密鍵を抽出
@@ -133,16 +789,304 @@ impl IBE {
         master_key: &IBEMasterKey,
         identity: &str,
     ) -> Result<IBEPrivateKey, JsValue> {
-        use miracl_core::bn254::{big::BIG, ecp2::ECP2};
+        let identity_input = IBEImpl::identity_input(identity, self.hash_to_curve);
+        self.extract_input(master_key, identity.to_string(), identity_input)
+    }
+
+    /// 事前ハッシュしたアイデンティティ（prehash_identityの結果）から秘密鍵を抽出
+    /// アイデンティティそのものからextractで抽出した秘密鍵と同じ鍵になる（identityは空文字列）
+    /// 旧方式（legacy）では使用できない
+    #[wasm_bindgen]
+    pub fn extract_prehashed(
+        &self,
+        master_key: &IBEMasterKey,
+        identity_digest: &[u8],
+    ) -> Result<IBEPrivateKey, JsValue> {
+        let identity_input = prehashed_identity_input(identity_digest, self.hash_to_curve)?;
+        self.extract_input(master_key, String::new(), identity_input)
+    }
+
+    /// メッセージを暗号化
+    /// Boneh-Franklin IBEスキームのEncryptアルゴリズム
+    /// ペアリングから導出した対称鍵でメッセージをAES-256-GCMで暗号化する（Vはnonce || 暗号文 || 認証タグ）
+    /// 旧方式（legacy）のhash-to-curveでは、互換性のため従来の形式（対称鍵とのXOR）で暗号化する
+    #[wasm_bindgen]
+    pub fn encrypt(
+        &self,
+        public_params: &IBEPublicParams,
+        identity: &str,
+        message: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let identity_input = IBEImpl::identity_input(identity, self.hash_to_curve);
+        self.encrypt_input(public_params, &identity_input, message)
+    }
+
+    /// 事前ハッシュしたアイデンティティ（prehash_identityの結果）宛てにメッセージを暗号化
+    /// アイデンティティそのものを渡したencryptと同じ暗号文になり、同じ秘密鍵で復号できる
+    /// 旧方式（legacy）では使用できない
+    #[wasm_bindgen]
+    pub fn encrypt_prehashed(
+        &self,
+        public_params: &IBEPublicParams,
+        identity_digest: &[u8],
+        message: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let identity_input = prehashed_identity_input(identity_digest, self.hash_to_curve)?;
+        self.encrypt_input(public_params, &identity_input, message)
+    }
+
+    /// 合成IV（SIV）方式でメッセージを暗号化
+    /// rを乱数ではなくHMAC(message_key, ID || M)から決定的に導出するため、乱数生成器を信頼しなくてよい
+    /// 同じアイデンティティ宛ての同じメッセージは同じ暗号文になり、メッセージが同一であることだけが漏れる
+    /// 暗号文の形式はencryptと同じで、decryptでそのまま復号できる
+    #[wasm_bindgen]
+    pub fn encrypt_deterministic(
+        &self,
+        public_params: &IBEPublicParams,
+        identity: &str,
+        message: &[u8],
+        message_key: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        // 公開パラメータをECPに変換
+        let p_pub = public_params.point()?;
         
-        // マスター秘密鍵をBIGに変換
-        if master_key.secret.len() != 32 {
-            return Err(JsValue::from_str("Invalid master key length"));
+        if message.len() < MIN_V_COMPONENT_SIZE {
+            return Err(JsValue::from_str("Message must not be empty"));
+        }
+        if message_key.len() < MIN_MESSAGE_KEY_SIZE {
+            return Err(JsValue::from_str(&format!(
+                "Message key must be at least {} bytes, got {}",
+                MIN_MESSAGE_KEY_SIZE,
+                message_key.len()
+            )));
+        }
+        
+        // rを決定的に導出して暗号化
+        let identity = IBEImpl::identity_input(identity, self.hash_to_curve);
+        let r = IBEImpl::synthetic_r(message_key, &identity, message);
+        if r.iszilch() {
+            return Err(JsValue::from_str("Derived randomness is zero"));
         }
-        let s = BIG::frombytes(&master_key.secret);
+        // 対称鍵はrごとに異なり、rはメッセージごとに異なるため、nonceは固定値でよい
+        let (u, v) = IBEImpl::encrypt_with_r(&p_pub, &identity, message, &r, &[0u8; GCM_NONCE_SIZE], self.hash_to_curve);
+        
+        Ok(self.encode_ciphertext(&u, &v))
+    }
+
+    /// メッセージを暗号化し、base64url（パディングなし）の文字列で返す
+    /// URLやQRコードに埋め込むためのもので、decrypt_from_stringで復号できる
+    #[wasm_bindgen]
+    pub fn encrypt_to_string(
+        &self,
+        public_params: &IBEPublicParams,
+        identity: &str,
+        message: &[u8],
+    ) -> Result<String, JsValue> {
+        let ciphertext = self.encrypt(public_params, identity, message)?;
+        Ok(URL_SAFE_NO_PAD.encode(ciphertext))
+    }
+
+    /// base64url文字列の暗号文を復号化
+    /// 空白やパディング（=）が含まれていても受け付ける
+    #[wasm_bindgen]
+    pub fn decrypt_from_string(
+        &self,
+        private_key: &IBEPrivateKey,
+        encoded: &str,
+    ) -> Result<Vec<u8>, JsValue> {
+        let ciphertext = decode_ciphertext_string(encoded)?;
+        self.decrypt(private_key, &ciphertext)
+    }
+
+    /// 暗号文を復号化
+    /// Boneh-Franklin IBEスキームのDecryptアルゴリズム
+    /// AES-256-GCMの認証タグが一致しない場合（暗号文の改ざん・アイデンティティの異なる鍵）はエラーを返す
+    /// AES-256-GCMのフラグのない旧形式の暗号文はエラーを返す（decrypt_legacyで移行する）
+    #[wasm_bindgen]
+    pub fn decrypt(
+        &self,
+        private_key: &IBEPrivateKey,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let (d_id, u, v, gcm) = self.parse_decrypt_input(private_key, ciphertext)?;
+        if !gcm {
+            return Err(decrypt_error(UNAUTHENTICATED_FORMAT));
+        }
+        
+        // 暗号文を復号化（対称鍵はアイデンティティに束縛されている）
+        IBEImpl::decrypt(&d_id, &private_key.identity_input, &u, v).ok_or_else(|| decrypt_error(AUTHENTICATION_FAILED))
+    }
+
+    /// AES-256-GCMの導入前の旧形式（対称鍵を繰り返したXOR）の暗号文を復号化する
+    /// 旧方式（legacy）の秘密鍵で、ヘッダなし（0x04から始まる）の暗号文だけを受け付ける
+    /// 保存済みの旧形式の暗号文を復号し、encryptで暗号化し直すための移行専用のAPI
+    /// 注意: 旧形式には完全性の保護がないため、改ざんされた暗号文やアイデンティティの異なる鍵でも
+    /// エラーにならず無関係なバイト列が返る。受信した暗号文の復号には使わないこと
+    /// AES-256-GCMのフラグ付きの暗号文はエラーを返す（decryptで復号する）
+    #[wasm_bindgen]
+    pub fn decrypt_legacy(
+        &self,
+        private_key: &IBEPrivateKey,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let (d_id, u, v, gcm) = self.parse_decrypt_input(private_key, ciphertext)?;
+        if gcm {
+            return Err(decrypt_error("IBE ciphertext is not in the legacy format; decrypt it with decrypt"));
+        }
+        
+        Ok(IBEImpl::decrypt_xor(&d_id, &private_key.identity_input, &u, v))
+    }
+
+    /// 暗号文を呼び出し側のバッファに復号化し、書き込んだバイト数を返す
+    /// 復号のたびに新しいバッファを確保しないため、繰り返し復号する場合のメモリ確保を減らせる
+    /// バッファが平文より短い場合は、ペアリングの計算前にエラーを返す
+    /// バッファの書き込んだバイト数以降の内容は変更しない（認証に失敗した場合、平文の長さ分は0で埋める）
+    #[wasm_bindgen]
+    pub fn decrypt_into(
+        &self,
+        private_key: &IBEPrivateKey,
+        ciphertext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, JsValue> {
+        let (d_id, u, v, gcm) = self.parse_decrypt_input(private_key, ciphertext)?;
+        if !gcm {
+            return Err(decrypt_error(UNAUTHENTICATED_FORMAT));
+        }
+        let len = v.len() - GCM_NONCE_SIZE - GCM_TAG_SIZE;
+        if out.len() < len {
+            return Err(JsValue::from_str(&format!(
+                "Output buffer too small: need {} bytes, got {}",
+                len,
+                out.len()
+            )));
+        }
+        
+        if !IBEImpl::decrypt_into(&d_id, &private_key.identity_input, &u, v, &mut out[..len]) {
+            return Err(decrypt_error(AUTHENTICATION_FAILED));
+        }
+        
+        Ok(len)
+    }
+
+    /// 群要素（G1またはG2の点）を暗号化
+    /// 閾値暗号やMPCなどのプロトコルで、バイト列ではなく群要素を受け渡すための低レベルAPI
+    #[wasm_bindgen]
+    pub fn encrypt_point(
+        &self,
+        public_params: &IBEPublicParams,
+        identity: &str,
+        point_bytes: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        validate_point(point_bytes)?;
+        
+        // 直列化された点をペアリング由来の鍵で暗号化
+        self.encrypt(public_params, identity, point_bytes)
+    }
+
+    /// encrypt_pointで暗号化した群要素を復号化
+    /// 復号結果が曲線上の有効な点でない場合はエラーを返す
+    #[wasm_bindgen]
+    pub fn decrypt_point(
+        &self,
+        private_key: &IBEPrivateKey,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let point_bytes = self.decrypt(private_key, ciphertext)?;
+        
+        validate_point(&point_bytes)?;
+        
+        Ok(point_bytes)
+    }
+
+    /// ポスト量子ハイブリッド方式でメッセージを暗号化
+    /// メッセージの対称鍵をペアリング層（IBE）の鍵シェアとML-KEM（Kyber）の共有秘密の両方から導出するため、
+    /// BN254のペアリングが破られてもKyberの秘密鍵がなければ復号できない
+    /// 復号にはアイデンティティの秘密鍵と、kem_public_keyに対応するKyberの秘密鍵の両方が必要
+    #[wasm_bindgen]
+    pub fn encrypt_hybrid(
+        &self,
+        public_params: &IBEPublicParams,
+        identity: &str,
+        kem_public_key: &[u8],
+        message: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        if message.len() < MIN_V_COMPONENT_SIZE {
+            return Err(JsValue::from_str("Message must not be empty"));
+        }
+        hybrid::check_kem_public_key(kem_public_key).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        
+        // ランダムな鍵シェアをペアリング層で暗号化
+        let key_share = hybrid::random_key_share()?;
+        let pairing_ciphertext = self.encrypt(public_params, identity, key_share.as_slice())?;
+        
+        Ok(hybrid::seal(kem_public_key, &key_share, &pairing_ciphertext, message)?)
+    }
+
+    /// 暗号文の復号を試み、失敗した場合はエラーの代わりにundefinedを返す
+    /// 受信した暗号文を手元のすべての鍵で順に試すような用途向けで、失敗してもdecrypt_failedイベントは通知しない
+    /// AES-256-GCMの認証タグで成否を判定するため、アイデンティティの異なる鍵ではundefinedになる
+    /// 完全性タグのない旧形式（GCM導入前）の暗号文は鍵の正否を判定できないため、常にundefinedになる
+    #[wasm_bindgen]
+    pub fn try_decrypt(&self, private_key: &IBEPrivateKey, ciphertext: &[u8]) -> Option<Vec<u8>> {
+        muted(|| self.decrypt(private_key, ciphertext)).ok()
+    }
+
+    /// encrypt_hybridで暗号化した暗号文の復号を試み、失敗した場合はエラーの代わりにundefinedを返す
+    /// AES-256-GCMの認証タグで成否を判定するため、アイデンティティやKyberの鍵が異なる場合、
+    /// 暗号文が壊れている場合はいずれもundefinedになる（decrypt_failedイベントは通知しない）
+    #[wasm_bindgen]
+    pub fn try_decrypt_hybrid(
+        &self,
+        private_key: &IBEPrivateKey,
+        kem_private_key: &[u8],
+        kem_public_key: &[u8],
+        ciphertext: &[u8],
+    ) -> Option<Vec<u8>> {
+        muted(|| self.decrypt_hybrid(private_key, kem_private_key, kem_public_key, ciphertext)).ok()
+    }
+
+    /// encrypt_hybridで暗号化した暗号文を復号化
+    /// どちらかの鍵が誤っている場合は認証に失敗し、エラーを返す
+    #[wasm_bindgen]
+    pub fn decrypt_hybrid(
+        &self,
+        private_key: &IBEPrivateKey,
+        kem_private_key: &[u8],
+        kem_public_key: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let parts = hybrid::parse(ciphertext).map_err(|e| decrypt_error(&e.to_string()))?;
+        
+        // ペアリング層から鍵シェアを復号
+        let key_share = Zeroizing::new(self.decrypt(private_key, parts.pairing_ciphertext)?);
+        if key_share.len() != hybrid::KEY_SHARE_SIZE {
+            return Err(decrypt_error("Invalid key share in hybrid ciphertext"));
+        }
+        
+        hybrid::open(ciphertext, &key_share, kem_private_key, kem_public_key).map_err(|e| decrypt_error(&e.to_string()))
+    }
+
+    /// 設定（strictモード・hash-to-curveの方式）を引き継いだ複製を作る（JSではclone()）
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> IBE {
+        self.clone()
+    }
+}
+
+impl IBE {
+    /// 写像・鍵導出用のアイデンティティ（IBEImpl::identity_inputの結果）から秘密鍵を抽出
+    /// Boneh-Franklin IBEスキームのExtractアルゴリズム
+    fn extract_input(
+        &self,
+        master_key: &IBEMasterKey,
+        identity: String,
+        identity_input: Vec<u8>,
+    ) -> Result<IBEPrivateKey, JsValue> {
+        // マスター秘密鍵をBIGに変換
+        let s = import_master_scalar(&master_key.secret)?;
         
         // 秘密鍵を抽出
-        let d_id = IBEImpl::extract(&s, identity);
+        let d_id = IBEImpl::extract(&s, &identity_input, self.hash_to_curve);
         
         // 秘密鍵をバイト列に変換
         let mut key_bytes = vec![0u8; 130];
@@ -150,67 +1094,87 @@ impl IBE {
         
         Ok(IBEPrivateKey {
             key: key_bytes,
+            identity,
+            identity_input,
+            hash_to_curve: self.hash_to_curve,
         })
     }
 
-    /// メッセージを暗号化
+    /// 写像・鍵導出用のアイデンティティ（IBEImpl::identity_inputの結果）宛てにメッセージを暗号化
     /// Boneh-Franklin IBEスキームのEncryptアルゴリズム
-    #[wasm_bindgen]
-    pub fn encrypt(
+    fn encrypt_input(
         &self,
         public_params: &IBEPublicParams,
-        identity: &str,
+        identity_input: &[u8],
         message: &[u8],
     ) -> Result<Vec<u8>, JsValue> {
-        use miracl_core::bn254::ecp::ECP;
-        
         // 公開パラメータをECPに変換
-        if public_params.params.len() < 65 {
-            return Err(JsValue::from_str("Invalid public params length"));
+        let p_pub = public_params.point()?;
+        
+        if message.len() < MIN_V_COMPONENT_SIZE {
+            return Err(JsValue::from_str("Message must not be empty"));
         }
-        let p_pub = ECP::frombytes(&public_params.params);
         
-        // メッセージを暗号化
-        let (u, v) = IBEImpl::encrypt(&p_pub, identity, message);
+        // メッセージを暗号化（旧方式のhash-to-curveでもAES-256-GCMを使う）
+        let (u, v) = IBEImpl::encrypt(&p_pub, identity_input, message, self.hash_to_curve)?;
         
-        // 暗号文をバイト列に変換（U || Vの形式）
-        let mut u_bytes = vec![0u8; 65];
-        u.tobytes(&mut u_bytes, false);
+        Ok(self.encode_ciphertext(&u, &v))
+    }
+
+    /// 暗号文をバイト列に変換（[hash-to-curveの方式のID | GCMのフラグ (1バイト)] || U || Vの形式）
+    fn encode_ciphertext(&self, u: &miracl_core::bn254::ecp::ECP, v: &[u8]) -> Vec<u8> {
+        let mut ciphertext = Vec::with_capacity(1 + U_COMPONENT_SIZE + v.len());
+        ciphertext.push(hash_to_curve_id(self.hash_to_curve) | GCM_PAYLOAD_FLAG);
         
-        let mut ciphertext = u_bytes;
-        ciphertext.extend_from_slice(&v);
+        let mut u_bytes = vec![0u8; U_COMPONENT_SIZE];
+        u.tobytes(&mut u_bytes, false);
+        ciphertext.extend_from_slice(&u_bytes);
+        ciphertext.extend_from_slice(v);
         
-        Ok(ciphertext)
+        ciphertext
     }
 
-    /// 暗号文を復号化
-    /// Boneh-Franklin IBEスキームのDecryptアルゴリズム
-    #[wasm_bindgen]
-    pub fn decrypt(
+    /// 復号の入力を検証し、秘密鍵の点・暗号文のUコンポーネント・Vコンポーネント・AES-256-GCMのフラグに分解する
+    /// 暗号文に記録されたhash-to-curveの方式が秘密鍵の方式と異なる場合はエラーを返す
+    #[allow(clippy::type_complexity)]
+    fn parse_decrypt_input<'a>(
         &self,
         private_key: &IBEPrivateKey,
-        ciphertext: &[u8],
-    ) -> Result<Vec<u8>, JsValue> {
-        use miracl_core::bn254::{ecp::ECP, ecp2::ECP2};
+        ciphertext: &'a [u8],
+    ) -> Result<(miracl_core::bn254::ecp2::ECP2, miracl_core::bn254::ecp::ECP, &'a [u8], bool), JsValue> {
+        let (method, gcm, ciphertext) = split_hash_to_curve(ciphertext)?;
+        if method != private_key.hash_to_curve {
+            return Err(decrypt_error(&format!(
+                "Hash-to-curve method mismatch: ciphertext uses {}, private key uses {}",
+                method.name(),
+                private_key.hash_to_curve.name()
+            )));
+        }
         
-        if ciphertext.len() < 65 {
-            return Err(JsValue::from_str("Invalid ciphertext length"));
+        // GCMの暗号文のVコンポーネントには、平文に加えてnonceと認証タグが含まれる
+        let overhead = if gcm { GCM_NONCE_SIZE + GCM_TAG_SIZE } else { 0 };
+        if ciphertext.len() < U_COMPONENT_SIZE + overhead {
+            return Err(decrypt_error(&format!(
+                "Truncated ciphertext: expected at least {} bytes, got {}",
+                U_COMPONENT_SIZE + overhead + MIN_V_COMPONENT_SIZE,
+                ciphertext.len()
+            )));
+        }
+        if ciphertext.len() < U_COMPONENT_SIZE + overhead + MIN_V_COMPONENT_SIZE {
+            return Err(decrypt_error("Ciphertext has no payload (empty V component)"));
         }
         
         // 暗号文を解析（U || Vの形式）
-        let u = ECP::frombytes(&ciphertext[..65]);
-        let v = &ciphertext[65..];
+        let u = ecp_from_bytes(&ciphertext[..U_COMPONENT_SIZE], self.strict)?;
+        let v = &ciphertext[U_COMPONENT_SIZE..];
         
         // 秘密鍵をECP2に変換
-        if private_key.key.len() < 130 {
-            return Err(JsValue::from_str("Invalid private key length"));
+        if private_key.key.len() < G2_POINT_SIZE {
+            return Err(decrypt_error("Invalid private key length"));
         }
-        let d_id = ECP2::frombytes(&private_key.key);
-        
-        // 暗号文を復号化
-        let message = IBEImpl::decrypt(&d_id, &u, v);
+        let d_id = ecp2_from_bytes(&private_key.key[..G2_POINT_SIZE], self.strict)?;
         
-        Ok(message)
+        Ok((d_id, u, v, gcm))
     }
 }
 
@@ -0,0 +1,241 @@
+// 認証タグを切り詰めたChaCha20-Poly1305の内部モジュール
+// 通信量が限られるプロトコル向けに、タグを先頭から指定バイト数だけ残して送る
+//
+// 注意: タグを短くすると偽造への耐性が下がる（tバイトのタグは1回の試行あたり約2^(-8t)の確率で偽造が通る）
+//       既定の16バイトを使い、やむを得ない場合にのみ12バイト以上に短くすること
+
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::aead::{Aead, AeadInPlace, KeyInit, Nonce};
+use chacha20poly1305::{ChaCha20Poly1305, Key};
+
+use crate::constant_time_eq;
+
+// 暗号文（ストリーム・複数受信者向けコンテナ）の形式のバージョン
+// 2: ヘッダに認証タグのサイズを記録する現在の形式
+// 1: タグのサイズを記録しない旧形式（タグは常に16バイト、復号のみ対応）
+pub const CIPHERTEXT_VERSION: u8 = 2;
+pub const CIPHERTEXT_VERSION_V1: u8 = 1;
+
+// 復号に対応している形式のバージョン（新しい順）
+pub const SUPPORTED_CIPHERTEXT_VERSIONS: [u8; 2] = [CIPHERTEXT_VERSION, CIPHERTEXT_VERSION_V1];
+
+// 未対応のバージョンのエラーメッセージ（対応しているバージョンの一覧を含める）
+pub fn unsupported_version(kind: &str, version: u8) -> String {
+    let supported: Vec<String> = SUPPORTED_CIPHERTEXT_VERSIONS.iter().map(u8::to_string).collect();
+    format!(
+        "Unsupported {} version: {} (supported versions: {})",
+        kind,
+        version,
+        supported.join(", ")
+    )
+}
+
+// 認証タグの既定のサイズ（切り詰めなし）
+pub const DEFAULT_TAG_SIZE: usize = 16;
+
+// 認証タグの最小サイズ
+pub const MIN_TAG_SIZE: usize = 12;
+
+// タグのサイズが許容範囲内かを確認
+pub fn check_tag_size(tag_size: usize) -> Result<(), String> {
+    if !(MIN_TAG_SIZE..=DEFAULT_TAG_SIZE).contains(&tag_size) {
+        return Err(format!(
+            "Invalid tag size: expected {} to {} bytes, got {}",
+            MIN_TAG_SIZE, DEFAULT_TAG_SIZE, tag_size
+        ));
+    }
+    Ok(())
+}
+
+// 暗号化し、先頭tag_sizeバイトに切り詰めたタグを付ける（暗号文 || タグ）
+pub fn seal<C: AeadInPlace>(cipher: &C, nonce: &[u8; 12], plaintext: &[u8], tag_size: usize) -> Option<Vec<u8>> {
+    let mut sealed = plaintext.to_vec();
+    let tag = cipher
+        .encrypt_in_place_detached(Nonce::<C>::from_slice(nonce), b"", &mut sealed)
+        .ok()?;
+    sealed.extend_from_slice(&tag[..tag_size]);
+    Some(sealed)
+}
+
+// 切り詰めたタグを検証して復号（暗号文 || タグ）
+// タグが切り詰められている場合は、いったん鍵ストリームで復号した平文を暗号化し直して
+// 完全なタグを再計算し、その先頭を比較する（ChaCha20・AESのCTRモードはXORなので同じ暗号文に戻る）
+// 平文はタグの検証に成功した場合のみ返す
+pub fn open<C: AeadInPlace>(cipher: &C, nonce: &[u8; 12], sealed: &[u8], tag_size: usize) -> Option<Vec<u8>> {
+    if tag_size == DEFAULT_TAG_SIZE {
+        return cipher.decrypt(Nonce::<C>::from_slice(nonce), sealed).ok();
+    }
+    
+    let (ciphertext, tag) = sealed.split_at(sealed.len().checked_sub(tag_size)?);
+    
+    let mut plaintext = ciphertext.to_vec();
+    cipher
+        .encrypt_in_place_detached(Nonce::<C>::from_slice(nonce), b"", &mut plaintext)
+        .ok()?;
+    
+    let mut reencrypted = plaintext.clone();
+    let full_tag = cipher
+        .encrypt_in_place_detached(Nonce::<C>::from_slice(nonce), b"", &mut reencrypted)
+        .ok()?;
+    
+    if !constant_time_eq(&full_tag[..tag_size], tag) {
+        return None;
+    }
+    Some(plaintext)
+}
+
+// コンテナのtag_sizeのバイトに論理和で付ける、本文をAES-256-GCMで暗号化したことを示すフラグ
+// フラグのないコンテナ（以前の形式を含む）の本文はChaCha20-Poly1305
+pub const AES_GCM_FLAG: u8 = 0x80;
+
+// 本文の暗号化に使う認証付き暗号
+// どちらも鍵は32バイト、nonceは12バイト、タグは16バイト
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AeadCipher {
+    ChaCha20Poly1305,
+    Aes256Gcm,
+}
+
+impl AeadCipher {
+    // 名前から暗号を取得（"chacha20-poly1305"・"aes-256-gcm"）
+    pub fn from_name(name: &str) -> Option<AeadCipher> {
+        match name {
+            "chacha20-poly1305" => Some(AeadCipher::ChaCha20Poly1305),
+            "aes-256-gcm" => Some(AeadCipher::Aes256Gcm),
+            _ => None,
+        }
+    }
+
+    // 暗号の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            AeadCipher::ChaCha20Poly1305 => "chacha20-poly1305",
+            AeadCipher::Aes256Gcm => "aes-256-gcm",
+        }
+    }
+
+    // tag_sizeのバイト（タグのサイズ | フラグ）を作る
+    pub fn tag_byte(self, tag_size: usize) -> u8 {
+        match self {
+            AeadCipher::ChaCha20Poly1305 => tag_size as u8,
+            AeadCipher::Aes256Gcm => tag_size as u8 | AES_GCM_FLAG,
+        }
+    }
+
+    // tag_sizeのバイトから暗号とタグのサイズを取り出す
+    pub fn from_tag_byte(byte: u8) -> (AeadCipher, usize) {
+        if byte & AES_GCM_FLAG != 0 {
+            (AeadCipher::Aes256Gcm, (byte & !AES_GCM_FLAG) as usize)
+        } else {
+            (AeadCipher::ChaCha20Poly1305, byte as usize)
+        }
+    }
+
+    // 鍵で暗号化し、先頭tag_sizeバイトに切り詰めたタグを付ける（暗号文 || タグ）
+    pub fn seal(self, key: &[u8; 32], nonce: &[u8; 12], plaintext: &[u8], tag_size: usize) -> Option<Vec<u8>> {
+        match self {
+            AeadCipher::ChaCha20Poly1305 => seal(&ChaCha20Poly1305::new(Key::from_slice(key)), nonce, plaintext, tag_size),
+            AeadCipher::Aes256Gcm => seal(&Aes256Gcm::new(Key::from_slice(key)), nonce, plaintext, tag_size),
+        }
+    }
+
+    // 鍵で切り詰めたタグを検証して復号（暗号文 || タグ）
+    pub fn open(self, key: &[u8; 32], nonce: &[u8; 12], sealed: &[u8], tag_size: usize) -> Option<Vec<u8>> {
+        match self {
+            AeadCipher::ChaCha20Poly1305 => open(&ChaCha20Poly1305::new(Key::from_slice(key)), nonce, sealed, tag_size),
+            AeadCipher::Aes256Gcm => open(&Aes256Gcm::new(Key::from_slice(key)), nonce, sealed, tag_size),
+        }
+    }
+}
+
+// recommend_aeadの既定の作業量（各暗号で暗号化する4KiBのブロックの数、合計1MiB）
+pub const DEFAULT_WORK_FACTOR: u32 = 256;
+
+// recommend_aeadの作業量の上限（合計256MiB）
+pub const MAX_WORK_FACTOR: u32 = 65536;
+
+// ベンチマークで1回に暗号化するブロックのサイズ
+const BENCH_BLOCK_SIZE: usize = 4096;
+
+thread_local! {
+    // 最後にベンチマークで選んだ暗号（"auto"の指定で再利用する）
+    static RECOMMENDED: std::cell::Cell<Option<AeadCipher>> = const { std::cell::Cell::new(None) };
+}
+
+// 作業量が許容範囲内かを確認
+pub fn check_work_factor(work_factor: u32) -> Result<(), String> {
+    if !(1..=MAX_WORK_FACTOR).contains(&work_factor) {
+        return Err(format!(
+            "Invalid work factor: expected 1 to {}, got {}",
+            MAX_WORK_FACTOR, work_factor
+        ));
+    }
+    Ok(())
+}
+
+// 現在時刻（ミリ秒）
+// performance.now()が使える環境ではそれを使い、使えない場合はDate.now()で代用する
+fn now_ms() -> f64 {
+    use wasm_bindgen::JsCast;
+    
+    let performance = js_sys::Reflect::get(&js_sys::global(), &"performance".into()).ok();
+    performance
+        .and_then(|performance| {
+            let now = js_sys::Reflect::get(&performance, &"now".into())
+                .ok()?
+                .dyn_into::<js_sys::Function>()
+                .ok()?;
+            now.call0(&performance).ok()?.as_f64()
+        })
+        .unwrap_or_else(js_sys::Date::now)
+}
+
+// work_factor個のブロックを暗号化するのにかかった時間（ミリ秒）
+fn measure(cipher: AeadCipher, work_factor: u32) -> f64 {
+    let key = [0x42u8; 32];
+    let block = [0u8; BENCH_BLOCK_SIZE];
+    
+    let start = now_ms();
+    for counter in 0..work_factor {
+        let mut nonce = [0u8; 12];
+        nonce[..4].copy_from_slice(&counter.to_be_bytes());
+        std::hint::black_box(cipher.seal(&key, &nonce, std::hint::black_box(&block), DEFAULT_TAG_SIZE));
+    }
+    now_ms() - start
+}
+
+// 両方の暗号を計測し、速い方を返す（同じ時間の場合はChaCha20-Poly1305）
+// 結果は"auto"の指定で再利用できるよう記録する
+pub fn recommend(work_factor: u32) -> AeadCipher {
+    // 初回の呼び出しのコスト（鍵スケジュールの初期化など）を計測から除く
+    measure(AeadCipher::ChaCha20Poly1305, 1);
+    measure(AeadCipher::Aes256Gcm, 1);
+    
+    let chacha = measure(AeadCipher::ChaCha20Poly1305, work_factor);
+    let aes = measure(AeadCipher::Aes256Gcm, work_factor);
+    let recommended = if aes < chacha {
+        AeadCipher::Aes256Gcm
+    } else {
+        AeadCipher::ChaCha20Poly1305
+    };
+    
+    RECOMMENDED.with(|cell| cell.set(Some(recommended)));
+    recommended
+}
+
+// 暗号の指定を解決する
+// 省略時は以前の形式と同じChaCha20-Poly1305、"auto"は記録済みの推奨（なければ既定の作業量で計測する）
+pub fn resolve_cipher(name: Option<&str>) -> Result<AeadCipher, String> {
+    match name {
+        None => Ok(AeadCipher::ChaCha20Poly1305),
+        Some("auto") => Ok(RECOMMENDED
+            .with(|cell| cell.get())
+            .unwrap_or_else(|| recommend(DEFAULT_WORK_FACTOR))),
+        Some(name) => AeadCipher::from_name(name).ok_or_else(|| {
+            format!(
+                "Unknown AEAD cipher: {} (expected chacha20-poly1305, aes-256-gcm, or auto)",
+                name
+            )
+        }),
+    }
+}
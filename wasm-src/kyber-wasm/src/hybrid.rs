@@ -0,0 +1,239 @@
+// ハイブリッドKEM（X25519 + ML-KEM）の内部モジュール
+// どちらか一方の方式が破られても共有秘密の安全性を保つため、古典的な鍵共有と耐量子KEMを併用する
+//
+// 暗号文の形式:
+//   ciphertext := X25519の一時公開鍵 (32バイト) || ML-KEMの暗号文
+// 共有秘密の導出:
+//   ikm  := X25519の共有秘密 || ML-KEMの共有秘密
+//   info := label || X25519の一時公開鍵 || ML-KEMの暗号文 || 受信者のX25519公開鍵
+//   共有秘密 := HKDF-SHA256(salt なし, ikm, info) の先頭32バイト
+// X25519の一時公開鍵とML-KEMの暗号文をinfoに含めるため、どちらかを差し替えると共有秘密が一致しない
+// 受信者のX25519公開鍵も含めるのは、X25519の共有秘密だけでは受信者の鍵に束縛されないため
+// （ML-KEMの共有秘密は暗号文のハッシュに公開鍵を含むため、ML-KEMの公開鍵は含めない）
+// ML-KEMのパラメータセットは通常のカプセル化と同じく、ML-KEMの公開鍵のサイズから判別する
+
+use hkdf::Hkdf;
+use sha2::Sha256;
+use wasm_bindgen::prelude::*;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::level::{Level, SHARED_SECRET_SIZE};
+use crate::rng::KeyRng;
+use crate::{resolve_level, to_fixed, KyberEncapsulation};
+
+// X25519の秘密鍵・公開鍵のサイズ（一時公開鍵はそのまま暗号文の先頭に置く）
+const X25519_SECRET_KEY_SIZE: usize = 32;
+const X25519_PUBLIC_KEY_SIZE: usize = 32;
+
+// 共有秘密の導出のドメイン分離用ラベル
+const COMBINE_LABEL: &[u8] = b"kyber-wasm hybrid x25519 ml-kem v1";
+
+// 2つの共有秘密と暗号文から、ハイブリッドKEMの共有秘密を導出
+fn combine(
+    x25519_shared_secret: &[u8; 32],
+    mlkem_shared_secret: &[u8; SHARED_SECRET_SIZE],
+    x25519_ciphertext: &[u8; X25519_PUBLIC_KEY_SIZE],
+    mlkem_ciphertext: &[u8],
+    x25519_public_key: &[u8; X25519_PUBLIC_KEY_SIZE],
+) -> [u8; SHARED_SECRET_SIZE] {
+    let mut ikm = Zeroizing::new([0u8; 32 + SHARED_SECRET_SIZE]);
+    ikm[..32].copy_from_slice(x25519_shared_secret);
+    ikm[32..].copy_from_slice(mlkem_shared_secret);
+    
+    let mut info = Vec::with_capacity(COMBINE_LABEL.len() + 2 * X25519_PUBLIC_KEY_SIZE + mlkem_ciphertext.len());
+    info.extend_from_slice(COMBINE_LABEL);
+    info.extend_from_slice(x25519_ciphertext);
+    info.extend_from_slice(mlkem_ciphertext);
+    info.extend_from_slice(x25519_public_key);
+    
+    let mut shared_secret = [0u8; SHARED_SECRET_SIZE];
+    Hkdf::<Sha256>::new(None, &*ikm)
+        .expand(&info, &mut shared_secret)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    shared_secret
+}
+
+// X25519の共有秘密を確認
+// 小位数の点を公開鍵として渡されると共有秘密がすべて0になり、X25519の部分が寄与しなくなるため拒否する
+fn contributory(shared_secret: x25519_dalek::SharedSecret) -> Result<Zeroizing<[u8; 32]>, JsValue> {
+    if !shared_secret.was_contributory() {
+        return Err(JsValue::from_str("Invalid X25519 public key: low-order point"));
+    }
+    Ok(Zeroizing::new(shared_secret.to_bytes()))
+}
+
+// ハイブリッドKEMの鍵ペアの型定義
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct KyberHybridKeyPair {
+    classical_public_key: Vec<u8>,
+    classical_private_key: Vec<u8>,
+    pq_public_key: Vec<u8>,
+    pq_private_key: Vec<u8>,
+    level: Level,
+}
+
+#[wasm_bindgen]
+impl KyberHybridKeyPair {
+    /**
+     * ML-KEMの鍵のパラメータセット（512・768・1024）
+     */
+    #[wasm_bindgen(getter)]
+    pub fn level(&self) -> u16 {
+        self.level.number()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn classical_public_key(&self) -> Vec<u8> {
+        self.classical_public_key.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn classical_private_key(&self) -> Vec<u8> {
+        self.classical_private_key.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn pq_public_key(&self) -> Vec<u8> {
+        self.pq_public_key.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn pq_private_key(&self) -> Vec<u8> {
+        self.pq_private_key.clone()
+    }
+    
+    /**
+     * X25519とML-KEMの秘密鍵をメモリから消去する
+     * 消去した後の秘密鍵は空になる（freeで解放した場合も自動で消去される）
+     */
+    pub fn zeroize(&mut self) {
+        self.classical_private_key.zeroize();
+        self.pq_private_key.zeroize();
+    }
+}
+
+impl Drop for KyberHybridKeyPair {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/**
+ * ハイブリッドKEM（X25519 + ML-KEM）の鍵ペアを生成
+ * 
+ * @param level ML-KEMのパラメータセット（512・768・1024、省略時は768）
+ * @returns X25519の鍵ペアとML-KEMの鍵ペア
+ */
+#[wasm_bindgen]
+pub fn generate_hybrid_keypair(level: Option<u16>) -> Result<KyberHybridKeyPair, JsValue> {
+    let level = resolve_level(level)?;
+    
+    // X25519の鍵ペアを生成
+    let x25519_secret = StaticSecret::random_from_rng(KeyRng);
+    let x25519_public = PublicKey::from(&x25519_secret);
+    
+    // ML-KEMの鍵ペアを生成
+    let (pq_public_key, pq_private_key) = level.keygen(&mut KeyRng);
+    
+    Ok(KyberHybridKeyPair {
+        classical_public_key: x25519_public.as_bytes().to_vec(),
+        classical_private_key: x25519_secret.to_bytes().to_vec(),
+        pq_public_key,
+        pq_private_key,
+        level,
+    })
+}
+
+/**
+ * ハイブリッドKEMのカプセル化
+ * X25519の一時鍵による鍵共有とML-KEMのカプセル化を行い、2つの共有秘密をHKDF-SHA256で1つにまとめる
+ * 
+ * @param classical_public_key 受信者のX25519公開鍵（32バイト）
+ * @param pq_public_key 受信者のML-KEM公開鍵（いずれかのパラメータセットのサイズ）
+ * @returns 暗号文（X25519の一時公開鍵 || ML-KEMの暗号文）と32バイトの共有秘密
+ * @throws 公開鍵のサイズが不正な場合、X25519公開鍵が小位数の点の場合
+ */
+#[wasm_bindgen]
+pub fn hybrid_encapsulate(classical_public_key: &[u8], pq_public_key: &[u8]) -> Result<KyberEncapsulation, JsValue> {
+    let x25519_public_key = to_fixed::<X25519_PUBLIC_KEY_SIZE>("X25519 public key", classical_public_key)
+        .map_err(|e| JsValue::from_str(&e))?;
+    
+    // ML-KEMのカプセル化（パラメータセットは公開鍵のサイズから判別）
+    let (mlkem_ciphertext, mlkem_shared_secret) = Level::from_public_key(pq_public_key)
+        .and_then(|level| level.encaps(pq_public_key, &mut KeyRng))
+        .map_err(|e| JsValue::from_str(&e))?;
+    
+    // X25519の一時鍵で鍵共有
+    let ephemeral = EphemeralSecret::random_from_rng(KeyRng);
+    let x25519_ciphertext = PublicKey::from(&ephemeral).to_bytes();
+    let x25519_shared_secret = contributory(ephemeral.diffie_hellman(&PublicKey::from(x25519_public_key)))?;
+    
+    let shared_secret = combine(
+        &x25519_shared_secret,
+        &mlkem_shared_secret,
+        &x25519_ciphertext,
+        &mlkem_ciphertext,
+        &x25519_public_key,
+    );
+    
+    let mut ciphertext = Vec::with_capacity(X25519_PUBLIC_KEY_SIZE + mlkem_ciphertext.len());
+    ciphertext.extend_from_slice(&x25519_ciphertext);
+    ciphertext.extend_from_slice(&mlkem_ciphertext);
+    
+    Ok(KyberEncapsulation {
+        ciphertext,
+        shared_secret: shared_secret.to_vec(),
+    })
+}
+
+/**
+ * ハイブリッドKEMのデカプセル化
+ * 
+ * @param ciphertext hybrid_encapsulateで生成した暗号文
+ * @param classical_private_key X25519秘密鍵（32バイト）
+ * @param pq_private_key ML-KEM秘密鍵（公開鍵と同じパラメータセットのサイズ）
+ * @param pq_public_key ML-KEM公開鍵（秘密鍵の復元に必要）
+ * @returns 32バイトの共有秘密
+ * @throws 暗号文・鍵のサイズが不正な場合、暗号文のX25519公開鍵が小位数の点の場合
+ */
+#[wasm_bindgen]
+pub fn hybrid_decapsulate(
+    ciphertext: &[u8],
+    classical_private_key: &[u8],
+    pq_private_key: &[u8],
+    pq_public_key: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let x25519_secret = to_fixed::<X25519_SECRET_KEY_SIZE>("X25519 secret key", classical_private_key)
+        .map(StaticSecret::from)
+        .map_err(|e| JsValue::from_str(&e))?;
+    
+    // X25519の一時公開鍵とML-KEMの暗号文に分割
+    let level = Level::from_public_key(pq_public_key).map_err(|e| JsValue::from_str(&e))?;
+    let Some((x25519_ciphertext, mlkem_ciphertext)) = ciphertext.split_first_chunk::<X25519_PUBLIC_KEY_SIZE>() else {
+        return Err(JsValue::from_str(&format!(
+            "Invalid hybrid ciphertext size: expected {}, got {}",
+            X25519_PUBLIC_KEY_SIZE + level.ciphertext_size(),
+            ciphertext.len()
+        )));
+    };
+    
+    // ML-KEMのデカプセル化（暗号文・秘密鍵のサイズを公開鍵のパラメータセットと照合する）
+    let mlkem_shared_secret = level
+        .decaps(mlkem_ciphertext, pq_private_key, pq_public_key)
+        .map_err(|e| JsValue::from_str(&e))?;
+    
+    let x25519_public_key = PublicKey::from(&x25519_secret).to_bytes();
+    let x25519_shared_secret = contributory(x25519_secret.diffie_hellman(&PublicKey::from(*x25519_ciphertext)))?;
+    
+    let shared_secret = combine(
+        &x25519_shared_secret,
+        &mlkem_shared_secret,
+        x25519_ciphertext,
+        mlkem_ciphertext,
+        &x25519_public_key,
+    );
+    
+    Ok(shared_secret.to_vec())
+}
@@ -0,0 +1,243 @@
+// ML-KEMのパラメータセット（レベル）の内部モジュール
+// 鍵ペアの生成時にML-KEM-512・ML-KEM-768・ML-KEM-1024を選択できるようにする
+// 鍵と暗号文にはレベルのタグを付けない。公開鍵・秘密鍵・暗号文のサイズはそれぞれレベルごとに異なるため、
+// カプセル化・デカプセル化では公開鍵のサイズからレベルを判別し、秘密鍵・暗号文のサイズをそのレベルと照合する
+//
+//   レベル        公開鍵   秘密鍵   暗号文
+//   ML-KEM-512      800     1632      768
+//   ML-KEM-768     1184     2400     1088
+//   ML-KEM-1024    1568     3168     1568
+//
+// ストリーム暗号化・複数受信者向けコンテナ・シーク可能な形式は、これまで通りML-KEM-768の鍵のみに対応する
+
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+use crate::to_fixed;
+
+// 共有秘密のサイズ（すべてのレベルで32バイト）
+pub const SHARED_SECRET_SIZE: usize = 32;
+
+// 共有秘密（破棄時に消去される）
+pub type SharedSecret = Zeroizing<[u8; SHARED_SECRET_SIZE]>;
+
+// ML-KEMのパラメータセット（レベル）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    MlKem512,
+    MlKem768,
+    MlKem1024,
+}
+
+// レベルに対応するpqcrypto-stdのモジュールを$mとして$bodyを評価する
+macro_rules! with_level {
+    ($level:expr, $m:ident => $body:expr) => {
+        match $level {
+            Level::MlKem512 => {
+                use pqcrypto_std::mlkem::mlkem512 as $m;
+                $body
+            }
+            Level::MlKem768 => {
+                use pqcrypto_std::mlkem::mlkem768 as $m;
+                $body
+            }
+            Level::MlKem1024 => {
+                use pqcrypto_std::mlkem::mlkem1024 as $m;
+                $body
+            }
+        }
+    };
+}
+
+impl Level {
+    pub const ALL: [Level; 3] = [Level::MlKem512, Level::MlKem768, Level::MlKem1024];
+
+    // レベルを指定しない場合のレベル（以前から生成していたML-KEM-768）
+    pub const DEFAULT: Level = Level::MlKem768;
+
+    // generate_keypairのlevel引数（512・768・1024）からレベルを選ぶ
+    pub fn from_number(level: u16) -> Result<Level, String> {
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.number() == level)
+            .ok_or_else(|| format!("Unsupported ML-KEM level: {} (expected 512, 768, or 1024)", level))
+    }
+
+    pub fn number(self) -> u16 {
+        match self {
+            Level::MlKem512 => 512,
+            Level::MlKem768 => 768,
+            Level::MlKem1024 => 1024,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::MlKem512 => "ML-KEM-512",
+            Level::MlKem768 => "ML-KEM-768",
+            Level::MlKem1024 => "ML-KEM-1024",
+        }
+    }
+
+    pub fn public_key_size(self) -> usize {
+        with_level!(self, m => m::EncapsKey::BYTE_SIZE)
+    }
+
+    pub fn private_key_size(self) -> usize {
+        with_level!(self, m => m::DecapsKey::BYTE_SIZE)
+    }
+
+    pub fn ciphertext_size(self) -> usize {
+        with_level!(self, m => m::EncapsKey::CIPHERTEXT_SIZE)
+    }
+
+    // 公開鍵のサイズからレベルを判別
+    pub fn from_public_key(public_key: &[u8]) -> Result<Level, String> {
+        Self::from_size("public key", public_key.len(), Level::public_key_size)
+    }
+
+    // 暗号文のサイズからレベルを判別
+    pub fn from_ciphertext(ciphertext: &[u8]) -> Result<Level, String> {
+        Self::from_size("ciphertext", ciphertext.len(), Level::ciphertext_size)
+    }
+
+    // サイズが一致するレベルを探す。どのレベルとも一致しない場合は、各レベルのサイズを示すエラーを返す
+    fn from_size(name: &str, len: usize, size: fn(Level) -> usize) -> Result<Level, String> {
+        Self::ALL.into_iter().find(|&level| size(level) == len).ok_or_else(|| {
+            format!(
+                "Invalid {} size: expected {}, {}, or {}, got {}",
+                name,
+                size(Level::MlKem512),
+                size(Level::MlKem768),
+                size(Level::MlKem1024),
+                len
+            )
+        })
+    }
+
+    // 鍵ペア（公開鍵, 秘密鍵）を生成
+    pub fn keygen(self, rng: &mut (impl RngCore + CryptoRng)) -> (Vec<u8>, Vec<u8>) {
+        with_level!(self, m => {
+            let (ek, dk) = m::keygen(rng);
+            
+            let mut pk_bytes = [0u8; m::EncapsKey::BYTE_SIZE];
+            ek.to_bytes(&mut pk_bytes);
+            let mut sk_bytes = Zeroizing::new([0u8; m::DecapsKey::BYTE_SIZE]);
+            dk.to_bytes(&mut *sk_bytes, &ek);
+            
+            (pk_bytes.to_vec(), sk_bytes.to_vec())
+        })
+    }
+
+    // カプセル化を行い、暗号文と共有秘密を返す
+    pub fn encaps(
+        self,
+        public_key: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Vec<u8>, SharedSecret), String> {
+        with_level!(self, m => {
+            let pk_array = to_fixed::<{ m::EncapsKey::BYTE_SIZE }>("public key", public_key)?;
+            let ek = m::EncapsKey::from_bytes(&pk_array);
+            
+            let mut ss_bytes = SharedSecret::default();
+            let mut ct_bytes = [0u8; m::EncapsKey::CIPHERTEXT_SIZE];
+            ek.encaps(&mut ct_bytes, &mut *ss_bytes, rng);
+            
+            Ok((ct_bytes.to_vec(), ss_bytes))
+        })
+    }
+
+    // デカプセル化を行い、共有秘密を返す
+    // 暗号文・秘密鍵・公開鍵のサイズがこのレベルと一致しない場合はエラー
+    pub fn decaps(
+        self,
+        ciphertext: &[u8],
+        private_key: &[u8],
+        public_key: &[u8],
+    ) -> Result<SharedSecret, String> {
+        Keys::new(self, private_key, public_key)?.decaps(ciphertext)
+    }
+}
+
+// 復元済みの鍵（公開鍵, 秘密鍵）
+// from_bytesをやり直さずに同じ鍵で何度もデカプセル化するために保持する
+pub enum Keys {
+    MlKem512(pqcrypto_std::mlkem::mlkem512::EncapsKey, pqcrypto_std::mlkem::mlkem512::DecapsKey),
+    MlKem768(pqcrypto_std::mlkem::mlkem768::EncapsKey, pqcrypto_std::mlkem::mlkem768::DecapsKey),
+    MlKem1024(pqcrypto_std::mlkem::mlkem1024::EncapsKey, pqcrypto_std::mlkem::mlkem1024::DecapsKey),
+}
+
+// 保持している鍵のレベルに対応するモジュールを$m、鍵を$ek・$dkとして$bodyを評価する
+macro_rules! with_keys {
+    ($keys:expr, ($m:ident, $ek:ident, $dk:ident) => $body:expr) => {
+        match $keys {
+            Keys::MlKem512($ek, $dk) => {
+                use pqcrypto_std::mlkem::mlkem512 as $m;
+                $body
+            }
+            Keys::MlKem768($ek, $dk) => {
+                use pqcrypto_std::mlkem::mlkem768 as $m;
+                $body
+            }
+            Keys::MlKem1024($ek, $dk) => {
+                use pqcrypto_std::mlkem::mlkem1024 as $m;
+                $body
+            }
+        }
+    };
+}
+
+impl Keys {
+    // 秘密鍵と公開鍵を指定したレベルの鍵として復元
+    pub fn new(level: Level, private_key: &[u8], public_key: &[u8]) -> Result<Keys, String> {
+        // $mのモジュールの鍵として復元し、$variantの値にする
+        macro_rules! restore {
+            ($m:ident, $variant:ident) => {{
+                use pqcrypto_std::mlkem::$m as m;
+                let sk_array = Zeroizing::new(to_fixed::<{ m::DecapsKey::BYTE_SIZE }>("secret key", private_key)?);
+                let pk_array = to_fixed::<{ m::EncapsKey::BYTE_SIZE }>("public key", public_key)?;
+                
+                // 鍵を復元（from_bytesはResultを返さない）
+                Keys::$variant(m::EncapsKey::from_bytes(&pk_array), m::DecapsKey::from_bytes(&*sk_array))
+            }};
+        }
+        
+        Ok(match level {
+            Level::MlKem512 => restore!(mlkem512, MlKem512),
+            Level::MlKem768 => restore!(mlkem768, MlKem768),
+            Level::MlKem1024 => restore!(mlkem1024, MlKem1024),
+        })
+    }
+
+    pub fn level(&self) -> Level {
+        match self {
+            Keys::MlKem512(..) => Level::MlKem512,
+            Keys::MlKem768(..) => Level::MlKem768,
+            Keys::MlKem1024(..) => Level::MlKem1024,
+        }
+    }
+
+    // 保持している鍵で暗号文をデカプセル化
+    pub fn decaps(&self, ciphertext: &[u8]) -> Result<SharedSecret, String> {
+        with_keys!(self, (m, ek, dk) => {
+            let ct_array = to_fixed::<{ m::EncapsKey::CIPHERTEXT_SIZE }>("ciphertext", ciphertext)?;
+            
+            // デカプセル化を実行（引数順序: 共有秘密, 公開鍵, 暗号文）
+            let mut ss_bytes = SharedSecret::default();
+            dk.decaps(&mut *ss_bytes, ek, &ct_array);
+            Ok(ss_bytes)
+        })
+    }
+
+    // 直列化した鍵（公開鍵, 秘密鍵）
+    pub fn to_bytes(&self) -> (Vec<u8>, Vec<u8>) {
+        with_keys!(self, (m, ek, dk) => {
+            let mut pk_array = [0u8; m::EncapsKey::BYTE_SIZE];
+            ek.to_bytes(&mut pk_array);
+            let mut sk_array = Zeroizing::new([0u8; m::DecapsKey::BYTE_SIZE]);
+            dk.to_bytes(&mut *sk_array, ek);
+            
+            (pk_array.to_vec(), sk_array.to_vec())
+        })
+    }
+}
@@ -1,6 +1,23 @@
 use wasm_bindgen::prelude::*;
-use pqcrypto_std::mlkem::{keygen, EncapsKey, DecapsKey};
-use rand::rngs::OsRng;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+mod aead;
+mod hybrid;
+mod level;
+#[path = "../../shared/pq/rng.rs"]
+mod rng;
+mod stream;
+mod multi;
+mod seekable;
+#[cfg(feature = "test-rng")]
+#[path = "../../shared/pq/test_rng.rs"]
+mod test_rng;
+use level::{Keys, Level};
+use rand::{CryptoRng, RngCore};
+use rng::{KeyRng, SeedRng};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
 
 // wasm-bindgenの初期化
 #[wasm_bindgen(start)]
@@ -9,28 +26,120 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
+/**
+ * テスト専用: 鍵生成・カプセル化に使う乱数生成モードを切り替える
+ * test-rng featureを有効にしたビルドでのみ公開される（本番ビルドには含まれない）
+ * 
+ * @param mode "os"（通常）、"zero_bytes"（すべて0）、"deterministic"（seedから決定的に生成）のいずれか
+ * @param seed deterministicモードのシード（他のモードでは無視される）
+ */
+#[cfg(feature = "test-rng")]
+#[wasm_bindgen]
+pub fn set_test_rng_mode(mode: &str, seed: &[u8]) -> Result<(), JsValue> {
+    use test_rng::TestRngMode;
+    
+    let mode = match mode {
+        "os" => TestRngMode::Os,
+        "zero_bytes" => TestRngMode::ZeroBytes,
+        "deterministic" => TestRngMode::Deterministic {
+            seed: seed.to_vec(),
+            counter: 0,
+        },
+        _ => return Err(JsValue::from_str(&format!("Unknown RNG mode: {}", mode))),
+    };
+    test_rng::set_mode(mode);
+    Ok(())
+}
+
 // Kyber鍵ペアの型定義
 #[wasm_bindgen]
+#[derive(Clone)]
 pub struct KyberKeyPair {
     public_key: Vec<u8>,
     private_key: Vec<u8>,
+    level: Level,
 }
 
 #[wasm_bindgen]
 impl KyberKeyPair {
+    /**
+     * 鍵ペアのパラメータセット（512・768・1024）
+     * take_public_key・take_private_keyで取り出した後も変わらない
+     */
+    #[wasm_bindgen(getter)]
+    pub fn level(&self) -> u16 {
+        self.level.number()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn public_key(&self) -> Vec<u8> {
         self.public_key.clone()
     }
 
+    /**
+     * 公開鍵を取り出す（所有権を移すため、クローンしない）
+     * 一度だけエクスポートする用途向けで、取り出した後の公開鍵は空になる
+     * 繰り返し参照する場合はgetterのpublic_keyを使用すること
+     * 
+     * @returns 公開鍵（2回目以降は空）
+     */
+    pub fn take_public_key(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.public_key)
+    }
+
     #[wasm_bindgen(getter)]
     pub fn private_key(&self) -> Vec<u8> {
         self.private_key.clone()
     }
+
+    /**
+     * 秘密鍵を取り出す（所有権を移すため、クローンしない）
+     * 一度だけエクスポートする用途向けで、取り出した後の秘密鍵は空になる
+     * 繰り返し参照する場合はgetterのprivate_keyを使用すること
+     * 
+     * @returns 秘密鍵（2回目以降は空）
+     */
+    pub fn take_private_key(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.private_key)
+    }
+
+    /**
+     * 同じ内容の複製を作る（JSではclone()）
+     * 元の鍵ペアを保持したまま、Workerなどに複製を渡せる
+     * take_public_key・take_private_keyで取り出した後の鍵は空のまま複製される
+     * 
+     * @returns 複製
+     */
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> KyberKeyPair {
+        self.clone()
+    }
+
+    /**
+     * 秘密鍵をメモリから消去する
+     * wasmの線形メモリはJSから読み取れるため、秘密鍵が不要になった時点で呼び出すこと
+     * 消去した後の秘密鍵は空になる（freeで解放した場合も自動で消去される）
+     */
+    pub fn zeroize(&mut self) {
+        self.private_key.zeroize();
+    }
+}
+
+impl Drop for KyberKeyPair {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
+// 転送形式のバージョン
+const TRANSPORT_VERSION: u8 = 1;
+
+// 転送形式のヘッダサイズ（version (1バイト) || length (4バイト、ビッグエンディアン)）
+const TRANSPORT_HEADER_SIZE: usize = 5;
+
 // カプセル化結果の型定義
 #[wasm_bindgen]
+#[derive(Clone)]
 pub struct KyberEncapsulation {
     ciphertext: Vec<u8>,
     shared_secret: Vec<u8>,
@@ -38,146 +147,548 @@ pub struct KyberEncapsulation {
 
 #[wasm_bindgen]
 impl KyberEncapsulation {
+    /**
+     * 暗号文を転送形式に変換
+     * 共有秘密は秘密情報のため含めない
+     * 
+     * @returns version (1バイト) || length (4バイト) || 暗号文
+     */
+    pub fn to_transport(&self) -> Vec<u8> {
+        let mut transport = Vec::with_capacity(TRANSPORT_HEADER_SIZE + self.ciphertext.len());
+        transport.push(TRANSPORT_VERSION);
+        transport.extend_from_slice(&(self.ciphertext.len() as u32).to_be_bytes());
+        transport.extend_from_slice(&self.ciphertext);
+        transport
+    }
+
+    /**
+     * 鍵確認タグを計算
+     * イニシエータはこのタグを暗号文と一緒に送り、レスポンダはresponder_sessionで検証する
+     * 
+     * @returns 共有秘密と暗号文から導出した32バイトのタグ
+     */
+    pub fn confirmation_tag(&self) -> Vec<u8> {
+        compute_confirmation_tag(&self.shared_secret, &self.ciphertext).to_vec()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn ciphertext(&self) -> Vec<u8> {
         self.ciphertext.clone()
     }
 
+    /**
+     * 暗号文を取り出す（所有権を移すため、クローンしない）
+     * 一度だけエクスポートする用途向けで、取り出した後の暗号文は空になる
+     * 繰り返し参照する場合はgetterのciphertextを使用すること
+     * 
+     * @returns 暗号文（2回目以降は空）
+     */
+    pub fn take_ciphertext(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.ciphertext)
+    }
+
     #[wasm_bindgen(getter)]
     pub fn shared_secret(&self) -> Vec<u8> {
         self.shared_secret.clone()
     }
+
+    /**
+     * 共有秘密を取り出す（所有権を移すため、クローンしない）
+     * 一度だけエクスポートする用途向けで、取り出した後の共有秘密は空になる
+     * 繰り返し参照する場合はgetterのshared_secretを使用すること
+     * 
+     * @returns 共有秘密（2回目以降は空）
+     */
+    pub fn take_shared_secret(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.shared_secret)
+    }
+
+    /**
+     * 同じ内容の複製を作る（JSではclone()）
+     * take_ciphertext・take_shared_secretで取り出した後の値は空のまま複製される
+     * 
+     * @returns 複製
+     */
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> KyberEncapsulation {
+        self.clone()
+    }
+}
+
+impl Drop for KyberEncapsulation {
+    fn drop(&mut self) {
+        self.shared_secret.zeroize();
+    }
 }
 
 /**
  * CRYSTALS-Kyber鍵ペアを生成
  * ML-KEMを使用（NIST標準化されたKyber）
+ * カプセル化・デカプセル化では鍵のサイズからパラメータセットを判別するため、レベルを再度指定する必要はない
  * 
+ * @param level パラメータセット（512・768・1024、省略時は768）
  * @returns 公開鍵と秘密鍵のペア
  */
 #[wasm_bindgen]
-pub fn generate_keypair() -> KyberKeyPair {
-    // 乱数生成器を作成
-    let mut rng = OsRng;
-    
+pub fn generate_keypair(level: Option<u16>) -> Result<KyberKeyPair, JsValue> {
+    Ok(keypair_with_rng(resolve_level(level)?, &mut KeyRng))
+}
+
+// level引数からレベルを選ぶ（省略時はLevel::DEFAULT）
+pub(crate) fn resolve_level(level: Option<u16>) -> Result<Level, JsValue> {
+    match level {
+        Some(level) => Level::from_number(level).map_err(|e| JsValue::from_str(&e)),
+        None => Ok(Level::DEFAULT),
+    }
+}
+
+// 指定した乱数生成器で鍵ペアを生成（generate_keypairとシードからの鍵生成で共通）
+fn keypair_with_rng(level: Level, rng: &mut (impl RngCore + CryptoRng)) -> KyberKeyPair {
     // ML-KEMの鍵ペアを生成
-    let (ek, dk) = keygen(&mut rng);
-    
-    // バイト配列に変換
-    let mut pk_bytes = [0u8; EncapsKey::BYTE_SIZE];
-    ek.to_bytes(&mut pk_bytes);
-    
-    let mut sk_bytes = [0u8; DecapsKey::BYTE_SIZE];
-    dk.to_bytes(&mut sk_bytes, &ek);
+    let (public_key, private_key) = level.keygen(rng);
     
     KyberKeyPair {
-        public_key: pk_bytes.to_vec(),
-        private_key: sk_bytes.to_vec(),
+        public_key,
+        private_key,
+        level,
+    }
+}
+
+// シードのサイズ（FIPS 203のML-KEM.KeyGen_internalに渡すd || zと同じ64バイト）
+const SEED_SIZE: usize = 64;
+
+// シードの形式を確認
+// すべて0のシードは初期化漏れのバッファを渡した可能性が高いため拒否する
+fn check_seed(seed: &[u8]) -> Result<(), String> {
+    if seed.len() != SEED_SIZE {
+        return Err(format!("Invalid seed size: expected {}, got {}", SEED_SIZE, seed.len()));
+    }
+    if seed.iter().all(|&b| b == 0) {
+        return Err("Invalid seed: all bytes are zero".to_string());
     }
+    Ok(())
+}
+
+/**
+ * シードから決定的にKyber鍵ペアを生成
+ * 同じシードからは常に同じ鍵ペアが得られる（FIPS 203のシードd || zから生成した鍵ペアと一致する）
+ * シードは秘密鍵と同等に扱うこと
+ * 
+ * @param seed 鍵生成シード（64バイト、すべて0は不可）
+ * @returns 公開鍵と秘密鍵のペア
+ */
+#[wasm_bindgen]
+pub fn generate_keypair_from_seed(seed: &[u8]) -> Result<KyberKeyPair, JsValue> {
+    check_seed(seed).map_err(|e| JsValue::from_str(&e))?;
+    SeedRng::with_seed(seed, |rng| keypair_with_rng(Level::DEFAULT, rng)).map_err(|e| JsValue::from_str(&e))
+}
+
+/**
+ * 複数のシードからまとめて決定的にKyber鍵ペアを生成
+ * 大量の鍵を再現可能な形で払い出す用途向けで、wasmの呼び出しが1回で済む
+ * 鍵の生成前にすべてのシードを確認し、1つでも不正なものがあれば何も生成せずにエラーを返す
+ * 
+ * @param seeds 鍵生成シード（それぞれ64バイト、すべて0は不可）の配列
+ * @returns シードと同じ順の鍵ペアの配列（各要素はgenerate_keypair_from_seedの結果と一致する）
+ */
+#[wasm_bindgen]
+pub fn generate_keypairs_from_seeds(seeds: js_sys::Array) -> Result<Vec<KyberKeyPair>, JsValue> {
+    use wasm_bindgen::JsCast;
+    
+    let seeds = seeds
+        .iter()
+        .enumerate()
+        .map(|(index, seed)| {
+            let seed = seed
+                .dyn_into::<js_sys::Uint8Array>()
+                .map_err(|_| format!("Seed {} is not a Uint8Array", index))?
+                .to_vec();
+            check_seed(&seed).map_err(|e| format!("Seed {}: {}", index, e))?;
+            Ok(seed)
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|e| JsValue::from_str(&e))?;
+    
+    seeds
+        .iter()
+        .map(|seed| SeedRng::with_seed(seed, |rng| keypair_with_rng(Level::DEFAULT, rng)))
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+// バイト列を固定サイズ配列に変換
+// 長さの確認と変換を1か所で行うため、確認とコピーのサイズが食い違うことがない
+pub(crate) fn to_fixed<const N: usize>(name: &str, bytes: &[u8]) -> Result<[u8; N], String> {
+    bytes
+        .try_into()
+        .map_err(|_| format!("Invalid {} size: expected {}, got {}", name, N, bytes.len()))
+}
+
+// 鍵識別子（KID）として使うSHA-256の先頭のバイト数（base64urlで22文字）
+const KEY_ID_SIZE: usize = 16;
+
+/**
+ * 公開鍵から安定した鍵識別子（KID）を導出
+ * SHA-256(公開鍵) の先頭16バイトをbase64url（パディングなし）で表した22文字の文字列
+ * Dilithium・FALCON・IBE・ABEのkey_idと同じ方式のため、鍵の種類によらず同じ形式で索引できる
+ * 
+ * @param public_key ML-KEM公開鍵（バイト配列、いずれかのパラメータセットのサイズ）
+ * @returns 鍵識別子（22文字）
+ */
+#[wasm_bindgen]
+pub fn key_id(public_key: &[u8]) -> Result<String, JsValue> {
+    Level::from_public_key(public_key).map_err(|e| JsValue::from_str(&e))?;
+    let digest = Sha256::digest(public_key);
+    Ok(URL_SAFE_NO_PAD.encode(&digest[..KEY_ID_SIZE]))
 }
 
 /**
  * 鍵カプセル化（Encapsulation）
  * 公開鍵を使用して共有秘密を生成し、カプセル化する
+ * パラメータセットは公開鍵のサイズから判別する
  * 
- * @param public_key 公開鍵（バイト配列、固定サイズ）
+ * @param public_key 公開鍵（バイト配列、いずれかのパラメータセットのサイズ）
  * @returns 暗号文と共有秘密
+ * @throws 公開鍵のサイズがどのパラメータセットとも一致しない場合
  */
 #[wasm_bindgen]
-pub fn encapsulate(public_key: &[u8]) -> KyberEncapsulation {
-    // 公開鍵のサイズをチェック
-    if public_key.len() != EncapsKey::BYTE_SIZE {
-        wasm_bindgen::throw_str(&format!(
-            "Invalid public key size: expected {}, got {}",
-            EncapsKey::BYTE_SIZE,
-            public_key.len()
-        ));
-    }
+pub fn encapsulate(public_key: &[u8]) -> Result<KyberEncapsulation, JsValue> {
+    // 公開鍵のサイズからパラメータセットを判別し、カプセル化を実行
+    let (ciphertext, shared_secret) = Level::from_public_key(public_key)
+        .and_then(|level| level.encaps(public_key, &mut KeyRng))
+        .map_err(|e| JsValue::from_str(&e))?;
     
-    // 固定サイズ配列に変換
-    let mut pk_array = [0u8; EncapsKey::BYTE_SIZE];
-    pk_array.copy_from_slice(public_key);
+    Ok(KyberEncapsulation {
+        ciphertext,
+        shared_secret: shared_secret.to_vec(),
+    })
+}
+
+// 決定的なカプセル化に渡す乱数（FIPS 203のML-KEM.Encaps_internalに渡すmと同じ32バイト）
+const COINS_SIZE: usize = 32;
+
+/**
+ * 乱数を指定して決定的に鍵カプセル化を行う
+ * 既知解テスト（KAT）で、FIPS 203のML-KEM.Encaps_internal(ek, m)の出力と照合するためのもの
+ * 同じ公開鍵と乱数からは常に同じ暗号文と共有秘密が得られるため、通常の用途ではencapsulateを使用すること
+ * 
+ * @param public_key 公開鍵（バイト配列、いずれかのパラメータセットのサイズ）
+ * @param coins カプセル化の乱数m（32バイト）
+ * @returns 暗号文と共有秘密
+ * @throws 公開鍵・乱数のサイズが不正な場合
+ */
+#[wasm_bindgen]
+pub fn encapsulate_deterministic(public_key: &[u8], coins: &[u8]) -> Result<KyberEncapsulation, JsValue> {
+    if coins.len() != COINS_SIZE {
+        return Err(JsValue::from_str(&format!(
+            "Invalid coins size: expected {}, got {}",
+            COINS_SIZE,
+            coins.len()
+        )));
+    }
     
-    // 公開鍵を復元（from_bytesはResultを返さない）
-    let ek = EncapsKey::from_bytes(&pk_array);
+    // カプセル化が消費する乱数を指定した乱数mに置き換える
+    let (ciphertext, shared_secret) = Level::from_public_key(public_key)
+        .and_then(|level| SeedRng::with_seed(coins, |rng| level.encaps(public_key, rng))?)
+        .map_err(|e| JsValue::from_str(&e))?;
     
-    // 乱数生成器を作成
-    let mut rng = OsRng;
+    Ok(KyberEncapsulation {
+        ciphertext,
+        shared_secret: shared_secret.to_vec(),
+    })
+}
+
+/**
+ * 転送形式から暗号文を取り出す
+ * 
+ * @param transport KyberEncapsulation.to_transportで生成したバイト列
+ * @returns 暗号文（decapsulateにそのまま渡せる）
+ */
+#[wasm_bindgen]
+pub fn from_transport(transport: &[u8]) -> Result<Vec<u8>, JsValue> {
+    if transport.len() < TRANSPORT_HEADER_SIZE {
+        return Err(JsValue::from_str("Transport data is too short"));
+    }
     
-    // カプセル化を実行（共有秘密と暗号文のバッファを準備）
-    let mut ss_bytes = [0u8; 32]; // 共有秘密は32バイト
-    let mut ct_bytes = [0u8; EncapsKey::CIPHERTEXT_SIZE];
+    if transport[0] != TRANSPORT_VERSION {
+        return Err(JsValue::from_str(&format!(
+            "Unsupported transport version: expected {}, got {}",
+            TRANSPORT_VERSION, transport[0]
+        )));
+    }
     
-    // encapsの引数順序: (暗号文, 共有秘密, 乱数生成器)
-    ek.encaps(&mut ct_bytes, &mut ss_bytes, &mut rng);
+    let mut length_bytes = [0u8; 4];
+    length_bytes.copy_from_slice(&transport[1..TRANSPORT_HEADER_SIZE]);
+    let length = u32::from_be_bytes(length_bytes) as usize;
     
-    KyberEncapsulation {
-        ciphertext: ct_bytes.to_vec(),
-        shared_secret: ss_bytes.to_vec(),
+    let ciphertext = &transport[TRANSPORT_HEADER_SIZE..];
+    if ciphertext.len() != length {
+        return Err(JsValue::from_str(&format!(
+            "Invalid ciphertext length in transport data: header says {}, got {}",
+            length,
+            ciphertext.len()
+        )));
     }
+    Level::from_ciphertext(ciphertext)
+        .map_err(|e| JsValue::from_str(&format!("Invalid ciphertext in transport data: {}", e)))?;
+    
+    Ok(ciphertext.to_vec())
 }
 
 /**
  * 鍵デカプセル化（Decapsulation）
  * 秘密鍵と暗号文を使用して共有秘密を復元する
+ * パラメータセットは公開鍵のサイズから判別し、秘密鍵・暗号文のサイズがそれと一致することを確認する
  * 
- * @param ciphertext 暗号文（バイト配列、固定サイズ）
- * @param private_key 秘密鍵（バイト配列、固定サイズ）
+ * @param ciphertext 暗号文（バイト配列、公開鍵と同じパラメータセットのサイズ）
+ * @param private_key 秘密鍵（バイト配列、公開鍵と同じパラメータセットのサイズ）
  * @param public_key 公開鍵（秘密鍵の復元に必要）
  * @returns 共有秘密
+ * @throws 暗号文・秘密鍵・公開鍵のサイズが不正な場合
  */
 #[wasm_bindgen]
-pub fn decapsulate(ciphertext: &[u8], private_key: &[u8], public_key: &[u8]) -> Vec<u8> {
-    // サイズチェック
-    if ciphertext.len() != EncapsKey::CIPHERTEXT_SIZE {
-        wasm_bindgen::throw_str(&format!(
-            "Invalid ciphertext size: expected {}, got {}",
-            EncapsKey::CIPHERTEXT_SIZE,
-            ciphertext.len()
-        ));
+pub fn decapsulate(ciphertext: &[u8], private_key: &[u8], public_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+    // 公開鍵のサイズからパラメータセットを判別し、そのサイズで暗号文・秘密鍵を確認してデカプセル化
+    let shared_secret = Level::from_public_key(public_key)
+        .and_then(|level| level.decaps(ciphertext, private_key, public_key))
+        .map_err(|e| JsValue::from_str(&e))?;
+    
+    Ok(shared_secret.to_vec())
+}
+
+/**
+ * クレートのバージョンを取得
+ * 
+ * @returns Cargo.tomlのバージョン文字列
+ */
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+/**
+ * 復号に対応している暗号文の形式のバージョンを取得
+ * ストリーム暗号化と複数受信者向けコンテナの先頭1バイトのバージョンで、新しい順に並ぶ
+ * 暗号化では常に先頭（最新）のバージョンを使い、それ以外は旧形式の復号のためだけに対応する
+ * 
+ * @returns 対応しているバージョンの一覧（例: [2, 1]）
+ */
+#[wasm_bindgen]
+pub fn supported_ciphertext_versions() -> Vec<u8> {
+    aead::SUPPORTED_CIPHERTEXT_VERSIONS.to_vec()
+}
+
+/**
+ * 実行環境でAES-256-GCMとChaCha20-Poly1305の速度を計測し、速い方の暗号を推奨する
+ * AES-NIなどのハードウェア支援の有無で速い方が変わるため、起動時に1回呼び出す想定
+ * 結果は記録され、kyber_seal_multiで暗号に"auto"を指定した場合に使われる
+ * 
+ * @param work_factor 各暗号で暗号化する4KiBのブロックの数（1〜65536、省略時は256）。大きいほど計測が安定するが時間がかかる
+ * @returns "aes-256-gcm" または "chacha20-poly1305"
+ */
+#[wasm_bindgen]
+pub fn recommend_aead(work_factor: Option<u32>) -> Result<String, JsValue> {
+    let work_factor = work_factor.unwrap_or(aead::DEFAULT_WORK_FACTOR);
+    aead::check_work_factor(work_factor).map_err(|e| JsValue::from_str(&e))?;
+    Ok(aead::recommend(work_factor).name().to_string())
+}
+
+/**
+ * ビルドに含まれている機能を取得
+ * 
+ * @returns アルゴリズム名と利用可能なパラメータセットを持つオブジェクト
+ */
+#[wasm_bindgen]
+pub fn features() -> Result<JsValue, JsValue> {
+    let parameter_sets = js_sys::Array::new();
+    for level in Level::ALL {
+        parameter_sets.push(&level.name().into());
     }
     
-    if private_key.len() != DecapsKey::BYTE_SIZE {
-        wasm_bindgen::throw_str(&format!(
-            "Invalid secret key size: expected {}, got {}",
-            DecapsKey::BYTE_SIZE,
-            private_key.len()
-        ));
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"algorithm".into(), &"ML-KEM".into())?;
+    js_sys::Reflect::set(&result, &"parameter_sets".into(), &parameter_sets)?;
+    js_sys::Reflect::set(&result, &"hybrid".into(), &"X25519".into())?;
+    
+    Ok(result.into())
+}
+
+/**
+ * 復元済みの鍵を保持するデカプセル化器
+ * 同じ鍵で多数の暗号文をデカプセル化する場合に、鍵の復元（from_bytes）を1回で済ませる
+ */
+#[wasm_bindgen]
+pub struct KyberDecapsulator {
+    keys: Keys,
+}
+
+#[wasm_bindgen]
+impl KyberDecapsulator {
+    /**
+     * 秘密鍵と公開鍵からデカプセル化器を生成
+     * パラメータセットは公開鍵のサイズから判別する
+     * 
+     * @param private_key 秘密鍵（バイト配列、公開鍵と同じパラメータセットのサイズ）
+     * @param public_key 公開鍵（バイト配列、いずれかのパラメータセットのサイズ）
+     */
+    #[wasm_bindgen(constructor)]
+    pub fn new(private_key: &[u8], public_key: &[u8]) -> Result<KyberDecapsulator, JsValue> {
+        // 鍵を復元（サイズが異なる場合はエラー）
+        let keys = Level::from_public_key(public_key)
+            .and_then(|level| Keys::new(level, private_key, public_key))
+            .map_err(|e| JsValue::from_str(&e))?;
+        
+        Ok(KyberDecapsulator { keys })
+    }
+
+    /**
+     * 保持している鍵のパラメータセット（512・768・1024）
+     */
+    #[wasm_bindgen(getter)]
+    pub fn level(&self) -> u16 {
+        self.keys.level().number()
+    }
+
+    /**
+     * 保持している鍵で暗号文をデカプセル化
+     * 
+     * @param ciphertext 暗号文（バイト配列、鍵と同じパラメータセットのサイズ）
+     * @returns 共有秘密
+     */
+    pub fn decapsulate(&self, ciphertext: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let shared_secret = self.keys.decaps(ciphertext).map_err(|e| JsValue::from_str(&e))?;
+        
+        Ok(shared_secret.to_vec())
+    }
+
+    /**
+     * 同じ鍵を保持するデカプセル化器の複製を作る（JSではclone()）
+     * 元のデカプセル化器を保持したまま、Workerなどに複製を渡せる
+     * 
+     * @returns 複製
+     */
+    #[wasm_bindgen(js_name = clone)]
+    pub fn duplicate(&self) -> KyberDecapsulator {
+        self.clone()
+    }
+}
+
+// ML-KEMの鍵の型はCloneを実装していないため、直列化して復元することで複製する
+impl Clone for KyberDecapsulator {
+    fn clone(&self) -> Self {
+        let (public_key, private_key) = self.keys.to_bytes();
+        
+        KyberDecapsulator {
+            keys: Keys::new(self.keys.level(), &private_key, &public_key)
+                .expect("serialized keys have the sizes of their own level"),
+        }
     }
+}
+
+// 鍵確認タグのドメイン分離用ラベル
+const CONFIRMATION_LABEL: &[u8] = b"kyber-wasm key confirmation v1";
+
+/// 鍵確認タグを計算（SHA-256(label || 共有秘密 || 暗号文)）
+fn compute_confirmation_tag(shared_secret: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(CONFIRMATION_LABEL);
+    hasher.update(shared_secret);
+    hasher.update(ciphertext);
+    hasher.finalize().into()
+}
+
+/// 定数時間でバイト列を比較
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/**
+ * 鍵確認付きのデカプセル化（レスポンダ側）
+ * ML-KEMは不正な暗号文に対してもエラーを返さず擬似乱数の共有秘密を返す（暗黙的拒否）ため、
+ * イニシエータの鍵確認タグを検証してから共有秘密を返す
+ * 
+ * @param ciphertext 暗号文
+ * @param private_key 秘密鍵
+ * @param public_key 公開鍵
+ * @param expected_confirmation イニシエータから受け取った鍵確認タグ
+ * @returns 鍵確認に成功した場合のみ共有秘密
+ */
+#[wasm_bindgen]
+pub fn responder_session(
+    ciphertext: &[u8],
+    private_key: &[u8],
+    public_key: &[u8],
+    expected_confirmation: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let shared_secret = decapsulate(ciphertext, private_key, public_key)?;
     
-    if public_key.len() != EncapsKey::BYTE_SIZE {
-        wasm_bindgen::throw_str(&format!(
-            "Invalid public key size: expected {}, got {}",
-            EncapsKey::BYTE_SIZE,
-            public_key.len()
+    let tag = compute_confirmation_tag(&shared_secret, ciphertext);
+    if !constant_time_eq(&tag, expected_confirmation) {
+        return Err(JsValue::from_str(
+            "Key confirmation failed: the ciphertext was tampered with or the keys do not match",
         ));
     }
     
-    // 固定サイズ配列に変換
-    let mut ct_array = [0u8; EncapsKey::CIPHERTEXT_SIZE];
-    ct_array.copy_from_slice(ciphertext);
-    
-    let mut sk_array = [0u8; DecapsKey::BYTE_SIZE];
-    sk_array.copy_from_slice(private_key);
-    
-    let mut pk_array = [0u8; EncapsKey::BYTE_SIZE];
-    pk_array.copy_from_slice(public_key);
-    
-    // 鍵を復元（from_bytesはResultを返さない）
-    let ek = EncapsKey::from_bytes(&pk_array);
-    let dk = DecapsKey::from_bytes(&sk_array);
-    
-    // 共有秘密のバッファを準備
-    let mut ss_bytes = [0u8; 32]; // 共有秘密は32バイト
+    Ok(shared_secret)
+}
+
+/**
+ * モジュールが正しく動作しているかを確認
+ * すべてのパラメータセットで鍵生成→カプセル化→デカプセル化の往復を行い、
+ * 改ざんした暗号文から同じ共有秘密が得られないことも確認する
+ * 
+ * @returns 正常に動作していればtrue
+ */
+#[wasm_bindgen]
+pub fn health_check() -> bool {
+    Level::ALL
+        .into_iter()
+        .all(|level| run_health_check(level, false) && !run_health_check(level, true))
+}
+
+// 往復を実行し、両者の共有秘密が一致したかを返す
+// corruptがtrueの場合はデカプセル化の前に暗号文を1ビット反転させる
+fn run_health_check(level: Level, corrupt: bool) -> bool {
+    let keypair = keypair_with_rng(level, &mut KeyRng);
+    let Ok(encapsulation) = encapsulate(&keypair.public_key) else {
+        return false;
+    };
     
-    // デカプセル化を実行（引数順序: 共有秘密, 公開鍵, 暗号文）
-    dk.decaps(&mut ss_bytes, &ek, &ct_array);
+    let mut ciphertext = encapsulation.ciphertext.clone();
+    if corrupt {
+        ciphertext[0] ^= 0x01;
+    }
     
-    ss_bytes.to_vec()
+    decapsulate(&ciphertext, &keypair.private_key, &keypair.public_key)
+        .is_ok_and(|shared_secret| constant_time_eq(&shared_secret, &encapsulation.shared_secret))
 }
 
-// 基本的なテスト関数
+/**
+ * 乱数生成器が正常に動作しているかを確認
+ * 鍵ペアを2回生成し、両者が異なること・すべて0でないことを確かめる
+ * 乱数がすべて0になる、固定のシードから生成されているなどの不具合を起動時に検出するためのもので、
+ * falseの場合は鍵を生成・使用しないこと
+ * 
+ * @returns 乱数生成器が正常と判断できればtrue
+ */
 #[wasm_bindgen]
-pub fn add(a: u32, b: u32) -> u32 {
-    a + b
+pub fn entropy_sanity_check() -> bool {
+    // 乱数生成器の出力そのものがすべて0でないか
+    let mut sample = [0u8; 32];
+    KeyRng.fill_bytes(&mut sample);
+    if sample.iter().all(|&b| b == 0) {
+        return false;
+    }
+    
+    let first = keypair_with_rng(Level::DEFAULT, &mut KeyRng);
+    let second = keypair_with_rng(Level::DEFAULT, &mut KeyRng);
+    
+    let is_nonzero = |bytes: &[u8]| bytes.iter().any(|&b| b != 0);
+    is_nonzero(&first.public_key)
+        && is_nonzero(&first.private_key)
+        && !constant_time_eq(&first.public_key, &second.public_key)
+        && !constant_time_eq(&first.private_key, &second.private_key)
 }
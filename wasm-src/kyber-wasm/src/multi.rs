@@ -0,0 +1,213 @@
+// 複数受信者向けコンテナの内部モジュール
+// ランダムなコンテンツ鍵で本文を1回だけ暗号化し、コンテンツ鍵を受信者ごとにKyberで封入する
+//
+// コンテナ形式:
+//   container := version (1バイト) || tag_size (1バイト) || count (1バイト) || slot* || body
+//   slot      := Kyber暗号文 || 封入したコンテンツ鍵 (32バイト || タグ16バイト)
+//   body      := AEAD(コンテンツ鍵, nonce, 平文) (暗号文 || タグtag_sizeバイト)
+// 本文のAEADはChaCha20-Poly1305かAES-256-GCMで、AES-256-GCMの場合はtag_sizeのバイトに0x80を論理和で付ける
+// （スロットの封入は常にChaCha20-Poly1305）
+// バージョン1のヘッダにはtag_sizeがなく、本文のタグは常に16バイト（復号のみ対応）
+// タグを切り詰めるのは本文のみで、スロットのタグは自分宛てのスロットの判定にも使うため常に16バイト
+// 本文のnonceは SHA-256(label || ヘッダ || slot*) の先頭12バイトで、
+// スロットを並び順どおりにすべてハッシュするため、どれか1つのスロットを書き換えると
+// 全受信者の本文の認証に失敗する
+// 注意: コンテンツ鍵を知っている受信者は、本文ごと作り直したコンテナを作成できる
+//       送信者の真正性が必要な場合はDilithiumなどの署名を併用すること
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use pqcrypto_std::mlkem::EncapsKey;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::aead::{self, AeadCipher, CIPHERTEXT_VERSION, CIPHERTEXT_VERSION_V1, DEFAULT_TAG_SIZE};
+use crate::rng::KeyRng;
+use crate::{encapsulate, KyberDecapsulator};
+
+// コンテナヘッダのサイズ（version || tag_size || count）
+const CONTAINER_HEADER_SIZE: usize = 3;
+
+// 旧形式のコンテナヘッダのサイズ（version || count）
+const CONTAINER_HEADER_SIZE_V1: usize = 2;
+
+// コンテンツ鍵のサイズ
+const CONTENT_KEY_SIZE: usize = 32;
+
+// 1スロットのサイズ（Kyber暗号文 || 封入したコンテンツ鍵）
+const SLOT_SIZE: usize = EncapsKey::CIPHERTEXT_SIZE + CONTENT_KEY_SIZE + DEFAULT_TAG_SIZE;
+
+// 受信者数の上限（countは1バイト）
+const MAX_RECIPIENTS: usize = u8::MAX as usize;
+
+// 鍵の封入と本文のnonce導出のドメイン分離用ラベル
+const WRAP_KEY_LABEL: &[u8] = b"kyber-wasm multi-recipient wrap v1";
+const BODY_NONCE_LABEL: &[u8] = b"kyber-wasm multi-recipient body nonce v1";
+
+// 共有秘密からコンテンツ鍵の封入用の暗号を導出（SHA-256(label || 共有秘密)）
+// 封入鍵はスロットごとに1回しか使わないため、nonceは0固定でよい
+fn wrap_cipher(shared_secret: &[u8]) -> ChaCha20Poly1305 {
+    let mut hasher = Sha256::new();
+    hasher.update(WRAP_KEY_LABEL);
+    hasher.update(shared_secret);
+    let key: Zeroizing<[u8; 32]> = Zeroizing::new(hasher.finalize().into());
+    ChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+}
+
+// ヘッダとすべてのスロットから本文のnonceを導出
+fn body_nonce(header_and_slots: &[u8]) -> [u8; 12] {
+    let mut hasher = Sha256::new();
+    hasher.update(BODY_NONCE_LABEL);
+    hasher.update(header_and_slots);
+    let digest = hasher.finalize();
+    
+    let mut nonce = [0u8; 12];
+    nonce.copy_from_slice(&digest[..12]);
+    nonce
+}
+
+/**
+ * 複数の受信者向けにメッセージを暗号化
+ * 本文は1回だけ暗号化し、コンテンツ鍵を受信者ごとにKyberで封入する
+ * 
+ * @param public_keys 受信者の公開鍵の配列（1〜255個、コンテナ内のスロットはこの順に並ぶ）
+ * @param plaintext 暗号化するメッセージ
+ * @param tag_size 本文の認証タグのサイズ（12〜16バイト、省略時は16）。短くすると偽造への耐性が下がる
+ * @param cipher 本文の暗号（"chacha20-poly1305"・"aes-256-gcm"・"auto"、省略時は"chacha20-poly1305"）
+ *               "auto"はrecommend_aeadで推奨された暗号（未計測の場合はその場で計測する）
+ * @returns コンテナ（kyber_open_multiで復号可能）
+ */
+#[wasm_bindgen]
+pub fn kyber_seal_multi(
+    public_keys: js_sys::Array,
+    plaintext: &[u8],
+    tag_size: Option<usize>,
+    cipher: Option<String>,
+) -> Result<Vec<u8>, JsValue> {
+    use wasm_bindgen::JsCast;
+    
+    let public_keys = public_keys
+        .iter()
+        .enumerate()
+        .map(|(index, key)| {
+            key.dyn_into::<js_sys::Uint8Array>()
+                .map(|key| key.to_vec())
+                .map_err(|_| JsValue::from_str(&format!("Recipient {} is not a Uint8Array", index)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    let cipher = aead::resolve_cipher(cipher.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    seal_multi(&public_keys, plaintext, tag_size.unwrap_or(DEFAULT_TAG_SIZE), cipher)
+}
+
+// コンテナを作成（kyber_seal_multiの本体）
+fn seal_multi(
+    public_keys: &[Vec<u8>],
+    plaintext: &[u8],
+    tag_size: usize,
+    cipher: AeadCipher,
+) -> Result<Vec<u8>, JsValue> {
+    aead::check_tag_size(tag_size).map_err(|e| JsValue::from_str(&e))?;
+    
+    let count = public_keys.len();
+    if count == 0 || count > MAX_RECIPIENTS {
+        return Err(JsValue::from_str(&format!(
+            "Invalid number of recipients: expected 1 to {}, got {}",
+            MAX_RECIPIENTS, count
+        )));
+    }
+    
+    for (index, public_key) in public_keys.iter().enumerate() {
+        if public_key.len() != EncapsKey::BYTE_SIZE {
+            return Err(JsValue::from_str(&format!(
+                "Invalid public key size for recipient {}: expected {}, got {}",
+                index,
+                EncapsKey::BYTE_SIZE,
+                public_key.len()
+            )));
+        }
+    }
+    
+    let mut content_key = Zeroizing::new([0u8; CONTENT_KEY_SIZE]);
+    KeyRng.fill_bytes(content_key.as_mut_slice());
+    
+    let mut container = Vec::with_capacity(CONTAINER_HEADER_SIZE + count * SLOT_SIZE + plaintext.len() + tag_size);
+    container.push(CIPHERTEXT_VERSION);
+    container.push(cipher.tag_byte(tag_size));
+    container.push(count as u8);
+    
+    for public_key in public_keys {
+        let encapsulation = encapsulate(public_key)?;
+        let wrapped_key = wrap_cipher(&encapsulation.shared_secret)
+            .encrypt(Nonce::from_slice(&[0u8; 12]), content_key.as_slice())
+            .map_err(|_| JsValue::from_str("Failed to wrap content key"))?;
+        
+        container.extend_from_slice(&encapsulation.ciphertext);
+        container.extend_from_slice(&wrapped_key);
+    }
+    
+    let nonce = body_nonce(&container);
+    let body = cipher
+        .seal(&content_key, &nonce, plaintext, tag_size)
+        .ok_or_else(|| JsValue::from_str("Failed to encrypt body"))?;
+    container.extend_from_slice(&body);
+    
+    Ok(container)
+}
+
+/**
+ * 複数受信者向けのコンテナを復号
+ * 自分の鍵で開けるスロットを探し、取り出したコンテンツ鍵で本文を復号する
+ * 
+ * @param container kyber_seal_multiで作成したコンテナ
+ * @param private_key 秘密鍵（バイト配列、固定サイズ）
+ * @param public_key 公開鍵（秘密鍵の復元に必要）
+ * @returns 復号したメッセージ
+ */
+#[wasm_bindgen]
+pub fn kyber_open_multi(container: &[u8], private_key: &[u8], public_key: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let decapsulator = KyberDecapsulator::new(private_key, public_key)?;
+    
+    let (header_size, (cipher, tag_size)) = match *container {
+        [CIPHERTEXT_VERSION, tag_byte, _, ..] => (CONTAINER_HEADER_SIZE, AeadCipher::from_tag_byte(tag_byte)),
+        [CIPHERTEXT_VERSION_V1, _, ..] => (CONTAINER_HEADER_SIZE_V1, (AeadCipher::ChaCha20Poly1305, DEFAULT_TAG_SIZE)),
+        [CIPHERTEXT_VERSION, ..] | [CIPHERTEXT_VERSION_V1, ..] | [] => {
+            return Err(JsValue::from_str("Container is too short"));
+        }
+        [version, ..] => return Err(JsValue::from_str(&aead::unsupported_version("container", version))),
+    };
+    aead::check_tag_size(tag_size).map_err(|e| JsValue::from_str(&e))?;
+    
+    let count = container[header_size - 1] as usize;
+    if count == 0 {
+        return Err(JsValue::from_str("Container has no recipients"));
+    }
+    
+    let body_offset = header_size + count * SLOT_SIZE;
+    if container.len() < body_offset + tag_size {
+        return Err(JsValue::from_str("Container is too short"));
+    }
+    
+    // ML-KEMは自分宛てでない暗号文にも擬似乱数の共有秘密を返すため、
+    // コンテンツ鍵の封入を開けたかどうかで自分のスロットを判定する
+    let content_key: Zeroizing<[u8; CONTENT_KEY_SIZE]> = container[header_size..body_offset]
+        .chunks_exact(SLOT_SIZE)
+        .find_map(|slot| {
+            let (ciphertext, wrapped_key) = slot.split_at(EncapsKey::CIPHERTEXT_SIZE);
+            let shared_secret = Zeroizing::new(decapsulator.decapsulate(ciphertext).ok()?);
+            let unwrapped = Zeroizing::new(
+                wrap_cipher(&shared_secret)
+                    .decrypt(Nonce::from_slice(&[0u8; 12]), wrapped_key)
+                    .ok()?,
+            );
+            <[u8; CONTENT_KEY_SIZE]>::try_from(unwrapped.as_slice()).ok().map(Zeroizing::new)
+        })
+        .ok_or_else(|| JsValue::from_str("No recipient slot can be opened with this key"))?;
+    
+    let nonce = body_nonce(&container[..body_offset]);
+    cipher
+        .open(&content_key, &nonce, &container[body_offset..], tag_size)
+        .ok_or_else(|| JsValue::from_str("Container authentication failed: the body or a recipient slot was tampered with"))
+}
@@ -0,0 +1,311 @@
+// ランダムアクセス可能な暗号化ファイルの内部モジュール
+// 平文を固定サイズのチャンクに分け、チャンクごとに別の鍵で認証付き暗号化する
+// リモートに置いた大きなファイルから、必要なチャンクだけをダウンロードして一部の範囲を復号できる
+//
+// ファイル形式:
+//   header := version (1バイト) || tag_size (1バイト) || chunk_size (4バイト、ビッグエンディアン)
+//             || plaintext_length (8バイト、ビッグエンディアン) || Kyber暗号文
+//   chunk  := ChaCha20-Poly1305(チャンク鍵, 0, 平文のチャンク) (暗号文 || タグtag_sizeバイト)
+//   file   := header || chunk*
+// 最後のチャンク以外はすべてchunk_sizeバイトの平文を持つため、i番目のチャンクの位置は
+// header_size + i * (chunk_size + tag_size) で求まる（ヘッダのchunk_sizeとplaintext_lengthが索引になる）
+// チャンク鍵は SHA-256(label || ファイル鍵 || チャンク番号 (8バイト)) で、
+// ファイル鍵は SHA-256(label || 共有秘密 || ヘッダのKyber暗号文より前の部分) から導出する
+// ヘッダを書き換えるとすべてのチャンクの鍵が変わり、チャンクを並べ替えると別の鍵で検証されるため、
+// いずれもチャンクの認証に失敗する。チャンク鍵は1回しか使わないため、nonceは0固定でよい
+
+use chacha20poly1305::aead::KeyInit;
+use chacha20poly1305::{ChaCha20Poly1305, Key};
+use pqcrypto_std::mlkem::EncapsKey;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::aead::{self, CIPHERTEXT_VERSION, DEFAULT_TAG_SIZE};
+use crate::{encapsulate, KyberDecapsulator};
+
+// Kyber暗号文より前のヘッダのサイズ（version || tag_size || chunk_size || plaintext_length）
+const HEADER_PARAMS_SIZE: usize = 14;
+
+// ヘッダのサイズ
+const SEEKABLE_HEADER_SIZE: usize = HEADER_PARAMS_SIZE + EncapsKey::CIPHERTEXT_SIZE;
+
+// チャンクの平文の既定のサイズ（64KiB）
+const DEFAULT_CHUNK_SIZE: usize = 1 << 16;
+
+// チャンクの平文の最大サイズ（16MiB）
+const MAX_CHUNK_SIZE: usize = 1 << 24;
+
+// ファイル鍵とチャンク鍵の導出のドメイン分離用ラベル
+const FILE_KEY_LABEL: &[u8] = b"kyber-wasm seekable file v1";
+const CHUNK_KEY_LABEL: &[u8] = b"kyber-wasm seekable chunk v1";
+
+// ゼロのnonce（チャンク鍵はチャンクごとに異なる）
+const ZERO_NONCE: [u8; 12] = [0u8; 12];
+
+// ヘッダから読み取ったパラメータ
+struct SeekableHeader {
+    tag_size: usize,
+    chunk_size: u64,
+    plaintext_length: u64,
+}
+
+impl SeekableHeader {
+    // ヘッダを解析（Kyber暗号文は含めて渡す）
+    fn parse(header: &[u8]) -> Result<SeekableHeader, JsValue> {
+        if header.len() < SEEKABLE_HEADER_SIZE {
+            return Err(JsValue::from_str(&format!(
+                "Seekable header is too short: expected {} bytes, got {}",
+                SEEKABLE_HEADER_SIZE,
+                header.len()
+            )));
+        }
+        
+        if header[0] != CIPHERTEXT_VERSION {
+            return Err(JsValue::from_str(&format!(
+                "Unsupported seekable version: {} (supported versions: {})",
+                header[0], CIPHERTEXT_VERSION
+            )));
+        }
+        
+        let tag_size = header[1] as usize;
+        aead::check_tag_size(tag_size).map_err(|e| JsValue::from_str(&e))?;
+        
+        let mut chunk_size_bytes = [0u8; 4];
+        chunk_size_bytes.copy_from_slice(&header[2..6]);
+        let chunk_size = u32::from_be_bytes(chunk_size_bytes) as usize;
+        check_chunk_size(chunk_size).map_err(|e| JsValue::from_str(&e))?;
+        
+        let mut length_bytes = [0u8; 8];
+        length_bytes.copy_from_slice(&header[6..HEADER_PARAMS_SIZE]);
+        
+        Ok(SeekableHeader {
+            tag_size,
+            chunk_size: chunk_size as u64,
+            plaintext_length: u64::from_be_bytes(length_bytes),
+        })
+    }
+    
+    // 暗号化したチャンク1つのサイズ（最後のチャンクは短い場合がある）
+    fn sealed_chunk_size(&self) -> u64 {
+        self.chunk_size + self.tag_size as u64
+    }
+    
+    // 平文の範囲を含むチャンク番号の範囲 [first, last)
+    fn chunk_span(&self, start: u64, length: u64) -> Result<(u64, u64), JsValue> {
+        let end = start
+            .checked_add(length)
+            .filter(|&end| end <= self.plaintext_length)
+            .ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "Range is out of bounds: start {} and length {} exceed the plaintext length {}",
+                    start, length, self.plaintext_length
+                ))
+            })?;
+        
+        if length == 0 {
+            return Ok((start / self.chunk_size, start / self.chunk_size));
+        }
+        Ok((start / self.chunk_size, end.div_ceil(self.chunk_size)))
+    }
+    
+    // チャンク番号 first の暗号化ファイル内での開始位置
+    fn sealed_offset(&self, first: u64) -> Result<u64, JsValue> {
+        first
+            .checked_mul(self.sealed_chunk_size())
+            .and_then(|offset| offset.checked_add(SEEKABLE_HEADER_SIZE as u64))
+            .ok_or_else(range_overflow)
+    }
+    
+    // チャンク番号の範囲 [first, last) が暗号化ファイル内で占めるバイト数
+    // plaintext_lengthはヘッダから読んだ値のため、巨大な値でも桁あふれしないよう検査する
+    fn sealed_span_length(&self, first: u64, last: u64) -> Result<u64, JsValue> {
+        if first == last {
+            return Ok(0);
+        }
+        let plaintext_end = last
+            .checked_mul(self.chunk_size)
+            .ok_or_else(range_overflow)?
+            .min(self.plaintext_length);
+        // first * chunk_size <= start < plaintext_end のため、この減算は桁あふれしない
+        let plaintext_span = plaintext_end - first * self.chunk_size;
+        (last - first)
+            .checked_mul(self.tag_size as u64)
+            .and_then(|tags| tags.checked_add(plaintext_span))
+            .ok_or_else(range_overflow)
+    }
+}
+
+fn range_overflow() -> JsValue {
+    JsValue::from_str("Range is out of bounds: the sealed offset overflows a 64-bit integer")
+}
+
+// チャンクのサイズが許容範囲内かを確認
+fn check_chunk_size(chunk_size: usize) -> Result<(), String> {
+    if !(1..=MAX_CHUNK_SIZE).contains(&chunk_size) {
+        return Err(format!(
+            "Invalid chunk size: expected 1 to {} bytes, got {}",
+            MAX_CHUNK_SIZE, chunk_size
+        ));
+    }
+    Ok(())
+}
+
+// 共有秘密とヘッダのパラメータからファイル鍵を導出
+fn file_key(shared_secret: &[u8], header_params: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    hasher.update(FILE_KEY_LABEL);
+    hasher.update(shared_secret);
+    hasher.update(header_params);
+    Zeroizing::new(hasher.finalize().into())
+}
+
+// ファイル鍵とチャンク番号からチャンクの暗号を導出
+fn chunk_cipher(file_key: &[u8; 32], index: u64) -> ChaCha20Poly1305 {
+    let mut hasher = Sha256::new();
+    hasher.update(CHUNK_KEY_LABEL);
+    hasher.update(file_key);
+    hasher.update(index.to_be_bytes());
+    let key: Zeroizing<[u8; 32]> = Zeroizing::new(hasher.finalize().into());
+    ChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+}
+
+/**
+ * ランダムアクセス可能な形式でファイルを暗号化
+ * 平文を固定サイズのチャンクに分け、チャンクごとに別の鍵で認証付き暗号化する
+ * 
+ * @param public_key 受信者の公開鍵（バイト配列、固定サイズ）
+ * @param plaintext 暗号化するファイルの内容
+ * @param chunk_size チャンクの平文のサイズ（1バイト〜16MiB、省略時は64KiB）
+ * @param tag_size チャンクごとの認証タグのサイズ（12〜16バイト、省略時は16）。短くすると偽造への耐性が下がる
+ * @returns 暗号化ファイル（ヘッダ || チャンク*、kyber_decrypt_rangeで一部を復号可能）
+ */
+#[wasm_bindgen]
+pub fn kyber_seal_seekable(
+    public_key: &[u8],
+    plaintext: &[u8],
+    chunk_size: Option<usize>,
+    tag_size: Option<usize>,
+) -> Result<Vec<u8>, JsValue> {
+    let chunk_size = chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+    let tag_size = tag_size.unwrap_or(DEFAULT_TAG_SIZE);
+    check_chunk_size(chunk_size).map_err(|e| JsValue::from_str(&e))?;
+    aead::check_tag_size(tag_size).map_err(|e| JsValue::from_str(&e))?;
+    
+    if public_key.len() != EncapsKey::BYTE_SIZE {
+        return Err(JsValue::from_str(&format!(
+            "Invalid public key size: expected {}, got {}",
+            EncapsKey::BYTE_SIZE,
+            public_key.len()
+        )));
+    }
+    
+    let encapsulation = encapsulate(public_key)?;
+    
+    let chunk_count = plaintext.len().div_ceil(chunk_size);
+    let mut sealed = Vec::with_capacity(SEEKABLE_HEADER_SIZE + plaintext.len() + chunk_count * tag_size);
+    sealed.push(CIPHERTEXT_VERSION);
+    sealed.push(tag_size as u8);
+    sealed.extend_from_slice(&(chunk_size as u32).to_be_bytes());
+    sealed.extend_from_slice(&(plaintext.len() as u64).to_be_bytes());
+    
+    let file_key = file_key(&encapsulation.shared_secret, &sealed);
+    sealed.extend_from_slice(&encapsulation.ciphertext);
+    
+    for (index, chunk) in plaintext.chunks(chunk_size).enumerate() {
+        let sealed_chunk = aead::seal(&chunk_cipher(&file_key, index as u64), &ZERO_NONCE, chunk, tag_size)
+            .ok_or_else(|| JsValue::from_str("Failed to encrypt chunk"))?;
+        sealed.extend_from_slice(&sealed_chunk);
+    }
+    
+    Ok(sealed)
+}
+
+/**
+ * 暗号化ファイルのヘッダのサイズを取得
+ * ファイルの先頭からこのバイト数をダウンロードすれば、kyber_seekable_chunk_rangeとkyber_decrypt_rangeに渡せる
+ * 
+ * @returns ヘッダのサイズ（バイト）
+ */
+#[wasm_bindgen]
+pub fn kyber_seekable_header_size() -> usize {
+    SEEKABLE_HEADER_SIZE
+}
+
+/**
+ * 平文の範囲の復号に必要な、暗号化ファイル内のバイト範囲を取得
+ * 返された範囲をダウンロードしてkyber_decrypt_rangeに渡す
+ * 
+ * @param sealed_header 暗号化ファイルのヘッダ（先頭kyber_seekable_header_sizeバイト）
+ * @param start 平文の範囲の開始位置
+ * @param length 平文の範囲の長さ
+ * @returns [暗号化ファイル内の開始位置, バイト数]
+ */
+#[wasm_bindgen]
+pub fn kyber_seekable_chunk_range(sealed_header: &[u8], start: u64, length: u64) -> Result<Vec<u64>, JsValue> {
+    let header = SeekableHeader::parse(sealed_header)?;
+    let (first, last) = header.chunk_span(start, length)?;
+    
+    Ok(vec![
+        header.sealed_offset(first)?,
+        header.sealed_span_length(first, last)?,
+    ])
+}
+
+/**
+ * 暗号化ファイルの一部の範囲だけを復号
+ * 範囲を含むチャンクだけを検証・復号するため、ファイル全体をダウンロードする必要がない
+ * 
+ * @param sealed_header 暗号化ファイルのヘッダ（先頭kyber_seekable_header_sizeバイト）
+ * @param chunk_bytes kyber_seekable_chunk_rangeが返した範囲の暗号化ファイルのバイト列
+ * @param start 平文の範囲の開始位置
+ * @param length 平文の範囲の長さ
+ * @param private_key 秘密鍵（バイト配列、固定サイズ）
+ * @param public_key 公開鍵（秘密鍵の復元に必要）
+ * @returns 復号した平文の範囲
+ */
+#[wasm_bindgen]
+pub fn kyber_decrypt_range(
+    sealed_header: &[u8],
+    chunk_bytes: &[u8],
+    start: u64,
+    length: u64,
+    private_key: &[u8],
+    public_key: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let header = SeekableHeader::parse(sealed_header)?;
+    let (first, last) = header.chunk_span(start, length)?;
+    
+    let expected_length = header.sealed_span_length(first, last)?;
+    if chunk_bytes.len() as u64 != expected_length {
+        return Err(JsValue::from_str(&format!(
+            "Invalid chunk bytes length: expected {}, got {}",
+            expected_length,
+            chunk_bytes.len()
+        )));
+    }
+    
+    if first == last {
+        return Ok(Vec::new());
+    }
+    
+    let decapsulator = KyberDecapsulator::new(private_key, public_key)?;
+    let shared_secret = Zeroizing::new(decapsulator.decapsulate(&sealed_header[HEADER_PARAMS_SIZE..SEEKABLE_HEADER_SIZE])?);
+    let file_key = file_key(&shared_secret, &sealed_header[..HEADER_PARAMS_SIZE]);
+    
+    let mut plaintext = Vec::with_capacity(chunk_bytes.len());
+    for (offset, sealed_chunk) in chunk_bytes.chunks(header.sealed_chunk_size() as usize).enumerate() {
+        let index = first + offset as u64;
+        let chunk = aead::open(&chunk_cipher(&file_key, index), &ZERO_NONCE, sealed_chunk, header.tag_size)
+            .ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "Chunk {} authentication failed: the file was tampered with or the keys do not match",
+                    index
+                ))
+            })?;
+        plaintext.extend_from_slice(&chunk);
+    }
+    
+    let skip = (start - first * header.chunk_size) as usize;
+    Ok(plaintext[skip..skip + length as usize].to_vec())
+}
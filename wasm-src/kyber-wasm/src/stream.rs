@@ -0,0 +1,322 @@
+// ストリーム暗号化の内部モジュール
+// Kyberで共有秘密を封入し、ChaCha20-Poly1305でチャンクごとに認証付き暗号化する（STREAM構成）
+// JSのTransformStreamから stream_push / stream_finish を呼び出し、ファイル全体をバッファせずに処理する
+//
+// ストリーム形式:
+//   header := version (1バイト) || tag_size (1バイト) || Kyber暗号文
+//   frame  := length (4バイト、ビッグエンディアン) || flag (1バイト) || 暗号化チャンク (平文 || タグtag_sizeバイト)
+//   stream := header || frame* （最後のframeのみflag = 1）
+// バージョン1のヘッダにはtag_sizeがなく、タグは常に16バイト（復号のみ対応）
+// nonceは「チャンク番号 (8バイト) || 0 (3バイト) || flag (1バイト)」で、
+// チャンクの並べ替え・削除・末尾の切り詰めを検出できる
+
+use chacha20poly1305::aead::KeyInit;
+use chacha20poly1305::{ChaCha20Poly1305, Key};
+use pqcrypto_std::mlkem::EncapsKey;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::aead::{self, CIPHERTEXT_VERSION, CIPHERTEXT_VERSION_V1, DEFAULT_TAG_SIZE};
+use crate::{encapsulate, KyberDecapsulator};
+
+// ストリームヘッダのサイズ（version || tag_size || Kyber暗号文）
+const STREAM_HEADER_SIZE: usize = 2 + EncapsKey::CIPHERTEXT_SIZE;
+
+// 旧形式のストリームヘッダのサイズ（version || Kyber暗号文）
+const STREAM_HEADER_SIZE_V1: usize = 1 + EncapsKey::CIPHERTEXT_SIZE;
+
+// フレームヘッダのサイズ（length (4バイト) || flag (1バイト)）
+const FRAME_HEADER_SIZE: usize = 5;
+
+// 1チャンクの平文の最大サイズ（16MiB）
+const MAX_CHUNK_SIZE: usize = 1 << 24;
+
+// フレームの種類
+const FLAG_CONTINUE: u8 = 0;
+const FLAG_FINAL: u8 = 1;
+
+// ストリーム鍵導出のドメイン分離用ラベル
+const STREAM_KEY_LABEL: &[u8] = b"kyber-wasm stream v1";
+
+// 共有秘密からストリーム鍵を導出（SHA-256(label || 共有秘密)）
+fn stream_cipher(shared_secret: &[u8]) -> ChaCha20Poly1305 {
+    let mut hasher = Sha256::new();
+    hasher.update(STREAM_KEY_LABEL);
+    hasher.update(shared_secret);
+    let key: Zeroizing<[u8; 32]> = Zeroizing::new(hasher.finalize().into());
+    ChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+}
+
+// チャンク番号とflagからnonceを生成
+fn stream_nonce(counter: u64, flag: u8) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..8].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = flag;
+    nonce
+}
+
+/**
+ * ストリーム暗号化器
+ * stream_pushとstream_finishの出力を順に連結したものが暗号化ストリームになる
+ * 複製すると同じ鍵とnonceで別のチャンクを暗号化してしまうため、clone()は提供しない
+ */
+#[wasm_bindgen]
+pub struct KyberStreamEncryptor {
+    cipher: ChaCha20Poly1305,
+    tag_size: usize,
+    // まだ出力していないストリームヘッダ
+    pending_header: Option<Vec<u8>>,
+    counter: u64,
+    finished: bool,
+}
+
+#[wasm_bindgen]
+impl KyberStreamEncryptor {
+    /**
+     * 公開鍵で共有秘密をカプセル化し、ストリーム暗号化を開始
+     * 
+     * @param public_key 受信者の公開鍵（バイト配列、固定サイズ）
+     * @param tag_size チャンクごとの認証タグのサイズ（12〜16バイト、省略時は16）。短くすると偽造への耐性が下がる
+     * @returns ストリーム暗号化器
+     */
+    pub fn stream_init(public_key: &[u8], tag_size: Option<usize>) -> Result<KyberStreamEncryptor, JsValue> {
+        let tag_size = tag_size.unwrap_or(DEFAULT_TAG_SIZE);
+        aead::check_tag_size(tag_size).map_err(|e| JsValue::from_str(&e))?;
+        
+        if public_key.len() != EncapsKey::BYTE_SIZE {
+            return Err(JsValue::from_str(&format!(
+                "Invalid public key size: expected {}, got {}",
+                EncapsKey::BYTE_SIZE,
+                public_key.len()
+            )));
+        }
+        
+        let encapsulation = encapsulate(public_key)?;
+        
+        let mut header = Vec::with_capacity(STREAM_HEADER_SIZE);
+        header.push(CIPHERTEXT_VERSION);
+        header.push(tag_size as u8);
+        header.extend_from_slice(&encapsulation.ciphertext);
+        
+        Ok(KyberStreamEncryptor {
+            cipher: stream_cipher(&encapsulation.shared_secret),
+            tag_size,
+            pending_header: Some(header),
+            counter: 0,
+            finished: false,
+        })
+    }
+
+    /**
+     * チャンクを暗号化
+     * 最初の呼び出しではストリームヘッダが先頭に付く
+     * 
+     * @param chunk 平文のチャンク（最大16MiB）
+     * @returns 認証付きで暗号化したフレーム
+     */
+    pub fn stream_push(&mut self, chunk: &[u8]) -> Result<Vec<u8>, JsValue> {
+        self.seal(chunk, FLAG_CONTINUE)
+    }
+
+    /**
+     * ストリームを終了
+     * 終端を示す空のフレームを出力し、以降のstream_pushは拒否する
+     * 
+     * @returns 終端フレーム
+     */
+    pub fn stream_finish(&mut self) -> Result<Vec<u8>, JsValue> {
+        let frame = self.seal(&[], FLAG_FINAL)?;
+        self.finished = true;
+        Ok(frame)
+    }
+}
+
+impl KyberStreamEncryptor {
+    // チャンクを暗号化してフレームを生成
+    fn seal(&mut self, chunk: &[u8], flag: u8) -> Result<Vec<u8>, JsValue> {
+        if self.finished {
+            return Err(JsValue::from_str("Stream is already finished"));
+        }
+        
+        if chunk.len() > MAX_CHUNK_SIZE {
+            return Err(JsValue::from_str(&format!(
+                "Chunk is too large: maximum {} bytes, got {}",
+                MAX_CHUNK_SIZE,
+                chunk.len()
+            )));
+        }
+        
+        let nonce = stream_nonce(self.counter, flag);
+        let sealed = aead::seal(&self.cipher, &nonce, chunk, self.tag_size)
+            .ok_or_else(|| JsValue::from_str("Failed to encrypt chunk"))?;
+        
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| JsValue::from_str("Too many chunks in stream"))?;
+        
+        let mut output = self.pending_header.take().unwrap_or_default();
+        output.extend_from_slice(&(sealed.len() as u32).to_be_bytes());
+        output.push(flag);
+        output.extend_from_slice(&sealed);
+        
+        Ok(output)
+    }
+}
+
+/**
+ * ストリーム復号器
+ * 暗号化ストリームを任意の境界で分割して stream_push に渡せる
+ */
+#[wasm_bindgen]
+pub struct KyberStreamDecryptor {
+    decapsulator: KyberDecapsulator,
+    // ストリームヘッダを読むまではNone
+    cipher: Option<ChaCha20Poly1305>,
+    tag_size: usize,
+    // まだ完全なフレームになっていない入力
+    buffer: Vec<u8>,
+    counter: u64,
+    finished: bool,
+    failed: bool,
+}
+
+#[wasm_bindgen]
+impl KyberStreamDecryptor {
+    /**
+     * ストリーム復号を開始
+     * 
+     * @param private_key 秘密鍵（バイト配列、固定サイズ）
+     * @param public_key 公開鍵（秘密鍵の復元に必要）
+     * @returns ストリーム復号器
+     */
+    pub fn stream_init(private_key: &[u8], public_key: &[u8]) -> Result<KyberStreamDecryptor, JsValue> {
+        Ok(KyberStreamDecryptor {
+            decapsulator: KyberDecapsulator::new(private_key, public_key)?,
+            cipher: None,
+            tag_size: DEFAULT_TAG_SIZE,
+            buffer: Vec::new(),
+            counter: 0,
+            finished: false,
+            failed: false,
+        })
+    }
+
+    /**
+     * 暗号化ストリームの一部を入力し、復号できた平文を返す
+     * フレームが途中で分割されている場合は、残りが届くまで保持する
+     * 
+     * @param data 暗号化ストリームの一部
+     * @returns 完全なフレームから復号した平文（まだない場合は空）
+     */
+    pub fn stream_push(&mut self, data: &[u8]) -> Result<Vec<u8>, JsValue> {
+        if self.failed {
+            return Err(JsValue::from_str("Stream decryption has already failed"));
+        }
+        
+        self.buffer.extend_from_slice(data);
+        
+        let result = self.open_frames();
+        if result.is_err() {
+            // 改ざんを検出した後は、それ以降の入力を一切受け付けない
+            self.failed = true;
+            self.buffer.clear();
+        }
+        result
+    }
+
+    /**
+     * ストリームの終了を確認
+     * 終端フレームを受け取っていない場合（切り詰め）はエラーを返す
+     * 
+     * @returns 残りの平文（常に空）
+     */
+    pub fn stream_finish(&mut self) -> Result<Vec<u8>, JsValue> {
+        if self.failed {
+            return Err(JsValue::from_str("Stream decryption has already failed"));
+        }
+        
+        if !self.finished {
+            return Err(JsValue::from_str("Truncated stream: final chunk is missing"));
+        }
+        
+        Ok(Vec::new())
+    }
+}
+
+impl KyberStreamDecryptor {
+    // バッファ内の完全なフレームをすべて復号
+    fn open_frames(&mut self) -> Result<Vec<u8>, JsValue> {
+        let mut plaintext = Vec::new();
+        
+        if self.cipher.is_none() {
+            let Some(&version) = self.buffer.first() else {
+                return Ok(plaintext);
+            };
+            
+            let header_size = match version {
+                CIPHERTEXT_VERSION => STREAM_HEADER_SIZE,
+                CIPHERTEXT_VERSION_V1 => STREAM_HEADER_SIZE_V1,
+                _ => return Err(JsValue::from_str(&aead::unsupported_version("stream", version))),
+            };
+            if self.buffer.len() < header_size {
+                return Ok(plaintext);
+            }
+            
+            if version == CIPHERTEXT_VERSION {
+                self.tag_size = self.buffer[1] as usize;
+                aead::check_tag_size(self.tag_size).map_err(|e| JsValue::from_str(&e))?;
+            }
+            
+            let ciphertext = &self.buffer[header_size - EncapsKey::CIPHERTEXT_SIZE..header_size];
+            let shared_secret = Zeroizing::new(self.decapsulator.decapsulate(ciphertext)?);
+            self.cipher = Some(stream_cipher(&shared_secret));
+            self.buffer.drain(..header_size);
+        }
+        
+        let mut offset = 0;
+        while self.buffer.len() - offset >= FRAME_HEADER_SIZE {
+            if self.finished {
+                return Err(JsValue::from_str("Unexpected data after the final chunk"));
+            }
+            
+            let mut length_bytes = [0u8; 4];
+            length_bytes.copy_from_slice(&self.buffer[offset..offset + 4]);
+            let length = u32::from_be_bytes(length_bytes) as usize;
+            let flag = self.buffer[offset + 4];
+            
+            if !(self.tag_size..=MAX_CHUNK_SIZE + self.tag_size).contains(&length) {
+                return Err(JsValue::from_str(&format!("Invalid chunk length: {}", length)));
+            }
+            if flag != FLAG_CONTINUE && flag != FLAG_FINAL {
+                return Err(JsValue::from_str(&format!("Invalid chunk flag: {}", flag)));
+            }
+            
+            let frame_end = offset + FRAME_HEADER_SIZE + length;
+            if self.buffer.len() < frame_end {
+                break;
+            }
+            
+            let nonce = stream_nonce(self.counter, flag);
+            let cipher = self.cipher.as_ref().ok_or_else(|| JsValue::from_str("Stream header is missing"))?;
+            let chunk = aead::open(cipher, &nonce, &self.buffer[offset + FRAME_HEADER_SIZE..frame_end], self.tag_size)
+                .ok_or_else(|| JsValue::from_str("Chunk authentication failed: the stream was tampered with or the keys do not match"))?;
+            
+            plaintext.extend_from_slice(&chunk);
+            self.counter = self
+                .counter
+                .checked_add(1)
+                .ok_or_else(|| JsValue::from_str("Too many chunks in stream"))?;
+            self.finished = flag == FLAG_FINAL;
+            offset = frame_end;
+        }
+        
+        if self.finished && self.buffer.len() > offset {
+            return Err(JsValue::from_str("Unexpected data after the final chunk"));
+        }
+        
+        self.buffer.drain(..offset);
+        Ok(plaintext)
+    }
+}
@@ -0,0 +1,52 @@
+// セキュリティ関連イベントの通知モジュール
+// 復号失敗や点の検証失敗などを、アプリが登録したコールバックに通知する
+// 通知内容には鍵や平文などの秘密情報を含めない
+//
+// abe-wasmとibe-wasmの両方が#[path]でこのファイルを取り込む（共有クレートは作らない）
+
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::prelude::*;
+
+// js_sys::FunctionはSendではなく、登録したスレッド（インスタンス）でしか呼び出せないため、
+// コールバックはスレッドごとに保持する（Web Workerごとに個別に登録する必要がある）
+thread_local! {
+    static EVENT_CALLBACK: RefCell<Option<js_sys::Function>> = const { RefCell::new(None) };
+    static MUTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// セキュリティ関連イベントのコールバックを設定
+/// コールバックは {event, detail} 形式のオブジェクトを引数に呼び出される
+/// undefinedまたはnullを渡すと解除する
+#[wasm_bindgen]
+pub fn set_event_callback(callback: Option<js_sys::Function>) {
+    EVENT_CALLBACK.with(|cb| *cb.borrow_mut() = callback);
+}
+
+/// イベントを通知
+/// detailにはエラー理由などの説明のみを渡し、秘密情報を含めないこと
+pub fn emit_event(event: &str, detail: &str) {
+    if MUTED.with(Cell::get) {
+        return;
+    }
+    
+    // コールバック内から再度登録・解除されても借用が衝突しないよう、複製してから呼び出す
+    let callback = EVENT_CALLBACK.with(|cb| cb.borrow().clone());
+    
+    if let Some(callback) = callback {
+        let object = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&object, &"event".into(), &event.into());
+        let _ = js_sys::Reflect::set(&object, &"detail".into(), &detail.into());
+        
+        // コールバック内の例外で暗号処理が中断されないよう、結果は無視する
+        let _ = callback.call1(&JsValue::NULL, &object);
+    }
+}
+
+/// クロージャの実行中はイベントを通知しない
+/// 失敗することが前提の試行（try_decryptなど）で、コールバックに通知が大量に届かないようにする
+pub fn muted<T>(f: impl FnOnce() -> T) -> T {
+    let previous = MUTED.with(|muted| muted.replace(true));
+    let result = f();
+    MUTED.with(|muted| muted.set(previous));
+    result
+}
@@ -0,0 +1,218 @@
+// ポスト量子ハイブリッド暗号化の内部モジュール
+// BN254上のペアリングは量子計算機に対して安全ではないため、移行期間向けに
+// ペアリング層の鍵とML-KEMの共有秘密の両方からメッセージの対称鍵（DEM鍵）を導出する
+// ペアリング層だけが破られても、ML-KEMの秘密鍵がなければメッセージは復号できない
+//
+// 暗号文の形式:
+//   hybrid  := header || body
+//   header  := version (1バイト) || KEM暗号文 || len(pairing) (4バイト) || pairing
+//   pairing := ペアリング層の暗号文（ランダムな32バイトの鍵シェアをCP-ABEまたはIBEで暗号化したもの）
+//   body    := nonce (12バイト) || AES-256-GCM(dem_key, メッセージ, 追加認証データ = header) || タグ (16バイト)
+// dem_keyは SHA-256(label || 鍵シェア || 共有秘密 || KEM暗号文 || pairing) で導出する
+//
+// abe-wasmとibe-wasmの両方が#[path]でこのファイルを取り込む（共有クレートは作らない）
+// クレートごとに異なるもの（ラベルのクレート名、エラーメッセージの文言）はここに書かず、
+// CARGO_PKG_NAMEと各クレートのlib.rsのHybridErrorの表示で区別する
+
+use miracl_core::gcm;
+use pqcrypto_std::mlkem::{DecapsKey, EncapsKey};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::{RngFailure, WasmRAND};
+
+// ハイブリッド暗号文の形式のバージョン
+// バージョン1（SHA-256の鍵ストリームとHMAC-SHA256）の暗号文は復号できない
+const HYBRID_VERSION: u8 = 2;
+
+// ペアリング層で暗号化する鍵シェアのサイズ
+pub const KEY_SHARE_SIZE: usize = 32;
+
+// AES-256-GCMのnonceと認証タグのサイズ
+pub const AEAD_NONCE_SIZE: usize = 12;
+pub const AEAD_TAG_SIZE: usize = 16;
+
+// ペアリング層の暗号文の長さフィールドのサイズ
+const LENGTH_SIZE: usize = 4;
+
+// DEM鍵の導出のドメイン分離用ラベル（クレートごとに異なる）
+const DEM_KEY_LABEL: &[u8] = concat!(env!("CARGO_PKG_NAME"), " hybrid dem v2").as_bytes();
+
+// ハイブリッド暗号文の各部分
+pub struct HybridParts<'a> {
+    pub header: &'a [u8],
+    pub kem_ciphertext: &'a [u8],
+    pub pairing_ciphertext: &'a [u8],
+    pub body: &'a [u8],
+}
+
+// ハイブリッド暗号化のエラー
+// メッセージの文言は各クレートのlib.rsのDisplayの実装で決める
+#[derive(Debug)]
+pub enum HybridError {
+    InvalidPublicKeySize { expected: usize, actual: usize },
+    InvalidPrivateKeySize { expected: usize, actual: usize },
+    Empty,
+    UnsupportedVersion { expected: u8, actual: u8 },
+    TooShort,
+    AuthenticationFailed,
+}
+
+// ML-KEMの公開鍵のサイズを確認
+pub fn check_kem_public_key(public_key: &[u8]) -> Result<(), HybridError> {
+    if public_key.len() != EncapsKey::BYTE_SIZE {
+        return Err(HybridError::InvalidPublicKeySize {
+            expected: EncapsKey::BYTE_SIZE,
+            actual: public_key.len(),
+        });
+    }
+    Ok(())
+}
+
+// ペアリング層で暗号化するランダムな鍵シェアを生成
+pub fn random_key_share() -> Result<Zeroizing<[u8; KEY_SHARE_SIZE]>, RngFailure> {
+    let mut rng = WasmRAND::new();
+    let mut share = Zeroizing::new([0u8; KEY_SHARE_SIZE]);
+    rand_core::RngCore::fill_bytes(&mut rng, share.as_mut_slice());
+    if rng.failed() || share.iter().all(|&b| b == 0) {
+        return Err(RngFailure);
+    }
+    Ok(share)
+}
+
+// 鍵シェア・共有秘密・KEM暗号文・ペアリング層の暗号文からDEM鍵を導出
+fn derive_key(key_share: &[u8], shared_secret: &[u8], kem_ciphertext: &[u8], pairing_ciphertext: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    hasher.update(DEM_KEY_LABEL);
+    hasher.update(key_share);
+    hasher.update(shared_secret);
+    hasher.update(kem_ciphertext);
+    hasher.update(pairing_ciphertext);
+    Zeroizing::new(hasher.finalize().into())
+}
+
+// メッセージをAES-256-GCMで暗号化し、nonce (12バイト) || 暗号文 || タグ (16バイト) を返す（nonceはランダムに選ぶ）
+pub fn seal_aead(key: &[u8; 32], aad: &[u8], message: &[u8]) -> Result<Vec<u8>, RngFailure> {
+    let mut rng = WasmRAND::new();
+    let mut sealed = vec![0u8; AEAD_NONCE_SIZE + message.len() + AEAD_TAG_SIZE];
+    let (nonce, rest) = sealed.split_at_mut(AEAD_NONCE_SIZE);
+    rand_core::RngCore::fill_bytes(&mut rng, nonce);
+    if rng.failed() {
+        return Err(RngFailure);
+    }
+    let (body, tag) = rest.split_at_mut(message.len());
+    gcm::encrypt(body, tag, key, nonce, aad, message);
+    Ok(sealed)
+}
+
+// seal_aeadの出力を復号する
+// 短すぎる場合や認証タグが一致しない場合（改ざん・鍵の不一致）はNoneを返す
+pub fn open_aead(key: &[u8; 32], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+    let body_len = sealed.len().checked_sub(AEAD_NONCE_SIZE + AEAD_TAG_SIZE)?;
+    let (nonce, rest) = sealed.split_at(AEAD_NONCE_SIZE);
+    let (body, tag) = rest.split_at(body_len);
+    
+    let mut message = vec![0u8; body_len];
+    let mut expected_tag = [0u8; AEAD_TAG_SIZE];
+    gcm::decrypt(&mut message, &mut expected_tag, key, nonce, aad, body);
+    constant_time_eq(&expected_tag, tag).then_some(message)
+}
+
+// タイミングで一致した長さが漏れないよう、全バイトを比較する
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ハイブリッド暗号文を作成
+// pairing_ciphertextはkey_shareをペアリング層で暗号化したもの
+pub fn seal(
+    kem_public_key: &[u8],
+    key_share: &[u8; KEY_SHARE_SIZE],
+    pairing_ciphertext: &[u8],
+    message: &[u8],
+) -> Result<Vec<u8>, RngFailure> {
+    let mut pk_array = [0u8; EncapsKey::BYTE_SIZE];
+    pk_array.copy_from_slice(kem_public_key);
+    let ek = EncapsKey::from_bytes(&pk_array);
+    
+    let mut rng = WasmRAND::new();
+    let mut kem_ciphertext = [0u8; EncapsKey::CIPHERTEXT_SIZE];
+    let mut shared_secret = Zeroizing::new([0u8; 32]);
+    ek.encaps(&mut kem_ciphertext, &mut shared_secret, &mut rng);
+    if rng.failed() {
+        return Err(RngFailure);
+    }
+    
+    let dem_key = derive_key(key_share, shared_secret.as_slice(), &kem_ciphertext, pairing_ciphertext);
+    
+    let mut ciphertext = Vec::with_capacity(
+        1 + kem_ciphertext.len() + LENGTH_SIZE + pairing_ciphertext.len() + AEAD_NONCE_SIZE + message.len() + AEAD_TAG_SIZE,
+    );
+    ciphertext.push(HYBRID_VERSION);
+    ciphertext.extend_from_slice(&kem_ciphertext);
+    ciphertext.extend_from_slice(&(pairing_ciphertext.len() as u32).to_be_bytes());
+    ciphertext.extend_from_slice(pairing_ciphertext);
+    
+    let body = seal_aead(&dem_key, &ciphertext, message)?;
+    ciphertext.extend_from_slice(&body);
+    
+    Ok(ciphertext)
+}
+
+// ハイブリッド暗号文をヘッダ（KEM暗号文とペアリング層の暗号文）と本文に分ける
+pub fn parse(ciphertext: &[u8]) -> Result<HybridParts<'_>, HybridError> {
+    let (&version, rest) = ciphertext.split_first().ok_or(HybridError::Empty)?;
+    if version != HYBRID_VERSION {
+        return Err(HybridError::UnsupportedVersion {
+            expected: HYBRID_VERSION,
+            actual: version,
+        });
+    }
+    
+    let (kem_ciphertext, rest) = rest
+        .split_at_checked(EncapsKey::CIPHERTEXT_SIZE)
+        .ok_or(HybridError::TooShort)?;
+    let (length, rest) = rest.split_first_chunk::<LENGTH_SIZE>().ok_or(HybridError::TooShort)?;
+    let pairing_length = u32::from_be_bytes(*length) as usize;
+    let (pairing_ciphertext, body) = rest.split_at_checked(pairing_length).ok_or(HybridError::TooShort)?;
+    if body.len() < AEAD_NONCE_SIZE + AEAD_TAG_SIZE {
+        return Err(HybridError::TooShort);
+    }
+    
+    Ok(HybridParts {
+        header: &ciphertext[..ciphertext.len() - body.len()],
+        kem_ciphertext,
+        pairing_ciphertext,
+        body,
+    })
+}
+
+// ハイブリッド暗号文を復号
+// key_shareはペアリング層の暗号文を復号して得た鍵シェア
+// ML-KEMは誤った秘密鍵でも擬似乱数の共有秘密を返すため、誤りは認証タグの不一致として検出される
+pub fn open(
+    ciphertext: &[u8],
+    key_share: &[u8],
+    kem_private_key: &[u8],
+    kem_public_key: &[u8],
+) -> Result<Vec<u8>, HybridError> {
+    let parts = parse(ciphertext)?;
+    
+    let sk_array: Zeroizing<[u8; DecapsKey::BYTE_SIZE]> = Zeroizing::new(kem_private_key.try_into().map_err(|_| {
+        HybridError::InvalidPrivateKeySize {
+            expected: DecapsKey::BYTE_SIZE,
+            actual: kem_private_key.len(),
+        }
+    })?);
+    check_kem_public_key(kem_public_key)?;
+    let mut pk_array = [0u8; EncapsKey::BYTE_SIZE];
+    pk_array.copy_from_slice(kem_public_key);
+    let mut ct_array = [0u8; EncapsKey::CIPHERTEXT_SIZE];
+    ct_array.copy_from_slice(parts.kem_ciphertext);
+    
+    let mut shared_secret = Zeroizing::new([0u8; 32]);
+    DecapsKey::from_bytes(&sk_array).decaps(&mut shared_secret, &EncapsKey::from_bytes(&pk_array), &ct_array);
+    
+    let dem_key = derive_key(key_share, shared_secret.as_slice(), parts.kem_ciphertext, parts.pairing_ciphertext);
+    open_aead(&dem_key, parts.header, parts.body).ok_or(HybridError::AuthenticationFailed)
+}
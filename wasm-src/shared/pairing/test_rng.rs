@@ -0,0 +1,56 @@
+// テスト専用の乱数生成モード（test-rng featureを有効にしたビルドでのみコンパイルされる）
+// WasmRANDのエントロピー源を差し替え、乱数生成が失敗・劣化した場合に
+// 弱い鍵を生成せずにエラーとなることを確認できるようにする
+//
+// abe-wasmとibe-wasmの両方が#[path]でこのファイルを取り込む（共有クレートは作らない）
+
+use getrandom::getrandom;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+
+/// 乱数生成モード
+#[derive(Clone)]
+pub enum TestRngMode {
+    /// OSの乱数（通常の動作）
+    Os,
+    /// すべて0のバイト列を返す（壊れたエントロピー源）
+    ZeroBytes,
+    /// 乱数の取得に失敗する
+    Fail,
+    /// シードから決定的なバイト列を生成する（SHA-256(seed || counter)）
+    Deterministic { seed: Vec<u8>, counter: u64 },
+}
+
+thread_local! {
+    static MODE: RefCell<TestRngMode> = const { RefCell::new(TestRngMode::Os) };
+}
+
+/// 乱数生成モードを設定
+/// Deterministicのカウンタは設定のたびに0から始まる
+pub fn set_mode(mode: TestRngMode) {
+    MODE.with(|current| *current.borrow_mut() = mode);
+}
+
+/// 現在のモードでバッファを埋める
+/// 乱数の取得に失敗した場合はfalseを返す
+pub fn fill(buffer: &mut [u8]) -> bool {
+    MODE.with(|mode| match &mut *mode.borrow_mut() {
+        TestRngMode::Os => getrandom(buffer).is_ok(),
+        TestRngMode::ZeroBytes => {
+            buffer.fill(0);
+            true
+        }
+        TestRngMode::Fail => false,
+        TestRngMode::Deterministic { seed, counter } => {
+            for chunk in buffer.chunks_mut(32) {
+                let block = Sha256::new()
+                    .chain_update(&*seed)
+                    .chain_update(counter.to_be_bytes())
+                    .finalize();
+                chunk.copy_from_slice(&block[..chunk.len()]);
+                *counter += 1;
+            }
+            true
+        }
+    })
+}
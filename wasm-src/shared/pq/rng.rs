@@ -0,0 +1,110 @@
+// 鍵生成・カプセル化・署名に使う乱数生成器の内部モジュール
+// 通常はOSの乱数（OsRng）を使い、test-rng featureを有効にしたビルドでは
+// set_test_rng_modeで切り替えたテスト用のエントロピー源を使う
+//
+// kyber-wasmとdilithium-wasmの両方が#[path]でこのファイルを取り込む（共有クレートは作らない）
+
+use rand::{CryptoRng, Error, RngCore};
+
+// クレート内で共通に使う乱数生成器
+pub struct KeyRng;
+
+impl RngCore for KeyRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    #[cfg(not(feature = "test-rng"))]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand::rngs::OsRng.fill_bytes(dest);
+    }
+
+    #[cfg(feature = "test-rng")]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        crate::test_rng::fill(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for KeyRng {}
+
+// シードのバイト列をそのまま順に返す乱数生成器
+// シードからの決定的な鍵生成（generate_keypair_from_seed）と、kyber-wasmの決定的なカプセル化（encapsulate_deterministic）で、
+// 鍵生成・カプセル化が消費する乱数をシードに置き換えるために使う
+// dilithium-wasmの決定的署名（sign_deterministic）では、署名が消費する乱数rndをすべて0の32バイトに置き換える
+// 消費する長さとシードの長さは呼び出し側で一致させること
+// 不足した場合はpanicせず、残りを0で埋めて不足を記録する（with_seedがエラーとして返す）
+pub struct SeedRng<'a> {
+    seed: &'a [u8],
+    exhausted: bool,
+}
+
+// シードが不足した場合のエラーメッセージ
+const SEED_EXHAUSTED: &str = "Seed exhausted during deterministic generation";
+
+impl<'a> SeedRng<'a> {
+    // シードを乱数として処理fを実行する
+    // fがシードより多くの乱数を消費した場合は、fの結果を捨ててエラーを返す
+    pub fn with_seed<T>(seed: &'a [u8], f: impl FnOnce(&mut SeedRng<'a>) -> T) -> Result<T, String> {
+        let mut rng = SeedRng {
+            seed,
+            exhausted: false,
+        };
+        let result = f(&mut rng);
+        if rng.exhausted {
+            return Err(SEED_EXHAUSTED.to_string());
+        }
+        Ok(result)
+    }
+}
+
+impl RngCore for SeedRng<'_> {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        // 不足した場合はエラーを記録し、残りのシードの後を0で埋める
+        let _ = self.try_fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        match self.seed.split_at_checked(dest.len()) {
+            Some((head, rest)) => {
+                dest.copy_from_slice(head);
+                self.seed = rest;
+                Ok(())
+            }
+            None => {
+                let (head, tail) = dest.split_at_mut(self.seed.len());
+                head.copy_from_slice(self.seed);
+                tail.fill(0);
+                self.seed = &[];
+                self.exhausted = true;
+                Err(Error::new(SEED_EXHAUSTED))
+            }
+        }
+    }
+}
+
+impl CryptoRng for SeedRng<'_> {}
@@ -0,0 +1,49 @@
+// テスト専用の乱数生成モード（test-rng featureを有効にしたビルドでのみコンパイルされる）
+// KeyRngのエントロピー源を差し替え、壊れた乱数生成器をentropy_sanity_checkで
+// 検出できることを確認できるようにする
+//
+// kyber-wasmとdilithium-wasmの両方が#[path]でこのファイルを取り込む（共有クレートは作らない）
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+
+/// 乱数生成モード
+#[derive(Clone)]
+pub enum TestRngMode {
+    /// OSの乱数（通常の動作）
+    Os,
+    /// すべて0のバイト列を返す（壊れたエントロピー源）
+    ZeroBytes,
+    /// シードから決定的なバイト列を生成する（SHA-256(seed || counter)）
+    Deterministic { seed: Vec<u8>, counter: u64 },
+}
+
+thread_local! {
+    static MODE: RefCell<TestRngMode> = const { RefCell::new(TestRngMode::Os) };
+}
+
+/// 乱数生成モードを設定
+/// Deterministicのカウンタは設定のたびに0から始まる
+pub fn set_mode(mode: TestRngMode) {
+    MODE.with(|current| *current.borrow_mut() = mode);
+}
+
+/// 現在のモードでバッファを埋める
+pub fn fill(buffer: &mut [u8]) {
+    MODE.with(|mode| match &mut *mode.borrow_mut() {
+        TestRngMode::Os => OsRng.fill_bytes(buffer),
+        TestRngMode::ZeroBytes => buffer.fill(0),
+        TestRngMode::Deterministic { seed, counter } => {
+            for chunk in buffer.chunks_mut(32) {
+                let block = Sha256::new()
+                    .chain_update(&*seed)
+                    .chain_update(counter.to_be_bytes())
+                    .finalize();
+                chunk.copy_from_slice(&block[..chunk.len()]);
+                *counter += 1;
+            }
+        }
+    })
+}